@@ -141,6 +141,43 @@ fn extract_type_from_vec_if_exists(ty: &Type) -> Option<&Type> {
     )
 }
 
+/// Returns the key and value types of a HashMap or BTreeMap.
+/// If the type is not a map, it returns None.
+fn extract_types_from_map_if_exists(ty: &Type) -> Option<(&Type, &Type)> {
+    if let syn::Type::Path(syn::TypePath { qself: None, path }) = ty {
+        let segments_str = &path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>()
+            .join(":");
+
+        let map_segment = [
+            "HashMap",
+            "std:collections:HashMap",
+            "BTreeMap",
+            "std:collections:BTreeMap",
+        ]
+        .iter()
+        .find(|s| segments_str == *s)
+        .and_then(|_| path.segments.last())?;
+
+        if let syn::PathArguments::AngleBracketed(syn::AngleBracketedGenericArguments {
+            args,
+            ..
+        }) = &map_segment.arguments
+        {
+            let mut types = args.iter().filter_map(|generic_arg| match generic_arg {
+                syn::GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            });
+
+            return types.next().zip(types.next());
+        }
+    }
+    None
+}
+
 fn extract_type_from_option_if_exists(ty: &Type) -> Option<&Type> {
     extract_type_if_exists(ty, &["Option", "std:option:Option", "core:option:Option"])
 }
@@ -304,8 +341,28 @@ fn derive_class_from_struct(struct_name: &Ident, fields: Vec<&Field>) -> TokenSt
                 };
             };
 
+            // see if its a map field
+            if let Some((key_type, value_type)) = extract_types_from_map_if_exists(ty) {
+                if !is_simple_type(key_type) || !is_simple_type(value_type) {
+                    panic!(
+                        "[{}] Only simple types are supported inside a map",
+                        field_name
+                    );
+                }
+
+                return DartField {
+                    keywords: vec![String::from("final")],
+                    name: field_name.to_case(Case::Camel),
+                    type_: DartType::Map(
+                        rust_primitive_to_dart_primitive(&key_type.to_token_stream().to_string()),
+                        rust_primitive_to_dart_primitive(&value_type.to_token_stream().to_string()),
+                    ),
+                    optional,
+                };
+            }
+
             panic!(
-                "[{}] Only simple types and Vec fields are supported",
+                "[{}] Only simple types, Vec and map fields are supported",
                 field_name
             );
         })
@@ -2,6 +2,7 @@
 #[allow(dead_code)]
 mod tests {
     use convertible::{definitions::dart::DartFactory, macros::DartConvertible};
+    use std::collections::HashMap;
 
     #[derive(DartConvertible)]
     pub struct Project {
@@ -9,6 +10,7 @@ mod tests {
         pub installed: bool,
         pub scripts: Vec<Script>,
         pub optional_id: Option<Vec<String>>,
+        pub labels: HashMap<String, u32>,
     }
 
     #[derive(DartConvertible)]
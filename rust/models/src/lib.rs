@@ -1 +1,3 @@
 mod models_2;
+pub mod project_metadata;
+pub mod run_config;
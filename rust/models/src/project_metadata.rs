@@ -0,0 +1,51 @@
+use convertible::macros::DartConvertible;
+use serde::{Deserialize, Serialize};
+
+/// The metadata of a saved project, sent to the client, e.g. in a project listing.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, DartConvertible)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectMetadata {
+    pub id: String,
+    pub name: String,
+    /// Free-form, e.g. what the project tests.
+    pub description: Option<String>,
+    /// Free-form, e.g. the team or the person responsible for the project.
+    pub owner: Option<String>,
+    /// Labels to filter the projects by, sorted.
+    pub tags: Vec<String>,
+    pub tenant_id: String,
+    /// Milliseconds since the unix epoch.
+    pub created_at: u64,
+    /// Milliseconds since the unix epoch.
+    pub updated_at: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use convertible::definitions::dart::DartConvertible as _;
+
+    #[test]
+    fn convert_project_metadata_and_expect_camel_case_fields() {
+        let project_metadata = ProjectMetadata {
+            id: String::from("project"),
+            name: String::from("Project"),
+            description: None,
+            owner: Some(String::from("checkout-team")),
+            tags: vec![String::from("smoke")],
+            tenant_id: String::from("default"),
+            created_at: 1_700_000_000_000,
+            updated_at: 1_700_000_000_100,
+        };
+
+        let json = serde_json::to_value(&project_metadata).expect("Error serializing metadata.");
+        let dart_code = ProjectMetadata::to_dart();
+
+        assert_eq!(json["owner"], "checkout-team");
+        assert_eq!(json["description"], serde_json::Value::Null);
+        assert_eq!(json["createdAt"], 1_700_000_000_000_u64);
+        assert!(dart_code.contains("final String? description;"));
+        assert!(dart_code.contains("final List<String> tags;"));
+        assert!(dart_code.contains("final int updatedAt;"));
+    }
+}
@@ -0,0 +1,268 @@
+use convertible::macros::DartConvertible;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error as ThisError;
+use url::Url;
+
+/// A locust test run of a project, sent by the client.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, DartConvertible)]
+#[serde(rename_all = "camelCase")]
+pub struct RunConfig {
+    /// The locustfile, relative to the ```locust``` dir of the project, e.g. ```main.py```.
+    pub script_id: String,
+    pub users: u32,
+    /// Users started per second.
+    pub spawn_rate: f64,
+    /// In seconds.
+    pub duration: u64,
+    /// The locust workers of a distributed run, the users are spread over them.
+    /// ```0``` runs locust standalone, in a single process.
+    #[serde(default)]
+    pub workers: u32,
+    /// The base URL of the tested system, e.g. ```https://staging.example.com```.
+    /// ```None``` uses the host of the locustfile.
+    pub host: Option<String>,
+    /// Only the tasks with one of these tags run. All tasks run if empty.
+    pub tags: Vec<String>,
+    /// Set in the environment of locust, e.g. the credentials of the tested system.
+    pub env_overrides: HashMap<String, String>,
+}
+
+/// What a ```RunConfig``` may ask for, see ```RunConfig::validate```.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunConfigLimits {
+    pub max_users: u32,
+    pub max_spawn_rate: f64,
+    /// In seconds.
+    pub min_duration: u64,
+    /// In seconds.
+    pub max_duration: u64,
+    /// Every worker is a process, see ```RunConfig::workers```.
+    pub max_workers: u32,
+    /// The hosts that may be tested, e.g. ```staging.example.com``` or ```localhost:8080```.
+    /// A host without a port, or with port 80, allows every port.
+    pub allowed_hosts: Vec<String>,
+}
+
+impl Default for RunConfigLimits {
+    fn default() -> Self {
+        Self {
+            max_users: 10_000,
+            max_spawn_rate: 1_000.0,
+            min_duration: 1,
+            max_duration: 24 * 60 * 60,
+            max_workers: 16,
+            allowed_hosts: Vec::new(),
+        }
+    }
+}
+
+#[derive(ThisError, Debug, PartialEq)]
+pub enum RunConfigError {
+    #[error("Script id must be a relative path inside the locust dir: {0}")]
+    InvalidScriptId(String),
+    #[error("Users must be between 1 and {max}, got: {users}")]
+    InvalidUsers { users: u32, max: u32 },
+    #[error("Spawn rate must be greater than 0 and at most {max}, got: {spawn_rate}")]
+    InvalidSpawnRate { spawn_rate: f64, max: f64 },
+    #[error("Duration must be between {min} and {max} seconds, got: {duration}")]
+    InvalidDuration { duration: u64, min: u64, max: u64 },
+    #[error("Workers must be at most {max}, got: {workers}")]
+    InvalidWorkers { workers: u32, max: u32 },
+    #[error("Host must be an http or https URL: {0}")]
+    InvalidHost(String),
+    #[error("Host is not allowed: {0}")]
+    HostNotAllowed(String),
+    #[error("Tag must not be empty or contain whitespace: {0:?}")]
+    InvalidTag(String),
+    #[error("Environment variable name must not be empty or contain '=': {0:?}")]
+    InvalidEnvOverride(String),
+}
+
+impl RunConfig {
+    /// Correctness: A run without ```host``` is not checked against ```RunConfigLimits::allowed_hosts```,
+    /// the locustfile decides which system is tested.
+    pub fn validate(&self, limits: &RunConfigLimits) -> Result<(), RunConfigError> {
+        let is_relative_path = self
+            .script_id
+            .split(['/', '\\'])
+            .all(|segment| !segment.is_empty() && segment != "." && segment != "..");
+        if !is_relative_path {
+            return Err(RunConfigError::InvalidScriptId(self.script_id.clone()));
+        }
+
+        if self.users == 0 || self.users > limits.max_users {
+            return Err(RunConfigError::InvalidUsers {
+                users: self.users,
+                max: limits.max_users,
+            });
+        }
+
+        // NaN is not greater than 0.
+        if !(self.spawn_rate > 0.0 && self.spawn_rate <= limits.max_spawn_rate) {
+            return Err(RunConfigError::InvalidSpawnRate {
+                spawn_rate: self.spawn_rate,
+                max: limits.max_spawn_rate,
+            });
+        }
+
+        if self.duration < limits.min_duration || self.duration > limits.max_duration {
+            return Err(RunConfigError::InvalidDuration {
+                duration: self.duration,
+                min: limits.min_duration,
+                max: limits.max_duration,
+            });
+        }
+
+        if self.workers > limits.max_workers {
+            return Err(RunConfigError::InvalidWorkers {
+                workers: self.workers,
+                max: limits.max_workers,
+            });
+        }
+
+        if let Some(host) = &self.host {
+            Self::validate_host(host, &limits.allowed_hosts)?;
+        }
+
+        if let Some(tag) = self
+            .tags
+            .iter()
+            .find(|tag| tag.is_empty() || tag.contains(char::is_whitespace))
+        {
+            return Err(RunConfigError::InvalidTag(tag.clone()));
+        }
+
+        if let Some(name) = self
+            .env_overrides
+            .keys()
+            .find(|name| name.is_empty() || name.contains(['=', '\0']))
+        {
+            return Err(RunConfigError::InvalidEnvOverride(name.clone()));
+        }
+
+        Ok(())
+    }
+
+    fn validate_host(host: &str, allowed_hosts: &[String]) -> Result<(), RunConfigError> {
+        let url = Url::parse(host).map_err(|_| RunConfigError::InvalidHost(host.to_owned()))?;
+        let (Some(host_name), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+            return Err(RunConfigError::InvalidHost(host.to_owned()));
+        };
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(RunConfigError::InvalidHost(host.to_owned()));
+        }
+
+        let is_allowed = allowed_hosts.iter().any(|allowed_host| {
+            match Url::parse(&format!("http://{allowed_host}")) {
+                Ok(allowed_url) => {
+                    allowed_url.host_str() == Some(host_name)
+                        && match allowed_url.port() {
+                            Some(allowed_port) => allowed_port == port,
+                            None => true,
+                        }
+                }
+                Err(_) => false,
+            }
+        });
+
+        if !is_allowed {
+            return Err(RunConfigError::HostNotAllowed(host.to_owned()));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_config() -> RunConfig {
+        RunConfig {
+            script_id: String::from("main.py"),
+            users: 10,
+            spawn_rate: 2.5,
+            duration: 60,
+            workers: 2,
+            host: Some(String::from("https://Staging.example.com/api")),
+            tags: vec![String::from("checkout")],
+            env_overrides: HashMap::from([(String::from("API_KEY"), String::from("key"))]),
+        }
+    }
+
+    #[test]
+    fn validate_run_configs_and_expect_errors() {
+        let limits = RunConfigLimits {
+            allowed_hosts: vec![
+                String::from("staging.example.com"),
+                String::from("localhost:8080"),
+            ],
+            ..RunConfigLimits::default()
+        };
+
+        let with = |change: fn(&mut RunConfig)| {
+            let mut run_config = run_config();
+            change(&mut run_config);
+            run_config.validate(&limits)
+        };
+
+        assert_eq!(run_config().validate(&limits), Ok(()));
+        assert_eq!(
+            with(|run_config| run_config.host = Some(String::from("http://localhost:8080"))),
+            Ok(())
+        );
+        assert_eq!(with(|run_config| run_config.host = None), Ok(()));
+        assert_eq!(
+            with(|run_config| run_config.script_id = String::from("../main.py")),
+            Err(RunConfigError::InvalidScriptId(String::from("../main.py")))
+        );
+        assert_eq!(
+            with(|run_config| run_config.users = 0),
+            Err(RunConfigError::InvalidUsers {
+                users: 0,
+                max: 10_000
+            })
+        );
+        assert!(matches!(
+            with(|run_config| run_config.spawn_rate = f64::NAN),
+            Err(RunConfigError::InvalidSpawnRate { .. })
+        ));
+        assert_eq!(
+            with(|run_config| run_config.duration = 0),
+            Err(RunConfigError::InvalidDuration {
+                duration: 0,
+                min: 1,
+                max: 24 * 60 * 60
+            })
+        );
+        assert_eq!(
+            with(|run_config| run_config.workers = 17),
+            Err(RunConfigError::InvalidWorkers {
+                workers: 17,
+                max: 16
+            })
+        );
+        assert_eq!(
+            with(|run_config| run_config.host = Some(String::from("http://localhost:9090"))),
+            Err(RunConfigError::HostNotAllowed(String::from(
+                "http://localhost:9090"
+            )))
+        );
+        assert_eq!(
+            with(|run_config| run_config.host = Some(String::from("ftp://staging.example.com"))),
+            Err(RunConfigError::InvalidHost(String::from(
+                "ftp://staging.example.com"
+            )))
+        );
+        assert_eq!(
+            with(|run_config| run_config.tags = vec![String::from("two words")]),
+            Err(RunConfigError::InvalidTag(String::from("two words")))
+        );
+        assert_eq!(
+            with(|run_config| run_config.env_overrides =
+                HashMap::from([(String::from("A=B"), String::new())])),
+            Err(RunConfigError::InvalidEnvOverride(String::from("A=B")))
+        );
+    }
+}
@@ -0,0 +1,36 @@
+//! A synthetic child that writes a lot of output, shared by the benches. Unix only.
+
+use ptaas_rs::project_managers::process::ProcessBuilder;
+
+/// The line every synthetic child writes, roughly as long as a line of a locust log.
+pub const LINE: &str = "Locust is swarming the target with requests";
+
+/// Which streams a synthetic child writes to.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub enum Streams {
+    Stdout,
+    /// Every line is written to both streams.
+    StdoutAndStderr,
+    /// Every line is colored and written to both streams, like the output of ```pip install```.
+    ColoredStdoutAndStderr,
+}
+
+/// Sets the program and args of ```builder``` to a shell that writes ```lines``` lines as fast as possible.
+pub fn high_output_child(
+    builder: ProcessBuilder,
+    lines: usize,
+    streams: Streams,
+) -> ProcessBuilder {
+    let script = match streams {
+        Streams::Stdout => format!("yes '{LINE}' | head -n {lines}"),
+        Streams::StdoutAndStderr => {
+            format!("yes '{LINE}' | head -n {lines} | tee /dev/stderr")
+        }
+        Streams::ColoredStdoutAndStderr => {
+            format!("yes '\x1b[32m{LINE}\x1b[0m' | head -n {lines} | tee /dev/stderr")
+        }
+    };
+
+    builder.program("sh").args(["-c", &script])
+}
@@ -0,0 +1,88 @@
+//! Forwards the output of a high-throughput child to a file sink and a channel,
+//! and reports the allocations per forwarded line.
+//! Run with ```cargo bench --bench output_forwarding```. Unix only.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use common::Streams;
+use ptaas_rs::project_managers::process::{ProcessBuilder, Status, TerminationStatus};
+use tokio::sync::mpsc;
+
+mod common;
+
+const LINES: usize = 200_000;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[tokio::main]
+async fn main() {
+    let output_file = std::env::temp_dir().join(format!(
+        "ptaas_output_forwarding_bench_{}",
+        std::process::id()
+    ));
+    let (stdout_sender, mut stdout_receiver) = mpsc::channel(1024);
+
+    let receiver_handle = tokio::spawn(async move {
+        let mut received_lines = 0;
+        while stdout_receiver.recv().await.is_some() {
+            received_lines += 1;
+        }
+        received_lines
+    });
+
+    let builder = ProcessBuilder::new("bench_id", "bench_process");
+    let (mut process, _controller) = common::high_output_child(builder, LINES, Streams::Stdout)
+        .stdout_lines(stdout_sender)
+        .stdout_file(&output_file)
+        .build()
+        .expect("Error building process.");
+
+    let allocations_before = ALLOCATIONS.load(Ordering::Relaxed);
+    let started_at = Instant::now();
+
+    let result = process.run_built().await;
+    drop(process);
+    let received_lines = receiver_handle.await.expect("Error joining receiver.");
+
+    let elapsed = started_at.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations_before;
+    let _ = std::fs::remove_file(&output_file);
+
+    assert!(matches!(
+        result,
+        Ok(Status::Terminated(
+            TerminationStatus::TerminatedSuccessfully
+        ))
+    ));
+    assert_eq!(received_lines, LINES);
+
+    println!(
+        "{LINES} lines in {elapsed:?}, {allocations} allocations, {:.2} allocations per line",
+        allocations as f64 / LINES as f64
+    );
+}
@@ -0,0 +1,201 @@
+//! Baselines of the output pipeline of a process:
+//! - ```forward_io```: lines per second from a synthetic child into a channel.
+//! - ```status_holder_contention```: concurrent status reads while the output is forwarded.
+//! - ```install_log_capture```: both streams of a colored child into log files and channels,
+//!   configured like the processes of ```LocalProjectInstaller```.
+//!
+//! Run with ```cargo bench --bench process_output```. Unix only.
+
+use std::{path::PathBuf, sync::Arc};
+
+use common::Streams;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ptaas_rs::project_managers::process::{ProcessBuilder, Status, StripAnsi, TerminationStatus};
+use tokio::{runtime::Runtime, sync::mpsc};
+
+mod common;
+
+const LINES: usize = 100_000;
+const STATUS_READS_PER_READER: usize = 1_000;
+
+/// Receives all lines of ```receiver``` and returns how many there were.
+fn drain(mut receiver: mpsc::Receiver<String>) -> tokio::task::JoinHandle<usize> {
+    tokio::spawn(async move {
+        let mut received_lines = 0;
+        while receiver.recv().await.is_some() {
+            received_lines += 1;
+        }
+        received_lines
+    })
+}
+
+fn assert_terminated_successfully(status: Status) {
+    assert!(matches!(
+        status,
+        Status::Terminated(TerminationStatus::TerminatedSuccessfully)
+    ));
+}
+
+fn forward_io(criterion: &mut Criterion) {
+    let runtime = Runtime::new().expect("Error creating runtime.");
+    let mut group = criterion.benchmark_group("forward_io");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(LINES as u64));
+
+    for channel_capacity in [1, 100, 1024] {
+        group.bench_with_input(
+            BenchmarkId::new("channel_capacity", channel_capacity),
+            &channel_capacity,
+            |bencher, &channel_capacity| {
+                bencher.to_async(&runtime).iter(|| async move {
+                    let (stdout_sender, stdout_receiver) = mpsc::channel(channel_capacity);
+                    let receiver_handle = drain(stdout_receiver);
+
+                    let builder = ProcessBuilder::new("bench_id", "bench_process");
+                    let (mut process, _controller) =
+                        common::high_output_child(builder, LINES, Streams::Stdout)
+                            .stdout_lines(stdout_sender)
+                            .build()
+                            .expect("Error building process.");
+
+                    let status = process.run_built().await.expect("Error running process.");
+                    drop(process);
+
+                    assert_terminated_successfully(status);
+                    assert_eq!(
+                        receiver_handle.await.expect("Error joining receiver."),
+                        LINES
+                    );
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn status_holder_contention(criterion: &mut Criterion) {
+    let runtime = Runtime::new().expect("Error creating runtime.");
+    let mut group = criterion.benchmark_group("status_holder_contention");
+    group.sample_size(10);
+
+    for readers in [1, 4, 16] {
+        group.throughput(Throughput::Elements(
+            (readers * STATUS_READS_PER_READER) as u64,
+        ));
+        group.bench_with_input(
+            BenchmarkId::new("readers", readers),
+            &readers,
+            |bencher, &readers| {
+                bencher.to_async(&runtime).iter(|| async move {
+                    let (stdout_sender, stdout_receiver) = mpsc::channel(1024);
+                    let receiver_handle = drain(stdout_receiver);
+
+                    let builder = ProcessBuilder::new("bench_id", "bench_process");
+                    let (mut process, controller) =
+                        common::high_output_child(builder, LINES, Streams::Stdout)
+                            .stdout_lines(stdout_sender)
+                            .build()
+                            .expect("Error building process.");
+
+                    let process_handle = tokio::spawn(async move { process.run_built().await });
+
+                    let controller = Arc::new(controller);
+                    let reader_handles: Vec<_> = (0..readers)
+                        .map(|_| {
+                            let controller = controller.clone();
+                            tokio::spawn(async move {
+                                for _ in 0..STATUS_READS_PER_READER {
+                                    let _ = controller.status().await;
+                                }
+                            })
+                        })
+                        .collect();
+
+                    for reader_handle in reader_handles {
+                        reader_handle.await.expect("Error joining reader.");
+                    }
+
+                    let status = process_handle
+                        .await
+                        .expect("Error joining process.")
+                        .expect("Error running process.");
+
+                    assert_terminated_successfully(status);
+                    assert_eq!(
+                        receiver_handle.await.expect("Error joining receiver."),
+                        LINES
+                    );
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn install_log_capture(criterion: &mut Criterion) {
+    let runtime = Runtime::new().expect("Error creating runtime.");
+    let log_dir: PathBuf = std::env::temp_dir().join(format!(
+        "ptaas_install_log_capture_bench_{}",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&log_dir).expect("Error creating log dir.");
+
+    let mut group = criterion.benchmark_group("install_log_capture");
+    group.sample_size(10);
+    // Every line is written to both streams.
+    group.throughput(Throughput::Elements(2 * LINES as u64));
+
+    group.bench_function("colored_stdout_and_stderr", |bencher| {
+        bencher.to_async(&runtime).iter(|| {
+            let log_dir = log_dir.clone();
+
+            async move {
+                let (stdout_sender, stdout_receiver) = mpsc::channel(100);
+                let (stderr_sender, stderr_receiver) = mpsc::channel(100);
+                let stdout_receiver_handle = drain(stdout_receiver);
+                let stderr_receiver_handle = drain(stderr_receiver);
+
+                let builder = ProcessBuilder::new("req_id", "install_req_process");
+                let (mut process, _controller) =
+                    common::high_output_child(builder, LINES, Streams::ColoredStdoutAndStderr)
+                        .stdout_lines(stdout_sender)
+                        .stderr_lines(stderr_sender)
+                        .stdout_file(log_dir.join("req_out.txt"))
+                        .stderr_file(log_dir.join("req_err.txt"))
+                        .strip_ansi(StripAnsi::both())
+                        .build()
+                        .expect("Error building process.");
+
+                let status = process.run_built().await.expect("Error running process.");
+                drop(process);
+
+                assert_terminated_successfully(status);
+                assert_eq!(
+                    stdout_receiver_handle
+                        .await
+                        .expect("Error joining receiver."),
+                    LINES
+                );
+                assert_eq!(
+                    stderr_receiver_handle
+                        .await
+                        .expect("Error joining receiver."),
+                    LINES
+                );
+            }
+        });
+    });
+
+    group.finish();
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
+
+criterion_group!(
+    benches,
+    forward_io,
+    status_holder_contention,
+    install_log_capture
+);
+criterion_main!(benches);
@@ -36,4 +36,6 @@ async fn main() {
             std::process::exit(1);
         }
     };
+
+    manager.shutdown().await;
 }
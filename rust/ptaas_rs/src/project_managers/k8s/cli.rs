@@ -0,0 +1,54 @@
+use crate::project_managers::process::{self, ProcessRunError, Status, TerminationStatus};
+use std::{ffi::OsStr, path::Path, time::Duration};
+use thiserror::Error as ThisError;
+
+/// Only the end of the output is kept, e.g. the failing step of a build.
+const MAX_OUTPUT_SIZE: usize = 4 * 1024 * 1024;
+
+/// Building an image installs the requirements of the project, that takes minutes.
+const TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+#[derive(ThisError, Debug)]
+pub enum CliError {
+    #[error("Could not run {program}: {error}")]
+    CouldNotRun {
+        program: String,
+        #[source]
+        error: ProcessRunError,
+    },
+    #[error("{program} failed: {stderr}")]
+    Failed { program: String, stderr: String },
+}
+
+/// Runs ```program``` to completion, e.g. ```docker build```, and returns its stdout.
+/// Fails with ```CliError::Failed``` and its stderr if it does not terminate successfully, e.g. if it timed out.
+pub(super) async fn run(
+    program: &Path,
+    args: Vec<&OsStr>,
+    current_dir: &Path,
+) -> Result<String, CliError> {
+    let program_name = program.to_string_lossy().into_owned();
+
+    let output = process::capture(
+        program.as_os_str(),
+        args,
+        current_dir,
+        Vec::new(),
+        MAX_OUTPUT_SIZE,
+        TIMEOUT,
+        None,
+    )
+    .await
+    .map_err(|error| CliError::CouldNotRun {
+        program: program_name.clone(),
+        error,
+    })?;
+
+    match output.status {
+        Status::Terminated(TerminationStatus::TerminatedSuccessfully) => Ok(output.stdout),
+        _ => Err(CliError::Failed {
+            program: program_name,
+            stderr: output.stderr,
+        }),
+    }
+}
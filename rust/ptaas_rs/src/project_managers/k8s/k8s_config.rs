@@ -0,0 +1,27 @@
+use std::path::PathBuf;
+
+/// The registry and the namespace of a ```K8sProjectManager```.
+/// The cluster is reached with the ```kube::Client``` passed to ```K8sProjectManager::new```, e.g. from the kubeconfig.
+/// The image builder uses its own configuration, e.g. the credentials of ```docker login```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct K8sConfig {
+    /// Builds and pushes the images of the projects, e.g. ```docker``` or ```podman```.
+    pub image_builder_program: PathBuf,
+    /// Where the images of the projects are pushed to. The cluster must be able to pull from it.
+    pub registry: String,
+    /// Must contain locust. The requirements of a project are installed on top of it.
+    pub base_image: String,
+    /// Holds the pods of the runs, it must exist.
+    pub namespace: String,
+}
+
+impl Default for K8sConfig {
+    fn default() -> Self {
+        Self {
+            image_builder_program: PathBuf::from("docker"),
+            registry: String::from("localhost:5000"),
+            base_image: String::from("locustio/locust:2.15.1"),
+            namespace: String::from("ptaas"),
+        }
+    }
+}
@@ -0,0 +1,932 @@
+use super::{
+    cli::{self, CliError},
+    k8s_config::K8sConfig,
+    manifests::{self, RunResources, PROJECT_LABEL},
+    pod_status::{self, RunStatus},
+};
+use crate::project_managers::{
+    local::{
+        project_listing, project_state::ProjectStates, AddProjectError, DeleteProjectError,
+        InstallFailure, InstallProjectError, InstallTicket, InstallerEvent, LocalProjectInstaller,
+        Page, Pagination, ProjectCheckError, ProjectEvent, ProjectEventKind, ProjectFilter,
+        ProjectKind, ProjectState, ProjectStateChange, ProjectStatus, ProjectStore,
+        ProjectStoreError, ProjectSummary, ProjectTransitionError, StoredProject, TenantId,
+        UninstallProjectError,
+    },
+    process::ProcessIoConfig,
+    ProjectManager,
+};
+use async_trait::async_trait;
+use futures::StreamExt;
+use k8s_openapi::{
+    api::{
+        apps::v1::Deployment,
+        core::v1::{Pod, Service},
+    },
+    NamespaceResourceScope,
+};
+use kube::{
+    api::{DeleteParams, ListParams, Patch, PatchParams},
+    runtime::{
+        reflector,
+        wait::delete::{delete_and_finalize, Error as DeleteError},
+        watcher, WatchStreamExt,
+    },
+    Api, Client, Error as KubeError, Resource,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::{BTreeSet, HashMap},
+    ffi::OsStr,
+    fmt::Debug,
+    io::{Error as IoError, ErrorKind},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use thiserror::Error as ThisError;
+use tokio::{
+    fs,
+    sync::{broadcast, mpsc, RwLock},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{info_span, Instrument};
+
+/// The field manager of the applied resources, see ```K8sProjectManager::apply_run```.
+const FIELD_MANAGER: &str = "ptaas";
+
+/// Deleting waits for the finalizers of the resources, e.g. until the pods terminated.
+const DELETE_TIMEOUT: Duration = Duration::from_secs(2 * 60);
+
+/// Sent as ```InstallerEvent::Failed```, the installation of a ```K8sProjectManager``` has no phases.
+#[derive(ThisError, Debug)]
+enum InstallImageError {
+    #[error("Project is not valid: {0}")]
+    InvalidProject(#[source] ProjectCheckError),
+    #[error("Only requirements projects can be installed into an image, got: {0:?}")]
+    UnsupportedProjectKind(ProjectKind),
+    #[error("Could not write the dockerfile: {0}")]
+    CouldNotWriteDockerfile(#[source] IoError),
+    #[error("Could not build the image: {0}")]
+    CouldNotBuildImage(#[source] CliError),
+    #[error("Could not push the image: {0}")]
+    CouldNotPushImage(#[source] CliError),
+}
+
+#[derive(ThisError, Debug)]
+pub enum K8sRunError {
+    #[error("Project can not be run or stopped: {0}")]
+    Transition(
+        #[source]
+        #[from]
+        ProjectTransitionError,
+    ),
+    #[error("Could not apply the resources of the run: {0}")]
+    CouldNotApplyResources(#[source] KubeError),
+    #[error("Could not delete the resources of the run: {0}")]
+    CouldNotDeleteResources(#[source] DeleteResourcesError),
+}
+
+#[derive(ThisError, Debug)]
+pub enum DeleteResourcesError {
+    #[error("Could not list the resources: {0}")]
+    CouldNotList(#[source] KubeError),
+    #[error("Could not delete {name}: {error}")]
+    CouldNotDelete {
+        name: String,
+        #[source]
+        error: DeleteError,
+    },
+    #[error("Resources were not deleted after {0:?}")]
+    TimedOut(Duration),
+}
+
+/// A run and the task that watches its pods.
+struct Run {
+    status: RunStatus,
+    cancellation_token: CancellationToken,
+}
+
+/// Installs the projects into images and runs them as a locust master with workers in a kubernetes cluster,
+/// through the kubernetes api. The images are built and pushed with ```K8sConfig::image_builder_program```.
+/// The projects go through the same ```ProjectState```s as the ones of a ```LocalProjectManager```.
+/// Correctness: Only one run per project. The pods of a run, that was active during a restart, are not found again,
+/// they are deleted with the next run of the project.
+#[derive(Clone)]
+pub struct K8sProjectManager {
+    /// Holds a dockerfile per project.
+    work_dir: PathBuf,
+    client: Client,
+    k8s_config: K8sConfig,
+    project_store: Arc<dyn ProjectStore>,
+    project_states: ProjectStates,
+    runs: Arc<RwLock<HashMap</* id */ String, Run>>>,
+}
+
+impl K8sProjectManager {
+    /// ```work_dir``` is created. The resources of the runs are created in ```K8sConfig::namespace``` with ```client```,
+    /// e.g. from ```Client::try_default```.
+    pub async fn new(
+        work_dir: PathBuf,
+        client: Client,
+        project_store: Arc<dyn ProjectStore>,
+        k8s_config: K8sConfig,
+    ) -> Result<Self, IoError> {
+        fs::create_dir_all(&work_dir).await?;
+
+        Ok(Self {
+            work_dir,
+            client,
+            k8s_config,
+            project_store,
+            project_states: ProjectStates::default(),
+            runs: Arc::default(),
+        })
+    }
+
+    pub fn k8s_config(&self) -> &K8sConfig {
+        &self.k8s_config
+    }
+
+    fn get_dockerfile_path(&self, project_id: &str) -> PathBuf {
+        self.work_dir.join(format!("{project_id}.Dockerfile"))
+    }
+
+    fn api<K>(&self) -> Api<K>
+    where
+        K: Resource<Scope = NamespaceResourceScope>,
+        K::DynamicType: Default,
+    {
+        Api::namespaced(self.client.clone(), &self.k8s_config.namespace)
+    }
+
+    /// Builds the image of the project on top of ```K8sConfig::base_image``` and pushes it.
+    async fn install_image(&self, project: &StoredProject) -> Result<(), InstallImageError> {
+        // Only checks the project, its dirs are never written.
+        let (installer, _controller) = LocalProjectInstaller::new(
+            project.id.clone(),
+            project.source_dir.clone(),
+            self.work_dir.join(&project.id),
+            self.work_dir.join(&project.id),
+            None,
+            ProcessIoConfig::default(),
+        );
+        let project_kind = installer
+            .check()
+            .await
+            .map_err(InstallImageError::InvalidProject)?;
+        if project_kind != ProjectKind::Requirements {
+            return Err(InstallImageError::UnsupportedProjectKind(project_kind));
+        }
+
+        self.transition(&project.id, ProjectState::Installing).await;
+
+        let dockerfile_path = self.get_dockerfile_path(&project.id);
+        fs::write(&dockerfile_path, manifests::dockerfile(&self.k8s_config))
+            .await
+            .map_err(InstallImageError::CouldNotWriteDockerfile)?;
+
+        let image = manifests::image(&self.k8s_config, &project.id);
+        cli::run(
+            &self.k8s_config.image_builder_program,
+            vec![
+                OsStr::new("build"),
+                OsStr::new("--file"),
+                dockerfile_path.as_os_str(),
+                OsStr::new("--tag"),
+                OsStr::new(&image),
+                project.source_dir.as_os_str(),
+            ],
+            &self.work_dir,
+        )
+        .await
+        .map_err(InstallImageError::CouldNotBuildImage)?;
+
+        cli::run(
+            &self.k8s_config.image_builder_program,
+            vec![OsStr::new("push"), OsStr::new(&image)],
+            &self.work_dir,
+        )
+        .await
+        .map_err(InstallImageError::CouldNotPushImage)?;
+
+        Ok(())
+    }
+
+    /// A failed transition is logged, the project stays in its state.
+    async fn transition(&self, project_id: &str, next: ProjectState) {
+        if let Err(error) = self
+            .project_states
+            .transition(self.project_store.as_ref(), project_id, next, None)
+            .await
+        {
+            tracing::warn!(project_id, %error, "Could not transition project");
+        }
+    }
+
+    /// Deploys a locust master and ```workers``` workers, see ```manifests::run_resources```.
+    /// The project is ```ProjectState::Running``` until the master exits or the run is stopped, see ```stop_run```.
+    /// The pods are watched, see ```run_status```.
+    pub async fn start_run(
+        &self,
+        project_id: &str,
+        workers: usize,
+        locust_args: Vec<String>,
+    ) -> Result<(), K8sRunError> {
+        self.project_states
+            .transition(
+                self.project_store.as_ref(),
+                project_id,
+                ProjectState::Running,
+                None,
+            )
+            .await?;
+
+        if let Err(error) = self.apply_run(project_id, workers, &locust_args).await {
+            self.delete_resources(project_id).await.ok();
+            self.transition(project_id, ProjectState::Stopping).await;
+            self.transition(project_id, ProjectState::Installed).await;
+
+            return Err(error);
+        }
+
+        let cancellation_token = CancellationToken::new();
+        self.runs.write().await.insert(
+            project_id.to_owned(),
+            Run {
+                status: RunStatus {
+                    master: None,
+                    workers_running: 0,
+                    workers,
+                },
+                cancellation_token: cancellation_token.clone(),
+            },
+        );
+
+        let k8s_project_manager = self.clone();
+        let project_id = project_id.to_owned();
+        let span = info_span!("K8sProjectManager::monitor_run", project_id);
+        tokio::spawn(
+            async move {
+                k8s_project_manager
+                    .monitor_run(&project_id, workers, cancellation_token)
+                    .await;
+            }
+            .instrument(span),
+        );
+
+        Ok(())
+    }
+
+    async fn apply_run(
+        &self,
+        project_id: &str,
+        workers: usize,
+        locust_args: &[String],
+    ) -> Result<(), K8sRunError> {
+        // The resources of a previous run may be left, e.g. after a restart.
+        self.delete_resources(project_id)
+            .await
+            .map_err(K8sRunError::CouldNotDeleteResources)?;
+
+        let RunResources {
+            master_pod,
+            master_service,
+            worker_deployment,
+        } = manifests::run_resources(&self.k8s_config, project_id, workers, locust_args);
+
+        self.apply(master_service).await?;
+        self.apply(master_pod).await?;
+        self.apply(worker_deployment).await?;
+
+        Ok(())
+    }
+
+    /// Server side apply, the resource is created or replaced.
+    async fn apply<K>(&self, resource: K) -> Result<(), K8sRunError>
+    where
+        K: Resource<Scope = NamespaceResourceScope> + Clone + Debug + DeserializeOwned + Serialize,
+        K::DynamicType: Default,
+    {
+        let name = resource.meta().name.clone().unwrap_or_default();
+
+        self.api::<K>()
+            .patch(
+                &name,
+                &PatchParams::apply(FIELD_MANAGER).force(),
+                &Patch::Apply(&resource),
+            )
+            .await
+            .map_err(K8sRunError::CouldNotApplyResources)?;
+
+        Ok(())
+    }
+
+    /// Deletes the deployment, the service and the pods of the project, whether they exist or not,
+    /// and waits until they are gone.
+    async fn delete_resources(&self, project_id: &str) -> Result<(), DeleteResourcesError> {
+        let selector = format!("{PROJECT_LABEL}={}", manifests::resource_name(project_id));
+
+        tokio::time::timeout(DELETE_TIMEOUT, async {
+            // The deployment first, it would replace the deleted worker pods.
+            self.delete_labelled(self.api::<Deployment>(), &selector)
+                .await?;
+            self.delete_labelled(self.api::<Service>(), &selector)
+                .await?;
+            self.delete_labelled(self.api::<Pod>(), &selector).await
+        })
+        .await
+        .map_err(|_| DeleteResourcesError::TimedOut(DELETE_TIMEOUT))?
+    }
+
+    /// Correctness: A resource that is already gone is not an error.
+    async fn delete_labelled<K>(
+        &self,
+        api: Api<K>,
+        selector: &str,
+    ) -> Result<(), DeleteResourcesError>
+    where
+        K: Resource + Clone + Debug + DeserializeOwned + Send + 'static,
+    {
+        let resources = api
+            .list_metadata(&ListParams::default().labels(selector))
+            .await
+            .map_err(DeleteResourcesError::CouldNotList)?;
+
+        for name in resources
+            .items
+            .into_iter()
+            .filter_map(|resource| resource.metadata.name)
+        {
+            match delete_and_finalize(api.clone(), &name, &DeleteParams::foreground()).await {
+                Err(DeleteError::Delete(KubeError::Api(response))) if response.code == 404 => {}
+                Err(error) => return Err(DeleteResourcesError::CouldNotDelete { name, error }),
+                Ok(()) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Keeps the pods of the run in a reflector and updates the ```RunStatus``` on every change.
+    /// Correctness: A failed watch is logged and retried with a backoff, e.g. while the api server is not reachable.
+    async fn monitor_run(
+        &self,
+        project_id: &str,
+        workers: usize,
+        cancellation_token: CancellationToken,
+    ) {
+        let selector = format!("{PROJECT_LABEL}={}", manifests::resource_name(project_id));
+        let (reader, writer) = reflector::store();
+        let pod_events = reflector(
+            writer,
+            watcher(
+                self.api::<Pod>(),
+                watcher::Config::default().labels(&selector),
+            ),
+        )
+        .default_backoff()
+        .touched_objects();
+        futures::pin_mut!(pod_events);
+
+        loop {
+            let pod_event = tokio::select! {
+                _ = cancellation_token.cancelled() => return,
+                pod_event = pod_events.next() => pod_event,
+            };
+
+            match pod_event {
+                Some(Ok(_)) => {}
+                Some(Err(error)) => {
+                    tracing::warn!(%error, "Could not watch the pods of the run");
+                    continue;
+                }
+                None => {
+                    tracing::warn!("Watch of the pods of the run ended");
+                    return;
+                }
+            }
+
+            let run_status =
+                pod_status::run_status(reader.state().iter().map(Arc::as_ref), workers);
+
+            tracing::debug!(?run_status, "Pods of the run changed");
+
+            let finished = run_status.is_finished();
+            if let Some(run) = self.runs.write().await.get_mut(project_id) {
+                run.status = run_status;
+            }
+
+            if finished {
+                tracing::info!("Locust master exited, stopping the run");
+
+                if let Err(error) = self.stop_run(project_id).await {
+                    tracing::warn!(%error, "Could not stop the finished run, it is stopped with the next stop_run");
+                }
+
+                return;
+            }
+        }
+    }
+
+    /// Deletes the resources of the run, the project is ```ProjectState::Installed``` again.
+    /// Correctness: The project stays ```ProjectState::Stopping``` and the run is kept if the resources could not be deleted,
+    /// ```stop_run``` can be called again.
+    pub async fn stop_run(&self, project_id: &str) -> Result<(), K8sRunError> {
+        self.project_states
+            .transition(
+                self.project_store.as_ref(),
+                project_id,
+                ProjectState::Stopping,
+                None,
+            )
+            .await?;
+
+        if let Some(run) = self.runs.read().await.get(project_id) {
+            run.cancellation_token.cancel();
+        }
+
+        self.delete_resources(project_id)
+            .await
+            .map_err(K8sRunError::CouldNotDeleteResources)?;
+
+        self.runs.write().await.remove(project_id);
+
+        self.project_states
+            .transition(
+                self.project_store.as_ref(),
+                project_id,
+                ProjectState::Installed,
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// ```None``` if the project is not running.
+    pub async fn run_status(&self, project_id: &str) -> Option<RunStatus> {
+        self.runs
+            .read()
+            .await
+            .get(project_id)
+            .map(|run| run.status.clone())
+    }
+}
+
+#[async_trait]
+impl ProjectManager for K8sProjectManager {
+    type AddError = AddProjectError;
+    type InstallError = InstallProjectError;
+    type UninstallError = UninstallProjectError;
+    type DeleteError = DeleteProjectError;
+    type QueryError = ProjectStoreError;
+
+    async fn add_project(
+        &self,
+        project_id: String,
+        project_name: String,
+        project_dir: PathBuf,
+    ) -> Result<(), Self::AddError> {
+        let (installer, _controller) = LocalProjectInstaller::new(
+            project_id.clone(),
+            project_dir.clone(),
+            self.work_dir.join(&project_id),
+            self.work_dir.join(&project_id),
+            None,
+            ProcessIoConfig::default(),
+        );
+        installer.check().await?;
+
+        let now = SystemTime::now();
+        self.project_store
+            .insert(&StoredProject {
+                id: project_id.clone(),
+                name: project_name,
+                source_dir: project_dir,
+                status: ProjectStatus::Uploaded,
+                created_at: now,
+                updated_at: now,
+                requirements_hash: None,
+                tags: BTreeSet::new(),
+                tenant_id: TenantId::default(),
+                description: None,
+                owner: None,
+            })
+            .await?;
+
+        self.project_states
+            .set(project_id, ProjectState::Uploaded)
+            .await;
+
+        Ok(())
+    }
+
+    /// The installation starts right away, there is no queue. ```InstallerEvent::Failed``` is the only event.
+    async fn install_project(
+        &self,
+        project_id: String,
+        event_sender: Option<mpsc::Sender<InstallerEvent>>,
+    ) -> Result<InstallTicket, Self::InstallError> {
+        let project = self
+            .project_store
+            .get(&project_id)
+            .await
+            .map_err(InstallProjectError::CouldNotLoadProject)?
+            .ok_or_else(|| InstallProjectError::ProjectDoesNotExist(project_id.clone()))?;
+
+        self.project_states
+            .transition(
+                self.project_store.as_ref(),
+                &project_id,
+                ProjectState::QueuedForInstall,
+                None,
+            )
+            .await?;
+
+        let k8s_project_manager = self.clone();
+        let span = info_span!("K8sProjectManager::install_project", project_id);
+        let task = tokio::spawn(
+            async move {
+                k8s_project_manager
+                    .transition(&project.id, ProjectState::Checking)
+                    .await;
+
+                match k8s_project_manager.install_image(&project).await {
+                    Ok(()) => {
+                        tracing::info!("Installed project");
+
+                        k8s_project_manager
+                            .transition(&project.id, ProjectState::Installed)
+                            .await;
+                    }
+                    Err(error) => {
+                        tracing::warn!(%error, "Could not install project");
+
+                        k8s_project_manager
+                            .transition(
+                                &project.id,
+                                ProjectState::InstallFailed(InstallFailure::Failed),
+                            )
+                            .await;
+
+                        let installer_event = InstallerEvent::Failed {
+                            error: error.to_string(),
+                        };
+                        k8s_project_manager.project_states.events().send(
+                            &project.id,
+                            ProjectEventKind::InstallProgress(installer_event.clone()),
+                        );
+                        if let Some(event_sender) = event_sender {
+                            let _ = event_sender.send(installer_event).await;
+                        }
+                    }
+                }
+            }
+            .instrument(span),
+        );
+
+        Ok(InstallTicket {
+            task,
+            queue_position: None,
+        })
+    }
+
+    /// Removes the local image, the image in the registry is kept.
+    async fn uninstall_project(&self, project_id: String) -> Result<(), Self::UninstallError> {
+        self.project_states
+            .transition(
+                self.project_store.as_ref(),
+                &project_id,
+                ProjectState::Uninstalling,
+                None,
+            )
+            .await?;
+
+        let image = manifests::image(&self.k8s_config, &project_id);
+        if let Err(error) = cli::run(
+            &self.k8s_config.image_builder_program,
+            vec![OsStr::new("rmi"), OsStr::new("--force"), OsStr::new(&image)],
+            &self.work_dir,
+        )
+        .await
+        {
+            tracing::warn!(project_id, %error, "Could not remove the image");
+        }
+
+        self.project_states
+            .transition(
+                self.project_store.as_ref(),
+                &project_id,
+                ProjectState::Uploaded,
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_project(&self, project_id: String) -> Result<(), Self::DeleteError> {
+        let project = self
+            .project_store
+            .get(&project_id)
+            .await
+            .map_err(DeleteProjectError::CouldNotLoadProject)?
+            .ok_or_else(|| DeleteProjectError::ProjectDoesNotExist(project_id.clone()))?;
+
+        let state = self
+            .project_states
+            .get(self.project_store.as_ref(), &project_id)
+            .await
+            .map_err(DeleteProjectError::CouldNotLoadProject)?;
+        if state != Some(ProjectState::Uploaded) {
+            ProjectManager::uninstall_project(self, project_id.clone()).await?;
+        }
+
+        self.project_states
+            .transition(
+                self.project_store.as_ref(),
+                &project_id,
+                ProjectState::Deleted,
+                None,
+            )
+            .await?;
+
+        remove_file_if_exists(&self.get_dockerfile_path(&project_id))
+            .await
+            .map_err(DeleteProjectError::CouldNotDeleteUploadedProject)?;
+
+        match fs::remove_dir_all(&project.source_dir).await {
+            Err(error) if error.kind() != ErrorKind::NotFound => {
+                return Err(DeleteProjectError::CouldNotDeleteUploadedProject(error))
+            }
+            _ => {}
+        }
+
+        self.project_store
+            .remove(&project_id)
+            .await
+            .map_err(DeleteProjectError::CouldNotRemoveProject)?;
+
+        Ok(())
+    }
+
+    async fn list_projects(
+        &self,
+        filter: ProjectFilter,
+        page: Pagination,
+    ) -> Result<Page<ProjectSummary>, Self::QueryError> {
+        let projects = self
+            .project_states
+            .with_states(self.project_store.list().await?)
+            .await;
+
+        Ok(project_listing::list(projects, &filter, page))
+    }
+
+    async fn project_state(
+        &self,
+        project_id: &str,
+    ) -> Result<Option<ProjectState>, Self::QueryError> {
+        self.project_states
+            .get(self.project_store.as_ref(), project_id)
+            .await
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<ProjectStateChange> {
+        self.project_states.subscribe()
+    }
+
+    fn subscribe_events(&self) -> broadcast::Receiver<ProjectEvent> {
+        self.project_states.events().subscribe()
+    }
+}
+
+/// A missing file is not an error.
+async fn remove_file_if_exists(path: &Path) -> Result<(), IoError> {
+    match fs::remove_file(path).await {
+        Err(error) if error.kind() != ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        project_managers::local::InMemoryProjectStore,
+        util::{copy_dir_all, test_dir},
+    };
+    use tracing_test::traced_test;
+
+    const CRATE_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+    /// Nothing listens on the port, every request fails.
+    fn unreachable_client() -> Client {
+        Client::try_from(kube::Config::new(
+            "http://127.0.0.1:1".parse().expect("Error parsing url."),
+        ))
+        .expect("Error creating client.")
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn install_project_without_image_builder_and_expect_failed_event() {
+        let temp_dir = test_dir("k8s");
+        let test_dir = temp_dir.path();
+        let project_dir = test_dir.join("uploaded_projects").join("valid_offline");
+        copy_dir_all(
+            &Path::new(CRATE_DIR)
+                .join("tests_dir")
+                .join("uploaded_projects")
+                .join("valid_offline"),
+            &project_dir,
+            |_| false,
+        )
+        .await
+        .expect("Could not copy uploaded project");
+        let k8s_project_manager = K8sProjectManager::new(
+            test_dir.join("k8s"),
+            unreachable_client(),
+            Arc::new(InMemoryProjectStore::default()),
+            K8sConfig {
+                image_builder_program: PathBuf::from("ptaas_image_builder_does_not_exist"),
+                ..K8sConfig::default()
+            },
+        )
+        .await
+        .expect("Error creating project manager.");
+        let (event_sender, mut event_receiver) = mpsc::channel(16);
+
+        k8s_project_manager
+            .add_project(
+                String::from("valid_offline"),
+                String::from("Valid offline"),
+                project_dir,
+            )
+            .await
+            .expect("Error adding project.");
+        k8s_project_manager
+            .install_project(String::from("valid_offline"), Some(event_sender))
+            .await
+            .expect("Error starting installation.")
+            .task
+            .await
+            .expect("Installation task panicked.");
+        let event = event_receiver.recv().await;
+        let project_state = k8s_project_manager.project_state("valid_offline").await;
+        let start_run_result = k8s_project_manager
+            .start_run("valid_offline", 2, Vec::new())
+            .await;
+
+        assert!(matches!(
+            event,
+            Some(InstallerEvent::Failed { error }) if error.starts_with("Could not build the image")
+        ));
+        assert_eq!(
+            project_state.expect("Error getting project state."),
+            Some(ProjectState::InstallFailed(InstallFailure::Failed))
+        );
+        assert!(matches!(
+            start_run_result,
+            Err(K8sRunError::Transition(
+                ProjectTransitionError::InvalidTransition { .. }
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn start_run_with_unreachable_api_and_expect_installed_project() {
+        let temp_dir = test_dir("k8s_unreachable");
+        let test_dir = temp_dir.path();
+        let project_dir = test_dir.join("uploaded_projects").join("valid_offline");
+        copy_dir_all(
+            &Path::new(CRATE_DIR)
+                .join("tests_dir")
+                .join("uploaded_projects")
+                .join("valid_offline"),
+            &project_dir,
+            |_| false,
+        )
+        .await
+        .expect("Could not copy uploaded project");
+        let k8s_project_manager = K8sProjectManager::new(
+            test_dir.join("k8s"),
+            unreachable_client(),
+            Arc::new(InMemoryProjectStore::default()),
+            K8sConfig::default(),
+        )
+        .await
+        .expect("Error creating project manager.");
+
+        k8s_project_manager
+            .add_project(
+                String::from("valid_offline"),
+                String::from("Valid offline"),
+                project_dir,
+            )
+            .await
+            .expect("Error adding project.");
+        k8s_project_manager
+            .project_states
+            .set(String::from("valid_offline"), ProjectState::Installed)
+            .await;
+        let start_run_result = k8s_project_manager
+            .start_run("valid_offline", 2, Vec::new())
+            .await;
+        let project_state = k8s_project_manager.project_state("valid_offline").await;
+        let run_status = k8s_project_manager.run_status("valid_offline").await;
+
+        assert!(
+            matches!(
+                start_run_result,
+                Err(K8sRunError::CouldNotDeleteResources(
+                    DeleteResourcesError::CouldNotList(_)
+                ))
+            ),
+            "Unexpected result: {:?}",
+            start_run_result
+        );
+        assert_eq!(
+            project_state.expect("Error getting project state."),
+            Some(ProjectState::Installed)
+        );
+        assert_eq!(run_status, None);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn stop_run_with_unreachable_api_twice_and_expect_stopping_project_with_run() {
+        let temp_dir = test_dir("k8s_stop_unreachable");
+        let test_dir = temp_dir.path();
+        let project_dir = test_dir.join("uploaded_projects").join("valid_offline");
+        copy_dir_all(
+            &Path::new(CRATE_DIR)
+                .join("tests_dir")
+                .join("uploaded_projects")
+                .join("valid_offline"),
+            &project_dir,
+            |_| false,
+        )
+        .await
+        .expect("Could not copy uploaded project");
+        let k8s_project_manager = K8sProjectManager::new(
+            test_dir.join("k8s"),
+            unreachable_client(),
+            Arc::new(InMemoryProjectStore::default()),
+            K8sConfig::default(),
+        )
+        .await
+        .expect("Error creating project manager.");
+        let run_status = RunStatus {
+            master: None,
+            workers_running: 0,
+            workers: 2,
+        };
+
+        k8s_project_manager
+            .add_project(
+                String::from("valid_offline"),
+                String::from("Valid offline"),
+                project_dir,
+            )
+            .await
+            .expect("Error adding project.");
+        k8s_project_manager
+            .project_states
+            .set(String::from("valid_offline"), ProjectState::Running)
+            .await;
+        k8s_project_manager.runs.write().await.insert(
+            String::from("valid_offline"),
+            Run {
+                status: run_status.clone(),
+                cancellation_token: CancellationToken::new(),
+            },
+        );
+        let first_stop_run_result = k8s_project_manager.stop_run("valid_offline").await;
+        let second_stop_run_result = k8s_project_manager.stop_run("valid_offline").await;
+        let project_state = k8s_project_manager.project_state("valid_offline").await;
+
+        for stop_run_result in [first_stop_run_result, second_stop_run_result] {
+            assert!(
+                matches!(
+                    stop_run_result,
+                    Err(K8sRunError::CouldNotDeleteResources(
+                        DeleteResourcesError::CouldNotList(_)
+                    ))
+                ),
+                "Unexpected result: {:?}",
+                stop_run_result
+            );
+        }
+        assert_eq!(
+            project_state.expect("Error getting project state."),
+            Some(ProjectState::Stopping)
+        );
+        assert_eq!(
+            k8s_project_manager.run_status("valid_offline").await,
+            Some(run_status)
+        );
+    }
+}
@@ -0,0 +1,275 @@
+use super::k8s_config::K8sConfig;
+use k8s_openapi::{
+    api::{
+        apps::v1::{Deployment, DeploymentSpec},
+        core::v1::{
+            Container, ContainerPort, Pod, PodSpec, PodTemplateSpec, Service, ServicePort,
+            ServiceSpec,
+        },
+    },
+    apimachinery::pkg::apis::meta::v1::{LabelSelector, ObjectMeta},
+};
+use std::collections::BTreeMap;
+
+/// Selects the resources of a project, e.g. to delete its run, the value is its ```resource_name```.
+pub(super) const PROJECT_LABEL: &str = "ptaas.io/project";
+/// ```master``` or ```worker```.
+pub(super) const ROLE_LABEL: &str = "ptaas.io/role";
+
+/// The workers connect to the master on this port.
+const MASTER_BIND_PORT: u16 = 5557;
+const WEB_UI_PORT: u16 = 8089;
+/// Names are at most 63 characters, the prefix and the suffixes, e.g. ```-master```, take the rest.
+const MAX_NAME_LENGTH: usize = 40;
+/// The uploaded project is copied here, see ```dockerfile```.
+const PROJECT_DIR_IN_IMAGE: &str = "/project";
+
+/// The name of the image and of the resources of a project, a dns label derived from its id.
+/// Correctness: Ids that only differ in characters that are not allowed, or after ```MAX_NAME_LENGTH```, share the name.
+pub(super) fn resource_name(project_id: &str) -> String {
+    let name: String = project_id
+        .to_lowercase()
+        .chars()
+        .map(|character| {
+            if character.is_ascii_alphanumeric() {
+                character
+            } else {
+                '-'
+            }
+        })
+        .take(MAX_NAME_LENGTH)
+        .collect();
+
+    format!("ptaas-{}", name.trim_matches('-'))
+}
+
+/// The image a project is installed into, see ```K8sConfig::registry```.
+/// Correctness: A new installation replaces the image, the pods always pull it.
+pub(super) fn image(k8s_config: &K8sConfig, project_id: &str) -> String {
+    format!(
+        "{}/{}:latest",
+        k8s_config.registry.trim_end_matches('/'),
+        resource_name(project_id)
+    )
+}
+
+/// The requirements are installed before the project is copied, so the layer is reused if only the tests change.
+pub(super) fn dockerfile(k8s_config: &K8sConfig) -> String {
+    format!(
+        "FROM {base_image}\n\
+         COPY requirements.txt {PROJECT_DIR_IN_IMAGE}/requirements.txt\n\
+         RUN pip install --no-cache-dir -r {PROJECT_DIR_IN_IMAGE}/requirements.txt\n\
+         COPY . {PROJECT_DIR_IN_IMAGE}\n\
+         WORKDIR {PROJECT_DIR_IN_IMAGE}\n",
+        base_image = k8s_config.base_image,
+    )
+}
+
+fn labels(name: &str, role: &str) -> BTreeMap<String, String> {
+    BTreeMap::from([
+        (PROJECT_LABEL.to_owned(), name.to_owned()),
+        (ROLE_LABEL.to_owned(), role.to_owned()),
+    ])
+}
+
+fn metadata(name: &str, labels: BTreeMap<String, String>) -> ObjectMeta {
+    ObjectMeta {
+        name: Some(name.to_owned()),
+        labels: Some(labels),
+        ..ObjectMeta::default()
+    }
+}
+
+/// The image is pulled on every start, see ```image```.
+fn locust_container(image: &str, args: Vec<String>, ports: &[u16]) -> Container {
+    Container {
+        name: String::from("locust"),
+        image: Some(image.to_owned()),
+        image_pull_policy: Some(String::from("Always")),
+        args: Some(args),
+        ports: (!ports.is_empty()).then(|| {
+            ports
+                .iter()
+                .map(|port| ContainerPort {
+                    container_port: i32::from(*port),
+                    ..ContainerPort::default()
+                })
+                .collect()
+        }),
+        ..Container::default()
+    }
+}
+
+/// The resources of a run, applied by ```K8sProjectManager::start_run```.
+#[derive(Debug, Clone)]
+pub(super) struct RunResources {
+    pub master_pod: Pod,
+    /// The workers connect to the master through it.
+    pub master_service: Service,
+    pub worker_deployment: Deployment,
+}
+
+/// A master pod, a service the workers connect to and a deployment of ```workers``` worker pods.
+/// ```locust_args``` are passed to the master, e.g. ```--headless``` and the users.
+/// Correctness: The master is not restarted, the run is over when it exits, see ```RunStatus::is_finished```.
+pub(super) fn run_resources(
+    k8s_config: &K8sConfig,
+    project_id: &str,
+    workers: usize,
+    locust_args: &[String],
+) -> RunResources {
+    let name = resource_name(project_id);
+    let master_name = format!("{name}-master");
+    let worker_name = format!("{name}-worker");
+    let image = image(k8s_config, project_id);
+    let locustfile = format!("{PROJECT_DIR_IN_IMAGE}/locust");
+
+    let mut master_args = vec![
+        String::from("--locustfile"),
+        locustfile.clone(),
+        String::from("--master"),
+        String::from("--expect-workers"),
+        workers.to_string(),
+    ];
+    master_args.extend_from_slice(locust_args);
+
+    let worker_args = vec![
+        String::from("--locustfile"),
+        locustfile,
+        String::from("--worker"),
+        String::from("--master-host"),
+        master_name.clone(),
+    ];
+
+    RunResources {
+        master_pod: Pod {
+            metadata: metadata(&master_name, labels(&name, "master")),
+            spec: Some(PodSpec {
+                restart_policy: Some(String::from("Never")),
+                containers: vec![locust_container(
+                    &image,
+                    master_args,
+                    &[MASTER_BIND_PORT, WEB_UI_PORT],
+                )],
+                ..PodSpec::default()
+            }),
+            ..Pod::default()
+        },
+        master_service: Service {
+            metadata: metadata(&master_name, labels(&name, "master")),
+            spec: Some(ServiceSpec {
+                selector: Some(labels(&name, "master")),
+                ports: Some(vec![
+                    ServicePort {
+                        name: Some(String::from("master")),
+                        port: i32::from(MASTER_BIND_PORT),
+                        ..ServicePort::default()
+                    },
+                    ServicePort {
+                        name: Some(String::from("web-ui")),
+                        port: i32::from(WEB_UI_PORT),
+                        ..ServicePort::default()
+                    },
+                ]),
+                ..ServiceSpec::default()
+            }),
+            ..Service::default()
+        },
+        worker_deployment: Deployment {
+            metadata: metadata(&worker_name, labels(&name, "worker")),
+            spec: Some(DeploymentSpec {
+                replicas: Some(i32::try_from(workers).unwrap_or(i32::MAX)),
+                selector: LabelSelector {
+                    match_labels: Some(labels(&name, "worker")),
+                    ..LabelSelector::default()
+                },
+                template: PodTemplateSpec {
+                    metadata: Some(ObjectMeta {
+                        labels: Some(labels(&name, "worker")),
+                        ..ObjectMeta::default()
+                    }),
+                    spec: Some(PodSpec {
+                        containers: vec![locust_container(&image, worker_args, &[])],
+                        ..PodSpec::default()
+                    }),
+                },
+                ..DeploymentSpec::default()
+            }),
+            ..Deployment::default()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_run_resources_and_expect_workers_connected_to_master_service() {
+        let k8s_config = K8sConfig {
+            registry: String::from("registry.example.com/ptaas/"),
+            ..K8sConfig::default()
+        };
+
+        let run_resources =
+            run_resources(&k8s_config, "My_Project", 3, &[String::from("--headless")]);
+        let master_container = &run_resources
+            .master_pod
+            .spec
+            .expect("Master pod has no spec.")
+            .containers[0];
+        let worker_spec = run_resources
+            .worker_deployment
+            .spec
+            .expect("Worker deployment has no spec.");
+        let worker_container = &worker_spec
+            .template
+            .spec
+            .as_ref()
+            .expect("Worker template has no spec.")
+            .containers[0];
+
+        assert_eq!(resource_name("My_Project"), "ptaas-my-project");
+        assert_eq!(
+            image(&k8s_config, "My_Project"),
+            "registry.example.com/ptaas/ptaas-my-project:latest"
+        );
+        assert_eq!(
+            master_container.args.as_deref(),
+            Some(
+                [
+                    "--locustfile",
+                    "/project/locust",
+                    "--master",
+                    "--expect-workers",
+                    "3",
+                    "--headless"
+                ]
+                .map(String::from)
+                .as_slice()
+            )
+        );
+        assert_eq!(
+            run_resources.master_service.metadata.name.as_deref(),
+            Some("ptaas-my-project-master")
+        );
+        assert_eq!(worker_spec.replicas, Some(3));
+        assert_eq!(
+            worker_container
+                .args
+                .as_ref()
+                .and_then(|args| args.get(4))
+                .map(String::as_str),
+            Some("ptaas-my-project-master")
+        );
+        assert_eq!(
+            worker_spec
+                .template
+                .metadata
+                .and_then(|metadata| metadata.labels)
+                .and_then(|labels| labels.get(ROLE_LABEL).cloned())
+                .as_deref(),
+            Some("worker")
+        );
+    }
+}
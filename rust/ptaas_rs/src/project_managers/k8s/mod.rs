@@ -0,0 +1,10 @@
+mod cli;
+mod k8s_config;
+mod k8s_project_manager;
+mod manifests;
+mod pod_status;
+
+pub use cli::CliError;
+pub use k8s_config::K8sConfig;
+pub use k8s_project_manager::{DeleteResourcesError, K8sProjectManager, K8sRunError};
+pub use pod_status::{PodPhase, RunStatus};
@@ -0,0 +1,102 @@
+use super::manifests::ROLE_LABEL;
+use k8s_openapi::api::core::v1::Pod;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PodPhase {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    Unknown,
+}
+
+impl From<&str> for PodPhase {
+    fn from(phase: &str) -> Self {
+        match phase {
+            "Pending" => Self::Pending,
+            "Running" => Self::Running,
+            "Succeeded" => Self::Succeeded,
+            "Failed" => Self::Failed,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// The pods of a run of a ```K8sProjectManager```, as of the last event of the pod watcher, see ```K8sProjectManager::start_run```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunStatus {
+    /// ```None``` if the master pod does not exist, e.g. right after the run was started.
+    pub master: Option<PodPhase>,
+    pub workers_running: usize,
+    /// The requested workers, see ```K8sProjectManager::start_run```.
+    pub workers: usize,
+}
+
+impl RunStatus {
+    /// The master exited, the run is over.
+    pub fn is_finished(&self) -> bool {
+        matches!(self.master, Some(PodPhase::Succeeded | PodPhase::Failed))
+    }
+}
+
+/// The status of a run from the pods of the run, e.g. the state of a reflector.
+/// Pods without a role are ignored.
+pub(super) fn run_status<'a>(pods: impl IntoIterator<Item = &'a Pod>, workers: usize) -> RunStatus {
+    let mut run_status = RunStatus {
+        master: None,
+        workers_running: 0,
+        workers,
+    };
+
+    for pod in pods {
+        let phase = pod
+            .status
+            .as_ref()
+            .and_then(|status| status.phase.as_deref())
+            .map_or(PodPhase::Unknown, PodPhase::from);
+        let role = pod
+            .metadata
+            .labels
+            .as_ref()
+            .and_then(|labels| labels.get(ROLE_LABEL))
+            .map(String::as_str);
+
+        match role {
+            Some("master") => run_status.master = Some(phase),
+            Some("worker") if phase == PodPhase::Running => run_status.workers_running += 1,
+            _ => {}
+        }
+    }
+
+    run_status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_status_of_pods_of_finished_run_and_expect_running_workers_counted() {
+        let pods: Vec<Pod> = serde_json::from_str(
+            r#"[
+                { "metadata": { "name": "ptaas-project-master", "labels": { "ptaas.io/role": "master" } }, "status": { "phase": "Succeeded" } },
+                { "metadata": { "name": "ptaas-project-worker-1", "labels": { "ptaas.io/role": "worker" } }, "status": { "phase": "Running" } },
+                { "metadata": { "name": "ptaas-project-worker-2", "labels": { "ptaas.io/role": "worker" } }, "status": { "phase": "Pending" } },
+                { "metadata": { "name": "unrelated" } }
+            ]"#,
+        )
+        .expect("Error parsing pods.");
+
+        let run_status = run_status(&pods, 2);
+
+        assert_eq!(
+            run_status,
+            RunStatus {
+                master: Some(PodPhase::Succeeded),
+                workers_running: 1,
+                workers: 2,
+            }
+        );
+        assert!(run_status.is_finished());
+    }
+}
@@ -0,0 +1,463 @@
+use flate2::read::GzDecoder;
+use std::{
+    fs::File,
+    io::{Error as IoError, Read},
+    path::{Component, Path, PathBuf},
+};
+use thiserror::Error as ThisError;
+use tokio::{fs, task::JoinError};
+use uuid::Uuid;
+use zip::{result::ZipError, ZipArchive};
+
+/// Marks a dir next to an uploaded project dir as extracting, e.g. ```uploaded_projects/<id>.extracting-<uuid>```.
+const EXTRACTING_DIR_MARKER: &str = ".extracting-";
+
+/// Limits of an uploaded archive, checked while it is extracted.
+/// Correctness: The sizes are the ones of the extracted files, not the ones the archive claims.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArchiveLimits {
+    pub max_entries: usize,
+    pub max_total_size_bytes: u64,
+}
+
+impl Default for ArchiveLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: 10_000,
+            max_total_size_bytes: 512 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveFormat {
+    Zip,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    fn detect(archive_path: &Path) -> Option<Self> {
+        let file_name = archive_path.file_name()?.to_str()?.to_lowercase();
+
+        if file_name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(ThisError, Debug)]
+pub enum ArchiveError {
+    #[error("Archive format is not supported, expected .zip, .tar.gz or .tgz: {0}")]
+    UnsupportedFormat(PathBuf),
+    #[error("Could not read archive: {0}")]
+    CouldNotReadArchive(#[source] IoError),
+    #[error("Invalid zip archive: {0}")]
+    InvalidZip(#[source] ZipError),
+    #[error("Archive entry points outside of the project dir: {0}")]
+    PathTraversal(PathBuf),
+    #[error("Archive entry is neither a file nor a dir: {0}")]
+    UnsupportedEntry(PathBuf),
+    #[error("Archive has more than {max_entries} entries")]
+    TooManyEntries { max_entries: usize },
+    #[error("Extracted archive is bigger than {max_total_size_bytes} bytes")]
+    TooLarge { max_total_size_bytes: u64 },
+    #[error("Could not extract archive: {0}")]
+    CouldNotExtract(#[source] IoError),
+    #[error("Extraction task failed: {0}")]
+    TaskFailed(#[source] JoinError),
+}
+
+/// Extracts a zip or tar.gz archive, e.g. uploaded by the client, to ```uploaded_project_dir```.
+/// The archive is extracted next to ```uploaded_project_dir``` first and replaces it once the extraction succeeded.
+/// An archive with a single top level dir, e.g. ```project/requirements.txt```, is extracted without it.
+/// Correctness: Entries with absolute paths or ```..``` components fail with ```ArchiveError::PathTraversal```,
+/// symlinks and other special entries of tar archives with ```ArchiveError::UnsupportedEntry```.
+/// Symlinks of zip archives are extracted as regular files, containing the link target.
+pub async fn extract_uploaded_archive(
+    archive_path: &Path,
+    uploaded_project_dir: &Path,
+    archive_limits: ArchiveLimits,
+) -> Result<(), ArchiveError> {
+    let archive_format = ArchiveFormat::detect(archive_path)
+        .ok_or_else(|| ArchiveError::UnsupportedFormat(archive_path.into()))?;
+
+    let extracting_dir = extracting_dir_path(uploaded_project_dir);
+
+    let extraction_result = {
+        let archive_path = archive_path.to_path_buf();
+        let extracting_dir = extracting_dir.clone();
+
+        tokio::task::spawn_blocking(move || {
+            extract_blocking(
+                archive_format,
+                &archive_path,
+                &extracting_dir,
+                archive_limits,
+            )
+        })
+        .await
+        .map_err(ArchiveError::TaskFailed)
+        .and_then(|extraction_result| extraction_result)
+    };
+
+    let replace_result = match extraction_result {
+        Ok(()) => replace_uploaded_project_dir(&extracting_dir, uploaded_project_dir)
+            .await
+            .map_err(ArchiveError::CouldNotExtract),
+        Err(error) => Err(error),
+    };
+
+    if replace_result.is_err() {
+        let _ = fs::remove_dir_all(&extracting_dir).await;
+    }
+
+    replace_result
+}
+
+fn extracting_dir_path(uploaded_project_dir: &Path) -> PathBuf {
+    let mut file_name = uploaded_project_dir
+        .file_name()
+        .unwrap_or_default()
+        .to_os_string();
+    file_name.push(EXTRACTING_DIR_MARKER);
+    file_name.push(Uuid::new_v4().to_string());
+
+    uploaded_project_dir.with_file_name(file_name)
+}
+
+/// The path of an entry relative to the extraction dir, ```None``` if it would leave it.
+fn enclosed_path(entry_path: &Path) -> Option<PathBuf> {
+    let mut enclosed_path = PathBuf::new();
+
+    for component in entry_path.components() {
+        match component {
+            Component::Normal(component) => enclosed_path.push(component),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+
+    Some(enclosed_path)
+}
+
+/// Counts the entries and the extracted bytes of an archive against its limits.
+struct ExtractionBudget {
+    archive_limits: ArchiveLimits,
+    entries: usize,
+    total_size_bytes: u64,
+}
+
+impl ExtractionBudget {
+    fn new(archive_limits: ArchiveLimits) -> Self {
+        Self {
+            archive_limits,
+            entries: 0,
+            total_size_bytes: 0,
+        }
+    }
+
+    fn add_entry(&mut self) -> Result<(), ArchiveError> {
+        self.entries += 1;
+
+        if self.entries > self.archive_limits.max_entries {
+            return Err(ArchiveError::TooManyEntries {
+                max_entries: self.archive_limits.max_entries,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Copies at most the remaining bytes of the budget, plus one to detect an exceeded budget.
+    fn extract_file(&mut self, reader: &mut impl Read, path: &Path) -> Result<(), ArchiveError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(ArchiveError::CouldNotExtract)?;
+        }
+
+        let mut file = File::create(path).map_err(ArchiveError::CouldNotExtract)?;
+        let remaining_size_bytes = self.archive_limits.max_total_size_bytes - self.total_size_bytes;
+        let copied_bytes = std::io::copy(&mut reader.take(remaining_size_bytes + 1), &mut file)
+            .map_err(ArchiveError::CouldNotExtract)?;
+
+        if copied_bytes > remaining_size_bytes {
+            return Err(ArchiveError::TooLarge {
+                max_total_size_bytes: self.archive_limits.max_total_size_bytes,
+            });
+        }
+
+        self.total_size_bytes += copied_bytes;
+
+        Ok(())
+    }
+}
+
+fn extract_blocking(
+    archive_format: ArchiveFormat,
+    archive_path: &Path,
+    extracting_dir: &Path,
+    archive_limits: ArchiveLimits,
+) -> Result<(), ArchiveError> {
+    let archive_file = File::open(archive_path).map_err(ArchiveError::CouldNotReadArchive)?;
+    std::fs::create_dir_all(extracting_dir).map_err(ArchiveError::CouldNotExtract)?;
+
+    let mut extraction_budget = ExtractionBudget::new(archive_limits);
+
+    match archive_format {
+        ArchiveFormat::Zip => extract_zip(archive_file, extracting_dir, &mut extraction_budget),
+        ArchiveFormat::TarGz => {
+            extract_tar_gz(archive_file, extracting_dir, &mut extraction_budget)
+        }
+    }
+}
+
+fn extract_zip(
+    archive_file: File,
+    extracting_dir: &Path,
+    extraction_budget: &mut ExtractionBudget,
+) -> Result<(), ArchiveError> {
+    let mut zip_archive = ZipArchive::new(archive_file).map_err(ArchiveError::InvalidZip)?;
+
+    for index in 0..zip_archive.len() {
+        extraction_budget.add_entry()?;
+
+        let mut zip_file = zip_archive
+            .by_index(index)
+            .map_err(ArchiveError::InvalidZip)?;
+        let entry_path = PathBuf::from(zip_file.name());
+        let enclosed_path =
+            enclosed_path(&entry_path).ok_or(ArchiveError::PathTraversal(entry_path))?;
+        let path = extracting_dir.join(enclosed_path);
+
+        if zip_file.is_dir() {
+            std::fs::create_dir_all(&path).map_err(ArchiveError::CouldNotExtract)?;
+        } else {
+            extraction_budget.extract_file(&mut zip_file, &path)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn extract_tar_gz(
+    archive_file: File,
+    extracting_dir: &Path,
+    extraction_budget: &mut ExtractionBudget,
+) -> Result<(), ArchiveError> {
+    let mut tar_archive = tar::Archive::new(GzDecoder::new(archive_file));
+
+    for entry in tar_archive
+        .entries()
+        .map_err(ArchiveError::CouldNotReadArchive)?
+    {
+        extraction_budget.add_entry()?;
+
+        let mut entry = entry.map_err(ArchiveError::CouldNotReadArchive)?;
+        let entry_path = entry
+            .path()
+            .map_err(ArchiveError::CouldNotReadArchive)?
+            .into_owned();
+        let enclosed_path = enclosed_path(&entry_path)
+            .ok_or_else(|| ArchiveError::PathTraversal(entry_path.clone()))?;
+        let path = extracting_dir.join(enclosed_path);
+
+        match entry.header().entry_type() {
+            tar::EntryType::Directory => {
+                std::fs::create_dir_all(&path).map_err(ArchiveError::CouldNotExtract)?;
+            }
+            tar::EntryType::Regular | tar::EntryType::Continuous => {
+                extraction_budget.extract_file(&mut entry, &path)?;
+            }
+            // Written by some tar implementations, e.g. pax headers, not files of the project.
+            tar::EntryType::XGlobalHeader | tar::EntryType::XHeader => {}
+            _ => return Err(ArchiveError::UnsupportedEntry(entry_path)),
+        }
+    }
+
+    Ok(())
+}
+
+/// A single top level dir is unwrapped, see ```extract_uploaded_archive```.
+async fn replace_uploaded_project_dir(
+    extracting_dir: &Path,
+    uploaded_project_dir: &Path,
+) -> Result<(), IoError> {
+    let mut entries = fs::read_dir(extracting_dir).await?;
+    let mut top_level_entries = Vec::new();
+
+    while let Some(entry) = entries.next_entry().await? {
+        top_level_entries.push(entry);
+    }
+
+    let project_root = match top_level_entries.as_slice() {
+        [entry] if entry.file_type().await?.is_dir() => entry.path(),
+        _ => extracting_dir.to_path_buf(),
+    };
+
+    if fs::try_exists(uploaded_project_dir).await? {
+        fs::remove_dir_all(uploaded_project_dir).await?;
+    }
+
+    fs::rename(&project_root, uploaded_project_dir).await?;
+
+    if project_root != extracting_dir {
+        fs::remove_dir_all(extracting_dir).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_dir;
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+    use tracing_test::traced_test;
+    use zip::{write::FileOptions, ZipWriter};
+
+    fn write_zip(archive_path: &Path, files: &[(&str, &str)]) {
+        let mut zip_writer =
+            ZipWriter::new(File::create(archive_path).expect("Error creating archive."));
+
+        for (name, content) in files {
+            zip_writer
+                .start_file(*name, FileOptions::default())
+                .expect("Error starting file.");
+            zip_writer
+                .write_all(content.as_bytes())
+                .expect("Error writing file.");
+        }
+
+        zip_writer.finish().expect("Error finishing archive.");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn extract_zip_with_top_level_dir_and_expect_project_without_it() {
+        let temp_dir = test_dir("archive_zip");
+        let test_dir = temp_dir.path();
+        let archive_path = test_dir.join("project.zip");
+        let uploaded_project_dir = test_dir.join("project");
+        write_zip(
+            &archive_path,
+            &[
+                ("project/requirements.txt", "locust==2.15.1\n"),
+                (
+                    "project/locust/locustfile.py",
+                    "from locust import HttpUser\n",
+                ),
+            ],
+        );
+
+        let extraction_result = extract_uploaded_archive(
+            &archive_path,
+            &uploaded_project_dir,
+            ArchiveLimits::default(),
+        )
+        .await;
+
+        let requirements = fs::read_to_string(uploaded_project_dir.join("requirements.txt")).await;
+        let locustfile_exists =
+            fs::try_exists(uploaded_project_dir.join("locust").join("locustfile.py")).await;
+
+        extraction_result.expect("Error extracting archive.");
+        assert_eq!(
+            requirements.expect("Error reading requirements."),
+            "locust==2.15.1\n"
+        );
+        assert!(locustfile_exists.expect("Error checking locustfile."));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn extract_tar_gz_with_parent_dir_entry_and_expect_path_traversal_error() {
+        let temp_dir = test_dir("archive_traversal");
+        let test_dir = temp_dir.path();
+        let archive_path = test_dir.join("project.tar.gz");
+        let uploaded_project_dir = test_dir.join("project");
+
+        let mut tar_builder = tar::Builder::new(GzEncoder::new(
+            File::create(&archive_path).expect("Error creating archive."),
+            Compression::default(),
+        ));
+        let content = b"print('escaped')\n";
+        let mut header = tar::Header::new_old();
+        // ```Header::set_path``` rejects ```..```, the name is written directly like a malicious archive would.
+        let name = b"../escaped.py";
+        header.as_old_mut().name[..name.len()].copy_from_slice(name);
+        header.set_size(content.len() as u64);
+        header.set_entry_type(tar::EntryType::Regular);
+        header.set_cksum();
+        tar_builder
+            .append(&header, &content[..])
+            .expect("Error appending entry.");
+        tar_builder
+            .into_inner()
+            .expect("Error finishing archive.")
+            .finish()
+            .expect("Error finishing gzip.");
+
+        let extraction_result = extract_uploaded_archive(
+            &archive_path,
+            &uploaded_project_dir,
+            ArchiveLimits::default(),
+        )
+        .await;
+
+        let escaped_exists = fs::try_exists(test_dir.join("escaped.py")).await;
+        let uploaded_project_dir_exists = fs::try_exists(&uploaded_project_dir).await;
+
+        assert!(
+            matches!(extraction_result, Err(ArchiveError::PathTraversal(_))),
+            "Unexpected result: {:?}",
+            extraction_result
+        );
+        assert!(!escaped_exists.expect("Error checking escaped file."));
+        assert!(!uploaded_project_dir_exists.expect("Error checking uploaded project dir."));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn extract_zip_bigger_than_limit_and_expect_too_large_error() {
+        let temp_dir = test_dir("archive_too_large");
+        let test_dir = temp_dir.path();
+        let archive_path = test_dir.join("project.zip");
+        let uploaded_project_dir = test_dir.join("project");
+        let requirements = "locust==2.15.1\n".repeat(100);
+        write_zip(&archive_path, &[("requirements.txt", &requirements)]);
+
+        let extraction_result = extract_uploaded_archive(
+            &archive_path,
+            &uploaded_project_dir,
+            ArchiveLimits {
+                max_entries: 10,
+                max_total_size_bytes: 1024,
+            },
+        )
+        .await;
+
+        let mut remaining_entries = fs::read_dir(&test_dir)
+            .await
+            .expect("Error reading test dir.");
+        let mut remaining_file_names = Vec::new();
+        while let Ok(Some(entry)) = remaining_entries.next_entry().await {
+            remaining_file_names.push(entry.file_name());
+        }
+
+        assert!(
+            matches!(
+                extraction_result,
+                Err(ArchiveError::TooLarge {
+                    max_total_size_bytes: 1024
+                })
+            ),
+            "Unexpected result: {:?}",
+            extraction_result
+        );
+        assert_eq!(remaining_file_names, vec!["project.zip"]);
+    }
+}
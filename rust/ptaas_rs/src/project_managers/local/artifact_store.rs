@@ -0,0 +1,587 @@
+use super::blob_store::{delete_dir, BlobStore, BlobStoreError};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{Error as IoError, ErrorKind},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use thiserror::Error as ThisError;
+use tokio::{
+    fs::{self, File},
+    sync::Mutex,
+};
+use tokio_util::io::ReaderStream;
+use uuid::Uuid;
+
+const ARTIFACT_INDEX_FILE_NAME: &str = "artifacts.json";
+
+/// What produced an ```Artifact```.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ArtifactSource {
+    /// The last installation of the project, its files are replaced by the next installation.
+    Install,
+    Run {
+        run_id: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ArtifactKind {
+    /// The output of a process, e.g. ```req_out.txt``` or a rotated ```req_out.txt.1```.
+    Log,
+    /// The stats of locust.
+    Csv,
+    /// E.g. ```run_report.json``` or ```report.html```.
+    Report,
+}
+
+impl ArtifactKind {
+    fn of_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("csv") => Self::Csv,
+            Some("json" | "html" | "md") => Self::Report,
+            _ => Self::Log,
+        }
+    }
+}
+
+/// A file produced by an installation or a run, registered in the ```ArtifactStore```.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Artifact {
+    pub id: String,
+    pub project_id: String,
+    pub source: ArtifactSource,
+    pub kind: ArtifactKind,
+    /// The file name, e.g. for the ```Content-Disposition``` of a download.
+    pub name: String,
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    /// Registered again after the file was replaced, e.g. by the next installation.
+    pub registered_at: SystemTime,
+    /// The uploaded copy of the file, if the store has a ```BlobStore```, see ```ArtifactStore::set_blob_store```.
+    #[serde(default)]
+    pub blob_key: Option<String>,
+}
+
+/// What the ```ArtifactStore``` deletes. Nothing is deleted by default.
+/// Correctness: A run, whose ```run_report.json``` was deleted, is still listed by ```LocalProjectManager::list_runs```,
+/// but its report can not be loaded anymore.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArtifactRetention {
+    /// Older artifacts are deleted by ```ArtifactStore::prune```.
+    pub max_age: Option<Duration>,
+    /// The oldest artifacts of a project are deleted, once a registered artifact exceeds the quota.
+    /// The registered artifact itself is kept, even if it alone exceeds the quota.
+    pub max_project_size_bytes: Option<u64>,
+}
+
+/// The files of the installations and runs, indexed in ```artifacts.json```.
+/// Correctness: Only the index is shared between clones, the ```ArtifactRetention``` and the ```BlobStore``` are not.
+#[derive(Debug, Clone)]
+pub struct ArtifactStore {
+    index_path: PathBuf,
+    retention: ArtifactRetention,
+    blob_store: Option<Arc<dyn BlobStore>>,
+    /// The oldest artifact first. Locked while the index is written, so the writes do not overtake each other.
+    artifacts: Arc<Mutex<Vec<Artifact>>>,
+}
+
+#[derive(ThisError, Debug)]
+pub enum ArtifactStoreError {
+    #[error("Artifact does not exist: {0}")]
+    ArtifactDoesNotExist(String),
+    #[error("Could not read the artifact: {0}")]
+    CouldNotReadArtifact(#[source] IoError),
+    #[error("Could not read the artifact index: {0}")]
+    CouldNotReadIndex(#[source] IoError),
+    #[error("Could not parse the artifact index: {0}")]
+    CouldNotParseIndex(#[source] serde_json::Error),
+    #[error("Could not write the artifact index: {0}")]
+    CouldNotWriteIndex(#[source] IoError),
+    #[error("Could not upload the artifact: {0}")]
+    CouldNotUploadArtifact(#[source] BlobStoreError),
+    #[error("Could not download the artifact: {0}")]
+    CouldNotDownloadArtifact(#[source] BlobStoreError),
+    #[error("Could not delete the uploaded artifacts: {0}")]
+    CouldNotDeleteUploadedArtifacts(#[source] BlobStoreError),
+}
+
+impl ArtifactStore {
+    /// Loads the index in ```dir```, a missing index is an empty store.
+    pub(super) async fn load(dir: &Path) -> Result<Self, ArtifactStoreError> {
+        let index_path = dir.join(ARTIFACT_INDEX_FILE_NAME);
+        let artifacts = match fs::read(&index_path).await {
+            Ok(index) => {
+                serde_json::from_slice(&index).map_err(ArtifactStoreError::CouldNotParseIndex)?
+            }
+            Err(error) if error.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(error) => return Err(ArtifactStoreError::CouldNotReadIndex(error)),
+        };
+
+        Ok(Self {
+            index_path,
+            retention: ArtifactRetention::default(),
+            blob_store: None,
+            artifacts: Arc::new(Mutex::new(artifacts)),
+        })
+    }
+
+    pub fn set_retention(&mut self, retention: ArtifactRetention) {
+        self.retention = retention;
+    }
+
+    pub fn retention(&self) -> ArtifactRetention {
+        self.retention
+    }
+
+    /// Uploads the registered files to ```blob_store```, so they can be downloaded by every replica.
+    /// Correctness: The artifacts registered before are not uploaded.
+    pub fn set_blob_store(&mut self, blob_store: Option<Arc<dyn BlobStore>>) {
+        self.blob_store = blob_store;
+    }
+
+    pub fn blob_store(&self) -> Option<&Arc<dyn BlobStore>> {
+        self.blob_store.as_ref()
+    }
+
+    /// Registers the file at ```path```. A registered file keeps its id, its size is updated, e.g. of an overwritten log file.
+    /// Deletes the oldest artifacts of the project, that exceed ```ArtifactRetention::max_project_size_bytes```.
+    /// Correctness: The index is locked while the file is uploaded.
+    pub async fn register(
+        &self,
+        project_id: &str,
+        source: ArtifactSource,
+        path: PathBuf,
+    ) -> Result<Artifact, ArtifactStoreError> {
+        let metadata = fs::metadata(&path)
+            .await
+            .map_err(ArtifactStoreError::CouldNotReadArtifact)?;
+
+        let mut artifacts = self.artifacts.lock().await;
+        let id = match artifacts.iter().position(|artifact| artifact.path == path) {
+            Some(index) => artifacts.remove(index).id,
+            None => Uuid::new_v4().to_string(),
+        };
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let blob_key = match &self.blob_store {
+            Some(blob_store) => {
+                let blob_key = format!("{}/{id}/{name}", blob_prefix(project_id));
+                blob_store
+                    .put_file(&blob_key, &path)
+                    .await
+                    .map_err(ArtifactStoreError::CouldNotUploadArtifact)?;
+
+                Some(blob_key)
+            }
+            None => None,
+        };
+
+        let artifact = Artifact {
+            id,
+            project_id: project_id.to_owned(),
+            source,
+            kind: ArtifactKind::of_path(&path),
+            name,
+            size_bytes: metadata.len(),
+            registered_at: SystemTime::now(),
+            path,
+            blob_key,
+        };
+        artifacts.push(artifact.clone());
+
+        if let Some(max_project_size_bytes) = self.retention.max_project_size_bytes {
+            let over_quota = take_over_quota(&mut artifacts, &artifact, max_project_size_bytes);
+            for deleted in
+                delete_files(self.blob_store.as_deref(), &mut artifacts, over_quota).await
+            {
+                tracing::info!(path = ?deleted.path, "Deleted artifact over quota");
+            }
+        }
+
+        self.write_index(&artifacts).await?;
+
+        Ok(artifact)
+    }
+
+    /// Registers every file in ```dir```, sub dirs are skipped.
+    pub async fn register_dir(
+        &self,
+        project_id: &str,
+        source: ArtifactSource,
+        dir: &Path,
+    ) -> Result<Vec<Artifact>, ArtifactStoreError> {
+        let mut entries = fs::read_dir(dir)
+            .await
+            .map_err(ArtifactStoreError::CouldNotReadArtifact)?;
+        let mut paths = Vec::new();
+
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(ArtifactStoreError::CouldNotReadArtifact)?
+        {
+            let file_type = entry
+                .file_type()
+                .await
+                .map_err(ArtifactStoreError::CouldNotReadArtifact)?;
+            if file_type.is_file() {
+                paths.push(entry.path());
+            }
+        }
+
+        // The artifacts of a dir are listed by name.
+        paths.sort();
+
+        let mut registered = Vec::with_capacity(paths.len());
+        for path in paths {
+            registered.push(self.register(project_id, source.clone(), path).await?);
+        }
+
+        Ok(registered)
+    }
+
+    /// The artifacts of the project, the oldest artifact first.
+    pub async fn list(&self, project_id: &str) -> Vec<Artifact> {
+        self.artifacts
+            .lock()
+            .await
+            .iter()
+            .filter(|artifact| artifact.project_id == project_id)
+            .cloned()
+            .collect()
+    }
+
+    pub async fn get(&self, id: &str) -> Option<Artifact> {
+        self.artifacts
+            .lock()
+            .await
+            .iter()
+            .find(|artifact| artifact.id == id)
+            .cloned()
+    }
+
+    /// Opens the artifact for a download, the stream reads the file in chunks.
+    /// A missing file is downloaded from the ```BlobStore``` first, e.g. if it was registered by another replica.
+    pub async fn open(
+        &self,
+        id: &str,
+    ) -> Result<(Artifact, ReaderStream<File>), ArtifactStoreError> {
+        let artifact = self
+            .get(id)
+            .await
+            .ok_or_else(|| ArtifactStoreError::ArtifactDoesNotExist(id.to_owned()))?;
+        let file = match (
+            File::open(&artifact.path).await,
+            &self.blob_store,
+            &artifact.blob_key,
+        ) {
+            (Err(error), Some(blob_store), Some(blob_key))
+                if error.kind() == ErrorKind::NotFound =>
+            {
+                blob_store
+                    .get_file(blob_key, &artifact.path)
+                    .await
+                    .map_err(ArtifactStoreError::CouldNotDownloadArtifact)?;
+
+                File::open(&artifact.path).await
+            }
+            (file, _, _) => file,
+        }
+        .map_err(ArtifactStoreError::CouldNotReadArtifact)?;
+
+        Ok((artifact, ReaderStream::new(file)))
+    }
+
+    /// Deletes the artifacts older than ```ArtifactRetention::max_age``` at ```now``` and returns them.
+    /// Correctness: An artifact, whose file could not be deleted, is logged and kept, the next prune retries it.
+    pub async fn prune(&self, now: SystemTime) -> Result<Vec<Artifact>, ArtifactStoreError> {
+        let Some(max_age) = self.retention.max_age else {
+            return Ok(Vec::new());
+        };
+
+        let mut artifacts = self.artifacts.lock().await;
+        let (expired, kept) = artifacts.drain(..).partition(|artifact: &Artifact| {
+            now.duration_since(artifact.registered_at)
+                .is_ok_and(|age| age > max_age)
+        });
+        *artifacts = kept;
+
+        let deleted = delete_files(self.blob_store.as_deref(), &mut artifacts, expired).await;
+        self.write_index(&artifacts).await?;
+
+        Ok(deleted)
+    }
+
+    /// Forgets the artifacts of a deleted project, their files are deleted with the dirs of the project.
+    /// Their uploaded copies are deleted here.
+    pub(super) async fn remove_project(&self, project_id: &str) -> Result<(), ArtifactStoreError> {
+        let mut artifacts = self.artifacts.lock().await;
+        if let Some(blob_store) = &self.blob_store {
+            delete_dir(blob_store.as_ref(), &blob_prefix(project_id))
+                .await
+                .map_err(ArtifactStoreError::CouldNotDeleteUploadedArtifacts)?;
+        }
+        artifacts.retain(|artifact| artifact.project_id != project_id);
+
+        self.write_index(&artifacts).await
+    }
+
+    async fn write_index(&self, artifacts: &[Artifact]) -> Result<(), ArtifactStoreError> {
+        let write = async {
+            let index = serde_json::to_vec_pretty(artifacts)?;
+
+            fs::write(&self.index_path, index).await
+        };
+
+        write.await.map_err(ArtifactStoreError::CouldNotWriteIndex)
+    }
+}
+
+/// Takes the oldest artifacts of the project of ```registered``` out of ```artifacts```, until the project is within the quota.
+fn take_over_quota(
+    artifacts: &mut Vec<Artifact>,
+    registered: &Artifact,
+    max_project_size_bytes: u64,
+) -> Vec<Artifact> {
+    let project_size: u64 = artifacts
+        .iter()
+        .filter(|artifact| artifact.project_id == registered.project_id)
+        .map(|artifact| artifact.size_bytes)
+        .sum();
+    let mut excess_bytes = project_size.saturating_sub(max_project_size_bytes);
+    let mut over_quota = Vec::new();
+
+    artifacts.retain(|artifact| {
+        if excess_bytes == 0
+            || artifact.project_id != registered.project_id
+            || artifact.id == registered.id
+        {
+            return true;
+        }
+
+        excess_bytes = excess_bytes.saturating_sub(artifact.size_bytes);
+        over_quota.push(artifact.clone());
+        false
+    });
+
+    over_quota
+}
+
+/// The blobs of the artifacts of a project, see ```ArtifactStore::set_blob_store```.
+fn blob_prefix(project_id: &str) -> String {
+    format!("artifacts/{project_id}")
+}
+
+/// Deletes the files and their uploaded copies and returns the deleted artifacts, a missing file counts as deleted.
+/// The artifacts, whose files could not be deleted, are put back into ```artifacts```.
+async fn delete_files(
+    blob_store: Option<&dyn BlobStore>,
+    artifacts: &mut Vec<Artifact>,
+    to_delete: Vec<Artifact>,
+) -> Vec<Artifact> {
+    let mut deleted = Vec::with_capacity(to_delete.len());
+
+    for artifact in to_delete {
+        if let (Some(blob_store), Some(blob_key)) = (blob_store, &artifact.blob_key) {
+            if let Err(error) = blob_store.delete(blob_key).await {
+                tracing::warn!(%error, blob_key, "Could not delete uploaded artifact");
+
+                artifacts.push(artifact);
+                continue;
+            }
+        }
+
+        match fs::remove_file(&artifact.path).await {
+            Ok(()) => deleted.push(artifact),
+            Err(error) if error.kind() == ErrorKind::NotFound => deleted.push(artifact),
+            Err(error) => {
+                tracing::warn!(%error, path = ?artifact.path, "Could not delete artifact");
+
+                artifacts.push(artifact);
+            }
+        }
+    }
+
+    artifacts.sort_by_key(|artifact| artifact.registered_at);
+
+    deleted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_managers::local::blob_store::LocalBlobStore;
+    use crate::util::test_dir;
+    use tokio::io::AsyncReadExt;
+    use tokio_util::io::StreamReader;
+
+    #[tokio::test]
+    async fn register_prune_and_open_artifacts_and_expect_quota_and_retention() {
+        let temp_dir = test_dir("artifact_store");
+        let test_dir = temp_dir.path();
+        let run_dir = test_dir.join("runs").join("run");
+        fs::create_dir_all(run_dir.join("sub_dir"))
+            .await
+            .expect("Error creating test dir.");
+        let log_path = test_dir.join("req_out.txt");
+        fs::write(&log_path, "installed")
+            .await
+            .expect("Error writing log.");
+        fs::write(run_dir.join("locust_stats.csv"), "a".repeat(40))
+            .await
+            .expect("Error writing stats.");
+        fs::write(run_dir.join("run_report.json"), "b".repeat(40))
+            .await
+            .expect("Error writing report.");
+
+        let mut artifact_store = ArtifactStore::load(test_dir)
+            .await
+            .expect("Error loading artifact store.");
+        artifact_store.set_retention(ArtifactRetention {
+            max_age: Some(Duration::from_secs(60)),
+            max_project_size_bytes: Some(100),
+        });
+
+        let log = artifact_store
+            .register("project", ArtifactSource::Install, log_path.clone())
+            .await
+            .expect("Error registering log.");
+        let run_artifacts = artifact_store
+            .register_dir(
+                "project",
+                ArtifactSource::Run {
+                    run_id: String::from("run"),
+                },
+                &run_dir,
+            )
+            .await
+            .expect("Error registering run dir.");
+        // The log of the next installation exceeds the quota, the oldest run artifact is deleted.
+        fs::write(&log_path, "installed again, with a longer log")
+            .await
+            .expect("Error writing log.");
+        let registered_again = artifact_store
+            .register("project", ArtifactSource::Install, log_path.clone())
+            .await
+            .expect("Error registering log again.");
+        let listed = artifact_store.list("project").await;
+        let stats_exists = fs::try_exists(run_dir.join("locust_stats.csv")).await;
+
+        let (opened, stream) = artifact_store
+            .open(&registered_again.id)
+            .await
+            .expect("Error opening log.");
+        let mut content = String::new();
+        StreamReader::new(stream)
+            .read_to_string(&mut content)
+            .await
+            .expect("Error reading log.");
+        let missing = artifact_store.open("missing").await;
+
+        let loaded_again = ArtifactStore::load(test_dir)
+            .await
+            .expect("Error loading artifact store again.")
+            .list("project")
+            .await;
+        let pruned_early = artifact_store
+            .prune(SystemTime::now())
+            .await
+            .expect("Error pruning artifacts.");
+        let pruned = artifact_store
+            .prune(SystemTime::now() + Duration::from_secs(120))
+            .await
+            .expect("Error pruning artifacts.");
+        let log_exists = fs::try_exists(&log_path).await;
+
+        assert_eq!(log.kind, ArtifactKind::Log);
+        assert_eq!(
+            run_artifacts
+                .iter()
+                .map(|artifact| (artifact.name.as_str(), artifact.kind))
+                .collect::<Vec<_>>(),
+            [
+                ("locust_stats.csv", ArtifactKind::Csv),
+                ("run_report.json", ArtifactKind::Report)
+            ]
+        );
+        assert_eq!(registered_again.id, log.id);
+        assert_eq!(registered_again.size_bytes, 34);
+        assert_eq!(
+            listed
+                .iter()
+                .map(|artifact| artifact.name.as_str())
+                .collect::<Vec<_>>(),
+            ["run_report.json", "req_out.txt"]
+        );
+        assert!(!stats_exists.expect("Error checking stats."));
+        assert_eq!(opened, registered_again);
+        assert_eq!(content, "installed again, with a longer log");
+        assert!(matches!(
+            missing,
+            Err(ArtifactStoreError::ArtifactDoesNotExist(_))
+        ));
+        assert_eq!(loaded_again, listed);
+        assert!(pruned_early.is_empty());
+        assert_eq!(pruned, listed);
+        assert!(artifact_store.list("project").await.is_empty());
+        assert!(!log_exists.expect("Error checking log."));
+    }
+
+    #[tokio::test]
+    async fn open_artifact_registered_by_another_replica_and_expect_downloaded_file() {
+        let temp_dir = test_dir("artifact_store_blobs");
+        let test_dir = temp_dir.path();
+        let report_path = test_dir.join("run_report.json");
+        fs::write(&report_path, "{}")
+            .await
+            .expect("Error writing report.");
+        let blob_store: Arc<dyn BlobStore> = Arc::new(LocalBlobStore::new(test_dir.join("blobs")));
+        let mut artifact_store = ArtifactStore::load(test_dir)
+            .await
+            .expect("Error loading artifact store.");
+        artifact_store.set_blob_store(Some(blob_store.clone()));
+
+        let report = artifact_store
+            .register(
+                "project",
+                ArtifactSource::Run {
+                    run_id: String::from("run"),
+                },
+                report_path.clone(),
+            )
+            .await
+            .expect("Error registering report.");
+        fs::remove_file(&report_path)
+            .await
+            .expect("Error removing report.");
+        let (_, stream) = artifact_store
+            .open(&report.id)
+            .await
+            .expect("Error opening report.");
+        let mut content = String::new();
+        StreamReader::new(stream)
+            .read_to_string(&mut content)
+            .await
+            .expect("Error reading report.");
+        artifact_store
+            .remove_project("project")
+            .await
+            .expect("Error removing project.");
+        let blob_keys = blob_store.list("artifacts/").await;
+
+        assert_eq!(
+            report.blob_key,
+            Some(format!("artifacts/project/{}/run_report.json", report.id))
+        );
+        assert_eq!(content, "{}");
+        assert!(blob_keys.expect("Error listing blobs.").is_empty());
+    }
+}
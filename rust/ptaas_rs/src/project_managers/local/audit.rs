@@ -0,0 +1,262 @@
+use crate::project_managers::process::{
+    self, ProcessRunError, Status, TerminationStatus, TerminationWithErrorStatus,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use thiserror::Error as ThisError;
+use tokio_util::sync::CancellationToken;
+
+/// The JSON report of a large environment stays far below this.
+const MAX_PIP_AUDIT_OUTPUT_SIZE: usize = 16 * 1024 * 1024;
+
+/// pip-audit queries the vulnerability service for every package, an unreachable service is given up on.
+const PIP_AUDIT_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// What the installer does with the vulnerabilities found by the audit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AuditPolicy {
+    /// The audit does not run.
+    #[default]
+    Disabled,
+    /// The vulnerabilities are attached to ```InstallReport```.
+    Report,
+    /// The installation fails with ```ErrorThatTriggersCleanUp::VulnerabilitiesFound```.
+    /// Correctness: pip-audit does not report severities, every vulnerability that is not ignored is critical.
+    FailOnVulnerabilities,
+}
+
+/// The audit phase of a ```LocalProjectInstaller```, that runs ```pip-audit``` against the virtual environment
+/// after the requirements are installed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditConfig {
+    pub policy: AuditPolicy,
+    /// A program name, that is looked up in ```PATH```, or a path. pip-audit is not installed into the environment.
+    pub pip_audit_path: PathBuf,
+    /// Passed to pip-audit with ```--ignore-vuln```, e.g. accepted risks. Aliases like CVE ids are matched too.
+    pub ignored_vulnerability_ids: Vec<String>,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            policy: AuditPolicy::default(),
+            pip_audit_path: PathBuf::from("pip-audit"),
+            ignored_vulnerability_ids: Vec::new(),
+        }
+    }
+}
+
+/// The vulnerable packages of an environment. Packages without vulnerabilities are left out.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VulnerabilityReport {
+    pub vulnerable_packages: Vec<VulnerablePackage>,
+}
+
+impl VulnerabilityReport {
+    pub fn is_empty(&self) -> bool {
+        self.vulnerable_packages.is_empty()
+    }
+
+    pub fn vulnerability_count(&self) -> usize {
+        self.vulnerable_packages
+            .iter()
+            .map(|vulnerable_package| vulnerable_package.vulnerabilities.len())
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VulnerablePackage {
+    pub name: String,
+    pub version: String,
+    pub vulnerabilities: Vec<Vulnerability>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Vulnerability {
+    /// e.g. ```PYSEC-2023-62``` or ```GHSA-j8r2-6x86-q33q```.
+    pub id: String,
+    /// e.g. ```CVE-2023-32681```.
+    pub aliases: Vec<String>,
+    /// The versions that fix the vulnerability. Empty if there is no fix yet.
+    pub fix_versions: Vec<String>,
+    pub description: String,
+}
+
+/// The output of ```pip-audit --format json```.
+#[derive(Deserialize)]
+struct PipAuditOutput {
+    dependencies: Vec<PipAuditDependency>,
+}
+
+#[derive(Deserialize)]
+struct PipAuditDependency {
+    name: String,
+    /// Missing for skipped dependencies, e.g. packages that are not on PyPI.
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default)]
+    vulns: Vec<PipAuditVulnerability>,
+}
+
+#[derive(Deserialize)]
+struct PipAuditVulnerability {
+    id: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    fix_versions: Vec<String>,
+    #[serde(default)]
+    description: String,
+}
+
+impl VulnerabilityReport {
+    fn parse(pip_audit_output: &str) -> Result<Self, serde_json::Error> {
+        let pip_audit_output: PipAuditOutput = serde_json::from_str(pip_audit_output)?;
+
+        let vulnerable_packages = pip_audit_output
+            .dependencies
+            .into_iter()
+            .filter(|dependency| !dependency.vulns.is_empty())
+            .map(|dependency| VulnerablePackage {
+                name: dependency.name,
+                version: dependency.version.unwrap_or_default(),
+                vulnerabilities: dependency
+                    .vulns
+                    .into_iter()
+                    .map(|vuln| Vulnerability {
+                        id: vuln.id,
+                        aliases: vuln.aliases,
+                        fix_versions: vuln.fix_versions,
+                        description: vuln.description,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(Self {
+            vulnerable_packages,
+        })
+    }
+}
+
+/// Runs pip-audit against the environment of ```env_python_path``` with ```PIPAPI_PYTHON_LOCATION```.
+/// pip-audit exits with 1 if it found vulnerabilities, that is not a failure.
+pub(super) async fn run(
+    audit_config: &AuditConfig,
+    env_python_path: &Path,
+    current_dir: &Path,
+    cancellation_token: &CancellationToken,
+) -> Result<VulnerabilityReport, AuditError> {
+    let mut args = vec![
+        OsStr::new("--format"),
+        OsStr::new("json"),
+        OsStr::new("--progress-spinner"),
+        OsStr::new("off"),
+    ];
+    for ignored_vulnerability_id in &audit_config.ignored_vulnerability_ids {
+        args.push(OsStr::new("--ignore-vuln"));
+        args.push(OsStr::new(ignored_vulnerability_id));
+    }
+
+    let output = process::capture(
+        audit_config.pip_audit_path.as_os_str(),
+        args,
+        current_dir,
+        vec![(
+            OsString::from("PIPAPI_PYTHON_LOCATION"),
+            env_python_path.as_os_str().to_os_string(),
+        )],
+        MAX_PIP_AUDIT_OUTPUT_SIZE,
+        PIP_AUDIT_TIMEOUT,
+        Some(cancellation_token),
+    )
+    .await
+    .map_err(AuditError::CouldNotRunPipAudit)?;
+
+    match output.status {
+        Status::Terminated(TerminationStatus::TerminatedSuccessfully)
+        | Status::Terminated(TerminationStatus::TerminatedWithError(
+            TerminationWithErrorStatus::TerminatedWithErrorCode(1),
+        )) => {}
+        status => return Err(AuditError::PipAuditFailed(status, output.stderr)),
+    }
+
+    VulnerabilityReport::parse(&output.stdout).map_err(AuditError::CouldNotParseReport)
+}
+
+#[derive(ThisError, Debug)]
+pub enum AuditError {
+    #[error("Could not run pip-audit: {0}")]
+    CouldNotRunPipAudit(#[source] ProcessRunError),
+    #[error("pip-audit failed: {1}")]
+    PipAuditFailed(Status, String),
+    #[error("Could not parse the pip-audit report: {0}")]
+    CouldNotParseReport(#[source] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_test::traced_test;
+
+    #[test]
+    fn parse_pip_audit_output_and_expect_only_vulnerable_packages() {
+        let pip_audit_output = r#"{"dependencies": [
+            {"name": "locust", "version": "2.15.1", "vulns": []},
+            {"name": "requests", "version": "2.30.0", "vulns": [
+                {"id": "GHSA-j8r2-6x86-q33q", "fix_versions": ["2.31.0"], "aliases": ["CVE-2023-32681"], "description": "Leaked Proxy-Authorization header"}
+            ]},
+            {"name": "internal-package", "skip_reason": "Dependency not found on PyPI"}
+        ], "fixes": []}"#;
+
+        let vulnerability_report =
+            VulnerabilityReport::parse(pip_audit_output).expect("Error parsing report.");
+
+        assert_eq!(vulnerability_report.vulnerability_count(), 1);
+        assert_eq!(
+            vulnerability_report.vulnerable_packages[0],
+            VulnerablePackage {
+                name: String::from("requests"),
+                version: String::from("2.30.0"),
+                vulnerabilities: vec![Vulnerability {
+                    id: String::from("GHSA-j8r2-6x86-q33q"),
+                    aliases: vec![String::from("CVE-2023-32681")],
+                    fix_versions: vec![String::from("2.31.0")],
+                    description: String::from("Leaked Proxy-Authorization header"),
+                }],
+            }
+        );
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn run_pip_audit_that_finds_vulnerabilities_and_expect_report() {
+        let tests_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests_dir");
+        let audit_config = AuditConfig {
+            policy: AuditPolicy::Report,
+            pip_audit_path: tests_dir.join("pip_audit_with_vulnerabilities.sh"),
+            ignored_vulnerability_ids: Vec::new(),
+        };
+
+        let vulnerability_report = run(
+            &audit_config,
+            Path::new("python3"),
+            &tests_dir,
+            &CancellationToken::new(),
+        )
+        .await
+        .expect("Error running pip-audit.");
+
+        assert_eq!(vulnerability_report.vulnerability_count(), 1);
+        assert_eq!(vulnerability_report.vulnerable_packages[0].name, "requests");
+    }
+}
@@ -0,0 +1,276 @@
+use super::{
+    installer_events::InstallerEvent,
+    local_project_installer::{CheckAndInstallError, InstallOutcome, LocalProjectInstaller},
+};
+use std::sync::Arc;
+use thiserror::Error as ThisError;
+use tokio::{
+    sync::{mpsc, Semaphore},
+    task::{JoinError, JoinHandle},
+};
+use tracing::Instrument;
+
+/// Events of one installation are forwarded as soon as they are received, a small buffer is enough.
+const PROJECT_EVENT_CHANNEL_CAPACITY: usize = 64;
+
+/// An ```InstallerEvent``` of a project installed by a ```BatchInstaller```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchInstallerEvent {
+    pub project_id: String,
+    pub installer_event: InstallerEvent,
+}
+
+/// Returned by ```BatchInstaller::install```.
+#[derive(Debug)]
+pub struct BatchInstallReport {
+    /// In the order of the given installers.
+    pub results: Vec<BatchInstallResult>,
+}
+
+impl BatchInstallReport {
+    pub fn succeeded_count(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|batch_install_result| batch_install_result.result.is_ok())
+            .count()
+    }
+
+    pub fn failed_count(&self) -> usize {
+        self.results.len() - self.succeeded_count()
+    }
+}
+
+#[derive(Debug)]
+pub struct BatchInstallResult {
+    pub project_id: String,
+    pub result: Result<InstallOutcome, BatchInstallError>,
+}
+
+#[derive(ThisError, Debug)]
+pub enum BatchInstallError {
+    #[error("Failed to check and install project: {0}")]
+    CheckAndInstallError(
+        #[from]
+        #[source]
+        CheckAndInstallError,
+    ),
+    #[error("Installation task failed: {0}")]
+    TaskFailed(#[source] JoinError),
+}
+
+/// Installs many projects with ```LocalProjectInstaller::check_and_install```,
+/// with at most ```max_parallelism``` installations running at the same time.
+/// Correctness: The limit is shared by all clones of the batch installer and all calls to ```BatchInstaller::install```.
+/// A queued installation stays in ```InstallerStatus::Pending``` until a slot is free.
+#[derive(Clone)]
+pub struct BatchInstaller {
+    semaphore: Arc<Semaphore>,
+}
+
+impl BatchInstaller {
+    /// ```max_parallelism``` is at least 1.
+    pub fn new(max_parallelism: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_parallelism.max(1))),
+        }
+    }
+
+    /// Number of installations that could be started right now.
+    pub fn available_slots(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    /// Waits until every project is installed, failed installations do not stop the others.
+    /// The events of every installation are tagged with the project id and sent to ```event_sender```,
+    /// they replace the event senders the installers were created with.
+    pub async fn install(
+        &self,
+        installers: Vec<LocalProjectInstaller>,
+        event_sender: Option<mpsc::Sender<BatchInstallerEvent>>,
+    ) -> BatchInstallReport {
+        let mut tasks: Vec<(
+            String,
+            JoinHandle<Result<InstallOutcome, CheckAndInstallError>>,
+        )> = Vec::with_capacity(installers.len());
+
+        for mut installer in installers {
+            let project_id = installer.id().to_owned();
+
+            installer.set_event_sender(
+                event_sender
+                    .clone()
+                    .map(|event_sender| Self::spawn_event_tagger(project_id.clone(), event_sender)),
+            );
+
+            let semaphore = self.semaphore.clone();
+            let task = tokio::spawn(
+                async move {
+                    // Only fails if the semaphore is closed, which never happens.
+                    let _permit = semaphore.acquire_owned().await;
+                    installer.check_and_install().await
+                }
+                .in_current_span(),
+            );
+
+            tasks.push((project_id, task));
+        }
+
+        let mut results = Vec::with_capacity(tasks.len());
+
+        for (project_id, task) in tasks {
+            let result = match task.await {
+                Ok(result) => result.map_err(BatchInstallError::CheckAndInstallError),
+                Err(join_error) => Err(BatchInstallError::TaskFailed(join_error)),
+            };
+
+            results.push(BatchInstallResult { project_id, result });
+        }
+
+        BatchInstallReport { results }
+    }
+
+    /// Tags the events of one installation with its project id.
+    /// The task finishes once the installer is dropped.
+    fn spawn_event_tagger(
+        project_id: String,
+        event_sender: mpsc::Sender<BatchInstallerEvent>,
+    ) -> mpsc::Sender<InstallerEvent> {
+        let (project_event_sender, mut project_event_receiver) =
+            mpsc::channel(PROJECT_EVENT_CHANNEL_CAPACITY);
+
+        tokio::spawn(
+            async move {
+                while let Some(installer_event) = project_event_receiver.recv().await {
+                    let batch_installer_event = BatchInstallerEvent {
+                        project_id: project_id.clone(),
+                        installer_event,
+                    };
+
+                    if event_sender.send(batch_installer_event).await.is_err() {
+                        tracing::trace!("Batch installer event receiver is closed");
+                        break;
+                    }
+                }
+            }
+            .in_current_span(),
+        );
+
+        project_event_sender
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_managers::{
+        local::{
+            disk_space::EnvSizeQuota, local_project_installer::InstallPhase, test_dirs::TestDirs,
+        },
+        process::ProcessIoConfig,
+    };
+    use std::time::Duration;
+    use tracing_test::traced_test;
+
+    fn create_installer(project_id: &str, test_dirs: &TestDirs) -> LocalProjectInstaller {
+        let (installer, _controller) = LocalProjectInstaller::new(
+            project_id.to_owned(),
+            test_dirs.uploaded_projects_dir().join(project_id),
+            test_dirs.installed_projects_dir().join(project_id),
+            test_dirs.environments_dir().join(project_id),
+            None,
+            ProcessIoConfig::default(),
+        );
+
+        installer
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn install_invalid_projects_and_expect_failed_results_in_order() {
+        let test_dirs = TestDirs::new("batch_installer").await;
+        let batch_installer = BatchInstaller::new(1);
+        let (event_sender, mut event_receiver) = mpsc::channel(16);
+
+        let batch_install_report = batch_installer
+            .install(
+                vec![
+                    create_installer("requirements_does_not_exist", &test_dirs),
+                    create_installer("locust_dir_is_empty", &test_dirs),
+                    create_installer("empty", &test_dirs),
+                ],
+                Some(event_sender),
+            )
+            .await;
+
+        assert_eq!(
+            batch_install_report
+                .results
+                .iter()
+                .map(|batch_install_result| batch_install_result.project_id.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "requirements_does_not_exist",
+                "locust_dir_is_empty",
+                "empty"
+            ]
+        );
+        assert!(batch_install_report
+            .results
+            .iter()
+            .all(|batch_install_result| matches!(
+                batch_install_result.result,
+                Err(BatchInstallError::CheckAndInstallError(
+                    CheckAndInstallError::CheckError(_)
+                ))
+            )));
+        assert_eq!(batch_install_report.succeeded_count(), 0);
+        assert_eq!(batch_install_report.failed_count(), 3);
+        // Projects that fail the check are not installed, there are no events.
+        assert!(event_receiver.recv().await.is_none());
+        assert_eq!(batch_installer.available_slots(), 1);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn install_projects_and_expect_events_tagged_with_project_ids() {
+        let test_dirs = TestDirs::new("batch_installer").await;
+        let batch_installer = BatchInstaller::new(2);
+        let (event_sender, mut event_receiver) = mpsc::channel(1024);
+        let mut installer = create_installer("valid", &test_dirs);
+        installer.set_env_size_quota(Some(EnvSizeQuota {
+            max_size_bytes: 1024,
+            check_interval: Duration::from_millis(100),
+        }));
+
+        let batch_install_report = batch_installer
+            .install(
+                vec![installer, create_installer("empty", &test_dirs)],
+                Some(event_sender),
+            )
+            .await;
+
+        let mut batch_installer_events = Vec::new();
+        while let Some(batch_installer_event) = event_receiver.recv().await {
+            batch_installer_events.push(batch_installer_event);
+        }
+
+        assert_eq!(batch_install_report.failed_count(), 2);
+        assert!(batch_installer_events
+            .iter()
+            .all(|batch_installer_event| batch_installer_event.project_id == "valid"));
+        assert_eq!(
+            batch_installer_events
+                .first()
+                .map(|batch_installer_event| &batch_installer_event.installer_event),
+            Some(&InstallerEvent::PhaseStarted {
+                phase: InstallPhase::Venv
+            })
+        );
+        assert!(matches!(
+            batch_installer_events
+                .last()
+                .map(|batch_installer_event| &batch_installer_event.installer_event),
+            Some(InstallerEvent::Failed { .. })
+        ));
+    }
+}
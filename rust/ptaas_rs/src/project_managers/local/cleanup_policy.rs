@@ -0,0 +1,62 @@
+use super::local_project_installer::ErrorThatTriggersCleanUp;
+
+/// What a ```LocalProjectInstaller``` does with the staging dir of a failed installation.
+/// A kept staging dir is returned with ```InstallError::KeptEnvironment```, e.g. to debug the failure,
+/// see ```LocalProjectManager::schedule_cleanup```.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CleanupPolicy {
+    /// Deletes the staging dir on every failure.
+    #[default]
+    Always,
+    /// Keeps the staging dir on every failure, including timeouts and exceeded quotas.
+    Never,
+    /// Keeps the staging dir only if the requirements process failed, the most common failure to debug.
+    KeepOnRequirementsFailure,
+}
+
+impl CleanupPolicy {
+    /// Whether the staging dir is kept after ```error```.
+    pub(super) fn keeps(self, error: &ErrorThatTriggersCleanUp) -> bool {
+        match self {
+            Self::Always => false,
+            Self::Never => true,
+            Self::KeepOnRequirementsFailure => matches!(
+                error,
+                ErrorThatTriggersCleanUp::RequirementsInstallError(_)
+                    | ErrorThatTriggersCleanUp::RequirementsInstallErrorAfterAttempts { .. }
+            ),
+        }
+    }
+
+    /// Whether the staging dir is kept after the installation was aborted, e.g. on timeout.
+    pub(super) fn keeps_aborted(self) -> bool {
+        self == Self::Never
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_managers::local::local_project_installer::SubInstallError;
+    use crate::project_managers::process::{Status, TerminationStatus};
+
+    #[test]
+    fn keep_on_requirements_failure_and_expect_only_requirements_errors_kept() {
+        let requirements_error =
+            ErrorThatTriggersCleanUp::RequirementsInstallError(SubInstallError::UnexpectedStatus(
+                Status::Terminated(TerminationStatus::TerminatedWithUnknownExitStatus),
+            ));
+        let venv_error =
+            ErrorThatTriggersCleanUp::VenvInstallError(SubInstallError::UnexpectedStatus(
+                Status::Terminated(TerminationStatus::TerminatedWithUnknownExitStatus),
+            ));
+
+        let cleanup_policy = CleanupPolicy::KeepOnRequirementsFailure;
+
+        assert!(cleanup_policy.keeps(&requirements_error));
+        assert!(!cleanup_policy.keeps(&venv_error));
+        assert!(!cleanup_policy.keeps_aborted());
+        assert!(!CleanupPolicy::Always.keeps(&requirements_error));
+        assert!(CleanupPolicy::Never.keeps(&venv_error));
+    }
+}
@@ -0,0 +1,174 @@
+use std::{
+    io::Error as IoError,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use sysinfo::{DiskExt, System, SystemExt};
+use tokio::fs;
+
+/// The pre-flight check of a ```LocalProjectInstaller```, that fails before the virtual environment is created,
+/// if the environments volume is too small for the installation.
+/// The required space is ```uploaded project size * size_factor + min_headroom_bytes```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskSpaceConfig {
+    pub enabled: bool,
+    /// The dependencies of a project are usually much bigger than the project itself.
+    pub size_factor: u64,
+    /// Space for the virtual environment itself, e.g. pip and setuptools.
+    pub min_headroom_bytes: u64,
+}
+
+impl Default for DiskSpaceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            size_factor: 10,
+            min_headroom_bytes: 512 * 1024 * 1024,
+        }
+    }
+}
+
+impl DiskSpaceConfig {
+    pub(super) fn required_bytes(&self, uploaded_project_size: u64) -> u64 {
+        uploaded_project_size
+            .saturating_mul(self.size_factor)
+            .saturating_add(self.min_headroom_bytes)
+    }
+}
+
+/// The max size of the virtual environment of a project, so one project can not fill the disk for all others.
+/// The environment is measured every ```check_interval``` during the requirements phase of a ```LocalProjectInstaller```.
+/// Correctness: The environment may grow above the max size for up to ```check_interval```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnvSizeQuota {
+    pub max_size_bytes: u64,
+    pub check_interval: Duration,
+}
+
+impl EnvSizeQuota {
+    pub fn new(max_size_bytes: u64) -> Self {
+        Self {
+            max_size_bytes,
+            check_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Measures ```dir``` every ```EnvSizeQuota::check_interval``` and returns its size, once it is bigger than the quota.
+/// Measurements that fail, e.g. because pip deleted a file while it was measured, are skipped.
+pub(super) async fn wait_until_quota_exceeded(dir: &Path, env_size_quota: EnvSizeQuota) -> u64 {
+    loop {
+        tokio::time::sleep(env_size_quota.check_interval).await;
+
+        match dir_size(dir).await {
+            Ok(size) if size > env_size_quota.max_size_bytes => return size,
+            Ok(size) => {
+                tracing::trace!(?dir, size, "Environment is within its quota");
+            }
+            Err(error) => {
+                tracing::debug!(?dir, %error, "Could not measure environment");
+            }
+        }
+    }
+}
+
+/// The size of all files in ```dir``` and its sub directories.
+pub(super) async fn dir_size(dir: &Path) -> Result<u64, IoError> {
+    let mut size = 0;
+    let mut dirs = vec![dir.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        let mut dir_content = fs::read_dir(&dir).await?;
+
+        while let Some(entry) = dir_content.next_entry().await? {
+            let metadata = entry.metadata().await?;
+
+            if metadata.is_dir() {
+                dirs.push(entry.path());
+                continue;
+            }
+
+            size += metadata.len();
+        }
+    }
+
+    Ok(size)
+}
+
+/// The available space of the disk, that ```path``` is on. ```path``` does not need to exist yet.
+/// ```None``` if the disk is unknown, e.g. on unsupported platforms.
+/// Correctness: The disk is the one with the longest mount point, that is a prefix of the canonical ```path```.
+pub(super) async fn available_space(path: &Path) -> Option<u64> {
+    let existing_ancestor = path.ancestors().find(|ancestor| ancestor.exists())?;
+    let canonical_path = fs::canonicalize(existing_ancestor).await.ok()?;
+
+    tokio::task::spawn_blocking(move || available_space_blocking(canonical_path))
+        .await
+        .ok()
+        .flatten()
+}
+
+fn available_space_blocking(canonical_path: PathBuf) -> Option<u64> {
+    let mut system = System::new();
+    system.refresh_disks_list();
+
+    system
+        .disks()
+        .iter()
+        .filter(|disk| canonical_path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_dir;
+    use tracing_test::traced_test;
+
+    #[test]
+    fn compute_required_bytes_and_expect_size_times_factor_plus_headroom() {
+        let disk_space_config = DiskSpaceConfig {
+            enabled: true,
+            size_factor: 10,
+            min_headroom_bytes: 100,
+        };
+
+        assert_eq!(disk_space_config.required_bytes(5), 150);
+        assert_eq!(disk_space_config.required_bytes(u64::MAX), u64::MAX);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn grow_dir_above_quota_and_expect_its_size() {
+        let temp_dir = test_dir("env_size_quota");
+        let dir = temp_dir.path();
+        fs::create_dir_all(dir.join("lib"))
+            .await
+            .expect("Error creating dir.");
+        fs::write(dir.join("lib").join("package.py"), [0; 100])
+            .await
+            .expect("Error writing file.");
+
+        let size = wait_until_quota_exceeded(
+            dir,
+            EnvSizeQuota {
+                max_size_bytes: 50,
+                check_interval: Duration::from_millis(10),
+            },
+        )
+        .await;
+
+        assert_eq!(size, 100);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+    async fn get_available_space_of_missing_dir_and_expect_space_of_its_disk() {
+        let temp_dir = test_dir("disk_space_missing");
+        let path = temp_dir.path().join("does_not_exist").join("env");
+
+        assert!(available_space(&path).await.is_some());
+    }
+}
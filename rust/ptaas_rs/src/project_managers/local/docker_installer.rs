@@ -0,0 +1,803 @@
+use super::{
+    install_metrics::InstallMetrics,
+    install_record::PhaseRecord,
+    installer_events::{self, InstallerEvent},
+    local_project_installer::{
+        generate_process_run_result, CheckAndInstallError, CleanUpError, ErrorThatTriggersCleanUp,
+        InstallError, InstallOutcome, InstallPhase, InstallReport, InstallerKillAndWaitError,
+        InstallerStatus, LocalProjectInstaller, LocalProjectInstallerController, ProjectCheckError,
+        ProjectKind, SendingCancellationSignalToInstallerError, SubInstallError,
+        SubStartInstallError,
+    },
+    locust_version,
+    log_rotation::LogRotationConfig,
+    staging,
+};
+use crate::project_managers::process::{
+    self, KillSignal, OsProcessArgs, Process, ProcessIoConfig, ProcessPriority, RetryBackoff,
+    RetryPolicy, Status, StripAnsi, TerminationStatus,
+};
+use semver::Version;
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tokio::{
+    fs,
+    sync::{mpsc, watch},
+    time::Instant,
+};
+use tracing::{debug_span, Instrument};
+use uuid::Uuid;
+
+/// The interpreter of the image, that creates the virtual environment.
+const PYTHON: &str = "python3";
+
+/// Only the end of the output of ```docker rm``` is kept, e.g. ```No such container```.
+const MAX_DOCKER_OUTPUT_SIZE: usize = 4 * 1024;
+
+/// ```docker rm --force``` stops the container first, that takes seconds.
+const DOCKER_RM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The container a ```DockerInstaller``` runs the installation in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DockerConfig {
+    /// A compatible cli, e.g. ```podman```, works too.
+    pub program: PathBuf,
+    /// Must contain ```python3``` with the venv module, e.g. ```python:3.11-slim```.
+    /// Correctness: The environment links to the python of the image, it only runs in containers of the same image.
+    pub image: String,
+    /// Keeps the network of the requirements container, pip needs it unless it installs from a local index.
+    /// The other containers never have a network.
+    pub network: bool,
+    pub memory_limit_bytes: Option<u64>,
+    /// Limits the processes of a container, e.g. against fork bombs in a ```setup.py```.
+    pub pids_limit: Option<u64>,
+}
+
+impl Default for DockerConfig {
+    fn default() -> Self {
+        Self {
+            program: PathBuf::from("docker"),
+            image: String::from("python:3.11-slim"),
+            network: true,
+            memory_limit_bytes: None,
+            pids_limit: Some(512),
+        }
+    }
+}
+
+/// A ```docker run``` of one phase.
+struct ContainerRun<'a> {
+    name: &'a str,
+    network: bool,
+    /// Mounted at the same path, so the paths of the environment are the same inside and outside of the container.
+    read_only_paths: Vec<&'a Path>,
+    writable_paths: Vec<&'a Path>,
+    current_dir: &'a Path,
+    command: Vec<OsString>,
+}
+
+impl DockerConfig {
+    fn run_args(&self, container_run: ContainerRun) -> Vec<OsString> {
+        let mut run_args: Vec<OsString> = vec![
+            "run".into(),
+            "--rm".into(),
+            // Python ignores SIGTERM as pid 1, the init process forwards it.
+            "--init".into(),
+            "--name".into(),
+            container_run.name.into(),
+        ];
+
+        if !container_run.network {
+            run_args.extend(["--network".into(), "none".into()]);
+        }
+
+        if let Some(memory_limit_bytes) = self.memory_limit_bytes {
+            run_args.extend(["--memory".into(), memory_limit_bytes.to_string().into()]);
+        }
+
+        if let Some(pids_limit) = self.pids_limit {
+            run_args.extend(["--pids-limit".into(), pids_limit.to_string().into()]);
+        }
+
+        // The files written to the mounted dirs belong to the user of the installer, so they can be deleted.
+        #[cfg(unix)]
+        {
+            // Safety: getuid and getgid have no memory safety requirements.
+            let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+            run_args.extend(["--user".into(), format!("{uid}:{gid}").into()]);
+        }
+
+        // The user has no home in the image, pip writes its cache there.
+        run_args.extend(["--env".into(), "HOME=/tmp".into()]);
+
+        for path in container_run.read_only_paths {
+            run_args.extend(["--volume".into(), volume_arg(path, true)]);
+        }
+
+        for path in container_run.writable_paths {
+            run_args.extend(["--volume".into(), volume_arg(path, false)]);
+        }
+
+        run_args.extend([
+            "--workdir".into(),
+            container_run.current_dir.as_os_str().to_os_string(),
+        ]);
+
+        run_args.push(self.image.clone().into());
+        run_args.extend(container_run.command);
+
+        run_args
+    }
+}
+
+/// Mounts ```path``` at the same path in the container.
+fn volume_arg(path: &Path, read_only: bool) -> OsString {
+    let mut volume = OsString::from(path.as_os_str());
+    volume.push(":");
+    volume.push(path.as_os_str());
+
+    if read_only {
+        volume.push(":ro");
+    }
+
+    volume
+}
+
+/// Unique for every installer, so the containers of a cancelled installation can be removed by name.
+#[derive(Debug, Clone)]
+struct ContainerNames {
+    venv: String,
+    requirements: String,
+    locust: String,
+}
+
+impl ContainerNames {
+    fn new() -> Self {
+        let uuid = Uuid::new_v4();
+
+        Self {
+            venv: format!("ptaas-install-{uuid}-venv"),
+            requirements: format!("ptaas-install-{uuid}-requirements"),
+            locust: format!("ptaas-install-{uuid}-locust"),
+        }
+    }
+
+    fn all(&self) -> [&str; 3] {
+        [&self.venv, &self.requirements, &self.locust]
+    }
+}
+
+/// Removes the container, if it is still running. Failures are logged, the container is usually removed by ```--rm``` already.
+/// Correctness: Not cancelled with the installation, the containers of a cancelled installation are removed with it.
+async fn remove_container(docker_program: &Path, container_name: &str) {
+    let output_result = process::capture(
+        docker_program.as_os_str(),
+        vec![
+            OsStr::new("rm"),
+            OsStr::new("--force"),
+            OsStr::new(container_name),
+        ],
+        Path::new("."),
+        Vec::new(),
+        MAX_DOCKER_OUTPUT_SIZE,
+        DOCKER_RM_TIMEOUT,
+        None,
+    )
+    .await;
+
+    match output_result {
+        Ok(output)
+            if matches!(
+                output.status,
+                Status::Terminated(TerminationStatus::TerminatedSuccessfully)
+            ) =>
+        {
+            tracing::debug!(container_name, "Removed container");
+        }
+        Ok(output) => {
+            tracing::debug!(
+                container_name,
+                stderr = output.stderr,
+                "Container was not removed"
+            );
+        }
+        Err(error) => {
+            tracing::warn!(container_name, %error, "Could not run docker to remove container");
+        }
+    }
+}
+
+/// Responsible for cancelling a ```DockerInstaller```, see ```LocalProjectInstallerController```.
+/// Correctness: Killing the docker cli does not stop its container,
+/// the containers of the installation are removed after cancelling or shutting down, whether it succeeded or not.
+pub struct DockerInstallerController {
+    installer_controller: LocalProjectInstallerController,
+    docker_program: PathBuf,
+    container_names: ContainerNames,
+}
+
+impl DockerInstallerController {
+    pub fn status(&self) -> InstallerStatus {
+        self.installer_controller.status()
+    }
+
+    pub fn subscribe_status(&self) -> watch::Receiver<InstallerStatus> {
+        self.installer_controller.subscribe_status()
+    }
+
+    pub async fn cancel(
+        &mut self,
+    ) -> Result<Option<InstallerKillAndWaitError>, SendingCancellationSignalToInstallerError> {
+        let cancel_result = self.installer_controller.cancel().await;
+
+        self.remove_containers().await;
+
+        cancel_result
+    }
+
+    pub async fn shutdown(&mut self) -> Result<(), InstallerKillAndWaitError> {
+        let shutdown_result = self.installer_controller.shutdown().await;
+
+        self.remove_containers().await;
+
+        shutdown_result
+    }
+
+    async fn remove_containers(&self) {
+        for container_name in self.container_names.all() {
+            remove_container(&self.docker_program, container_name).await;
+        }
+    }
+}
+
+/// Installs a project like a ```LocalProjectInstaller```, but creates the virtual environment and installs the requirements
+/// in containers, since requirements may run arbitrary code while they are installed, e.g. a ```setup.py```.
+/// The uploaded project dir is mounted read-only, the staging dir of the environment writable, both at the same path as on the host.
+/// Correctness: Only ```ProjectKind::Requirements``` projects are supported, building a pyproject project writes to the project dir.
+/// The checks of the project run on the host, they do not run its code.
+/// The python version check, the audit, the lockfile and the install report of the ```LocalProjectInstaller``` are skipped.
+/// The dirs must be absolute and must not contain ```:```, docker does not mount them otherwise.
+pub struct DockerInstaller {
+    /// Checks the project, writes the io files and copies the project to the installed project dir.
+    /// Its processes are never run.
+    local_installer: LocalProjectInstaller,
+    uploaded_project_dir: PathBuf,
+    project_env_dir: PathBuf,
+    /// Next to ```project_env_dir```, a new one for every installation.
+    staging_env_dir: PathBuf,
+    docker_config: DockerConfig,
+    venv_process: Process,
+    req_process: Process,
+    container_names: ContainerNames,
+    event_sender: Option<mpsc::Sender<InstallerEvent>>,
+    io_config: ProcessIoConfig,
+    /// Limits the whole installation, not each phase.
+    timeout: Option<Duration>,
+    /// The phases of the running installation. There is no install report, they are only sent as events.
+    phase_records: Vec<PhaseRecord>,
+    /// See ```LocalProjectInstaller::metrics```.
+    metrics: InstallMetrics,
+}
+
+impl DockerInstaller {
+    pub fn new(
+        id: String,
+        uploaded_project_dir: PathBuf,
+        installed_project_dir: PathBuf,
+        project_env_dir: PathBuf,
+        event_sender: Option<mpsc::Sender<InstallerEvent>>,
+        io_config: ProcessIoConfig,
+        docker_config: DockerConfig,
+    ) -> (Self, DockerInstallerController) {
+        let (local_installer, local_installer_controller) = LocalProjectInstaller::new(
+            id,
+            uploaded_project_dir.clone(),
+            installed_project_dir,
+            project_env_dir.clone(),
+            None,
+            io_config,
+        );
+
+        let (venv_process, venv_controller) = Process::new(
+            String::from("docker_venv_id"),
+            String::from("docker_install_venv_process"),
+        );
+
+        let (req_process, req_controller) = Process::new(
+            String::from("docker_req_id"),
+            String::from("docker_install_req_process"),
+        );
+
+        let container_names = ContainerNames::new();
+
+        (
+            Self {
+                local_installer,
+                uploaded_project_dir,
+                staging_env_dir: staging::staging_dir_path(&project_env_dir),
+                project_env_dir,
+                docker_config: docker_config.clone(),
+                venv_process,
+                req_process,
+                container_names: container_names.clone(),
+                event_sender,
+                io_config,
+                timeout: None,
+                phase_records: Vec::new(),
+                metrics: InstallMetrics::default(),
+            },
+            DockerInstallerController {
+                installer_controller: local_installer_controller
+                    .with_process_controllers(venv_controller, req_controller),
+                docker_program: docker_config.program,
+                container_names,
+            },
+        )
+    }
+
+    pub fn id(&self) -> &str {
+        self.local_installer.id()
+    }
+
+    /// See ```LocalProjectInstaller::set_timeout```.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// See ```LocalProjectInstaller::check```.
+    pub async fn check(&self) -> Result<ProjectKind, ProjectCheckError> {
+        self.local_installer.check().await
+    }
+
+    /// Checks and installs the project. Unlike ```LocalProjectInstaller::check_and_install```,
+    /// the installation is never skipped, there is no requirements hash.
+    pub async fn check_and_install(&mut self) -> Result<InstallOutcome, CheckAndInstallError> {
+        self.metrics = InstallMetrics::default();
+        let check_and_install_result = self.check_and_install_without_finishing().await;
+
+        self.local_installer.set_status(InstallerStatus::Finished(
+            check_and_install_result
+                .as_ref()
+                .map(Clone::clone)
+                .map_err(ToString::to_string),
+        ));
+
+        check_and_install_result
+    }
+
+    async fn check_and_install_without_finishing(
+        &mut self,
+    ) -> Result<InstallOutcome, CheckAndInstallError> {
+        self.local_installer.set_status(InstallerStatus::Checking);
+
+        let check_started_at = Instant::now();
+        let check_result = self.check().await;
+        self.metrics.check = Some(check_started_at.elapsed());
+        check_result.map_err(CheckAndInstallError::CheckError)?;
+
+        let install_report = self
+            .install_without_finishing()
+            .await
+            .map_err(CheckAndInstallError::InstallError)?;
+
+        Ok(InstallOutcome::Installed(install_report))
+    }
+
+    /// See ```LocalProjectInstaller::metrics```.
+    pub fn metrics(&self) -> InstallMetrics {
+        self.metrics
+    }
+
+    /// Runs in a span with the id of the project, like ```LocalProjectInstaller::install```.
+    pub async fn install(&mut self) -> Result<InstallReport, InstallError> {
+        self.metrics = InstallMetrics::default();
+        let install_result = self.install_without_finishing().await;
+
+        self.local_installer.set_status(InstallerStatus::Finished(
+            install_result
+                .as_ref()
+                .map(|install_report| InstallOutcome::Installed(install_report.clone()))
+                .map_err(ToString::to_string),
+        ));
+
+        install_result
+    }
+
+    async fn install_without_finishing(&mut self) -> Result<InstallReport, InstallError> {
+        let debug_span = debug_span!("DockerInstaller::install", id = self.id());
+        self.phase_records.clear();
+
+        let mut install_result = self.install_in_span().instrument(debug_span).await;
+
+        self.metrics.record_phases(&self.phase_records);
+        if let Ok(install_report) = &mut install_result {
+            install_report.metrics = self.metrics;
+        }
+
+        if let Err(error) = &install_result {
+            installer_events::send(
+                self.event_sender.as_ref(),
+                InstallerEvent::Failed {
+                    error: error.to_string(),
+                },
+            )
+            .await;
+        }
+
+        install_result
+    }
+
+    async fn install_in_span(&mut self) -> Result<InstallReport, InstallError> {
+        let started_at = Instant::now();
+        self.staging_env_dir = staging::staging_dir_path(&self.project_env_dir);
+
+        let project_kind = self
+            .local_installer
+            .detect_project_kind()
+            .await
+            .map_err(InstallError::CouldNotDetectProjectKind)?;
+
+        if project_kind != ProjectKind::Requirements {
+            return Err(InstallError::UnsupportedInContainer(project_kind));
+        }
+
+        self.local_installer.create_io_files().await?;
+
+        // Docker would create a missing mount point as root.
+        fs::create_dir_all(&self.staging_env_dir)
+            .await
+            .map_err(|error| {
+                InstallError::VenvStartError(SubStartInstallError::CreateStagingDirError(error))
+            })?;
+
+        self.local_installer
+            .set_status(InstallerStatus::CreatingVenv);
+
+        let venv_args = self.docker_config.run_args(ContainerRun {
+            name: &self.container_names.venv,
+            network: false,
+            read_only_paths: vec![&self.uploaded_project_dir],
+            writable_paths: vec![&self.staging_env_dir],
+            current_dir: &self.uploaded_project_dir,
+            command: vec![
+                PYTHON.into(),
+                "-m".into(),
+                "venv".into(),
+                self.staging_env_dir.clone().into_os_string(),
+            ],
+        });
+
+        let venv_process_args = self.os_process_args(
+            venv_args,
+            self.local_installer.get_venv_out_file_path(),
+            self.local_installer.get_venv_err_file_path(),
+            ProcessPriority::default(),
+        );
+
+        let venv_process_result = match LocalProjectInstaller::run_phase(
+            &mut self.venv_process,
+            venv_process_args,
+            RetryPolicy::new(1, RetryBackoff::Fixed(Duration::ZERO)),
+            InstallPhase::Venv,
+            started_at,
+            self.timeout,
+            None,
+            LogRotationConfig::default(),
+            &mut self.phase_records,
+            self.event_sender.as_ref(),
+        )
+        .await
+        {
+            Ok(venv_process_result) => {
+                venv_process_result.map(|retried_status| retried_status.status)
+            }
+            Err(aborted) => return Err(self.clean_up_on_abort_and_return_error(aborted).await),
+        };
+        let venv_process_run_result =
+            generate_process_run_result!(venv_process_result, VenvInstallError);
+
+        if let Err(error) = venv_process_run_result {
+            return Err(self.clean_up_on_error_and_return_error(error).await);
+        }
+
+        self.local_installer
+            .set_status(InstallerStatus::InstallingRequirements);
+
+        // The container is always linux.
+        let env_python_path = self.staging_env_dir.join("bin").join("python");
+
+        let req_args = self.docker_config.run_args(ContainerRun {
+            name: &self.container_names.requirements,
+            network: self.docker_config.network,
+            read_only_paths: vec![&self.uploaded_project_dir],
+            writable_paths: vec![&self.staging_env_dir],
+            current_dir: &self.uploaded_project_dir,
+            command: vec![
+                env_python_path.into_os_string(),
+                "-m".into(),
+                "pip".into(),
+                "install".into(),
+                "-r".into(),
+                self.local_installer
+                    .get_requirements_file_path()
+                    .into_os_string(),
+            ],
+        });
+
+        let req_process_args = self.os_process_args(
+            req_args,
+            self.local_installer.get_req_out_file_path(),
+            self.local_installer.get_req_err_file_path(),
+            // Pip installs are heavy, running tests should stay responsive.
+            ProcessPriority::Low,
+        );
+
+        let req_process_result = match LocalProjectInstaller::run_phase(
+            &mut self.req_process,
+            req_process_args,
+            RetryPolicy::new(1, RetryBackoff::Fixed(Duration::ZERO)),
+            InstallPhase::Requirements,
+            started_at,
+            self.timeout,
+            None,
+            LogRotationConfig::default(),
+            &mut self.phase_records,
+            self.event_sender.as_ref(),
+        )
+        .await
+        {
+            Ok(req_process_result) => {
+                req_process_result.map(|retried_status| retried_status.status)
+            }
+            Err(aborted) => return Err(self.clean_up_on_abort_and_return_error(aborted).await),
+        };
+        let req_process_run_result =
+            generate_process_run_result!(req_process_result, RequirementsInstallError);
+
+        if let Err(error) = req_process_run_result {
+            return Err(self.clean_up_on_error_and_return_error(error).await);
+        }
+
+        let locust_version = self.check_locust_version(started_at).await?;
+
+        if let Err(error) = self.local_installer.copy_project_to_installed_dir().await {
+            return Err(self
+                .clean_up_on_error_and_return_error(ErrorThatTriggersCleanUp::CouldNotCopyProject(
+                    error,
+                ))
+                .await);
+        }
+
+        if let Err(error) = staging::promote(&self.staging_env_dir, &self.project_env_dir).await {
+            return Err(self
+                .clean_up_on_error_and_return_error(
+                    ErrorThatTriggersCleanUp::CouldNotPromoteEnvironment(error),
+                )
+                .await);
+        }
+
+        Ok(InstallReport {
+            locust_version,
+            vulnerability_report: None,
+            // Set by ```install_without_finishing```, once every phase is recorded.
+            metrics: InstallMetrics::default(),
+        })
+    }
+
+    /// Runs ```locust --version``` of the environment in a container, without a network.
+    async fn check_locust_version(&mut self, started_at: Instant) -> Result<Version, InstallError> {
+        let locust_args = self.docker_config.run_args(ContainerRun {
+            name: &self.container_names.locust,
+            network: false,
+            read_only_paths: vec![&self.uploaded_project_dir, &self.staging_env_dir],
+            writable_paths: Vec::new(),
+            current_dir: &self.uploaded_project_dir,
+            command: vec![
+                self.staging_env_dir
+                    .join("bin")
+                    .join("locust")
+                    .into_os_string(),
+                "--version".into(),
+            ],
+        });
+
+        let probe_result = match LocalProjectInstaller::within_timeout(
+            locust_version::probe_with(
+                self.docker_config.program.as_os_str(),
+                locust_args.iter().map(OsString::as_os_str).collect(),
+                &self.uploaded_project_dir,
+                self.local_installer.cancellation_token(),
+            ),
+            InstallPhase::LocustVersion,
+            started_at,
+            self.timeout,
+            &mut self.phase_records,
+            self.event_sender.as_ref(),
+        )
+        .await
+        {
+            Ok(probe_result) => probe_result,
+            Err(timed_out) => return Err(self.clean_up_on_abort_and_return_error(timed_out).await),
+        };
+
+        match probe_result {
+            Ok(version) => {
+                tracing::debug!(%version, "Locust is installed");
+                Ok(version)
+            }
+            Err(error) => Err(self
+                .clean_up_on_error_and_return_error(ErrorThatTriggersCleanUp::CouldNotProbeLocust(
+                    error,
+                ))
+                .await),
+        }
+    }
+
+    fn os_process_args(
+        &self,
+        args: Vec<OsString>,
+        stdout_file: PathBuf,
+        stderr_file: PathBuf,
+        priority: ProcessPriority,
+    ) -> OsProcessArgs<Vec<OsString>, OsString, PathBuf> {
+        OsProcessArgs {
+            program: self.docker_config.program.clone().into_os_string(),
+            args,
+            current_dir: self.uploaded_project_dir.clone(),
+            stdout_file: Some(stdout_file),
+            stderr_file: Some(stderr_file),
+            // Forwarded to the container by the docker cli.
+            kill_signal: KillSignal::default(),
+            strip_ansi: StripAnsi::both(),
+            priority,
+            io_config: self.io_config,
+            ..OsProcessArgs::default()
+        }
+    }
+
+    /// Deletes the staging dir, the environment dir is only written on success.
+    async fn clean_up_on_error(&mut self) -> Result<(), CleanUpError> {
+        let cancellation_token = self.local_installer.start_clean_up();
+        installer_events::send(self.event_sender.as_ref(), InstallerEvent::CleanupStarted).await;
+
+        let clean_up_started_at = Instant::now();
+        let clean_up_result = LocalProjectInstaller::delete_env_dir_if_exists(
+            &self.staging_env_dir,
+            &cancellation_token,
+        )
+        .await;
+        self.metrics.clean_up = Some(clean_up_started_at.elapsed());
+
+        clean_up_result
+    }
+
+    /// See ```LocalProjectInstaller::clean_up_on_error_and_return_error```.
+    async fn clean_up_on_error_and_return_error(
+        &mut self,
+        error: ErrorThatTriggersCleanUp,
+    ) -> InstallError {
+        match self.clean_up_on_error().await {
+            Ok(_) => InstallError::ErrorThatTriggersCleanUp(error),
+            Err(clean_up_error) => InstallError::CleanUpError(error, clean_up_error),
+        }
+    }
+
+    /// The docker cli of an aborted phase is shut down, its container may still be running and is removed.
+    /// See ```LocalProjectInstaller::clean_up_on_abort_and_return_error```.
+    async fn clean_up_on_abort_and_return_error(&mut self, aborted: InstallError) -> InstallError {
+        for container_name in self.container_names.all() {
+            remove_container(&self.docker_config.program, container_name).await;
+        }
+
+        match self.clean_up_on_error().await {
+            Ok(_) => aborted,
+            Err(clean_up_error) => InstallError::AbortedCleanUpError {
+                aborted: Box::new(aborted),
+                clean_up_error,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_managers::local::test_dirs::TestDirs;
+    use tracing_test::traced_test;
+
+    #[test]
+    fn run_args_mount_project_read_only_without_network() {
+        let docker_config = DockerConfig {
+            memory_limit_bytes: Some(1024),
+            ..DockerConfig::default()
+        };
+
+        let run_args = docker_config.run_args(ContainerRun {
+            name: "ptaas-install-venv",
+            network: false,
+            read_only_paths: vec![Path::new("/uploaded_projects/project")],
+            writable_paths: vec![Path::new("/environments/project.staging")],
+            current_dir: Path::new("/uploaded_projects/project"),
+            command: vec!["python3".into(), "-m".into(), "venv".into()],
+        });
+
+        let windows = |first: &str, second: &str| {
+            run_args
+                .windows(2)
+                .any(|window| window[0] == first && window[1] == second)
+        };
+        assert!(windows("--network", "none"));
+        assert!(windows("--memory", "1024"));
+        assert!(windows("--pids-limit", "512"));
+        assert!(windows(
+            "--volume",
+            "/uploaded_projects/project:/uploaded_projects/project:ro"
+        ));
+        assert!(windows(
+            "--volume",
+            "/environments/project.staging:/environments/project.staging"
+        ));
+        assert_eq!(
+            run_args[run_args.len() - 4..],
+            ["python:3.11-slim", "python3", "-m", "venv"]
+        );
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn install_without_docker_and_expect_venv_run_error_and_no_staging_dir() {
+        let test_dirs = TestDirs::new("docker_installer").await;
+        let environments_dir = test_dirs.environments_dir();
+        let (mut installer, controller) = DockerInstaller::new(
+            String::from("valid"),
+            test_dirs.uploaded_projects_dir().join("valid"),
+            test_dirs.installed_projects_dir().join("valid_docker"),
+            environments_dir.join("valid_docker"),
+            None,
+            ProcessIoConfig::default(),
+            DockerConfig {
+                program: PathBuf::from("ptaas_docker_does_not_exist"),
+                ..DockerConfig::default()
+            },
+        );
+
+        let check_and_install_result = installer.check_and_install().await;
+
+        let mut environments_dir_content = fs::read_dir(&environments_dir)
+            .await
+            .expect("Error reading environments dir.");
+        while let Some(entry) = environments_dir_content
+            .next_entry()
+            .await
+            .expect("Error reading environments dir.")
+        {
+            assert!(
+                !entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with("valid_docker"),
+                "Unexpected environment: {:?}",
+                entry.path()
+            );
+        }
+        assert!(
+            matches!(
+                check_and_install_result,
+                Err(CheckAndInstallError::InstallError(
+                    InstallError::ErrorThatTriggersCleanUp(
+                        ErrorThatTriggersCleanUp::VenvInstallError(SubInstallError::RunError(_))
+                    )
+                ))
+            ),
+            "Unexpected result: {:?}",
+            check_and_install_result
+        );
+        assert!(matches!(
+            controller.status(),
+            InstallerStatus::Finished(Err(_))
+        ));
+    }
+}
@@ -0,0 +1,251 @@
+use std::{
+    collections::BTreeSet,
+    io::{Error as IoError, ErrorKind},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use thiserror::Error as ThisError;
+use tokio::{fs, sync::Mutex};
+
+/// Environments shared by projects with the same requirements, keyed by the requirements hash, e.g. ```shared_environments/<hash>```.
+/// A project points at a shared environment with a symlink at its environment dir, see ```LocalProjectInstaller::set_env_store```.
+/// The projects that use an environment are stored next to it, e.g. ```shared_environments/<hash>.users.json```,
+/// an environment is deleted once no project uses it.
+/// Correctness: The users are synchronized between the clones of a store, not between processes.
+#[derive(Debug, Clone)]
+pub struct EnvStore {
+    store_dir: PathBuf,
+    users_lock: Arc<Mutex<()>>,
+}
+
+#[derive(ThisError, Debug)]
+pub enum EnvStoreError {
+    #[error("Could not read the users of the environment: {0}")]
+    CouldNotReadUsers(#[source] IoError),
+    #[error("Could not parse the users of the environment: {0}")]
+    CouldNotParseUsers(#[source] serde_json::Error),
+    #[error("Could not write the users of the environment: {0}")]
+    CouldNotWriteUsers(#[source] IoError),
+    #[error("Could not link the project to the environment: {0}")]
+    CouldNotLink(#[source] IoError),
+    #[error("Could not delete the unused environment: {0}")]
+    CouldNotDeleteEnvironment(#[source] IoError),
+}
+
+impl EnvStore {
+    pub fn new(store_dir: PathBuf) -> Self {
+        Self {
+            store_dir,
+            users_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub fn store_dir(&self) -> &Path {
+        &self.store_dir
+    }
+
+    /// Where the environment of ```requirements_hash``` is installed, it may not exist yet.
+    pub(super) fn env_dir(&self, requirements_hash: &str) -> PathBuf {
+        self.store_dir.join(requirements_hash)
+    }
+
+    fn users_file_path(&self, requirements_hash: &str) -> PathBuf {
+        self.store_dir
+            .join(format!("{requirements_hash}.users.json"))
+    }
+
+    /// The ids of the projects that use the environment of ```requirements_hash```.
+    pub async fn users(&self, requirements_hash: &str) -> Result<BTreeSet<String>, EnvStoreError> {
+        let users = match fs::read(self.users_file_path(requirements_hash)).await {
+            Ok(users) => users,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(BTreeSet::new()),
+            Err(error) => return Err(EnvStoreError::CouldNotReadUsers(error)),
+        };
+
+        serde_json::from_slice(&users).map_err(EnvStoreError::CouldNotParseUsers)
+    }
+
+    async fn write_users(
+        &self,
+        requirements_hash: &str,
+        users: &BTreeSet<String>,
+    ) -> Result<(), EnvStoreError> {
+        let users = serde_json::to_vec(users).map_err(EnvStoreError::CouldNotParseUsers)?;
+
+        fs::write(self.users_file_path(requirements_hash), users)
+            .await
+            .map_err(EnvStoreError::CouldNotWriteUsers)
+    }
+
+    /// The hash of the shared environment ```project_env_dir``` points at, ```None``` if it is not a link into the store.
+    async fn linked_requirements_hash(&self, project_env_dir: &Path) -> Option<String> {
+        let target = fs::read_link(project_env_dir).await.ok()?;
+
+        if target.parent() != Some(self.store_dir.as_path()) {
+            return None;
+        }
+
+        target
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().into_owned())
+    }
+
+    /// Points ```project_env_dir``` at the environment of ```requirements_hash``` and registers the project as its user.
+    /// A previous environment of the project is released, a previous environment that was not shared is deleted.
+    pub(super) async fn link(
+        &self,
+        project_id: &str,
+        requirements_hash: &str,
+        project_env_dir: &Path,
+    ) -> Result<(), EnvStoreError> {
+        let _users_guard = self.users_lock.lock().await;
+
+        let linked_requirements_hash = self.linked_requirements_hash(project_env_dir).await;
+
+        if linked_requirements_hash.as_deref() != Some(requirements_hash) {
+            self.release_locked(project_id, project_env_dir).await?;
+
+            Self::remove_env_dir_if_exists(project_env_dir)
+                .await
+                .map_err(EnvStoreError::CouldNotLink)?;
+
+            Self::symlink(&self.env_dir(requirements_hash), project_env_dir)
+                .await
+                .map_err(EnvStoreError::CouldNotLink)?;
+        }
+
+        let mut users = self.users(requirements_hash).await?;
+        if users.insert(project_id.to_owned()) {
+            self.write_users(requirements_hash, &users).await?;
+        }
+
+        tracing::debug!(
+            project_id,
+            requirements_hash,
+            users = users.len(),
+            "Linked project to shared environment"
+        );
+
+        Ok(())
+    }
+
+    /// Removes the link of the project and deletes the shared environment, if the project was its last user, e.g. on uninstall.
+    /// Returns whether the environment was deleted.
+    /// Correctness: An environment dir that is not a link into the store is left as it is.
+    pub async fn release(
+        &self,
+        project_id: &str,
+        project_env_dir: &Path,
+    ) -> Result<bool, EnvStoreError> {
+        let _users_guard = self.users_lock.lock().await;
+
+        self.release_locked(project_id, project_env_dir).await
+    }
+
+    async fn release_locked(
+        &self,
+        project_id: &str,
+        project_env_dir: &Path,
+    ) -> Result<bool, EnvStoreError> {
+        let Some(requirements_hash) = self.linked_requirements_hash(project_env_dir).await else {
+            return Ok(false);
+        };
+
+        fs::remove_file(project_env_dir)
+            .await
+            .map_err(EnvStoreError::CouldNotLink)?;
+
+        let mut users = self.users(&requirements_hash).await?;
+        users.remove(project_id);
+
+        if !users.is_empty() {
+            self.write_users(&requirements_hash, &users).await?;
+            return Ok(false);
+        }
+
+        tracing::info!(requirements_hash, "Deleting unused shared environment");
+
+        Self::remove_env_dir_if_exists(&self.env_dir(&requirements_hash))
+            .await
+            .map_err(EnvStoreError::CouldNotDeleteEnvironment)?;
+
+        match fs::remove_file(self.users_file_path(&requirements_hash)).await {
+            Err(error) if error.kind() != ErrorKind::NotFound => {
+                Err(EnvStoreError::CouldNotWriteUsers(error))
+            }
+            _ => Ok(true),
+        }
+    }
+
+    /// A link is removed, not the dir it points at.
+    async fn remove_env_dir_if_exists(env_dir: &Path) -> Result<(), IoError> {
+        match fs::symlink_metadata(env_dir).await {
+            Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(env_dir).await,
+            Ok(_) => fs::remove_file(env_dir).await,
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(()),
+            Err(error) => Err(error),
+        }
+    }
+
+    async fn symlink(env_dir: &Path, project_env_dir: &Path) -> Result<(), IoError> {
+        if let Some(parent_dir) = project_env_dir.parent() {
+            fs::create_dir_all(parent_dir).await?;
+        }
+
+        #[cfg(unix)]
+        return fs::symlink(env_dir, project_env_dir).await;
+
+        #[cfg(windows)]
+        return fs::symlink_dir(env_dir, project_env_dir).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_dir;
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn link_two_projects_and_expect_env_deleted_after_last_release() {
+        let temp_dir = test_dir("env_store");
+        let test_dir = temp_dir.path();
+        let env_store = EnvStore::new(test_dir.join("shared_environments"));
+        fs::create_dir_all(env_store.env_dir("hash"))
+            .await
+            .expect("Error creating dir.");
+        let first_env_dir = test_dir.join("environments").join("first");
+        let second_env_dir = test_dir.join("environments").join("second");
+
+        env_store
+            .link("first", "hash", &first_env_dir)
+            .await
+            .expect("Error linking first project.");
+        env_store
+            .link("second", "hash", &second_env_dir)
+            .await
+            .expect("Error linking second project.");
+        // Linking again does not register the project twice.
+        env_store
+            .link("second", "hash", &second_env_dir)
+            .await
+            .expect("Error linking second project.");
+        let users = env_store.users("hash").await;
+        let first_released = env_store.release("first", &first_env_dir).await;
+        let env_exists_after_first_release = fs::try_exists(env_store.env_dir("hash")).await;
+        let second_released = env_store.release("second", &second_env_dir).await;
+        let env_exists_after_second_release = fs::try_exists(env_store.env_dir("hash")).await;
+        let second_env_dir_exists = fs::try_exists(&second_env_dir).await;
+
+        assert_eq!(
+            users.expect("Error reading users."),
+            BTreeSet::from([String::from("first"), String::from("second")])
+        );
+        assert!(!first_released.expect("Error releasing first project."));
+        assert!(env_exists_after_first_release.expect("Error checking env."));
+        assert!(second_released.expect("Error releasing second project."));
+        assert!(!env_exists_after_second_release.expect("Error checking env."));
+        assert!(!second_env_dir_exists.expect("Error checking env."));
+    }
+}
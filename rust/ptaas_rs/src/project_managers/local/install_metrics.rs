@@ -0,0 +1,80 @@
+use super::{install_record::PhaseRecord, local_project_installer::InstallPhase};
+use std::time::Duration;
+
+/// How long the last installation took, phase by phase, see ```LocalProjectInstaller::metrics```.
+/// A duration is ```None``` if its phase did not run, e.g. the venv phase of a resumed installation
+/// or the clean up of a successful one.
+/// Correctness: The requirements duration includes every attempt, see ```PipRetryConfig```.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InstallMetrics {
+    /// Checking the uploaded project, see ```LocalProjectInstaller::check```.
+    pub check: Option<Duration>,
+    pub venv: Option<Duration>,
+    pub requirements: Option<Duration>,
+    /// Deleting the staging dir of a failed installation.
+    pub clean_up: Option<Duration>,
+}
+
+impl InstallMetrics {
+    /// The sum of the phases that ran.
+    pub fn total(&self) -> Duration {
+        [self.check, self.venv, self.requirements, self.clean_up]
+            .into_iter()
+            .flatten()
+            .sum()
+    }
+
+    /// Takes the venv and requirements durations from the recorded phases, the other phases are not measured here.
+    pub(super) fn record_phases(&mut self, phase_records: &[PhaseRecord]) {
+        for phase_record in phase_records {
+            let duration = phase_record
+                .finished_at
+                .duration_since(phase_record.started_at)
+                .unwrap_or_default();
+
+            match phase_record.phase {
+                InstallPhase::Venv => self.venv = Some(duration),
+                InstallPhase::Requirements => self.requirements = Some(duration),
+                InstallPhase::LocustVersion | InstallPhase::Audit | InstallPhase::Lockfile => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+
+    #[test]
+    fn record_phases_and_expect_venv_and_requirements_durations() {
+        let started_at = SystemTime::UNIX_EPOCH;
+        let phase_record = |phase, seconds| PhaseRecord {
+            phase,
+            started_at,
+            finished_at: started_at + Duration::from_secs(seconds),
+            exit_code: Some(0),
+        };
+        let mut install_metrics = InstallMetrics {
+            check: Some(Duration::from_secs(1)),
+            ..InstallMetrics::default()
+        };
+
+        install_metrics.record_phases(&[
+            phase_record(InstallPhase::Venv, 2),
+            phase_record(InstallPhase::Requirements, 30),
+            phase_record(InstallPhase::LocustVersion, 4),
+        ]);
+
+        assert_eq!(
+            install_metrics,
+            InstallMetrics {
+                check: Some(Duration::from_secs(1)),
+                venv: Some(Duration::from_secs(2)),
+                requirements: Some(Duration::from_secs(30)),
+                clean_up: None,
+            }
+        );
+        assert_eq!(install_metrics.total(), Duration::from_secs(33));
+    }
+}
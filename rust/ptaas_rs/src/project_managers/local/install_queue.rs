@@ -0,0 +1,144 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, PoisonError},
+};
+use tokio::{
+    sync::{OwnedSemaphorePermit, Semaphore},
+    task::JoinHandle,
+};
+use tokio_util::sync::CancellationToken;
+
+/// Returned by ```LocalProjectManager::do_install_project```.
+#[derive(Debug)]
+pub struct InstallTicket {
+    /// Finishes after the installation, or once it was cancelled while queued.
+    pub task: JoinHandle<()>,
+    /// The position in the install queue when the installation was started, ```1``` is next.
+    /// ```None``` if the installation started right away.
+    pub queue_position: Option<usize>,
+}
+
+/// Limits the installations of a ```LocalProjectManager``` that run at the same time, the others wait in order.
+/// Correctness: A project is in the queue until its installation got a slot, e.g. at position ```1``` while it is starting.
+#[derive(Debug, Clone)]
+pub(super) struct InstallQueue {
+    max_concurrent_installations: usize,
+    /// Grants the slots in the order they were requested.
+    semaphore: Arc<Semaphore>,
+    waiting: Arc<Mutex<VecDeque<(/* id */ String, CancellationToken)>>>,
+}
+
+impl InstallQueue {
+    /// At least one installation runs at a time.
+    pub(super) fn new(max_concurrent_installations: usize) -> Self {
+        let max_concurrent_installations = max_concurrent_installations.max(1);
+
+        Self {
+            max_concurrent_installations,
+            semaphore: Arc::new(Semaphore::new(max_concurrent_installations)),
+            waiting: Arc::default(),
+        }
+    }
+
+    pub(super) fn max_concurrent_installations(&self) -> usize {
+        self.max_concurrent_installations
+    }
+
+    /// Returns the position, see ```InstallTicket::queue_position```, and the token, that cancels the waiting.
+    pub(super) fn enqueue(&self, project_id: &str) -> (Option<usize>, CancellationToken) {
+        let cancellation_token = CancellationToken::new();
+        let mut waiting = self.waiting.lock().unwrap_or_else(PoisonError::into_inner);
+        waiting.push_back((project_id.to_owned(), cancellation_token.clone()));
+
+        let queue_position = waiting
+            .len()
+            .checked_sub(self.semaphore.available_permits())
+            .filter(|queue_position| *queue_position > 0);
+
+        (queue_position, cancellation_token)
+    }
+
+    /// Waits for a slot, the installation runs until the permit is dropped.
+    /// ```None``` if the waiting was cancelled, see ```cancel```. The project leaves the queue either way.
+    pub(super) async fn wait_for_turn(
+        &self,
+        project_id: &str,
+        cancellation_token: &CancellationToken,
+    ) -> Option<OwnedSemaphorePermit> {
+        let permit = tokio::select! {
+            permit = self.semaphore.clone().acquire_owned() => permit.ok(),
+            _ = cancellation_token.cancelled() => None,
+        };
+
+        self.waiting
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|(waiting_project_id, _)| waiting_project_id != project_id);
+
+        permit
+    }
+
+    /// ```None``` if the project is not waiting.
+    pub(super) fn position(&self, project_id: &str) -> Option<usize> {
+        self.waiting
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .position(|(waiting_project_id, _)| waiting_project_id == project_id)
+            .map(|index| index + 1)
+    }
+
+    /// Returns whether the project was waiting.
+    pub(super) fn cancel(&self, project_id: &str) -> bool {
+        self.waiting
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter(|(waiting_project_id, _)| waiting_project_id == project_id)
+            .map(|(_, cancellation_token)| cancellation_token.cancel())
+            .count()
+            > 0
+    }
+
+    pub(super) fn cancel_all(&self) {
+        for (_, cancellation_token) in self
+            .waiting
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+        {
+            cancellation_token.cancel();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn enqueue_beyond_limit_and_expect_fifo_slots_and_cancelled_waiting() {
+        let install_queue = InstallQueue::new(1);
+
+        let (first_position, first_token) = install_queue.enqueue("first");
+        let first_permit = install_queue.wait_for_turn("first", &first_token).await;
+        let (second_position, second_token) = install_queue.enqueue("second");
+        let (third_position, third_token) = install_queue.enqueue("third");
+        let third_position_after_second = install_queue.position("third");
+        let second_cancelled = install_queue.cancel("second");
+        let second_permit = install_queue.wait_for_turn("second", &second_token).await;
+        let third_position_after_cancel = install_queue.position("third");
+        drop(first_permit);
+        let third_permit = install_queue.wait_for_turn("third", &third_token).await;
+
+        assert_eq!(first_position, None);
+        assert_eq!(second_position, Some(1));
+        assert_eq!(third_position, Some(2));
+        assert_eq!(third_position_after_second, Some(2));
+        assert!(second_cancelled);
+        assert!(second_permit.is_none());
+        assert_eq!(third_position_after_cancel, Some(1));
+        assert!(third_permit.is_some());
+        assert_eq!(install_queue.position("third"), None);
+    }
+}
@@ -0,0 +1,186 @@
+use super::{local_project_installer::InstallPhase, project_store::ProjectStoreError};
+use crate::project_managers::process::{Status, TerminationStatus, TerminationWithErrorStatus};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::Error as IoError,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use thiserror::Error as ThisError;
+use tokio::fs;
+
+const INSTALL_REPORT_FILE_NAME: &str = "install_report.json";
+
+/// The last installation of a project, successful or not.
+/// Persisted as ```install_report.json``` in the installed project dir, see ```LocalProjectInstaller::load_report```.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallRecord {
+    pub id: String,
+    pub started_at: SystemTime,
+    pub finished_at: SystemTime,
+    pub status: InstallRecordStatus,
+    /// The phases that were started, in order. A failed installation ends with the phase that failed.
+    pub phases: Vec<PhaseRecord>,
+    /// ```None``` if the installation failed before the lockfile was written.
+    pub package_count: Option<usize>,
+    pub locust_version: Option<String>,
+    pub log_files: InstallLogFiles,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InstallRecordStatus {
+    Succeeded,
+    /// The message of the ```InstallError```.
+    Failed {
+        error: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseRecord {
+    pub phase: InstallPhase,
+    pub started_at: SystemTime,
+    pub finished_at: SystemTime,
+    /// The exit code of the last attempt of the phase's process.
+    /// ```None``` for phases that were aborted, that do not report one, or processes terminated by a signal.
+    pub exit_code: Option<i32>,
+}
+
+impl PhaseRecord {
+    /// Finishes now.
+    pub(super) fn new(phase: InstallPhase, started_at: SystemTime, exit_code: Option<i32>) -> Self {
+        Self {
+            phase,
+            started_at,
+            finished_at: SystemTime::now(),
+            exit_code,
+        }
+    }
+}
+
+/// The io files of the installer processes, they are overwritten by the next installation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallLogFiles {
+    pub venv_out: PathBuf,
+    pub venv_err: PathBuf,
+    pub req_out: PathBuf,
+    pub req_err: PathBuf,
+}
+
+pub(super) fn exit_code(status: &Status) -> Option<i32> {
+    match status {
+        Status::Terminated(TerminationStatus::TerminatedSuccessfully) => Some(0),
+        Status::Terminated(TerminationStatus::TerminatedWithError(
+            TerminationWithErrorStatus::TerminatedWithErrorCode(code),
+        )) => Some(*code),
+        Status::Terminated(TerminationStatus::Killed(_, os_exit_status)) => os_exit_status.code,
+        _ => None,
+    }
+}
+
+pub(super) fn report_file_path(installed_project_dir: &Path) -> PathBuf {
+    installed_project_dir.join(INSTALL_REPORT_FILE_NAME)
+}
+
+/// Replaces the report of the previous installation.
+pub(super) async fn write(
+    installed_project_dir: &Path,
+    install_record: &InstallRecord,
+) -> Result<(), IoError> {
+    let report = serde_json::to_vec_pretty(install_record)?;
+
+    fs::create_dir_all(installed_project_dir).await?;
+    fs::write(report_file_path(installed_project_dir), report).await
+}
+
+pub(super) async fn read(installed_project_dir: &Path) -> Result<InstallRecord, LoadReportError> {
+    let report = fs::read(report_file_path(installed_project_dir))
+        .await
+        .map_err(LoadReportError::CouldNotReadReport)?;
+
+    serde_json::from_slice(&report).map_err(LoadReportError::CouldNotParseReport)
+}
+
+#[derive(ThisError, Debug)]
+pub enum LoadReportError {
+    #[error("Could not read the install report: {0}")]
+    CouldNotReadReport(#[source] IoError),
+    #[error("Could not parse the install report: {0}")]
+    CouldNotParseReport(#[source] serde_json::Error),
+    #[error("Could not load the project: {0}")]
+    ProjectStore(#[source] ProjectStoreError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_managers::process::{KilledTerminationStatus, OsExitStatus};
+    use crate::util::test_dir;
+    use tracing_test::traced_test;
+
+    #[test]
+    fn get_exit_codes_of_statuses_and_expect_codes() {
+        assert_eq!(
+            exit_code(&Status::Terminated(
+                TerminationStatus::TerminatedSuccessfully
+            )),
+            Some(0)
+        );
+        assert_eq!(
+            exit_code(&Status::Terminated(TerminationStatus::TerminatedWithError(
+                TerminationWithErrorStatus::TerminatedWithErrorCode(1)
+            ))),
+            Some(1)
+        );
+        assert_eq!(
+            exit_code(&Status::Terminated(TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByTimeout,
+                OsExitStatus {
+                    code: None,
+                    signal: Some(9)
+                }
+            ))),
+            None
+        );
+        assert_eq!(exit_code(&Status::Running), None);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn write_and_read_report_and_expect_same_record() {
+        let temp_dir = test_dir("install_record");
+        let installed_project_dir = temp_dir.path().join("project");
+        let now = SystemTime::now();
+        let install_record = InstallRecord {
+            id: String::from("project"),
+            started_at: now,
+            finished_at: now,
+            status: InstallRecordStatus::Failed {
+                error: String::from("Installation timed out"),
+            },
+            phases: vec![PhaseRecord::new(InstallPhase::Venv, now, Some(0))],
+            package_count: None,
+            locust_version: None,
+            log_files: InstallLogFiles {
+                venv_out: PathBuf::from("venv_out.txt"),
+                venv_err: PathBuf::from("venv_err.txt"),
+                req_out: PathBuf::from("req_out.txt"),
+                req_err: PathBuf::from("req_err.txt"),
+            },
+        };
+
+        write(&installed_project_dir, &install_record)
+            .await
+            .expect("Error writing report.");
+        let read_install_record = read(&installed_project_dir).await;
+
+        assert_eq!(
+            read_install_record.expect("Error reading report."),
+            install_record
+        );
+    }
+}
@@ -0,0 +1,41 @@
+/// Which tools create the virtual environment and install the dependencies of a project.
+/// Poetry projects are always installed with ```poetry install```, see ```ProjectKind```.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InstallerBackend {
+    /// ```python -m venv``` and ```pip install```.
+    #[default]
+    Pip,
+    /// ```uv venv``` and ```uv pip install```, an order of magnitude faster for large requirement sets.
+    /// Falls back to ```Pip``` if ```uv``` is not in ```PATH```.
+    /// Correctness: ```uv``` keeps its own cache, a pip cache is not used.
+    Uv,
+}
+
+impl InstallerBackend {
+    /// The backend that is actually used, ```Uv``` only if ```uv``` is available.
+    pub(super) fn resolve(self) -> Self {
+        match self {
+            Self::Uv if which::which("uv").is_err() => {
+                tracing::warn!("uv is not in PATH, falling back to pip");
+                Self::Pip
+            }
+            backend => backend,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_backends_and_expect_uv_only_if_available() {
+        let uv_available = which::which("uv").is_ok();
+
+        assert_eq!(InstallerBackend::Pip.resolve(), InstallerBackend::Pip);
+        assert_eq!(
+            InstallerBackend::Uv.resolve() == InstallerBackend::Uv,
+            uv_available
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use super::local_project_installer::InstallPhase;
+use serde::{Deserialize, Serialize};
+use tokio::{sync::mpsc, task::JoinHandle};
+
+/// Lines are forwarded as soon as they are read, a small buffer is enough.
+const OUTPUT_CHANNEL_CAPACITY: usize = 64;
+
+/// The events of a ```LocalProjectInstaller```, in the order they happened.
+/// Correctness: The output lines of a phase are sent after its ```PhaseStarted``` and before its ```PhaseFinished``` event.
+/// The lines of stdout and stderr are interleaved in the order they were read.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InstallerEvent {
+    PhaseStarted {
+        phase: InstallPhase,
+    },
+    /// Only the virtual environment and requirements phases forward their output.
+    OutputLine {
+        stream: OutputStream,
+        phase: InstallPhase,
+        line: String,
+    },
+    /// ```exit_code``` is the one of ```PhaseRecord```.
+    PhaseFinished {
+        phase: InstallPhase,
+        exit_code: Option<i32>,
+    },
+    /// The staging dir of the failed installation is being deleted.
+    CleanupStarted,
+    /// The last event of a failed installation, with the message of the ```InstallError```.
+    Failed {
+        error: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// A closed receiver is not an error, the installation does not depend on its consumer.
+pub(super) async fn send(
+    event_sender: Option<&mpsc::Sender<InstallerEvent>>,
+    installer_event: InstallerEvent,
+) {
+    if let Some(event_sender) = event_sender {
+        if event_sender.send(installer_event).await.is_err() {
+            tracing::trace!("Installer event receiver is closed");
+        }
+    }
+}
+
+/// Turns the lines of a phase's process into ```InstallerEvent::OutputLine``` events.
+pub(super) struct OutputForwarder {
+    task: JoinHandle<()>,
+}
+
+impl OutputForwarder {
+    /// Returns the stdout and stderr senders for the process of ```phase```.
+    /// The forwarder finishes once both senders are dropped, e.g. after the process terminated.
+    pub(super) fn spawn(
+        event_sender: mpsc::Sender<InstallerEvent>,
+        phase: InstallPhase,
+    ) -> (Self, mpsc::Sender<String>, mpsc::Sender<String>) {
+        let (stdout_sender, mut stdout_receiver) = mpsc::channel(OUTPUT_CHANNEL_CAPACITY);
+        let (stderr_sender, mut stderr_receiver) = mpsc::channel(OUTPUT_CHANNEL_CAPACITY);
+
+        let task = tokio::spawn(async move {
+            let mut stdout_open = true;
+            let mut stderr_open = true;
+
+            while stdout_open || stderr_open {
+                let (stream, line) = tokio::select! {
+                    line = stdout_receiver.recv(), if stdout_open => match line {
+                        Some(line) => (OutputStream::Stdout, line),
+                        None => {
+                            stdout_open = false;
+                            continue;
+                        }
+                    },
+                    line = stderr_receiver.recv(), if stderr_open => match line {
+                        Some(line) => (OutputStream::Stderr, line),
+                        None => {
+                            stderr_open = false;
+                            continue;
+                        }
+                    },
+                };
+
+                // Dropping the receivers removes the channel sinks of the process.
+                if event_sender
+                    .send(InstallerEvent::OutputLine {
+                        stream,
+                        phase,
+                        line,
+                    })
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        (Self { task }, stdout_sender, stderr_sender)
+    }
+
+    /// Waits until every line is forwarded.
+    pub(super) async fn join(self) {
+        if let Err(join_error) = self.task.await {
+            tracing::warn!(%join_error, "Output forwarder failed");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn forward_lines_of_both_streams_and_expect_output_line_events() {
+        let (event_sender, mut event_receiver) = mpsc::channel(16);
+        let (output_forwarder, stdout_sender, stderr_sender) =
+            OutputForwarder::spawn(event_sender, InstallPhase::Requirements);
+
+        stdout_sender
+            .send(String::from("Collecting locust"))
+            .await
+            .expect("Error sending line.");
+        drop(stdout_sender);
+        // Both streams are read concurrently, the stdout line is forwarded before the stderr line is sent.
+        let mut installer_events = vec![event_receiver.recv().await.expect("No event.")];
+        stderr_sender
+            .send(String::from("WARNING: Retrying"))
+            .await
+            .expect("Error sending line.");
+        drop(stderr_sender);
+        output_forwarder.join().await;
+
+        while let Some(installer_event) = event_receiver.recv().await {
+            installer_events.push(installer_event);
+        }
+
+        assert_eq!(
+            installer_events,
+            vec![
+                InstallerEvent::OutputLine {
+                    stream: OutputStream::Stdout,
+                    phase: InstallPhase::Requirements,
+                    line: String::from("Collecting locust"),
+                },
+                InstallerEvent::OutputLine {
+                    stream: OutputStream::Stderr,
+                    phase: InstallPhase::Requirements,
+                    line: String::from("WARNING: Retrying"),
+                },
+            ]
+        );
+    }
+}
@@ -1,22 +1,55 @@
 use crate::{
     project_managers::process::{
-        KilledTerminationStatus, OsProcessArgs, Process, ProcessController,
-        ProcessKillAndWaitError, ProcessRunError, SendingCancellationSignalToProcessError, Status,
+        KilledTerminationStatus, OsExitStatus, OsProcessArgs, Process, ProcessController,
+        ProcessIoConfig, ProcessKillAndWaitError, ProcessPriority, ProcessRunError, RetriedStatus,
+        RetryBackoff, RetryPolicy, SendingCancellationSignalToProcessError, Status, StripAnsi,
         TerminationStatus, TerminationWithErrorStatus,
     },
-    util::{remove_dir_all_with_max_attempts_and_delay, MaxAttemptsExceeded},
+    util::{copy_dir_all, remove_dir_all_with_max_attempts_and_delay, RemoveDirAllError},
 };
+
+use super::{
+    archive::ArchiveLimits,
+    audit::{self, AuditConfig, AuditError, AuditPolicy, VulnerabilityReport},
+    cleanup_policy::CleanupPolicy,
+    disk_space::{self, DiskSpaceConfig, EnvSizeQuota},
+    env_store::{EnvStore, EnvStoreError},
+    install_metrics::InstallMetrics,
+    install_record::{
+        self, InstallLogFiles, InstallRecord, InstallRecordStatus, LoadReportError, PhaseRecord,
+    },
+    installer_backend::InstallerBackend,
+    installer_events::{self, InstallerEvent, OutputForwarder},
+    lockfile::{self, FreezeError, PackageVersion},
+    locust_version::{self, LocustProbeError},
+    log_rotation::{self, LogFile, LogRotationConfig},
+    manifest::{self, ManifestError},
+    pip_options::PipOptions,
+    pip_retry::PipRetryConfig,
+    project_checks::{ProjectChecks, ProjectChecksError},
+    project_source::{ProjectSource, ProjectSourceError},
+    python::{self, PythonConfig, PythonProbeError, PythonVersion},
+    requirements_hash::{self, RequirementsHashError},
+    staging,
+    syntax_check::{self, SyntaxCheckError},
+};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
 use std::{
+    ffi::{OsStr, OsString},
+    future::Future,
     io::Error as IoError,
     path::{Path, PathBuf},
-    time::Duration,
+    time::{Duration, SystemTime},
 };
 use thiserror::Error as ThisError;
 use tokio::{
     fs::{self, File, ReadDir},
-    io::AsyncWriteExt,
-    sync::mpsc,
+    sync::{mpsc, watch},
+    time::Instant,
 };
+use tokio_util::sync::CancellationToken;
+use tracing::{debug_span, Instrument};
 
 /// Responsible for cancelling a local project installation.
 /// Correctness: The virtual environment process is cancelled, if it is running, and the ```cancel``` method returns.
@@ -25,12 +58,50 @@ use tokio::{
 pub struct LocalProjectInstallerController {
     venv_controller: ProcessController,
     req_controller: ProcessController,
+    status_receiver: watch::Receiver<InstallerStatus>,
+    /// The token of the running clean up, replaced by the installer on every clean up.
+    clean_up_cancellation_receiver: watch::Receiver<CancellationToken>,
+    /// Cancels the short processes around the phases, e.g. the python version probe.
+    cancellation_token: CancellationToken,
 }
 
 impl LocalProjectInstallerController {
+    /// Cancels the given processes instead of the ones of the ```LocalProjectInstaller```, see ```DockerInstaller```.
+    pub(super) fn with_process_controllers(
+        self,
+        venv_controller: ProcessController,
+        req_controller: ProcessController,
+    ) -> Self {
+        Self {
+            venv_controller,
+            req_controller,
+            ..self
+        }
+    }
+
+    /// Where the installation is right now.
+    pub fn status(&self) -> InstallerStatus {
+        self.status_receiver.borrow().clone()
+    }
+
+    /// Notified on every status change, e.g. to wait for ```InstallerStatus::Finished```.
+    pub fn subscribe_status(&self) -> watch::Receiver<InstallerStatus> {
+        self.status_receiver.clone()
+    }
+
+    /// Cancels the running process, or the clean up if the installer is cleaning up, see ```InstallerStatus::CleaningUp```.
+    /// A cancelled clean up returns ```CleanUpError``` with the staging dir left behind.
+    /// Correctness: The staging dir of a cancelled clean up is deleted by ```LocalProjectManager``` on its next start.
     pub async fn cancel(
         &mut self,
     ) -> Result<Option<InstallerKillAndWaitError>, SendingCancellationSignalToInstallerError> {
+        if self.status() == InstallerStatus::CleaningUp {
+            self.cancel_clean_up();
+            return Ok(None);
+        }
+
+        self.cancellation_token.cancel();
+
         match self.cancel_venv().await {
             Ok(option_kill_and_wait_error) => {
                 Ok(option_kill_and_wait_error.map(InstallerKillAndWaitError::VenvKillAndWaitError))
@@ -46,6 +117,10 @@ impl LocalProjectInstallerController {
         }
     }
 
+    fn cancel_clean_up(&self) {
+        self.clean_up_cancellation_receiver.borrow().cancel();
+    }
+
     async fn cancel_venv(
         &mut self,
     ) -> Result<Option<ProcessKillAndWaitError>, SendingCancellationSignalToProcessError> {
@@ -67,6 +142,41 @@ impl LocalProjectInstallerController {
             .map_err(SendingCancellationSignalToInstallerError::ReqCancellationError)?
             .map(InstallerKillAndWaitError::ReqKillAndWaitError))
     }
+
+    /// Shuts down both processes and waits for them, see ```ProcessController::shutdown```.
+    /// Correctness: The virtual environment process is shut down first, a killed virtual environment process prevents the requirements process from starting.
+    /// The requirements process is shut down even if shutting down the virtual environment process failed. The first error is returned.
+    pub async fn shutdown(&mut self) -> Result<(), InstallerKillAndWaitError> {
+        self.cancellation_token.cancel();
+
+        let venv_result = self
+            .venv_controller
+            .shutdown()
+            .await
+            .map_err(InstallerKillAndWaitError::VenvKillAndWaitError);
+
+        let req_result = self
+            .req_controller
+            .shutdown()
+            .await
+            .map_err(InstallerKillAndWaitError::ReqKillAndWaitError);
+
+        venv_result.and(req_result)
+    }
+}
+
+/// The state of a ```LocalProjectInstaller```, see ```LocalProjectInstallerController::status```.
+/// Correctness: The phases after the requirements phase, e.g. the audit, are reported as ```InstallingRequirements```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstallerStatus {
+    /// Neither ```check_and_install``` nor ```install``` was called yet.
+    Pending,
+    Checking,
+    CreatingVenv,
+    InstallingRequirements,
+    CleaningUp,
+    /// The error is the message of the ```CheckAndInstallError``` or ```InstallError```.
+    Finished(Result<InstallOutcome, String>),
 }
 
 #[derive(ThisError, Debug)]
@@ -91,9 +201,9 @@ macro_rules! generate_process_run_result {
             Ok(status) => match status {
                 Status::Terminated(term_status) => match term_status {
                     TerminationStatus::TerminatedSuccessfully => Ok(()),
-                    TerminationStatus::Killed(killed_term_status) => Err(
+                    TerminationStatus::Killed(killed_term_status, os_exit_status) => Err(
                         ErrorThatTriggersCleanUp::$error_that_triggers_cleanup_variant(
-                            SubInstallError::Killed(killed_term_status),
+                            SubInstallError::Killed(killed_term_status, os_exit_status),
                         ),
                     ),
                     TerminationStatus::TerminatedWithError(term_with_error_status) => Err(
@@ -101,6 +211,13 @@ macro_rules! generate_process_run_result {
                             SubInstallError::TerminatedWithError(term_with_error_status),
                         ),
                     ),
+                    TerminationStatus::TerminatedWithUnknownExitStatus => Err(
+                        ErrorThatTriggersCleanUp::$error_that_triggers_cleanup_variant(
+                            SubInstallError::UnexpectedStatus(Status::Terminated(
+                                TerminationStatus::TerminatedWithUnknownExitStatus,
+                            )),
+                        ),
+                    ),
                 },
                 _ => Err(
                     ErrorThatTriggersCleanUp::$error_that_triggers_cleanup_variant(
@@ -117,21 +234,76 @@ macro_rules! generate_process_run_result {
     };
 }
 
+// Also maps the process results of the ```DockerInstaller```.
+pub(super) use generate_process_run_result;
+
 /// Responsible for installing a project locally.
 /// Creates a virtual environment and installs the project's requirements in it.
 /// Correctness: The virtual environment is created, if the project is valid.
 /// The requirements are installed, if the virtual environment is created.
 /// The requirements process will only be started, if the virtual environment process has terminated successfully.
-/// On installation failures, the virtual environment is deleted.
+/// The virtual environment is built in a staging dir and moved to the environment dir, once the installation succeeded.
+/// On installation failures, the staging dir is deleted and a previous environment is kept.
 pub struct LocalProjectInstaller {
     id: String,
     uploaded_project_dir: PathBuf,
+    /// Fetched to ```uploaded_project_dir``` by ```check_and_install```, before the project is checked.
+    project_source: ProjectSource,
+    archive_limits: ArchiveLimits,
+    /// ```check``` compares the uploaded project with its ```ptaas_manifest.json```, see ```write_manifest```.
+    verify_manifest: bool,
+    /// Run by ```check```, after the project dir is checked.
+    project_checks: ProjectChecks,
+    /// The uploaded project is copied here on success, next to ```installed_lock.txt``` and ```install_report.json```.
     installed_project_dir: PathBuf,
     project_env_dir: PathBuf,
+    /// Next to ```project_env_dir```, a new one for every installation.
+    staging_env_dir: PathBuf,
     venv_process: Process,
     req_process: Process,
-    stdout_sender: Option<mpsc::Sender<String>>,
-    stderr_sender: Option<mpsc::Sender<String>>,
+    /// Receives the events of every installation, see ```InstallerEvent```.
+    event_sender: Option<mpsc::Sender<InstallerEvent>>,
+    /// Used for both processes. The log files are flushed according to its flush policy.
+    io_config: ProcessIoConfig,
+    /// Limits the whole installation, not each phase.
+    timeout: Option<Duration>,
+    /// Passed to the requirements process as ```PIP_CACHE_DIR```.
+    pip_cache_dir: Option<PathBuf>,
+    /// Installs even if the requirements hash matches the installed environment.
+    force_reinstall: bool,
+    /// Cancelled by ```LocalProjectInstallerController::cancel```, passed to the short processes around the phases.
+    cancellation_token: CancellationToken,
+    /// Creates the virtual environment.
+    python_config: PythonConfig,
+    installer_backend: InstallerBackend,
+    /// Retries the requirements phase on network errors.
+    pip_retry_config: PipRetryConfig,
+    /// Passed to the requirements process as environment variables.
+    pip_options: PipOptions,
+    /// Audits the installed requirements.
+    audit_config: AuditConfig,
+    /// Checked against the installed locust version.
+    required_locust_version: Option<VersionReq>,
+    /// Checked before the virtual environment is created.
+    disk_space_config: DiskSpaceConfig,
+    /// Enforced during the requirements phase.
+    env_size_quota: Option<EnvSizeQuota>,
+    /// Rotates the io files when an installation starts and while the phases run.
+    log_rotation_config: LogRotationConfig,
+    /// The phases of the running installation, see ```InstallRecord```.
+    phase_records: Vec<PhaseRecord>,
+    /// The durations of the last installation, see ```metrics```.
+    metrics: InstallMetrics,
+    status_sender: watch::Sender<InstallerStatus>,
+    clean_up_cancellation_sender: watch::Sender<CancellationToken>,
+    /// Keeps the staging dir for ```resume``` if the requirements phase fails.
+    keep_env_on_requirements_failure: bool,
+    /// Set by ```resume```, the staging dir of a previous installation is used.
+    resuming: bool,
+    /// Shares the environment with the projects that have the same requirements.
+    env_store: Option<EnvStore>,
+    /// Whether the staging dir of a failed installation is deleted.
+    cleanup_policy: CleanupPolicy,
 }
 
 impl LocalProjectInstaller {
@@ -140,8 +312,8 @@ impl LocalProjectInstaller {
         uploaded_project_dir: PathBuf,
         installed_project_dir: PathBuf,
         project_env_dir: PathBuf,
-        stdout_sender: Option<mpsc::Sender<String>>,
-        stderr_sender: Option<mpsc::Sender<String>>,
+        event_sender: Option<mpsc::Sender<InstallerEvent>>,
+        io_config: ProcessIoConfig,
     ) -> (Self, LocalProjectInstallerController) {
         let (venv_process, venv_controller) = Process::new(
             String::from("venv_id"),
@@ -151,41 +323,205 @@ impl LocalProjectInstaller {
         let (req_process, req_controller) =
             Process::new(String::from("req_id"), String::from("install_req_process"));
 
+        let (status_sender, status_receiver) = watch::channel(InstallerStatus::Pending);
+        let (clean_up_cancellation_sender, clean_up_cancellation_receiver) =
+            watch::channel(CancellationToken::new());
+        let cancellation_token = CancellationToken::new();
+
         (
             Self {
                 id,
                 uploaded_project_dir,
+                project_source: ProjectSource::default(),
+                archive_limits: ArchiveLimits::default(),
+                verify_manifest: false,
+                project_checks: ProjectChecks::default(),
                 installed_project_dir,
+                staging_env_dir: staging::staging_dir_path(&project_env_dir),
                 project_env_dir,
                 venv_process,
                 req_process,
-                stdout_sender,
-                stderr_sender,
+                event_sender,
+                io_config,
+                timeout: None,
+                pip_cache_dir: None,
+                force_reinstall: false,
+                cancellation_token: cancellation_token.clone(),
+                python_config: PythonConfig::default(),
+                installer_backend: InstallerBackend::default(),
+                pip_retry_config: PipRetryConfig::default(),
+                pip_options: PipOptions::default(),
+                audit_config: AuditConfig::default(),
+                required_locust_version: None,
+                disk_space_config: DiskSpaceConfig::default(),
+                env_size_quota: None,
+                log_rotation_config: LogRotationConfig::default(),
+                phase_records: Vec::new(),
+                metrics: InstallMetrics::default(),
+                status_sender,
+                clean_up_cancellation_sender,
+                keep_env_on_requirements_failure: false,
+                resuming: false,
+                env_store: None,
+                cleanup_policy: CleanupPolicy::default(),
             },
             LocalProjectInstallerController {
                 venv_controller,
                 req_controller,
+                status_receiver,
+                clean_up_cancellation_receiver,
+                cancellation_token,
             },
         )
     }
 
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Replaces the event sender the installer was created with, e.g. to tag the events, see ```BatchInstaller```.
+    pub fn set_event_sender(&mut self, event_sender: Option<mpsc::Sender<InstallerEvent>>) {
+        self.event_sender = event_sender;
+    }
+
+    /// Correctness: Unless the project is ```ProjectSource::Uploaded```,
+    /// ```uploaded_project_dir``` is replaced by the fetched project on every ```check_and_install```.
+    pub fn set_project_source(&mut self, project_source: ProjectSource) {
+        self.project_source = project_source;
+    }
+
+    /// Checked while a ```ProjectSource::Archive``` is extracted.
+    pub fn set_archive_limits(&mut self, archive_limits: ArchiveLimits) {
+        self.archive_limits = archive_limits;
+    }
+
+    /// Detects tampered or partially uploaded projects, the installer's io files are ignored.
+    /// Correctness: A project without a manifest fails the check.
+    pub fn set_verify_manifest(&mut self, verify_manifest: bool) {
+        self.verify_manifest = verify_manifest;
+    }
+
+    /// The installation fails with ```InstallError::TimedOut``` if it is still running after ```timeout```.
+    /// Correctness: The running phase is shut down and the virtual environment is deleted, like on any other failure.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Shares downloaded wheels with other installations, see ```PipCacheConfig```. ```None``` leaves the cache to pip.
+    pub fn set_pip_cache_dir(&mut self, pip_cache_dir: Option<PathBuf>) {
+        self.pip_cache_dir = pip_cache_dir;
+    }
+
+    /// The python version is checked before the virtual environment is created, see ```InstallError::PythonVersionUnsupported```.
+    pub fn set_python_config(&mut self, python_config: PythonConfig) {
+        self.python_config = python_config;
+    }
+
+    pub fn set_installer_backend(&mut self, installer_backend: InstallerBackend) {
+        self.installer_backend = installer_backend;
+    }
+
+    /// A requirements phase that failed every attempt fails with ```ErrorThatTriggersCleanUp::RequirementsInstallErrorAfterAttempts```,
+    /// if it was retried.
+    pub fn set_pip_retry_config(&mut self, pip_retry_config: PipRetryConfig) {
+        self.pip_retry_config = pip_retry_config;
+    }
+
+    /// A custom package index, proxy, etc. See ```PipOptions```.
+    pub fn set_pip_options(&mut self, pip_options: PipOptions) {
+        self.pip_options = pip_options;
+    }
+
+    /// Runs pip-audit after the requirements are installed, see ```AuditPolicy```.
+    pub fn set_audit_config(&mut self, audit_config: AuditConfig) {
+        self.audit_config = audit_config;
+    }
+
+    /// Projects with a locust version that does not match fail with ```ErrorThatTriggersCleanUp::LocustVersionTooOld```,
+    /// instead of at run time.
+    pub fn set_required_locust_version(&mut self, required_locust_version: Option<VersionReq>) {
+        self.required_locust_version = required_locust_version;
+    }
+
+    /// Fails early with ```InstallError::InsufficientDiskSpace```, instead of letting pip fail halfway.
+    pub fn set_disk_space_config(&mut self, disk_space_config: DiskSpaceConfig) {
+        self.disk_space_config = disk_space_config;
+    }
+
+    /// The requirements phase is shut down with ```InstallError::QuotaExceeded``` once the environment is bigger.
+    /// Correctness: The virtual environment is deleted, like on any other failure.
+    pub fn set_env_size_quota(&mut self, env_size_quota: Option<EnvSizeQuota>) {
+        self.env_size_quota = env_size_quota;
+    }
+
+    /// The io files of previous installations are kept as rotated files, see ```list_log_files```.
+    pub fn set_log_rotation_config(&mut self, log_rotation_config: LogRotationConfig) {
+        self.log_rotation_config = log_rotation_config;
+    }
+
+    /// A failed requirements phase keeps the virtual environment, instead of deleting it, so ```resume``` does not recreate it.
+    /// Correctness: Only failures of the requirements process keep the environment, not a timeout or an exceeded quota.
+    pub fn set_keep_env_on_requirements_failure(&mut self, keep_env_on_requirements_failure: bool) {
+        self.keep_env_on_requirements_failure = keep_env_on_requirements_failure;
+    }
+
+    /// ```check_and_install``` links ```project_env_dir``` to the environment of the store with the same requirements hash,
+    /// instead of installing one for the project. The environment is installed in the store, if it does not exist yet.
+    /// Correctness: A project that is linked to an existing environment has no ```installed_lock.txt``` and no install report.
+    pub fn set_env_store(&mut self, env_store: Option<EnvStore>) {
+        self.env_store = env_store;
+    }
+
+    /// A failed installation that keeps its staging dir fails with ```InstallError::KeptEnvironment```.
+    /// Correctness: ```set_keep_env_on_requirements_failure``` takes precedence, the staging dir is kept for ```resume``` then.
+    pub fn set_cleanup_policy(&mut self, cleanup_policy: CleanupPolicy) {
+        self.cleanup_policy = cleanup_policy;
+    }
+
+    /// Tunes what a valid project is, e.g. forbids ```.env``` files. The io files of the installer are not checked.
+    pub fn set_project_checks(&mut self, project_checks: ProjectChecks) {
+        self.project_checks = project_checks;
+    }
+
+    /// ```check_and_install``` installs even if the requirements did not change since the last installation.
+    pub fn force_reinstall(&mut self) {
+        self.force_reinstall = true;
+    }
+
     /// A 'check' function fails if the project is not valid.
     /// Otherwise it returns Ok(()).
-    pub async fn check(&self) -> Result<(), ProjectCheckError> {
+    /// Returns how the dependencies of the project are declared.
+    pub async fn check(&self) -> Result<ProjectKind, ProjectCheckError> {
         let uploaded_project_dir = &self.uploaded_project_dir;
 
         let _ = Self::check_dir_exists_and_not_empty(uploaded_project_dir)
             .await
             .map_err(|err| ProjectCheckError::ProjectDir(err.into()))?;
 
-        self.check_requirements_txt_exists_and_locust_in_requirements_txt()
+        self.project_checks
+            .run(uploaded_project_dir, |relative_path| {
+                self.is_io_file(relative_path)
+            })
+            .await?;
+
+        if self.verify_manifest {
+            manifest::verify(uploaded_project_dir, |relative_path| {
+                self.is_io_file(relative_path)
+            })
             .await?;
+        }
+
+        let project_kind = self.detect_project_kind().await?;
 
         self.check_locust_dir_exists_and_not_empty_and_contains_python_scripts()
             .await
             .map_err(ProjectCheckError::LocustDir)?;
 
-        Ok(())
+        self.check_locust_dir_python_syntax()
+            .await
+            .map_err(ProjectCheckError::LocustDir)?;
+
+        Ok(project_kind)
     }
 
     fn path_to_str_mapped_error(path: &Path) -> Result<&str, InstallError> {
@@ -193,308 +529,1470 @@ impl LocalProjectInstaller {
             .ok_or(InstallError::FailedToConvertPathBufToString(path.into()))
     }
 
-    pub async fn install(&mut self) -> Result<(), InstallError> {
-        let uploaded_project_dir_str = Self::path_to_str_mapped_error(&self.uploaded_project_dir)?;
+    /// The program and args that create the virtual environment in ```env_dir_str```.
+    fn venv_command<'a>(
+        installer_backend: InstallerBackend,
+        interpreter_path_str: &'a str,
+        env_dir_str: &'a str,
+    ) -> (&'a str, Vec<&'a str>) {
+        match installer_backend {
+            InstallerBackend::Pip => (interpreter_path_str, vec!["-m", "venv", env_dir_str]),
+            InstallerBackend::Uv => (
+                "uv",
+                vec!["venv", "--python", interpreter_path_str, env_dir_str],
+            ),
+        }
+    }
 
-        let project_env_dir_str = Self::path_to_str_mapped_error(&self.project_env_dir)?;
+    /// The program and args that install the dependencies of the project.
+    /// Pip runs as a module of the environment python, not with the ```pip3``` launcher,
+    /// e.g. ```Scripts\pip3.exe``` can not replace itself on windows, if a project requires another pip version.
+    /// Correctness: The args are passed as they are, no shell is involved, paths with spaces are not quoted.
+    fn requirements_command<'a>(
+        project_kind: ProjectKind,
+        installer_backend: InstallerBackend,
+        env_python_path_str: &'a str,
+        requirements_file_path_str: &'a str,
+        wheelhouse_dir_str: Option<&'a str>,
+    ) -> (&'a str, Vec<&'a str>) {
+        let (req_program, mut req_args) = match (project_kind, installer_backend) {
+            // Locust projects are rarely packages themselves, only their dependencies are installed.
+            (ProjectKind::Poetry, _) => ("poetry", vec!["install", "--no-root"]),
+            (ProjectKind::Requirements, InstallerBackend::Pip) => (
+                env_python_path_str,
+                vec!["-m", "pip", "install", "-r", requirements_file_path_str],
+            ),
+            (ProjectKind::Requirements, InstallerBackend::Uv) => (
+                "uv",
+                vec!["pip", "install", "-r", requirements_file_path_str],
+            ),
+            (ProjectKind::Pyproject, InstallerBackend::Pip) => {
+                (env_python_path_str, vec!["-m", "pip", "install", "."])
+            }
+            (ProjectKind::Pyproject, InstallerBackend::Uv) => ("uv", vec!["pip", "install", "."]),
+        };
 
-        let requirements_file_path = self.get_requirements_file_path();
-        let requirements_file_path_str = Self::path_to_str_mapped_error(&requirements_file_path)?;
+        if let Some(wheelhouse_dir_str) = wheelhouse_dir_str {
+            if project_kind != ProjectKind::Poetry {
+                req_args.extend(["--no-index", "--find-links", wheelhouse_dir_str]);
+            }
+        }
 
-        let pip_path = self.create_os_specific_pip_path();
-        let pip_path_str = Self::path_to_str_mapped_error(&pip_path)?;
-
-        let IoFiles {
-            venv_stdout_file,
-            venv_stderr_file,
-            req_stdout_file,
-            req_stderr_file,
-        } = self.create_io_files().await?;
-
-        let IoChannels {
-            venv_stdout_sender,
-            venv_stdout_receiver,
-            venv_stderr_sender,
-            venv_stderr_receiver,
-            req_stdout_sender,
-            req_stdout_receiver,
-            req_stderr_sender,
-            req_stderr_receiver,
-        } = Self::create_io_channels();
-
-        Self::do_forward_ios_and_write_to_files(IoForwardArgs {
-            stdout_sender: self.stdout_sender.clone(),
-            stderr_sender: self.stderr_sender.clone(),
-            stdout_receiver: venv_stdout_receiver,
-            stdout_file: venv_stdout_file,
-            stderr_receiver: venv_stderr_receiver,
-            stderr_file: venv_stderr_file,
-            stdout_name: "venv_stdout",
-            stderr_name: "venv_stderr",
-        });
+        (req_program, req_args)
+    }
 
-        let venv_process_args = OsProcessArgs {
-            program: "python3",
-            args: vec!["-m", "venv", project_env_dir_str],
-            current_dir: uploaded_project_dir_str,
-            stdout_sender: Some(venv_stdout_sender),
-            stderr_sender: Some(venv_stderr_sender),
-        };
+    /// The envs of the requirements process, that installs into ```env_dir```.
+    fn requirements_envs(
+        &self,
+        project_kind: ProjectKind,
+        installer_backend: InstallerBackend,
+        env_dir: &Path,
+    ) -> Vec<(OsString, OsString)> {
+        let mut req_envs: Vec<(OsString, OsString)> = self
+            .pip_cache_dir
+            .iter()
+            .map(|pip_cache_dir| {
+                (
+                    OsString::from("PIP_CACHE_DIR"),
+                    pip_cache_dir.clone().into_os_string(),
+                )
+            })
+            .collect();
+
+        req_envs.extend(self.pip_options.envs(project_kind, installer_backend));
+
+        // Poetry and uv install into the active virtual environment.
+        if project_kind == ProjectKind::Poetry || installer_backend == InstallerBackend::Uv {
+            req_envs.push((
+                OsString::from("VIRTUAL_ENV"),
+                env_dir.as_os_str().to_os_string(),
+            ));
+        }
 
-        let venv_process_result = self.venv_process.run(venv_process_args).await;
-        let venv_process_run_result =
-            generate_process_run_result!(venv_process_result, VenvInstallError);
+        req_envs
+    }
 
-        if let Err(error) = venv_process_run_result {
-            return Err(self.clean_up_on_error_and_return_error(error).await);
-        }
+    /// Runs the checks of ```check_and_install``` and resolves the commands ```install``` would run, without running them.
+    /// Nothing is written, neither the environment nor the io files.
+    /// Correctness: The staging dir of the plan is not the one of the next installation, every installation gets a new one.
+    pub async fn plan(&self) -> Result<InstallPlan, CheckAndInstallError> {
+        let project_kind = self.check().await?;
 
-        Self::do_forward_ios_and_write_to_files(IoForwardArgs {
-            stdout_sender: self.stdout_sender.clone(),
-            stderr_sender: self.stderr_sender.clone(),
-            stdout_receiver: req_stdout_receiver,
-            stdout_file: req_stdout_file,
-            stderr_receiver: req_stderr_receiver,
-            stderr_file: req_stderr_file,
-            stdout_name: "req_stdout",
-            stderr_name: "req_stderr",
-        });
+        let staging_env_dir = staging::staging_dir_path(&self.project_env_dir);
+        let staging_env_dir_str = Self::path_to_str_mapped_error(&staging_env_dir)?;
 
-        let req_process_args = OsProcessArgs {
-            program: pip_path_str,
-            args: vec!["install", "-r", requirements_file_path_str],
-            current_dir: uploaded_project_dir_str,
-            stdout_sender: Some(req_stdout_sender),
-            stderr_sender: Some(req_stderr_sender),
-        };
+        let uploaded_project_dir_str = Self::path_to_str_mapped_error(&self.uploaded_project_dir)?;
 
-        let req_process_result = self.req_process.run(req_process_args).await;
-        let req_process_run_result =
-            generate_process_run_result!(req_process_result, RequirementsInstallError);
+        let requirements_file_path = self.get_requirements_file_path();
+        let requirements_file_path_str = Self::path_to_str_mapped_error(&requirements_file_path)?;
 
-        if let Err(error) = req_process_run_result {
-            return Err(self.clean_up_on_error_and_return_error(error).await);
-        }
+        let env_python_path = Self::create_os_specific_python_path(&staging_env_dir);
+        let env_python_path_str = Self::path_to_str_mapped_error(&env_python_path)?;
 
-        Ok(())
-    }
+        let interpreter_path_str =
+            Self::path_to_str_mapped_error(&self.python_config.interpreter_path)?;
 
-    pub async fn check_and_install(&mut self) -> Result<(), CheckAndInstallError> {
-        self.check()
-            .await
-            .map_err(CheckAndInstallError::CheckError)?;
+        let wheelhouse_dir_str = self
+            .pip_options
+            .wheelhouse_dir
+            .as_deref()
+            .map(Self::path_to_str_mapped_error)
+            .transpose()?;
 
-        self.install()
-            .await
-            .map_err(CheckAndInstallError::InstallError)?;
+        self.check_python_version().await?;
 
-        Ok(())
-    }
+        self.check_disk_space().await?;
 
-    fn do_forward_io_and_write_to_file(
-        sender_to_forward_to: Option<mpsc::Sender<String>>,
-        mut receiver: mpsc::Receiver<String>,
-        mut file: File,
-        io_name: &'static str,
-    ) {
-        tokio::spawn(async move {
-            while let Some(mut line) = receiver.recv().await {
-                line.push('\n');
-                if let Err(err) = file.write_all(line.as_bytes()).await {
-                    tracing::error!(%err, io_name, "Failed to write to file");
-                    break;
-                }
-                if let Some(sender) = &sender_to_forward_to {
-                    if let Err(err) = sender.send(line).await {
-                        tracing::error!(%err, io_name, "Failed to send line to sender");
-                    }
-                }
-            }
-        });
-    }
+        let installer_backend = self.installer_backend.resolve();
 
-    fn do_forward_ios_and_write_to_files(args: IoForwardArgs) {
-        Self::do_forward_io_and_write_to_file(
-            args.stdout_sender,
-            args.stdout_receiver,
-            args.stdout_file,
-            args.stdout_name,
-        );
+        let (venv_program, venv_args) =
+            Self::venv_command(installer_backend, interpreter_path_str, staging_env_dir_str);
 
-        Self::do_forward_io_and_write_to_file(
-            args.stderr_sender,
-            args.stderr_receiver,
-            args.stderr_file,
-            args.stderr_name,
+        let (req_program, req_args) = Self::requirements_command(
+            project_kind,
+            installer_backend,
+            env_python_path_str,
+            requirements_file_path_str,
+            wheelhouse_dir_str,
         );
+
+        Ok(InstallPlan {
+            project_kind,
+            installer_backend,
+            venv_command: PlannedCommand {
+                program: String::from(venv_program),
+                args: venv_args.into_iter().map(String::from).collect(),
+                current_dir: PathBuf::from(uploaded_project_dir_str),
+                envs: Vec::new(),
+            },
+            requirements_command: PlannedCommand {
+                program: String::from(req_program),
+                args: req_args.into_iter().map(String::from).collect(),
+                current_dir: PathBuf::from(uploaded_project_dir_str),
+                envs: self.requirements_envs(project_kind, installer_backend, &staging_env_dir),
+            },
+            staging_env_dir,
+            env_dir: self.project_env_dir.clone(),
+            installed_project_dir: self.installed_project_dir.clone(),
+        })
     }
 
-    async fn delete_environment_dir_if_exists(
-        &self,
-    ) -> Result<Vec<IoError>, DeleteEnvironmentDirError> {
-        if fs::try_exists(&self.project_env_dir).await? {
-            let errors = self.delete_environment_dir().await?;
-            return Ok(errors);
-        }
+    /// Runs in a span with the id of the project, the tasks of the processes log in it too.
+    /// Successful or not, the installation is recorded in ```install_report.json```, see ```load_report```.
+    pub async fn install(&mut self) -> Result<InstallReport, InstallError> {
+        self.metrics = InstallMetrics::default();
+        let install_result = self.install_without_finishing().await;
 
-        Ok(Vec::new())
-    }
+        self.set_status(InstallerStatus::Finished(
+            install_result
+                .as_ref()
+                .map(|install_report| InstallOutcome::Installed(install_report.clone()))
+                .map_err(ToString::to_string),
+        ));
 
-    async fn delete_environment_dir(&self) -> Result<Vec<IoError>, MaxAttemptsExceeded> {
-        remove_dir_all_with_max_attempts_and_delay(5, Duration::from_secs(2), &self.project_env_dir)
-            .await
+        install_result
     }
 
-    fn get_requirements_file_path(&self) -> PathBuf {
-        self.uploaded_project_dir.join("requirements.txt")
+    /// A ```watch``` channel does not fail, if the controller is dropped.
+    pub(super) fn set_status(&self, status: InstallerStatus) {
+        self.status_sender.send_replace(status);
     }
 
-    fn get_locust_dir_path(&self) -> PathBuf {
-        self.uploaded_project_dir.join("locust")
+    /// Cancelled by ```LocalProjectInstallerController::cancel```, see ```DockerInstaller```.
+    pub(super) fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation_token
     }
 
-    fn get_venv_out_file_path(&self) -> PathBuf {
-        self.uploaded_project_dir.join("venv_out.txt")
-    }
+    /// Sets ```InstallerStatus::CleaningUp``` with a new token, a cancelled clean up does not cancel the next one.
+    /// Correctness: The token is replaced before the status, ```LocalProjectInstallerController::cancel``` never cancels a previous one.
+    pub(super) fn start_clean_up(&self) -> CancellationToken {
+        let cancellation_token = CancellationToken::new();
+        self.clean_up_cancellation_sender
+            .send_replace(cancellation_token.clone());
+        self.set_status(InstallerStatus::CleaningUp);
 
-    fn get_venv_err_file_path(&self) -> PathBuf {
-        self.uploaded_project_dir.join("venv_err.txt")
+        cancellation_token
     }
 
-    fn get_req_out_file_path(&self) -> PathBuf {
-        self.uploaded_project_dir.join("req_out.txt")
-    }
+    /// ```check_and_install``` finishes after the requirements hash is written.
+    async fn install_without_finishing(&mut self) -> Result<InstallReport, InstallError> {
+        let debug_span = debug_span!("LocalProjectInstaller::install", id = self.id);
+        let started_at = SystemTime::now();
+        self.phase_records.clear();
 
-    fn get_req_err_file_path(&self) -> PathBuf {
-        self.uploaded_project_dir.join("req_err.txt")
-    }
+        let mut install_result = self.install_in_span().instrument(debug_span).await;
 
-    pub async fn get_venv_out_from_file(&self) -> Result<String, IoError> {
-        fs::read_to_string(self.get_venv_out_file_path()).await
-    }
+        self.metrics.record_phases(&self.phase_records);
+        if let Ok(install_report) = &mut install_result {
+            install_report.metrics = self.metrics;
+        }
 
-    pub async fn get_venv_err_from_file(&self) -> Result<String, IoError> {
-        fs::read_to_string(self.get_venv_err_file_path()).await
-    }
+        self.write_report(started_at, &install_result).await;
 
-    pub async fn get_req_out_from_file(&self) -> Result<String, IoError> {
-        fs::read_to_string(self.get_req_out_file_path()).await
-    }
+        if let Err(error) = &install_result {
+            installer_events::send(
+                self.event_sender.as_ref(),
+                InstallerEvent::Failed {
+                    error: error.to_string(),
+                },
+            )
+            .await;
+        }
 
-    pub async fn get_req_err_from_file(&self) -> Result<String, IoError> {
-        fs::read_to_string(self.get_req_err_file_path()).await
+        install_result
     }
 
-    async fn check_dir_exists_and_not_empty(
-        dir: &Path,
-    ) -> Result<ReadDir, DirExistsAndNotEmptyError> {
-        if !fs::try_exists(dir)
-            .await
-            .map_err(DirExistsAndNotEmptyError::CouldNotCheckIfDirExists)?
-        {
-            return Err(DirExistsAndNotEmptyError::DirDoesNotExist);
-        }
+    /// Continues the last installation, that failed in the requirements phase, see ```set_keep_env_on_requirements_failure```.
+    /// The phases that completed are skipped, e.g. the virtual environment is not recreated.
+    /// Installs from scratch if there is nothing to resume.
+    pub async fn resume(&mut self) -> Result<InstallReport, InstallError> {
+        let Some(resumable_staging_dir) =
+            staging::find_resumable_staging_dir(&self.project_env_dir).await
+        else {
+            tracing::debug!(id = self.id, "Nothing to resume, installing");
+            return self.install().await;
+        };
 
-        let mut dir_content = fs::read_dir(dir)
-            .await
-            .map_err(DirExistsAndNotEmptyError::CouldNotCheckIfDirIsEmpty)?;
+        tracing::info!(
+            id = self.id,
+            ?resumable_staging_dir,
+            "Resuming installation"
+        );
 
-        if dir_content
-            .next_entry()
-            .await
-            .map_err(DirExistsAndNotEmptyError::CouldNotCheckIfDirIsEmpty)?
-            .is_none()
-        {
-            return Err(DirExistsAndNotEmptyError::DirIsEmpty);
+        if let Err(error) = staging::unmark_resumable(&resumable_staging_dir).await {
+            tracing::warn!(%error, "Could not unmark the resumed staging dir");
         }
 
-        Ok(dir_content)
+        self.staging_env_dir = resumable_staging_dir;
+        self.resuming = true;
+        let install_result = self.install().await;
+        self.resuming = false;
+
+        install_result
     }
 
-    async fn check_locust_dir_exists_and_not_empty_and_contains_python_scripts(
-        &self,
-    ) -> Result<(), LocustDirError> {
-        let dir = self.get_locust_dir_path();
-        let mut dir_content = Self::check_dir_exists_and_not_empty(&dir).await?;
+    /// Correctness: A report that could not be written is logged, it does not fail the installation.
+    async fn write_report(
+        &mut self,
+        started_at: SystemTime,
+        install_result: &Result<InstallReport, InstallError>,
+    ) {
+        let (status, package_count, locust_version) = match install_result {
+            Ok(install_report) => (
+                InstallRecordStatus::Succeeded,
+                self.installed_packages()
+                    .await
+                    .ok()
+                    .map(|installed_packages| installed_packages.len()),
+                Some(install_report.locust_version.to_string()),
+            ),
+            Err(error) => (
+                InstallRecordStatus::Failed {
+                    error: error.to_string(),
+                },
+                None,
+                None,
+            ),
+        };
 
-        while let Some(entry) = dir_content
-            .next_entry()
-            .await
-            .map_err(LocustDirError::CouldNotIterateOverLocustDir)?
+        let install_record = InstallRecord {
+            id: self.id.clone(),
+            started_at,
+            finished_at: SystemTime::now(),
+            status,
+            phases: std::mem::take(&mut self.phase_records),
+            package_count,
+            locust_version,
+            log_files: InstallLogFiles {
+                venv_out: self.get_venv_out_file_path(),
+                venv_err: self.get_venv_err_file_path(),
+                req_out: self.get_req_out_file_path(),
+                req_err: self.get_req_err_file_path(),
+            },
+        };
+
+        if let Err(error) =
+            install_record::write(&self.installed_project_dir, &install_record).await
         {
-            if let Some("py") = entry.path().extension().and_then(|ext| ext.to_str()) {
-                return Ok(());
-            }
+            tracing::warn!(id = self.id, %error, "Could not write the install report");
         }
+    }
 
-        Err(LocustDirError::NoPythonFilesInLocustDir)
+    /// The record of the last installation, without parsing its logs.
+    pub async fn load_report(&self) -> Result<InstallRecord, LoadReportError> {
+        install_record::read(&self.installed_project_dir).await
     }
 
-    async fn check_requirements_txt_exists_and_locust_in_requirements_txt(
-        &self,
-    ) -> Result<(), RequirementsError> {
-        let requirements_file_path = self.get_requirements_file_path();
-        if !fs::try_exists(&requirements_file_path)
-            .await
-            .map_err(RequirementsError::CouldNotCheckIfRequirementsTxtExists)?
-        {
-            return Err(RequirementsError::RequirementsTxtDoesNotExist);
+    async fn install_in_span(&mut self) -> Result<InstallReport, InstallError> {
+        let started_at = Instant::now();
+        if !self.resuming {
+            staging::remove_resumable_staging_dirs(&self.project_env_dir).await;
+            self.staging_env_dir = staging::staging_dir_path(&self.project_env_dir);
         }
 
-        let requirements_file_content = fs::read_to_string(requirements_file_path)
+        let project_kind = self
+            .detect_project_kind()
             .await
-            .map_err(RequirementsError::CouldNotReadRequirementsTxt)?;
+            .map_err(InstallError::CouldNotDetectProjectKind)?;
 
-        if !requirements_file_content.contains("locust") {
-            return Err(RequirementsError::LocustIsNotInRequirementsTxt);
-        }
+        let uploaded_project_dir_str = Self::path_to_str_mapped_error(&self.uploaded_project_dir)?;
 
-        Ok(())
-    }
+        let staging_env_dir_str = Self::path_to_str_mapped_error(&self.staging_env_dir)?;
 
-    fn create_os_specific_pip_path(&self) -> PathBuf {
-        if cfg!(target_os = "windows") {
-            self.project_env_dir.join("Scripts").join("pip3")
-        } else if cfg!(target_os = "linux") {
-            self.project_env_dir.join("bin").join("pip3")
+        let requirements_file_path = self.get_requirements_file_path();
+        let requirements_file_path_str = Self::path_to_str_mapped_error(&requirements_file_path)?;
+
+        let env_python_path = Self::create_os_specific_python_path(&self.staging_env_dir);
+        let env_python_path_str = Self::path_to_str_mapped_error(&env_python_path)?;
+
+        let interpreter_path_str =
+            Self::path_to_str_mapped_error(&self.python_config.interpreter_path)?;
+
+        let wheelhouse_dir_str = self
+            .pip_options
+            .wheelhouse_dir
+            .as_deref()
+            .map(Self::path_to_str_mapped_error)
+            .transpose()?;
+
+        self.check_python_version().await?;
+
+        self.check_disk_space().await?;
+
+        let installer_backend = self.installer_backend.resolve();
+
+        self.create_io_files().await?;
+
+        if staging::is_phase_completed(&self.staging_env_dir, InstallPhase::Venv).await {
+            tracing::info!("Virtual environment is already created, skipping venv phase");
         } else {
-            tracing::warn!("Unknown OS, assuming linux");
-            self.project_env_dir.join("bin").join("pip3")
-        }
-    }
+            self.set_status(InstallerStatus::CreatingVenv);
+
+            let (venv_program, venv_args) =
+                Self::venv_command(installer_backend, interpreter_path_str, staging_env_dir_str);
+
+            let venv_process_args = OsProcessArgs {
+                program: venv_program,
+                args: venv_args,
+                current_dir: uploaded_project_dir_str,
+                stdout_file: Some(self.get_venv_out_file_path()),
+                stderr_file: Some(self.get_venv_err_file_path()),
+                strip_ansi: StripAnsi::both(),
+                io_config: self.io_config,
+                ..OsProcessArgs::default()
+            };
 
-    async fn clean_up_on_error(&mut self) -> Result<(), CleanUpError> {
-        //TODO: what to do with errors vec?
-        let io_errors_vector = self
-            .delete_environment_dir_if_exists()
+            let venv_process_result = match Self::run_phase(
+                &mut self.venv_process,
+                venv_process_args,
+                RetryPolicy::new(1, RetryBackoff::Fixed(Duration::ZERO)),
+                InstallPhase::Venv,
+                started_at,
+                self.timeout,
+                None,
+                self.log_rotation_config,
+                &mut self.phase_records,
+                self.event_sender.as_ref(),
+            )
             .await
-            .map_err(CleanUpError::CouldNotDeleteEnvironment)?;
-        Ok(())
-    }
+            {
+                Ok(venv_process_result) => {
+                    venv_process_result.map(|retried_status| retried_status.status)
+                }
+                Err(aborted) => return Err(self.clean_up_on_abort_and_return_error(aborted).await),
+            };
+            let venv_process_run_result =
+                generate_process_run_result!(venv_process_result, VenvInstallError);
 
-    /// If an error occurs during the clean up, a ```CleanUpError``` is returned.
-    /// If no error occurs during the clean up, the given error mapped to a ```InstallError``` is returned.
-    async fn clean_up_on_error_and_return_error(
-        &mut self,
-        error: ErrorThatTriggersCleanUp,
-    ) -> InstallError {
-        match self.clean_up_on_error().await {
-            Ok(_) => InstallError::ErrorThatTriggersCleanUp(error),
-            Err(clean_up_error) => InstallError::CleanUpError(error, clean_up_error),
+            if let Err(error) = venv_process_run_result {
+                return Err(self.clean_up_on_error_and_return_error(error).await);
+            }
+
+            if let Err(error) =
+                staging::mark_phase_completed(&self.staging_env_dir, InstallPhase::Venv).await
+            {
+                return Err(self
+                    .clean_up_on_error_and_return_error(
+                        ErrorThatTriggersCleanUp::CouldNotMarkPhaseCompleted(error),
+                    )
+                    .await);
+            }
         }
-    }
 
-    async fn create_file(&self, path: &Path) -> Result<File, CreateFileError> {
-        File::create(&path)
-            .await
-            .map_err(|e| CreateFileError::CouldNotCreateFile(e, path.into()))
-    }
+        if staging::is_phase_completed(&self.staging_env_dir, InstallPhase::Requirements).await {
+            tracing::info!("Requirements are already installed, skipping requirements phase");
+        } else {
+            self.set_status(InstallerStatus::InstallingRequirements);
+
+            let (req_program, req_args) = Self::requirements_command(
+                project_kind,
+                installer_backend,
+                env_python_path_str,
+                requirements_file_path_str,
+                wheelhouse_dir_str,
+            );
+
+            let req_envs =
+                self.requirements_envs(project_kind, installer_backend, &self.staging_env_dir);
+
+            let req_process_args = OsProcessArgs {
+                program: req_program,
+                args: req_args,
+                current_dir: uploaded_project_dir_str,
+                stdout_file: Some(self.get_req_out_file_path()),
+                stderr_file: Some(self.get_req_err_file_path()),
+                strip_ansi: StripAnsi::both(),
+                // Pip installs are heavy, running tests should stay responsive.
+                priority: ProcessPriority::Low,
+                io_config: self.io_config,
+                envs: req_envs,
+                ..OsProcessArgs::default()
+            };
 
-    async fn create_venv_file(&self, path: &Path) -> Result<File, InstallError> {
-        self.create_file(path)
+            let req_process_result = match Self::run_phase(
+                &mut self.req_process,
+                req_process_args,
+                self.pip_retry_config.clone().into_retry_policy(),
+                InstallPhase::Requirements,
+                started_at,
+                self.timeout,
+                self.env_size_quota
+                    .map(|env_size_quota| (self.staging_env_dir.as_path(), env_size_quota)),
+                self.log_rotation_config,
+                &mut self.phase_records,
+                self.event_sender.as_ref(),
+            )
             .await
-            .map_err(|e| InstallError::VenvStartError(SubStartInstallError::CreateFileError(e)))
-    }
+            {
+                Ok(req_process_result) => req_process_result,
+                Err(aborted) => return Err(self.clean_up_on_abort_and_return_error(aborted).await),
+            };
+            let attempts = req_process_result
+                .as_ref()
+                .map(|retried_status| retried_status.attempts.clone())
+                .unwrap_or_default();
+            let req_process_result = req_process_result.map(|retried_status| retried_status.status);
+            let req_process_run_result = generate_process_run_result!(
+                req_process_result,
+                RequirementsInstallError
+            )
+            .map_err(|error| match error {
+                ErrorThatTriggersCleanUp::RequirementsInstallError(error) if attempts.len() > 1 => {
+                    ErrorThatTriggersCleanUp::RequirementsInstallErrorAfterAttempts {
+                        error,
+                        attempts,
+                    }
+                }
+                error => error,
+            });
+
+            if let Err(error) = req_process_run_result {
+                if self.keep_env_on_requirements_failure {
+                    return Err(self.keep_for_resume_and_return_error(error).await);
+                }
+
+                return Err(self.clean_up_on_error_and_return_error(error).await);
+            }
+
+            if let Err(error) =
+                staging::mark_phase_completed(&self.staging_env_dir, InstallPhase::Requirements)
+                    .await
+            {
+                return Err(self
+                    .clean_up_on_error_and_return_error(
+                        ErrorThatTriggersCleanUp::CouldNotMarkPhaseCompleted(error),
+                    )
+                    .await);
+            }
+        }
+
+        let locust_version = self.check_locust_version(started_at).await?;
+
+        let vulnerability_report = self.audit(started_at).await?;
+
+        self.write_installed_lockfile(installer_backend, started_at)
+            .await?;
+
+        if let Err(error) = self.copy_project_to_installed_dir().await {
+            return Err(self
+                .clean_up_on_error_and_return_error(ErrorThatTriggersCleanUp::CouldNotCopyProject(
+                    error,
+                ))
+                .await);
+        }
+
+        if let Err(error) = staging::promote(&self.staging_env_dir, &self.project_env_dir).await {
+            return Err(self
+                .clean_up_on_error_and_return_error(
+                    ErrorThatTriggersCleanUp::CouldNotPromoteEnvironment(error),
+                )
+                .await);
+        }
+
+        Ok(InstallReport {
+            locust_version,
+            vulnerability_report,
+            // Set by ```install_without_finishing```, once every phase is recorded.
+            metrics: InstallMetrics::default(),
+        })
+    }
+
+    /// Runs ```locust --version``` in the environment and checks it against ```required_locust_version```.
+    async fn check_locust_version(&mut self, started_at: Instant) -> Result<Version, InstallError> {
+        let locust_path = Self::create_os_specific_executable_path(&self.staging_env_dir, "locust");
+        let probe_result = match Self::within_timeout(
+            locust_version::probe(
+                &locust_path,
+                &self.uploaded_project_dir,
+                &self.cancellation_token,
+            ),
+            InstallPhase::LocustVersion,
+            started_at,
+            self.timeout,
+            &mut self.phase_records,
+            self.event_sender.as_ref(),
+        )
+        .await
+        {
+            Ok(probe_result) => probe_result,
+            Err(timed_out) => return Err(self.clean_up_on_abort_and_return_error(timed_out).await),
+        };
+
+        let version = match probe_result {
+            Ok(version) => version,
+            Err(error) => {
+                return Err(self
+                    .clean_up_on_error_and_return_error(
+                        ErrorThatTriggersCleanUp::CouldNotProbeLocust(error),
+                    )
+                    .await)
+            }
+        };
+
+        if let Some(required_locust_version) = &self.required_locust_version {
+            if !required_locust_version.matches(&version) {
+                let error = ErrorThatTriggersCleanUp::LocustVersionTooOld {
+                    version,
+                    required_locust_version: required_locust_version.clone(),
+                };
+                return Err(self.clean_up_on_error_and_return_error(error).await);
+            }
+        }
+
+        tracing::debug!(%version, "Locust version is supported");
+
+        Ok(version)
+    }
+
+    /// Writes the output of ```pip freeze``` to ```installed_lock.txt``` in the installed project dir,
+    /// see ```installed_packages```.
+    async fn write_installed_lockfile(
+        &mut self,
+        installer_backend: InstallerBackend,
+        started_at: Instant,
+    ) -> Result<(), InstallError> {
+        let env_python_path = Self::create_os_specific_python_path(&self.staging_env_dir);
+        let freeze_result = match Self::within_timeout(
+            lockfile::freeze(
+                installer_backend,
+                &env_python_path,
+                &self.uploaded_project_dir,
+                &self.cancellation_token,
+            ),
+            InstallPhase::Lockfile,
+            started_at,
+            self.timeout,
+            &mut self.phase_records,
+            self.event_sender.as_ref(),
+        )
+        .await
+        {
+            Ok(freeze_result) => freeze_result,
+            Err(timed_out) => return Err(self.clean_up_on_abort_and_return_error(timed_out).await),
+        };
+
+        let lockfile = match freeze_result {
+            Ok(lockfile) => lockfile,
+            Err(error) => {
+                return Err(self
+                    .clean_up_on_error_and_return_error(ErrorThatTriggersCleanUp::FreezeError(
+                        error,
+                    ))
+                    .await)
+            }
+        };
+
+        let write_result = async {
+            fs::create_dir_all(&self.installed_project_dir).await?;
+            fs::write(self.get_installed_lock_file_path(), lockfile).await
+        }
+        .await;
+
+        if let Err(error) = write_result {
+            return Err(self
+                .clean_up_on_error_and_return_error(
+                    ErrorThatTriggersCleanUp::CouldNotWriteLockfile(error),
+                )
+                .await);
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the project in the installed project dir with the uploaded one, without the io files of the installer processes.
+    /// The lockfile and the install report are kept.
+    pub(super) async fn copy_project_to_installed_dir(&self) -> Result<(), IoError> {
+        let kept_file_paths = [
+            self.get_installed_lock_file_path(),
+            install_record::report_file_path(&self.installed_project_dir),
+        ];
+
+        fs::create_dir_all(&self.installed_project_dir).await?;
+
+        let mut installed_project_dir_content = fs::read_dir(&self.installed_project_dir).await?;
+        while let Some(entry) = installed_project_dir_content.next_entry().await? {
+            if kept_file_paths.contains(&entry.path()) {
+                continue;
+            }
+
+            if entry.file_type().await?.is_dir() {
+                fs::remove_dir_all(entry.path()).await?;
+            } else {
+                fs::remove_file(entry.path()).await?;
+            }
+        }
+
+        copy_dir_all(
+            &self.uploaded_project_dir,
+            &self.installed_project_dir,
+            |relative_path| self.is_io_file(relative_path),
+        )
+        .await?;
+
+        // The manifest of the installed project, for ```verify_installed_project```.
+        let project_manifest = manifest::generate(&self.uploaded_project_dir, |relative_path| {
+            self.is_io_file(relative_path)
+        })
+        .await?;
+
+        manifest::write(&self.installed_project_dir, &project_manifest).await
+    }
+
+    /// Compares the installed project with the manifest written by the last successful installation,
+    /// e.g. for integrity audits. ```installed_lock.txt``` and ```install_report.json``` are ignored.
+    pub async fn verify_installed_project(&self) -> Result<(), ManifestError> {
+        let kept_file_paths = [
+            self.get_installed_lock_file_path(),
+            install_record::report_file_path(&self.installed_project_dir),
+        ];
+
+        manifest::verify(&self.installed_project_dir, |relative_path| {
+            kept_file_paths.contains(&self.installed_project_dir.join(relative_path))
+        })
+        .await
+    }
+
+    /// Whether ```relative_path``` of the uploaded project dir is an io file or a rotated one.
+    pub(super) fn is_io_file(&self, relative_path: &Path) -> bool {
+        let path = self.uploaded_project_dir.join(relative_path);
+
+        self.get_io_file_paths().iter().any(|io_file_path| {
+            path == *io_file_path || log_rotation::is_rotated_file_of(&path, io_file_path)
+        })
+    }
+
+    /// The packages of the environment after the last successful installation.
+    pub async fn installed_packages(&self) -> Result<Vec<PackageVersion>, IoError> {
+        let lockfile = fs::read_to_string(self.get_installed_lock_file_path()).await?;
+
+        Ok(lockfile::parse(&lockfile))
+    }
+
+    /// Awaits ```future``` with the time that is left of ```timeout``` since ```started_at```.
+    /// On timeout ```future``` is dropped and ```InstallError::TimedOut``` is returned.
+    /// The phase is appended to ```phase_records``` and sent to ```event_sender``` either way.
+    pub(super) async fn within_timeout<F: Future>(
+        future: F,
+        phase: InstallPhase,
+        started_at: Instant,
+        timeout: Option<Duration>,
+        phase_records: &mut Vec<PhaseRecord>,
+        event_sender: Option<&mpsc::Sender<InstallerEvent>>,
+    ) -> Result<F::Output, InstallError> {
+        let phase_started_at = SystemTime::now();
+        installer_events::send(event_sender, InstallerEvent::PhaseStarted { phase }).await;
+
+        let output = match timeout {
+            Some(timeout) => {
+                let remaining = timeout.saturating_sub(started_at.elapsed());
+                tokio::time::timeout(remaining, future).await
+            }
+            None => Ok(future.await),
+        };
+
+        phase_records.push(PhaseRecord::new(phase, phase_started_at, None));
+        installer_events::send(
+            event_sender,
+            InstallerEvent::PhaseFinished {
+                phase,
+                exit_code: None,
+            },
+        )
+        .await;
+
+        if let Ok(output) = output {
+            return Ok(output);
+        }
+
+        tracing::warn!(?phase, "Installation timed out");
+
+        Err(InstallError::TimedOut {
+            phase,
+            elapsed: started_at.elapsed(),
+        })
+    }
+
+    /// Returns ```None``` if the audit is disabled.
+    /// Correctness: A failing audit fails the installation, even with ```AuditPolicy::Report```.
+    async fn audit(
+        &mut self,
+        started_at: Instant,
+    ) -> Result<Option<VulnerabilityReport>, InstallError> {
+        if self.audit_config.policy == AuditPolicy::Disabled {
+            return Ok(None);
+        }
+
+        let env_python_path = Self::create_os_specific_python_path(&self.staging_env_dir);
+        let audit_future = audit::run(
+            &self.audit_config,
+            &env_python_path,
+            &self.uploaded_project_dir,
+            &self.cancellation_token,
+        );
+
+        // Dropping the audit process on timeout kills it.
+        let audit_result = match Self::within_timeout(
+            audit_future,
+            InstallPhase::Audit,
+            started_at,
+            self.timeout,
+            &mut self.phase_records,
+            self.event_sender.as_ref(),
+        )
+        .await
+        {
+            Ok(audit_result) => audit_result,
+            Err(timed_out) => return Err(self.clean_up_on_abort_and_return_error(timed_out).await),
+        };
+
+        let vulnerability_report = match audit_result {
+            Ok(vulnerability_report) => vulnerability_report,
+            Err(error) => {
+                return Err(self
+                    .clean_up_on_error_and_return_error(ErrorThatTriggersCleanUp::AuditError(error))
+                    .await)
+            }
+        };
+
+        tracing::info!(
+            vulnerabilities = vulnerability_report.vulnerability_count(),
+            "Audited requirements"
+        );
+
+        if self.audit_config.policy == AuditPolicy::FailOnVulnerabilities
+            && !vulnerability_report.is_empty()
+        {
+            return Err(self
+                .clean_up_on_error_and_return_error(ErrorThatTriggersCleanUp::VulnerabilitiesFound(
+                    vulnerability_report,
+                ))
+                .await);
+        }
+
+        Ok(Some(vulnerability_report))
+    }
+
+    /// Fails early, instead of with an opaque pip error later.
+    async fn check_python_version(&self) -> Result<(), InstallError> {
+        let version = python::probe_version(
+            &self.python_config.interpreter_path,
+            &self.uploaded_project_dir,
+            &self.cancellation_token,
+        )
+        .await
+        .map_err(InstallError::CouldNotProbePython)?;
+
+        if !self.python_config.supports(version) {
+            return Err(InstallError::PythonVersionUnsupported {
+                version,
+                min_version: self.python_config.min_version,
+                max_version: self.python_config.max_version,
+            });
+        }
+
+        tracing::debug!(%version, "Python version is supported");
+
+        Ok(())
+    }
+
+    /// Compares the space the installation may need with the available space of the environments volume.
+    /// Correctness: An unknown disk is not an error, the check is skipped.
+    async fn check_disk_space(&self) -> Result<(), InstallError> {
+        if !self.disk_space_config.enabled {
+            return Ok(());
+        }
+
+        let uploaded_project_size = disk_space::dir_size(&self.uploaded_project_dir)
+            .await
+            .map_err(InstallError::CouldNotCheckDiskSpace)?;
+        let required = self.disk_space_config.required_bytes(uploaded_project_size);
+
+        let Some(available) = disk_space::available_space(&self.project_env_dir).await else {
+            tracing::warn!(project_env_dir = ?self.project_env_dir, "Could not find the disk of the environment, skipping disk space check");
+            return Ok(());
+        };
+
+        if available < required {
+            return Err(InstallError::InsufficientDiskSpace {
+                required,
+                available,
+            });
+        }
+
+        tracing::debug!(required, available, "Disk space is sufficient");
+
+        Ok(())
+    }
+
+    /// Runs ```process``` with the time that is left of ```timeout``` since ```started_at```,
+    /// while the size of the given environment dir is checked against its quota.
+    /// On timeout the process is shut down and ```InstallError::TimedOut``` is returned,
+    /// on an exceeded quota ```InstallError::QuotaExceeded```.
+    /// The phase is appended to ```phase_records``` with the exit code of its last attempt,
+    /// its output is sent to ```event_sender``` as ```InstallerEvent::OutputLine``` events.
+    /// Correctness: The timeout includes the backoff between attempts.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) async fn run_phase<I, S, P>(
+        process: &mut Process,
+        mut os_process_args: OsProcessArgs<I, S, P>,
+        retry_policy: RetryPolicy,
+        phase: InstallPhase,
+        started_at: Instant,
+        timeout: Option<Duration>,
+        env_size_quota: Option<(&Path, EnvSizeQuota)>,
+        log_rotation_config: LogRotationConfig,
+        phase_records: &mut Vec<PhaseRecord>,
+        event_sender: Option<&mpsc::Sender<InstallerEvent>>,
+    ) -> Result<Result<RetriedStatus, ProcessRunError>, InstallError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+        P: AsRef<Path>,
+    {
+        let phase_started_at = SystemTime::now();
+        installer_events::send(event_sender, InstallerEvent::PhaseStarted { phase }).await;
+
+        let output_forwarder = event_sender.map(|event_sender| {
+            let (output_forwarder, stdout_sender, stderr_sender) =
+                OutputForwarder::spawn(event_sender.clone(), phase);
+            os_process_args.stdout_sender = Some(stdout_sender);
+            os_process_args.stderr_sender = Some(stderr_sender);
+            output_forwarder
+        });
+
+        let timed_out = async {
+            match timeout {
+                Some(timeout) => {
+                    tokio::time::sleep(timeout.saturating_sub(started_at.elapsed())).await
+                }
+                None => std::future::pending().await,
+            }
+        };
+
+        let quota_exceeded = async {
+            match env_size_quota {
+                Some((env_dir, env_size_quota)) => (
+                    disk_space::wait_until_quota_exceeded(env_dir, env_size_quota).await,
+                    env_size_quota.max_size_bytes,
+                ),
+                None => std::future::pending().await,
+            }
+        };
+
+        let log_file_paths = os_process_args
+            .stdout_file
+            .iter()
+            .chain(os_process_args.stderr_file.iter())
+            .cloned()
+            .collect();
+
+        let error = tokio::select! {
+            process_result = process.run_with_retry(os_process_args, retry_policy) => {
+                let exit_code = process_result
+                    .as_ref()
+                    .ok()
+                    .and_then(|retried_status| install_record::exit_code(&retried_status.status));
+                phase_records.push(PhaseRecord::new(phase, phase_started_at, exit_code));
+                Self::finish_phase(output_forwarder, event_sender, phase, exit_code).await;
+
+                return Ok(process_result);
+            }
+            _ = timed_out => {
+                tracing::warn!(?phase, "Installation timed out");
+
+                InstallError::TimedOut {
+                    phase,
+                    elapsed: started_at.elapsed(),
+                }
+            }
+            (size, max_size) = quota_exceeded => {
+                tracing::warn!(?phase, size, max_size, "Environment exceeded its quota");
+
+                InstallError::QuotaExceeded { size, max_size }
+            }
+            _ = log_rotation::rotate_while_running(log_file_paths, log_rotation_config) => {
+                unreachable!("Log rotation never finishes")
+            }
+        };
+
+        if let Err(kill_and_wait_error) = process.shutdown().await {
+            tracing::warn!(%kill_and_wait_error, ?phase, "Could not shut down the aborted process");
+        }
+
+        phase_records.push(PhaseRecord::new(phase, phase_started_at, None));
+        Self::finish_phase(output_forwarder, event_sender, phase, None).await;
+
+        Err(error)
+    }
+
+    /// The ```PhaseFinished``` event is sent after the last output line of the phase.
+    async fn finish_phase(
+        output_forwarder: Option<OutputForwarder>,
+        event_sender: Option<&mpsc::Sender<InstallerEvent>>,
+        phase: InstallPhase,
+        exit_code: Option<i32>,
+    ) {
+        if let Some(output_forwarder) = output_forwarder {
+            output_forwarder.join().await;
+        }
+
+        installer_events::send(
+            event_sender,
+            InstallerEvent::PhaseFinished { phase, exit_code },
+        )
+        .await;
+    }
+
+    /// Skips the installation with ```InstallOutcome::AlreadyInstalled``` if the environment is healthy
+    /// and was installed from the same requirements with the same python version, see ```force_reinstall```.
+    /// Correctness: The stored hash is removed before installing, so a failed installation is never skipped.
+    pub async fn check_and_install(&mut self) -> Result<InstallOutcome, CheckAndInstallError> {
+        self.metrics = InstallMetrics::default();
+        let check_and_install_result = self.check_and_install_without_finishing().await;
+
+        self.set_status(InstallerStatus::Finished(
+            check_and_install_result
+                .as_ref()
+                .map(Clone::clone)
+                .map_err(ToString::to_string),
+        ));
+
+        check_and_install_result
+    }
+
+    /// The hash of the requirements the environment was installed from, written by ```check_and_install```.
+    /// ```None``` if the environment was not installed by ```check_and_install```.
+    pub async fn requirements_hash(&self) -> Result<Option<String>, IoError> {
+        requirements_hash::read(&self.get_requirements_hash_file_path()).await
+    }
+
+    /// How long the phases of the last installation took, also of a failed one,
+    /// e.g. to aggregate the installation performance of every project.
+    pub fn metrics(&self) -> InstallMetrics {
+        self.metrics
+    }
+
+    async fn check_and_install_without_finishing(
+        &mut self,
+    ) -> Result<InstallOutcome, CheckAndInstallError> {
+        self.set_status(InstallerStatus::Checking);
+
+        self.project_source
+            .fetch(
+                &self.uploaded_project_dir,
+                self.archive_limits,
+                &self.cancellation_token,
+            )
+            .await?;
+
+        let check_started_at = Instant::now();
+        let check_result = self.check().await;
+        self.metrics.check = Some(check_started_at.elapsed());
+        let project_kind = check_result.map_err(CheckAndInstallError::CheckError)?;
+
+        let requirements_hash = requirements_hash::compute(
+            &self.get_dependency_file_paths(project_kind),
+            &self.python_config.interpreter_path,
+            &self.uploaded_project_dir,
+            &self.cancellation_token,
+        )
+        .await?;
+        let requirements_hash_file_path = self.get_requirements_hash_file_path();
+        let map_hash_file_error = |error| {
+            RequirementsHashError::CouldNotAccessHashFile(
+                error,
+                requirements_hash_file_path.clone(),
+            )
+        };
+
+        if !self.force_reinstall && self.is_environment_healthy().await {
+            let stored_requirements_hash = requirements_hash::read(&requirements_hash_file_path)
+                .await
+                .map_err(map_hash_file_error)?;
+
+            if stored_requirements_hash.as_deref() == Some(requirements_hash.as_str()) {
+                tracing::debug!(
+                    id = self.id,
+                    "Requirements did not change, skipping installation"
+                );
+                return Ok(InstallOutcome::AlreadyInstalled);
+            }
+        }
+
+        requirements_hash::remove(&requirements_hash_file_path)
+            .await
+            .map_err(map_hash_file_error)?;
+
+        let install_outcome = match self.env_store.clone() {
+            Some(env_store) => self.install_shared(&env_store, &requirements_hash).await?,
+            None => InstallOutcome::Installed(
+                self.install_without_finishing()
+                    .await
+                    .map_err(CheckAndInstallError::InstallError)?,
+            ),
+        };
+
+        requirements_hash::write(&requirements_hash_file_path, &requirements_hash)
+            .await
+            .map_err(map_hash_file_error)?;
+
+        Ok(install_outcome)
+    }
+
+    /// Links the project to the shared environment of ```requirements_hash```, which is installed first, if it does not exist.
+    /// Correctness: The project is copied to the installed project dir either way, the environment may be installed for another project.
+    async fn install_shared(
+        &mut self,
+        env_store: &EnvStore,
+        requirements_hash: &str,
+    ) -> Result<InstallOutcome, CheckAndInstallError> {
+        let shared_env_dir = env_store.env_dir(requirements_hash);
+        let shared_env_healthy =
+            fs::try_exists(Self::create_os_specific_python_path(&shared_env_dir))
+                .await
+                .unwrap_or(false);
+
+        let install_outcome = if !self.force_reinstall && shared_env_healthy {
+            tracing::debug!(
+                id = self.id,
+                requirements_hash,
+                "Requirements are installed in a shared environment, skipping installation"
+            );
+
+            self.copy_project_to_installed_dir()
+                .await
+                .map_err(CheckAndInstallError::CouldNotCopyProject)?;
+
+            InstallOutcome::AlreadyInstalled
+        } else {
+            // Installed in the store, environments are not moved after they are promoted.
+            let project_env_dir = std::mem::replace(&mut self.project_env_dir, shared_env_dir);
+            let install_result = self.install_without_finishing().await;
+            self.project_env_dir = project_env_dir;
+
+            InstallOutcome::Installed(install_result.map_err(CheckAndInstallError::InstallError)?)
+        };
+
+        env_store
+            .link(&self.id, requirements_hash, &self.project_env_dir)
+            .await?;
+
+        Ok(install_outcome)
+    }
+
+    /// An environment is healthy, if its python exists. ```uv venv``` does not install pip.
+    async fn is_environment_healthy(&self) -> bool {
+        fs::try_exists(Self::create_os_specific_python_path(&self.project_env_dir))
+            .await
+            .unwrap_or(false)
+    }
+
+    /// Deletes ```env_dir```, if it exists. Failed attempts before the dir was deleted are logged,
+    /// a dir that could not be deleted is returned with the errors of every attempt and what is left of it.
+    /// Stops once ```cancellation_token``` is cancelled, see ```LocalProjectInstallerController::cancel```.
+    pub(super) async fn delete_env_dir_if_exists(
+        env_dir: &Path,
+        cancellation_token: &CancellationToken,
+    ) -> Result<(), CleanUpError> {
+        let delete_result = async {
+            if !fs::try_exists(env_dir).await? {
+                return Ok(Vec::new());
+            }
+
+            Ok(Self::delete_env_dir(env_dir, cancellation_token).await?)
+        }
+        .await;
+
+        match delete_result {
+            Ok(attempt_errors) => {
+                if !attempt_errors.is_empty() {
+                    tracing::warn!(
+                        ?env_dir,
+                        ?attempt_errors,
+                        "Deleted environment dir after {} failed attempts",
+                        attempt_errors.len()
+                    );
+                }
+
+                Ok(())
+            }
+            Err(error) => Err(CleanUpError::CouldNotDeleteEnvironment {
+                error,
+                dir_state: DirState::of(env_dir).await,
+            }),
+        }
+    }
+
+    async fn delete_env_dir(
+        env_dir: &Path,
+        cancellation_token: &CancellationToken,
+    ) -> Result<Vec<IoError>, RemoveDirAllError> {
+        remove_dir_all_with_max_attempts_and_delay(
+            5,
+            Duration::from_secs(2),
+            env_dir,
+            cancellation_token,
+        )
+        .await
+    }
+
+    fn get_requirements_hash_file_path(&self) -> PathBuf {
+        requirements_hash::file_path(&self.project_env_dir)
+    }
+
+    pub(super) fn get_requirements_file_path(&self) -> PathBuf {
+        self.uploaded_project_dir.join("requirements.txt")
+    }
+
+    fn get_pyproject_file_path(&self) -> PathBuf {
+        self.uploaded_project_dir.join("pyproject.toml")
+    }
+
+    /// The files that declare the dependencies of a project of ```project_kind```, they may not exist, e.g. ```poetry.lock```.
+    fn get_dependency_file_paths(&self, project_kind: ProjectKind) -> Vec<PathBuf> {
+        match project_kind {
+            ProjectKind::Requirements => vec![self.get_requirements_file_path()],
+            ProjectKind::Pyproject => vec![self.get_pyproject_file_path()],
+            ProjectKind::Poetry => vec![
+                self.get_pyproject_file_path(),
+                self.uploaded_project_dir.join("poetry.lock"),
+            ],
+        }
+    }
+
+    pub(super) fn get_installed_lock_file_path(&self) -> PathBuf {
+        self.installed_project_dir.join("installed_lock.txt")
+    }
+
+    fn get_locust_dir_path(&self) -> PathBuf {
+        self.uploaded_project_dir.join("locust")
+    }
+
+    pub(super) fn get_venv_out_file_path(&self) -> PathBuf {
+        self.uploaded_project_dir.join("venv_out.txt")
+    }
+
+    pub(super) fn get_venv_err_file_path(&self) -> PathBuf {
+        self.uploaded_project_dir.join("venv_err.txt")
+    }
+
+    pub(super) fn get_req_out_file_path(&self) -> PathBuf {
+        self.uploaded_project_dir.join("req_out.txt")
+    }
+
+    pub(super) fn get_req_err_file_path(&self) -> PathBuf {
+        self.uploaded_project_dir.join("req_err.txt")
+    }
+
+    fn get_io_file_paths(&self) -> [PathBuf; 4] {
+        [
+            self.get_venv_out_file_path(),
+            self.get_venv_err_file_path(),
+            self.get_req_out_file_path(),
+            self.get_req_err_file_path(),
+        ]
+    }
+
+    /// The io files and their rotated files, e.g. to serve the logs of previous installations.
+    pub async fn list_log_files(&self) -> Result<Vec<LogFile>, IoError> {
+        let mut log_files = Vec::new();
+
+        for io_file_path in self.get_io_file_paths() {
+            log_files.extend(log_rotation::list(&io_file_path).await?);
+        }
+
+        Ok(log_files)
+    }
+
+    pub async fn get_venv_out_from_file(&self) -> Result<String, IoError> {
+        fs::read_to_string(self.get_venv_out_file_path()).await
+    }
+
+    pub async fn get_venv_err_from_file(&self) -> Result<String, IoError> {
+        fs::read_to_string(self.get_venv_err_file_path()).await
+    }
+
+    pub async fn get_req_out_from_file(&self) -> Result<String, IoError> {
+        fs::read_to_string(self.get_req_out_file_path()).await
+    }
+
+    pub async fn get_req_err_from_file(&self) -> Result<String, IoError> {
+        fs::read_to_string(self.get_req_err_file_path()).await
+    }
+
+    async fn check_dir_exists_and_not_empty(
+        dir: &Path,
+    ) -> Result<ReadDir, DirExistsAndNotEmptyError> {
+        if !fs::try_exists(dir)
+            .await
+            .map_err(DirExistsAndNotEmptyError::CouldNotCheckIfDirExists)?
+        {
+            return Err(DirExistsAndNotEmptyError::DirDoesNotExist);
+        }
+
+        let mut dir_content = fs::read_dir(dir)
+            .await
+            .map_err(DirExistsAndNotEmptyError::CouldNotCheckIfDirIsEmpty)?;
+
+        if dir_content
+            .next_entry()
+            .await
+            .map_err(DirExistsAndNotEmptyError::CouldNotCheckIfDirIsEmpty)?
+            .is_none()
+        {
+            return Err(DirExistsAndNotEmptyError::DirIsEmpty);
+        }
+
+        Ok(dir_content)
+    }
+
+    async fn check_locust_dir_exists_and_not_empty_and_contains_python_scripts(
+        &self,
+    ) -> Result<(), LocustDirError> {
+        let dir = self.get_locust_dir_path();
+        let mut dir_content = Self::check_dir_exists_and_not_empty(&dir).await?;
+
+        while let Some(entry) = dir_content
+            .next_entry()
+            .await
+            .map_err(LocustDirError::CouldNotIterateOverLocustDir)?
+        {
+            if let Some("py") = entry.path().extension().and_then(|ext| ext.to_str()) {
+                return Ok(());
+            }
+        }
+
+        Err(LocustDirError::NoPythonFilesInLocustDir)
+    }
+
+    /// Compiles every python file under the locust dir with the interpreter of ```PythonConfig```,
+    /// so broken scripts are found before the installation.
+    async fn check_locust_dir_python_syntax(&self) -> Result<(), LocustDirError> {
+        let python_files = Self::collect_python_files(&self.get_locust_dir_path())
+            .await
+            .map_err(LocustDirError::CouldNotIterateOverLocustDir)?;
+
+        let invalid_python_file = syntax_check::check(
+            &self.python_config.interpreter_path,
+            &self.uploaded_project_dir,
+            &python_files,
+            &self.cancellation_token,
+        )
+        .await
+        .map_err(LocustDirError::CouldNotCheckSyntax)?;
+
+        match invalid_python_file {
+            Some(invalid_python_file) => Err(LocustDirError::SyntaxError {
+                file: invalid_python_file
+                    .file
+                    .strip_prefix(&self.uploaded_project_dir)
+                    .map(Path::to_path_buf)
+                    .unwrap_or(invalid_python_file.file),
+                message: invalid_python_file.message,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Sorted, so the first broken file is the same on every check.
+    async fn collect_python_files(dir: &Path) -> Result<Vec<PathBuf>, IoError> {
+        let mut python_files = Vec::new();
+        let mut dirs = vec![dir.to_path_buf()];
+
+        while let Some(dir) = dirs.pop() {
+            let mut dir_content = fs::read_dir(&dir).await?;
+
+            while let Some(entry) = dir_content.next_entry().await? {
+                let path = entry.path();
+
+                if entry.file_type().await?.is_dir() {
+                    dirs.push(path);
+                } else if let Some("py") = path.extension().and_then(|ext| ext.to_str()) {
+                    python_files.push(path);
+                }
+            }
+        }
+
+        python_files.sort();
+
+        Ok(python_files)
+    }
+
+    /// A ```requirements.txt``` wins over a ```pyproject.toml```. Either must contain locust.
+    pub(super) async fn detect_project_kind(&self) -> Result<ProjectKind, RequirementsError> {
+        let requirements_file_path = self.get_requirements_file_path();
+        let pyproject_file_path = self.get_pyproject_file_path();
+
+        let (dependency_file_path, project_kind) = if fs::try_exists(&requirements_file_path)
+            .await
+            .map_err(RequirementsError::CouldNotCheckIfRequirementsTxtExists)?
+        {
+            (requirements_file_path, ProjectKind::Requirements)
+        } else if fs::try_exists(&pyproject_file_path)
+            .await
+            .map_err(RequirementsError::CouldNotCheckIfRequirementsTxtExists)?
+        {
+            (pyproject_file_path, ProjectKind::Pyproject)
+        } else {
+            return Err(RequirementsError::RequirementsTxtDoesNotExist);
+        };
+
+        let dependency_file_content = fs::read_to_string(dependency_file_path)
+            .await
+            .map_err(RequirementsError::CouldNotReadRequirementsTxt)?;
+
+        if !dependency_file_content.contains("locust") {
+            return Err(RequirementsError::LocustIsNotInRequirementsTxt);
+        }
+
+        if project_kind == ProjectKind::Pyproject
+            && dependency_file_content.contains("[tool.poetry]")
+        {
+            return Ok(ProjectKind::Poetry);
+        }
+
+        Ok(project_kind)
+    }
+
+    pub(super) fn create_os_specific_python_path(env_dir: &Path) -> PathBuf {
+        Self::create_os_specific_executable_path(env_dir, "python")
+    }
+
+    /// Windows keeps the executables of a virtual environment in ```Scripts```, linux and macOS in ```bin```.
+    /// Correctness: The ```.exe``` extension is part of the path on windows, the path is also checked with ```fs::try_exists```.
+    pub(super) fn create_os_specific_executable_path(env_dir: &Path, executable: &str) -> PathBuf {
+        if cfg!(target_os = "windows") {
+            env_dir.join("Scripts").join(format!("{executable}.exe"))
+        } else if cfg!(any(target_os = "linux", target_os = "macos")) {
+            env_dir.join("bin").join(executable)
+        } else {
+            tracing::warn!("Unknown OS, assuming linux");
+            env_dir.join("bin").join(executable)
+        }
+    }
+
+    /// Deletes the staging dir, the environment dir is only written on success.
+    async fn clean_up_on_error(&mut self) -> Result<(), CleanUpError> {
+        let cancellation_token = self.start_clean_up();
+        installer_events::send(self.event_sender.as_ref(), InstallerEvent::CleanupStarted).await;
+
+        let clean_up_started_at = Instant::now();
+        let clean_up_result =
+            Self::delete_env_dir_if_exists(&self.staging_env_dir, &cancellation_token).await;
+        self.metrics.clean_up = Some(clean_up_started_at.elapsed());
+
+        clean_up_result
+    }
+
+    /// If an error occurs during the clean up, a ```CleanUpError``` is returned.
+    /// If no error occurs during the clean up, the given error mapped to a ```InstallError``` is returned.
+    /// Nothing is cleaned up, if the ```CleanupPolicy``` keeps the staging dir.
+    async fn clean_up_on_error_and_return_error(
+        &mut self,
+        error: ErrorThatTriggersCleanUp,
+    ) -> InstallError {
+        if self.cleanup_policy.keeps(&error) {
+            return self.keep_and_return_error(InstallError::ErrorThatTriggersCleanUp(error));
+        }
+
+        match self.clean_up_on_error().await {
+            Ok(_) => InstallError::ErrorThatTriggersCleanUp(error),
+            Err(clean_up_error) => InstallError::CleanUpError(error, clean_up_error),
+        }
+    }
+
+    /// The staging dir is kept and marked for ```resume```, it is not collected as stale.
+    /// If it can not be marked, it is cleaned up like on any other failure.
+    async fn keep_for_resume_and_return_error(
+        &mut self,
+        error: ErrorThatTriggersCleanUp,
+    ) -> InstallError {
+        if let Err(mark_error) = staging::mark_resumable(&self.staging_env_dir).await {
+            tracing::warn!(%mark_error, "Could not mark the staging dir as resumable");
+            return self.clean_up_on_error_and_return_error(error).await;
+        }
+
+        tracing::info!(staging_env_dir = ?self.staging_env_dir, "Kept the environment for resume");
+
+        InstallError::ErrorThatTriggersCleanUp(error)
+    }
+
+    /// Like ```clean_up_on_error_and_return_error``` for an aborted installation, e.g. on timeout.
+    /// If an error occurs during the clean up, ```InstallError::AbortedCleanUpError``` is returned with both errors.
+    async fn clean_up_on_abort_and_return_error(&mut self, aborted: InstallError) -> InstallError {
+        if self.cleanup_policy.keeps_aborted() {
+            return self.keep_and_return_error(aborted);
+        }
+
+        match self.clean_up_on_error().await {
+            Ok(_) => aborted,
+            Err(clean_up_error) => InstallError::AbortedCleanUpError {
+                aborted: Box::new(aborted),
+                clean_up_error,
+            },
+        }
+    }
+
+    fn keep_and_return_error(&self, error: InstallError) -> InstallError {
+        tracing::info!(staging_env_dir = ?self.staging_env_dir, "Kept the environment of the failed installation");
+
+        InstallError::KeptEnvironment {
+            error: Box::new(error),
+            env_dir: self.staging_env_dir.clone(),
+        }
+    }
+
+    async fn create_file(&self, path: &Path) -> Result<File, CreateFileError> {
+        File::create(&path)
+            .await
+            .map_err(|e| CreateFileError::CouldNotCreateFile(e, path.into()))
+    }
+
+    async fn create_venv_file(&self, path: &Path) -> Result<File, InstallError> {
+        self.create_file(path)
+            .await
+            .map_err(|e| InstallError::VenvStartError(SubStartInstallError::CreateFileError(e)))
+    }
 
     async fn create_req_file(&self, path: &Path) -> Result<File, InstallError> {
         self.create_file(path).await.map_err(|e| {
@@ -522,36 +2020,23 @@ impl LocalProjectInstaller {
         self.create_req_file(&req_stderr_file_path).await
     }
 
-    async fn create_io_files(&self) -> Result<IoFiles, InstallError> {
-        let venv_stdout_file = self.create_venv_stdout_file().await?;
-        let venv_stderr_file = self.create_venv_stderr_file().await?;
-        let req_stdout_file = self.create_req_stdout_file().await?;
-        let req_stderr_file = self.create_req_stderr_file().await?;
-
-        Ok(IoFiles {
-            venv_stdout_file,
-            venv_stderr_file,
-            req_stdout_file,
-            req_stderr_file,
-        })
-    }
+    /// Creates empty io files before the processes start, so a previous installation's output is not appended to.
+    /// The output of the previous installation is rotated first, see ```LogRotationConfig```.
+    pub(super) async fn create_io_files(&self) -> Result<(), InstallError> {
+        for io_file_path in self.get_io_file_paths() {
+            // A log file that could not be rotated is overwritten, the installation does not depend on it.
+            if let Err(error) = log_rotation::rotate(&io_file_path, &self.log_rotation_config).await
+            {
+                tracing::warn!(?io_file_path, %error, "Could not rotate log file");
+            }
+        }
 
-    fn create_io_channels() -> IoChannels {
-        let (venv_stdout_sender, venv_stdout_receiver) = mpsc::channel::<String>(100);
-        let (venv_stderr_sender, venv_stderr_receiver) = mpsc::channel::<String>(100);
-        let (req_stdout_sender, req_stdout_receiver) = mpsc::channel::<String>(100);
-        let (req_stderr_sender, req_stderr_receiver) = mpsc::channel::<String>(100);
+        self.create_venv_stdout_file().await?;
+        self.create_venv_stderr_file().await?;
+        self.create_req_stdout_file().await?;
+        self.create_req_stderr_file().await?;
 
-        IoChannels {
-            venv_stdout_sender,
-            venv_stdout_receiver,
-            venv_stderr_sender,
-            venv_stderr_receiver,
-            req_stdout_sender,
-            req_stdout_receiver,
-            req_stderr_sender,
-            req_stderr_receiver,
-        }
+        Ok(())
     }
 }
 
@@ -575,6 +2060,18 @@ pub enum ProjectCheckError {
         #[from]
         LocustDirError,
     ),
+    #[error("Manifest error: {0}")]
+    Manifest(
+        #[source]
+        #[from]
+        ManifestError,
+    ),
+    #[error("Project checks error: {0}")]
+    ProjectChecks(
+        #[source]
+        #[from]
+        ProjectChecksError,
+    ),
 }
 
 #[derive(ThisError, Debug)]
@@ -589,18 +2086,30 @@ pub enum ProjectDirError {
     ProjectDirIsEmpty,
 }
 
+/// The variants name ```requirements.txt```, but apply to a ```pyproject.toml``` as well.
 #[derive(ThisError, Debug)]
 pub enum RequirementsError {
-    #[error("Could not check if requirements.txt exists: {0}")]
+    #[error("Could not check if requirements.txt or pyproject.toml exists: {0}")]
     CouldNotCheckIfRequirementsTxtExists(#[source] IoError),
-    #[error("requirements.txt does not exist")]
+    #[error("Neither requirements.txt nor pyproject.toml exists")]
     RequirementsTxtDoesNotExist,
-    #[error("Could not read requirements.txt: {0}")]
+    #[error("Could not read requirements.txt or pyproject.toml: {0}")]
     CouldNotReadRequirementsTxt(#[source] IoError),
-    #[error("Locust is not in requirements.txt")]
+    #[error("Locust is not in requirements.txt or pyproject.toml")]
     LocustIsNotInRequirementsTxt,
 }
 
+/// How the dependencies of a project are declared, returned by ```LocalProjectInstaller::check```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectKind {
+    /// Installed with ```pip install -r requirements.txt```.
+    Requirements,
+    /// A ```pyproject.toml``` without a ```[tool.poetry]``` table. Installed with ```pip install .```.
+    Pyproject,
+    /// A ```pyproject.toml``` with a ```[tool.poetry]``` table. Installed with ```poetry install``` into the virtual environment.
+    Poetry,
+}
+
 #[derive(ThisError, Debug)]
 pub enum LocustDirError {
     #[error("Could not check if locust dir exists: {0}")]
@@ -615,6 +2124,10 @@ pub enum LocustDirError {
     CouldNotIterateOverLocustDir(#[source] IoError),
     #[error("Locust dir does not contain any python files")]
     NoPythonFilesInLocustDir,
+    #[error("Could not check the syntax of the locust dir: {0}")]
+    CouldNotCheckSyntax(#[source] SyntaxCheckError),
+    #[error("Syntax error in {file:?}: {message}")]
+    SyntaxError { file: PathBuf, message: String },
 }
 
 #[derive(ThisError, Debug)]
@@ -625,6 +2138,9 @@ pub enum SubStartInstallError {
         #[source]
         CreateFileError,
     ),
+    /// The mount point of the environment in a container, see ```DockerInstaller```.
+    #[error("Could not create the staging dir: {0}")]
+    CreateStagingDirError(#[source] IoError),
 }
 
 #[derive(ThisError, Debug)]
@@ -636,7 +2152,7 @@ pub enum SubInstallError {
         ProcessRunError,
     ),
     #[error("Process killed")]
-    Killed(KilledTerminationStatus),
+    Killed(KilledTerminationStatus, OsExitStatus),
     #[error("Process terminated with error")]
     TerminatedWithError(TerminationWithErrorStatus),
     #[error("Process had unexpected status")]
@@ -645,6 +2161,12 @@ pub enum SubInstallError {
 
 #[derive(ThisError, Debug)]
 pub enum CheckAndInstallError {
+    #[error("Could not fetch the project: {0}")]
+    ProjectSourceError(
+        #[from]
+        #[source]
+        ProjectSourceError,
+    ),
     #[error("Project is not valid: {0}")]
     CheckError(
         #[from]
@@ -657,12 +2179,85 @@ pub enum CheckAndInstallError {
         #[source]
         InstallError,
     ),
+    #[error("Could not compare requirements: {0}")]
+    RequirementsHash(
+        #[from]
+        #[source]
+        RequirementsHashError,
+    ),
+    #[error("Could not share the environment: {0}")]
+    EnvStoreError(
+        #[from]
+        #[source]
+        EnvStoreError,
+    ),
+    #[error("Could not copy the project to the installed project dir: {0}")]
+    CouldNotCopyProject(#[source] IoError),
+}
+
+/// Returned by ```LocalProjectInstaller::install```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstallReport {
+    /// The output of ```locust --version``` in the environment.
+    pub locust_version: Version,
+    /// ```None``` if the audit is disabled.
+    pub vulnerability_report: Option<VulnerabilityReport>,
+    /// See ```LocalProjectInstaller::metrics```.
+    pub metrics: InstallMetrics,
+}
+
+/// Returned by ```LocalProjectInstaller::plan```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstallPlan {
+    pub project_kind: ProjectKind,
+    /// Resolved, ```InstallerBackend::Pip``` if uv is not in PATH.
+    pub installer_backend: InstallerBackend,
+    /// Where the environment is built, it is moved to ```env_dir``` after a successful installation.
+    pub staging_env_dir: PathBuf,
+    pub env_dir: PathBuf,
+    pub installed_project_dir: PathBuf,
+    pub venv_command: PlannedCommand,
+    pub requirements_command: PlannedCommand,
+}
+
+/// A process the installer would run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    pub current_dir: PathBuf,
+    /// Set in addition to the inherited envs.
+    pub envs: Vec<(OsString, OsString)>,
+}
+
+/// Returned by ```LocalProjectInstaller::check_and_install```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstallOutcome {
+    Installed(InstallReport),
+    /// The environment was already installed from the same requirements.
+    AlreadyInstalled,
 }
 
 #[derive(ThisError, Debug)]
 pub enum InstallError {
     #[error("Could not convert path buf to string: {0}")]
     FailedToConvertPathBufToString(PathBuf),
+    #[error("Could not detect the project kind: {0}")]
+    CouldNotDetectProjectKind(#[source] RequirementsError),
+    #[error("Projects of kind {0:?} can not be installed in a container")]
+    UnsupportedInContainer(ProjectKind),
+    #[error("Could not get the python version: {0}")]
+    CouldNotProbePython(#[source] PythonProbeError),
+    #[error("Python {version} is not supported, min: {min_version:?}, max: {max_version:?}")]
+    PythonVersionUnsupported {
+        version: PythonVersion,
+        min_version: Option<PythonVersion>,
+        max_version: Option<PythonVersion>,
+    },
+    #[error("Could not check the disk space: {0}")]
+    CouldNotCheckDiskSpace(#[source] IoError),
+    #[error("Insufficient disk space, required: {required} bytes, available: {available} bytes")]
+    InsufficientDiskSpace { required: u64, available: u64 },
     #[error("Virtual environment installation can not be started: {0}")]
     VenvStartError(#[source] SubStartInstallError),
     #[error("Requirements installation can not be started: {0}")]
@@ -675,6 +2270,53 @@ pub enum InstallError {
     ),
     #[error("An error occurred: {0}, and could not clean up: {1}")]
     CleanUpError(ErrorThatTriggersCleanUp, #[source] CleanUpError),
+    /// The installation was aborted, e.g. on timeout, on an exceeded quota or a timed out probe.
+    #[error("Installation was aborted: {aborted}, and could not clean up: {clean_up_error}")]
+    AbortedCleanUpError {
+        aborted: Box<InstallError>,
+        #[source]
+        clean_up_error: CleanUpError,
+    },
+    #[error("Environment size {size} bytes exceeded its quota of {max_size} bytes")]
+    QuotaExceeded { size: u64, max_size: u64 },
+    #[error("Installation timed out in the {phase:?} phase after {elapsed:?}")]
+    TimedOut {
+        phase: InstallPhase,
+        elapsed: Duration,
+    },
+    /// The staging dir was not deleted, see ```CleanupPolicy```.
+    #[error("{error}, the environment was kept in {env_dir:?}")]
+    KeptEnvironment {
+        #[source]
+        error: Box<InstallError>,
+        env_dir: PathBuf,
+    },
+}
+
+impl InstallError {
+    /// The environment the failed installation left behind, it is up to the caller to delete it.
+    pub fn kept_env_dir(&self) -> Option<&Path> {
+        match self {
+            Self::KeptEnvironment { env_dir, .. } => Some(env_dir),
+            _ => None,
+        }
+    }
+}
+
+/// The phase of an installation, each phase runs one process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InstallPhase {
+    /// Creating the virtual environment.
+    Venv,
+    /// Installing the dependencies into the virtual environment, see ```ProjectKind```.
+    Requirements,
+    /// Checking the installed locust version.
+    LocustVersion,
+    /// Auditing the installed requirements, see ```AuditConfig```.
+    Audit,
+    /// Writing ```installed_lock.txt```.
+    Lockfile,
 }
 
 #[derive(ThisError, Debug)]
@@ -683,12 +2325,110 @@ pub enum ErrorThatTriggersCleanUp {
     VenvInstallError(#[source] SubInstallError),
     #[error("Requirements installation failed: {0}")]
     RequirementsInstallError(#[source] SubInstallError),
+    #[error("Requirements installation failed after {} attempts: {error}", attempts.len())]
+    RequirementsInstallErrorAfterAttempts {
+        #[source]
+        error: SubInstallError,
+        /// The termination status of every attempt in order, the transient failures and the last one.
+        attempts: Vec<TerminationStatus>,
+    },
+    #[error("Could not get the locust version: {0}")]
+    CouldNotProbeLocust(#[source] LocustProbeError),
+    /// Also returned for versions that are newer than ```required_locust_version``` allows.
+    #[error("Locust {version} does not match the required version {required_locust_version}")]
+    LocustVersionTooOld {
+        version: Version,
+        required_locust_version: VersionReq,
+    },
+    #[error("Could not audit the requirements: {0}")]
+    AuditError(#[source] AuditError),
+    #[error("Found {} vulnerabilities", .0.vulnerability_count())]
+    VulnerabilitiesFound(VulnerabilityReport),
+    #[error("Could not freeze the installed packages: {0}")]
+    FreezeError(#[source] FreezeError),
+    #[error("Could not write the lockfile: {0}")]
+    CouldNotWriteLockfile(#[source] IoError),
+    #[error("Could not move the environment out of its staging dir: {0}")]
+    CouldNotPromoteEnvironment(#[source] IoError),
+    #[error("Could not copy the project to the installed project dir: {0}")]
+    CouldNotCopyProject(#[source] IoError),
+    #[error("Could not mark the phase as completed: {0}")]
+    CouldNotMarkPhaseCompleted(#[source] IoError),
 }
 
 #[derive(ThisError, Debug)]
 pub enum CleanUpError {
-    #[error("Could not delete environment dir: {0}")]
-    CouldNotDeleteEnvironment(#[source] DeleteEnvironmentDirError),
+    #[error("Could not delete environment dir, {dir_state:?}: {error}")]
+    CouldNotDeleteEnvironment {
+        #[source]
+        error: DeleteEnvironmentDirError,
+        /// What is left of the environment dir after the last attempt.
+        dir_state: DirState,
+    },
+}
+
+impl CleanUpError {
+    /// The error of every failed attempt to delete the environment dir, in order, e.g. permission errors.
+    pub fn attempt_errors(&self) -> &[IoError] {
+        match self {
+            Self::CouldNotDeleteEnvironment {
+                error: DeleteEnvironmentDirError::CouldNotRemoveDir(remove_dir_all_error),
+                ..
+            } => remove_dir_all_error.errors(),
+            Self::CouldNotDeleteEnvironment { .. } => &[],
+        }
+    }
+}
+
+/// The state of a dir after the clean up tried to delete it, see ```CleanUpError```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirState {
+    Deleted,
+    /// Files and dirs that were not deleted, counted recursively, e.g. files that are still open on windows.
+    Remaining {
+        entry_count: usize,
+    },
+    /// The dir could not be read.
+    Unknown,
+}
+
+impl DirState {
+    pub(super) async fn of(dir: &Path) -> Self {
+        match Self::count_entries(dir).await {
+            Ok(None) => Self::Deleted,
+            Ok(Some(entry_count)) => Self::Remaining { entry_count },
+            Err(error) => {
+                tracing::warn!(?dir, %error, "Could not read the dir state");
+                Self::Unknown
+            }
+        }
+    }
+
+    /// ```None``` if ```dir``` does not exist. Symlinks are not followed.
+    async fn count_entries(dir: &Path) -> Result<Option<usize>, IoError> {
+        match fs::symlink_metadata(dir).await {
+            Ok(_) => {}
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        }
+
+        let mut entry_count = 0;
+        let mut dirs = vec![dir.to_path_buf()];
+
+        while let Some(dir) = dirs.pop() {
+            let mut dir_content = fs::read_dir(&dir).await?;
+
+            while let Some(entry) = dir_content.next_entry().await? {
+                entry_count += 1;
+
+                if entry.file_type().await?.is_dir() {
+                    dirs.push(entry.path());
+                }
+            }
+        }
+
+        Ok(Some(entry_count))
+    }
 }
 
 #[derive(ThisError, Debug)]
@@ -748,46 +2488,21 @@ pub enum DeleteEnvironmentDirError {
         IoError,
     ),
     #[error("{0}")]
-    MaxAttemptsExceeded(
+    CouldNotRemoveDir(
         #[source]
         #[from]
-        MaxAttemptsExceeded,
+        RemoveDirAllError,
     ),
 }
 
-struct IoFiles {
-    venv_stdout_file: File,
-    venv_stderr_file: File,
-    req_stdout_file: File,
-    req_stderr_file: File,
-}
-
-struct IoChannels {
-    venv_stdout_sender: mpsc::Sender<String>,
-    venv_stdout_receiver: mpsc::Receiver<String>,
-    venv_stderr_sender: mpsc::Sender<String>,
-    venv_stderr_receiver: mpsc::Receiver<String>,
-    req_stdout_sender: mpsc::Sender<String>,
-    req_stdout_receiver: mpsc::Receiver<String>,
-    req_stderr_sender: mpsc::Sender<String>,
-    req_stderr_receiver: mpsc::Receiver<String>,
-}
-
-struct IoForwardArgs {
-    stdout_sender: Option<mpsc::Sender<String>>,
-    stderr_sender: Option<mpsc::Sender<String>>,
-    stdout_receiver: mpsc::Receiver<String>,
-    stdout_file: File,
-    stderr_receiver: mpsc::Receiver<String>,
-    stderr_file: File,
-    stdout_name: &'static str,
-    stderr_name: &'static str,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::Path;
+    use crate::project_managers::local::{
+        project_checks::{AllowedExtensions, MaxFileCount},
+        test_dirs::TestDirs,
+    };
+    use std::{collections::BTreeSet, path::Path};
     use tracing_test::traced_test;
 
     const CRATE_DIR: &str = env!("CARGO_MANIFEST_DIR");
@@ -796,36 +2511,19 @@ mod tests {
         Path::new(CRATE_DIR).join("tests_dir")
     }
 
-    fn get_uploaded_projects_dir() -> PathBuf {
-        get_tests_dir().join("uploaded_projects")
-    }
-
-    fn get_installed_projects_dir() -> PathBuf {
-        get_tests_dir().join("installed_projects")
-    }
-
-    fn get_environments_dir() -> PathBuf {
-        get_tests_dir().join("environments")
-    }
-
     async fn delete_gitkeep(dir: &Path) {
         tokio::fs::remove_file(dir.join(".gitkeep"))
             .await
             .expect("Could not delete .gitkeep");
     }
 
-    async fn restore_gitkeep(dir: &Path) {
-        tokio::fs::File::create(dir.join(".gitkeep"))
-            .await
-            .expect("Could not restore .gitkeep");
-    }
-
     fn create_installer_and_process_from_project_path(
         project_id_and_dir: String,
+        test_dirs: &TestDirs,
     ) -> (LocalProjectInstaller, LocalProjectInstallerController) {
-        let uploaded_project_dir = get_uploaded_projects_dir().join(&project_id_and_dir);
-        let installed_project_dir = get_installed_projects_dir().join(&project_id_and_dir);
-        let project_env_dir = get_environments_dir().join(&project_id_and_dir);
+        let uploaded_project_dir = test_dirs.uploaded_projects_dir().join(&project_id_and_dir);
+        let installed_project_dir = test_dirs.installed_projects_dir().join(&project_id_and_dir);
+        let project_env_dir = test_dirs.environments_dir().join(&project_id_and_dir);
 
         LocalProjectInstaller::new(
             project_id_and_dir,
@@ -833,7 +2531,7 @@ mod tests {
             installed_project_dir,
             project_env_dir,
             None,
-            None,
+            ProcessIoConfig::default(),
         )
     }
 
@@ -843,9 +2541,10 @@ mod tests {
         #[tokio::test]
         #[traced_test]
         pub async fn fail_on_project_dir_does_not_exist() {
+            let test_dirs = TestDirs::new("installer").await;
             let project_id_and_dir = String::from("project_dir_does_not_exist");
             let (installer, _controller) =
-                create_installer_and_process_from_project_path(project_id_and_dir);
+                create_installer_and_process_from_project_path(project_id_and_dir, &test_dirs);
 
             let result = installer.check().await;
             match result {
@@ -857,31 +2556,29 @@ mod tests {
         #[tokio::test]
         #[traced_test]
         pub async fn fail_on_project_dir_is_empty() {
+            let test_dirs = TestDirs::new("installer").await;
             let project_id_and_dir = String::from("empty");
-            let (installer, _controller) =
-                create_installer_and_process_from_project_path(project_id_and_dir.clone());
+            let (installer, _controller) = create_installer_and_process_from_project_path(
+                project_id_and_dir.clone(),
+                &test_dirs,
+            );
 
-            delete_gitkeep(&get_uploaded_projects_dir().join(&project_id_and_dir)).await;
+            delete_gitkeep(&test_dirs.uploaded_projects_dir().join(&project_id_and_dir)).await;
 
             let result = installer.check().await;
-            let panic_msg = match result {
-                Err(ProjectCheckError::ProjectDir(ProjectDirError::ProjectDirIsEmpty)) => None,
-                _ => Some(format!("Unexpected result: {:?}", result)),
-            };
-
-            restore_gitkeep(&get_uploaded_projects_dir().join(&project_id_and_dir)).await;
-
-            if let Some(msg) = panic_msg {
-                panic!("{}", msg);
+            match result {
+                Err(ProjectCheckError::ProjectDir(ProjectDirError::ProjectDirIsEmpty)) => {}
+                _ => panic!("Unexpected result: {:?}", result),
             }
         }
 
         #[tokio::test]
         #[traced_test]
         pub async fn fail_on_requirements_does_not_exist() {
+            let test_dirs = TestDirs::new("installer").await;
             let project_id_and_dir = String::from("requirements_does_not_exist");
             let (installer, _controller) =
-                create_installer_and_process_from_project_path(project_id_and_dir);
+                create_installer_and_process_from_project_path(project_id_and_dir, &test_dirs);
 
             let result = installer.check().await;
             match result {
@@ -895,80 +2592,163 @@ mod tests {
         #[tokio::test]
         #[traced_test]
         pub async fn fail_on_requirements_does_not_contain_locust() {
+            let test_dirs = TestDirs::new("installer").await;
             let project_id_and_dir = String::from("requirements_does_not_contain_locust");
             let (installer, _controller) =
-                create_installer_and_process_from_project_path(project_id_and_dir);
+                create_installer_and_process_from_project_path(project_id_and_dir, &test_dirs);
+
+            let result = installer.check().await;
+            match result {
+                Err(ProjectCheckError::Requirements(
+                    RequirementsError::LocustIsNotInRequirementsTxt,
+                )) => {}
+                _ => panic!("Unexpected result: {:?}", result),
+            }
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn fail_on_locust_dir_does_not_exist() {
+            let test_dirs = TestDirs::new("installer").await;
+            let project_id_and_dir = String::from("locust_dir_does_not_exist");
+            let (installer, _controller) =
+                create_installer_and_process_from_project_path(project_id_and_dir, &test_dirs);
+
+            let result = installer.check().await;
+            match result {
+                Err(ProjectCheckError::LocustDir(LocustDirError::LocustDirDoesNotExist)) => {}
+                _ => panic!("Unexpected result: {:?}", result),
+            }
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn fail_on_locust_dir_is_empty() {
+            let test_dirs = TestDirs::new("installer").await;
+            let project_id_and_dir = String::from("locust_dir_is_empty");
+            let (installer, _controller) =
+                create_installer_and_process_from_project_path(project_id_and_dir, &test_dirs);
+
+            let locust_dir = installer.get_locust_dir_path();
+            delete_gitkeep(&locust_dir).await;
+
+            let result = installer.check().await;
+            match result {
+                Err(ProjectCheckError::LocustDir(LocustDirError::LocustDirIsEmpty)) => {}
+                _ => panic!("Unexpected result: {:?}", result),
+            }
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn fail_on_locust_dir_contains_no_python_files() {
+            let test_dirs = TestDirs::new("installer").await;
+            let project_id_and_dir = String::from("locust_dir_is_contains_no_python_files");
+            let (installer, _controller) =
+                create_installer_and_process_from_project_path(project_id_and_dir, &test_dirs);
+
+            let result = installer.check().await;
+            match result {
+                Err(ProjectCheckError::LocustDir(LocustDirError::NoPythonFilesInLocustDir)) => {}
+                _ => panic!("Unexpected result: {:?}", result),
+            }
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn fail_on_locust_dir_contains_syntax_error() {
+            let test_dirs = TestDirs::new("installer").await;
+            let project_id_and_dir = String::from("locust_dir_contains_syntax_error");
+            let (installer, _controller) =
+                create_installer_and_process_from_project_path(project_id_and_dir, &test_dirs);
 
             let result = installer.check().await;
             match result {
-                Err(ProjectCheckError::Requirements(
-                    RequirementsError::LocustIsNotInRequirementsTxt,
-                )) => {}
+                Err(ProjectCheckError::LocustDir(LocustDirError::SyntaxError {
+                    file,
+                    message,
+                })) => {
+                    assert_eq!(file, Path::new("locust").join("nested").join("broken.py"));
+                    assert!(
+                        message.starts_with("line 1:"),
+                        "Unexpected message: {message}"
+                    );
+                }
                 _ => panic!("Unexpected result: {:?}", result),
             }
         }
 
         #[tokio::test]
         #[traced_test]
-        pub async fn fail_on_locust_dir_does_not_exist() {
-            let project_id_and_dir = String::from("locust_dir_does_not_exist");
+        pub async fn check_a_valid_project_and_expect_no_errors() {
+            let test_dirs = TestDirs::new("installer").await;
+            let project_id_and_dir = String::from("valid");
             let (installer, _controller) =
-                create_installer_and_process_from_project_path(project_id_and_dir);
+                create_installer_and_process_from_project_path(project_id_and_dir, &test_dirs);
 
             let result = installer.check().await;
             match result {
-                Err(ProjectCheckError::LocustDir(LocustDirError::LocustDirDoesNotExist)) => {}
+                Ok(ProjectKind::Requirements) => {}
                 _ => panic!("Unexpected result: {:?}", result),
             }
         }
 
         #[tokio::test]
         #[traced_test]
-        pub async fn fail_on_locust_dir_is_empty() {
-            let project_id_and_dir = String::from("locust_dir_is_empty");
+        pub async fn check_a_pyproject_project_and_expect_pyproject_kind() {
+            let test_dirs = TestDirs::new("installer").await;
+            let project_id_and_dir = String::from("valid_pyproject");
             let (installer, _controller) =
-                create_installer_and_process_from_project_path(project_id_and_dir);
-
-            let locust_dir = installer.get_locust_dir_path();
-            delete_gitkeep(&locust_dir).await;
+                create_installer_and_process_from_project_path(project_id_and_dir, &test_dirs);
 
             let result = installer.check().await;
-            let panic_msg = match result {
-                Err(ProjectCheckError::LocustDir(LocustDirError::LocustDirIsEmpty)) => None,
-                _ => Some(format!("Unexpected result: {:?}", result)),
-            };
-
-            restore_gitkeep(&get_uploaded_projects_dir().join(&locust_dir)).await;
-
-            if let Some(msg) = panic_msg {
-                panic!("{}", msg);
+            match result {
+                Ok(ProjectKind::Pyproject) => {}
+                _ => panic!("Unexpected result: {:?}", result),
             }
         }
 
         #[tokio::test]
         #[traced_test]
-        pub async fn fail_on_locust_dir_contains_no_python_files() {
-            let project_id_and_dir = String::from("locust_dir_is_contains_no_python_files");
+        pub async fn check_a_poetry_project_and_expect_poetry_kind() {
+            let test_dirs = TestDirs::new("installer").await;
+            let project_id_and_dir = String::from("valid_poetry");
             let (installer, _controller) =
-                create_installer_and_process_from_project_path(project_id_and_dir);
+                create_installer_and_process_from_project_path(project_id_and_dir, &test_dirs);
 
             let result = installer.check().await;
             match result {
-                Err(ProjectCheckError::LocustDir(LocustDirError::NoPythonFilesInLocustDir)) => {}
+                Ok(ProjectKind::Poetry) => {}
                 _ => panic!("Unexpected result: {:?}", result),
             }
         }
 
         #[tokio::test]
         #[traced_test]
-        pub async fn check_a_valid_project_and_expect_no_errors() {
+        pub async fn check_a_valid_project_with_project_checks_and_expect_only_file_count_violated()
+        {
+            let test_dirs = TestDirs::new("installer").await;
             let project_id_and_dir = String::from("valid");
-            let (installer, _controller) =
-                create_installer_and_process_from_project_path(project_id_and_dir);
+            let (mut installer, _controller) =
+                create_installer_and_process_from_project_path(project_id_and_dir, &test_dirs);
+            // The rotated io files, e.g. req_err.txt.1, are not checked.
+            installer.set_project_checks(
+                ProjectChecks::new()
+                    .with(AllowedExtensions(BTreeSet::from([
+                        String::from("py"),
+                        String::from("txt"),
+                    ])))
+                    .with(MaxFileCount { max_file_count: 4 }),
+            );
 
             let result = installer.check().await;
             match result {
-                Ok(_) => {}
+                Err(ProjectCheckError::ProjectChecks(ProjectChecksError::Violations(
+                    violations,
+                ))) => {
+                    assert_eq!(violations.len(), 1);
+                    assert_eq!(violations[0].check, "max_file_count");
+                }
                 _ => panic!("Unexpected result: {:?}", result),
             }
         }
@@ -980,9 +2760,15 @@ mod tests {
         #[tokio::test]
         #[traced_test]
         pub async fn fail_on_invalid_requirements_with_exit_code_1() {
+            let test_dirs = TestDirs::new("installer").await;
             let project_id_and_dir = String::from("invalid_requirements");
             let (mut installer, _controller) =
-                create_installer_and_process_from_project_path(project_id_and_dir);
+                create_installer_and_process_from_project_path(project_id_and_dir, &test_dirs);
+            // Without a reachable index, e.g. in CI, the failure looks transient and would be retried.
+            installer.set_pip_retry_config(PipRetryConfig {
+                max_attempts: 1,
+                ..PipRetryConfig::default()
+            });
 
             let result = installer.check_and_install().await;
 
@@ -1017,9 +2803,10 @@ mod tests {
         #[tokio::test]
         #[traced_test]
         pub async fn kill_installation_and_expect_killed() {
+            let test_dirs = TestDirs::new("installer").await;
             let project_id_and_dir = String::from("valid");
             let (mut installer, mut controller) =
-                create_installer_and_process_from_project_path(project_id_and_dir);
+                create_installer_and_process_from_project_path(project_id_and_dir, &test_dirs);
 
             tokio::spawn(async move {
                 tokio::time::sleep(Duration::from_secs(2)).await;
@@ -1048,13 +2835,13 @@ mod tests {
                 Err(CheckAndInstallError::InstallError(
                     InstallError::ErrorThatTriggersCleanUp(
                         ErrorThatTriggersCleanUp::RequirementsInstallError(
-                            SubInstallError::Killed(_),
+                            SubInstallError::Killed(_, _),
                         ),
                     ),
                 )) => {}
                 Err(CheckAndInstallError::InstallError(
                     InstallError::ErrorThatTriggersCleanUp(
-                        ErrorThatTriggersCleanUp::VenvInstallError(SubInstallError::Killed(_)),
+                        ErrorThatTriggersCleanUp::VenvInstallError(SubInstallError::Killed(_, _)),
                     ),
                 )) => {}
                 _ => panic!("Unexpected result: {:?}", result),
@@ -1064,16 +2851,16 @@ mod tests {
         #[tokio::test]
         #[traced_test]
         pub async fn valid() {
+            let test_dirs = TestDirs::new("installer").await;
             let project_id_and_dir = String::from("valid");
             let (mut installer, _controller) =
-                create_installer_and_process_from_project_path(project_id_and_dir);
+                create_installer_and_process_from_project_path(project_id_and_dir, &test_dirs);
 
             if let Err(e) = installer.check_and_install().await {
                 panic!("Unexpected error: {:?}", e);
             }
 
-            installer
-                .delete_environment_dir_if_exists()
+            fs::remove_dir_all(&installer.project_env_dir)
                 .await
                 .expect("Could not delete environment dir");
 
@@ -1089,5 +2876,995 @@ mod tests {
                 .expect("Could not get req err");
             println!("req_err: {}", req_err);
         }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn exceed_timeout_and_expect_timed_out_in_venv_phase() {
+            let test_dirs = TestDirs::new("installer").await;
+            let (mut installer, _controller) = LocalProjectInstaller::new(
+                String::from("valid"),
+                test_dirs.uploaded_projects_dir().join("valid"),
+                test_dirs.installed_projects_dir().join("valid_timed_out"),
+                test_dirs.environments_dir().join("valid_timed_out"),
+                None,
+                ProcessIoConfig::default(),
+            );
+            installer.set_timeout(Some(Duration::from_millis(100)));
+
+            let result = installer.check_and_install().await;
+
+            match result {
+                Err(CheckAndInstallError::InstallError(InstallError::TimedOut {
+                    phase: InstallPhase::Venv,
+                    elapsed,
+                })) => {
+                    assert!(elapsed >= Duration::from_millis(100));
+                }
+                _ => panic!("Unexpected result: {:?}", result),
+            }
+
+            assert!(
+                !fs::try_exists(test_dirs.environments_dir().join("valid_timed_out"))
+                    .await
+                    .expect("Could not check if environment dir exists")
+            );
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn install_unchanged_requirements_and_expect_already_installed() {
+            let test_dirs = TestDirs::new("installer").await;
+            let project_env_dir = test_dirs.environments_dir().join("valid_already_installed");
+            let (mut installer, _controller) = LocalProjectInstaller::new(
+                String::from("valid"),
+                test_dirs.uploaded_projects_dir().join("valid"),
+                test_dirs
+                    .installed_projects_dir()
+                    .join("valid_already_installed"),
+                project_env_dir.clone(),
+                None,
+                ProcessIoConfig::default(),
+            );
+
+            // A healthy environment, installed from the current requirements.
+            let python_path =
+                LocalProjectInstaller::create_os_specific_python_path(&project_env_dir);
+            fs::create_dir_all(python_path.parent().expect("Python path has no parent"))
+                .await
+                .expect("Could not create environment dir");
+            File::create(&python_path)
+                .await
+                .expect("Could not create python");
+            let requirements_hash = requirements_hash::compute(
+                &installer.get_dependency_file_paths(ProjectKind::Requirements),
+                &installer.python_config.interpreter_path,
+                &installer.uploaded_project_dir,
+                &installer.cancellation_token,
+            )
+            .await
+            .expect("Could not compute requirements hash");
+            requirements_hash::write(
+                &installer.get_requirements_hash_file_path(),
+                &requirements_hash,
+            )
+            .await
+            .expect("Could not write requirements hash");
+
+            let result = installer.check_and_install().await;
+
+            match result {
+                Ok(InstallOutcome::AlreadyInstalled) => {}
+                _ => panic!("Unexpected result: {:?}", result),
+            }
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn require_future_python_and_expect_python_version_unsupported() {
+            let test_dirs = TestDirs::new("installer").await;
+            let project_env_dir = test_dirs
+                .environments_dir()
+                .join("valid_python_unsupported");
+            let (mut installer, _controller) = LocalProjectInstaller::new(
+                String::from("valid"),
+                test_dirs.uploaded_projects_dir().join("valid"),
+                test_dirs
+                    .installed_projects_dir()
+                    .join("valid_python_unsupported"),
+                project_env_dir.clone(),
+                None,
+                ProcessIoConfig::default(),
+            );
+            installer.set_python_config(PythonConfig {
+                min_version: Some(PythonVersion::new(99, 0, 0)),
+                ..PythonConfig::default()
+            });
+
+            let result = installer.install().await;
+
+            match result {
+                Err(InstallError::PythonVersionUnsupported {
+                    min_version: Some(min_version),
+                    ..
+                }) => {
+                    assert_eq!(min_version, PythonVersion::new(99, 0, 0));
+                }
+                _ => panic!("Unexpected result: {:?}", result),
+            }
+
+            assert!(!fs::try_exists(&project_env_dir)
+                .await
+                .expect("Could not check if environment dir exists"));
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn install_from_missing_wheelhouse_and_expect_no_index_used() {
+            let test_dirs = TestDirs::new("installer").await;
+            let project_id_and_dir = String::from("valid_offline");
+            let (mut installer, _controller) =
+                create_installer_and_process_from_project_path(project_id_and_dir, &test_dirs);
+            installer.set_pip_options(PipOptions {
+                wheelhouse_dir: Some(get_tests_dir().join("wheelhouse_does_not_exist")),
+                ..PipOptions::default()
+            });
+
+            let result = installer.check_and_install().await;
+
+            let req_err = installer
+                .get_req_err_from_file()
+                .await
+                .expect("Could not get req err");
+            println!("req_err: {}", req_err);
+
+            match result {
+                Err(CheckAndInstallError::InstallError(
+                    InstallError::ErrorThatTriggersCleanUp(
+                        ErrorThatTriggersCleanUp::RequirementsInstallError(
+                            SubInstallError::TerminatedWithError(
+                                TerminationWithErrorStatus::TerminatedWithErrorCode(1),
+                            ),
+                        ),
+                    ),
+                )) => {}
+                _ => panic!("Unexpected result: {:?}", result),
+            }
+
+            assert!(req_err.contains("No matching distribution found for locust"));
+            assert!(!req_err.contains("NewConnectionError"));
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn read_installed_lockfile_and_expect_installed_packages() {
+            let test_dirs = TestDirs::new("installer").await;
+            // Environments are ignored by git, like the installed project dirs of real installations.
+            let installed_project_dir = test_dirs
+                .environments_dir()
+                .join("valid_lockfile_installed");
+            let (installer, _controller) = LocalProjectInstaller::new(
+                String::from("valid"),
+                test_dirs.uploaded_projects_dir().join("valid"),
+                installed_project_dir.clone(),
+                test_dirs.environments_dir().join("valid_lockfile"),
+                None,
+                ProcessIoConfig::default(),
+            );
+            fs::create_dir_all(&installed_project_dir)
+                .await
+                .expect("Could not create installed project dir");
+            fs::write(
+                installer.get_installed_lock_file_path(),
+                "locust==2.15.1\nrequests==2.31.0\n",
+            )
+            .await
+            .expect("Could not write lockfile");
+
+            let installed_packages = installer.installed_packages().await;
+
+            let installed_packages = installed_packages.expect("Could not read installed packages");
+            assert_eq!(installed_packages.len(), 2);
+            assert_eq!(installed_packages[0].name, "locust");
+            assert_eq!(installed_packages[0].version, "2.15.1");
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn require_more_disk_space_than_available_and_expect_insufficient_disk_space() {
+            let test_dirs = TestDirs::new("installer").await;
+            let project_env_dir = test_dirs
+                .environments_dir()
+                .join("valid_insufficient_disk_space");
+            let (mut installer, _controller) = LocalProjectInstaller::new(
+                String::from("valid"),
+                test_dirs.uploaded_projects_dir().join("valid"),
+                test_dirs
+                    .installed_projects_dir()
+                    .join("valid_insufficient_disk_space"),
+                project_env_dir.clone(),
+                None,
+                ProcessIoConfig::default(),
+            );
+            installer.set_disk_space_config(DiskSpaceConfig {
+                min_headroom_bytes: u64::MAX,
+                ..DiskSpaceConfig::default()
+            });
+
+            let result = installer.install().await;
+
+            match result {
+                Err(InstallError::InsufficientDiskSpace {
+                    required,
+                    available,
+                }) => {
+                    assert_eq!(required, u64::MAX);
+                    assert!(available < required);
+                }
+                _ => panic!("Unexpected result: {:?}", result),
+            }
+
+            assert!(!fs::try_exists(&project_env_dir)
+                .await
+                .expect("Could not check if environment dir exists"));
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn exceed_env_size_quota_and_expect_quota_exceeded() {
+            let test_dirs = TestDirs::new("installer").await;
+            let project_env_dir = test_dirs.environments_dir().join("valid_quota_exceeded");
+            let (mut installer, _controller) = LocalProjectInstaller::new(
+                String::from("valid"),
+                test_dirs.uploaded_projects_dir().join("valid"),
+                test_dirs
+                    .installed_projects_dir()
+                    .join("valid_quota_exceeded"),
+                project_env_dir.clone(),
+                None,
+                ProcessIoConfig::default(),
+            );
+            // The virtual environment alone is bigger, pip is installed into it.
+            installer.set_env_size_quota(Some(EnvSizeQuota {
+                max_size_bytes: 1024,
+                check_interval: Duration::from_millis(100),
+            }));
+
+            let result = installer.install().await;
+
+            match result {
+                Err(InstallError::QuotaExceeded { size, max_size }) => {
+                    assert_eq!(max_size, 1024);
+                    assert!(size > max_size);
+                }
+                _ => panic!("Unexpected result: {:?}", result),
+            }
+
+            assert!(!fs::try_exists(&project_env_dir)
+                .await
+                .expect("Could not check if environment dir exists"));
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn fail_clean_up_after_abort_and_expect_aborted_clean_up_error() {
+            let test_dirs = TestDirs::new("installer").await;
+            let (mut installer, mut controller) =
+                create_installer_and_process_from_project_path(String::from("valid"), &test_dirs);
+            // A file is not deleted by remove_dir_all, the clean up is retried until it is cancelled.
+            fs::write(&installer.staging_env_dir, "")
+                .await
+                .expect("Could not write staging file");
+            let mut status_receiver = controller.subscribe_status();
+            let cancel_task = tokio::spawn(async move {
+                status_receiver
+                    .wait_for(|status| *status == InstallerStatus::CleaningUp)
+                    .await
+                    .expect("Installer dropped before cleaning up");
+                controller
+                    .cancel()
+                    .await
+                    .expect("Could not cancel the clean up");
+            });
+
+            let error = installer
+                .clean_up_on_abort_and_return_error(InstallError::TimedOut {
+                    phase: InstallPhase::Requirements,
+                    elapsed: Duration::from_secs(1),
+                })
+                .await;
+            cancel_task.await.expect("Cancel task panicked");
+
+            match error {
+                InstallError::AbortedCleanUpError {
+                    aborted,
+                    clean_up_error: CleanUpError::CouldNotDeleteEnvironment { .. },
+                } => {
+                    assert!(matches!(*aborted, InstallError::TimedOut { .. }));
+                }
+                _ => panic!("Unexpected error: {:?}", error),
+            }
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn fail_installation_and_expect_no_staging_dir_left() {
+            let test_dirs = TestDirs::new("installer").await;
+            let project_env_dir = test_dirs.environments_dir().join("valid_staging_removed");
+            let (mut installer, _controller) = LocalProjectInstaller::new(
+                String::from("valid"),
+                test_dirs.uploaded_projects_dir().join("valid"),
+                test_dirs
+                    .installed_projects_dir()
+                    .join("valid_staging_removed"),
+                project_env_dir.clone(),
+                None,
+                ProcessIoConfig::default(),
+            );
+            installer.set_env_size_quota(Some(EnvSizeQuota {
+                max_size_bytes: 1024,
+                check_interval: Duration::from_millis(100),
+            }));
+
+            let result = installer.install().await;
+
+            assert!(
+                matches!(result, Err(InstallError::QuotaExceeded { .. })),
+                "Unexpected result: {:?}",
+                result
+            );
+            assert!(!fs::try_exists(&installer.staging_env_dir)
+                .await
+                .expect("Could not check if staging dir exists"));
+            assert!(!fs::try_exists(&project_env_dir)
+                .await
+                .expect("Could not check if environment dir exists"));
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn fail_installation_and_expect_failed_report() {
+            let test_dirs = TestDirs::new("installer").await;
+            let installed_project_dir = test_dirs.environments_dir().join("valid_report_installed");
+            let (mut installer, _controller) = LocalProjectInstaller::new(
+                String::from("valid"),
+                test_dirs.uploaded_projects_dir().join("valid"),
+                installed_project_dir.clone(),
+                test_dirs.environments_dir().join("valid_report"),
+                None,
+                ProcessIoConfig::default(),
+            );
+            installer.set_env_size_quota(Some(EnvSizeQuota {
+                max_size_bytes: 1024,
+                check_interval: Duration::from_millis(100),
+            }));
+
+            let result = installer.install().await;
+            let install_record = installer.load_report().await;
+
+            assert!(
+                matches!(result, Err(InstallError::QuotaExceeded { .. })),
+                "Unexpected result: {:?}",
+                result
+            );
+            let install_record = install_record.expect("Could not load report");
+            assert_eq!(install_record.id, "valid");
+            assert!(matches!(
+                install_record.status,
+                InstallRecordStatus::Failed { .. }
+            ));
+            assert_eq!(
+                install_record
+                    .phases
+                    .iter()
+                    .map(|phase_record| (phase_record.phase, phase_record.exit_code))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (InstallPhase::Venv, Some(0)),
+                    (InstallPhase::Requirements, None)
+                ]
+            );
+            assert_eq!(install_record.package_count, None);
+            assert_eq!(
+                install_record.log_files.req_err,
+                test_dirs
+                    .uploaded_projects_dir()
+                    .join("valid")
+                    .join("req_err.txt")
+            );
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn fail_installation_and_expect_ordered_events() {
+            let test_dirs = TestDirs::new("installer").await;
+            let (event_sender, mut event_receiver) = mpsc::channel(1024);
+            let (mut installer, _controller) = LocalProjectInstaller::new(
+                String::from("valid"),
+                test_dirs.uploaded_projects_dir().join("valid"),
+                test_dirs.environments_dir().join("valid_events_installed"),
+                test_dirs.environments_dir().join("valid_events"),
+                Some(event_sender),
+                ProcessIoConfig::default(),
+            );
+            installer.set_env_size_quota(Some(EnvSizeQuota {
+                max_size_bytes: 1024,
+                check_interval: Duration::from_millis(100),
+            }));
+
+            let result = installer.install().await;
+            drop(installer);
+
+            let mut installer_events = Vec::new();
+            while let Some(installer_event) = event_receiver.recv().await {
+                installer_events.push(installer_event);
+            }
+
+            assert!(
+                matches!(result, Err(InstallError::QuotaExceeded { .. })),
+                "Unexpected result: {:?}",
+                result
+            );
+            let installer_events_without_output = installer_events
+                .into_iter()
+                .filter(|installer_event| {
+                    !matches!(installer_event, InstallerEvent::OutputLine { .. })
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                &installer_events_without_output[..4],
+                &[
+                    InstallerEvent::PhaseStarted {
+                        phase: InstallPhase::Venv
+                    },
+                    InstallerEvent::PhaseFinished {
+                        phase: InstallPhase::Venv,
+                        exit_code: Some(0)
+                    },
+                    InstallerEvent::PhaseStarted {
+                        phase: InstallPhase::Requirements
+                    },
+                    InstallerEvent::PhaseFinished {
+                        phase: InstallPhase::Requirements,
+                        exit_code: None
+                    },
+                ]
+            );
+            assert_eq!(
+                installer_events_without_output[4],
+                InstallerEvent::CleanupStarted
+            );
+            assert!(matches!(
+                installer_events_without_output[5],
+                InstallerEvent::Failed { .. }
+            ));
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn resume_failed_requirements_phase_and_expect_venv_phase_skipped() {
+            let test_dirs = TestDirs::new("installer").await;
+            let project_env_dir = test_dirs
+                .environments_dir()
+                .join("invalid_requirements_resumed");
+            let installed_project_dir = test_dirs
+                .environments_dir()
+                .join("invalid_requirements_resumed_installed");
+            let (mut installer, _controller) = LocalProjectInstaller::new(
+                String::from("invalid_requirements"),
+                test_dirs
+                    .uploaded_projects_dir()
+                    .join("invalid_requirements"),
+                installed_project_dir.clone(),
+                project_env_dir.clone(),
+                None,
+                ProcessIoConfig::default(),
+            );
+            installer.set_pip_retry_config(PipRetryConfig {
+                max_attempts: 1,
+                ..PipRetryConfig::default()
+            });
+            installer.set_keep_env_on_requirements_failure(true);
+
+            let install_result = installer.install().await;
+            let kept_staging_dir = installer.staging_env_dir.clone();
+            let kept_staging_dir_exists = fs::try_exists(&kept_staging_dir).await;
+
+            let resume_result = installer.resume().await;
+            let install_record = installer.load_report().await;
+            let resumed_staging_dir = installer.staging_env_dir.clone();
+
+            for result in [install_result, resume_result] {
+                assert!(
+                    matches!(
+                        result,
+                        Err(InstallError::ErrorThatTriggersCleanUp(
+                            ErrorThatTriggersCleanUp::RequirementsInstallError(_)
+                        ))
+                    ),
+                    "Unexpected result: {:?}",
+                    result
+                );
+            }
+            assert!(kept_staging_dir_exists.expect("Could not check if staging dir exists"));
+            assert_eq!(resumed_staging_dir, kept_staging_dir);
+            assert_eq!(
+                install_record
+                    .expect("Could not load report")
+                    .phases
+                    .iter()
+                    .map(|phase_record| phase_record.phase)
+                    .collect::<Vec<_>>(),
+                vec![InstallPhase::Requirements]
+            );
+            assert!(!fs::try_exists(&project_env_dir)
+                .await
+                .expect("Could not check if environment dir exists"));
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn copy_project_to_installed_dir_and_expect_project_without_io_files() {
+            let test_dirs = TestDirs::new("installer").await;
+            let installed_project_dir = test_dirs.environments_dir().join("valid_copied_installed");
+            let (installer, _controller) = LocalProjectInstaller::new(
+                String::from("valid"),
+                test_dirs.uploaded_projects_dir().join("valid"),
+                installed_project_dir.clone(),
+                test_dirs.environments_dir().join("valid_copied"),
+                None,
+                ProcessIoConfig::default(),
+            );
+            fs::create_dir_all(&installed_project_dir)
+                .await
+                .expect("Could not create installed project dir");
+            fs::write(installed_project_dir.join("stale.py"), "")
+                .await
+                .expect("Could not write stale file");
+            fs::write(installer.get_installed_lock_file_path(), "locust==2.15.1\n")
+                .await
+                .expect("Could not write lockfile");
+            installer
+                .create_io_files()
+                .await
+                .expect("Could not create io files");
+
+            let copy_result = installer.copy_project_to_installed_dir().await;
+            let file_exists = |file: &str| fs::try_exists(installed_project_dir.join(file));
+            let requirements_exists = file_exists("requirements.txt").await;
+            let locust_dir_exists = file_exists("locust").await;
+            let lockfile_exists = file_exists("installed_lock.txt").await;
+            let stale_file_exists = file_exists("stale.py").await;
+            let io_file_exists = file_exists("req_out.txt").await;
+            let verify_result = installer.verify_installed_project().await;
+
+            copy_result.expect("Could not copy project");
+            assert!(requirements_exists.expect("Could not check requirements"));
+            assert!(locust_dir_exists.expect("Could not check locust dir"));
+            assert!(lockfile_exists.expect("Could not check lockfile"));
+            assert!(!stale_file_exists.expect("Could not check stale file"));
+            assert!(!io_file_exists.expect("Could not check io file"));
+            verify_result.expect("Installed project does not match its manifest");
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn fail_installation_and_expect_finished_status() {
+            let test_dirs = TestDirs::new("installer").await;
+            let installed_project_dir = test_dirs.environments_dir().join("valid_status_installed");
+            let (mut installer, controller) = LocalProjectInstaller::new(
+                String::from("valid"),
+                test_dirs.uploaded_projects_dir().join("valid"),
+                installed_project_dir.clone(),
+                test_dirs.environments_dir().join("valid_status"),
+                None,
+                ProcessIoConfig::default(),
+            );
+            installer.set_env_size_quota(Some(EnvSizeQuota {
+                max_size_bytes: 1024,
+                check_interval: Duration::from_millis(100),
+            }));
+
+            let status_before_install = controller.status();
+            let result = installer.install().await;
+
+            assert_eq!(status_before_install, InstallerStatus::Pending);
+            let error = result.expect_err("Installation did not fail");
+            assert_eq!(
+                controller.status(),
+                InstallerStatus::Finished(Err(error.to_string()))
+            );
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn plan_a_valid_project_and_expect_commands_and_no_staging_dir() {
+            let test_dirs = TestDirs::new("installer").await;
+            let project_env_dir = test_dirs.environments_dir().join("valid_plan");
+            let (mut installer, _controller) = LocalProjectInstaller::new(
+                String::from("valid"),
+                test_dirs.uploaded_projects_dir().join("valid"),
+                test_dirs.environments_dir().join("valid_plan_installed"),
+                project_env_dir.clone(),
+                None,
+                ProcessIoConfig::default(),
+            );
+            installer.set_installer_backend(InstallerBackend::Pip);
+
+            let install_plan = installer.plan().await.expect("Error planning installation");
+
+            let staging_env_dir_str = install_plan
+                .staging_env_dir
+                .to_str()
+                .expect("Staging dir is not valid unicode");
+            let env_python_path = LocalProjectInstaller::create_os_specific_python_path(
+                &install_plan.staging_env_dir,
+            );
+            assert_eq!(install_plan.project_kind, ProjectKind::Requirements);
+            assert_eq!(install_plan.installer_backend, InstallerBackend::Pip);
+            assert_eq!(install_plan.env_dir, project_env_dir);
+            assert_eq!(
+                install_plan.venv_command.args,
+                vec!["-m", "venv", staging_env_dir_str]
+            );
+            assert_eq!(
+                install_plan.requirements_command.program,
+                env_python_path
+                    .to_str()
+                    .expect("Python path is not valid unicode")
+            );
+            assert_eq!(
+                install_plan.requirements_command.args[..4],
+                ["-m", "pip", "install", "-r"]
+            );
+            assert!(!fs::try_exists(&install_plan.staging_env_dir)
+                .await
+                .expect("Error checking staging dir"));
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn create_io_files_twice_and_expect_rotated_log_files() {
+            let test_dirs = TestDirs::new("installer").await;
+            let uploaded_project_dir = test_dirs.environments_dir().join("logs_uploaded");
+            let (installer, _controller) = LocalProjectInstaller::new(
+                String::from("logs"),
+                uploaded_project_dir.clone(),
+                test_dirs.environments_dir().join("logs_installed"),
+                test_dirs.environments_dir().join("logs"),
+                None,
+                ProcessIoConfig::default(),
+            );
+            fs::create_dir_all(&uploaded_project_dir)
+                .await
+                .expect("Could not create uploaded project dir");
+
+            installer
+                .create_io_files()
+                .await
+                .expect("Could not create io files");
+            fs::write(
+                installer.get_req_err_file_path(),
+                "ERROR: No matching distribution",
+            )
+            .await
+            .expect("Could not write io file");
+            installer
+                .create_io_files()
+                .await
+                .expect("Could not create io files");
+
+            let log_files = installer.list_log_files().await;
+            let rotated_req_err =
+                fs::read_to_string(uploaded_project_dir.join("req_err.txt.1")).await;
+
+            let log_files = log_files.expect("Could not list log files");
+            assert_eq!(log_files.len(), 5);
+            assert_eq!(
+                log_files
+                    .iter()
+                    .filter(|log_file| log_file.rotation == 1)
+                    .map(|log_file| log_file.path.clone())
+                    .collect::<Vec<_>>(),
+                vec![uploaded_project_dir.join("req_err.txt.1")]
+            );
+            assert_eq!(
+                rotated_req_err.expect("Could not read rotated io file"),
+                "ERROR: No matching distribution"
+            );
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn check_and_install_an_archive_without_locust_and_expect_extracted_and_checked()
+        {
+            let test_dirs = TestDirs::new("installer").await;
+            let archive_dir = test_dirs
+                .environments_dir()
+                .join("archive_uploaded.extracted");
+            let archive_path = test_dirs.environments_dir().join("archive_uploaded.zip");
+            let mut zip_writer = zip::ZipWriter::new(
+                std::fs::File::create(&archive_path).expect("Could not create archive"),
+            );
+            zip_writer
+                .start_file("requirements.txt", zip::write::FileOptions::default())
+                .expect("Could not start file");
+            std::io::Write::write_all(&mut zip_writer, b"requests==2.31.0\n")
+                .expect("Could not write file");
+            zip_writer.finish().expect("Could not finish archive");
+
+            let (mut installer, _controller) = LocalProjectInstaller::new(
+                String::from("archive"),
+                archive_dir.clone(),
+                test_dirs.environments_dir().join("archive_installed"),
+                test_dirs.environments_dir().join("archive"),
+                None,
+                ProcessIoConfig::default(),
+            );
+            installer.set_project_source(ProjectSource::Archive(archive_path.clone()));
+
+            let result = installer.check_and_install().await;
+            let requirements_exists = fs::try_exists(archive_dir.join("requirements.txt")).await;
+
+            assert!(
+                matches!(
+                    result,
+                    Err(CheckAndInstallError::CheckError(
+                        ProjectCheckError::Requirements(
+                            RequirementsError::LocustIsNotInRequirementsTxt
+                        )
+                    ))
+                ),
+                "Unexpected result: {:?}",
+                result
+            );
+            assert!(requirements_exists.expect("Could not check requirements"));
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        pub async fn check_a_tampered_project_with_manifest_and_expect_manifest_error() {
+            let test_dirs = TestDirs::new("installer").await;
+            let uploaded_project_dir = test_dirs.environments_dir().join("manifest_uploaded");
+            copy_dir_all(
+                &test_dirs.uploaded_projects_dir().join("valid"),
+                &uploaded_project_dir,
+                |_| false,
+            )
+            .await
+            .expect("Could not copy project");
+            let (mut installer, _controller) = LocalProjectInstaller::new(
+                String::from("manifest"),
+                uploaded_project_dir.clone(),
+                test_dirs.environments_dir().join("manifest_installed"),
+                test_dirs.environments_dir().join("manifest"),
+                None,
+                ProcessIoConfig::default(),
+            );
+            installer.set_verify_manifest(true);
+
+            let result_without_manifest = installer.check().await;
+            manifest::write_manifest(&uploaded_project_dir)
+                .await
+                .expect("Could not write manifest");
+            // Io files are written by the installer after the upload.
+            fs::write(installer.get_req_out_file_path(), "Collecting locust")
+                .await
+                .expect("Could not write io file");
+            let result_with_manifest = installer.check().await;
+            fs::write(
+                uploaded_project_dir.join("requirements.txt"),
+                "locust\nrequests\n",
+            )
+            .await
+            .expect("Could not write requirements");
+            let result_after_tampering = installer.check().await;
+
+            assert!(
+                matches!(
+                    result_without_manifest,
+                    Err(ProjectCheckError::Manifest(
+                        ManifestError::CouldNotReadManifest(_)
+                    ))
+                ),
+                "Unexpected result: {:?}",
+                result_without_manifest
+            );
+            result_with_manifest.expect("Project with manifest is not valid");
+            match result_after_tampering {
+                Err(ProjectCheckError::Manifest(ManifestError::Mismatch(manifest_mismatch))) => {
+                    assert_eq!(manifest_mismatch.modified, vec!["requirements.txt"])
+                }
+                _ => panic!("Unexpected result: {:?}", result_after_tampering),
+            }
+        }
+        #[tokio::test]
+        #[traced_test]
+        pub async fn install_with_existing_shared_env_and_expect_linked_without_installing() {
+            let test_dirs = TestDirs::new("installer").await;
+            let test_dir = test_dirs.environments_dir().join("valid_shared");
+            let env_store = EnvStore::new(test_dir.join("shared_environments"));
+            let project_env_dir = test_dir.join("environments").join("valid");
+            let installed_project_dir = test_dir.join("installed_projects").join("valid");
+            let (mut installer, _controller) = LocalProjectInstaller::new(
+                String::from("valid"),
+                test_dirs.uploaded_projects_dir().join("valid"),
+                installed_project_dir.clone(),
+                project_env_dir.clone(),
+                None,
+                ProcessIoConfig::default(),
+            );
+            installer.set_env_store(Some(env_store.clone()));
+            let requirements_hash = requirements_hash::compute(
+                &installer.get_dependency_file_paths(ProjectKind::Requirements),
+                &installer.python_config.interpreter_path,
+                &installer.uploaded_project_dir,
+                &installer.cancellation_token,
+            )
+            .await
+            .expect("Could not compute requirements hash");
+            // Installed by another project with the same requirements.
+            let shared_python_path = LocalProjectInstaller::create_os_specific_python_path(
+                &env_store.env_dir(&requirements_hash),
+            );
+            fs::create_dir_all(shared_python_path.parent().expect("No parent dir"))
+                .await
+                .expect("Could not create shared environment");
+            fs::write(&shared_python_path, "")
+                .await
+                .expect("Could not write python");
+
+            let check_and_install_result = installer.check_and_install().await;
+            let linked_env_dir = fs::read_link(&project_env_dir).await;
+            let users = env_store.users(&requirements_hash).await;
+            let requirements_exists =
+                fs::try_exists(installed_project_dir.join("requirements.txt")).await;
+
+            assert!(
+                matches!(
+                    check_and_install_result,
+                    Ok(InstallOutcome::AlreadyInstalled)
+                ),
+                "Unexpected result: {:?}",
+                check_and_install_result
+            );
+            assert_eq!(
+                linked_env_dir.expect("Environment is not linked"),
+                env_store.env_dir(&requirements_hash)
+            );
+            assert!(users.expect("Could not read users").contains("valid"));
+            assert!(requirements_exists.expect("Could not check requirements"));
+        }
+        #[tokio::test]
+        #[traced_test]
+        pub async fn exceed_quota_with_never_cleanup_policy_and_expect_kept_environment() {
+            let test_dirs = TestDirs::new("installer").await;
+            let (mut installer, _controller) = LocalProjectInstaller::new(
+                String::from("valid"),
+                test_dirs.uploaded_projects_dir().join("valid"),
+                test_dirs.installed_projects_dir().join("valid_kept"),
+                test_dirs.environments_dir().join("valid_kept"),
+                None,
+                ProcessIoConfig::default(),
+            );
+            installer.set_cleanup_policy(CleanupPolicy::Never);
+            installer.set_env_size_quota(Some(EnvSizeQuota {
+                max_size_bytes: 1024,
+                check_interval: Duration::from_millis(100),
+            }));
+
+            let result = installer.install().await;
+            let staging_dir_exists = fs::try_exists(&installer.staging_env_dir).await;
+
+            match &result {
+                Err(InstallError::KeptEnvironment { error, env_dir }) => {
+                    assert!(matches!(**error, InstallError::QuotaExceeded { .. }));
+                    assert_eq!(env_dir, &installer.staging_env_dir);
+                }
+                _ => panic!("Unexpected result: {:?}", result),
+            }
+            assert!(staging_dir_exists.expect("Could not check if staging dir exists"));
+        }
+        #[tokio::test]
+        #[traced_test]
+        pub async fn read_dir_state_and_expect_remaining_entries_counted_recursively() {
+            let test_dirs = TestDirs::new("installer").await;
+            let dir = test_dirs.environments_dir().join("dir_state");
+            fs::create_dir_all(dir.join("bin"))
+                .await
+                .expect("Could not create dir");
+            fs::write(dir.join("bin").join("python"), "")
+                .await
+                .expect("Could not write file");
+
+            let remaining_dir_state = DirState::of(&dir).await;
+            let _ = fs::remove_dir_all(&dir).await;
+            let deleted_dir_state = DirState::of(&dir).await;
+
+            assert_eq!(remaining_dir_state, DirState::Remaining { entry_count: 2 });
+            assert_eq!(deleted_dir_state, DirState::Deleted);
+        }
+        #[tokio::test]
+        #[traced_test]
+        pub async fn install_a_project_with_spaces_in_id_and_expect_pip_run_in_env_with_spaces() {
+            let test_dirs = TestDirs::new("installer").await;
+            let project_id = String::from("valid offline with spaces");
+            let uploaded_project_dir = test_dirs.environments_dir().join("uploaded valid offline");
+            let project_env_dir = test_dirs.environments_dir().join(&project_id);
+            copy_dir_all(
+                &test_dirs.uploaded_projects_dir().join("valid_offline"),
+                &uploaded_project_dir,
+                |_| false,
+            )
+            .await
+            .expect("Could not copy uploaded project");
+            let (mut installer, _controller) = LocalProjectInstaller::new(
+                project_id.clone(),
+                uploaded_project_dir.clone(),
+                test_dirs.installed_projects_dir().join(&project_id),
+                project_env_dir,
+                None,
+                ProcessIoConfig::default(),
+            );
+            installer.set_installer_backend(InstallerBackend::Pip);
+            installer.set_pip_options(PipOptions {
+                wheelhouse_dir: Some(get_tests_dir().join("wheelhouse does not exist")),
+                ..PipOptions::default()
+            });
+
+            let install_plan = installer.plan().await.expect("Error planning installation");
+            let result = installer.check_and_install().await;
+            let req_err = installer.get_req_err_from_file().await;
+
+            let staging_env_dir_str = install_plan
+                .staging_env_dir
+                .to_str()
+                .expect("Staging dir is not valid unicode");
+            assert!(staging_env_dir_str.contains("valid offline with spaces"));
+            assert_eq!(
+                install_plan.venv_command.args.last().map(String::as_str),
+                Some(staging_env_dir_str)
+            );
+            assert!(install_plan
+                .requirements_command
+                .program
+                .starts_with(staging_env_dir_str));
+            match result {
+                Err(CheckAndInstallError::InstallError(
+                    InstallError::ErrorThatTriggersCleanUp(
+                        ErrorThatTriggersCleanUp::RequirementsInstallError(
+                            SubInstallError::TerminatedWithError(
+                                TerminationWithErrorStatus::TerminatedWithErrorCode(1),
+                            ),
+                        ),
+                    ),
+                )) => {}
+                _ => panic!("Unexpected result: {:?}", result),
+            }
+            assert!(req_err
+                .expect("Could not get req err")
+                .contains("No matching distribution found for locust"));
+        }
+        #[tokio::test]
+        #[traced_test]
+        pub async fn fail_requirements_phase_and_expect_metrics_of_every_phase() {
+            let test_dirs = TestDirs::new("installer").await;
+            let project_id_and_dir = String::from("valid_offline");
+            let (mut installer, _controller) =
+                create_installer_and_process_from_project_path(project_id_and_dir, &test_dirs);
+            installer.set_pip_options(PipOptions {
+                wheelhouse_dir: Some(get_tests_dir().join("wheelhouse_does_not_exist")),
+                ..PipOptions::default()
+            });
+
+            let result = installer.check_and_install().await;
+            let metrics = installer.metrics();
+
+            assert!(result.is_err(), "Installation did not fail");
+            assert!(metrics.check.is_some());
+            assert!(metrics.venv.is_some());
+            assert!(metrics.requirements.is_some());
+            assert!(metrics.clean_up.is_some());
+            assert!(metrics.total() >= metrics.venv.unwrap_or_default());
+        }
     }
 }
@@ -1,20 +1,133 @@
-use std::{collections::HashMap, io::Error as IoError, path::PathBuf, sync::Arc};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    future::Future,
+    io::{Error as IoError, ErrorKind},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 use thiserror::Error as ThisError;
 use tokio::{
     fs,
-    sync::{mpsc, RwLock},
+    sync::{broadcast, mpsc, watch, Notify, RwLock},
+    task::JoinHandle,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{info_span, Instrument};
+use uuid::Uuid;
+
+use super::{
+    archive::ArchiveLimits,
+    artifact_store::{ArtifactRetention, ArtifactSource, ArtifactStore, ArtifactStoreError},
+    audit::AuditConfig,
+    blob_store::{self, BlobStore, BlobStoreError},
+    cleanup_policy::CleanupPolicy,
+    disk_space,
+    env_store::{EnvStore, EnvStoreError},
+    install_queue::{InstallQueue, InstallTicket},
+    install_record::{self, InstallRecord, LoadReportError},
+    installer_backend::InstallerBackend,
+    installer_events::InstallerEvent,
+    local_project_installer::{
+        CheckAndInstallError, CleanUpError, InstallError, InstallerStatus, LocalProjectInstaller,
+        LocalProjectInstallerController, ProjectCheckError,
+        SendingCancellationSignalToInstallerError,
+    },
+    local_project_runner::{
+        LocalProjectRunner, RunController, RunError, RunOutcome, RunnerStatus, StopRunError,
+    },
+    log_rotation::LogRotationConfig,
+    manifest,
+    pip_cache::{self, PipCacheConfig},
+    pip_options::PipOptions,
+    pip_retry::PipRetryConfig,
+    project_checks::ProjectChecks,
+    project_events::ProjectEvent,
+    project_export::{
+        self, ExportProjectError, ImportProjectError, ImportedProject, ProjectExportMetadata,
+    },
+    project_listing::{self, Page, Pagination, ProjectFilter, ProjectSummary},
+    project_locks::ProjectLocks,
+    project_state::{
+        InstallFailure, ProjectState, ProjectStateChange, ProjectStates, ProjectTransitionError,
+    },
+    project_store::{
+        ProjectStatus, ProjectStore, ProjectStoreBackend, ProjectStoreError, StoredProject,
+        StoredRun,
+    },
+    python::PythonConfig,
+    recovery::{self, RecoveryReport},
+    report_generator::{self, ReportFormat, ReportThresholds},
+    requirements_hash,
+    run_metrics::RunMetricsSample,
+    run_report::{self, LoadRunReportError, RunReport, RunReportStatus},
+    staging,
+    storage_layout::{self, StorageLayout},
+    tenant::{self, TenantId, TenantQuota, TenantQuotaError, TenantSlot, TenantSlots},
 };
-use tracing::info_span;
 
-use super::local_project_installer::LocalProjectInstallerController;
+use crate::project_managers::process::ProcessIoConfig;
+use models::run_config::{RunConfig, RunConfigError, RunConfigLimits};
+
+// TODO: Create Traits: Controller
 
-// TODO: Create Traits: ProjectManager, Database, Controller
+/// Enough for small hosts, every installation runs pip.
+const DEFAULT_MAX_CONCURRENT_INSTALLATIONS: usize = 2;
 
 pub struct LocalProjectManager {
     root_dir: PathBuf,
+    /// Resolved against ```root_dir```, see ```StorageLayout::resolve```.
+    storage_layout: StorageLayout,
     // C: impl Controller: cancel...
     controllers: Arc<RwLock<HashMap</* id */ String, LocalProjectInstallerController>>>,
-    // D: impl Database: save, remove, get...
+    /// The controllers of the running tests, see ```start_run```.
+    runners: Arc<RwLock<HashMap</* id */ String, RunController>>>,
+    /// Notified whenever an installation task removed its controller, see ```cancel_installation```.
+    installation_finished: Arc<Notify>,
+    /// Installations, uninstallations and deletions of the same project do not run concurrently.
+    project_locks: ProjectLocks,
+    /// Limits the installations that run at the same time, see ```set_max_concurrent_installations```.
+    install_queue: InstallQueue,
+    /// The saved projects, in ```root_dir``` unless it is in memory, see ```ProjectStoreBackend```.
+    project_store: Arc<dyn ProjectStore>,
+    /// The lifecycle of the saved projects, see ```transition_project```.
+    project_states: ProjectStates,
+    pip_cache_config: PipCacheConfig,
+    /// Passed to every installer with ```LocalProjectInstaller::set_python_config```.
+    python_config: PythonConfig,
+    /// Passed to every installer with ```LocalProjectInstaller::set_installer_backend```.
+    installer_backend: InstallerBackend,
+    /// Passed to every installer with ```LocalProjectInstaller::set_pip_retry_config```.
+    pip_retry_config: PipRetryConfig,
+    /// Passed to every installer with ```LocalProjectInstaller::set_pip_options```, unless the project overrides them.
+    pip_options: PipOptions,
+    project_pip_options: HashMap</* id */ String, PipOptions>,
+    /// Passed to every installer with ```LocalProjectInstaller::set_audit_config```.
+    audit_config: AuditConfig,
+    /// Passed to every installer with ```LocalProjectInstaller::set_log_rotation_config```.
+    log_rotation_config: LogRotationConfig,
+    /// Passed to every installer with ```LocalProjectInstaller::set_env_store```. ```None``` if environments are not shared.
+    env_store: Option<EnvStore>,
+    /// Passed to every installer with ```LocalProjectInstaller::set_cleanup_policy```.
+    cleanup_policy: CleanupPolicy,
+    /// How long an environment kept by a failed installation is available, see ```schedule_cleanup```.
+    cleanup_delay: Duration,
+    /// Passed to every installer with ```LocalProjectInstaller::set_project_checks```.
+    project_checks: ProjectChecks,
+    /// Every run is validated against these, see ```start_run```.
+    run_config_limits: RunConfigLimits,
+    /// The verdicts of the rendered reports, see ```get_run_report_file```.
+    report_thresholds: ReportThresholds,
+    /// The files of the installations and runs, e.g. for the downloads of the API.
+    artifact_store: ArtifactStore,
+    /// Keeps the uploaded projects and the artifacts, see ```set_blob_store```. ```None``` if they are only kept locally.
+    blob_store: Option<Arc<dyn BlobStore>>,
+    /// The tenants without a quota are not limited, see ```set_tenant_quota```.
+    tenant_quotas: HashMap<TenantId, TenantQuota>,
+    /// The installations and runs in progress per tenant, see ```TenantQuota```.
+    tenant_slots: TenantSlots,
+    /// Created once in ```new```, see ```recover```.
+    recovery_report: RecoveryReport,
 }
 
 #[derive(ThisError, Debug)]
@@ -23,111 +136,2609 @@ pub enum LocalProjectManagerCreateError {
     CouldNotCheckIfRootDirExists(#[source] IoError),
     #[error("Could not create root dir: {0}")]
     CouldNotCreateRootDir(#[source] IoError),
+    #[error("Could not create dir: {0}")]
+    CouldNotCreateDir(#[source] IoError),
+    #[error("Could not migrate the legacy environments dir: {0}")]
+    CouldNotMigrateEnvironmentsDir(#[source] IoError),
+    #[error("Could not open the project store: {0}")]
+    CouldNotOpenProjectStore(#[source] ProjectStoreError),
+    #[error("Could not recover the projects: {0}")]
+    CouldNotRecoverProjects(#[source] ProjectStoreError),
+    #[error("Could not open the artifact store: {0}")]
+    CouldNotOpenArtifactStore(#[source] ArtifactStoreError),
+}
+
+#[derive(ThisError, Debug)]
+pub enum AddProjectError {
+    #[error("Project is not valid: {0}")]
+    InvalidProject(
+        #[source]
+        #[from]
+        ProjectCheckError,
+    ),
+    #[error("Could not save the project: {0}")]
+    CouldNotSaveProject(
+        #[source]
+        #[from]
+        ProjectStoreError,
+    ),
+    #[error("Could not upload the project: {0}")]
+    CouldNotUploadProject(#[source] BlobStoreError),
+    #[error("Could not create the dirs of the tenant: {0}")]
+    CouldNotCreateTenantDirs(#[source] IoError),
+    #[error("Quota of the tenant does not allow the project: {0}")]
+    Quota(
+        #[source]
+        #[from]
+        TenantQuotaError,
+    ),
+}
+
+#[derive(ThisError, Debug)]
+pub enum InstallProjectError {
+    #[error("Could not load the project: {0}")]
+    CouldNotLoadProject(#[source] ProjectStoreError),
+    #[error("Project does not exist: {0}")]
+    ProjectDoesNotExist(String),
+    #[error("Another operation on the project is in progress: {0}")]
+    AlreadyInProgress(String),
+    #[error("Could not download the uploaded project: {0}")]
+    CouldNotDownloadProject(#[source] BlobStoreError),
+    #[error("Quota of the tenant does not allow the installation: {0}")]
+    Quota(
+        #[source]
+        #[from]
+        TenantQuotaError,
+    ),
+    #[error("Project can not be installed: {0}")]
+    Transition(
+        #[source]
+        #[from]
+        ProjectTransitionError,
+    ),
+}
+
+#[derive(ThisError, Debug)]
+pub enum StartRunError {
+    #[error("Run config is not valid: {0}")]
+    InvalidRunConfig(
+        #[source]
+        #[from]
+        RunConfigError,
+    ),
+    #[error("Another operation on the project is in progress: {0}")]
+    AlreadyInProgress(String),
+    #[error("Could not load the project: {0}")]
+    CouldNotLoadProject(#[source] ProjectStoreError),
+    #[error("Quota of the tenant does not allow the run: {0}")]
+    Quota(
+        #[source]
+        #[from]
+        TenantQuotaError,
+    ),
+    #[error("Project can not be run: {0}")]
+    Transition(
+        #[source]
+        #[from]
+        ProjectTransitionError,
+    ),
+}
+
+#[derive(ThisError, Debug)]
+pub enum StopProjectRunError {
+    #[error("Project is not running: {0}")]
+    NotRunning(String),
+    #[error("Could not stop the run: {0}")]
+    Stop(
+        #[source]
+        #[from]
+        StopRunError,
+    ),
+}
+
+#[derive(ThisError, Debug)]
+pub enum UninstallProjectError {
+    #[error("Another operation on the project is in progress: {0}")]
+    AlreadyInProgress(String),
+    #[error("Could not load the project: {0}")]
+    CouldNotLoadProject(#[source] ProjectStoreError),
+    #[error("Project can not be uninstalled: {0}")]
+    Transition(
+        #[source]
+        #[from]
+        ProjectTransitionError,
+    ),
+    #[error("Could not cancel the installation: {0}")]
+    CouldNotCancelInstallation(#[source] SendingCancellationSignalToInstallerError),
+    #[error("Could not release the shared environment: {0}")]
+    CouldNotReleaseEnvironment(#[source] EnvStoreError),
+    #[error("Could not delete the environment: {0}")]
+    CouldNotDeleteEnvironment(#[source] CleanUpError),
+    #[error("Could not delete the requirements hash: {0}")]
+    CouldNotDeleteRequirementsHash(#[source] IoError),
+    #[error("Could not delete the installed project: {0}")]
+    CouldNotDeleteInstalledProject(#[source] IoError),
+}
+
+#[derive(ThisError, Debug)]
+pub enum DeleteProjectError {
+    #[error("Could not load the project: {0}")]
+    CouldNotLoadProject(#[source] ProjectStoreError),
+    #[error("Project does not exist: {0}")]
+    ProjectDoesNotExist(String),
+    #[error("Another operation on the project is in progress: {0}")]
+    AlreadyInProgress(String),
+    #[error("Could not uninstall the project: {0}")]
+    Uninstall(
+        #[source]
+        #[from]
+        UninstallProjectError,
+    ),
+    #[error("Project can not be deleted: {0}")]
+    Transition(
+        #[source]
+        #[from]
+        ProjectTransitionError,
+    ),
+    #[error("Could not delete the uploaded project: {0}")]
+    CouldNotDeleteUploadedProject(#[source] IoError),
+    #[error("Could not delete the runs of the project: {0}")]
+    CouldNotDeleteRuns(#[source] IoError),
+    #[error("Could not remove the artifacts of the project: {0}")]
+    CouldNotRemoveArtifacts(#[source] ArtifactStoreError),
+    #[error("Could not delete the uploaded copy of the project: {0}")]
+    CouldNotDeleteUploadedCopy(#[source] BlobStoreError),
+    #[error("Could not remove the project from the database: {0}")]
+    CouldNotRemoveProject(#[source] ProjectStoreError),
 }
 
 impl LocalProjectManager {
     pub async fn new(root_dir: PathBuf) -> Result<Self, LocalProjectManagerCreateError> {
+        Self::with_pip_cache_config(root_dir, PipCacheConfig::default()).await
+    }
+
+    pub async fn with_pip_cache_config(
+        root_dir: PathBuf,
+        pip_cache_config: PipCacheConfig,
+    ) -> Result<Self, LocalProjectManagerCreateError> {
+        Self::with_project_store_backend(root_dir, pip_cache_config, ProjectStoreBackend::default())
+            .await
+    }
+
+    /// The projects saved by a previous manager are only found with the same backend.
+    pub async fn with_project_store_backend(
+        root_dir: PathBuf,
+        pip_cache_config: PipCacheConfig,
+        project_store_backend: ProjectStoreBackend,
+    ) -> Result<Self, LocalProjectManagerCreateError> {
+        Self::with_storage_layout(
+            root_dir,
+            pip_cache_config,
+            project_store_backend,
+            StorageLayout::default(),
+        )
+        .await
+    }
+
+    /// The dirs of ```storage_layout``` are created, a legacy ```enviroments``` dir in ```root_dir``` is moved to the environments dir.
+    pub async fn with_storage_layout(
+        root_dir: PathBuf,
+        pip_cache_config: PipCacheConfig,
+        project_store_backend: ProjectStoreBackend,
+        storage_layout: StorageLayout,
+    ) -> Result<Self, LocalProjectManagerCreateError> {
         let span = info_span!("LocalProjectManager::new");
         let _span_guard = span.enter();
 
-        // TODO: replace with create_all_dirs_if_not_exist
-        if !fs::try_exists(&root_dir)
-            .await
-            .map_err(LocalProjectManagerCreateError::CouldNotCheckIfRootDirExists)?
+        let storage_layout = storage_layout.resolve(&root_dir);
+
+        if storage_layout::migrate_legacy_environments_dir(
+            &root_dir,
+            &storage_layout.environments_dir,
+        )
+        .await
+        .map_err(LocalProjectManagerCreateError::CouldNotMigrateEnvironmentsDir)?
         {
-            tracing::info!(?root_dir, "Root dir does not exist, creating it");
-            fs::create_dir_all(&root_dir)
-                .await
-                .map_err(LocalProjectManagerCreateError::CouldNotCreateRootDir)?;
+            tracing::info!(environments_dir = ?storage_layout.environments_dir, "Migrated legacy environments dir");
         }
 
+        Self::create_all_dirs_if_not_exist(&root_dir, &storage_layout).await?;
+
         let controllers = Arc::new(RwLock::new(HashMap::new()));
 
-        Ok(Self {
+        let project_store = project_store_backend
+            .open(&root_dir)
+            .await
+            .map_err(LocalProjectManagerCreateError::CouldNotOpenProjectStore)?;
+
+        let artifact_store = ArtifactStore::load(&root_dir)
+            .await
+            .map_err(LocalProjectManagerCreateError::CouldNotOpenArtifactStore)?;
+
+        let mut local_project_manager = Self {
             root_dir,
+            storage_layout,
             controllers,
-        })
+            runners: Arc::default(),
+            installation_finished: Arc::new(Notify::new()),
+            project_locks: ProjectLocks::default(),
+            install_queue: InstallQueue::new(DEFAULT_MAX_CONCURRENT_INSTALLATIONS),
+            project_store,
+            project_states: ProjectStates::default(),
+            pip_cache_config,
+            python_config: PythonConfig::default(),
+            installer_backend: InstallerBackend::default(),
+            pip_retry_config: PipRetryConfig::default(),
+            pip_options: PipOptions::default(),
+            project_pip_options: HashMap::new(),
+            audit_config: AuditConfig::default(),
+            log_rotation_config: LogRotationConfig::default(),
+            env_store: None,
+            cleanup_policy: CleanupPolicy::default(),
+            cleanup_delay: Duration::from_secs(60 * 60),
+            project_checks: ProjectChecks::default(),
+            run_config_limits: RunConfigLimits::default(),
+            report_thresholds: ReportThresholds::default(),
+            artifact_store,
+            blob_store: None,
+            tenant_quotas: HashMap::new(),
+            tenant_slots: TenantSlots::default(),
+            recovery_report: RecoveryReport::default(),
+        };
+
+        local_project_manager.remove_stale_staging_dirs().await;
+        local_project_manager.recovery_report = local_project_manager
+            .recover()
+            .await
+            .map_err(LocalProjectManagerCreateError::CouldNotRecoverProjects)?;
+
+        Ok(local_project_manager)
+    }
+
+    /// Reconciles the ```ProjectStore``` with the filesystem after a restart, see ```RecoveryReport```.
+    /// Correctness: Runs after ```remove_stale_staging_dirs```, the resumable staging dirs of removed projects are orphans.
+    /// An orphan that could not be removed is logged, it only takes up space.
+    async fn recover(&self) -> Result<RecoveryReport, ProjectStoreError> {
+        let mut recovery_report = RecoveryReport::default();
+        let projects = self.project_store.list().await?;
+
+        for project in &projects {
+            let install_failure = match project.status {
+                ProjectStatus::Installing => Some(InstallFailure::Interrupted),
+                ProjectStatus::Installed if !self.installation_exists(project).await => {
+                    Some(InstallFailure::Missing)
+                }
+                _ => None,
+            };
+
+            if let Some(install_failure) = install_failure {
+                tracing::warn!(
+                    project_id = project.id,
+                    ?install_failure,
+                    "Recovered project"
+                );
+
+                self.project_store
+                    .set_status(&project.id, ProjectStatus::Failed, None)
+                    .await?;
+                self.project_states
+                    .set(
+                        project.id.clone(),
+                        ProjectState::InstallFailed(install_failure),
+                    )
+                    .await;
+
+                match install_failure {
+                    InstallFailure::Interrupted => recovery_report
+                        .interrupted_installations
+                        .push(project.id.clone()),
+                    _ => recovery_report
+                        .missing_installations
+                        .push(project.id.clone()),
+                }
+            }
+
+            if !dir_exists(&project.source_dir).await {
+                tracing::warn!(project_id = project.id, source_dir = ?project.source_dir, "Uploaded project is missing");
+
+                recovery_report.missing_uploads.push(project.id.clone());
+            }
+        }
+
+        let project_ids: HashSet<&str> =
+            projects.iter().map(|project| project.id.as_str()).collect();
+
+        let mut dirs = Vec::new();
+        for tenant_storage_layout in self.tenant_storage_layouts().await {
+            for dir in [
+                tenant_storage_layout.environments_dir,
+                tenant_storage_layout.installed_projects_dir,
+            ] {
+                // The dirs outside of the root dir are shared by the tenants.
+                if !dirs.contains(&dir) {
+                    dirs.push(dir);
+                }
+            }
+        }
+
+        for dir in dirs {
+            match recovery::remove_orphans(&dir, &project_ids).await {
+                Ok(removed_orphans) => {
+                    for removed_orphan in &removed_orphans {
+                        tracing::info!(?removed_orphan, "Removed orphan");
+                    }
+
+                    recovery_report.removed_orphans.extend(removed_orphans);
+                }
+                Err(error) => {
+                    tracing::warn!(?dir, %error, "Could not remove orphans");
+                }
+            }
+        }
+
+        Ok(recovery_report)
+    }
+
+    /// The environment and the installed project, e.g. one of them was deleted while the manager was not running.
+    async fn installation_exists(&self, project: &StoredProject) -> bool {
+        dir_exists(&self.get_project_environment_dir(&project.tenant_id, project.id.clone())).await
+            && dir_exists(
+                &self.get_project_installation_dir(&project.tenant_id, project.id.clone()),
+            )
+            .await
+    }
+
+    /// The layouts of the tenants with a dir, the default tenant first, see ```StorageLayout::for_tenant```.
+    /// Correctness: Only the default tenant is returned if the tenants dir could not be read, the failure is logged.
+    async fn tenant_storage_layouts(&self) -> Vec<StorageLayout> {
+        let tenant_ids = tenant::tenant_ids(&self.root_dir)
+            .await
+            .unwrap_or_else(|error| {
+                tracing::warn!(%error, "Could not list the tenants");
+
+                vec![TenantId::default()]
+            });
+
+        tenant_ids
+            .iter()
+            .map(|tenant_id| self.tenant_storage_layout(tenant_id))
+            .collect()
+    }
+
+    /// What was recovered when the manager was created.
+    pub fn recovery_report(&self) -> &RecoveryReport {
+        &self.recovery_report
+    }
+
+    /// No installation is running at startup, every staging dir belongs to an installation that did not finish.
+    /// Shared environments are installed in their own dir, see ```EnvStore```.
+    /// Correctness: A failure is logged, a stale staging dir only takes up space.
+    async fn remove_stale_staging_dirs(&self) {
+        let mut environments_dirs: Vec<_> = self
+            .tenant_storage_layouts()
+            .await
+            .into_iter()
+            .map(|tenant_storage_layout| tenant_storage_layout.environments_dir)
+            .collect();
+        environments_dirs.dedup();
+        environments_dirs.push(self.get_shared_environments_dir());
+
+        for environments_dir in environments_dirs {
+            match staging::remove_stale_staging_dirs(&environments_dir).await {
+                Ok(removed_dirs) => {
+                    for removed_dir in removed_dirs {
+                        tracing::info!(?removed_dir, "Removed stale staging dir");
+                    }
+                }
+                Err(error) => {
+                    tracing::warn!(%error, "Could not remove stale staging dirs");
+                }
+            }
+        }
     }
 
     /// Creates all directories that are needed for the project manager to work.
-    /// ```root_dir```, ```enviroments_dir``` and ```installed_projects_dir``` are created.
-    async fn create_all_dirs_if_not_exist(&self) -> Result<(), ()> {
-        // self.root_dir
-        // self.get_enviroments_dir()
-        // self.get_installed_projects_dir()
-        todo!()
+    /// ```root_dir``` is created first, it holds the ```ProjectStore```, then the dirs of ```storage_layout```.
+    /// Correctness: The shared environments and the pip cache are created by their first installation.
+    async fn create_all_dirs_if_not_exist(
+        root_dir: &Path,
+        storage_layout: &StorageLayout,
+    ) -> Result<(), LocalProjectManagerCreateError> {
+        if !fs::try_exists(root_dir)
+            .await
+            .map_err(LocalProjectManagerCreateError::CouldNotCheckIfRootDirExists)?
+        {
+            tracing::info!(?root_dir, "Root dir does not exist, creating it");
+            fs::create_dir_all(root_dir)
+                .await
+                .map_err(LocalProjectManagerCreateError::CouldNotCreateRootDir)?;
+        }
+
+        for dir in storage_layout.dirs() {
+            Self::create_dir_if_not_exists(dir)
+                .await
+                .map_err(LocalProjectManagerCreateError::CouldNotCreateDir)?;
+        }
+
+        Ok(())
     }
 
-    async fn create_dir_if_not_exists(dir: PathBuf) -> Result<(), IoError> {
-        if !fs::try_exists(&dir).await? {
-            fs::create_dir_all(&dir).await?;
+    async fn create_dir_if_not_exists(dir: &Path) -> Result<(), IoError> {
+        if !fs::try_exists(dir).await? {
+            tracing::info!(?dir, "Dir does not exist, creating it");
+            fs::create_dir_all(dir).await?;
         }
 
         Ok(())
     }
 
-    fn get_installed_projects_dir(&self) -> PathBuf {
-        self.root_dir.join("installed_projects")
+    /// The resolved dirs, e.g. where to put an uploaded project before ```add_new_project_to_database```.
+    pub fn storage_layout(&self) -> &StorageLayout {
+        &self.storage_layout
+    }
+
+    fn get_shared_environments_dir(&self) -> PathBuf {
+        self.root_dir.join("shared_environments")
+    }
+
+    /// Passed to every installer with ```LocalProjectInstaller::set_pip_cache_dir```. ```None``` if the cache is disabled.
+    fn get_pip_cache_dir(&self) -> Option<PathBuf> {
+        self.pip_cache_config
+            .enabled
+            .then(|| self.root_dir.join("pip_cache"))
+    }
+
+    /// The dirs of the tenant, see ```StorageLayout::for_tenant```.
+    pub fn tenant_storage_layout(&self, tenant_id: &TenantId) -> StorageLayout {
+        self.storage_layout.for_tenant(&self.root_dir, tenant_id)
+    }
+
+    fn get_project_installation_dir(&self, tenant_id: &TenantId, project_id: String) -> PathBuf {
+        self.tenant_storage_layout(tenant_id)
+            .installed_projects_dir
+            .join(project_id)
     }
 
-    fn get_enviroments_dir(&self) -> PathBuf {
-        self.root_dir.join("enviroments")
+    fn get_project_environment_dir(&self, tenant_id: &TenantId, project_id: String) -> PathBuf {
+        self.tenant_storage_layout(tenant_id)
+            .environments_dir
+            .join(project_id)
     }
 
-    fn get_project_installation_dir(&self, project_id: String) -> PathBuf {
-        self.get_installed_projects_dir().join(project_id)
+    fn get_run_dir(&self, tenant_id: &TenantId, run_id: &str) -> PathBuf {
+        self.tenant_storage_layout(tenant_id).runs_dir.join(run_id)
     }
 
-    fn get_project_enviroment_dir(&self, project_id: String) -> PathBuf {
-        self.get_enviroments_dir().join(project_id)
+    /// The tenant of the project. A missing project belongs to the default tenant, e.g. to clean up its dirs.
+    async fn tenant_of(&self, project_id: &str) -> Result<TenantId, ProjectStoreError> {
+        Ok(self
+            .project_store
+            .get(project_id)
+            .await?
+            .map(|project| project.tenant_id)
+            .unwrap_or_default())
     }
 
     /// Checks if the project is valid.
-    /// Saves the project in the database if it is valid.
+    /// Saves the project in the database if it is valid, after it was uploaded to the ```BlobStore```, if there is one.
     /// ```project_dir``` is the base directory, from which the project should be installed.
-    /// The ```ProjectManager``` has no control over this directory.
+    /// The ```ProjectManager``` has no control over this directory, until the project is deleted, see ```delete_project```.
+    /// The project belongs to the default tenant, see ```add_new_tenant_project```.
     pub async fn add_new_project_to_database(
         &self,
         project_id: String,
         project_name: String,
         project_dir: PathBuf,
-    ) -> Result<(), ()> {
-        todo!()
+    ) -> Result<(), AddProjectError> {
+        self.add_new_tenant_project(TenantId::default(), project_id, project_name, project_dir)
+            .await
+    }
+
+    /// Like ```add_new_project_to_database```, the project belongs to the tenant.
+    /// Creates the dirs of the tenant, see ```tenant_storage_layout```, the caller puts ```project_dir``` in its uploaded projects dir.
+    /// Fails with ```AddProjectError::Quota``` if the tenant has ```TenantQuota::max_projects``` projects
+    /// or the project exceeds ```TenantQuota::max_disk_usage_bytes```.
+    /// Correctness: The quota is checked before the project is saved, concurrently added projects may exceed it.
+    pub async fn add_new_tenant_project(
+        &self,
+        tenant_id: TenantId,
+        project_id: String,
+        project_name: String,
+        project_dir: PathBuf,
+    ) -> Result<(), AddProjectError> {
+        let (mut installer, _controller) = LocalProjectInstaller::new(
+            project_id.clone(),
+            project_dir.clone(),
+            self.get_project_installation_dir(&tenant_id, project_id.clone()),
+            self.get_project_environment_dir(&tenant_id, project_id.clone()),
+            None,
+            ProcessIoConfig::default(),
+        );
+        installer.set_project_checks(self.project_checks.clone());
+
+        installer.check().await?;
+
+        let tenant_quota = self.tenant_quota(&tenant_id);
+        let tenant_projects = self.tenant_projects(&tenant_id).await?;
+        tenant_quota.check_project_count(&tenant_id, tenant_projects.len())?;
+        if tenant_quota.max_disk_usage_bytes.is_some() {
+            let project_size = disk_space::dir_size(&project_dir).await.unwrap_or(0);
+            let used_bytes = self.disk_usage(&tenant_projects).await?;
+            tenant_quota.check_disk_usage(&tenant_id, used_bytes + project_size)?;
+        }
+
+        self.create_tenant_dirs(&tenant_id)
+            .await
+            .map_err(AddProjectError::CouldNotCreateTenantDirs)?;
+
+        if let Some(blob_store) = &self.blob_store {
+            blob_store::put_dir(
+                blob_store.as_ref(),
+                &uploaded_project_blob_prefix(&project_id),
+                &project_dir,
+            )
+            .await
+            .map_err(AddProjectError::CouldNotUploadProject)?;
+        }
+
+        let now = SystemTime::now();
+        if let Err(error) = self
+            .project_store
+            .insert(&StoredProject {
+                id: project_id.clone(),
+                name: project_name,
+                source_dir: project_dir,
+                status: ProjectStatus::Uploaded,
+                created_at: now,
+                updated_at: now,
+                requirements_hash: None,
+                tags: BTreeSet::new(),
+                tenant_id,
+                description: None,
+                owner: None,
+            })
+            .await
+        {
+            self.delete_uploaded_copy(&project_id).await;
+
+            return Err(error.into());
+        }
+
+        // A deleted project with the same id is replaced.
+        self.project_states
+            .set(project_id, ProjectState::Uploaded)
+            .await;
+
+        Ok(())
+    }
+
+    async fn create_tenant_dirs(&self, tenant_id: &TenantId) -> Result<(), IoError> {
+        for dir in self.tenant_storage_layout(tenant_id).dirs() {
+            Self::create_dir_if_not_exists(dir).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn tenant_projects(
+        &self,
+        tenant_id: &TenantId,
+    ) -> Result<Vec<StoredProject>, TenantQuotaError> {
+        Ok(self
+            .project_store
+            .list()
+            .await
+            .map_err(TenantQuotaError::CouldNotLoadProjects)?
+            .into_iter()
+            .filter(|project| project.tenant_id == *tenant_id)
+            .collect())
     }
 
-    async fn remove_project_from_database(&self, project_id: String) -> Result<(), ()> {
-        todo!()
+    /// The size of the uploaded dirs, the installations, the environments and the runs of the projects.
+    /// Correctness: A dir that could not be measured is not counted, e.g. a missing environment.
+    async fn disk_usage(&self, projects: &[StoredProject]) -> Result<u64, TenantQuotaError> {
+        let mut used_bytes = 0;
+
+        for project in projects {
+            let mut dirs = vec![
+                project.source_dir.clone(),
+                self.get_project_installation_dir(&project.tenant_id, project.id.clone()),
+                self.get_project_environment_dir(&project.tenant_id, project.id.clone()),
+            ];
+            for run in self
+                .project_store
+                .list_runs(&project.id)
+                .await
+                .map_err(TenantQuotaError::CouldNotLoadProjects)?
+            {
+                dirs.push(self.get_run_dir(&project.tenant_id, &run.id));
+            }
+
+            for dir in dirs {
+                used_bytes += disk_space::dir_size(&dir).await.unwrap_or(0);
+            }
+        }
+
+        Ok(used_bytes)
+    }
+
+    /// The disk usage of the tenant, see ```TenantQuota::max_disk_usage_bytes```.
+    pub async fn tenant_disk_usage(&self, tenant_id: &TenantId) -> Result<u64, TenantQuotaError> {
+        self.disk_usage(&self.tenant_projects(tenant_id).await?)
+            .await
+    }
+
+    /// Correctness: A failure is logged, the copy only takes up space.
+    async fn delete_uploaded_copy(&self, project_id: &str) {
+        if let Some(blob_store) = &self.blob_store {
+            if let Err(error) = blob_store::delete_dir(
+                blob_store.as_ref(),
+                &uploaded_project_blob_prefix(project_id),
+            )
+            .await
+            {
+                tracing::warn!(%error, project_id, "Could not delete the uploaded copy of the project");
+            }
+        }
+    }
+
+    /// Returns whether the project was saved.
+    async fn remove_project_from_database(
+        &self,
+        project_id: String,
+    ) -> Result<bool, ProjectStoreError> {
+        self.project_store.remove(&project_id).await
+    }
+
+    /// The saved projects, including the ones saved before a restart.
+    pub async fn projects(&self) -> Result<Vec<StoredProject>, ProjectStoreError> {
+        self.project_store.list().await
+    }
+
+    pub async fn project(
+        &self,
+        project_id: &str,
+    ) -> Result<Option<StoredProject>, ProjectStoreError> {
+        self.project_store.get(project_id).await
+    }
+
+    /// Filters the saved projects, the oldest project first, see ```ProjectFilter```.
+    /// Correctness: The projects are filtered by the manager, every project is loaded from the store.
+    pub async fn list_projects(
+        &self,
+        filter: ProjectFilter,
+        page: Pagination,
+    ) -> Result<Page<ProjectSummary>, ProjectStoreError> {
+        let projects = self
+            .project_states
+            .with_states(self.project_store.list().await?)
+            .await;
+
+        Ok(project_listing::list(projects, &filter, page))
+    }
+
+    /// Replaces the tags of the project, see ```ProjectFilter::tags```.
+    pub async fn set_project_tags(
+        &self,
+        project_id: &str,
+        tags: BTreeSet<String>,
+    ) -> Result<(), ProjectStoreError> {
+        self.project_store.set_tags(project_id, &tags).await
+    }
+
+    /// Replaces the description and the owner of the project, see ```ProjectFilter::owner```.
+    pub async fn set_project_details(
+        &self,
+        project_id: &str,
+        description: Option<String>,
+        owner: Option<String>,
+    ) -> Result<(), ProjectStoreError> {
+        self.project_store
+            .set_details(project_id, description.as_deref(), owner.as_deref())
+            .await
+    }
+
+    /// Replaces the store of the ```ProjectStoreBackend```, e.g. with a store of another database.
+    /// Correctness: The projects of the replaced store are not moved, their states are forgotten.
+    pub fn set_project_store(&mut self, project_store: Arc<dyn ProjectStore>) {
+        self.project_store = project_store;
+        self.project_states = self.project_states.without_states();
+    }
+
+    /// ```None``` if the project does not exist.
+    pub async fn project_state(
+        &self,
+        project_id: &str,
+    ) -> Result<Option<ProjectState>, ProjectStoreError> {
+        self.project_states
+            .get(self.project_store.as_ref(), project_id)
+            .await
+    }
+
+    /// Moves the project to ```next```, e.g. to ```ProjectState::Running``` before a test is started.
+    /// Returns the previous state, fails with ```ProjectTransitionError::InvalidTransition```
+    /// if ```ProjectState::can_transition_to``` does not allow it, e.g. to run a project that is not installed.
+    /// Correctness: The installation states are maintained by ```do_install_project```.
+    pub async fn transition_project(
+        &self,
+        project_id: &str,
+        next: ProjectState,
+    ) -> Result<ProjectState, ProjectTransitionError> {
+        self.project_states
+            .transition(self.project_store.as_ref(), project_id, next, None)
+            .await
+    }
+
+    /// See ```ProjectStateChange```.
+    pub fn subscribe_project_states(&self) -> broadcast::Receiver<ProjectStateChange> {
+        self.project_states.subscribe()
+    }
+
+    /// See ```ProjectEvent```. The events of an installation are sent whether it has an ```event_sender``` or not.
+    pub fn subscribe_project_events(&self) -> broadcast::Receiver<ProjectEvent> {
+        self.project_states.events().subscribe()
     }
 
     /// Starts the installation of a project in a new task.
     /// The given ```project_id``` must be a valid project id, that is saved in the database.
-    /// Forwards the installation stdout and stderr to the given channels.
-    pub fn do_install_project(
+    /// Forwards the installation events, including stdout and stderr, to the given channel.
+    /// The project is ```ProjectState::QueuedForInstall``` until a slot is free, see ```set_max_concurrent_installations```.
+    /// Then it is ```ProjectState::Checking```, ```ProjectState::Installing``` once the environment is created,
+    /// and ```ProjectState::Installed``` or ```ProjectState::InstallFailed``` when the task finishes. Its controller is removed afterwards.
+    /// A kept environment of a failed installation is deleted after ```cleanup_delay```, see ```schedule_cleanup```.
+    /// Fails with ```InstallProjectError::AlreadyInProgress``` while the project is installed, uninstalled or deleted.
+    /// A missing uploaded dir is downloaded from the ```BlobStore``` first, e.g. if the project was added by another replica.
+    /// Fails with ```InstallProjectError::Quota``` if the tenant exceeds ```TenantQuota::max_disk_usage_bytes```
+    /// or has ```TenantQuota::max_concurrent_installations``` installations in progress.
+    /// Correctness: A failure of the task is logged, the project stays in its state if its status can not be saved.
+    pub async fn do_install_project(
         &self,
         project_id: String,
-        stdout_sender: Option<mpsc::Sender<String>>,
-        stderr_sender: Option<mpsc::Sender<String>>,
-    ) -> Result<(), ()> {
-        todo!()
+        event_sender: Option<mpsc::Sender<InstallerEvent>>,
+    ) -> Result<InstallTicket, InstallProjectError> {
+        let project = self
+            .project_store
+            .get(&project_id)
+            .await
+            .map_err(InstallProjectError::CouldNotLoadProject)?
+            .ok_or_else(|| InstallProjectError::ProjectDoesNotExist(project_id.clone()))?;
+
+        // Held by the task until the installation finished.
+        let project_lock_guard = self
+            .project_locks
+            .try_lock(&project_id)
+            .ok_or_else(|| InstallProjectError::AlreadyInProgress(project_id.clone()))?;
+
+        let tenant_quota = self.tenant_quota(&project.tenant_id);
+        if tenant_quota.max_disk_usage_bytes.is_some() {
+            let used_bytes = self.tenant_disk_usage(&project.tenant_id).await?;
+            tenant_quota.check_disk_usage(&project.tenant_id, used_bytes)?;
+        }
+
+        // Held by the task until the installation finished.
+        let tenant_slot_guard = self.tenant_slots.try_acquire(
+            &project.tenant_id,
+            TenantSlot::Installation,
+            &tenant_quota,
+        )?;
+
+        if let Some(blob_store) = &self.blob_store {
+            if !dir_exists(&project.source_dir).await {
+                blob_store::get_dir(
+                    blob_store.as_ref(),
+                    &uploaded_project_blob_prefix(&project_id),
+                    &project.source_dir,
+                )
+                .await
+                .map_err(InstallProjectError::CouldNotDownloadProject)?;
+
+                tracing::info!(project_id, source_dir = ?project.source_dir, "Downloaded uploaded project");
+            }
+        }
+
+        self.project_states
+            .transition(
+                self.project_store.as_ref(),
+                &project_id,
+                ProjectState::QueuedForInstall,
+                None,
+            )
+            .await?;
+
+        let (installer_event_sender, installer_events_forwarder) = self
+            .project_states
+            .events()
+            .forward_installer_events(project_id.clone(), event_sender);
+        let installation_dir =
+            self.get_project_installation_dir(&project.tenant_id, project_id.clone());
+        let (mut installer, controller) =
+            self.create_installer(project, Some(installer_event_sender));
+        let status_receiver = controller.subscribe_status();
+        self.controllers
+            .write()
+            .await
+            .insert(project_id.clone(), controller);
+
+        let project_store = self.project_store.clone();
+        let project_states = self.project_states.clone();
+        let controllers = self.controllers.clone();
+        let installation_finished = self.installation_finished.clone();
+        let cleanup_delay = self.cleanup_delay;
+        let install_queue = self.install_queue.clone();
+        let artifact_store = self.artifact_store.clone();
+        let (queue_position, queue_cancellation_token) = install_queue.enqueue(&project_id);
+        let span = info_span!("LocalProjectManager::do_install_project", project_id);
+
+        tracing::debug!(parent: &span, ?queue_position, "Queued installation");
+
+        let task = tokio::spawn(
+            async move {
+                let transition = |next, requirements_hash: Option<String>| {
+                    let project_store = project_store.clone();
+                    let project_states = project_states.clone();
+                    let project_id = project_id.clone();
+
+                    async move {
+                        if let Err(error) = project_states
+                            .transition(
+                                project_store.as_ref(),
+                                &project_id,
+                                next,
+                                requirements_hash.as_deref(),
+                            )
+                            .await
+                        {
+                            tracing::warn!(%error, "Could not transition the project");
+                        }
+                    }
+                };
+
+                let (next, requirements_hash) = match install_queue
+                    .wait_for_turn(&project_id, &queue_cancellation_token)
+                    .await
+                {
+                    Some(_installation_permit) => {
+                        transition(ProjectState::Checking, None).await;
+
+                        let installed = Self::install_in_slot(
+                            &mut installer,
+                            status_receiver,
+                            &transition,
+                            cleanup_delay,
+                        )
+                        .await;
+
+                        Self::register_install_artifacts(
+                            &artifact_store,
+                            &project_id,
+                            &installer,
+                            &installation_dir,
+                        )
+                        .await;
+
+                        installed
+                    }
+                    None => {
+                        tracing::info!("Installation was cancelled while queued");
+
+                        (ProjectState::InstallFailed(InstallFailure::Cancelled), None)
+                    }
+                };
+
+                // The installer's events are on the bus before its final state.
+                drop(installer);
+                let _ = installer_events_forwarder.await;
+
+                transition(next, requirements_hash).await;
+
+                // Unlocked before the waiting operations are notified, see ```cancel_installation```.
+                drop(project_lock_guard);
+                drop(tenant_slot_guard);
+                controllers.write().await.remove(&project_id);
+                installation_finished.notify_waiters();
+            }
+            .instrument(span),
+        );
+
+        Ok(InstallTicket {
+            task,
+            queue_position,
+        })
+    }
+
+    /// Runs the installation, that got a slot, see ```do_install_project```.
+    /// Returns the state the project ends in, with the requirements hash of a successful installation.
+    async fn install_in_slot<T, F>(
+        installer: &mut LocalProjectInstaller,
+        mut status_receiver: watch::Receiver<InstallerStatus>,
+        transition: &T,
+        cleanup_delay: Duration,
+    ) -> (ProjectState, Option<String>)
+    where
+        T: Fn(ProjectState, Option<String>) -> F,
+        F: Future<Output = ()>,
+    {
+        let install_result = {
+            let check_and_install = installer.check_and_install();
+            tokio::pin!(check_and_install);
+            let mut checking = true;
+
+            loop {
+                tokio::select! {
+                    install_result = &mut check_and_install => break install_result,
+                    changed = status_receiver.changed(), if checking => {
+                        let installing = matches!(
+                            *status_receiver.borrow_and_update(),
+                            InstallerStatus::CreatingVenv | InstallerStatus::InstallingRequirements
+                        );
+
+                        if changed.is_err() || installing {
+                            checking = false;
+                        }
+
+                        if installing {
+                            transition(ProjectState::Installing, None).await;
+                        }
+                    }
+                }
+            }
+        };
+
+        match install_result {
+            Ok(install_outcome) => {
+                tracing::info!(?install_outcome, "Project installed");
+
+                let requirements_hash =
+                    installer.requirements_hash().await.unwrap_or_else(|error| {
+                        tracing::warn!(%error, "Could not read the requirements hash");
+                        None
+                    });
+
+                (ProjectState::Installed, requirements_hash)
+            }
+            Err(error) => {
+                tracing::warn!(%error, "Failed to install project");
+
+                if let CheckAndInstallError::InstallError(install_error) = &error {
+                    if let Some(kept_env_dir) = install_error.kept_env_dir() {
+                        Self::spawn_cleanup(kept_env_dir.to_path_buf(), cleanup_delay);
+                    }
+                }
+
+                (ProjectState::InstallFailed(InstallFailure::Failed), None)
+            }
+        }
+    }
+
+    /// Registers the install report and the log files, the files of a previous installation are updated.
+    /// Correctness: A failure is logged, the installation is not affected.
+    async fn register_install_artifacts(
+        artifact_store: &ArtifactStore,
+        project_id: &str,
+        installer: &LocalProjectInstaller,
+        installation_dir: &Path,
+    ) {
+        let mut paths = vec![install_record::report_file_path(installation_dir)];
+        match installer.list_log_files().await {
+            Ok(log_files) => paths.extend(log_files.into_iter().map(|log_file| log_file.path)),
+            Err(error) => tracing::warn!(%error, "Could not list the log files"),
+        }
+
+        for path in paths {
+            // E.g. the report of an installation that failed its checks.
+            if !fs::try_exists(&path).await.unwrap_or(false) {
+                continue;
+            }
+
+            if let Err(error) = artifact_store
+                .register(project_id, ArtifactSource::Install, path)
+                .await
+            {
+                tracing::warn!(%error, "Could not register the artifact");
+            }
+        }
+    }
+
+    /// ```None``` if the installation of the project is not waiting for a slot.
+    pub fn install_queue_position(&self, project_id: &str) -> Option<usize> {
+        self.install_queue.position(project_id)
+    }
+
+    /// Passes the configuration of the manager to the installer of the project.
+    fn create_installer(
+        &self,
+        project: StoredProject,
+        event_sender: Option<mpsc::Sender<InstallerEvent>>,
+    ) -> (LocalProjectInstaller, LocalProjectInstallerController) {
+        let (mut installer, controller) = LocalProjectInstaller::new(
+            project.id.clone(),
+            project.source_dir,
+            self.get_project_installation_dir(&project.tenant_id, project.id.clone()),
+            self.get_project_environment_dir(&project.tenant_id, project.id.clone()),
+            event_sender,
+            ProcessIoConfig::default(),
+        );
+        installer.set_pip_cache_dir(self.get_pip_cache_dir());
+        installer.set_python_config(self.python_config.clone());
+        installer.set_installer_backend(self.installer_backend);
+        installer.set_pip_retry_config(self.pip_retry_config.clone());
+        installer.set_pip_options(self.project_pip_options(&project.id).clone());
+        installer.set_audit_config(self.audit_config.clone());
+        installer.set_log_rotation_config(self.log_rotation_config);
+        installer.set_env_store(self.env_store.clone());
+        installer.set_cleanup_policy(self.cleanup_policy);
+        installer.set_project_checks(self.project_checks.clone());
+
+        (installer, controller)
     }
 
-    /// After a successful installation, the project is copied to the installation directory.
-    async fn copy_installed_project_to_installation_dir(
+    /// The record of the last installation of the project, e.g. for the installation history of the API.
+    pub async fn load_install_report(
         &self,
         project_id: String,
-    ) -> Result<(), ()> {
-        todo!()
+    ) -> Result<InstallRecord, LoadReportError> {
+        let tenant_id = self
+            .tenant_of(&project_id)
+            .await
+            .map_err(LoadReportError::ProjectStore)?;
+
+        install_record::read(&self.get_project_installation_dir(&tenant_id, project_id)).await
     }
 
-    pub async fn uninstall_project(&self, project_id: String) {
-        todo!()
+    /// Cancels a running installation and waits for it, then deletes the environment and the installed project.
+    /// The project is ```ProjectState::Uploaded``` afterwards, it can be installed again.
+    /// A running project must be stopped first, see ```transition_project```.
+    /// Fails with ```UninstallProjectError::AlreadyInProgress``` while the project is uninstalled or deleted.
+    /// Correctness: A project that is partially uninstalled is ```ProjectState::InstallFailed```, uninstalling it can be retried.
+    pub async fn uninstall_project(&self, project_id: String) -> Result<(), UninstallProjectError> {
+        let span = info_span!("LocalProjectManager::uninstall_project", project_id);
+
+        async {
+            self.cancel_installation(&project_id)
+                .await
+                .map_err(UninstallProjectError::CouldNotCancelInstallation)?;
+
+            let _project_lock_guard = self
+                .project_locks
+                .try_lock(&project_id)
+                .ok_or_else(|| UninstallProjectError::AlreadyInProgress(project_id.clone()))?;
+
+            self.uninstall_locked(&project_id).await
+        }
+        .instrument(span)
+        .await
     }
 
-    pub async fn delete_project(&self, project_id: String) {
-        todo!()
+    /// The installation must be cancelled and the project locked, see ```uninstall_project```.
+    async fn uninstall_locked(&self, project_id: &str) -> Result<(), UninstallProjectError> {
+        self.project_states
+            .transition(
+                self.project_store.as_ref(),
+                project_id,
+                ProjectState::Uninstalling,
+                None,
+            )
+            .await?;
+
+        let (next, teardown_result) = match self.tear_down_installation(project_id).await {
+            Ok(()) => (ProjectState::Uploaded, Ok(())),
+            Err(error) => (
+                ProjectState::InstallFailed(InstallFailure::PartiallyUninstalled),
+                Err(error),
+            ),
+        };
+
+        self.project_states
+            .transition(self.project_store.as_ref(), project_id, next, None)
+            .await?;
+
+        tracing::info!(?next, "Uninstalled project");
+
+        teardown_result
     }
 
-    pub async fn current_installation_count(&self) -> usize {
-        self.controllers.read().await.len()
+    /// Cancels the installation of the project, if it is running, and waits for its task to finish, see ```do_install_project```.
+    async fn cancel_installation(
+        &self,
+        project_id: &str,
+    ) -> Result<(), SendingCancellationSignalToInstallerError> {
+        if self.install_queue.cancel(project_id) {
+            tracing::debug!("Cancelled queued installation");
+        } else if let Some(controller) = self.controllers.write().await.get_mut(project_id) {
+            if let Some(error) = controller.cancel().await? {
+                tracing::warn!(%error, "Could not wait for the cancelled installation");
+            }
+        }
+
+        loop {
+            let installation_finished = self.installation_finished.notified();
+            tokio::pin!(installation_finished);
+            // Registered before the check, a task that finishes in between is not missed.
+            installation_finished.as_mut().enable();
+
+            if !self.controllers.read().await.contains_key(project_id) {
+                return Ok(());
+            }
+
+            installation_finished.await;
+        }
+    }
+
+    /// Missing dirs and files are skipped, e.g. of a project whose installation failed.
+    async fn tear_down_installation(&self, project_id: &str) -> Result<(), UninstallProjectError> {
+        let tenant_id = self
+            .tenant_of(project_id)
+            .await
+            .map_err(UninstallProjectError::CouldNotLoadProject)?;
+        let project_env_dir = self.get_project_environment_dir(&tenant_id, project_id.to_owned());
+
+        if let Some(env_store) = &self.env_store {
+            env_store
+                .release(project_id, &project_env_dir)
+                .await
+                .map_err(UninstallProjectError::CouldNotReleaseEnvironment)?;
+        }
+
+        LocalProjectInstaller::delete_env_dir_if_exists(
+            &project_env_dir,
+            &CancellationToken::new(),
+        )
+        .await
+        .map_err(UninstallProjectError::CouldNotDeleteEnvironment)?;
+
+        requirements_hash::remove(&requirements_hash::file_path(&project_env_dir))
+            .await
+            .map_err(UninstallProjectError::CouldNotDeleteRequirementsHash)?;
+
+        remove_dir_all_if_exists(
+            &self.get_project_installation_dir(&tenant_id, project_id.to_owned()),
+        )
+        .await
+        .map_err(UninstallProjectError::CouldNotDeleteInstalledProject)
+    }
+
+    /// Uninstalls the project, if it is installed, then deletes its uploaded dir, its uploaded copy and removes it from the database.
+    /// Fails with ```DeleteProjectError::AlreadyInProgress``` while the project is uninstalled or deleted.
+    /// Correctness: The project is ```ProjectState::Deleted``` even if deleting its uploaded dir or removing it fails,
+    /// the remains are reported in the error and removed as a project with the same id is added again.
+    pub async fn delete_project(&self, project_id: String) -> Result<(), DeleteProjectError> {
+        let span = info_span!("LocalProjectManager::delete_project", project_id);
+
+        async {
+            let project = self
+                .project_store
+                .get(&project_id)
+                .await
+                .map_err(DeleteProjectError::CouldNotLoadProject)?
+                .ok_or_else(|| DeleteProjectError::ProjectDoesNotExist(project_id.clone()))?;
+
+            self.cancel_installation(&project_id)
+                .await
+                .map_err(UninstallProjectError::CouldNotCancelInstallation)?;
+
+            let _project_lock_guard = self
+                .project_locks
+                .try_lock(&project_id)
+                .ok_or_else(|| DeleteProjectError::AlreadyInProgress(project_id.clone()))?;
+
+            let state = self
+                .project_state(&project_id)
+                .await
+                .map_err(DeleteProjectError::CouldNotLoadProject)?;
+            if state != Some(ProjectState::Uploaded) {
+                self.uninstall_locked(&project_id).await?;
+            }
+
+            self.project_states
+                .transition(
+                    self.project_store.as_ref(),
+                    &project_id,
+                    ProjectState::Deleted,
+                    None,
+                )
+                .await?;
+
+            remove_dir_all_if_exists(&project.source_dir)
+                .await
+                .map_err(DeleteProjectError::CouldNotDeleteUploadedProject)?;
+
+            if let Some(blob_store) = &self.blob_store {
+                blob_store::delete_dir(
+                    blob_store.as_ref(),
+                    &uploaded_project_blob_prefix(&project_id),
+                )
+                .await
+                .map_err(DeleteProjectError::CouldNotDeleteUploadedCopy)?;
+            }
+
+            let runs = self
+                .project_store
+                .list_runs(&project_id)
+                .await
+                .map_err(DeleteProjectError::CouldNotLoadProject)?;
+            for run in runs {
+                remove_dir_all_if_exists(&self.get_run_dir(&project.tenant_id, &run.id))
+                    .await
+                    .map_err(DeleteProjectError::CouldNotDeleteRuns)?;
+            }
+
+            self.artifact_store
+                .remove_project(&project_id)
+                .await
+                .map_err(DeleteProjectError::CouldNotRemoveArtifacts)?;
+
+            self.remove_project_from_database(project_id)
+                .await
+                .map_err(DeleteProjectError::CouldNotRemoveProject)?;
+
+            tracing::info!("Deleted project");
+
+            Ok(())
+        }
+        .instrument(span)
+        .await
+    }
+
+    /// Writes the uploaded project, its metadata and the lockfile of its last successful installation
+    /// to a tar.gz archive at ```archive_path```, e.g. to move the project to another instance, see ```import_project```.
+    /// The environment and the io files of the installer are not exported.
+    pub async fn export_project(
+        &self,
+        project_id: &str,
+        archive_path: &Path,
+    ) -> Result<ProjectExportMetadata, ExportProjectError> {
+        let project = self
+            .project_store
+            .get(project_id)
+            .await
+            .map_err(ExportProjectError::CouldNotLoadProject)?
+            .ok_or_else(|| ExportProjectError::ProjectDoesNotExist(project_id.to_owned()))?;
+
+        let (installer, _controller) = self.create_installer(project.clone(), None);
+        let project_manifest = manifest::generate(&project.source_dir, |relative_path| {
+            installer.is_io_file(relative_path)
+        })
+        .await
+        .map_err(ExportProjectError::CouldNotReadProject)?;
+        let lockfile_path = installer.get_installed_lock_file_path();
+        let lockfile_path = fs::try_exists(&lockfile_path)
+            .await
+            .map_err(ExportProjectError::CouldNotReadProject)?
+            .then_some(lockfile_path);
+
+        let metadata = ProjectExportMetadata::of_project(&project);
+        project_export::write_archive(
+            archive_path,
+            &metadata,
+            &project.source_dir,
+            &project_manifest,
+            lockfile_path,
+        )
+        .await?;
+
+        tracing::info!(project_id, ?archive_path, "Exported project");
+
+        Ok(metadata)
+    }
+
+    /// Imports an archive of ```export_project```, e.g. of another instance, with the id, the name and the tags of the exported project.
+    /// The project is checked like an added project and is ```ProjectState::Uploaded```, it has to be installed.
+    /// Correctness: The archive is extracted to an ```.import-<uuid>``` dir in the uploaded projects dir,
+    /// an import that is interrupted by a restart leaves it behind.
+    pub async fn import_project(
+        &self,
+        archive_path: &Path,
+        archive_limits: ArchiveLimits,
+    ) -> Result<ImportedProject, ImportProjectError> {
+        let import_dir = self
+            .storage_layout
+            .uploaded_projects_dir
+            .join(format!(".import-{}", Uuid::new_v4()));
+
+        let import_result = self
+            .import_extracted_project(archive_path, &import_dir, archive_limits)
+            .await;
+
+        if let Err(error) = remove_dir_all_if_exists(&import_dir).await {
+            tracing::warn!(%error, ?import_dir, "Could not remove the import dir");
+        }
+
+        import_result
+    }
+
+    async fn import_extracted_project(
+        &self,
+        archive_path: &Path,
+        import_dir: &Path,
+        archive_limits: ArchiveLimits,
+    ) -> Result<ImportedProject, ImportProjectError> {
+        let extracted = project_export::extract(archive_path, import_dir, archive_limits).await?;
+        let metadata = extracted.metadata;
+        let project_id = metadata.id.clone();
+
+        let uploaded_project_dir = self
+            .tenant_storage_layout(&metadata.tenant_id)
+            .uploaded_projects_dir
+            .join(&project_id);
+        let project_exists = self
+            .project_store
+            .get(&project_id)
+            .await
+            .map_err(ImportProjectError::CouldNotLoadProject)?
+            .is_some();
+        if project_exists || dir_exists(&uploaded_project_dir).await {
+            return Err(ImportProjectError::ProjectAlreadyExists(project_id));
+        }
+
+        self.create_tenant_dirs(&metadata.tenant_id)
+            .await
+            .map_err(ImportProjectError::CouldNotMoveProject)?;
+        fs::rename(&extracted.source_dir, &uploaded_project_dir)
+            .await
+            .map_err(ImportProjectError::CouldNotMoveProject)?;
+
+        if let Err(error) = self
+            .add_new_tenant_project(
+                metadata.tenant_id.clone(),
+                project_id.clone(),
+                metadata.name.clone(),
+                uploaded_project_dir.clone(),
+            )
+            .await
+        {
+            let _ = remove_dir_all_if_exists(&uploaded_project_dir).await;
+
+            return Err(error.into());
+        }
+
+        if let Err(error) = self
+            .project_store
+            .set_tags(&project_id, &metadata.tags)
+            .await
+        {
+            tracing::warn!(%error, project_id, "Could not save the tags of the imported project");
+        }
+
+        if let Err(error) = self
+            .project_store
+            .set_details(
+                &project_id,
+                metadata.description.as_deref(),
+                metadata.owner.as_deref(),
+            )
+            .await
+        {
+            tracing::warn!(%error, project_id, "Could not save the details of the imported project");
+        }
+
+        tracing::info!(project_id, ?archive_path, "Imported project");
+
+        Ok(ImportedProject {
+            metadata,
+            packages: extracted.packages,
+        })
+    }
+
+    pub fn set_python_config(&mut self, python_config: PythonConfig) {
+        self.python_config = python_config;
+    }
+
+    pub fn python_config(&self) -> &PythonConfig {
+        &self.python_config
+    }
+
+    pub fn set_installer_backend(&mut self, installer_backend: InstallerBackend) {
+        self.installer_backend = installer_backend;
+    }
+
+    pub fn installer_backend(&self) -> InstallerBackend {
+        self.installer_backend
+    }
+
+    pub fn set_pip_retry_config(&mut self, pip_retry_config: PipRetryConfig) {
+        self.pip_retry_config = pip_retry_config;
+    }
+
+    pub fn pip_retry_config(&self) -> &PipRetryConfig {
+        &self.pip_retry_config
+    }
+
+    pub fn set_pip_options(&mut self, pip_options: PipOptions) {
+        self.pip_options = pip_options;
+    }
+
+    pub fn pip_options(&self) -> &PipOptions {
+        &self.pip_options
+    }
+
+    /// The options of a project replace the global options as a whole, they are not merged.
+    /// ```None``` removes the override.
+    pub fn set_project_pip_options(&mut self, project_id: String, pip_options: Option<PipOptions>) {
+        match pip_options {
+            Some(pip_options) => {
+                self.project_pip_options.insert(project_id, pip_options);
+            }
+            None => {
+                self.project_pip_options.remove(&project_id);
+            }
+        }
+    }
+
+    /// The options, that are used to install the project.
+    pub fn project_pip_options(&self, project_id: &str) -> &PipOptions {
+        self.project_pip_options
+            .get(project_id)
+            .unwrap_or(&self.pip_options)
+    }
+
+    pub fn set_audit_config(&mut self, audit_config: AuditConfig) {
+        self.audit_config = audit_config;
+    }
+
+    pub fn audit_config(&self) -> &AuditConfig {
+        &self.audit_config
+    }
+
+    pub fn set_log_rotation_config(&mut self, log_rotation_config: LogRotationConfig) {
+        self.log_rotation_config = log_rotation_config;
+    }
+
+    pub fn log_rotation_config(&self) -> &LogRotationConfig {
+        &self.log_rotation_config
+    }
+
+    /// Projects with the same requirements share one environment, see ```EnvStore```.
+    pub fn set_share_environments(&mut self, share_environments: bool) {
+        self.env_store =
+            share_environments.then(|| EnvStore::new(self.get_shared_environments_dir()));
+    }
+
+    pub fn share_environments(&self) -> bool {
+        self.env_store.is_some()
+    }
+
+    pub fn set_cleanup_policy(&mut self, cleanup_policy: CleanupPolicy) {
+        self.cleanup_policy = cleanup_policy;
+    }
+
+    pub fn cleanup_policy(&self) -> CleanupPolicy {
+        self.cleanup_policy
+    }
+
+    pub fn set_cleanup_delay(&mut self, cleanup_delay: Duration) {
+        self.cleanup_delay = cleanup_delay;
+    }
+
+    pub fn cleanup_delay(&self) -> Duration {
+        self.cleanup_delay
+    }
+
+    /// Queued installations wait for a slot in order, see ```InstallTicket::queue_position```.
+    /// Correctness: Installations started before keep the previous limit.
+    pub fn set_max_concurrent_installations(&mut self, max_concurrent_installations: usize) {
+        self.install_queue = InstallQueue::new(max_concurrent_installations);
+    }
+
+    pub fn max_concurrent_installations(&self) -> usize {
+        self.install_queue.max_concurrent_installations()
+    }
+
+    pub fn set_project_checks(&mut self, project_checks: ProjectChecks) {
+        self.project_checks = project_checks;
+    }
+
+    pub fn project_checks(&self) -> &ProjectChecks {
+        &self.project_checks
+    }
+
+    pub fn set_run_config_limits(&mut self, run_config_limits: RunConfigLimits) {
+        self.run_config_limits = run_config_limits;
+    }
+
+    pub fn run_config_limits(&self) -> &RunConfigLimits {
+        &self.run_config_limits
+    }
+
+    /// Correctness: The reports of finished runs are not rendered again, unless their files are deleted.
+    pub fn set_report_thresholds(&mut self, report_thresholds: ReportThresholds) {
+        self.report_thresholds = report_thresholds;
+    }
+
+    pub fn report_thresholds(&self) -> &ReportThresholds {
+        &self.report_thresholds
+    }
+
+    /// Lists, prunes and opens the registered artifacts. An installation registers its logs and its report,
+    /// a run registers every file of its run dir, after it finished.
+    pub fn artifact_store(&self) -> &ArtifactStore {
+        &self.artifact_store
+    }
+
+    /// Correctness: The installations and runs, that are in progress, register their artifacts with the previous retention.
+    pub fn set_artifact_retention(&mut self, artifact_retention: ArtifactRetention) {
+        self.artifact_store.set_retention(artifact_retention);
+    }
+
+    pub fn artifact_retention(&self) -> ArtifactRetention {
+        self.artifact_store.retention()
+    }
+
+    /// Keeps the uploaded projects and the artifacts in ```blob_store```, e.g. an ```S3BlobStore```, so every replica can install
+    /// the projects and download the artifacts. The environments and the installed projects stay local.
+    /// Correctness: The projects and artifacts added before are not uploaded.
+    pub fn set_blob_store(&mut self, blob_store: Option<Arc<dyn BlobStore>>) {
+        self.artifact_store.set_blob_store(blob_store.clone());
+        self.blob_store = blob_store;
+    }
+
+    pub fn blob_store(&self) -> Option<&Arc<dyn BlobStore>> {
+        self.blob_store.as_ref()
+    }
+
+    /// ```None``` removes the quota, the tenant is not limited.
+    /// Correctness: The installations and runs in progress are not interrupted by a lower quota.
+    pub fn set_tenant_quota(&mut self, tenant_id: TenantId, tenant_quota: Option<TenantQuota>) {
+        match tenant_quota {
+            Some(tenant_quota) => {
+                self.tenant_quotas.insert(tenant_id, tenant_quota);
+            }
+            None => {
+                self.tenant_quotas.remove(&tenant_id);
+            }
+        }
+    }
+
+    pub fn tenant_quota(&self, tenant_id: &TenantId) -> TenantQuota {
+        self.tenant_quotas
+            .get(tenant_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Deletes the environment a failed installation kept after ```cleanup_delay```, see ```CleanupPolicy```.
+    /// Returns ```None``` if the installation did not keep its environment.
+    /// Correctness: A failure is logged. A kept environment that is not deleted before a restart is removed as a stale staging dir.
+    pub fn schedule_cleanup(&self, install_error: &InstallError) -> Option<JoinHandle<()>> {
+        let kept_env_dir = install_error.kept_env_dir()?.to_path_buf();
+
+        Some(Self::spawn_cleanup(kept_env_dir, self.cleanup_delay))
+    }
+
+    fn spawn_cleanup(kept_env_dir: PathBuf, cleanup_delay: Duration) -> JoinHandle<()> {
+        tracing::info!(
+            ?kept_env_dir,
+            ?cleanup_delay,
+            "Scheduled cleanup of kept environment"
+        );
+
+        tokio::spawn(
+            async move {
+                tokio::time::sleep(cleanup_delay).await;
+
+                match fs::remove_dir_all(&kept_env_dir).await {
+                    Ok(_) => tracing::info!(?kept_env_dir, "Deleted kept environment"),
+                    Err(error) => {
+                        tracing::warn!(?kept_env_dir, %error, "Could not delete kept environment")
+                    }
+                }
+            }
+            .in_current_span(),
+        )
+    }
+
+    /// Unlinks the project from its shared environment, e.g. when it is uninstalled.
+    /// Returns whether the environment was deleted, because no other project uses it.
+    pub async fn release_project_environment(
+        &self,
+        tenant_id: &TenantId,
+        project_id: String,
+    ) -> Result<bool, EnvStoreError> {
+        let Some(env_store) = &self.env_store else {
+            return Ok(false);
+        };
+
+        env_store
+            .release(
+                &project_id,
+                &self.get_project_environment_dir(tenant_id, project_id.clone()),
+            )
+            .await
+    }
+
+    /// Deletes the oldest files of the pip cache until it fits ```PipCacheConfig::max_size_bytes```.
+    /// Returns the number of deleted bytes.
+    pub async fn evict_pip_cache(&self) -> Result<u64, IoError> {
+        let Some(pip_cache_dir) = self.get_pip_cache_dir() else {
+            return Ok(0);
+        };
+
+        pip_cache::evict_to_max_size(&pip_cache_dir, self.pip_cache_config.max_size_bytes).await
+    }
+
+    pub async fn current_installation_count(&self) -> usize {
+        self.controllers.read().await.len()
+    }
+
+    /// ```None``` if the project is not being installed.
+    pub async fn installation_status(&self, project_id: &str) -> Option<InstallerStatus> {
+        self.controllers
+            .read()
+            .await
+            .get(project_id)
+            .map(LocalProjectInstallerController::status)
+    }
+
+    /// Validates the run config against ```run_config_limits``` and runs locust against the installed project,
+    /// see ```LocalProjectRunner```. Returns the id of the run and the task, that finishes with the run.
+    /// The project is ```ProjectState::Running``` until then and ```ProjectState::Installed``` afterwards,
+    /// also if the run failed. The output and the stats of locust are written to ```<runs_dir>/<run_id>```, see ```StorageLayout```.
+    /// The ```RunReport``` is saved before the project is installed again, see ```get_run_report```.
+    /// Fails with ```StartRunError::AlreadyInProgress``` while the project is installed, uninstalled or deleted
+    /// and with ```StartRunError::Quota``` if the tenant has ```TenantQuota::max_concurrent_runs``` runs in progress.
+    pub async fn start_run(
+        &self,
+        project_id: String,
+        run_config: RunConfig,
+    ) -> Result<(String, JoinHandle<Result<RunOutcome, RunError>>), StartRunError> {
+        run_config.validate(&self.run_config_limits)?;
+
+        // Held by the task until the run finished.
+        let project_lock_guard = self
+            .project_locks
+            .try_lock(&project_id)
+            .ok_or_else(|| StartRunError::AlreadyInProgress(project_id.clone()))?;
+
+        let tenant_id = self
+            .tenant_of(&project_id)
+            .await
+            .map_err(StartRunError::CouldNotLoadProject)?;
+        // Held by the task until the run finished.
+        let tenant_slot_guard = self.tenant_slots.try_acquire(
+            &tenant_id,
+            TenantSlot::Run,
+            &self.tenant_quota(&tenant_id),
+        )?;
+
+        self.project_states
+            .transition(
+                self.project_store.as_ref(),
+                &project_id,
+                ProjectState::Running,
+                None,
+            )
+            .await?;
+
+        let run_id = Uuid::new_v4().to_string();
+        let run_dir = self.get_run_dir(&tenant_id, &run_id);
+        let started_at = SystemTime::now();
+
+        let (runner, controller) = LocalProjectRunner::new(
+            project_id.clone(),
+            self.get_project_installation_dir(&tenant_id, project_id.clone()),
+            self.get_project_environment_dir(&tenant_id, project_id.clone()),
+            run_dir.clone(),
+            ProcessIoConfig::default(),
+        );
+        let csv_prefix = runner.get_locust_csv_prefix();
+        self.runners
+            .write()
+            .await
+            .insert(project_id.clone(), controller);
+
+        let project_store = self.project_store.clone();
+        let project_states = self.project_states.clone();
+        let runners = self.runners.clone();
+        let report_thresholds = self.report_thresholds.clone();
+        let artifact_store = self.artifact_store.clone();
+        let span = info_span!("LocalProjectManager::start_run", project_id, run_id);
+        let task_run_id = run_id.clone();
+
+        let task = tokio::spawn(
+            async move {
+                let run_result = runner.run(&run_config).await;
+
+                let mut run_config = run_config;
+                run_config
+                    .env_overrides
+                    .values_mut()
+                    .for_each(String::clear);
+                let run_report = RunReport {
+                    id: task_run_id,
+                    project_id: project_id.clone(),
+                    run_config,
+                    started_at,
+                    finished_at: SystemTime::now(),
+                    status: match &run_result {
+                        Ok(outcome) => RunReportStatus::Finished { outcome: *outcome },
+                        Err(error) => RunReportStatus::Failed {
+                            error: error.to_string(),
+                        },
+                    },
+                    stats: Vec::new(),
+                    failures: Vec::new(),
+                    exceptions: Vec::new(),
+                };
+                Self::save_run_report(
+                    project_store.as_ref(),
+                    &artifact_store,
+                    &run_dir,
+                    &csv_prefix,
+                    &report_thresholds,
+                    run_report,
+                )
+                .await;
+
+                for next in [ProjectState::Stopping, ProjectState::Installed] {
+                    if let Err(error) = project_states
+                        .transition(project_store.as_ref(), &project_id, next, None)
+                        .await
+                    {
+                        tracing::warn!(%error, "Could not transition the project");
+                    }
+                }
+
+                drop(project_lock_guard);
+                drop(tenant_slot_guard);
+                runners.write().await.remove(&project_id);
+
+                run_result
+            }
+            .instrument(span),
+        );
+
+        Ok((run_id, task))
+    }
+
+    /// Adds the stats of locust to the report, writes it and its rendered reports to the run dir and saves the run in the database.
+    /// The files of the run dir are registered as artifacts.
+    /// Correctness: A run, that could not be saved, is missing in ```list_runs```. The run itself is not affected.
+    async fn save_run_report(
+        project_store: &dyn ProjectStore,
+        artifact_store: &ArtifactStore,
+        run_dir: &Path,
+        csv_prefix: &Path,
+        report_thresholds: &ReportThresholds,
+        mut run_report: RunReport,
+    ) {
+        match run_report::read_locust_stats(csv_prefix).await {
+            Ok(locust_stats) => {
+                run_report.stats = locust_stats.stats;
+                run_report.failures = locust_stats.failures;
+                run_report.exceptions = locust_stats.exceptions;
+            }
+            Err(error) => tracing::warn!(%error, "Could not read the stats of locust"),
+        }
+
+        if let Err(error) = run_report::write(run_dir, &run_report).await {
+            tracing::warn!(%error, "Could not write the run report");
+            return;
+        }
+
+        // A missing rendered report is rendered on download, see ```get_run_report_file```.
+        if let Err(error) = report_generator::write(run_dir, &run_report, report_thresholds).await {
+            tracing::warn!(%error, "Could not render the run report");
+        }
+
+        if let Err(error) = artifact_store
+            .register_dir(
+                &run_report.project_id,
+                ArtifactSource::Run {
+                    run_id: run_report.id.clone(),
+                },
+                run_dir,
+            )
+            .await
+        {
+            tracing::warn!(%error, "Could not register the artifacts of the run");
+        }
+
+        if let Err(error) = project_store.insert_run(&run_report.to_stored_run()).await {
+            tracing::warn!(%error, "Could not save the run");
+        }
+    }
+
+    /// The finished runs of the project, the oldest run first.
+    pub async fn list_runs(&self, project_id: &str) -> Result<Vec<StoredRun>, ProjectStoreError> {
+        self.project_store.list_runs(project_id).await
+    }
+
+    pub async fn get_run_report(&self, run_id: &str) -> Result<RunReport, LoadRunReportError> {
+        run_report::read(&self.get_stored_run_dir(run_id).await?).await
+    }
+
+    /// The run dir of a saved run, in the dirs of the tenant of its project.
+    async fn get_stored_run_dir(&self, run_id: &str) -> Result<PathBuf, LoadRunReportError> {
+        let run = self
+            .project_store
+            .get_run(run_id)
+            .await
+            .map_err(LoadRunReportError::CouldNotLoadRun)?
+            .ok_or_else(|| LoadRunReportError::RunDoesNotExist(run_id.to_owned()))?;
+        let tenant_id = self
+            .tenant_of(&run.project_id)
+            .await
+            .map_err(LoadRunReportError::CouldNotLoadRun)?;
+
+        Ok(self.get_run_dir(&tenant_id, run_id))
+    }
+
+    /// The rendered report of the run, to be downloaded with ```ReportFormat::content_type```.
+    /// A missing file is rendered from the stored report with the current ```report_thresholds```.
+    pub async fn get_run_report_file(
+        &self,
+        run_id: &str,
+        format: ReportFormat,
+    ) -> Result<PathBuf, LoadRunReportError> {
+        let run_dir = self.get_stored_run_dir(run_id).await?;
+        let run_report = run_report::read(&run_dir).await?;
+        let path = report_generator::report_file_path(&run_dir, format);
+
+        if !fs::try_exists(&path).await.unwrap_or(false) {
+            report_generator::write_format(&run_dir, &run_report, &self.report_thresholds, format)
+                .await
+                .map_err(LoadRunReportError::CouldNotWriteRenderedReport)?;
+        }
+
+        Ok(path)
+    }
+
+    /// Interrupts the run of the project, the task of ```start_run``` finishes with ```RunOutcome::Stopped```.
+    pub async fn stop_run(&self, project_id: &str) -> Result<(), StopProjectRunError> {
+        self.runners
+            .read()
+            .await
+            .get(project_id)
+            .ok_or_else(|| StopProjectRunError::NotRunning(project_id.to_owned()))?
+            .stop()?;
+
+        Ok(())
+    }
+
+    /// ```None``` if the project is not running.
+    pub async fn run_status(&self, project_id: &str) -> Option<RunnerStatus> {
+        self.runners
+            .read()
+            .await
+            .get(project_id)
+            .map(RunController::status)
+    }
+
+    /// Receives the live metrics of the run of the project, see ```RunController::subscribe_metrics```.
+    /// ```None``` if the project is not running.
+    pub async fn subscribe_run_metrics(
+        &self,
+        project_id: &str,
+    ) -> Option<broadcast::Receiver<RunMetricsSample>> {
+        self.runners
+            .read()
+            .await
+            .get(project_id)
+            .map(RunController::subscribe_metrics)
+    }
+
+    /// Shuts down all running installations and runs and waits for them.
+    /// Correctness: Must be awaited before the runtime shuts down, the processes can not be killed on drop then.
+    pub async fn shutdown(&self) {
+        let span = info_span!("LocalProjectManager::shutdown");
+        let _span_guard = span.enter();
+
+        // Queued installations do not start after the shutdown.
+        self.install_queue.cancel_all();
+        let controllers = std::mem::take(&mut *self.controllers.write().await);
+
+        for (project_id, mut controller) in controllers {
+            if let Err(error) = controller.shutdown().await {
+                tracing::warn!(project_id, %error, "Failed to shut down installation");
+            }
+        }
+
+        let runners = std::mem::take(&mut *self.runners.write().await);
+
+        for (project_id, runner) in runners {
+            let mut status_receiver = runner.subscribe_status();
+
+            if let Err(error) = runner.stop() {
+                tracing::debug!(project_id, %error, "Run was not stopped");
+            }
+
+            let _ = status_receiver
+                .wait_for(|status| matches!(status, RunnerStatus::Finished(_)))
+                .await;
+        }
+    }
+}
+
+/// A missing dir is not an error.
+async fn remove_dir_all_if_exists(dir: &Path) -> Result<(), IoError> {
+    match fs::remove_dir_all(dir).await {
+        Err(error) if error.kind() != ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
+
+/// A dir that could not be checked is assumed to exist.
+async fn dir_exists(dir: &Path) -> bool {
+    fs::try_exists(dir).await.unwrap_or(true)
+}
+
+/// The blobs of the uploaded dir of a project, see ```LocalProjectManager::set_blob_store```.
+fn uploaded_project_blob_prefix(project_id: &str) -> String {
+    format!("uploaded_projects/{project_id}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        project_managers::local::blob_store::LocalBlobStore,
+        util::{copy_dir_all, test_dir},
+    };
+    use tracing_test::traced_test;
+
+    const CRATE_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+    /// Adds ```valid_offline``` to a new manager in ```root_dir```, its installation fails in the requirements phase.
+    async fn create_manager_with_offline_project(root_dir: &Path) -> LocalProjectManager {
+        let _ = fs::remove_dir_all(root_dir).await;
+        let uploaded_project_dir = root_dir.join("uploaded_projects").join("valid_offline");
+        copy_dir_all(
+            &Path::new(CRATE_DIR)
+                .join("tests_dir")
+                .join("uploaded_projects")
+                .join("valid_offline"),
+            &uploaded_project_dir,
+            |_| false,
+        )
+        .await
+        .expect("Could not copy uploaded project");
+        let mut local_project_manager = LocalProjectManager::with_project_store_backend(
+            root_dir.to_path_buf(),
+            PipCacheConfig::default(),
+            ProjectStoreBackend::InMemory,
+        )
+        .await
+        .expect("Error creating project manager.");
+        local_project_manager.set_installer_backend(InstallerBackend::Pip);
+        local_project_manager.set_pip_options(PipOptions {
+            wheelhouse_dir: Some(root_dir.join("wheelhouse_does_not_exist")),
+            ..PipOptions::default()
+        });
+        local_project_manager
+            .add_new_project_to_database(
+                String::from("valid_offline"),
+                String::from("Valid offline"),
+                uploaded_project_dir,
+            )
+            .await
+            .expect("Error adding project.");
+
+        local_project_manager
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn install_project_from_missing_wheelhouse_and_expect_failed_status() {
+        let temp_dir = test_dir("project_manager");
+        let root_dir = temp_dir.path();
+        let local_project_manager = create_manager_with_offline_project(root_dir).await;
+        let (event_sender, mut event_receiver) = mpsc::channel(1024);
+
+        let install_ticket = local_project_manager
+            .do_install_project(String::from("valid_offline"), Some(event_sender))
+            .await
+            .expect("Error starting installation.");
+        let install_again_result = local_project_manager
+            .do_install_project(String::from("valid_offline"), None)
+            .await;
+        let queue_position = install_ticket.queue_position;
+        install_ticket
+            .task
+            .await
+            .expect("Installation task panicked.");
+        let project = local_project_manager.project("valid_offline").await;
+        let project_state = local_project_manager
+            .project_state("valid_offline")
+            .await
+            .expect("Error getting project state.");
+        let installation_count = local_project_manager.current_installation_count().await;
+        let mut installer_events = Vec::new();
+        while let Some(installer_event) = event_receiver.recv().await {
+            installer_events.push(installer_event);
+        }
+
+        assert!(matches!(
+            install_again_result,
+            Err(InstallProjectError::AlreadyInProgress(_))
+        ));
+        assert_eq!(queue_position, None);
+        let project = project
+            .expect("Error getting project.")
+            .expect("Project does not exist.");
+        assert_eq!(project.status, ProjectStatus::Failed);
+        assert_eq!(
+            project_state,
+            Some(ProjectState::InstallFailed(InstallFailure::Failed))
+        );
+        assert_eq!(installation_count, 0);
+        assert!(matches!(
+            installer_events.last(),
+            Some(InstallerEvent::Failed { .. })
+        ));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn run_project_without_locust_and_expect_installed_after_failed_run() {
+        let temp_dir = test_dir("project_manager_run");
+        let root_dir = temp_dir.path();
+        let local_project_manager = create_manager_with_offline_project(root_dir).await;
+        let run_config = RunConfig {
+            script_id: String::from("main.py"),
+            users: 1,
+            spawn_rate: 1.0,
+            duration: 1,
+            workers: 0,
+            host: None,
+            tags: Vec::new(),
+            env_overrides: HashMap::new(),
+        };
+
+        let uploaded_run_result = local_project_manager
+            .start_run(String::from("valid_offline"), run_config.clone())
+            .await;
+        // Installed without an environment, e.g. deleted outside of the manager.
+        for next in [
+            ProjectState::QueuedForInstall,
+            ProjectState::Checking,
+            ProjectState::Installed,
+        ] {
+            local_project_manager
+                .transition_project("valid_offline", next)
+                .await
+                .expect("Error transitioning project.");
+        }
+        let (run_id, run_task) = local_project_manager
+            .start_run(
+                String::from("valid_offline"),
+                RunConfig {
+                    env_overrides: HashMap::from([(
+                        String::from("API_KEY"),
+                        String::from("secret"),
+                    )]),
+                    ..run_config.clone()
+                },
+            )
+            .await
+            .expect("Error starting run.");
+        let run_result = run_task.await.expect("Run task panicked.");
+        let runs = local_project_manager.list_runs("valid_offline").await;
+        let run_report = local_project_manager.get_run_report(&run_id).await;
+        let missing_run_report = local_project_manager.get_run_report("missing").await;
+        let artifacts = local_project_manager
+            .artifact_store()
+            .list("valid_offline")
+            .await;
+        let html_report_path = local_project_manager
+            .get_run_report_file(&run_id, ReportFormat::Html)
+            .await
+            .expect("Error getting HTML report.");
+        let html_report = fs::read_to_string(&html_report_path)
+            .await
+            .expect("Error reading HTML report.");
+        fs::remove_file(
+            local_project_manager
+                .get_run_dir(&TenantId::default(), &run_id)
+                .join("report.md"),
+        )
+        .await
+        .expect("Error removing Markdown report.");
+        let markdown_report = match local_project_manager
+            .get_run_report_file(&run_id, ReportFormat::Markdown)
+            .await
+        {
+            Ok(path) => fs::read_to_string(path).await.ok(),
+            Err(_) => None,
+        };
+        let project_state = local_project_manager
+            .project_state("valid_offline")
+            .await
+            .expect("Error getting project state.");
+        let stop_result = local_project_manager.stop_run("valid_offline").await;
+        let invalid_run_result = local_project_manager
+            .start_run(
+                String::from("valid_offline"),
+                RunConfig {
+                    users: 0,
+                    ..run_config
+                },
+            )
+            .await;
+
+        assert!(matches!(
+            uploaded_run_result,
+            Err(StartRunError::Transition(
+                ProjectTransitionError::InvalidTransition {
+                    from: ProjectState::Uploaded,
+                    ..
+                }
+            ))
+        ));
+        assert!(matches!(run_result, Err(RunError::LocustfileNotFound(_))));
+        let runs = runs.expect("Error listing runs.");
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].id, run_id);
+        assert_eq!(runs[0].outcome, None);
+        let run_report = run_report.expect("Error getting run report.");
+        assert!(matches!(run_report.status, RunReportStatus::Failed { .. }));
+        assert!(run_report.stats.is_empty());
+        assert_eq!(
+            run_report.run_config.env_overrides,
+            HashMap::from([(String::from("API_KEY"), String::new())])
+        );
+        assert!(matches!(
+            missing_run_report,
+            Err(LoadRunReportError::RunDoesNotExist(_))
+        ));
+        assert_eq!(
+            html_report_path,
+            local_project_manager
+                .get_run_dir(&TenantId::default(), &run_id)
+                .join("report.html")
+        );
+        assert!(artifacts
+            .iter()
+            .any(|artifact| artifact.name == "run_report.json"
+                && artifact.source
+                    == ArtifactSource::Run {
+                        run_id: run_id.clone()
+                    }));
+        assert!(html_report.contains("<h2 class=\"failed\">Failed</h2>"));
+        assert!(markdown_report
+            .expect("Error rendering Markdown report.")
+            .contains("**Verdict:** Failed\n\n- Run failed: "));
+        assert_eq!(project_state, Some(ProjectState::Installed));
+        assert!(matches!(
+            stop_result,
+            Err(StopProjectRunError::NotRunning(_))
+        ));
+        assert!(matches!(
+            invalid_run_result,
+            Err(StartRunError::InvalidRunConfig(
+                RunConfigError::InvalidUsers { .. }
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn export_and_import_project_and_expect_same_project_in_other_manager() {
+        let temp_dir = test_dir("project_manager_export");
+        let root_dir = temp_dir.path();
+        let other_temp_dir = test_dir("project_manager_import");
+        let other_root_dir = other_temp_dir.path();
+        let local_project_manager = create_manager_with_offline_project(root_dir).await;
+        local_project_manager
+            .set_project_tags("valid_offline", BTreeSet::from([String::from("checkout")]))
+            .await
+            .expect("Error setting tags.");
+        let other_local_project_manager = LocalProjectManager::with_project_store_backend(
+            other_root_dir.to_path_buf(),
+            PipCacheConfig::default(),
+            ProjectStoreBackend::InMemory,
+        )
+        .await
+        .expect("Error creating other project manager.");
+        let archive_path = root_dir.join("valid_offline.tar.gz");
+
+        let metadata = local_project_manager
+            .export_project("valid_offline", &archive_path)
+            .await;
+        let missing_export_result = local_project_manager
+            .export_project("missing", &root_dir.join("missing.tar.gz"))
+            .await;
+        let imported_project = other_local_project_manager
+            .import_project(&archive_path, ArchiveLimits::default())
+            .await;
+        let import_again_result = other_local_project_manager
+            .import_project(&archive_path, ArchiveLimits::default())
+            .await;
+        let project = other_local_project_manager.project("valid_offline").await;
+        let project_state = other_local_project_manager
+            .project_state("valid_offline")
+            .await
+            .expect("Error getting project state.");
+        let mut uploaded_dir_names = Vec::new();
+        let mut uploaded_dirs = fs::read_dir(other_root_dir.join("uploaded_projects"))
+            .await
+            .expect("Error reading uploaded projects dir.");
+        while let Some(entry) = uploaded_dirs
+            .next_entry()
+            .await
+            .expect("Error reading uploaded projects dir.")
+        {
+            uploaded_dir_names.push(entry.file_name());
+        }
+
+        let metadata = metadata.expect("Error exporting project.");
+        assert_eq!(metadata.id, "valid_offline");
+        assert!(matches!(
+            missing_export_result,
+            Err(ExportProjectError::ProjectDoesNotExist(_))
+        ));
+        let imported_project = imported_project.expect("Error importing project.");
+        assert_eq!(imported_project.metadata, metadata);
+        assert!(imported_project.packages.is_empty());
+        assert!(matches!(
+            import_again_result,
+            Err(ImportProjectError::ProjectAlreadyExists(_))
+        ));
+        let project = project
+            .expect("Error getting project.")
+            .expect("Project does not exist.");
+        assert_eq!(project.name, "Valid offline");
+        assert_eq!(project.tags, BTreeSet::from([String::from("checkout")]));
+        assert_eq!(project_state, Some(ProjectState::Uploaded));
+        assert_eq!(uploaded_dir_names, ["valid_offline"]);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn uninstall_and_delete_failed_project_and_expect_every_dir_deleted() {
+        let temp_dir = test_dir("project_manager_delete");
+        let root_dir = temp_dir.path();
+        let local_project_manager = create_manager_with_offline_project(root_dir).await;
+        local_project_manager
+            .do_install_project(String::from("valid_offline"), None)
+            .await
+            .expect("Error starting installation.")
+            .task
+            .await
+            .expect("Installation task panicked.");
+        let project_env_dir = local_project_manager
+            .get_project_environment_dir(&TenantId::default(), String::from("valid_offline"));
+        let installed_project_dir = local_project_manager
+            .get_project_installation_dir(&TenantId::default(), String::from("valid_offline"));
+        for dir in [&project_env_dir, &installed_project_dir] {
+            fs::create_dir_all(dir).await.expect("Error creating dir.");
+        }
+
+        let uninstall_result = local_project_manager
+            .uninstall_project(String::from("valid_offline"))
+            .await;
+        let state_after_uninstall = local_project_manager.project_state("valid_offline").await;
+        let dirs_exist_after_uninstall = [
+            fs::try_exists(&project_env_dir).await,
+            fs::try_exists(&installed_project_dir).await,
+        ];
+        let uninstall_again_result = local_project_manager
+            .uninstall_project(String::from("valid_offline"))
+            .await;
+        let delete_result = local_project_manager
+            .delete_project(String::from("valid_offline"))
+            .await;
+        let uploaded_project_dir_exists =
+            fs::try_exists(root_dir.join("uploaded_projects").join("valid_offline")).await;
+        let project = local_project_manager.project("valid_offline").await;
+
+        uninstall_result.expect("Error uninstalling project.");
+        assert_eq!(
+            state_after_uninstall.expect("Error getting project state."),
+            Some(ProjectState::Uploaded)
+        );
+        for dir_exists in dirs_exist_after_uninstall {
+            assert!(!dir_exists.expect("Error checking dir."));
+        }
+        assert!(matches!(
+            uninstall_again_result,
+            Err(UninstallProjectError::Transition(
+                ProjectTransitionError::InvalidTransition {
+                    from: ProjectState::Uploaded,
+                    ..
+                }
+            ))
+        ));
+        delete_result.expect("Error deleting project.");
+        assert!(!uploaded_project_dir_exists.expect("Error checking dir."));
+        assert_eq!(project.expect("Error getting project."), None);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn restart_during_installation_and_expect_interrupted_project_and_removed_orphans() {
+        let temp_dir = test_dir("project_manager_recovery");
+        let root_dir = temp_dir.path();
+        let create_manager = || {
+            LocalProjectManager::with_project_store_backend(
+                root_dir.to_path_buf(),
+                PipCacheConfig::default(),
+                ProjectStoreBackend::JsonFile,
+            )
+        };
+        let local_project_manager = create_manager()
+            .await
+            .expect("Error creating project manager.");
+        let uploaded_project_dir = root_dir.join("uploaded_projects").join("valid_offline");
+        fs::create_dir_all(&uploaded_project_dir)
+            .await
+            .expect("Error creating dir.");
+        let now = SystemTime::now();
+        local_project_manager
+            .project_store
+            .insert(&StoredProject {
+                id: String::from("valid_offline"),
+                name: String::from("Valid offline"),
+                source_dir: uploaded_project_dir,
+                status: ProjectStatus::Installing,
+                created_at: now,
+                updated_at: now,
+                requirements_hash: None,
+                tags: BTreeSet::new(),
+                tenant_id: TenantId::default(),
+                description: None,
+                owner: None,
+            })
+            .await
+            .expect("Error inserting project.");
+        let orphan_env_dir = local_project_manager
+            .get_project_environment_dir(&TenantId::default(), String::from("ghost"));
+        let orphan_installed_project_dir = local_project_manager
+            .get_project_installation_dir(&TenantId::default(), String::from("ghost"));
+        for dir in [&orphan_env_dir, &orphan_installed_project_dir] {
+            fs::create_dir_all(dir).await.expect("Error creating dir.");
+        }
+        drop(local_project_manager);
+
+        let local_project_manager = create_manager()
+            .await
+            .expect("Error creating project manager.");
+        let recovery_report = local_project_manager.recovery_report().clone();
+        let project_state = local_project_manager.project_state("valid_offline").await;
+
+        assert_eq!(
+            recovery_report.interrupted_installations,
+            vec![String::from("valid_offline")]
+        );
+        assert!(recovery_report.missing_installations.is_empty());
+        assert!(recovery_report.missing_uploads.is_empty());
+        assert_eq!(
+            recovery_report.removed_orphans,
+            vec![orphan_env_dir, orphan_installed_project_dir]
+        );
+        assert_eq!(
+            project_state.expect("Error getting project state."),
+            Some(ProjectState::InstallFailed(InstallFailure::Interrupted))
+        );
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn create_manager_with_storage_layout_and_expect_dirs_created_and_environments_migrated()
+    {
+        let temp_dir = test_dir("project_manager_storage_layout");
+        let test_dir = temp_dir.path();
+        let root_dir = test_dir.join("root");
+        let logs_dir = test_dir.join("logs");
+        fs::create_dir_all(root_dir.join("enviroments").join("project"))
+            .await
+            .expect("Error creating dir.");
+
+        let local_project_manager = LocalProjectManager::with_storage_layout(
+            root_dir.clone(),
+            PipCacheConfig::default(),
+            ProjectStoreBackend::InMemory,
+            StorageLayout {
+                environments_dir: PathBuf::from("envs"),
+                logs_dir: logs_dir.clone(),
+                ..StorageLayout::default()
+            },
+        )
+        .await
+        .expect("Error creating project manager.");
+        let mut dirs_exist = Vec::new();
+        for dir in local_project_manager.storage_layout().dirs() {
+            dirs_exist.push(fs::try_exists(dir).await.unwrap_or(false));
+        }
+        let legacy_environments_dir_exists = fs::try_exists(root_dir.join("enviroments")).await;
+
+        assert_eq!(dirs_exist, vec![true; 5]);
+        assert_eq!(local_project_manager.storage_layout().logs_dir, logs_dir);
+        assert_eq!(
+            local_project_manager.storage_layout().environments_dir,
+            root_dir.join("envs")
+        );
+        // Migrated, then removed, no project of the store uses it.
+        assert_eq!(
+            local_project_manager.recovery_report().removed_orphans,
+            vec![root_dir.join("envs").join("project")]
+        );
+        assert!(!legacy_environments_dir_exists.expect("Error checking dir."));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn add_projects_of_tenant_and_expect_tenant_dirs_and_quota_enforced() {
+        let temp_dir = test_dir("project_manager_tenants");
+        let test_dir = temp_dir.path();
+        let root_dir = test_dir.join("root");
+        let tenant_id = TenantId::new("acme").expect("Error creating tenant id.");
+        let mut local_project_manager = LocalProjectManager::with_project_store_backend(
+            root_dir.clone(),
+            PipCacheConfig::default(),
+            ProjectStoreBackend::InMemory,
+        )
+        .await
+        .expect("Error creating project manager.");
+        local_project_manager.set_tenant_quota(
+            tenant_id.clone(),
+            Some(TenantQuota {
+                max_projects: Some(1),
+                max_concurrent_runs: Some(0),
+                ..TenantQuota::default()
+            }),
+        );
+        let tenant_storage_layout = local_project_manager.tenant_storage_layout(&tenant_id);
+        for project_id in ["first", "second"] {
+            copy_dir_all(
+                &Path::new(CRATE_DIR)
+                    .join("tests_dir")
+                    .join("uploaded_projects")
+                    .join("valid_offline"),
+                &tenant_storage_layout.uploaded_projects_dir.join(project_id),
+                |_| false,
+            )
+            .await
+            .expect("Could not copy uploaded project");
+        }
+
+        let add_results = [
+            local_project_manager
+                .add_new_tenant_project(
+                    tenant_id.clone(),
+                    String::from("first"),
+                    String::from("First"),
+                    tenant_storage_layout.uploaded_projects_dir.join("first"),
+                )
+                .await,
+            local_project_manager
+                .add_new_tenant_project(
+                    tenant_id.clone(),
+                    String::from("second"),
+                    String::from("Second"),
+                    tenant_storage_layout.uploaded_projects_dir.join("second"),
+                )
+                .await,
+        ];
+        let runs_dir_exists = fs::try_exists(&tenant_storage_layout.runs_dir).await;
+        let run_result = local_project_manager
+            .start_run(
+                String::from("first"),
+                RunConfig {
+                    script_id: String::from("main.py"),
+                    users: 1,
+                    spawn_rate: 1.0,
+                    duration: 1,
+                    workers: 0,
+                    host: None,
+                    tags: Vec::new(),
+                    env_overrides: HashMap::new(),
+                },
+            )
+            .await;
+        let mut tenant_projects = Vec::new();
+        for tenant_id in [Some(tenant_id.clone()), Some(TenantId::default())] {
+            tenant_projects.push(
+                local_project_manager
+                    .list_projects(
+                        ProjectFilter {
+                            tenant_id,
+                            ..ProjectFilter::default()
+                        },
+                        Pagination::default(),
+                    )
+                    .await
+                    .expect("Error listing projects.")
+                    .total_items,
+            );
+        }
+        let tenant_disk_usage = local_project_manager.tenant_disk_usage(&tenant_id).await;
+
+        assert_eq!(
+            tenant_storage_layout.runs_dir,
+            root_dir.join("tenants").join("acme").join("runs")
+        );
+        assert!(add_results[0].is_ok());
+        assert!(matches!(
+            add_results[1],
+            Err(AddProjectError::Quota(TenantQuotaError::TooManyProjects {
+                max: 1,
+                ..
+            }))
+        ));
+        assert!(runs_dir_exists.expect("Error checking runs dir."));
+        assert!(matches!(
+            run_result,
+            Err(StartRunError::Quota(TenantQuotaError::TooManyRuns {
+                max: 0,
+                ..
+            }))
+        ));
+        assert_eq!(
+            local_project_manager
+                .project_state("first")
+                .await
+                .expect("Error getting project state."),
+            Some(ProjectState::Uploaded)
+        );
+        assert_eq!(tenant_projects, [1, 0]);
+        assert!(tenant_disk_usage.expect("Error measuring disk usage.") > 0);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn install_project_added_by_another_replica_and_expect_downloaded_from_blob_store() {
+        let temp_dir = test_dir("project_manager_blob_store");
+        let test_dir = temp_dir.path();
+        let root_dir = test_dir.join("root");
+        let uploaded_project_dir = root_dir.join("uploaded_projects").join("valid_offline");
+        copy_dir_all(
+            &Path::new(CRATE_DIR)
+                .join("tests_dir")
+                .join("uploaded_projects")
+                .join("valid_offline"),
+            &uploaded_project_dir,
+            |_| false,
+        )
+        .await
+        .expect("Could not copy uploaded project");
+        let blob_store: Arc<dyn BlobStore> = Arc::new(LocalBlobStore::new(test_dir.join("blobs")));
+        let mut local_project_manager = LocalProjectManager::with_project_store_backend(
+            root_dir.clone(),
+            PipCacheConfig::default(),
+            ProjectStoreBackend::InMemory,
+        )
+        .await
+        .expect("Error creating project manager.");
+        local_project_manager.set_installer_backend(InstallerBackend::Pip);
+        local_project_manager.set_pip_options(PipOptions {
+            wheelhouse_dir: Some(root_dir.join("wheelhouse_does_not_exist")),
+            ..PipOptions::default()
+        });
+        local_project_manager.set_blob_store(Some(blob_store.clone()));
+
+        local_project_manager
+            .add_new_project_to_database(
+                String::from("valid_offline"),
+                String::from("Valid offline"),
+                uploaded_project_dir.clone(),
+            )
+            .await
+            .expect("Error adding project.");
+        let uploaded_keys = blob_store.list("uploaded_projects/valid_offline/").await;
+        // Another replica only has the blobs.
+        fs::remove_dir_all(&uploaded_project_dir)
+            .await
+            .expect("Error removing uploaded project.");
+        local_project_manager
+            .do_install_project(String::from("valid_offline"), None)
+            .await
+            .expect("Error starting installation.")
+            .task
+            .await
+            .expect("Installation task panicked.");
+        let requirements_downloaded =
+            fs::try_exists(uploaded_project_dir.join("requirements.txt")).await;
+        local_project_manager
+            .delete_project(String::from("valid_offline"))
+            .await
+            .expect("Error deleting project.");
+        let keys_after_delete = blob_store.list("uploaded_projects/").await;
+
+        assert!(uploaded_keys
+            .expect("Error listing blobs.")
+            .contains(&String::from(
+                "uploaded_projects/valid_offline/requirements.txt"
+            )));
+        assert!(requirements_downloaded.expect("Error checking requirements."));
+        assert!(keys_after_delete.expect("Error listing blobs.").is_empty());
     }
 }
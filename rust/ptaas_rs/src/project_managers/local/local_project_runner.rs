@@ -0,0 +1,939 @@
+use super::{
+    local_project_installer::LocalProjectInstaller,
+    run_metrics::{self, RunMetricsSample, METRICS_CHANNEL_CAPACITY},
+};
+use crate::project_managers::process::{
+    ControllerGroup, KillSignal, KilledTerminationStatus, OsProcessArgs, Process, ProcessBackend,
+    ProcessIoConfig, ProcessRunError, Status, StripAnsi, TerminationStatus,
+    TerminationWithErrorStatus,
+};
+use models::run_config::RunConfig;
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::OsString,
+    io::Error as IoError,
+    net::{Ipv4Addr, TcpListener},
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use thiserror::Error as ThisError;
+use tokio::{
+    fs,
+    sync::{broadcast, oneshot, watch},
+    task::{JoinError, JoinHandle},
+};
+use tokio_util::sync::CancellationToken;
+
+/// Passed to locust with ```--exit-code-on-error```, so a run with failed requests is told apart from a crash of locust,
+/// that exits with 1 like any python program.
+const EXIT_CODE_ON_FAILURES: i32 = 3;
+
+/// Locust writes its final stats on SIGINT, a run with many users needs a moment for it.
+const STOP_GRACE_PERIOD: Duration = Duration::from_secs(30);
+
+/// A worker has no stats to write, it only stops its users.
+const WORKER_STOP_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// The master and the workers of a distributed run are processes on the same host.
+const MASTER_HOST: &str = "127.0.0.1";
+
+/// The master starts the run without the workers, that did not connect in time, e.g. because they crashed.
+const EXPECT_WORKERS_MAX_WAIT: Duration = Duration::from_secs(60);
+
+/// A locust worker, see ```LocalProjectRunner::start_workers```.
+type WorkerTask = JoinHandle<Result<Status, ProcessRunError>>;
+
+/// The state of a ```LocalProjectRunner```, see ```RunController::status```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunnerStatus {
+    /// ```run``` was not called yet.
+    Pending,
+    Running,
+    /// ```RunController::stop``` was called, locust is writing its final stats.
+    Stopping,
+    /// The error is the message of the ```RunError```.
+    Finished(Result<RunOutcome, String>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RunOutcome {
+    /// Locust ran for its whole run time.
+    Completed,
+    /// Locust ran for its whole run time, but some requests failed.
+    CompletedWithFailures,
+    /// Stopped by ```RunController::stop``` before the run time was over.
+    Stopped,
+}
+
+impl RunOutcome {
+    pub(super) fn as_str(self) -> &'static str {
+        match self {
+            Self::Completed => "completed",
+            Self::CompletedWithFailures => "completedWithFailures",
+            Self::Stopped => "stopped",
+        }
+    }
+
+    pub(super) fn parse(outcome: &str) -> Option<Self> {
+        match outcome {
+            "completed" => Some(Self::Completed),
+            "completedWithFailures" => Some(Self::CompletedWithFailures),
+            "stopped" => Some(Self::Stopped),
+            _ => None,
+        }
+    }
+}
+
+#[derive(ThisError, Debug)]
+pub enum RunError {
+    #[error("Locustfile must be a relative path inside the locust dir: {0}")]
+    InvalidLocustfile(PathBuf),
+    #[error("Locustfile does not exist: {0}")]
+    LocustfileNotFound(PathBuf),
+    #[error("Locust is not installed in the environment: {0}")]
+    LocustNotInstalled(PathBuf),
+    #[error("Could not check if a path exists: {0}")]
+    CouldNotCheckPath(#[source] IoError),
+    #[error("Could not create the run dir: {0}")]
+    CouldNotCreateRunDir(#[source] IoError),
+    #[error("Could not find a free port for the locust master: {0}")]
+    CouldNotFindMasterPort(#[source] IoError),
+    #[error("Could not run locust: {0}")]
+    CouldNotRunLocust(#[source] ProcessRunError),
+    #[error("Could not run a locust worker: {0}")]
+    CouldNotRunLocustWorker(#[source] ProcessRunError),
+    #[error("Locust failed: {0:?}")]
+    LocustFailed(TerminationStatus),
+    #[error("Locust worker {worker} failed: {status:?}")]
+    LocustWorkerFailed {
+        worker: u32,
+        status: TerminationStatus,
+    },
+}
+
+#[derive(ThisError, Debug, PartialEq, Eq)]
+pub enum StopRunError {
+    #[error("Run has not started yet")]
+    NotStarted,
+    #[error("Run is already stopping")]
+    AlreadyStopping,
+    #[error("Run has already finished")]
+    AlreadyFinished,
+}
+
+/// Responsible for stopping a run of a ```LocalProjectRunner```.
+#[derive(Debug, Clone)]
+pub struct RunController {
+    cancellation_token: CancellationToken,
+    status_sender: Arc<watch::Sender<RunnerStatus>>,
+    metrics_sender: broadcast::Sender<RunMetricsSample>,
+}
+
+impl RunController {
+    pub fn status(&self) -> RunnerStatus {
+        self.status_sender.borrow().clone()
+    }
+
+    /// Notified on every status change, e.g. to wait for ```RunnerStatus::Finished```.
+    pub fn subscribe_status(&self) -> watch::Receiver<RunnerStatus> {
+        self.status_sender.subscribe()
+    }
+
+    /// Receives a ```RunMetricsSample``` every second while locust is running, e.g. to plot live charts.
+    /// Correctness: A receiver that lags behind by more than ```METRICS_CHANNEL_CAPACITY``` samples misses the oldest ones.
+    pub fn subscribe_metrics(&self) -> broadcast::Receiver<RunMetricsSample> {
+        self.metrics_sender.subscribe()
+    }
+
+    /// Interrupts locust, like Ctrl+C, and returns right away. The run finishes with ```RunOutcome::Stopped```.
+    /// Correctness: Locust is killed if it does not exit within ```STOP_GRACE_PERIOD```.
+    pub fn stop(&self) -> Result<(), StopRunError> {
+        let mut result = Ok(());
+
+        self.status_sender.send_if_modified(|status| match status {
+            RunnerStatus::Pending => {
+                result = Err(StopRunError::NotStarted);
+                false
+            }
+            RunnerStatus::Stopping => {
+                result = Err(StopRunError::AlreadyStopping);
+                false
+            }
+            RunnerStatus::Finished(_) => {
+                result = Err(StopRunError::AlreadyFinished);
+                false
+            }
+            RunnerStatus::Running => {
+                *status = RunnerStatus::Stopping;
+                true
+            }
+        });
+
+        if result.is_ok() {
+            self.cancellation_token.cancel();
+        }
+
+        result
+    }
+}
+
+/// Runs a locust test of an installed project headless, see ```RunConfig```.
+/// The output of locust is written to ```locust_out.txt``` and ```locust_err.txt``` in the run dir,
+/// its stats to the ```locust_*.csv``` files, see ```RunController::subscribe_metrics```.
+/// A distributed run has a master and ```RunConfig::workers``` workers, with ```locust_worker_<n>_out.txt``` etc.
+pub struct LocalProjectRunner {
+    id: String,
+    /// Holds the ```locust``` dir, see ```LocalProjectInstaller```. Locust runs in this dir.
+    installed_project_dir: PathBuf,
+    project_env_dir: PathBuf,
+    run_dir: PathBuf,
+    io_config: ProcessIoConfig,
+    /// Spawns locust, ```None``` spawns a real os process.
+    process_backend: Option<Arc<dyn ProcessBackend>>,
+    cancellation_token: CancellationToken,
+    status_sender: Arc<watch::Sender<RunnerStatus>>,
+    metrics_sender: broadcast::Sender<RunMetricsSample>,
+}
+
+impl LocalProjectRunner {
+    pub fn new(
+        id: String,
+        installed_project_dir: PathBuf,
+        project_env_dir: PathBuf,
+        run_dir: PathBuf,
+        io_config: ProcessIoConfig,
+    ) -> (Self, RunController) {
+        let cancellation_token = CancellationToken::new();
+        let status_sender = Arc::new(watch::channel(RunnerStatus::Pending).0);
+        let metrics_sender = broadcast::channel(METRICS_CHANNEL_CAPACITY).0;
+
+        (
+            Self {
+                id,
+                installed_project_dir,
+                project_env_dir,
+                run_dir,
+                io_config,
+                process_backend: None,
+                cancellation_token: cancellation_token.clone(),
+                status_sender: status_sender.clone(),
+                metrics_sender: metrics_sender.clone(),
+            },
+            RunController {
+                cancellation_token,
+                status_sender,
+                metrics_sender,
+            },
+        )
+    }
+
+    pub fn set_process_backend(&mut self, process_backend: Arc<dyn ProcessBackend>) {
+        self.process_backend = Some(process_backend);
+    }
+
+    pub(super) fn get_locust_out_file_path(&self) -> PathBuf {
+        self.run_dir.join("locust_out.txt")
+    }
+
+    pub(super) fn get_locust_err_file_path(&self) -> PathBuf {
+        self.run_dir.join("locust_err.txt")
+    }
+
+    /// The output files of a worker of a distributed run, stdout first.
+    pub(super) fn get_locust_worker_file_paths(&self, worker: u32) -> (PathBuf, PathBuf) {
+        (
+            self.run_dir.join(format!("locust_worker_{worker}_out.txt")),
+            self.run_dir.join(format!("locust_worker_{worker}_err.txt")),
+        )
+    }
+
+    /// The prefix of the ```--csv``` files of locust.
+    pub(super) fn get_locust_csv_prefix(&self) -> PathBuf {
+        self.run_dir.join("locust")
+    }
+
+    pub(super) fn get_stats_history_file_path(&self) -> PathBuf {
+        self.run_dir.join("locust_stats_history.csv")
+    }
+
+    /// The locust flags of the run, the locustfile is relative to ```installed_project_dir```.
+    /// ```master_port``` makes locust the master of a distributed run, see ```RunConfig::workers```.
+    /// The run config is not validated here, see ```RunConfig::validate```.
+    fn locust_args(
+        run_config: &RunConfig,
+        csv_prefix: &Path,
+        master_port: Option<u16>,
+    ) -> Vec<OsString> {
+        let mut args = vec![
+            OsString::from("-f"),
+            Path::new("locust").join(&run_config.script_id).into(),
+            OsString::from("--headless"),
+            OsString::from("-u"),
+            OsString::from(run_config.users.to_string()),
+            OsString::from("-r"),
+            OsString::from(run_config.spawn_rate.to_string()),
+            OsString::from("-t"),
+            OsString::from(format!("{}s", run_config.duration)),
+            OsString::from("--exit-code-on-error"),
+            OsString::from(EXIT_CODE_ON_FAILURES.to_string()),
+            OsString::from("--csv"),
+            csv_prefix.into(),
+        ];
+
+        if let Some(master_port) = master_port {
+            args.extend([
+                OsString::from("--master"),
+                OsString::from("--master-bind-host"),
+                OsString::from(MASTER_HOST),
+                OsString::from("--master-bind-port"),
+                OsString::from(master_port.to_string()),
+                OsString::from("--expect-workers"),
+                OsString::from(run_config.workers.to_string()),
+                OsString::from("--expect-workers-max-wait"),
+                OsString::from(EXPECT_WORKERS_MAX_WAIT.as_secs().to_string()),
+            ]);
+        }
+
+        args.extend(Self::locust_filter_args(run_config));
+
+        args
+    }
+
+    /// The locust flags of a worker, it gets the users and the run time from the master.
+    fn locust_worker_args(run_config: &RunConfig, master_port: u16) -> Vec<OsString> {
+        let mut args = vec![
+            OsString::from("-f"),
+            Path::new("locust").join(&run_config.script_id).into(),
+            OsString::from("--worker"),
+            OsString::from("--master-host"),
+            OsString::from(MASTER_HOST),
+            OsString::from("--master-port"),
+            OsString::from(master_port.to_string()),
+        ];
+
+        args.extend(Self::locust_filter_args(run_config));
+
+        args
+    }
+
+    /// The flags, that the master and the workers need, the tasks run on the workers.
+    fn locust_filter_args(run_config: &RunConfig) -> Vec<OsString> {
+        let mut args = Vec::new();
+
+        if let Some(host) = &run_config.host {
+            args.extend([OsString::from("-H"), OsString::from(host)]);
+        }
+
+        if !run_config.tags.is_empty() {
+            args.push(OsString::from("-T"));
+            args.extend(run_config.tags.iter().map(OsString::from));
+        }
+
+        args
+    }
+
+    /// A locust process of the run, its output is appended to ```output_files```, stdout first.
+    /// Runs ```<env_python_path> -m locust```, that does not depend on the launchers of the environment.
+    fn locust_process_args(
+        &self,
+        env_python_path: &Path,
+        args: Vec<OsString>,
+        output_files: (PathBuf, PathBuf),
+        grace_period: Duration,
+        run_config: &RunConfig,
+    ) -> OsProcessArgs<Vec<OsString>, OsString, PathBuf> {
+        OsProcessArgs {
+            program: env_python_path.into(),
+            args: [OsString::from("-m"), OsString::from("locust")]
+                .into_iter()
+                .chain(args)
+                .collect(),
+            current_dir: self.installed_project_dir.clone(),
+            stdout_file: Some(output_files.0),
+            stderr_file: Some(output_files.1),
+            kill_signal: KillSignal::Interrupt { grace_period },
+            strip_ansi: StripAnsi::both(),
+            backend: self.process_backend.clone(),
+            io_config: self.io_config,
+            envs: run_config
+                .env_overrides
+                .iter()
+                .map(|(name, value)| (OsString::from(name), OsString::from(value)))
+                .collect(),
+            ..OsProcessArgs::default()
+        }
+    }
+
+    /// Runs locust until its run time is over or the run is stopped, see ```RunController::stop```.
+    /// The runner runs once, the status is ```RunnerStatus::Finished``` afterwards.
+    pub async fn run(self, run_config: &RunConfig) -> Result<RunOutcome, RunError> {
+        let run_result = self.run_locust(run_config).await;
+
+        match &run_result {
+            Ok(run_outcome) => tracing::info!(id = self.id, ?run_outcome, "Run finished"),
+            Err(error) => tracing::warn!(id = self.id, %error, "Run failed"),
+        }
+
+        self.status_sender.send_replace(RunnerStatus::Finished(
+            run_result
+                .as_ref()
+                .map(|run_outcome| *run_outcome)
+                .map_err(ToString::to_string),
+        ));
+
+        run_result
+    }
+
+    async fn run_locust(&self, run_config: &RunConfig) -> Result<RunOutcome, RunError> {
+        let locustfile = Path::new(&run_config.script_id);
+        let is_inside_locust_dir = locustfile
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+        if !is_inside_locust_dir || locustfile.as_os_str().is_empty() {
+            return Err(RunError::InvalidLocustfile(locustfile.to_path_buf()));
+        }
+
+        let locustfile_path = self.installed_project_dir.join("locust").join(locustfile);
+        if !fs::try_exists(&locustfile_path)
+            .await
+            .map_err(RunError::CouldNotCheckPath)?
+        {
+            return Err(RunError::LocustfileNotFound(locustfile_path));
+        }
+
+        let locust_path = LocalProjectInstaller::create_os_specific_executable_path(
+            &self.project_env_dir,
+            "locust",
+        );
+        if !fs::try_exists(&locust_path)
+            .await
+            .map_err(RunError::CouldNotCheckPath)?
+        {
+            return Err(RunError::LocustNotInstalled(locust_path));
+        }
+        // The launcher only tells that locust is installed, the launchers of a promoted environment
+        // may still point to its staging dir, see ```staging::relocate```.
+        let env_python_path =
+            LocalProjectInstaller::create_os_specific_python_path(&self.project_env_dir);
+
+        fs::create_dir_all(&self.run_dir)
+            .await
+            .map_err(RunError::CouldNotCreateRunDir)?;
+
+        let master_port = match run_config.workers {
+            0 => None,
+            _ => Some(free_port().map_err(RunError::CouldNotFindMasterPort)?),
+        };
+
+        let (mut process, _controller) = Process::with_cancellation_token(
+            format!("{}_run_id", self.id),
+            String::from("locust_run_process"),
+            self.cancellation_token.clone(),
+        );
+
+        // A run stopped from now on is never spawned, see ```Process::with_cancellation_token```.
+        self.status_sender.send_replace(RunnerStatus::Running);
+
+        tracing::info!(
+            id = self.id,
+            script_id = run_config.script_id,
+            users = run_config.users,
+            workers = run_config.workers,
+            duration = run_config.duration,
+            "Starting run"
+        );
+
+        let (mut workers, worker_tasks) = match master_port {
+            Some(master_port) => {
+                self.start_workers(&env_python_path, run_config, master_port)
+                    .await
+            }
+            None => (ControllerGroup::new(), Vec::new()),
+        };
+
+        let run_finished = CancellationToken::new();
+        let metrics_task = run_metrics::follow_stats_history(
+            self.get_stats_history_file_path(),
+            self.metrics_sender.clone(),
+            run_finished.clone(),
+        );
+
+        let status_result = process
+            .run(self.locust_process_args(
+                &env_python_path,
+                Self::locust_args(run_config, &self.get_locust_csv_prefix(), master_port),
+                (
+                    self.get_locust_out_file_path(),
+                    self.get_locust_err_file_path(),
+                ),
+                STOP_GRACE_PERIOD,
+                run_config,
+            ))
+            .await;
+
+        // The last samples are written by locust on exit.
+        run_finished.cancel();
+        if let Err(error) = metrics_task.await {
+            tracing::warn!(%error, "Metrics task panicked");
+        }
+
+        // The workers quit with the master, the ones left, e.g. after the master crashed, are stopped.
+        for cancel_result in workers.cancel_all().await {
+            if let Ok(Some(error)) = cancel_result.result {
+                tracing::warn!(given_id = cancel_result.given_id, %error, "Could not stop worker");
+            }
+        }
+        let mut worker_results = Vec::with_capacity(worker_tasks.len());
+        for worker_task in worker_tasks {
+            worker_results.push(worker_task.await);
+        }
+
+        let status = status_result.map_err(RunError::CouldNotRunLocust)?;
+        let run_outcome = Self::run_outcome(status, self.cancellation_token.is_cancelled())?;
+        Self::workers_result(worker_results)?;
+
+        Ok(run_outcome)
+    }
+
+    /// Starts the workers of a distributed run, they connect to the master on ```master_port```.
+    /// Returns once every worker is running or terminated, so the group reaches all of them.
+    /// Correctness: ```ControllerGroup::cancel_all``` can not cancel a process, that is not running yet.
+    async fn start_workers(
+        &self,
+        env_python_path: &Path,
+        run_config: &RunConfig,
+        master_port: u16,
+    ) -> (ControllerGroup, Vec<WorkerTask>) {
+        let mut workers = ControllerGroup::new();
+        let mut worker_tasks = Vec::new();
+
+        for worker in 0..run_config.workers {
+            let (mut process, controller) = Process::new(
+                format!("{}_run_worker_{worker}_id", self.id),
+                String::from("locust_worker_process"),
+            );
+            let process_args = self.locust_process_args(
+                env_python_path,
+                Self::locust_worker_args(run_config, master_port),
+                self.get_locust_worker_file_paths(worker),
+                WORKER_STOP_GRACE_PERIOD,
+                run_config,
+            );
+
+            // Dropped once the worker finished, also if it could not be spawned and never runs.
+            let (finished_sender, finished_receiver) = oneshot::channel::<()>();
+            worker_tasks.push(tokio::spawn(async move {
+                let _finished_sender = finished_sender;
+                process.run(process_args).await
+            }));
+
+            tokio::select! {
+                _ = controller.wait_until_running() => {}
+                _ = finished_receiver => {}
+            }
+
+            workers.push(controller);
+        }
+
+        (workers, worker_tasks)
+    }
+
+    /// The first worker, that did not quit like the worker of a finished run, fails the run.
+    /// Correctness: The workers are cancelled once the master exited, a cancelled worker did not fail.
+    fn workers_result(
+        worker_results: Vec<Result<Result<Status, ProcessRunError>, JoinError>>,
+    ) -> Result<(), RunError> {
+        for (worker, worker_result) in (0..).zip(worker_results) {
+            let status = match worker_result {
+                Ok(Ok(status)) => status,
+                Ok(Err(error)) => return Err(RunError::CouldNotRunLocustWorker(error)),
+                Err(error) => {
+                    tracing::error!(worker, %error, "Worker task panicked");
+
+                    Status::Terminated(TerminationStatus::TerminatedWithUnknownExitStatus)
+                }
+            };
+
+            match status {
+                Status::Terminated(
+                    TerminationStatus::TerminatedSuccessfully
+                    | TerminationStatus::TerminatedWithError(
+                        TerminationWithErrorStatus::TerminatedWithErrorCode(EXIT_CODE_ON_FAILURES),
+                    )
+                    | TerminationStatus::Killed(
+                        KilledTerminationStatus::KilledByCancellationSignal,
+                        _,
+                    ),
+                ) => {}
+                Status::Terminated(status) => {
+                    return Err(RunError::LocustWorkerFailed { worker, status })
+                }
+                // ```Process::run``` returns once the os process terminated.
+                Status::Created | Status::Running | Status::Paused => {
+                    return Err(RunError::LocustWorkerFailed {
+                        worker,
+                        status: TerminationStatus::TerminatedWithUnknownExitStatus,
+                    })
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// A stopped locust exits on its own, with the code of its stats, or is killed after the grace period.
+    fn run_outcome(status: Status, stopped: bool) -> Result<RunOutcome, RunError> {
+        match status {
+            Status::Terminated(TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByCancellationSignal,
+                _,
+            )) if stopped => Ok(RunOutcome::Stopped),
+            Status::Terminated(TerminationStatus::TerminatedSuccessfully) if stopped => {
+                Ok(RunOutcome::Stopped)
+            }
+            Status::Terminated(TerminationStatus::TerminatedWithError(
+                TerminationWithErrorStatus::TerminatedWithErrorCode(EXIT_CODE_ON_FAILURES),
+            )) if stopped => Ok(RunOutcome::Stopped),
+            Status::Terminated(TerminationStatus::TerminatedSuccessfully) => {
+                Ok(RunOutcome::Completed)
+            }
+            Status::Terminated(TerminationStatus::TerminatedWithError(
+                TerminationWithErrorStatus::TerminatedWithErrorCode(EXIT_CODE_ON_FAILURES),
+            )) => Ok(RunOutcome::CompletedWithFailures),
+            Status::Terminated(termination_status) => {
+                Err(RunError::LocustFailed(termination_status))
+            }
+            // ```Process::run``` returns once the os process terminated.
+            Status::Created | Status::Running | Status::Paused => Err(RunError::LocustFailed(
+                TerminationStatus::TerminatedWithUnknownExitStatus,
+            )),
+        }
+    }
+}
+
+/// Asks the os for a free port on ```MASTER_HOST```.
+/// Correctness: The port is released before locust binds it, another process may take it in between.
+fn free_port() -> Result<u16, IoError> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+
+    Ok(listener.local_addr()?.port())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_managers::{
+        local::staging,
+        process::{FakeBackend, FakeScript, OsExitStatus},
+    };
+    use crate::util::test_dir;
+    use std::collections::HashMap;
+
+    async fn create_runner(
+        test_dir: &Path,
+        script: FakeScript,
+    ) -> (LocalProjectRunner, RunController) {
+        let installed_project_dir = test_dir.join("installed_project");
+        let project_env_dir = test_dir.join("env");
+        let locust_path =
+            LocalProjectInstaller::create_os_specific_executable_path(&project_env_dir, "locust");
+        let env_python_path =
+            LocalProjectInstaller::create_os_specific_python_path(&project_env_dir);
+        fs::create_dir_all(installed_project_dir.join("locust"))
+            .await
+            .expect("Error creating locust dir.");
+        fs::write(installed_project_dir.join("locust").join("main.py"), "")
+            .await
+            .expect("Error writing locustfile.");
+        fs::create_dir_all(locust_path.parent().expect("Locust path has no parent."))
+            .await
+            .expect("Error creating env dir.");
+        fs::write(&locust_path, "")
+            .await
+            .expect("Error writing locust.");
+
+        let (mut runner, controller) = LocalProjectRunner::new(
+            String::from("project"),
+            installed_project_dir,
+            project_env_dir,
+            test_dir.join("run"),
+            ProcessIoConfig::default(),
+        );
+        runner.set_process_backend(
+            FakeBackend::new()
+                .script(env_python_path, script)
+                .into_shared(),
+        );
+
+        (runner, controller)
+    }
+
+    fn run_config(script_id: &str) -> RunConfig {
+        RunConfig {
+            script_id: String::from(script_id),
+            users: 10,
+            spawn_rate: 2.5,
+            duration: 60,
+            workers: 0,
+            host: Some(String::from("http://localhost:8080")),
+            tags: vec![String::from("a"), String::from("b")],
+            env_overrides: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn run_distributed_locust_and_expect_outcome_of_master_and_workers() {
+        let temp_dir = test_dir("runner_distributed");
+        let test_dir = temp_dir.path();
+
+        let (runner, _controller) = create_runner(
+            test_dir,
+            FakeScript::new()
+                .stdout("Starting Locust")
+                .exit_code(EXIT_CODE_ON_FAILURES),
+        )
+        .await;
+        let run_result = runner
+            .run(&RunConfig {
+                workers: 2,
+                ..run_config("main.py")
+            })
+            .await;
+        let worker_out =
+            fs::read_to_string(test_dir.join("run").join("locust_worker_1_out.txt")).await;
+
+        assert_eq!(
+            run_result.expect("Error running locust."),
+            RunOutcome::CompletedWithFailures
+        );
+        assert!(worker_out
+            .expect("Error reading worker output.")
+            .contains("Starting Locust"));
+        assert!(LocalProjectRunner::workers_result(vec![
+            Ok(Ok(Status::Terminated(
+                TerminationStatus::TerminatedSuccessfully
+            ))),
+            Ok(Ok(Status::Terminated(TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByCancellationSignal,
+                OsExitStatus::default()
+            )))),
+        ])
+        .is_ok());
+        assert!(matches!(
+            LocalProjectRunner::workers_result(vec![
+                Ok(Ok(Status::Terminated(
+                    TerminationStatus::TerminatedSuccessfully
+                ))),
+                Ok(Ok(Status::Terminated(
+                    TerminationStatus::TerminatedWithError(
+                        TerminationWithErrorStatus::TerminatedWithErrorCode(1)
+                    )
+                ))),
+            ]),
+            Err(RunError::LocustWorkerFailed { worker: 1, .. })
+        ));
+        let master_args = LocalProjectRunner::locust_args(
+            &RunConfig {
+                workers: 2,
+                ..run_config("main.py")
+            },
+            Path::new("locust"),
+            Some(5557),
+        );
+        assert!(master_args
+            .windows(2)
+            .any(|args| args == ["--expect-workers", "2"]));
+        assert_eq!(
+            LocalProjectRunner::locust_worker_args(&run_config("main.py"), 5557)[2..],
+            [
+                "--worker",
+                "--master-host",
+                "127.0.0.1",
+                "--master-port",
+                "5557",
+                "-H",
+                "http://localhost:8080",
+                "-T",
+                "a",
+                "b"
+            ]
+            .map(OsString::from)
+        );
+    }
+
+    #[tokio::test]
+    async fn run_and_stop_locust_and_expect_outcomes() {
+        let temp_dir = test_dir("runner");
+        let test_dir = temp_dir.path();
+
+        let (runner, controller) = create_runner(
+            test_dir,
+            FakeScript::new()
+                .stdout("Name  # reqs  # fails")
+                .exit_code(EXIT_CODE_ON_FAILURES),
+        )
+        .await;
+        let completed_result = runner.run(&run_config("main.py")).await;
+        let completed_status = controller.status();
+        let locust_out = fs::read_to_string(test_dir.join("run").join("locust_out.txt")).await;
+
+        let (runner, controller) =
+            create_runner(test_dir, FakeScript::new().delay(Duration::from_secs(60))).await;
+        let mut status_receiver = controller.subscribe_status();
+        let run_task = tokio::spawn(async move { runner.run(&run_config("main.py")).await });
+        status_receiver
+            .wait_for(|status| *status == RunnerStatus::Running)
+            .await
+            .expect("Runner status sender dropped.");
+        let stop_result = controller.stop();
+        let stopped_result = run_task.await.expect("Run task panicked.");
+        let stop_again_result = controller.stop();
+
+        let (runner, _controller) = create_runner(test_dir, FakeScript::new()).await;
+        let invalid_result = runner.run(&run_config("../main.py")).await;
+
+        assert_eq!(
+            completed_result.expect("Error running locust."),
+            RunOutcome::CompletedWithFailures
+        );
+        assert_eq!(
+            completed_status,
+            RunnerStatus::Finished(Ok(RunOutcome::CompletedWithFailures))
+        );
+        assert!(locust_out
+            .expect("Error reading locust output.")
+            .contains("# reqs"));
+        assert_eq!(stop_result, Ok(()));
+        assert_eq!(
+            stopped_result.expect("Error running locust."),
+            RunOutcome::Stopped
+        );
+        assert_eq!(stop_again_result, Err(StopRunError::AlreadyFinished));
+        assert!(matches!(
+            invalid_result,
+            Err(RunError::InvalidLocustfile(_))
+        ));
+        assert_eq!(
+            LocalProjectRunner::locust_args(&run_config("main.py"), Path::new("locust"), None),
+            [
+                "-f",
+                Path::new("locust")
+                    .join("main.py")
+                    .to_str()
+                    .unwrap_or_default(),
+                "--headless",
+                "-u",
+                "10",
+                "-r",
+                "2.5",
+                "-t",
+                "60s",
+                "--exit-code-on-error",
+                "3",
+                "--csv",
+                "locust",
+                "-H",
+                "http://localhost:8080",
+                "-T",
+                "a",
+                "b"
+            ]
+            .map(OsString::from)
+        );
+    }
+
+    /// Runs ```<python> -c <code>``` and returns its stdout.
+    async fn python(python_path: &Path, code: &str) -> String {
+        let output = tokio::process::Command::new(python_path)
+            .args(["-c", code])
+            .output()
+            .await
+            .expect("Error running python.");
+        assert!(output.status.success(), "python -c {code:?} failed");
+
+        String::from_utf8_lossy(&output.stdout).trim().to_owned()
+    }
+
+    #[tokio::test]
+    async fn run_promoted_environment_with_broken_launcher_and_expect_env_python_to_run_locust() {
+        let temp_dir = test_dir("runner_promoted");
+        let test_dir = temp_dir.path();
+        let installed_project_dir = test_dir.join("installed_project");
+        let project_env_dir = test_dir.join("environments").join("project");
+        let staging_env_dir = staging::staging_dir_path(&project_env_dir);
+        fs::create_dir_all(installed_project_dir.join("locust"))
+            .await
+            .expect("Error creating locust dir.");
+        fs::write(installed_project_dir.join("locust").join("main.py"), "")
+            .await
+            .expect("Error writing locustfile.");
+        let venv_status = tokio::process::Command::new("python3")
+            .arg("-m")
+            .arg("venv")
+            .arg(&staging_env_dir)
+            .status()
+            .await
+            .expect("Error running python.");
+        assert!(venv_status.success(), "python -m venv failed");
+
+        // A fake locust package in the environment, that prints the environment it runs in.
+        let site_packages_dir = PathBuf::from(
+            python(
+                &LocalProjectInstaller::create_os_specific_python_path(&staging_env_dir),
+                "import sysconfig; print(sysconfig.get_paths()['purelib'])",
+            )
+            .await,
+        );
+        fs::create_dir_all(site_packages_dir.join("locust"))
+            .await
+            .expect("Error creating locust package.");
+        fs::write(site_packages_dir.join("locust").join("__init__.py"), "")
+            .await
+            .expect("Error writing locust package.");
+        fs::write(
+            site_packages_dir.join("locust").join("__main__.py"),
+            "import sys\nprint('locust ran in', sys.prefix)\n",
+        )
+        .await
+        .expect("Error writing locust package.");
+        // A binary launcher is not relocated, it keeps pointing to the staging dir.
+        let mut launcher = vec![0xff, 0xfe];
+        launcher.extend_from_slice(staging_env_dir.to_string_lossy().as_bytes());
+        fs::write(
+            LocalProjectInstaller::create_os_specific_executable_path(&staging_env_dir, "locust"),
+            launcher,
+        )
+        .await
+        .expect("Error writing launcher.");
+
+        staging::promote(&staging_env_dir, &project_env_dir)
+            .await
+            .expect("Error promoting staging dir.");
+        let env_prefix = python(
+            &LocalProjectInstaller::create_os_specific_python_path(&project_env_dir),
+            "import sys; print(sys.prefix)",
+        )
+        .await;
+
+        let (runner, _controller) = LocalProjectRunner::new(
+            String::from("project"),
+            installed_project_dir,
+            project_env_dir,
+            test_dir.join("run"),
+            ProcessIoConfig::default(),
+        );
+        let run_result = runner.run(&run_config("main.py")).await;
+        let locust_out = fs::read_to_string(test_dir.join("run").join("locust_out.txt")).await;
+
+        assert_eq!(
+            run_result.expect("Error running locust."),
+            RunOutcome::Completed
+        );
+        assert_eq!(
+            locust_out.expect("Error reading locust output.").trim(),
+            format!("locust ran in {env_prefix}")
+        );
+    }
+}
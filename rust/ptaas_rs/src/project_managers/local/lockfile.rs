@@ -0,0 +1,138 @@
+use super::installer_backend::InstallerBackend;
+use crate::project_managers::process::{self, ProcessRunError, Status, TerminationStatus};
+use serde::{Deserialize, Serialize};
+use std::{ffi::OsStr, path::Path, time::Duration};
+use thiserror::Error as ThisError;
+use tokio_util::sync::CancellationToken;
+
+/// A line per installed package.
+const MAX_FREEZE_OUTPUT_SIZE: usize = 4 * 1024 * 1024;
+
+/// ```pip freeze``` only reads the installed metadata.
+const FREEZE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A package of an installed environment, e.g. ```locust==2.15.1```.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageVersion {
+    pub name: String,
+    /// The url for packages that were installed from a direct reference, e.g. ```file:///wheels/locust.whl```.
+    pub version: String,
+}
+
+/// Runs ```pip freeze``` in the environment of ```env_python_path``` and returns its output.
+/// ```uv venv``` does not install pip, the uv backend uses ```uv pip freeze```.
+pub(super) async fn freeze(
+    installer_backend: InstallerBackend,
+    env_python_path: &Path,
+    current_dir: &Path,
+    cancellation_token: &CancellationToken,
+) -> Result<String, FreezeError> {
+    let (program, args) = match installer_backend {
+        InstallerBackend::Pip => (
+            env_python_path.as_os_str(),
+            vec![OsStr::new("-m"), OsStr::new("pip"), OsStr::new("freeze")],
+        ),
+        InstallerBackend::Uv => (
+            OsStr::new("uv"),
+            vec![
+                OsStr::new("pip"),
+                OsStr::new("freeze"),
+                OsStr::new("--python"),
+                env_python_path.as_os_str(),
+            ],
+        ),
+    };
+
+    let output = process::capture(
+        program,
+        args,
+        current_dir,
+        Vec::new(),
+        MAX_FREEZE_OUTPUT_SIZE,
+        FREEZE_TIMEOUT,
+        Some(cancellation_token),
+    )
+    .await
+    .map_err(FreezeError::CouldNotRunPipFreeze)?;
+
+    if !matches!(
+        output.status,
+        Status::Terminated(TerminationStatus::TerminatedSuccessfully)
+    ) {
+        return Err(FreezeError::PipFreezeFailed(output.status, output.stderr));
+    }
+
+    Ok(output.stdout)
+}
+
+/// Parses the output of ```pip freeze```. Comments, options and editable installs are skipped.
+pub(super) fn parse(lockfile: &str) -> Vec<PackageVersion> {
+    lockfile
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('-'))
+        .filter_map(|line| {
+            let (name, version) = line.split_once("==").or_else(|| line.split_once(" @ "))?;
+
+            Some(PackageVersion {
+                name: name.trim().to_owned(),
+                version: version.trim().to_owned(),
+            })
+        })
+        .collect()
+}
+
+#[derive(ThisError, Debug)]
+pub enum FreezeError {
+    #[error("Could not run pip freeze: {0}")]
+    CouldNotRunPipFreeze(#[source] ProcessRunError),
+    #[error("pip freeze failed: {1}")]
+    PipFreezeFailed(Status, String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_test::traced_test;
+
+    #[test]
+    fn parse_freeze_output_and_expect_pinned_and_direct_packages() {
+        let lockfile = "# Editable install with no version control (ptaas==0.1.0)\n-e /projects/ptaas\nlocust==2.15.1\ninternal-lib @ file:///wheels/internal_lib-1.0-py3-none-any.whl\n\n";
+
+        assert_eq!(
+            parse(lockfile),
+            vec![
+                PackageVersion {
+                    name: String::from("locust"),
+                    version: String::from("2.15.1"),
+                },
+                PackageVersion {
+                    name: String::from("internal-lib"),
+                    version: String::from("file:///wheels/internal_lib-1.0-py3-none-any.whl"),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn freeze_system_python_and_expect_pinned_packages() {
+        let lockfile = freeze(
+            InstallerBackend::Pip,
+            Path::new("python3"),
+            Path::new("."),
+            &CancellationToken::new(),
+        )
+        .await
+        .expect("Error running pip freeze.");
+
+        assert_eq!(
+            parse(&lockfile).len(),
+            lockfile
+                .lines()
+                .filter(|line| line.contains("==") || line.contains(" @ "))
+                .count()
+        );
+    }
+}
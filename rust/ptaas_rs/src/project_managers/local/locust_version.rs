@@ -0,0 +1,105 @@
+use crate::project_managers::process::{self, ProcessRunError, Status, TerminationStatus};
+use semver::Version;
+use std::{ffi::OsStr, path::Path, time::Duration};
+use thiserror::Error as ThisError;
+use tokio_util::sync::CancellationToken;
+
+/// ```locust --version``` prints a single short line.
+const MAX_LOCUST_VERSION_SIZE: usize = 1024;
+
+/// Importing locust takes seconds, starting its container a bit longer, see ```probe_with```.
+const LOCUST_VERSION_TIMEOUT: Duration = Duration::from_secs(2 * 60);
+
+/// Runs ```<locust_path> --version```, e.g. ```<env>/bin/locust```.
+pub(super) async fn probe(
+    locust_path: &Path,
+    current_dir: &Path,
+    cancellation_token: &CancellationToken,
+) -> Result<Version, LocustProbeError> {
+    probe_with(
+        locust_path.as_os_str(),
+        vec![OsStr::new("--version")],
+        current_dir,
+        cancellation_token,
+    )
+    .await
+}
+
+/// Runs the given command, that prints the output of ```locust --version```, e.g. in a container, see ```DockerInstaller```.
+pub(super) async fn probe_with(
+    program: &OsStr,
+    args: Vec<&OsStr>,
+    current_dir: &Path,
+    cancellation_token: &CancellationToken,
+) -> Result<Version, LocustProbeError> {
+    let output = process::capture(
+        program,
+        args,
+        current_dir,
+        Vec::new(),
+        MAX_LOCUST_VERSION_SIZE,
+        LOCUST_VERSION_TIMEOUT,
+        Some(cancellation_token),
+    )
+    .await
+    .map_err(LocustProbeError::CouldNotRunLocust)?;
+
+    if !matches!(
+        output.status,
+        Status::Terminated(TerminationStatus::TerminatedSuccessfully)
+    ) {
+        return Err(LocustProbeError::LocustFailed(output.status));
+    }
+
+    let version_output = format!("{}{}", output.stdout, output.stderr);
+    parse(&version_output).ok_or(LocustProbeError::UnknownVersion(version_output))
+}
+
+/// Parses the output of ```locust --version```, e.g. ```locust 2.15.1 from /env/lib/python3.11/site-packages/locust (python 3.11.4)```.
+/// Python pre-releases are not semver, ```2.16.0.dev12``` is ```2.16.0```.
+fn parse(output: &str) -> Option<Version> {
+    let version = output.split_whitespace().nth(1)?;
+    let mut components = version.splitn(3, '.');
+
+    let major = components.next()?.parse().ok()?;
+    let minor = components.next()?.parse().ok()?;
+    let patch = match components.next() {
+        Some(patch) => {
+            let digits_end = patch
+                .find(|character: char| !character.is_ascii_digit())
+                .unwrap_or(patch.len());
+            patch[..digits_end].parse().ok()?
+        }
+        None => 0,
+    };
+
+    Some(Version::new(major, minor, patch))
+}
+
+#[derive(ThisError, Debug)]
+pub enum LocustProbeError {
+    #[error("Could not run locust to get its version: {0}")]
+    CouldNotRunLocust(#[source] ProcessRunError),
+    #[error("Locust failed to print its version")]
+    LocustFailed(Status),
+    #[error("Could not parse the locust version: {0}")]
+    UnknownVersion(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_version_outputs_and_expect_versions() {
+        assert_eq!(
+            parse("locust 2.15.1 from /env/lib/python3.11/site-packages/locust (python 3.11.4)\n"),
+            Some(Version::new(2, 15, 1))
+        );
+        assert_eq!(
+            parse("locust 2.16.0.dev12 from /env/lib/python3.11/site-packages/locust"),
+            Some(Version::new(2, 16, 0))
+        );
+        assert_eq!(parse("locust: command not found"), None);
+    }
+}
@@ -0,0 +1,275 @@
+use std::{
+    ffi::OsString,
+    io::{Error as IoError, ErrorKind},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+use tokio::fs;
+
+/// Rotation and retention of the io files of a ```LocalProjectInstaller```, e.g. ```req_err.txt```.
+/// A log file is rotated to ```req_err.txt.1```, the previous ```req_err.txt.1``` to ```req_err.txt.2``` and so on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogRotationConfig {
+    /// A log file is rotated once it is bigger, checked every ```check_interval``` while its phase runs.
+    /// ```None``` rotates the log files only when an installation starts.
+    pub max_file_size_bytes: Option<u64>,
+    pub check_interval: Duration,
+    /// Rotated files of a log file that are kept, the oldest are deleted. ```0``` overwrites the log files.
+    pub max_rotated_files: usize,
+    /// Rotated files that were last modified longer ago are deleted.
+    pub max_age: Option<Duration>,
+}
+
+impl Default for LogRotationConfig {
+    fn default() -> Self {
+        Self {
+            max_file_size_bytes: Some(10 * 1024 * 1024),
+            check_interval: Duration::from_secs(1),
+            max_rotated_files: 5,
+            max_age: None,
+        }
+    }
+}
+
+/// A log file of an installation, returned by ```LocalProjectInstaller::list_log_files```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFile {
+    pub path: PathBuf,
+    /// ```0``` for the log file itself, ```1``` for the newest rotated file.
+    pub rotation: usize,
+    pub size_bytes: u64,
+    pub modified: SystemTime,
+}
+
+fn rotated_file_path(log_file_path: &Path, rotation: usize) -> PathBuf {
+    let mut rotated_file_path = OsString::from(log_file_path.as_os_str());
+    rotated_file_path.push(format!(".{rotation}"));
+
+    PathBuf::from(rotated_file_path)
+}
+
+/// Whether ```path``` is a rotated file of ```log_file_path```.
+pub(super) fn is_rotated_file_of(path: &Path, log_file_path: &Path) -> bool {
+    let (Some(file_name), Some(log_file_name)) = (
+        path.file_name().and_then(|name| name.to_str()),
+        log_file_path.file_name().and_then(|name| name.to_str()),
+    ) else {
+        return false;
+    };
+
+    path.parent() == log_file_path.parent()
+        && file_name
+            .strip_prefix(log_file_name)
+            .and_then(|suffix| suffix.strip_prefix('.'))
+            .is_some_and(|rotation| rotation.parse::<usize>().is_ok())
+}
+
+async fn remove_file_if_exists(path: &Path) -> Result<(), IoError> {
+    match fs::remove_file(path).await {
+        Err(error) if error.kind() != ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
+
+/// Moves ```log_file_path``` to its first rotated file and deletes the rotated files that exceed the retention.
+/// A missing or empty log file is not rotated.
+/// Correctness: A process writing to ```log_file_path``` reopens it on its next flush, see ```CaptureFile```.
+pub(super) async fn rotate(
+    log_file_path: &Path,
+    log_rotation_config: &LogRotationConfig,
+) -> Result<(), IoError> {
+    let size = match fs::metadata(log_file_path).await {
+        Ok(metadata) => metadata.len(),
+        Err(error) if error.kind() == ErrorKind::NotFound => 0,
+        Err(error) => return Err(error),
+    };
+
+    if size > 0 {
+        if log_rotation_config.max_rotated_files == 0 {
+            remove_file_if_exists(log_file_path).await?;
+        } else {
+            remove_file_if_exists(&rotated_file_path(
+                log_file_path,
+                log_rotation_config.max_rotated_files,
+            ))
+            .await?;
+
+            for rotation in (1..log_rotation_config.max_rotated_files).rev() {
+                match fs::rename(
+                    rotated_file_path(log_file_path, rotation),
+                    rotated_file_path(log_file_path, rotation + 1),
+                )
+                .await
+                {
+                    Err(error) if error.kind() != ErrorKind::NotFound => return Err(error),
+                    _ => {}
+                }
+            }
+
+            fs::rename(log_file_path, rotated_file_path(log_file_path, 1)).await?;
+        }
+    }
+
+    enforce_retention(log_file_path, log_rotation_config).await
+}
+
+/// Deletes the rotated files of ```log_file_path``` beyond ```max_rotated_files``` or older than ```max_age```.
+async fn enforce_retention(
+    log_file_path: &Path,
+    log_rotation_config: &LogRotationConfig,
+) -> Result<(), IoError> {
+    for log_file in list(log_file_path).await? {
+        if log_file.rotation == 0 {
+            continue;
+        }
+
+        let expired = log_rotation_config.max_age.is_some_and(|max_age| {
+            log_file
+                .modified
+                .elapsed()
+                .is_ok_and(|elapsed| elapsed > max_age)
+        });
+
+        if log_file.rotation > log_rotation_config.max_rotated_files || expired {
+            remove_file_if_exists(&log_file.path).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// ```log_file_path``` and its rotated files that exist, ordered by rotation.
+pub(super) async fn list(log_file_path: &Path) -> Result<Vec<LogFile>, IoError> {
+    let Some(log_dir) = log_file_path.parent() else {
+        return Ok(Vec::new());
+    };
+
+    let mut log_files = Vec::new();
+
+    if let Some(log_file) = log_file(log_file_path, 0).await? {
+        log_files.push(log_file);
+    }
+
+    let mut entries = match fs::read_dir(log_dir).await {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(log_files),
+        Err(error) => return Err(error),
+    };
+
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+
+        if !is_rotated_file_of(&path, log_file_path) {
+            continue;
+        }
+
+        let rotation = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(|extension| extension.parse().ok())
+            .unwrap_or_default();
+
+        if let Some(log_file) = log_file(&path, rotation).await? {
+            log_files.push(log_file);
+        }
+    }
+
+    log_files.sort_by_key(|log_file| log_file.rotation);
+
+    Ok(log_files)
+}
+
+async fn log_file(path: &Path, rotation: usize) -> Result<Option<LogFile>, IoError> {
+    let metadata = match fs::metadata(path).await {
+        Ok(metadata) => metadata,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error),
+    };
+
+    Ok(Some(LogFile {
+        path: path.to_path_buf(),
+        rotation,
+        size_bytes: metadata.len(),
+        modified: metadata.modified()?,
+    }))
+}
+
+/// Rotates the log files that grew bigger than ```max_file_size_bytes``` while their phase runs. Never returns.
+/// Correctness: Errors are logged, a log file that could not be rotated keeps growing.
+pub(super) async fn rotate_while_running(
+    log_file_paths: Vec<PathBuf>,
+    log_rotation_config: LogRotationConfig,
+) {
+    let Some(max_file_size_bytes) = log_rotation_config.max_file_size_bytes else {
+        return std::future::pending().await;
+    };
+
+    loop {
+        tokio::time::sleep(log_rotation_config.check_interval).await;
+
+        for log_file_path in &log_file_paths {
+            let Ok(metadata) = fs::metadata(log_file_path).await else {
+                continue;
+            };
+
+            if metadata.len() <= max_file_size_bytes {
+                continue;
+            }
+
+            tracing::debug!(
+                ?log_file_path,
+                "Log file exceeded its max size, rotating it"
+            );
+            if let Err(error) = rotate(log_file_path, &log_rotation_config).await {
+                tracing::warn!(?log_file_path, %error, "Could not rotate log file");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_dir;
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn rotate_log_file_three_times_and_expect_two_rotated_files_kept() {
+        let temp_dir = test_dir("log_rotation");
+        let log_dir = temp_dir.path();
+        let log_file_path = log_dir.join("req_err.txt");
+        let log_rotation_config = LogRotationConfig {
+            max_rotated_files: 2,
+            ..LogRotationConfig::default()
+        };
+
+        for installation in ["first", "second", "third"] {
+            fs::write(&log_file_path, installation)
+                .await
+                .expect("Error writing log file.");
+            rotate(&log_file_path, &log_rotation_config)
+                .await
+                .expect("Error rotating log file.");
+        }
+        fs::write(&log_file_path, "fourth")
+            .await
+            .expect("Error writing log file.");
+
+        let log_files = list(&log_file_path).await;
+        let mut contents = Vec::new();
+        for log_file in log_files.as_deref().unwrap_or_default() {
+            contents.push(fs::read_to_string(&log_file.path).await.unwrap_or_default());
+        }
+
+        assert_eq!(
+            log_files
+                .expect("Error listing log files.")
+                .iter()
+                .map(|log_file| log_file.rotation)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        assert_eq!(contents, vec!["fourth", "third", "second"]);
+    }
+}
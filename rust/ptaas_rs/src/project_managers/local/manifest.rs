@@ -0,0 +1,263 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    io::Error as IoError,
+    path::{Path, PathBuf},
+};
+use thiserror::Error as ThisError;
+use tokio::{fs, io::AsyncReadExt};
+
+pub(super) const MANIFEST_FILE_NAME: &str = "ptaas_manifest.json";
+
+/// Files are hashed in chunks, they are never read into memory as a whole.
+const HASH_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The files of a project and their hashes, written next to them as ```ptaas_manifest.json```.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectManifest {
+    /// Relative paths with ```/``` separators, e.g. ```locust/locustfile.py```, to their hex encoded SHA-256.
+    pub files: BTreeMap<String, String>,
+}
+
+/// The differences between a project and its manifest, returned by ```ManifestError::Mismatch```.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManifestMismatch {
+    /// In the manifest, but not in the project, e.g. after a partial upload.
+    pub missing: Vec<String>,
+    pub modified: Vec<String>,
+    /// In the project, but not in the manifest.
+    pub unexpected: Vec<String>,
+}
+
+impl ManifestMismatch {
+    fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.modified.is_empty() && self.unexpected.is_empty()
+    }
+}
+
+#[derive(ThisError, Debug)]
+pub enum ManifestError {
+    #[error("Could not read the project: {0}")]
+    CouldNotReadProject(#[source] IoError),
+    #[error("Could not read the manifest: {0}")]
+    CouldNotReadManifest(#[source] IoError),
+    #[error("Could not parse the manifest: {0}")]
+    CouldNotParseManifest(#[source] serde_json::Error),
+    #[error("Could not write the manifest: {0}")]
+    CouldNotWriteManifest(#[source] IoError),
+    #[error(
+        "Project does not match its manifest: {} missing, {} modified, {} unexpected files",
+        .0.missing.len(),
+        .0.modified.len(),
+        .0.unexpected.len()
+    )]
+    Mismatch(ManifestMismatch),
+}
+
+/// Hashes the files of ```project_dir```, except the manifest itself and the files ```skip``` returns ```true``` for.
+/// ```skip``` gets the path relative to ```project_dir```.
+pub(super) async fn generate<F>(project_dir: &Path, skip: F) -> Result<ProjectManifest, IoError>
+where
+    F: Fn(&Path) -> bool,
+{
+    let mut files = BTreeMap::new();
+    let mut dirs = vec![PathBuf::new()];
+
+    while let Some(relative_dir) = dirs.pop() {
+        let mut dir_content = fs::read_dir(project_dir.join(&relative_dir)).await?;
+
+        while let Some(entry) = dir_content.next_entry().await? {
+            let relative_path = relative_dir.join(entry.file_name());
+            if relative_path == Path::new(MANIFEST_FILE_NAME) || skip(&relative_path) {
+                continue;
+            }
+
+            if fs::metadata(entry.path()).await?.is_dir() {
+                dirs.push(relative_path);
+                continue;
+            }
+
+            files.insert(
+                manifest_path(&relative_path),
+                hash_file(&entry.path()).await?,
+            );
+        }
+    }
+
+    Ok(ProjectManifest { files })
+}
+
+/// The same on every platform, the manifest may be generated on another one.
+fn manifest_path(relative_path: &Path) -> String {
+    relative_path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+async fn hash_file(path: &Path) -> Result<String, IoError> {
+    let mut file = fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut chunk = vec![0; HASH_CHUNK_SIZE];
+
+    loop {
+        let read_bytes = file.read(&mut chunk).await?;
+        if read_bytes == 0 {
+            break;
+        }
+
+        hasher.update(&chunk[..read_bytes]);
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .fold(String::with_capacity(64), |mut hash, byte| {
+            // Writing to a String never fails.
+            let _ = write!(hash, "{byte:02x}");
+            hash
+        }))
+}
+
+/// Generates the manifest of ```project_dir``` and writes it to it, e.g. once a project is uploaded.
+pub async fn write_manifest(project_dir: &Path) -> Result<ProjectManifest, ManifestError> {
+    let project_manifest = generate(project_dir, |_| false)
+        .await
+        .map_err(ManifestError::CouldNotReadProject)?;
+
+    write(project_dir, &project_manifest)
+        .await
+        .map_err(ManifestError::CouldNotWriteManifest)?;
+
+    Ok(project_manifest)
+}
+
+pub(super) async fn write(
+    project_dir: &Path,
+    project_manifest: &ProjectManifest,
+) -> Result<(), IoError> {
+    let manifest = serde_json::to_vec_pretty(project_manifest)?;
+
+    fs::write(project_dir.join(MANIFEST_FILE_NAME), manifest).await
+}
+
+pub(super) async fn read(project_dir: &Path) -> Result<ProjectManifest, ManifestError> {
+    let manifest = fs::read(project_dir.join(MANIFEST_FILE_NAME))
+        .await
+        .map_err(ManifestError::CouldNotReadManifest)?;
+
+    serde_json::from_slice(&manifest).map_err(ManifestError::CouldNotParseManifest)
+}
+
+/// Compares ```project_dir``` with the manifest written to it, files ```skip``` returns ```true``` for are ignored,
+/// e.g. files the installer writes to the project dir, whether they are in the manifest or not.
+pub(super) async fn verify<F>(project_dir: &Path, skip: F) -> Result<(), ManifestError>
+where
+    F: Fn(&Path) -> bool,
+{
+    let mut expected_manifest = read(project_dir).await?;
+    expected_manifest
+        .files
+        .retain(|path, _| !skip(Path::new(path)));
+
+    let actual_manifest = generate(project_dir, skip)
+        .await
+        .map_err(ManifestError::CouldNotReadProject)?;
+
+    let mut manifest_mismatch = ManifestMismatch::default();
+
+    for (path, expected_hash) in &expected_manifest.files {
+        match actual_manifest.files.get(path) {
+            None => manifest_mismatch.missing.push(path.clone()),
+            Some(actual_hash) if actual_hash != expected_hash => {
+                manifest_mismatch.modified.push(path.clone())
+            }
+            Some(_) => {}
+        }
+    }
+
+    manifest_mismatch.unexpected = actual_manifest
+        .files
+        .into_keys()
+        .filter(|path| !expected_manifest.files.contains_key(path))
+        .collect();
+
+    if manifest_mismatch.is_empty() {
+        Ok(())
+    } else {
+        Err(ManifestError::Mismatch(manifest_mismatch))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_dir;
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn tamper_with_project_and_expect_mismatch() {
+        let temp_dir = test_dir("manifest");
+        let project_dir = temp_dir.path();
+        fs::create_dir_all(project_dir.join("locust"))
+            .await
+            .expect("Error creating dir.");
+        fs::write(project_dir.join("requirements.txt"), "locust==2.15.1\n")
+            .await
+            .expect("Error writing file.");
+        fs::write(project_dir.join("locust").join("locustfile.py"), "")
+            .await
+            .expect("Error writing file.");
+        fs::write(project_dir.join("locust").join("tasks.py"), "")
+            .await
+            .expect("Error writing file.");
+
+        let project_manifest = write_manifest(project_dir)
+            .await
+            .expect("Error writing manifest.");
+        let verify_result_before_tampering = verify(project_dir, |_| false).await;
+
+        fs::write(project_dir.join("requirements.txt"), "locust==2.16.0\n")
+            .await
+            .expect("Error writing file.");
+        fs::remove_file(project_dir.join("locust").join("tasks.py"))
+            .await
+            .expect("Error removing file.");
+        fs::write(project_dir.join("req_out.txt"), "")
+            .await
+            .expect("Error writing file.");
+        fs::write(project_dir.join("backdoor.py"), "")
+            .await
+            .expect("Error writing file.");
+        let verify_result = verify(project_dir, |relative_path| {
+            relative_path == Path::new("req_out.txt")
+        })
+        .await;
+
+        assert_eq!(
+            project_manifest.files.keys().collect::<Vec<_>>(),
+            vec![
+                "locust/locustfile.py",
+                "locust/tasks.py",
+                "requirements.txt"
+            ]
+        );
+        verify_result_before_tampering.expect("Project does not match its manifest.");
+        match verify_result {
+            Err(ManifestError::Mismatch(manifest_mismatch)) => assert_eq!(
+                manifest_mismatch,
+                ManifestMismatch {
+                    missing: vec![String::from("locust/tasks.py")],
+                    modified: vec![String::from("requirements.txt")],
+                    unexpected: vec![String::from("backdoor.py")],
+                }
+            ),
+            _ => panic!("Unexpected result: {:?}", verify_result),
+        }
+    }
+}
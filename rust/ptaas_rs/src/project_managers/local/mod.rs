@@ -1,4 +1,105 @@
+mod archive;
+mod artifact_store;
+mod audit;
+mod batch_installer;
+mod blob_store;
+mod cleanup_policy;
+mod disk_space;
+mod docker_installer;
+mod env_store;
+mod install_metrics;
+mod install_queue;
+mod install_record;
+mod installer_backend;
+mod installer_events;
 mod local_project_installer;
 mod local_project_manager;
+mod local_project_runner;
+mod lockfile;
+mod locust_version;
+mod log_rotation;
+mod manifest;
+mod pip_cache;
+mod pip_options;
+mod pip_retry;
+mod project_checks;
+pub(crate) mod project_events;
+mod project_export;
+pub(crate) mod project_listing;
+mod project_locks;
+mod project_source;
+pub(crate) mod project_state;
+mod project_store;
+mod python;
+mod recovery;
+mod report_generator;
+mod requirements_hash;
+mod run_metrics;
+mod run_report;
+mod staging;
+mod storage_layout;
+mod syntax_check;
+mod tenant;
+#[cfg(test)]
+mod test_dirs;
 
-pub use local_project_manager::LocalProjectManager;
+pub use archive::{extract_uploaded_archive, ArchiveError, ArchiveLimits};
+pub use artifact_store::{
+    Artifact, ArtifactKind, ArtifactRetention, ArtifactSource, ArtifactStore, ArtifactStoreError,
+};
+pub use audit::{AuditConfig, AuditPolicy, Vulnerability, VulnerabilityReport, VulnerablePackage};
+pub use batch_installer::{
+    BatchInstallError, BatchInstallReport, BatchInstallResult, BatchInstaller, BatchInstallerEvent,
+};
+pub use blob_store::{BlobStore, BlobStoreError, LocalBlobStore, S3BlobStore, S3Config};
+pub use cleanup_policy::CleanupPolicy;
+pub use disk_space::{DiskSpaceConfig, EnvSizeQuota};
+pub use docker_installer::{DockerConfig, DockerInstaller, DockerInstallerController};
+pub use env_store::{EnvStore, EnvStoreError};
+pub use install_metrics::InstallMetrics;
+pub use install_queue::InstallTicket;
+pub use install_record::{InstallLogFiles, InstallRecord, InstallRecordStatus, PhaseRecord};
+pub use installer_backend::InstallerBackend;
+pub use installer_events::{InstallerEvent, OutputStream};
+pub(crate) use local_project_installer::LocalProjectInstaller;
+pub use local_project_installer::{
+    CleanUpError, DirState, InstallOutcome, InstallPlan, InstallReport, InstallerStatus,
+    PlannedCommand, ProjectCheckError, ProjectKind,
+};
+pub use local_project_manager::{
+    AddProjectError, DeleteProjectError, InstallProjectError, LocalProjectManager,
+    LocalProjectManagerCreateError, StartRunError, StopProjectRunError, UninstallProjectError,
+};
+pub use local_project_runner::{
+    LocalProjectRunner, RunController, RunError, RunOutcome, RunnerStatus, StopRunError,
+};
+pub use lockfile::PackageVersion;
+pub use log_rotation::{LogFile, LogRotationConfig};
+pub use manifest::{write_manifest, ManifestError, ManifestMismatch, ProjectManifest};
+pub use pip_cache::PipCacheConfig;
+pub use pip_options::PipOptions;
+pub use pip_retry::PipRetryConfig;
+pub use project_checks::{
+    AllowedExtensions, ForbiddenFiles, MaxFileCount, MaxProjectSize, ProjectCheck,
+    ProjectCheckViolation, ProjectChecks, ProjectChecksError, ProjectFile, RequiredFiles,
+};
+pub use project_events::{ProjectEvent, ProjectEventKind};
+pub use project_export::{
+    ExportProjectError, ImportProjectError, ImportedProject, ProjectExportMetadata,
+};
+pub use project_listing::{Page, Pagination, ProjectFilter, ProjectSummary};
+pub use project_source::{GitSourceError, ProjectSource, ProjectSourceError};
+pub use project_state::{InstallFailure, ProjectState, ProjectStateChange, ProjectTransitionError};
+pub use project_store::{
+    InMemoryProjectStore, JsonFileProjectStore, ProjectStatus, ProjectStore, ProjectStoreBackend,
+    ProjectStoreError, SqliteProjectStore, StoredProject, StoredRun,
+};
+pub use python::{PythonConfig, PythonVersion};
+pub use recovery::RecoveryReport;
+pub use report_generator::{ReportFormat, ReportThresholds, Verdict};
+pub use run_metrics::RunMetricsSample;
+pub use run_report::{
+    ExceptionStats, FailureStats, LoadRunReportError, RequestStats, RunReport, RunReportStatus,
+};
+pub use storage_layout::StorageLayout;
+pub use tenant::{InvalidTenantId, TenantId, TenantQuota, TenantQuotaError};
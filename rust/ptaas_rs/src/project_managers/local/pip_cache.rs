@@ -0,0 +1,147 @@
+use std::{
+    io::Error as IoError,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use tokio::fs;
+
+/// A pip cache shared by all installations of a ```LocalProjectManager```, so repeated installations reuse downloaded wheels.
+/// The cache is passed to the requirements process with ```PIP_CACHE_DIR```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipCacheConfig {
+    /// ```false``` leaves the cache to pip, e.g. the cache of the user running this program.
+    pub enabled: bool,
+    /// ```LocalProjectManager::evict_pip_cache``` deletes the oldest files once the cache is bigger.
+    pub max_size_bytes: u64,
+}
+
+impl Default for PipCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_size_bytes: 5 * 1024 * 1024 * 1024,
+        }
+    }
+}
+
+struct CachedFile {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+/// Deletes the least recently modified files in ```dir``` until it is at most ```max_size_bytes``` big.
+/// Returns the number of deleted bytes. A missing ```dir``` is an empty cache.
+/// Correctness: Files that can not be deleted, e.g. on windows while pip is reading them, are skipped.
+/// Empty directories are left behind, pip reuses them.
+pub(super) async fn evict_to_max_size(dir: &Path, max_size_bytes: u64) -> Result<u64, IoError> {
+    if !fs::try_exists(dir).await? {
+        return Ok(0);
+    }
+
+    let mut cached_files = collect_cached_files(dir).await?;
+    let mut size: u64 = cached_files
+        .iter()
+        .map(|cached_file| cached_file.size)
+        .sum();
+
+    cached_files.sort_by_key(|cached_file| cached_file.modified);
+
+    let mut deleted_bytes = 0;
+    for cached_file in cached_files {
+        if size <= max_size_bytes {
+            break;
+        }
+
+        match fs::remove_file(&cached_file.path).await {
+            Ok(()) => {
+                size -= cached_file.size;
+                deleted_bytes += cached_file.size;
+            }
+            Err(error) => {
+                tracing::warn!(path = ?cached_file.path, %error, "Could not evict file from pip cache");
+            }
+        }
+    }
+
+    tracing::debug!(?dir, deleted_bytes, size, "Evicted pip cache");
+
+    Ok(deleted_bytes)
+}
+
+async fn collect_cached_files(dir: &Path) -> Result<Vec<CachedFile>, IoError> {
+    let mut cached_files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        let mut dir_content = fs::read_dir(&dir).await?;
+
+        while let Some(entry) = dir_content.next_entry().await? {
+            let metadata = entry.metadata().await?;
+
+            if metadata.is_dir() {
+                dirs.push(entry.path());
+                continue;
+            }
+
+            cached_files.push(CachedFile {
+                path: entry.path(),
+                size: metadata.len(),
+                modified: metadata.modified()?,
+            });
+        }
+    }
+
+    Ok(cached_files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_dir;
+    use std::time::Duration;
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn exceed_max_size_and_expect_oldest_files_evicted() {
+        let temp_dir = test_dir("pip_cache");
+        let dir = temp_dir.path();
+        fs::create_dir_all(dir.join("wheels"))
+            .await
+            .expect("Error creating dir.");
+
+        let files = [
+            dir.join("wheels").join("oldest.whl"),
+            dir.join("http_older"),
+            dir.join("wheels").join("newest.whl"),
+        ];
+        for file in &files {
+            fs::write(file, [0; 10]).await.expect("Error writing file.");
+            // Modification times may be coarse.
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let deleted_bytes = evict_to_max_size(dir, 15)
+            .await
+            .expect("Error evicting pip cache.");
+
+        let exists = |index: usize| std::fs::metadata(&files[index]).is_ok();
+        let result = (deleted_bytes, exists(0), exists(1), exists(2));
+
+        assert_eq!(result, (20, false, false, true));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn evict_missing_dir_and_expect_nothing_deleted() {
+        let temp_dir = test_dir("pip_cache_missing");
+        let dir = temp_dir.path().join("does_not_exist");
+
+        let deleted_bytes = evict_to_max_size(&dir, 0)
+            .await
+            .expect("Error evicting pip cache.");
+
+        assert_eq!(deleted_bytes, 0);
+    }
+}
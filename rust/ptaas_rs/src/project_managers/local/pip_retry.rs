@@ -0,0 +1,83 @@
+use crate::project_managers::process::{RetryBackoff, RetryPolicy, TerminationStatus};
+use std::time::Duration;
+
+/// Parts of pip's and uv's stderr, that point to an unreachable or overloaded package index.
+const TRANSIENT_FAILURE_PATTERNS: &[&str] = &[
+    "NewConnectionError",
+    "ConnectTimeoutError",
+    "ReadTimeoutError",
+    "ProtocolError",
+    "Connection refused",
+    "Connection reset",
+    "Connection aborted",
+    "Network is unreachable",
+    "Temporary failure in name resolution",
+    "Max retries exceeded",
+    "500 Server Error",
+    "502 Server Error",
+    "503 Server Error",
+    "504 Server Error",
+    "error sending request",
+];
+
+/// Retries of the requirements phase of a ```LocalProjectInstaller```.
+/// Only failures that look like network errors are retried, an invalid requirement fails on the first attempt.
+/// Correctness: pip retries single requests itself, a retried phase starts the whole installation again.
+#[derive(Debug, Clone)]
+pub struct PipRetryConfig {
+    /// Including the first attempt. 1 disables retries.
+    pub max_attempts: usize,
+    pub backoff: RetryBackoff,
+}
+
+impl Default for PipRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff: RetryBackoff::Exponential {
+                initial: Duration::from_secs(1),
+                max: Duration::from_secs(30),
+            },
+        }
+    }
+}
+
+impl PipRetryConfig {
+    pub(super) fn into_retry_policy(self) -> RetryPolicy {
+        RetryPolicy::new(self.max_attempts, self.backoff).retry_on_stderr(
+            |termination_status, stderr| {
+                matches!(
+                    termination_status,
+                    TerminationStatus::TerminatedWithError(_)
+                ) && is_transient_failure(stderr)
+            },
+        )
+    }
+}
+
+fn is_transient_failure(stderr: &str) -> bool {
+    TRANSIENT_FAILURE_PATTERNS
+        .iter()
+        .any(|pattern| stderr.contains(pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_pip_outputs_and_expect_only_network_errors_transient() {
+        assert!(is_transient_failure(
+            "WARNING: Retrying (Retry(total=0, connect=None, read=None, redirect=None, status=None)) after connection broken by 'NewConnectionError('<pip._vendor.urllib3.connection.HTTPSConnection object at 0x7f065df86490>: Failed to establish a new connection: [Errno -2] Name or service not known')': /simple/locustt/\n"
+        ));
+        assert!(is_transient_failure(
+            "ERROR: HTTP error 503 while getting https://files.pythonhosted.org/packages/locust.whl\nERROR: 503 Server Error: Service Unavailable for url: https://files.pythonhosted.org/packages/locust.whl\n"
+        ));
+        assert!(is_transient_failure(
+            "error: Failed to fetch: `https://pypi.org/simple/locust/`\n  Caused by: error sending request for url (https://pypi.org/simple/locust/)\n"
+        ));
+        assert!(!is_transient_failure(
+            "ERROR: Could not find a version that satisfies the requirement locustt (from versions: 2.15.1)\nERROR: No matching distribution found for locustt\n"
+        ));
+    }
+}
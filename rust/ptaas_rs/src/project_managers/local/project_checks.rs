@@ -0,0 +1,360 @@
+use async_trait::async_trait;
+use std::{
+    collections::BTreeSet,
+    fmt::Debug,
+    io::Error as IoError,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use thiserror::Error as ThisError;
+use tokio::fs;
+
+/// A file of an uploaded project, walked once for every check, see ```ProjectChecks```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectFile {
+    /// Relative to the project dir, e.g. ```locust/locustfile.py```.
+    pub relative_path: PathBuf,
+    pub size_bytes: u64,
+}
+
+/// A rule an uploaded project must follow, in addition to the checks of ```LocalProjectInstaller::check```.
+#[async_trait]
+pub trait ProjectCheck: Debug + Send + Sync {
+    /// Names the check in a ```ProjectCheckViolation```, e.g. ```max_file_count```.
+    fn name(&self) -> &str;
+
+    /// Returns why the project violates the check. ```project_files``` are the files of ```project_dir```, dirs are not listed.
+    async fn check(&self, project_dir: &Path, project_files: &[ProjectFile]) -> Result<(), String>;
+}
+
+/// The checks of a deployment, run in order by ```LocalProjectInstaller::check```. Empty by default.
+/// Every check runs, the violations of all of them are returned together.
+#[derive(Debug, Clone, Default)]
+pub struct ProjectChecks {
+    checks: Vec<Arc<dyn ProjectCheck>>,
+}
+
+impl ProjectChecks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with(mut self, check: impl ProjectCheck + 'static) -> Self {
+        self.checks.push(Arc::new(check));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.checks.is_empty()
+    }
+
+    /// The files ```skip``` returns ```true``` for are not passed to the checks, e.g. the io files of the installer.
+    pub(super) async fn run<F>(&self, project_dir: &Path, skip: F) -> Result<(), ProjectChecksError>
+    where
+        F: Fn(&Path) -> bool,
+    {
+        if self.checks.is_empty() {
+            return Ok(());
+        }
+
+        let project_files = list_files(project_dir, skip)
+            .await
+            .map_err(ProjectChecksError::CouldNotReadProject)?;
+
+        let mut violations = Vec::new();
+
+        for check in &self.checks {
+            if let Err(reason) = check.check(project_dir, &project_files).await {
+                violations.push(ProjectCheckViolation {
+                    check: check.name().to_owned(),
+                    reason,
+                });
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ProjectChecksError::Violations(violations))
+        }
+    }
+}
+
+/// Symlinks are listed as files, they are not followed.
+async fn list_files<F>(project_dir: &Path, skip: F) -> Result<Vec<ProjectFile>, IoError>
+where
+    F: Fn(&Path) -> bool,
+{
+    let mut project_files = Vec::new();
+    let mut dirs = vec![PathBuf::new()];
+
+    while let Some(relative_dir) = dirs.pop() {
+        let mut dir_content = fs::read_dir(project_dir.join(&relative_dir)).await?;
+
+        while let Some(entry) = dir_content.next_entry().await? {
+            let relative_path = relative_dir.join(entry.file_name());
+            if skip(&relative_path) {
+                continue;
+            }
+
+            let metadata = fs::symlink_metadata(entry.path()).await?;
+            if metadata.is_dir() {
+                dirs.push(relative_path);
+                continue;
+            }
+
+            project_files.push(ProjectFile {
+                relative_path,
+                size_bytes: metadata.len(),
+            });
+        }
+    }
+
+    project_files.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+
+    Ok(project_files)
+}
+
+#[derive(ThisError, Debug)]
+#[error("{check}: {reason}")]
+pub struct ProjectCheckViolation {
+    pub check: String,
+    pub reason: String,
+}
+
+#[derive(ThisError, Debug)]
+pub enum ProjectChecksError {
+    #[error("Could not read the project: {0}")]
+    CouldNotReadProject(#[source] IoError),
+    #[error(
+        "Project violates {} checks: {}",
+        .0.len(),
+        .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+    )]
+    Violations(Vec<ProjectCheckViolation>),
+}
+
+/// Files that must exist, relative to the project dir, e.g. ```locust/locustfile.py```.
+#[derive(Debug, Clone)]
+pub struct RequiredFiles(pub Vec<PathBuf>);
+
+#[async_trait]
+impl ProjectCheck for RequiredFiles {
+    fn name(&self) -> &str {
+        "required_files"
+    }
+
+    async fn check(
+        &self,
+        _project_dir: &Path,
+        project_files: &[ProjectFile],
+    ) -> Result<(), String> {
+        let missing: Vec<_> = self
+            .0
+            .iter()
+            .filter(|required_file| {
+                !project_files
+                    .iter()
+                    .any(|project_file| &project_file.relative_path == *required_file)
+            })
+            .map(|required_file| required_file.display().to_string())
+            .collect();
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("missing {}", missing.join(", ")))
+        }
+    }
+}
+
+/// File names that must not exist in any dir of the project, e.g. ```.env```.
+#[derive(Debug, Clone)]
+pub struct ForbiddenFiles(pub Vec<String>);
+
+#[async_trait]
+impl ProjectCheck for ForbiddenFiles {
+    fn name(&self) -> &str {
+        "forbidden_files"
+    }
+
+    async fn check(
+        &self,
+        _project_dir: &Path,
+        project_files: &[ProjectFile],
+    ) -> Result<(), String> {
+        let forbidden: Vec<_> = project_files
+            .iter()
+            .filter(|project_file| {
+                project_file
+                    .relative_path
+                    .file_name()
+                    .is_some_and(|file_name| self.0.iter().any(|name| file_name == name.as_str()))
+            })
+            .map(|project_file| project_file.relative_path.display().to_string())
+            .collect();
+
+        if forbidden.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("found {}", forbidden.join(", ")))
+        }
+    }
+}
+
+/// The summed size of the files of the project.
+#[derive(Debug, Clone, Copy)]
+pub struct MaxProjectSize {
+    pub max_size_bytes: u64,
+}
+
+#[async_trait]
+impl ProjectCheck for MaxProjectSize {
+    fn name(&self) -> &str {
+        "max_project_size"
+    }
+
+    async fn check(
+        &self,
+        _project_dir: &Path,
+        project_files: &[ProjectFile],
+    ) -> Result<(), String> {
+        let size_bytes: u64 = project_files
+            .iter()
+            .map(|project_file| project_file.size_bytes)
+            .sum();
+
+        if size_bytes <= self.max_size_bytes {
+            Ok(())
+        } else {
+            Err(format!(
+                "{size_bytes} bytes, at most {} bytes are allowed",
+                self.max_size_bytes
+            ))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MaxFileCount {
+    pub max_file_count: usize,
+}
+
+#[async_trait]
+impl ProjectCheck for MaxFileCount {
+    fn name(&self) -> &str {
+        "max_file_count"
+    }
+
+    async fn check(
+        &self,
+        _project_dir: &Path,
+        project_files: &[ProjectFile],
+    ) -> Result<(), String> {
+        if project_files.len() <= self.max_file_count {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} files, at most {} files are allowed",
+                project_files.len(),
+                self.max_file_count
+            ))
+        }
+    }
+}
+
+/// Extensions without a dot, e.g. ```py```. Files without an extension are allowed, if the set contains an empty string.
+/// Correctness: Extensions are compared case sensitively.
+#[derive(Debug, Clone)]
+pub struct AllowedExtensions(pub BTreeSet<String>);
+
+#[async_trait]
+impl ProjectCheck for AllowedExtensions {
+    fn name(&self) -> &str {
+        "allowed_extensions"
+    }
+
+    async fn check(
+        &self,
+        _project_dir: &Path,
+        project_files: &[ProjectFile],
+    ) -> Result<(), String> {
+        let not_allowed: Vec<_> = project_files
+            .iter()
+            .filter(|project_file| {
+                let extension = project_file
+                    .relative_path
+                    .extension()
+                    .map(|extension| extension.to_string_lossy())
+                    .unwrap_or_default();
+
+                !self.0.contains(extension.as_ref())
+            })
+            .map(|project_file| project_file.relative_path.display().to_string())
+            .collect();
+
+        if not_allowed.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("not allowed {}", not_allowed.join(", ")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_dir;
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn run_checks_on_project_with_env_file_and_expect_every_violation() {
+        let temp_dir = test_dir("project_checks");
+        let project_dir = temp_dir.path();
+        fs::create_dir_all(project_dir.join("locust"))
+            .await
+            .expect("Error creating dir.");
+        fs::write(project_dir.join("requirements.txt"), "locust==2.15.1\n")
+            .await
+            .expect("Error writing file.");
+        fs::write(project_dir.join("locust").join("locustfile.py"), "")
+            .await
+            .expect("Error writing file.");
+        fs::write(project_dir.join("locust").join(".env"), "SECRET=1\n")
+            .await
+            .expect("Error writing file.");
+        fs::write(project_dir.join("req_out.txt"), "")
+            .await
+            .expect("Error writing file.");
+
+        let project_checks = ProjectChecks::new()
+            .with(RequiredFiles(vec![PathBuf::from("locust/locustfile.py")]))
+            .with(ForbiddenFiles(vec![String::from(".env")]))
+            .with(MaxProjectSize {
+                max_size_bytes: 1024,
+            })
+            .with(MaxFileCount { max_file_count: 2 })
+            .with(AllowedExtensions(BTreeSet::from([
+                String::from("py"),
+                String::from("txt"),
+            ])));
+        let run_result = project_checks
+            .run(project_dir, |relative_path| {
+                relative_path == Path::new("req_out.txt")
+            })
+            .await;
+
+        match run_result {
+            Err(ProjectChecksError::Violations(violations)) => assert_eq!(
+                violations
+                    .iter()
+                    .map(|violation| violation.check.as_str())
+                    .collect::<Vec<_>>(),
+                vec!["forbidden_files", "max_file_count", "allowed_extensions"]
+            ),
+            _ => panic!("Unexpected result: {:?}", run_result),
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use super::{
+    installer_events::InstallerEvent,
+    project_state::{InstallFailure, ProjectState, ProjectStateChange},
+};
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+use tokio::{
+    sync::{broadcast, mpsc},
+    task::JoinHandle,
+};
+
+/// The output lines of an installation are events too, the bus holds more of them than ```ProjectStates``` holds changes.
+const EVENTS_CHANNEL_CAPACITY: usize = 1024;
+
+/// Something that happened to a project, e.g. for the websocket layer, the audit log and the webhooks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectEvent {
+    pub project_id: String,
+    pub kind: ProjectEventKind,
+    pub timestamp: SystemTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProjectEventKind {
+    /// The project was added, see ```ProjectManager::add_project```.
+    Created,
+    /// Waiting for a free installation slot.
+    InstallQueued,
+    /// The project was checked and its environment is being created.
+    InstallStarted,
+    /// An event of the running installation, including its output lines.
+    InstallProgress(InstallerEvent),
+    Installed,
+    InstallFailed(InstallFailure),
+    RunStarted,
+    RunStopping,
+    RunStopped,
+    Uninstalling,
+    Uninstalled,
+    Deleted,
+}
+
+impl ProjectEventKind {
+    /// ```None``` for the changes, that are not an event on their own, e.g. ```ProjectState::Checking```.
+    fn of_state_change(state_change: &ProjectStateChange) -> Option<Self> {
+        match (state_change.previous, state_change.state) {
+            (None, ProjectState::Uploaded) => Some(Self::Created),
+            (Some(ProjectState::Uninstalling), ProjectState::Uploaded) => Some(Self::Uninstalled),
+            (_, ProjectState::QueuedForInstall) => Some(Self::InstallQueued),
+            (_, ProjectState::Installing) => Some(Self::InstallStarted),
+            (Some(ProjectState::Stopping), ProjectState::Installed) => Some(Self::RunStopped),
+            (_, ProjectState::Installed) => Some(Self::Installed),
+            (_, ProjectState::InstallFailed(install_failure)) => {
+                Some(Self::InstallFailed(install_failure))
+            }
+            (_, ProjectState::Running) => Some(Self::RunStarted),
+            (_, ProjectState::Stopping) => Some(Self::RunStopping),
+            (_, ProjectState::Uninstalling) => Some(Self::Uninstalling),
+            (_, ProjectState::Deleted) => Some(Self::Deleted),
+            (_, ProjectState::Uploaded | ProjectState::Checking) => None,
+        }
+    }
+}
+
+/// The event bus of a manager, shared by its ```ProjectStates```, see ```LocalProjectManager::subscribe_project_events```.
+#[derive(Debug, Clone)]
+pub(crate) struct ProjectEvents {
+    events: broadcast::Sender<ProjectEvent>,
+}
+
+impl Default for ProjectEvents {
+    fn default() -> Self {
+        Self {
+            events: broadcast::channel(EVENTS_CHANNEL_CAPACITY).0,
+        }
+    }
+}
+
+impl ProjectEvents {
+    /// Receives the events of all projects from now on, in the order they happened.
+    /// Correctness: A receiver that lags behind by more than ```EVENTS_CHANNEL_CAPACITY``` events misses the oldest ones.
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<ProjectEvent> {
+        self.events.subscribe()
+    }
+
+    /// No subscriber is not an error.
+    pub(crate) fn send(&self, project_id: &str, kind: ProjectEventKind) {
+        let _ = self.events.send(ProjectEvent {
+            project_id: project_id.to_owned(),
+            kind,
+            timestamp: SystemTime::now(),
+        });
+    }
+
+    pub(super) fn send_state_change(&self, state_change: &ProjectStateChange) {
+        if let Some(kind) = ProjectEventKind::of_state_change(state_change) {
+            self.send(&state_change.project_id, kind);
+        }
+    }
+
+    /// Returns the sender for the installer. Its events are sent as ```ProjectEventKind::InstallProgress```
+    /// and forwarded to ```event_sender```, until the installer drops the returned sender.
+    /// Correctness: A slow ```event_sender``` slows the installer down, as it does without the bus.
+    pub(crate) fn forward_installer_events(
+        &self,
+        project_id: String,
+        event_sender: Option<mpsc::Sender<InstallerEvent>>,
+    ) -> (mpsc::Sender<InstallerEvent>, JoinHandle<()>) {
+        let (installer_event_sender, mut installer_event_receiver) =
+            mpsc::channel::<InstallerEvent>(EVENTS_CHANNEL_CAPACITY);
+        let project_events = self.clone();
+
+        let task = tokio::spawn(async move {
+            while let Some(installer_event) = installer_event_receiver.recv().await {
+                project_events.send(
+                    &project_id,
+                    ProjectEventKind::InstallProgress(installer_event.clone()),
+                );
+
+                if let Some(event_sender) = &event_sender {
+                    if event_sender.send(installer_event).await.is_err() {
+                        tracing::trace!("Installer event receiver is closed");
+                    }
+                }
+            }
+        });
+
+        (installer_event_sender, task)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_managers::local::local_project_installer::InstallPhase;
+
+    #[tokio::test]
+    async fn forward_installer_events_and_expect_progress_and_forwarded_events() {
+        let project_events = ProjectEvents::default();
+        let mut events = project_events.subscribe();
+        let (event_sender, mut event_receiver) = mpsc::channel(16);
+
+        let (installer_event_sender, task) =
+            project_events.forward_installer_events(String::from("project"), Some(event_sender));
+        installer_event_sender
+            .send(InstallerEvent::PhaseStarted {
+                phase: InstallPhase::Venv,
+            })
+            .await
+            .expect("Error sending installer event.");
+        drop(installer_event_sender);
+        task.await.expect("Forwarding task panicked.");
+        project_events.send_state_change(&ProjectStateChange {
+            project_id: String::from("project"),
+            previous: Some(ProjectState::Stopping),
+            state: ProjectState::Installed,
+        });
+        project_events.send_state_change(&ProjectStateChange {
+            project_id: String::from("project"),
+            previous: Some(ProjectState::QueuedForInstall),
+            state: ProjectState::Checking,
+        });
+
+        let kinds: Vec<ProjectEventKind> = std::iter::from_fn(|| events.try_recv().ok())
+            .map(|event| event.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ProjectEventKind::InstallProgress(InstallerEvent::PhaseStarted {
+                    phase: InstallPhase::Venv
+                }),
+                ProjectEventKind::RunStopped,
+            ]
+        );
+        assert_eq!(
+            event_receiver.recv().await,
+            Some(InstallerEvent::PhaseStarted {
+                phase: InstallPhase::Venv
+            })
+        );
+    }
+}
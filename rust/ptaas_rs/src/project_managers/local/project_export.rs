@@ -0,0 +1,377 @@
+use super::{
+    archive::{self, ArchiveError, ArchiveLimits},
+    local_project_manager::AddProjectError,
+    lockfile::{self, PackageVersion},
+    manifest::{self, ManifestError, ProjectManifest, MANIFEST_FILE_NAME},
+    project_store::{ProjectStoreError, StoredProject},
+    tenant::TenantId,
+};
+use flate2::{write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    ffi::OsStr,
+    fs::File,
+    io::{Error as IoError, ErrorKind},
+    path::{Component, Path, PathBuf},
+    time::SystemTime,
+};
+use thiserror::Error as ThisError;
+use tokio::{fs, task::JoinError};
+
+/// Bumped when the layout of the archive changes. Archives of a newer version are not imported.
+pub(super) const FORMAT_VERSION: u32 = 1;
+
+const METADATA_FILE_NAME: &str = "ptaas_project.json";
+const SOURCE_DIR_NAME: &str = "source";
+const LOCKFILE_NAME: &str = "installed_lock.txt";
+
+/// The project of an exported archive, written as ```ptaas_project.json``` next to the ```source``` dir
+/// and the ```installed_lock.txt``` of the last successful installation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectExportMetadata {
+    pub format_version: u32,
+    pub id: String,
+    pub name: String,
+    pub tags: BTreeSet<String>,
+    /// The project is imported into the same tenant. Archives without a tenant are imported into the default tenant.
+    #[serde(default)]
+    pub tenant_id: TenantId,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub owner: Option<String>,
+    pub created_at: SystemTime,
+    pub exported_at: SystemTime,
+}
+
+impl ProjectExportMetadata {
+    pub(super) fn of_project(project: &StoredProject) -> Self {
+        Self {
+            format_version: FORMAT_VERSION,
+            id: project.id.clone(),
+            name: project.name.clone(),
+            tags: project.tags.clone(),
+            tenant_id: project.tenant_id.clone(),
+            description: project.description.clone(),
+            owner: project.owner.clone(),
+            created_at: project.created_at,
+            exported_at: SystemTime::now(),
+        }
+    }
+}
+
+/// A project imported by ```LocalProjectManager::import_project```, it is ```ProjectState::Uploaded```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedProject {
+    /// The metadata of the archive, the imported project itself is created at the import.
+    pub metadata: ProjectExportMetadata,
+    /// The packages of the exported installation, empty if the project was not installed.
+    /// Correctness: The packages are not pinned, the imported project is installed from its requirements.
+    pub packages: Vec<PackageVersion>,
+}
+
+#[derive(ThisError, Debug)]
+pub enum ExportProjectError {
+    #[error("Could not load the project: {0}")]
+    CouldNotLoadProject(#[source] ProjectStoreError),
+    #[error("Project does not exist: {0}")]
+    ProjectDoesNotExist(String),
+    #[error("Could not read the uploaded project: {0}")]
+    CouldNotReadProject(#[source] IoError),
+    #[error("Could not write the archive: {0}")]
+    CouldNotWriteArchive(#[source] IoError),
+    #[error("Archive task failed: {0}")]
+    TaskFailed(#[source] JoinError),
+}
+
+#[derive(ThisError, Debug)]
+pub enum ImportProjectError {
+    #[error("Could not extract the archive: {0}")]
+    Archive(
+        #[source]
+        #[from]
+        ArchiveError,
+    ),
+    #[error("Could not read the project metadata: {0}")]
+    CouldNotReadMetadata(#[source] IoError),
+    #[error("Could not parse the project metadata: {0}")]
+    CouldNotParseMetadata(#[source] serde_json::Error),
+    #[error(
+        "Archive format version {version} is not supported, expected at most {FORMAT_VERSION}"
+    )]
+    UnsupportedFormatVersion { version: u32 },
+    #[error("Project id must be a dir name: {0:?}")]
+    InvalidProjectId(String),
+    #[error("Uploaded project does not match the exported project: {0}")]
+    Manifest(
+        #[source]
+        #[from]
+        ManifestError,
+    ),
+    #[error("Could not read the lockfile: {0}")]
+    CouldNotReadLockfile(#[source] IoError),
+    #[error("Could not load the project: {0}")]
+    CouldNotLoadProject(#[source] ProjectStoreError),
+    #[error("Project already exists: {0}")]
+    ProjectAlreadyExists(String),
+    #[error("Could not move the uploaded project: {0}")]
+    CouldNotMoveProject(#[source] IoError),
+    #[error("Could not add the project: {0}")]
+    Add(
+        #[source]
+        #[from]
+        AddProjectError,
+    ),
+}
+
+/// An extracted archive, see ```extract```.
+#[derive(Debug)]
+pub(super) struct ExtractedProject {
+    pub(super) metadata: ProjectExportMetadata,
+    /// The uploaded project, without the manifest of the archive.
+    pub(super) source_dir: PathBuf,
+    pub(super) packages: Vec<PackageVersion>,
+}
+
+/// Writes the uploaded project, the files of ```project_manifest```, to a tar.gz archive at ```archive_path```.
+/// The manifest is written with the project, so the import can check it.
+/// Correctness: A partially written archive is deleted. Empty dirs are not exported.
+pub(super) async fn write_archive(
+    archive_path: &Path,
+    metadata: &ProjectExportMetadata,
+    source_dir: &Path,
+    project_manifest: &ProjectManifest,
+    lockfile_path: Option<PathBuf>,
+) -> Result<(), ExportProjectError> {
+    let metadata = serde_json::to_vec_pretty(metadata)
+        .map_err(|error| ExportProjectError::CouldNotWriteArchive(error.into()))?;
+    let manifest = serde_json::to_vec_pretty(project_manifest)
+        .map_err(|error| ExportProjectError::CouldNotWriteArchive(error.into()))?;
+
+    let write_result = {
+        let archive_path = archive_path.to_path_buf();
+        let source_dir = source_dir.to_path_buf();
+        let relative_paths: Vec<String> = project_manifest.files.keys().cloned().collect();
+
+        tokio::task::spawn_blocking(move || {
+            write_archive_blocking(
+                &archive_path,
+                &metadata,
+                &manifest,
+                &source_dir,
+                &relative_paths,
+                lockfile_path.as_deref(),
+            )
+        })
+        .await
+        .map_err(ExportProjectError::TaskFailed)
+        .and_then(|write_result| write_result.map_err(ExportProjectError::CouldNotWriteArchive))
+    };
+
+    if write_result.is_err() {
+        let _ = fs::remove_file(archive_path).await;
+    }
+
+    write_result
+}
+
+fn write_archive_blocking(
+    archive_path: &Path,
+    metadata: &[u8],
+    manifest: &[u8],
+    source_dir: &Path,
+    relative_paths: &[String],
+    lockfile_path: Option<&Path>,
+) -> Result<(), IoError> {
+    let encoder = GzEncoder::new(File::create(archive_path)?, Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+
+    append_data(&mut builder, METADATA_FILE_NAME, metadata)?;
+    append_data(
+        &mut builder,
+        &format!("{SOURCE_DIR_NAME}/{MANIFEST_FILE_NAME}"),
+        manifest,
+    )?;
+
+    for relative_path in relative_paths {
+        builder.append_path_with_name(
+            source_dir.join(relative_path),
+            format!("{SOURCE_DIR_NAME}/{relative_path}"),
+        )?;
+    }
+
+    if let Some(lockfile_path) = lockfile_path {
+        builder.append_path_with_name(lockfile_path, LOCKFILE_NAME)?;
+    }
+
+    builder.into_inner()?.finish()?;
+
+    Ok(())
+}
+
+fn append_data(
+    builder: &mut tar::Builder<GzEncoder<File>>,
+    path: &str,
+    data: &[u8],
+) -> Result<(), IoError> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    );
+    header.set_cksum();
+
+    builder.append_data(&mut header, path, data)
+}
+
+/// Extracts an exported archive to ```import_dir``` and checks the uploaded project against its manifest.
+/// Correctness: The archive is extracted with ```extract_uploaded_archive```, its entries are checked the same way.
+/// The id of the archive is checked, it becomes the name of the uploaded project dir.
+pub(super) async fn extract(
+    archive_path: &Path,
+    import_dir: &Path,
+    archive_limits: ArchiveLimits,
+) -> Result<ExtractedProject, ImportProjectError> {
+    archive::extract_uploaded_archive(archive_path, import_dir, archive_limits).await?;
+
+    let metadata = fs::read(import_dir.join(METADATA_FILE_NAME))
+        .await
+        .map_err(ImportProjectError::CouldNotReadMetadata)?;
+    let metadata: ProjectExportMetadata =
+        serde_json::from_slice(&metadata).map_err(ImportProjectError::CouldNotParseMetadata)?;
+
+    if metadata.format_version > FORMAT_VERSION {
+        return Err(ImportProjectError::UnsupportedFormatVersion {
+            version: metadata.format_version,
+        });
+    }
+
+    if !is_dir_name(&metadata.id) {
+        return Err(ImportProjectError::InvalidProjectId(metadata.id));
+    }
+
+    let source_dir = import_dir.join(SOURCE_DIR_NAME);
+    manifest::verify(&source_dir, |_| false).await?;
+    fs::remove_file(source_dir.join(MANIFEST_FILE_NAME))
+        .await
+        .map_err(ImportProjectError::CouldNotMoveProject)?;
+
+    let packages = match fs::read_to_string(import_dir.join(LOCKFILE_NAME)).await {
+        Ok(lockfile) => lockfile::parse(&lockfile),
+        Err(error) if error.kind() == ErrorKind::NotFound => Vec::new(),
+        Err(error) => return Err(ImportProjectError::CouldNotReadLockfile(error)),
+    };
+
+    Ok(ExtractedProject {
+        metadata,
+        source_dir,
+        packages,
+    })
+}
+
+/// A single normal path component, e.g. not ```..``` or ```a/b```.
+fn is_dir_name(id: &str) -> bool {
+    let mut components = Path::new(id).components();
+
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(name)), None) if name == OsStr::new(id)
+    ) && !id.contains(['/', '\\'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_dir;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn write_and_extract_archive_and_expect_same_project() {
+        let temp_dir = test_dir("project_export");
+        let test_dir = temp_dir.path();
+        let source_dir = test_dir.join("source_project");
+        fs::create_dir_all(source_dir.join("locust"))
+            .await
+            .expect("Error creating test dir.");
+        fs::write(source_dir.join("requirements.txt"), "locust\n")
+            .await
+            .expect("Error writing requirements.");
+        fs::write(source_dir.join("locust").join("main.py"), "# main\n")
+            .await
+            .expect("Error writing locustfile.");
+        fs::write(source_dir.join("req_out.txt"), "installed")
+            .await
+            .expect("Error writing io file.");
+        let lockfile_path = test_dir.join(LOCKFILE_NAME);
+        fs::write(&lockfile_path, "locust==2.15.1\n")
+            .await
+            .expect("Error writing lockfile.");
+        let metadata = ProjectExportMetadata {
+            format_version: FORMAT_VERSION,
+            id: String::from("project"),
+            name: String::from("Project"),
+            tags: BTreeSet::from([String::from("checkout")]),
+            tenant_id: TenantId::default(),
+            description: None,
+            owner: Some(String::from("checkout-team")),
+            created_at: SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            exported_at: SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_100),
+        };
+        let archive_path = test_dir.join("project.tar.gz");
+
+        let project_manifest = manifest::generate(&source_dir, |relative_path| {
+            relative_path == Path::new("req_out.txt")
+        })
+        .await
+        .expect("Error generating manifest.");
+        write_archive(
+            &archive_path,
+            &metadata,
+            &source_dir,
+            &project_manifest,
+            Some(lockfile_path),
+        )
+        .await
+        .expect("Error writing archive.");
+        let extracted = extract(
+            &archive_path,
+            &test_dir.join("import"),
+            ArchiveLimits::default(),
+        )
+        .await;
+        let extracted_manifest = match &extracted {
+            Ok(extracted) => manifest::generate(&extracted.source_dir, |_| false)
+                .await
+                .ok(),
+            Err(_) => None,
+        };
+        let manifest_was_removed = match &extracted {
+            Ok(extracted) => !fs::try_exists(extracted.source_dir.join(MANIFEST_FILE_NAME))
+                .await
+                .unwrap_or(true),
+            Err(_) => false,
+        };
+
+        let extracted = extracted.expect("Error extracting archive.");
+        assert_eq!(extracted.metadata, metadata);
+        assert_eq!(
+            extracted.packages,
+            [PackageVersion {
+                name: String::from("locust"),
+                version: String::from("2.15.1"),
+            }]
+        );
+        assert_eq!(extracted_manifest, Some(project_manifest));
+        assert!(manifest_was_removed);
+        assert!(is_dir_name("project"));
+        assert!(!is_dir_name(".."));
+        assert!(!is_dir_name("a/b"));
+        assert!(!is_dir_name(""));
+    }
+}
@@ -0,0 +1,222 @@
+use super::{project_state::ProjectState, project_store::StoredProject, tenant::TenantId};
+use models::project_metadata::ProjectMetadata;
+use std::{collections::BTreeSet, mem, time::SystemTime};
+
+/// Selects the projects of ```LocalProjectManager::list_projects```, every project by default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectFilter {
+    /// Projects in any of the states. Empty matches every state. The reason of ```ProjectState::InstallFailed``` is ignored.
+    pub states: Vec<ProjectState>,
+    /// Case insensitive substring of the name.
+    pub name_contains: Option<String>,
+    /// Projects that have all of the tags.
+    pub tags: BTreeSet<String>,
+    /// The projects of the tenant. ```None``` matches every tenant.
+    pub tenant_id: Option<TenantId>,
+    /// The projects of the owner, case sensitive. ```None``` matches every project, including the ones without an owner.
+    pub owner: Option<String>,
+}
+
+impl ProjectFilter {
+    fn matches(&self, project: &StoredProject, state: ProjectState) -> bool {
+        (self.states.is_empty()
+            || self
+                .states
+                .iter()
+                .any(|filter_state| mem::discriminant(filter_state) == mem::discriminant(&state)))
+            && self.name_contains.as_ref().map_or(true, |name_contains| {
+                project
+                    .name
+                    .to_lowercase()
+                    .contains(&name_contains.to_lowercase())
+            })
+            && self.tags.is_subset(&project.tags)
+            && self
+                .tenant_id
+                .as_ref()
+                .map_or(true, |tenant_id| *tenant_id == project.tenant_id)
+            && self
+                .owner
+                .as_ref()
+                .map_or(true, |owner| project.owner.as_ref() == Some(owner))
+    }
+}
+
+/// The first page is ```0```. A ```page_size``` of ```0``` is treated as ```1```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pagination {
+    pub page: usize,
+    pub page_size: usize,
+}
+
+impl Default for Pagination {
+    fn default() -> Self {
+        Self {
+            page: 0,
+            page_size: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub pagination: Pagination,
+    /// The items on all pages.
+    pub total_items: usize,
+}
+
+impl<T> Page<T> {
+    pub fn total_pages(&self) -> usize {
+        let page_size = self.pagination.page_size.max(1);
+
+        (self.total_items + page_size - 1) / page_size
+    }
+}
+
+/// A project as listed by ```LocalProjectManager::list_projects```, e.g. for the projects of the API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectSummary {
+    pub id: String,
+    pub name: String,
+    pub state: ProjectState,
+    pub tags: BTreeSet<String>,
+    pub tenant_id: TenantId,
+    pub description: Option<String>,
+    pub owner: Option<String>,
+    pub created_at: SystemTime,
+    pub updated_at: SystemTime,
+}
+
+impl From<ProjectSummary> for ProjectMetadata {
+    fn from(project_summary: ProjectSummary) -> Self {
+        let to_millis = |time: SystemTime| {
+            time.duration_since(SystemTime::UNIX_EPOCH)
+                .map(|duration| u64::try_from(duration.as_millis()).unwrap_or(u64::MAX))
+                .unwrap_or(0)
+        };
+
+        Self {
+            id: project_summary.id,
+            name: project_summary.name,
+            description: project_summary.description,
+            owner: project_summary.owner,
+            tags: project_summary.tags.into_iter().collect(),
+            tenant_id: project_summary.tenant_id.into(),
+            created_at: to_millis(project_summary.created_at),
+            updated_at: to_millis(project_summary.updated_at),
+        }
+    }
+}
+
+/// Keeps the order of ```projects```, a page past the last one is empty.
+pub(crate) fn list(
+    projects: Vec<(StoredProject, ProjectState)>,
+    filter: &ProjectFilter,
+    pagination: Pagination,
+) -> Page<ProjectSummary> {
+    let matching: Vec<_> = projects
+        .into_iter()
+        .filter(|(project, state)| filter.matches(project, *state))
+        .collect();
+    let total_items = matching.len();
+    let page_size = pagination.page_size.max(1);
+
+    let items = matching
+        .into_iter()
+        .skip(pagination.page.saturating_mul(page_size))
+        .take(page_size)
+        .map(|(project, state)| ProjectSummary {
+            id: project.id,
+            name: project.name,
+            state,
+            tags: project.tags,
+            tenant_id: project.tenant_id,
+            description: project.description,
+            owner: project.owner,
+            created_at: project.created_at,
+            updated_at: project.updated_at,
+        })
+        .collect();
+
+    Page {
+        items,
+        pagination,
+        total_items,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_managers::local::{
+        project_state::InstallFailure, project_store::ProjectStatus,
+    };
+    use std::path::PathBuf;
+
+    #[test]
+    fn list_second_page_of_tagged_projects_and_expect_total_of_all_pages() {
+        let project = |id: &str, tags: &[&str], state| {
+            let owner = (id != "a").then(|| String::from("checkout-team"));
+            (
+                StoredProject {
+                    id: id.to_owned(),
+                    name: format!("Load Test {id}"),
+                    source_dir: PathBuf::from(id),
+                    status: ProjectStatus::Installed,
+                    created_at: SystemTime::UNIX_EPOCH,
+                    updated_at: SystemTime::UNIX_EPOCH,
+                    requirements_hash: None,
+                    tags: tags.iter().map(|tag| tag.to_string()).collect(),
+                    tenant_id: TenantId::default(),
+                    description: None,
+                    owner,
+                },
+                state,
+            )
+        };
+        let projects = vec![
+            project("a", &["smoke", "api"], ProjectState::Installed),
+            project("b", &["smoke"], ProjectState::Installed),
+            project("b2", &["smoke", "api"], ProjectState::Installed),
+            project("c", &["smoke", "api"], ProjectState::Running),
+            project(
+                "d",
+                &["smoke", "api"],
+                ProjectState::InstallFailed(InstallFailure::Failed),
+            ),
+            project("e", &["api"], ProjectState::Installed),
+        ];
+        let filter = ProjectFilter {
+            states: vec![ProjectState::Installed, ProjectState::Running],
+            name_contains: Some(String::from("load test")),
+            tags: BTreeSet::from([String::from("smoke"), String::from("api")]),
+            tenant_id: None,
+            owner: Some(String::from("checkout-team")),
+        };
+
+        let page = list(
+            projects,
+            &filter,
+            Pagination {
+                page: 1,
+                page_size: 1,
+            },
+        );
+
+        assert_eq!(
+            page.items
+                .iter()
+                .map(|project_summary| project_summary.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["c"]
+        );
+        assert_eq!(page.items[0].state, ProjectState::Running);
+        assert_eq!(page.total_items, 2);
+        assert_eq!(page.total_pages(), 2);
+        let project_metadata = ProjectMetadata::from(page.items[0].clone());
+        assert_eq!(project_metadata.owner.as_deref(), Some("checkout-team"));
+        assert_eq!(project_metadata.tags, vec!["api", "smoke"]);
+        assert_eq!(project_metadata.tenant_id, "default");
+    }
+}
@@ -0,0 +1,64 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// Allows one operation at a time per project, e.g. an installation or an uninstallation, see ```LocalProjectManager```.
+/// Correctness: A project is unlocked when its ```ProjectLockGuard``` is dropped, no entry is kept for unlocked projects.
+#[derive(Debug, Clone, Default)]
+pub(super) struct ProjectLocks {
+    locked: Arc<Mutex<HashSet</* id */ String>>>,
+}
+
+impl ProjectLocks {
+    /// ```None``` if an operation on the project is in progress. Does not wait.
+    pub(super) fn try_lock(&self, project_id: &str) -> Option<ProjectLockGuard> {
+        let newly_locked = self
+            .locked
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(project_id.to_owned());
+
+        newly_locked.then(|| ProjectLockGuard {
+            project_locks: self.clone(),
+            project_id: project_id.to_owned(),
+        })
+    }
+}
+
+/// Held for the whole operation, e.g. moved into the task of an installation.
+#[derive(Debug)]
+pub(super) struct ProjectLockGuard {
+    project_locks: ProjectLocks,
+    project_id: String,
+}
+
+impl Drop for ProjectLockGuard {
+    fn drop(&mut self) {
+        self.project_locks
+            .locked
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&self.project_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lock_project_twice_and_expect_second_lock_after_drop() {
+        let project_locks = ProjectLocks::default();
+
+        let guard = project_locks.try_lock("project");
+        let second_guard = project_locks.try_lock("project");
+        let other_guard = project_locks.try_lock("other");
+        drop(guard);
+        let guard_after_drop = project_locks.try_lock("project");
+
+        assert!(second_guard.is_none());
+        assert!(other_guard.is_some());
+        assert!(guard_after_drop.is_some());
+    }
+}
@@ -0,0 +1,374 @@
+use super::archive::{self, ArchiveError, ArchiveLimits};
+use crate::project_managers::process::{self, ProcessRunError, Status, TerminationStatus};
+use std::{
+    ffi::{OsStr, OsString},
+    io::Error as IoError,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use thiserror::Error as ThisError;
+use tokio::fs;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// Marks a dir next to an uploaded project dir as cloning, e.g. ```uploaded_projects/<id>.cloning-<uuid>```.
+const CLONING_DIR_MARKER: &str = ".cloning-";
+
+/// Only the end of git's stderr is kept, e.g. ```fatal: repository not found```.
+const MAX_GIT_OUTPUT_SIZE: usize = 4 * 1024;
+
+/// Cloning a big repository takes minutes, an unreachable remote is given up on.
+const GIT_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Where ```LocalProjectInstaller::check_and_install``` gets the project from, before it is checked.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ProjectSource {
+    /// The project is already in the uploaded project dir.
+    #[default]
+    Uploaded,
+    /// A zip or tar.gz archive of the project, e.g. uploaded by the client, see ```extract_uploaded_archive```.
+    Archive(PathBuf),
+    /// A git repository, cloned with the ```git``` in PATH. ```git_ref``` is a branch, tag or commit,
+    /// the default branch of the repository if ```None```.
+    Git {
+        url: String,
+        git_ref: Option<String>,
+    },
+}
+
+impl ProjectSource {
+    /// Replaces ```uploaded_project_dir``` with the project, nothing is done for ```ProjectSource::Uploaded```.
+    pub(super) async fn fetch(
+        &self,
+        uploaded_project_dir: &Path,
+        archive_limits: ArchiveLimits,
+        cancellation_token: &CancellationToken,
+    ) -> Result<(), ProjectSourceError> {
+        match self {
+            Self::Uploaded => Ok(()),
+            Self::Archive(archive_path) => {
+                archive::extract_uploaded_archive(
+                    archive_path,
+                    uploaded_project_dir,
+                    archive_limits,
+                )
+                .await?;
+                Ok(())
+            }
+            Self::Git { url, git_ref } => {
+                clone(
+                    url,
+                    git_ref.as_deref(),
+                    uploaded_project_dir,
+                    cancellation_token,
+                )
+                .await?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Clones next to ```uploaded_project_dir``` first and replaces it once the clone and checkout succeeded.
+/// The ```.git``` dir is removed, the project is copied to the installed project dir without it.
+/// Correctness: Git never prompts for credentials, a repository that requires them fails with ```GitSourceError::CloneFailed```.
+async fn clone(
+    url: &str,
+    git_ref: Option<&str>,
+    uploaded_project_dir: &Path,
+    cancellation_token: &CancellationToken,
+) -> Result<(), GitSourceError> {
+    // Refs starting with a dash would be parsed as options of git.
+    if let Some(git_ref) = git_ref.filter(|git_ref| git_ref.starts_with('-')) {
+        return Err(GitSourceError::InvalidRef(git_ref.to_owned()));
+    }
+
+    let cloning_dir = cloning_dir_path(uploaded_project_dir);
+
+    let clone_result = clone_into(
+        url,
+        git_ref,
+        &cloning_dir,
+        uploaded_project_dir,
+        cancellation_token,
+    )
+    .await;
+
+    if clone_result.is_err() {
+        let _ = fs::remove_dir_all(&cloning_dir).await;
+    }
+
+    clone_result
+}
+
+async fn clone_into(
+    url: &str,
+    git_ref: Option<&str>,
+    cloning_dir: &Path,
+    uploaded_project_dir: &Path,
+    cancellation_token: &CancellationToken,
+) -> Result<(), GitSourceError> {
+    // The parent of the uploaded project dir is the current dir of git, it must exist.
+    let parent_dir = cloning_dir.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(parent_dir)
+        .await
+        .map_err(GitSourceError::CouldNotReplaceUploadedProjectDir)?;
+
+    let stderr = run_git(
+        vec![
+            OsStr::new("clone"),
+            OsStr::new("--quiet"),
+            OsStr::new("--"),
+            OsStr::new(url),
+            cloning_dir.as_os_str(),
+        ],
+        parent_dir,
+        cancellation_token,
+    )
+    .await?;
+
+    if let Some(stderr) = stderr {
+        return Err(GitSourceError::CloneFailed(stderr));
+    }
+
+    if let Some(git_ref) = git_ref {
+        let stderr = run_git(
+            vec![
+                OsStr::new("checkout"),
+                OsStr::new("--quiet"),
+                OsStr::new("--detach"),
+                OsStr::new(git_ref),
+            ],
+            cloning_dir,
+            cancellation_token,
+        )
+        .await?;
+
+        if let Some(stderr) = stderr {
+            return Err(GitSourceError::CheckoutFailed {
+                git_ref: git_ref.to_owned(),
+                stderr,
+            });
+        }
+    }
+
+    replace_uploaded_project_dir(cloning_dir, uploaded_project_dir)
+        .await
+        .map_err(GitSourceError::CouldNotReplaceUploadedProjectDir)
+}
+
+fn cloning_dir_path(uploaded_project_dir: &Path) -> PathBuf {
+    let mut file_name = uploaded_project_dir
+        .file_name()
+        .unwrap_or_default()
+        .to_os_string();
+    file_name.push(CLONING_DIR_MARKER);
+    file_name.push(Uuid::new_v4().to_string());
+
+    uploaded_project_dir.with_file_name(file_name)
+}
+
+/// Returns the stderr of git if it failed, e.g. if it was cancelled or timed out.
+async fn run_git(
+    args: Vec<&OsStr>,
+    current_dir: &Path,
+    cancellation_token: &CancellationToken,
+) -> Result<Option<String>, GitSourceError> {
+    let output = process::capture(
+        OsStr::new("git"),
+        args,
+        current_dir,
+        vec![(OsString::from("GIT_TERMINAL_PROMPT"), OsString::from("0"))],
+        MAX_GIT_OUTPUT_SIZE,
+        GIT_TIMEOUT,
+        Some(cancellation_token),
+    )
+    .await
+    .map_err(GitSourceError::CouldNotRunGit)?;
+
+    if matches!(
+        output.status,
+        Status::Terminated(TerminationStatus::TerminatedSuccessfully)
+    ) {
+        Ok(None)
+    } else {
+        Ok(Some(output.stderr))
+    }
+}
+
+async fn replace_uploaded_project_dir(
+    cloning_dir: &Path,
+    uploaded_project_dir: &Path,
+) -> Result<(), IoError> {
+    fs::remove_dir_all(cloning_dir.join(".git")).await?;
+
+    if fs::try_exists(uploaded_project_dir).await? {
+        fs::remove_dir_all(uploaded_project_dir).await?;
+    }
+
+    fs::rename(cloning_dir, uploaded_project_dir).await
+}
+
+#[derive(ThisError, Debug)]
+pub enum ProjectSourceError {
+    #[error("Could not extract the uploaded archive: {0}")]
+    Archive(
+        #[from]
+        #[source]
+        ArchiveError,
+    ),
+    #[error("Could not get the project from git: {0}")]
+    Git(
+        #[from]
+        #[source]
+        GitSourceError,
+    ),
+}
+
+#[derive(ThisError, Debug)]
+pub enum GitSourceError {
+    #[error("Git ref must not start with a dash: {0}")]
+    InvalidRef(String),
+    #[error("Could not run git: {0}")]
+    CouldNotRunGit(#[source] ProcessRunError),
+    #[error("Git clone failed: {0}")]
+    CloneFailed(String),
+    #[error("Git checkout of {git_ref} failed: {stderr}")]
+    CheckoutFailed { git_ref: String, stderr: String },
+    #[error("Could not replace the uploaded project dir: {0}")]
+    CouldNotReplaceUploadedProjectDir(#[source] IoError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_dir;
+    use tracing_test::traced_test;
+
+    async fn git(repository_dir: &Path, args: &[&str]) {
+        let mut git_args = vec!["-c", "user.name=ptaas", "-c", "user.email=ptaas@localhost"];
+        git_args.extend(args);
+
+        let status = tokio::process::Command::new("git")
+            .args(git_args)
+            .current_dir(repository_dir)
+            .output()
+            .await
+            .expect("Error running git.")
+            .status;
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn fetch_git_source_with_tag_and_expect_tagged_project_without_git_dir() {
+        let temp_dir = test_dir("project_source_git");
+        let test_dir = temp_dir.path();
+        let repository_dir = test_dir.join("repository");
+        let uploaded_project_dir = test_dir.join("uploaded_projects").join("project");
+        fs::create_dir_all(&repository_dir)
+            .await
+            .expect("Error creating dir.");
+        git(&repository_dir, &["init", "--quiet"]).await;
+        fs::write(repository_dir.join("requirements.txt"), "locust==2.15.1\n")
+            .await
+            .expect("Error writing file.");
+        git(&repository_dir, &["add", "."]).await;
+        git(&repository_dir, &["commit", "--quiet", "-m", "v1"]).await;
+        git(&repository_dir, &["tag", "v1"]).await;
+        fs::write(repository_dir.join("requirements.txt"), "locust==2.16.0\n")
+            .await
+            .expect("Error writing file.");
+        git(&repository_dir, &["commit", "--quiet", "-am", "v2"]).await;
+
+        let project_source = ProjectSource::Git {
+            url: repository_dir.to_string_lossy().into_owned(),
+            git_ref: Some(String::from("v1")),
+        };
+        let fetch_result = project_source
+            .fetch(
+                &uploaded_project_dir,
+                ArchiveLimits::default(),
+                &CancellationToken::new(),
+            )
+            .await;
+
+        let requirements = fs::read_to_string(uploaded_project_dir.join("requirements.txt")).await;
+        let git_dir_exists = fs::try_exists(uploaded_project_dir.join(".git")).await;
+
+        fetch_result.expect("Error fetching project.");
+        assert_eq!(
+            requirements.expect("Error reading requirements."),
+            "locust==2.15.1\n"
+        );
+        assert!(!git_dir_exists.expect("Error checking git dir."));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn fetch_git_source_with_dash_ref_and_expect_invalid_ref_error() {
+        let temp_dir = test_dir("project_source_dash_ref");
+        let project_source = ProjectSource::Git {
+            url: String::from("https://example.com/locust.git"),
+            git_ref: Some(String::from("--upload-pack=touch")),
+        };
+
+        let fetch_result = project_source
+            .fetch(
+                &temp_dir.path().join("project"),
+                ArchiveLimits::default(),
+                &CancellationToken::new(),
+            )
+            .await;
+
+        assert!(
+            matches!(
+                fetch_result,
+                Err(ProjectSourceError::Git(GitSourceError::InvalidRef(_)))
+            ),
+            "Unexpected result: {:?}",
+            fetch_result
+        );
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn fetch_git_source_with_cancelled_token_and_expect_clone_failed_without_cloning_dir() {
+        let temp_dir = test_dir("project_source_cancelled");
+        let test_dir = temp_dir.path();
+        let uploaded_project_dir = test_dir.join("uploaded_projects").join("project");
+        let cancellation_token = CancellationToken::new();
+        cancellation_token.cancel();
+
+        let project_source = ProjectSource::Git {
+            url: String::from("https://example.com/locust.git"),
+            git_ref: None,
+        };
+        let fetch_result = project_source
+            .fetch(
+                &uploaded_project_dir,
+                ArchiveLimits::default(),
+                &cancellation_token,
+            )
+            .await;
+
+        let mut entries = fs::read_dir(test_dir.join("uploaded_projects"))
+            .await
+            .expect("Error reading dir.");
+        let has_entries = entries
+            .next_entry()
+            .await
+            .expect("Error reading entry.")
+            .is_some();
+
+        assert!(
+            matches!(
+                fetch_result,
+                Err(ProjectSourceError::Git(GitSourceError::CloneFailed(_)))
+            ),
+            "Unexpected result: {:?}",
+            fetch_result
+        );
+        assert!(!has_entries);
+    }
+}
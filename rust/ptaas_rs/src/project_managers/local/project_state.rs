@@ -0,0 +1,348 @@
+use super::{
+    project_events::ProjectEvents,
+    project_store::{ProjectStatus, ProjectStore, ProjectStoreError, StoredProject},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+use thiserror::Error as ThisError;
+use tokio::sync::{broadcast, RwLock};
+
+/// Subscribers, that fall further behind, miss the oldest changes, see ```ProjectStates::subscribe```.
+const CHANGES_CHANNEL_CAPACITY: usize = 256;
+
+/// Where a project is in its lifecycle, maintained by ```LocalProjectManager```, see ```ProjectState::can_transition_to```.
+/// Correctness: Only the states, that survive a restart, are saved as ```ProjectStatus```.
+/// A project, that was checked or installed during a restart, is ```InstallFailed(InstallFailure::Interrupted)``` afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProjectState {
+    Uploaded,
+    /// Waiting for a free installation slot, see ```LocalProjectManager::set_max_concurrent_installations```.
+    QueuedForInstall,
+    Checking,
+    Installing,
+    Installed,
+    InstallFailed(InstallFailure),
+    Running,
+    Stopping,
+    Uninstalling,
+    /// Final, the project can not be used anymore.
+    Deleted,
+}
+
+/// Why a project is ```ProjectState::InstallFailed```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InstallFailure {
+    /// The last installation failed, see ```LocalProjectManager::load_install_report```.
+    Failed,
+    /// The installation was cancelled while it waited for a slot.
+    Cancelled,
+    /// The manager stopped during the installation, e.g. ptaas_rs was restarted.
+    Interrupted,
+    /// The environment or the installed project was deleted outside of the manager.
+    Missing,
+    /// Uninstalling the project failed part way, see ```LocalProjectManager::uninstall_project```.
+    PartiallyUninstalled,
+}
+
+impl ProjectState {
+    /// An installed project must be uninstalled before it is deleted, a running one must be stopped before anything else.
+    /// A partially uninstalled project is ```InstallFailed```, so uninstalling it can be retried.
+    /// A project that could not be stopped stays ```Stopping```, so stopping it can be retried.
+    pub fn can_transition_to(self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            (Self::Uploaded, Self::QueuedForInstall | Self::Deleted)
+                | (
+                    Self::QueuedForInstall,
+                    Self::Checking | Self::InstallFailed(_)
+                )
+                | (
+                    Self::Checking,
+                    Self::Installing | Self::Installed | Self::InstallFailed(_)
+                )
+                | (Self::Installing, Self::Installed | Self::InstallFailed(_))
+                | (
+                    Self::Installed,
+                    Self::QueuedForInstall | Self::Running | Self::Uninstalling
+                )
+                | (
+                    Self::InstallFailed(_),
+                    Self::QueuedForInstall | Self::Uninstalling | Self::Deleted
+                )
+                | (Self::Running, Self::Stopping)
+                | (Self::Stopping, Self::Stopping | Self::Installed)
+                | (Self::Uninstalling, Self::Uploaded | Self::InstallFailed(_))
+        )
+    }
+
+    /// ```None``` if the state is not saved, the previous status is kept.
+    fn status(self) -> Option<ProjectStatus> {
+        match self {
+            Self::Uploaded => Some(ProjectStatus::Uploaded),
+            Self::Checking => Some(ProjectStatus::Installing),
+            Self::Installed => Some(ProjectStatus::Installed),
+            Self::InstallFailed(_) => Some(ProjectStatus::Failed),
+            Self::QueuedForInstall
+            | Self::Installing
+            | Self::Running
+            | Self::Stopping
+            | Self::Uninstalling
+            | Self::Deleted => None,
+        }
+    }
+}
+
+impl From<ProjectStatus> for ProjectState {
+    fn from(status: ProjectStatus) -> Self {
+        match status {
+            ProjectStatus::Uploaded => Self::Uploaded,
+            ProjectStatus::Installed => Self::Installed,
+            // An installation does not survive a restart.
+            ProjectStatus::Installing => Self::InstallFailed(InstallFailure::Interrupted),
+            ProjectStatus::Failed => Self::InstallFailed(InstallFailure::Failed),
+        }
+    }
+}
+
+#[derive(ThisError, Debug)]
+pub enum ProjectTransitionError {
+    #[error("Project does not exist: {0}")]
+    ProjectDoesNotExist(String),
+    #[error("Invalid transition of project {project_id} from {from:?} to {to:?}")]
+    InvalidTransition {
+        project_id: String,
+        from: ProjectState,
+        to: ProjectState,
+    },
+    #[error("Could not load or save the project: {0}")]
+    ProjectStore(
+        #[source]
+        #[from]
+        ProjectStoreError,
+    ),
+}
+
+/// A project entered ```state```, see ```ProjectManager::subscribe```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectStateChange {
+    pub project_id: String,
+    /// ```None``` for a new project.
+    pub previous: Option<ProjectState>,
+    pub state: ProjectState,
+}
+
+/// The states of the projects of a ```LocalProjectManager```, loaded from its ```ProjectStore``` on first use.
+#[derive(Debug, Clone)]
+pub(crate) struct ProjectStates {
+    states: Arc<RwLock<HashMap</* id */ String, ProjectState>>>,
+    changes: broadcast::Sender<ProjectStateChange>,
+    /// Every change is sent as a ```ProjectEvent``` too.
+    events: ProjectEvents,
+}
+
+impl Default for ProjectStates {
+    fn default() -> Self {
+        Self {
+            states: Arc::default(),
+            changes: broadcast::channel(CHANGES_CHANNEL_CAPACITY).0,
+            events: ProjectEvents::default(),
+        }
+    }
+}
+
+impl ProjectStates {
+    /// Forgets the states, e.g. for a new ```ProjectStore```. The subscribers are kept.
+    pub(crate) fn without_states(&self) -> Self {
+        Self {
+            states: Arc::default(),
+            changes: self.changes.clone(),
+            events: self.events.clone(),
+        }
+    }
+
+    /// The event bus of the manager, e.g. for the events of an installation, that are not a change of state.
+    pub(crate) fn events(&self) -> &ProjectEvents {
+        &self.events
+    }
+
+    /// Receives the changes of ```set``` and ```transition```, in the order they happened.
+    /// Correctness: A receiver that lags behind by more than ```CHANGES_CHANNEL_CAPACITY``` changes misses the oldest ones.
+    /// The states of the projects loaded from the ```ProjectStore``` at startup are not sent.
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<ProjectStateChange> {
+        self.changes.subscribe()
+    }
+
+    /// No subscriber is not an error.
+    fn send_change(&self, project_id: &str, previous: Option<ProjectState>, state: ProjectState) {
+        let state_change = ProjectStateChange {
+            project_id: project_id.to_owned(),
+            previous,
+            state,
+        };
+        self.events.send_state_change(&state_change);
+        let _ = self.changes.send(state_change);
+    }
+
+    /// ```None``` if the project does not exist.
+    pub(crate) async fn get(
+        &self,
+        project_store: &dyn ProjectStore,
+        project_id: &str,
+    ) -> Result<Option<ProjectState>, ProjectStoreError> {
+        if let Some(state) = self.states.read().await.get(project_id) {
+            return Ok(Some(*state));
+        }
+
+        Ok(project_store
+            .get(project_id)
+            .await?
+            .map(|project| ProjectState::from(project.status)))
+    }
+
+    /// Pairs every project with its state, e.g. to filter the projects by state.
+    pub(crate) async fn with_states(
+        &self,
+        projects: Vec<StoredProject>,
+    ) -> Vec<(StoredProject, ProjectState)> {
+        let states = self.states.read().await;
+
+        projects
+            .into_iter()
+            .map(|project| {
+                let state = states
+                    .get(&project.id)
+                    .copied()
+                    .unwrap_or_else(|| ProjectState::from(project.status));
+
+                (project, state)
+            })
+            .collect()
+    }
+
+    /// Overrides the state without checking the transition, e.g. for a project that was just saved.
+    pub(crate) async fn set(&self, project_id: String, state: ProjectState) {
+        let mut states = self.states.write().await;
+        let previous = states.insert(project_id.clone(), state);
+
+        self.send_change(&project_id, previous, state);
+    }
+
+    /// Returns the previous state. ```requirements_hash``` is saved with the status, see ```ProjectStore::set_status```.
+    /// Correctness: Transitions are serialized, two callers can not leave the same state.
+    /// The state is not changed if its status could not be saved.
+    pub(crate) async fn transition(
+        &self,
+        project_store: &dyn ProjectStore,
+        project_id: &str,
+        next: ProjectState,
+        requirements_hash: Option<&str>,
+    ) -> Result<ProjectState, ProjectTransitionError> {
+        let mut states = self.states.write().await;
+
+        let previous = match states.get(project_id) {
+            Some(state) => *state,
+            None => project_store
+                .get(project_id)
+                .await?
+                .map(|project| ProjectState::from(project.status))
+                .ok_or_else(|| {
+                    ProjectTransitionError::ProjectDoesNotExist(project_id.to_owned())
+                })?,
+        };
+
+        if !previous.can_transition_to(next) {
+            return Err(ProjectTransitionError::InvalidTransition {
+                project_id: project_id.to_owned(),
+                from: previous,
+                to: next,
+            });
+        }
+
+        if let Some(status) = next.status() {
+            project_store
+                .set_status(project_id, status, requirements_hash)
+                .await?;
+        }
+
+        states.insert(project_id.to_owned(), next);
+        self.send_change(project_id, Some(previous), next);
+
+        tracing::debug!(project_id, ?previous, ?next, "Project transitioned");
+
+        Ok(previous)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_managers::local::{project_store::InMemoryProjectStore, TenantId};
+    use std::{collections::BTreeSet, path::PathBuf, time::SystemTime};
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn run_uploaded_project_and_expect_invalid_transition() {
+        let project_store = InMemoryProjectStore::default();
+        let now = SystemTime::now();
+        project_store
+            .insert(&StoredProject {
+                id: String::from("project"),
+                name: String::from("project"),
+                source_dir: PathBuf::from("project"),
+                status: ProjectStatus::Uploaded,
+                created_at: now,
+                updated_at: now,
+                requirements_hash: None,
+                tags: BTreeSet::new(),
+                tenant_id: TenantId::default(),
+                description: None,
+                owner: None,
+            })
+            .await
+            .expect("Error inserting project.");
+        let project_states = ProjectStates::default();
+
+        let run_result = project_states
+            .transition(&project_store, "project", ProjectState::Running, None)
+            .await;
+        for next in [
+            ProjectState::QueuedForInstall,
+            ProjectState::Checking,
+            ProjectState::Installing,
+        ] {
+            project_states
+                .transition(&project_store, "project", next, None)
+                .await
+                .expect("Error transitioning project.");
+        }
+        project_states
+            .transition(
+                &project_store,
+                "project",
+                ProjectState::Installed,
+                Some("hash"),
+            )
+            .await
+            .expect("Error transitioning project.");
+        let stored_project = project_store
+            .get("project")
+            .await
+            .expect("Error getting project.")
+            .expect("Project does not exist.");
+
+        assert!(matches!(
+            run_result,
+            Err(ProjectTransitionError::InvalidTransition {
+                from: ProjectState::Uploaded,
+                to: ProjectState::Running,
+                ..
+            })
+        ));
+        assert_eq!(stored_project.status, ProjectStatus::Installed);
+        assert_eq!(stored_project.requirements_hash.as_deref(), Some("hash"));
+        assert!(ProjectState::Installed.can_transition_to(ProjectState::Running));
+        assert!(!ProjectState::Installed.can_transition_to(ProjectState::Deleted));
+    }
+}
@@ -0,0 +1,1105 @@
+use super::{
+    local_project_runner::RunOutcome,
+    tenant::{InvalidTenantId, TenantId},
+};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions, SqliteRow},
+    Row,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+    io::{Error as IoError, ErrorKind},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use thiserror::Error as ThisError;
+use tokio::{
+    fs,
+    sync::{Mutex, RwLock},
+};
+
+const SQLITE_FILE_NAME: &str = "projects.sqlite";
+const JSON_FILE_NAME: &str = "projects.json";
+const RUNS_JSON_FILE_NAME: &str = "runs.json";
+
+/// Where a project is, from the view of ```LocalProjectManager```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProjectStatus {
+    /// Checked and saved, not installed yet.
+    Uploaded,
+    Installing,
+    Installed,
+    /// The last installation failed, see ```LocalProjectManager::load_install_report```.
+    Failed,
+}
+
+impl ProjectStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Uploaded => "uploaded",
+            Self::Installing => "installing",
+            Self::Installed => "installed",
+            Self::Failed => "failed",
+        }
+    }
+
+    fn parse(status: &str) -> Option<Self> {
+        match status {
+            "uploaded" => Some(Self::Uploaded),
+            "installing" => Some(Self::Installing),
+            "installed" => Some(Self::Installed),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// A project saved by ```LocalProjectManager::add_new_project_to_database```.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredProject {
+    pub id: String,
+    pub name: String,
+    /// The dir the project is installed from, the manager has no control over it.
+    pub source_dir: PathBuf,
+    pub status: ProjectStatus,
+    pub created_at: SystemTime,
+    pub updated_at: SystemTime,
+    /// The requirements hash of the last successful installation, see ```LocalProjectInstaller::check_and_install```.
+    pub requirements_hash: Option<String>,
+    /// Labels to filter the projects by, see ```LocalProjectManager::list_projects```.
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    /// The projects saved before there were tenants belong to the default tenant.
+    #[serde(default)]
+    pub tenant_id: TenantId,
+    /// Free-form, e.g. what the project tests.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Free-form, e.g. the team or the person responsible for the project, see ```ProjectFilter::owner```.
+    #[serde(default)]
+    pub owner: Option<String>,
+}
+
+/// A finished run of a project, see ```LocalProjectManager::list_runs```.
+/// The stats of the run are in its ```RunReport```.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredRun {
+    pub id: String,
+    pub project_id: String,
+    pub script_id: String,
+    pub started_at: SystemTime,
+    pub finished_at: SystemTime,
+    /// ```None``` if the run failed.
+    pub outcome: Option<RunOutcome>,
+    pub request_count: u64,
+    pub failure_count: u64,
+}
+
+#[derive(ThisError, Debug)]
+pub enum ProjectStoreError {
+    #[error("Database error: {0}")]
+    Database(
+        #[source]
+        #[from]
+        sqlx::Error,
+    ),
+    #[error("Project already exists: {0}")]
+    ProjectAlreadyExists(String),
+    #[error("Project does not exist: {0}")]
+    ProjectDoesNotExist(String),
+    #[error("Run already exists: {0}")]
+    RunAlreadyExists(String),
+    #[error("Invalid project status in the database: {0}")]
+    InvalidStatus(String),
+    #[error("Invalid run outcome in the database: {0}")]
+    InvalidOutcome(String),
+    #[error("Invalid project tags in the database: {0}")]
+    InvalidTags(#[source] serde_json::Error),
+    #[error("Invalid tenant id in the database: {0}")]
+    InvalidTenantId(
+        #[source]
+        #[from]
+        InvalidTenantId,
+    ),
+    #[error("Could not read the projects file: {0}")]
+    CouldNotReadFile(#[source] IoError),
+    #[error("Could not parse the projects file: {0}")]
+    CouldNotParseFile(#[source] serde_json::Error),
+    #[error("Could not write the projects file: {0}")]
+    CouldNotWriteFile(#[source] IoError),
+}
+
+/// Persists the projects of a ```LocalProjectManager```, so they survive restarts, see ```ProjectStoreBackend```.
+#[async_trait]
+pub trait ProjectStore: Debug + Send + Sync {
+    /// Fails with ```ProjectStoreError::ProjectAlreadyExists``` if a project with the same id exists.
+    async fn insert(&self, project: &StoredProject) -> Result<(), ProjectStoreError>;
+
+    async fn get(&self, project_id: &str) -> Result<Option<StoredProject>, ProjectStoreError>;
+
+    /// Ordered by creation time, the oldest project first.
+    async fn list(&self) -> Result<Vec<StoredProject>, ProjectStoreError>;
+
+    /// Sets ```updated_at``` to now. ```requirements_hash``` replaces the stored one, if it is ```Some```.
+    async fn set_status(
+        &self,
+        project_id: &str,
+        status: ProjectStatus,
+        requirements_hash: Option<&str>,
+    ) -> Result<(), ProjectStoreError>;
+
+    /// Replaces the tags of the project. Sets ```updated_at``` to now.
+    async fn set_tags(
+        &self,
+        project_id: &str,
+        tags: &BTreeSet<String>,
+    ) -> Result<(), ProjectStoreError>;
+
+    /// Replaces the description and the owner of the project. Sets ```updated_at``` to now.
+    async fn set_details(
+        &self,
+        project_id: &str,
+        description: Option<&str>,
+        owner: Option<&str>,
+    ) -> Result<(), ProjectStoreError>;
+
+    /// Returns whether the project existed. The runs of the project are removed too.
+    async fn remove(&self, project_id: &str) -> Result<bool, ProjectStoreError>;
+
+    /// Fails with ```ProjectStoreError::RunAlreadyExists``` if a run with the same id exists.
+    async fn insert_run(&self, run: &StoredRun) -> Result<(), ProjectStoreError>;
+
+    async fn get_run(&self, run_id: &str) -> Result<Option<StoredRun>, ProjectStoreError>;
+
+    /// Ordered by start time, the oldest run first.
+    async fn list_runs(&self, project_id: &str) -> Result<Vec<StoredRun>, ProjectStoreError>;
+}
+
+/// Stores the projects in a SQLite database, e.g. ```<root_dir>/projects.sqlite```. The table is created on connect.
+/// Correctness: Timestamps are stored as milliseconds since the unix epoch, earlier ones are stored as the epoch.
+/// Tags are stored as a JSON array, the column is added to databases created without it, like the tenant id column.
+#[derive(Debug, Clone)]
+pub struct SqliteProjectStore {
+    pool: SqlitePool,
+}
+
+impl SqliteProjectStore {
+    /// Creates the database file, if it does not exist.
+    pub async fn connect(database_path: &Path) -> Result<Self, ProjectStoreError> {
+        let connect_options = SqliteConnectOptions::new()
+            .filename(database_path)
+            .create_if_missing(true);
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(4)
+            .connect_with(connect_options)
+            .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS projects (
+                id TEXT PRIMARY KEY NOT NULL,
+                name TEXT NOT NULL,
+                source_dir TEXT NOT NULL,
+                status TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                requirements_hash TEXT,
+                tags TEXT NOT NULL DEFAULT '[]',
+                tenant_id TEXT NOT NULL DEFAULT 'default',
+                description TEXT,
+                owner TEXT
+            )",
+        )
+        .execute(&pool)
+        .await?;
+
+        let has_tags_column: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('projects') WHERE name = 'tags'",
+        )
+        .fetch_one(&pool)
+        .await?;
+        if !has_tags_column {
+            sqlx::query("ALTER TABLE projects ADD COLUMN tags TEXT NOT NULL DEFAULT '[]'")
+                .execute(&pool)
+                .await?;
+        }
+
+        let has_tenant_id_column: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('projects') WHERE name = 'tenant_id'",
+        )
+        .fetch_one(&pool)
+        .await?;
+        if !has_tenant_id_column {
+            sqlx::query(
+                "ALTER TABLE projects ADD COLUMN tenant_id TEXT NOT NULL DEFAULT 'default'",
+            )
+            .execute(&pool)
+            .await?;
+        }
+
+        for column in ["description", "owner"] {
+            let has_column: bool = sqlx::query_scalar(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('projects') WHERE name = ?",
+            )
+            .bind(column)
+            .fetch_one(&pool)
+            .await?;
+            if !has_column {
+                sqlx::query(&format!("ALTER TABLE projects ADD COLUMN {column} TEXT"))
+                    .execute(&pool)
+                    .await?;
+            }
+        }
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS runs (
+                id TEXT PRIMARY KEY NOT NULL,
+                project_id TEXT NOT NULL,
+                script_id TEXT NOT NULL,
+                started_at INTEGER NOT NULL,
+                finished_at INTEGER NOT NULL,
+                outcome TEXT,
+                request_count INTEGER NOT NULL,
+                failure_count INTEGER NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS runs_project_id ON runs (project_id)")
+            .execute(&pool)
+            .await?;
+
+        Ok(Self { pool })
+    }
+
+    fn tags_to_json(tags: &BTreeSet<String>) -> Result<String, ProjectStoreError> {
+        serde_json::to_string(tags).map_err(ProjectStoreError::InvalidTags)
+    }
+
+    fn to_millis(time: SystemTime) -> i64 {
+        time.duration_since(SystemTime::UNIX_EPOCH)
+            .map(|duration| i64::try_from(duration.as_millis()).unwrap_or(i64::MAX))
+            .unwrap_or(0)
+    }
+
+    fn from_millis(millis: i64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_millis(u64::try_from(millis).unwrap_or(0))
+    }
+
+    fn stored_project_from_row(row: &SqliteRow) -> Result<StoredProject, ProjectStoreError> {
+        let status: String = row.try_get("status")?;
+        let tags: String = row.try_get("tags")?;
+
+        Ok(StoredProject {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            source_dir: PathBuf::from(row.try_get::<String, _>("source_dir")?),
+            status: ProjectStatus::parse(&status)
+                .ok_or(ProjectStoreError::InvalidStatus(status))?,
+            created_at: Self::from_millis(row.try_get("created_at")?),
+            updated_at: Self::from_millis(row.try_get("updated_at")?),
+            requirements_hash: row.try_get("requirements_hash")?,
+            tags: serde_json::from_str(&tags).map_err(ProjectStoreError::InvalidTags)?,
+            tenant_id: TenantId::new(row.try_get::<String, _>("tenant_id")?)?,
+            description: row.try_get("description")?,
+            owner: row.try_get("owner")?,
+        })
+    }
+
+    fn stored_run_from_row(row: &SqliteRow) -> Result<StoredRun, ProjectStoreError> {
+        let outcome: Option<String> = row.try_get("outcome")?;
+
+        Ok(StoredRun {
+            id: row.try_get("id")?,
+            project_id: row.try_get("project_id")?,
+            script_id: row.try_get("script_id")?,
+            started_at: Self::from_millis(row.try_get("started_at")?),
+            finished_at: Self::from_millis(row.try_get("finished_at")?),
+            outcome: outcome
+                .map(|outcome| {
+                    RunOutcome::parse(&outcome).ok_or(ProjectStoreError::InvalidOutcome(outcome))
+                })
+                .transpose()?,
+            request_count: u64::try_from(row.try_get::<i64, _>("request_count")?).unwrap_or(0),
+            failure_count: u64::try_from(row.try_get::<i64, _>("failure_count")?).unwrap_or(0),
+        })
+    }
+}
+
+#[async_trait]
+impl ProjectStore for SqliteProjectStore {
+    async fn insert(&self, project: &StoredProject) -> Result<(), ProjectStoreError> {
+        let insert_result = sqlx::query(
+            "INSERT INTO projects (id, name, source_dir, status, created_at, updated_at, requirements_hash, tags, tenant_id, description, owner)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&project.id)
+        .bind(&project.name)
+        .bind(project.source_dir.to_string_lossy())
+        .bind(project.status.as_str())
+        .bind(Self::to_millis(project.created_at))
+        .bind(Self::to_millis(project.updated_at))
+        .bind(&project.requirements_hash)
+        .bind(Self::tags_to_json(&project.tags)?)
+        .bind(project.tenant_id.as_str())
+        .bind(&project.description)
+        .bind(&project.owner)
+        .execute(&self.pool)
+        .await;
+
+        match insert_result {
+            Ok(_) => Ok(()),
+            Err(sqlx::Error::Database(error)) if error.is_unique_violation() => {
+                Err(ProjectStoreError::ProjectAlreadyExists(project.id.clone()))
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    async fn get(&self, project_id: &str) -> Result<Option<StoredProject>, ProjectStoreError> {
+        sqlx::query("SELECT * FROM projects WHERE id = ?")
+            .bind(project_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .as_ref()
+            .map(Self::stored_project_from_row)
+            .transpose()
+    }
+
+    async fn list(&self) -> Result<Vec<StoredProject>, ProjectStoreError> {
+        sqlx::query("SELECT * FROM projects ORDER BY created_at, id")
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(Self::stored_project_from_row)
+            .collect()
+    }
+
+    async fn set_status(
+        &self,
+        project_id: &str,
+        status: ProjectStatus,
+        requirements_hash: Option<&str>,
+    ) -> Result<(), ProjectStoreError> {
+        let update_result = sqlx::query(
+            "UPDATE projects
+            SET status = ?, updated_at = ?, requirements_hash = COALESCE(?, requirements_hash)
+            WHERE id = ?",
+        )
+        .bind(status.as_str())
+        .bind(Self::to_millis(SystemTime::now()))
+        .bind(requirements_hash)
+        .bind(project_id)
+        .execute(&self.pool)
+        .await?;
+
+        if update_result.rows_affected() == 0 {
+            return Err(ProjectStoreError::ProjectDoesNotExist(
+                project_id.to_owned(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn set_tags(
+        &self,
+        project_id: &str,
+        tags: &BTreeSet<String>,
+    ) -> Result<(), ProjectStoreError> {
+        let update_result =
+            sqlx::query("UPDATE projects SET tags = ?, updated_at = ? WHERE id = ?")
+                .bind(Self::tags_to_json(tags)?)
+                .bind(Self::to_millis(SystemTime::now()))
+                .bind(project_id)
+                .execute(&self.pool)
+                .await?;
+
+        if update_result.rows_affected() == 0 {
+            return Err(ProjectStoreError::ProjectDoesNotExist(
+                project_id.to_owned(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn set_details(
+        &self,
+        project_id: &str,
+        description: Option<&str>,
+        owner: Option<&str>,
+    ) -> Result<(), ProjectStoreError> {
+        let update_result = sqlx::query(
+            "UPDATE projects SET description = ?, owner = ?, updated_at = ? WHERE id = ?",
+        )
+        .bind(description)
+        .bind(owner)
+        .bind(Self::to_millis(SystemTime::now()))
+        .bind(project_id)
+        .execute(&self.pool)
+        .await?;
+
+        if update_result.rows_affected() == 0 {
+            return Err(ProjectStoreError::ProjectDoesNotExist(
+                project_id.to_owned(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn remove(&self, project_id: &str) -> Result<bool, ProjectStoreError> {
+        let mut transaction = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM runs WHERE project_id = ?")
+            .bind(project_id)
+            .execute(&mut *transaction)
+            .await?;
+
+        let delete_result = sqlx::query("DELETE FROM projects WHERE id = ?")
+            .bind(project_id)
+            .execute(&mut *transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        Ok(delete_result.rows_affected() > 0)
+    }
+
+    async fn insert_run(&self, run: &StoredRun) -> Result<(), ProjectStoreError> {
+        let insert_result = sqlx::query(
+            "INSERT INTO runs (id, project_id, script_id, started_at, finished_at, outcome, request_count, failure_count)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&run.id)
+        .bind(&run.project_id)
+        .bind(&run.script_id)
+        .bind(Self::to_millis(run.started_at))
+        .bind(Self::to_millis(run.finished_at))
+        .bind(run.outcome.map(RunOutcome::as_str))
+        .bind(i64::try_from(run.request_count).unwrap_or(i64::MAX))
+        .bind(i64::try_from(run.failure_count).unwrap_or(i64::MAX))
+        .execute(&self.pool)
+        .await;
+
+        match insert_result {
+            Ok(_) => Ok(()),
+            Err(sqlx::Error::Database(error)) if error.is_unique_violation() => {
+                Err(ProjectStoreError::RunAlreadyExists(run.id.clone()))
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    async fn get_run(&self, run_id: &str) -> Result<Option<StoredRun>, ProjectStoreError> {
+        sqlx::query("SELECT * FROM runs WHERE id = ?")
+            .bind(run_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .as_ref()
+            .map(Self::stored_run_from_row)
+            .transpose()
+    }
+
+    async fn list_runs(&self, project_id: &str) -> Result<Vec<StoredRun>, ProjectStoreError> {
+        sqlx::query("SELECT * FROM runs WHERE project_id = ? ORDER BY started_at, id")
+            .bind(project_id)
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(Self::stored_run_from_row)
+            .collect()
+    }
+}
+
+/// Where ```LocalProjectManager``` saves the projects, selected when it is created.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProjectStoreBackend {
+    /// ```projects.sqlite``` in the root dir, see ```SqliteProjectStore```.
+    #[default]
+    Sqlite,
+    /// ```projects.json``` and ```runs.json``` in the root dir, for small deployments, see ```JsonFileProjectStore```.
+    JsonFile,
+    /// The projects are lost on restart, e.g. for tests.
+    InMemory,
+}
+
+impl ProjectStoreBackend {
+    pub(super) async fn open(
+        self,
+        root_dir: &Path,
+    ) -> Result<Arc<dyn ProjectStore>, ProjectStoreError> {
+        Ok(match self {
+            Self::Sqlite => {
+                Arc::new(SqliteProjectStore::connect(&root_dir.join(SQLITE_FILE_NAME)).await?)
+            }
+            Self::JsonFile => Arc::new(JsonFileProjectStore::new(root_dir.join(JSON_FILE_NAME))),
+            Self::InMemory => Arc::new(InMemoryProjectStore::default()),
+        })
+    }
+}
+
+/// The projects of the in-memory and the JSON file store, by id.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+struct Projects(BTreeMap<String, StoredProject>);
+
+impl Projects {
+    fn insert(&mut self, project: &StoredProject) -> Result<(), ProjectStoreError> {
+        if self.0.contains_key(&project.id) {
+            return Err(ProjectStoreError::ProjectAlreadyExists(project.id.clone()));
+        }
+
+        self.0.insert(project.id.clone(), project.clone());
+
+        Ok(())
+    }
+
+    fn list(&self) -> Vec<StoredProject> {
+        let mut projects: Vec<_> = self.0.values().cloned().collect();
+        // Sorted by id already, the sort is stable.
+        projects.sort_by_key(|project| project.created_at);
+
+        projects
+    }
+
+    fn set_status(
+        &mut self,
+        project_id: &str,
+        status: ProjectStatus,
+        requirements_hash: Option<&str>,
+    ) -> Result<(), ProjectStoreError> {
+        let project = self
+            .0
+            .get_mut(project_id)
+            .ok_or_else(|| ProjectStoreError::ProjectDoesNotExist(project_id.to_owned()))?;
+
+        project.status = status;
+        project.updated_at = SystemTime::now();
+        if let Some(requirements_hash) = requirements_hash {
+            project.requirements_hash = Some(requirements_hash.to_owned());
+        }
+
+        Ok(())
+    }
+
+    fn set_tags(
+        &mut self,
+        project_id: &str,
+        tags: &BTreeSet<String>,
+    ) -> Result<(), ProjectStoreError> {
+        let project = self
+            .0
+            .get_mut(project_id)
+            .ok_or_else(|| ProjectStoreError::ProjectDoesNotExist(project_id.to_owned()))?;
+
+        project.tags = tags.clone();
+        project.updated_at = SystemTime::now();
+
+        Ok(())
+    }
+
+    fn set_details(
+        &mut self,
+        project_id: &str,
+        description: Option<&str>,
+        owner: Option<&str>,
+    ) -> Result<(), ProjectStoreError> {
+        let project = self
+            .0
+            .get_mut(project_id)
+            .ok_or_else(|| ProjectStoreError::ProjectDoesNotExist(project_id.to_owned()))?;
+
+        project.description = description.map(ToOwned::to_owned);
+        project.owner = owner.map(ToOwned::to_owned);
+        project.updated_at = SystemTime::now();
+
+        Ok(())
+    }
+}
+
+/// The runs of the in-memory and the JSON file store, by id.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+struct Runs(BTreeMap<String, StoredRun>);
+
+impl Runs {
+    fn insert(&mut self, run: &StoredRun) -> Result<(), ProjectStoreError> {
+        if self.0.contains_key(&run.id) {
+            return Err(ProjectStoreError::RunAlreadyExists(run.id.clone()));
+        }
+
+        self.0.insert(run.id.clone(), run.clone());
+
+        Ok(())
+    }
+
+    fn list(&self, project_id: &str) -> Vec<StoredRun> {
+        let mut runs: Vec<_> = self
+            .0
+            .values()
+            .filter(|run| run.project_id == project_id)
+            .cloned()
+            .collect();
+        // Sorted by id already, the sort is stable.
+        runs.sort_by_key(|run| run.started_at);
+
+        runs
+    }
+
+    /// Returns whether the project had runs.
+    fn remove_project(&mut self, project_id: &str) -> bool {
+        let run_count = self.0.len();
+        self.0.retain(|_, run| run.project_id != project_id);
+
+        self.0.len() != run_count
+    }
+}
+
+/// Keeps the projects in memory, they are lost on restart.
+#[derive(Debug, Default)]
+pub struct InMemoryProjectStore {
+    projects: RwLock<Projects>,
+    runs: RwLock<Runs>,
+}
+
+#[async_trait]
+impl ProjectStore for InMemoryProjectStore {
+    async fn insert(&self, project: &StoredProject) -> Result<(), ProjectStoreError> {
+        self.projects.write().await.insert(project)
+    }
+
+    async fn get(&self, project_id: &str) -> Result<Option<StoredProject>, ProjectStoreError> {
+        Ok(self.projects.read().await.0.get(project_id).cloned())
+    }
+
+    async fn list(&self) -> Result<Vec<StoredProject>, ProjectStoreError> {
+        Ok(self.projects.read().await.list())
+    }
+
+    async fn set_status(
+        &self,
+        project_id: &str,
+        status: ProjectStatus,
+        requirements_hash: Option<&str>,
+    ) -> Result<(), ProjectStoreError> {
+        self.projects
+            .write()
+            .await
+            .set_status(project_id, status, requirements_hash)
+    }
+
+    async fn set_tags(
+        &self,
+        project_id: &str,
+        tags: &BTreeSet<String>,
+    ) -> Result<(), ProjectStoreError> {
+        self.projects.write().await.set_tags(project_id, tags)
+    }
+
+    async fn set_details(
+        &self,
+        project_id: &str,
+        description: Option<&str>,
+        owner: Option<&str>,
+    ) -> Result<(), ProjectStoreError> {
+        self.projects
+            .write()
+            .await
+            .set_details(project_id, description, owner)
+    }
+
+    async fn remove(&self, project_id: &str) -> Result<bool, ProjectStoreError> {
+        self.runs.write().await.remove_project(project_id);
+
+        Ok(self.projects.write().await.0.remove(project_id).is_some())
+    }
+
+    async fn insert_run(&self, run: &StoredRun) -> Result<(), ProjectStoreError> {
+        self.runs.write().await.insert(run)
+    }
+
+    async fn get_run(&self, run_id: &str) -> Result<Option<StoredRun>, ProjectStoreError> {
+        Ok(self.runs.read().await.0.get(run_id).cloned())
+    }
+
+    async fn list_runs(&self, project_id: &str) -> Result<Vec<StoredRun>, ProjectStoreError> {
+        Ok(self.runs.read().await.list(project_id))
+    }
+}
+
+/// Keeps the projects in a JSON file, that is read and rewritten as a whole on every change.
+/// The runs are kept in ```runs.json``` next to it. The files are created on the first change.
+/// Correctness: A file is replaced with a rename, a crash while writing leaves the previous one.
+/// Writes are synchronized between the tasks of a process, not between processes.
+#[derive(Debug)]
+pub struct JsonFileProjectStore {
+    file_path: PathBuf,
+    runs_file_path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl JsonFileProjectStore {
+    pub fn new(file_path: PathBuf) -> Self {
+        Self {
+            runs_file_path: file_path.with_file_name(RUNS_JSON_FILE_NAME),
+            file_path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    async fn read<T: Default + DeserializeOwned>(file_path: &Path) -> Result<T, ProjectStoreError> {
+        let content = match fs::read(file_path).await {
+            Ok(content) => content,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(T::default()),
+            Err(error) => return Err(ProjectStoreError::CouldNotReadFile(error)),
+        };
+
+        serde_json::from_slice(&content).map_err(ProjectStoreError::CouldNotParseFile)
+    }
+
+    async fn write<T: Serialize>(file_path: &Path, value: &T) -> Result<(), ProjectStoreError> {
+        let content =
+            serde_json::to_vec_pretty(value).map_err(ProjectStoreError::CouldNotParseFile)?;
+
+        let mut temp_file_name = file_path.file_name().unwrap_or_default().to_os_string();
+        temp_file_name.push(".tmp");
+        let temp_file_path = file_path.with_file_name(temp_file_name);
+
+        fs::write(&temp_file_path, content)
+            .await
+            .map_err(ProjectStoreError::CouldNotWriteFile)?;
+
+        fs::rename(&temp_file_path, file_path)
+            .await
+            .map_err(ProjectStoreError::CouldNotWriteFile)
+    }
+
+    async fn read_projects(&self) -> Result<Projects, ProjectStoreError> {
+        Self::read(&self.file_path).await
+    }
+
+    async fn read_runs(&self) -> Result<Runs, ProjectStoreError> {
+        Self::read(&self.runs_file_path).await
+    }
+}
+
+#[async_trait]
+impl ProjectStore for JsonFileProjectStore {
+    async fn insert(&self, project: &StoredProject) -> Result<(), ProjectStoreError> {
+        let _guard = self.lock.lock().await;
+
+        let mut projects = self.read_projects().await?;
+        projects.insert(project)?;
+        Self::write(&self.file_path, &projects).await
+    }
+
+    async fn get(&self, project_id: &str) -> Result<Option<StoredProject>, ProjectStoreError> {
+        let _guard = self.lock.lock().await;
+
+        Ok(self.read_projects().await?.0.remove(project_id))
+    }
+
+    async fn list(&self) -> Result<Vec<StoredProject>, ProjectStoreError> {
+        let _guard = self.lock.lock().await;
+
+        Ok(self.read_projects().await?.list())
+    }
+
+    async fn set_status(
+        &self,
+        project_id: &str,
+        status: ProjectStatus,
+        requirements_hash: Option<&str>,
+    ) -> Result<(), ProjectStoreError> {
+        let _guard = self.lock.lock().await;
+
+        let mut projects = self.read_projects().await?;
+        projects.set_status(project_id, status, requirements_hash)?;
+        Self::write(&self.file_path, &projects).await
+    }
+
+    async fn set_tags(
+        &self,
+        project_id: &str,
+        tags: &BTreeSet<String>,
+    ) -> Result<(), ProjectStoreError> {
+        let _guard = self.lock.lock().await;
+
+        let mut projects = self.read_projects().await?;
+        projects.set_tags(project_id, tags)?;
+        Self::write(&self.file_path, &projects).await
+    }
+
+    async fn set_details(
+        &self,
+        project_id: &str,
+        description: Option<&str>,
+        owner: Option<&str>,
+    ) -> Result<(), ProjectStoreError> {
+        let _guard = self.lock.lock().await;
+
+        let mut projects = self.read_projects().await?;
+        projects.set_details(project_id, description, owner)?;
+        Self::write(&self.file_path, &projects).await
+    }
+
+    async fn remove(&self, project_id: &str) -> Result<bool, ProjectStoreError> {
+        let _guard = self.lock.lock().await;
+
+        let mut runs = self.read_runs().await?;
+        if runs.remove_project(project_id) {
+            Self::write(&self.runs_file_path, &runs).await?;
+        }
+
+        let mut projects = self.read_projects().await?;
+        if projects.0.remove(project_id).is_none() {
+            return Ok(false);
+        }
+
+        Self::write(&self.file_path, &projects).await?;
+
+        Ok(true)
+    }
+
+    async fn insert_run(&self, run: &StoredRun) -> Result<(), ProjectStoreError> {
+        let _guard = self.lock.lock().await;
+
+        let mut runs = self.read_runs().await?;
+        runs.insert(run)?;
+        Self::write(&self.runs_file_path, &runs).await
+    }
+
+    async fn get_run(&self, run_id: &str) -> Result<Option<StoredRun>, ProjectStoreError> {
+        let _guard = self.lock.lock().await;
+
+        Ok(self.read_runs().await?.0.remove(run_id))
+    }
+
+    async fn list_runs(&self, project_id: &str) -> Result<Vec<StoredRun>, ProjectStoreError> {
+        let _guard = self.lock.lock().await;
+
+        Ok(self.read_runs().await?.list(project_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_dir;
+    use tracing_test::traced_test;
+
+    fn run(id: &str, project_id: &str, started_at: SystemTime) -> StoredRun {
+        StoredRun {
+            id: id.to_owned(),
+            project_id: project_id.to_owned(),
+            script_id: String::from("main.py"),
+            started_at,
+            finished_at: started_at + Duration::from_secs(60),
+            outcome: Some(RunOutcome::CompletedWithFailures),
+            request_count: 100,
+            failure_count: 5,
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn insert_project_and_expect_it_after_reconnect() {
+        let temp_dir = test_dir("project_store");
+        let test_dir = temp_dir.path();
+        let database_path = test_dir.join("projects.sqlite");
+        let created_at = SystemTime::UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+        let project = StoredProject {
+            id: String::from("project"),
+            name: String::from("Project"),
+            source_dir: test_dir.join("uploaded_projects").join("project"),
+            status: ProjectStatus::Uploaded,
+            created_at,
+            updated_at: created_at,
+            requirements_hash: None,
+            tags: BTreeSet::new(),
+            tenant_id: TenantId::new("tenant").expect("Error creating tenant id."),
+            description: Some(String::from("Checkout flow")),
+            owner: None,
+        };
+
+        let project_store = SqliteProjectStore::connect(&database_path)
+            .await
+            .expect("Error connecting.");
+        project_store
+            .insert(&project)
+            .await
+            .expect("Error inserting project.");
+        let insert_again_result = project_store.insert(&project).await;
+        project_store
+            .set_status("project", ProjectStatus::Installed, Some("hash"))
+            .await
+            .expect("Error setting status.");
+        project_store
+            .set_tags("project", &BTreeSet::from([String::from("smoke")]))
+            .await
+            .expect("Error setting tags.");
+        project_store
+            .set_details("project", None, Some("checkout-team"))
+            .await
+            .expect("Error setting details.");
+        let set_missing_status_result = project_store
+            .set_status("missing", ProjectStatus::Installed, None)
+            .await;
+        project_store
+            .insert_run(&run("run", "project", created_at))
+            .await
+            .expect("Error inserting run.");
+        project_store
+            .insert_run(&StoredRun {
+                outcome: None,
+                ..run("failed_run", "project", created_at + Duration::from_secs(1))
+            })
+            .await
+            .expect("Error inserting run.");
+        let insert_run_again_result = project_store
+            .insert_run(&run("run", "project", created_at))
+            .await;
+        project_store.pool.close().await;
+
+        let reconnected_project_store = SqliteProjectStore::connect(&database_path)
+            .await
+            .expect("Error reconnecting.");
+        let projects = reconnected_project_store.list().await;
+        let runs = reconnected_project_store.list_runs("project").await;
+        let removed = reconnected_project_store.remove("project").await;
+        let project_after_remove = reconnected_project_store.get("project").await;
+        let run_after_remove = reconnected_project_store.get_run("run").await;
+        reconnected_project_store.pool.close().await;
+
+        assert!(matches!(
+            insert_again_result,
+            Err(ProjectStoreError::ProjectAlreadyExists(_))
+        ));
+        assert!(matches!(
+            set_missing_status_result,
+            Err(ProjectStoreError::ProjectDoesNotExist(_))
+        ));
+        let projects = projects.expect("Error listing projects.");
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].status, ProjectStatus::Installed);
+        assert_eq!(projects[0].requirements_hash.as_deref(), Some("hash"));
+        assert_eq!(projects[0].tags, BTreeSet::from([String::from("smoke")]));
+        assert_eq!(projects[0].description, None);
+        assert_eq!(projects[0].owner.as_deref(), Some("checkout-team"));
+        assert_eq!(projects[0].created_at, created_at);
+        assert!(projects[0].updated_at > created_at);
+        assert!(matches!(
+            insert_run_again_result,
+            Err(ProjectStoreError::RunAlreadyExists(_))
+        ));
+        assert_eq!(
+            runs.expect("Error listing runs."),
+            vec![
+                run("run", "project", created_at),
+                StoredRun {
+                    outcome: None,
+                    ..run("failed_run", "project", created_at + Duration::from_secs(1))
+                }
+            ]
+        );
+        assert!(removed.expect("Error removing project."));
+        assert_eq!(project_after_remove.expect("Error getting project."), None);
+        assert_eq!(run_after_remove.expect("Error getting run."), None);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn use_in_memory_and_json_file_stores_and_expect_same_behavior() {
+        let temp_dir = test_dir("project_store_json");
+        let test_dir = temp_dir.path();
+        let json_file_path = test_dir.join("projects.json");
+        let created_at = SystemTime::UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+        let project = |id: &str, created_at| StoredProject {
+            id: id.to_owned(),
+            name: id.to_owned(),
+            source_dir: test_dir.join("uploaded_projects").join(id),
+            status: ProjectStatus::Uploaded,
+            created_at,
+            updated_at: created_at,
+            requirements_hash: None,
+            tags: BTreeSet::new(),
+            tenant_id: TenantId::default(),
+            description: None,
+            owner: None,
+        };
+
+        let project_stores: [Box<dyn ProjectStore>; 2] = [
+            Box::new(InMemoryProjectStore::default()),
+            Box::new(JsonFileProjectStore::new(json_file_path.clone())),
+        ];
+        for project_store in &project_stores {
+            project_store
+                .insert(&project("second", created_at + Duration::from_secs(1)))
+                .await
+                .expect("Error inserting project.");
+            project_store
+                .insert(&project("first", created_at))
+                .await
+                .expect("Error inserting project.");
+            let insert_again_result = project_store.insert(&project("first", created_at)).await;
+            project_store
+                .set_status("first", ProjectStatus::Installed, Some("hash"))
+                .await
+                .expect("Error setting status.");
+            project_store
+                .set_details("first", Some("Checkout flow"), Some("checkout-team"))
+                .await
+                .expect("Error setting details.");
+            project_store
+                .insert_run(&run("first_run", "first", created_at))
+                .await
+                .expect("Error inserting run.");
+            project_store
+                .insert_run(&run("second_run", "second", created_at))
+                .await
+                .expect("Error inserting run.");
+            let removed = project_store.remove("second").await;
+
+            assert!(matches!(
+                insert_again_result,
+                Err(ProjectStoreError::ProjectAlreadyExists(_))
+            ));
+            assert!(removed.expect("Error removing project."));
+        }
+        let mut first_projects = Vec::new();
+        for project_store in &project_stores {
+            first_projects.push(project_store.get("first").await);
+        }
+        let reopened_project_store = JsonFileProjectStore::new(json_file_path);
+        let reopened_projects = reopened_project_store.list().await;
+        let reopened_first_runs = reopened_project_store.list_runs("first").await;
+        let reopened_second_run = reopened_project_store.get_run("second_run").await;
+
+        for first_project in first_projects {
+            let first_project = first_project
+                .expect("Error getting project.")
+                .expect("Project does not exist.");
+            assert_eq!(first_project.status, ProjectStatus::Installed);
+            assert_eq!(first_project.requirements_hash.as_deref(), Some("hash"));
+        }
+        let reopened_projects = reopened_projects.expect("Error listing projects.");
+        assert_eq!(reopened_projects.len(), 1);
+        assert_eq!(reopened_projects[0].id, "first");
+        assert_eq!(reopened_projects[0].owner.as_deref(), Some("checkout-team"));
+        assert_eq!(
+            reopened_first_runs.expect("Error listing runs."),
+            vec![run("first_run", "first", created_at)]
+        );
+        assert_eq!(reopened_second_run.expect("Error getting run."), None);
+    }
+}
@@ -0,0 +1,168 @@
+use crate::project_managers::process::{self, ProcessRunError, Status, TerminationStatus};
+use std::{
+    ffi::OsStr,
+    fmt::{Display, Formatter, Result as FmtResult},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use thiserror::Error as ThisError;
+use tokio_util::sync::CancellationToken;
+
+/// ```python --version``` prints a single short line.
+const MAX_PYTHON_VERSION_SIZE: usize = 1024;
+
+/// ```python --version``` returns at once, a hanging interpreter is killed.
+const PYTHON_VERSION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The python interpreter that creates the virtual environments, and the versions it may have.
+/// Correctness: The bounds are inclusive and compare the patch version too, e.g. a ```max_version``` of ```3.12.0``` rejects ```3.12.1```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PythonConfig {
+    /// A program name, that is looked up in ```PATH```, or a path, e.g. ```/usr/bin/python3.11```.
+    pub interpreter_path: PathBuf,
+    pub min_version: Option<PythonVersion>,
+    pub max_version: Option<PythonVersion>,
+}
+
+impl Default for PythonConfig {
+    fn default() -> Self {
+        Self {
+            interpreter_path: PathBuf::from("python3"),
+            min_version: None,
+            max_version: None,
+        }
+    }
+}
+
+impl PythonConfig {
+    pub fn supports(&self, version: PythonVersion) -> bool {
+        self.min_version
+            .map_or(true, |min_version| version >= min_version)
+            && self
+                .max_version
+                .map_or(true, |max_version| version <= max_version)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PythonVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl PythonVersion {
+    pub fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parses the output of ```python --version```, e.g. ```Python 3.11.4``` or ```Python 3.13.0rc1```.
+    /// A missing patch version is 0.
+    fn parse(output: &str) -> Option<Self> {
+        let version = output.trim().strip_prefix("Python ")?;
+        let mut components = version.splitn(3, '.');
+
+        let major = components.next()?.parse().ok()?;
+        let minor = components.next()?.parse().ok()?;
+        let patch = match components.next() {
+            Some(patch) => {
+                let digits_end = patch
+                    .find(|character: char| !character.is_ascii_digit())
+                    .unwrap_or(patch.len());
+                patch[..digits_end].parse().ok()?
+            }
+            None => 0,
+        };
+
+        Some(Self::new(major, minor, patch))
+    }
+}
+
+impl Display for PythonVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Runs ```<interpreter_path> --version```.
+/// Python 2 prints its version to stderr, so both streams are used.
+pub(super) async fn probe_version(
+    interpreter_path: &Path,
+    current_dir: &Path,
+    cancellation_token: &CancellationToken,
+) -> Result<PythonVersion, PythonProbeError> {
+    let output = process::capture(
+        interpreter_path.as_os_str(),
+        vec![OsStr::new("--version")],
+        current_dir,
+        Vec::new(),
+        MAX_PYTHON_VERSION_SIZE,
+        PYTHON_VERSION_TIMEOUT,
+        Some(cancellation_token),
+    )
+    .await
+    .map_err(PythonProbeError::CouldNotRunPython)?;
+
+    if !matches!(
+        output.status,
+        Status::Terminated(TerminationStatus::TerminatedSuccessfully)
+    ) {
+        return Err(PythonProbeError::PythonFailed(output.status));
+    }
+
+    let version_output = format!("{}{}", output.stdout, output.stderr);
+    PythonVersion::parse(&version_output).ok_or(PythonProbeError::UnknownVersion(version_output))
+}
+
+#[derive(ThisError, Debug)]
+pub enum PythonProbeError {
+    #[error("Could not run python to get its version: {0}")]
+    CouldNotRunPython(#[source] ProcessRunError),
+    #[error("Python failed to print its version")]
+    PythonFailed(Status),
+    #[error("Could not parse the python version: {0}")]
+    UnknownVersion(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_version_outputs_and_expect_versions() {
+        assert_eq!(
+            PythonVersion::parse("Python 3.11.4\n"),
+            Some(PythonVersion::new(3, 11, 4))
+        );
+        assert_eq!(
+            PythonVersion::parse("Python 3.13.0rc1"),
+            Some(PythonVersion::new(3, 13, 0))
+        );
+        assert_eq!(
+            PythonVersion::parse("Python 2.7"),
+            Some(PythonVersion::new(2, 7, 0))
+        );
+        assert_eq!(
+            PythonVersion::parse("pyenv: python3: command not found"),
+            None
+        );
+    }
+
+    #[test]
+    fn check_versions_against_bounds_and_expect_inclusive_bounds() {
+        let python_config = PythonConfig {
+            min_version: Some(PythonVersion::new(3, 8, 0)),
+            max_version: Some(PythonVersion::new(3, 12, 0)),
+            ..PythonConfig::default()
+        };
+
+        assert!(python_config.supports(PythonVersion::new(3, 8, 0)));
+        assert!(python_config.supports(PythonVersion::new(3, 12, 0)));
+        assert!(!python_config.supports(PythonVersion::new(3, 7, 17)));
+        assert!(!python_config.supports(PythonVersion::new(3, 12, 1)));
+    }
+}
@@ -0,0 +1,116 @@
+use super::{requirements_hash, staging};
+use std::{
+    collections::HashSet,
+    io::{Error as IoError, ErrorKind},
+    path::{Path, PathBuf},
+};
+use tokio::fs;
+
+/// What ```LocalProjectManager::new``` reconciled between its ```ProjectStore``` and the filesystem, see ```LocalProjectManager::recovery_report```.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecoveryReport {
+    /// Checked or installed while the previous manager stopped, now ```InstallFailure::Interrupted```.
+    pub interrupted_installations: Vec<String>,
+    /// Installed, but the environment or the installed project is gone, now ```InstallFailure::Missing```.
+    pub missing_installations: Vec<String>,
+    /// The uploaded project is gone. The projects are kept, see ```LocalProjectManager::delete_project```.
+    pub missing_uploads: Vec<String>,
+    /// Dirs and files that belong to no project, e.g. the environment of a project that was removed from the database.
+    pub removed_orphans: Vec<PathBuf>,
+}
+
+/// The project an entry of the environments or the installed projects dir belongs to.
+fn owner(file_name: &str) -> &str {
+    requirements_hash::env_dir_name_of_file(file_name)
+        .or_else(|| staging::env_dir_name_of_staging_dir(file_name))
+        .unwrap_or(file_name)
+}
+
+/// Removes the entries of ```dir``` that belong to none of ```project_ids```, a missing ```dir``` has no entries.
+/// A symlink to a shared environment is removed, not the environment, see ```EnvStore```.
+/// Correctness: Only called at startup, no installation is writing to ```dir```.
+pub(super) async fn remove_orphans(
+    dir: &Path,
+    project_ids: &HashSet<&str>,
+) -> Result<Vec<PathBuf>, IoError> {
+    let mut entries = match fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error),
+    };
+
+    let mut removed_orphans = Vec::new();
+
+    while let Some(entry) = entries.next_entry().await? {
+        let file_name = entry.file_name();
+
+        if project_ids.contains(owner(&file_name.to_string_lossy())) {
+            continue;
+        }
+
+        let orphan = entry.path();
+
+        if entry.file_type().await?.is_dir() {
+            fs::remove_dir_all(&orphan).await?;
+        } else {
+            fs::remove_file(&orphan).await?;
+        }
+
+        removed_orphans.push(orphan);
+    }
+
+    Ok(removed_orphans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_dir;
+
+    #[tokio::test]
+    async fn remove_orphans_and_expect_entries_of_projects_kept() {
+        let temp_dir = test_dir("recovery");
+        let dir = temp_dir.path();
+        for kept_dir in ["project", "project.staging-1234"] {
+            fs::create_dir_all(dir.join(kept_dir))
+                .await
+                .expect("Error creating dir.");
+        }
+        fs::create_dir_all(dir.join("ghost").join("bin"))
+            .await
+            .expect("Error creating dir.");
+        for file_name in ["project.requirements.sha256", "ghost.requirements.sha256"] {
+            fs::write(dir.join(file_name), "hash")
+                .await
+                .expect("Error writing file.");
+        }
+
+        let mut removed_orphans = remove_orphans(dir, &HashSet::from(["project"]))
+            .await
+            .expect("Error removing orphans.");
+        removed_orphans.sort();
+        let mut remaining = Vec::new();
+        let mut entries = fs::read_dir(&dir).await.expect("Error reading dir.");
+        while let Some(entry) = entries.next_entry().await.expect("Error reading dir.") {
+            remaining.push(entry.file_name().to_string_lossy().into_owned());
+        }
+        remaining.sort();
+        let missing_dir_result = remove_orphans(&dir.join("does_not_exist"), &HashSet::new()).await;
+
+        assert_eq!(
+            removed_orphans,
+            vec![dir.join("ghost"), dir.join("ghost.requirements.sha256")]
+        );
+        assert_eq!(
+            remaining,
+            vec![
+                "project",
+                "project.requirements.sha256",
+                "project.staging-1234"
+            ]
+        );
+        assert!(missing_dir_result
+            .expect("Error removing orphans.")
+            .is_empty());
+    }
+}
@@ -0,0 +1,623 @@
+use super::run_report::{RequestStats, RunReport, RunReportStatus};
+use crate::util::UtcDateTime;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Write,
+    io::Error as IoError,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use tokio::fs;
+
+/// The width of the bars of the percentile chart, the labels are left of it.
+const CHART_WIDTH: f64 = 600.0;
+const CHART_LABEL_WIDTH: f64 = 240.0;
+const CHART_BAR_HEIGHT: f64 = 14.0;
+
+const HTML_STYLE: &str = "body{font-family:sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin-bottom:2em}\
+th,td{border:1px solid #ccc;padding:4px 8px;text-align:left}\
+th{background:#f4f4f4}\
+.passed{color:#2e7d32}.failed{color:#c62828}\
+pre{margin:0;white-space:pre-wrap}";
+
+/// The percentiles of the chart, with their color.
+const CHART_SERIES: [(&str, &str); 3] =
+    [("p50", "#90caf9"), ("p95", "#1e88e5"), ("p99", "#0d47a1")];
+
+/// A rendered ```RunReport```, stored next to it in the run dir, see ```LocalProjectManager::get_run_report_file```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReportFormat {
+    /// A self-contained page, the charts are inline SVG.
+    Html,
+    /// A summary, e.g. for a pull request comment.
+    Markdown,
+}
+
+impl ReportFormat {
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Html => "report.html",
+            Self::Markdown => "report.md",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Html => "text/html; charset=utf-8",
+            Self::Markdown => "text/markdown; charset=utf-8",
+        }
+    }
+}
+
+/// When a run fails its verdict, see ```Verdict```.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportThresholds {
+    /// Failed requests per request, e.g. ```0.01``` for 1%.
+    pub max_failure_ratio: f64,
+    /// In milliseconds, ```None``` does not check the response times.
+    pub max_p95_response_time: Option<f64>,
+}
+
+impl Default for ReportThresholds {
+    fn default() -> Self {
+        Self {
+            max_failure_ratio: 0.01,
+            max_p95_response_time: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Verdict {
+    Passed,
+    Failed { reasons: Vec<String> },
+}
+
+impl Verdict {
+    /// Judges the ```Aggregated``` stats of the run. A failed run or a run without requests fails.
+    pub fn of_run_report(run_report: &RunReport, thresholds: &ReportThresholds) -> Self {
+        if let RunReportStatus::Failed { error } = &run_report.status {
+            return Self::Failed {
+                reasons: vec![format!("Run failed: {error}")],
+            };
+        }
+
+        let reasons = match run_report.aggregated() {
+            Some(aggregated) if aggregated.request_count > 0 => {
+                exceeded_thresholds(aggregated, thresholds)
+            }
+            _ => vec![String::from("No requests were made")],
+        };
+
+        Self::from_reasons(reasons)
+    }
+
+    fn from_reasons(reasons: Vec<String>) -> Self {
+        if reasons.is_empty() {
+            Self::Passed
+        } else {
+            Self::Failed { reasons }
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Passed => "Passed",
+            Self::Failed { .. } => "Failed",
+        }
+    }
+
+    fn reasons(&self) -> &[String] {
+        match self {
+            Self::Passed => &[],
+            Self::Failed { reasons } => reasons,
+        }
+    }
+}
+
+fn exceeded_thresholds(stats: &RequestStats, thresholds: &ReportThresholds) -> Vec<String> {
+    let mut reasons = Vec::new();
+
+    let failure_ratio = failure_ratio(stats);
+    if failure_ratio > thresholds.max_failure_ratio {
+        reasons.push(format!(
+            "Failure ratio of {} exceeds {}",
+            percent(failure_ratio),
+            percent(thresholds.max_failure_ratio)
+        ));
+    }
+
+    if let (Some(p95), Some(max_p95)) = (stats.p95, thresholds.max_p95_response_time) {
+        if p95 > max_p95 {
+            reasons.push(format!(
+                "95th percentile of {} ms exceeds {} ms",
+                number(p95),
+                number(max_p95)
+            ));
+        }
+    }
+
+    reasons
+}
+
+fn failure_ratio(stats: &RequestStats) -> f64 {
+    match stats.request_count {
+        0 => 0.0,
+        request_count => stats.failure_count as f64 / request_count as f64,
+    }
+}
+
+/// Writes ```report.html``` and ```report.md``` to the run dir, the previous ones are replaced.
+pub(super) async fn write(
+    run_dir: &Path,
+    run_report: &RunReport,
+    thresholds: &ReportThresholds,
+) -> Result<(), IoError> {
+    for format in [ReportFormat::Html, ReportFormat::Markdown] {
+        write_format(run_dir, run_report, thresholds, format).await?;
+    }
+
+    Ok(())
+}
+
+pub(super) async fn write_format(
+    run_dir: &Path,
+    run_report: &RunReport,
+    thresholds: &ReportThresholds,
+    format: ReportFormat,
+) -> Result<(), IoError> {
+    let content = match format {
+        ReportFormat::Html => render_html(run_report, thresholds),
+        ReportFormat::Markdown => render_markdown(run_report, thresholds),
+    };
+
+    fs::create_dir_all(run_dir).await?;
+    fs::write(report_file_path(run_dir, format), content).await
+}
+
+pub(super) fn report_file_path(run_dir: &Path, format: ReportFormat) -> PathBuf {
+    run_dir.join(format.file_name())
+}
+
+/// The settings of the run, the same rows in every format.
+fn summary_rows(run_report: &RunReport) -> Vec<(&'static str, String)> {
+    let run_config = &run_report.run_config;
+    let run_time = run_report
+        .finished_at
+        .duration_since(run_report.started_at)
+        .unwrap_or_default();
+
+    vec![
+        ("Project", run_report.project_id.clone()),
+        ("Script", run_config.script_id.clone()),
+        ("Users", run_config.users.to_string()),
+        ("Spawn rate", format!("{}/s", number(run_config.spawn_rate))),
+        ("Workers", run_config.workers.to_string()),
+        (
+            "Host",
+            run_config
+                .host
+                .clone()
+                .unwrap_or_else(|| String::from("From the locustfile")),
+        ),
+        ("Started", utc(run_report.started_at)),
+        ("Run time", format!("{} s", run_time.as_secs())),
+        (
+            "Status",
+            match &run_report.status {
+                RunReportStatus::Finished { outcome } => format!("{outcome:?}"),
+                RunReportStatus::Failed { error } => format!("Failed: {error}"),
+            },
+        ),
+    ]
+}
+
+fn render_html(run_report: &RunReport, thresholds: &ReportThresholds) -> String {
+    let verdict = Verdict::of_run_report(run_report, thresholds);
+    let verdict_class = verdict.label().to_lowercase();
+    let mut html = String::new();
+
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+        <title>Run {id}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n\
+        <h1>Run {id}</h1>\n<h2 class=\"{verdict_class}\">{verdict}</h2>\n",
+        id = escape_html(&run_report.id),
+        verdict = verdict.label(),
+    );
+
+    if !verdict.reasons().is_empty() {
+        html.push_str("<ul>\n");
+        for reason in verdict.reasons() {
+            let _ = writeln!(html, "<li>{}</li>", escape_html(reason));
+        }
+        html.push_str("</ul>\n");
+    }
+
+    html.push_str("<table>\n");
+    for (name, value) in summary_rows(run_report) {
+        let _ = writeln!(
+            html,
+            "<tr><th>{name}</th><td>{}</td></tr>",
+            escape_html(&value)
+        );
+    }
+    html.push_str("</table>\n");
+
+    html.push_str(
+        "<h2>Endpoints</h2>\n<table>\n<tr><th>Type</th><th>Name</th><th>Requests</th><th>Failures</th>\
+        <th>Median (ms)</th><th>Average (ms)</th><th>Min (ms)</th><th>Max (ms)</th>\
+        <th>p95 (ms)</th><th>p99 (ms)</th><th>Requests/s</th><th>Verdict</th></tr>\n",
+    );
+    for stats in &run_report.stats {
+        let verdict = Verdict::from_reasons(exceeded_thresholds(stats, thresholds));
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td>\
+            <td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"{}\">{}</td></tr>",
+            escape_html(&stats.request_type),
+            escape_html(&stats.name),
+            stats.request_count,
+            stats.failure_count,
+            number(stats.median_response_time),
+            number(stats.average_response_time),
+            number(stats.min_response_time),
+            number(stats.max_response_time),
+            optional_number(stats.p95),
+            optional_number(stats.p99),
+            number(stats.rps),
+            verdict.label().to_lowercase(),
+            verdict.label(),
+        );
+    }
+    html.push_str("</table>\n");
+
+    if !run_report.stats.is_empty() {
+        html.push_str("<h2>Response time percentiles</h2>\n");
+        html.push_str(&percentile_chart(&run_report.stats));
+    }
+
+    if !run_report.failures.is_empty() {
+        html.push_str(
+            "<h2>Failures</h2>\n<table>\n\
+            <tr><th>Method</th><th>Name</th><th>Error</th><th>Occurrences</th></tr>\n",
+        );
+        for failure in &run_report.failures {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape_html(&failure.method),
+                escape_html(&failure.name),
+                escape_html(&failure.error),
+                failure.occurrences,
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    if !run_report.exceptions.is_empty() {
+        html.push_str(
+            "<h2>Exceptions</h2>\n<table>\n\
+            <tr><th>Count</th><th>Message</th><th>Traceback</th></tr>\n",
+        );
+        for exception in &run_report.exceptions {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td><pre>{}</pre></td></tr>",
+                exception.count,
+                escape_html(&exception.message),
+                escape_html(&exception.traceback),
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+
+    html
+}
+
+/// A horizontal bar per percentile and request name, scaled to the slowest percentile.
+fn percentile_chart(stats: &[RequestStats]) -> String {
+    let max_value = stats
+        .iter()
+        .flat_map(|stats| [Some(stats.median_response_time), stats.p95, stats.p99])
+        .flatten()
+        .fold(0.0, f64::max);
+    let group_height = CHART_BAR_HEIGHT * (CHART_SERIES.len() as f64 + 1.0);
+    let height = group_height * stats.len() as f64;
+    let mut svg = String::new();
+
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+        font-family=\"sans-serif\" font-size=\"11\">",
+        width = CHART_LABEL_WIDTH + CHART_WIDTH + 80.0,
+    );
+
+    for (index, stats) in stats.iter().enumerate() {
+        let group_y = group_height * index as f64;
+        let _ = writeln!(
+            svg,
+            "<text x=\"0\" y=\"{}\">{}</text>",
+            group_y + CHART_BAR_HEIGHT * 2.0 - 3.0,
+            escape_html(format!("{} {}", stats.request_type, stats.name).trim_start()),
+        );
+
+        let values = [Some(stats.median_response_time), stats.p95, stats.p99];
+        for (series, ((name, color), value)) in CHART_SERIES.iter().zip(values).enumerate() {
+            let Some(value) = value else {
+                continue;
+            };
+            let bar_y = group_y + CHART_BAR_HEIGHT * series as f64;
+            let bar_width = match max_value {
+                max_value if max_value > 0.0 => value / max_value * CHART_WIDTH,
+                _ => 0.0,
+            };
+            let _ = writeln!(
+                svg,
+                "<rect x=\"{CHART_LABEL_WIDTH}\" y=\"{bar_y}\" width=\"{bar_width:.1}\" height=\"{}\" fill=\"{color}\">\
+                <title>{name}: {} ms</title></rect>\
+                <text x=\"{:.1}\" y=\"{}\">{name} {} ms</text>",
+                CHART_BAR_HEIGHT - 2.0,
+                number(value),
+                CHART_LABEL_WIDTH + bar_width + 4.0,
+                bar_y + CHART_BAR_HEIGHT - 4.0,
+                number(value),
+            );
+        }
+    }
+
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+fn render_markdown(run_report: &RunReport, thresholds: &ReportThresholds) -> String {
+    let verdict = Verdict::of_run_report(run_report, thresholds);
+    let mut markdown = String::new();
+
+    let _ = writeln!(markdown, "# Run {}\n", escape_markdown(&run_report.id));
+    let _ = writeln!(markdown, "**Verdict:** {}\n", verdict.label());
+    for reason in verdict.reasons() {
+        let _ = writeln!(markdown, "- {}", escape_markdown(reason));
+    }
+    if !verdict.reasons().is_empty() {
+        markdown.push('\n');
+    }
+
+    markdown.push_str("| | |\n| --- | --- |\n");
+    for (name, value) in summary_rows(run_report) {
+        let _ = writeln!(markdown, "| {name} | {} |", escape_markdown(&value));
+    }
+
+    if !run_report.stats.is_empty() {
+        markdown.push_str(
+            "\n## Endpoints\n\n\
+            | Type | Name | Requests | Failures | Median (ms) | p95 (ms) | p99 (ms) | Requests/s | Verdict |\n\
+            | --- | --- | ---: | ---: | ---: | ---: | ---: | ---: | --- |\n",
+        );
+        for stats in &run_report.stats {
+            let verdict = Verdict::from_reasons(exceeded_thresholds(stats, thresholds));
+            let _ = writeln!(
+                markdown,
+                "| {} | {} | {} | {} | {} | {} | {} | {} | {} |",
+                escape_markdown(&stats.request_type),
+                escape_markdown(&stats.name),
+                stats.request_count,
+                stats.failure_count,
+                number(stats.median_response_time),
+                optional_number(stats.p95),
+                optional_number(stats.p99),
+                number(stats.rps),
+                verdict.label(),
+            );
+        }
+    }
+
+    if !run_report.failures.is_empty() {
+        markdown.push_str(
+            "\n## Failures\n\n| Method | Name | Error | Occurrences |\n| --- | --- | --- | ---: |\n",
+        );
+        for failure in &run_report.failures {
+            let _ = writeln!(
+                markdown,
+                "| {} | {} | {} | {} |",
+                escape_markdown(&failure.method),
+                escape_markdown(&failure.name),
+                escape_markdown(&failure.error),
+                failure.occurrences,
+            );
+        }
+    }
+
+    // The tracebacks are only in the HTML report, they do not fit in a table.
+    if !run_report.exceptions.is_empty() {
+        markdown.push_str("\n## Exceptions\n\n| Count | Message |\n| ---: | --- |\n");
+        for exception in &run_report.exceptions {
+            let _ = writeln!(
+                markdown,
+                "| {} | {} |",
+                exception.count,
+                escape_markdown(&exception.message),
+            );
+        }
+    }
+
+    markdown
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// A table cell is a single line without unescaped pipes.
+fn escape_markdown(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace(['\r', '\n'], " ")
+}
+
+/// At most two decimals, without trailing zeros, e.g. ```48.25``` or ```44```.
+fn number(value: f64) -> String {
+    let number = format!("{value:.2}");
+
+    number
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_owned()
+}
+
+fn optional_number(value: Option<f64>) -> String {
+    value.map_or_else(|| String::from("N/A"), number)
+}
+
+fn percent(ratio: f64) -> String {
+    format!("{}%", number(ratio * 100.0))
+}
+
+/// E.g. ```2023-11-14 22:13:20 UTC```.
+fn utc(time: SystemTime) -> String {
+    let UtcDateTime {
+        year,
+        month,
+        day,
+        hour,
+        minute,
+        second,
+    } = UtcDateTime::new(time);
+
+    format!("{year:04}-{month:02}-{day:02} {hour:02}:{minute:02}:{second:02} UTC")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_managers::local::{
+        local_project_runner::RunOutcome,
+        run_report::{ExceptionStats, FailureStats},
+    };
+    use models::run_config::RunConfig;
+    use std::{
+        collections::HashMap,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    fn request_stats(name: &str, request_count: u64, failure_count: u64) -> RequestStats {
+        RequestStats {
+            request_type: String::from("GET"),
+            name: name.to_owned(),
+            request_count,
+            failure_count,
+            median_response_time: 44.0,
+            average_response_time: 48.25,
+            min_response_time: 12.0,
+            max_response_time: 200.0,
+            rps: 10.5,
+            p95: Some(90.0),
+            p99: Some(130.0),
+        }
+    }
+
+    #[test]
+    fn render_run_report_and_expect_verdicts_and_escaped_content() {
+        let started_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let run_report = RunReport {
+            id: String::from("run"),
+            project_id: String::from("project"),
+            run_config: RunConfig {
+                script_id: String::from("main.py"),
+                users: 10,
+                spawn_rate: 2.5,
+                duration: 60,
+                workers: 0,
+                host: None,
+                tags: Vec::new(),
+                env_overrides: HashMap::new(),
+            },
+            started_at,
+            finished_at: started_at + Duration::from_secs(60),
+            status: RunReportStatus::Finished {
+                outcome: RunOutcome::CompletedWithFailures,
+            },
+            stats: vec![
+                request_stats("/items?a=1&b=<2>", 100, 0),
+                request_stats("/login|logout", 100, 10),
+                RequestStats {
+                    request_type: String::new(),
+                    ..request_stats("Aggregated", 200, 10)
+                },
+            ],
+            failures: vec![FailureStats {
+                method: String::from("POST"),
+                name: String::from("/login|logout"),
+                error: String::from("HTTPError('500')"),
+                occurrences: 10,
+            }],
+            exceptions: vec![ExceptionStats {
+                count: 1,
+                message: String::from("division by zero"),
+                traceback: String::from("  File \"main.py\", line 9\n    1 / 0\n"),
+            }],
+        };
+        let thresholds = ReportThresholds {
+            max_p95_response_time: Some(100.0),
+            ..ReportThresholds::default()
+        };
+
+        let html = render_html(&run_report, &thresholds);
+        let markdown = render_markdown(&run_report, &thresholds);
+
+        assert_eq!(
+            Verdict::of_run_report(&run_report, &thresholds),
+            Verdict::Failed {
+                reasons: vec![String::from("Failure ratio of 5% exceeds 1%")]
+            }
+        );
+        assert_eq!(
+            Verdict::of_run_report(
+                &RunReport {
+                    status: RunReportStatus::Failed {
+                        error: String::from("Locust failed")
+                    },
+                    ..run_report.clone()
+                },
+                &thresholds
+            ),
+            Verdict::Failed {
+                reasons: vec![String::from("Run failed: Locust failed")]
+            }
+        );
+        assert_eq!(
+            Verdict::of_run_report(
+                &RunReport {
+                    stats: Vec::new(),
+                    ..run_report.clone()
+                },
+                &thresholds
+            ),
+            Verdict::Failed {
+                reasons: vec![String::from("No requests were made")]
+            }
+        );
+        assert!(html.contains("<h2 class=\"failed\">Failed</h2>"));
+        assert!(html.contains("/items?a=1&amp;b=&lt;2&gt;"));
+        assert!(html.contains("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(html.contains("<title>p99: 130 ms</title>"));
+        assert!(html.contains("<td>2023-11-14 22:13:20 UTC</td>"));
+        assert!(html.contains("File &quot;main.py&quot;, line 9\n    1 / 0"));
+        assert!(!html.contains("<2>"));
+        assert!(markdown.starts_with("# Run run\n\n**Verdict:** Failed\n\n- Failure ratio"));
+        assert!(markdown
+            .contains("| GET | /login\\|logout | 100 | 10 | 44 | 90 | 130 | 10.5 | Failed |"));
+        assert!(markdown.contains("| Run time | 60 s |"));
+        assert!(markdown.contains("| 1 | division by zero |"));
+    }
+}
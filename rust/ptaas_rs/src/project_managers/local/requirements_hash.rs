@@ -0,0 +1,157 @@
+use super::python::{self, PythonProbeError};
+use sha2::{Digest, Sha256};
+use std::{
+    fmt::Write,
+    io::Error as IoError,
+    path::{Path, PathBuf},
+};
+use thiserror::Error as ThisError;
+use tokio::fs;
+use tokio_util::sync::CancellationToken;
+
+/// Hex encoded SHA-256 of the dependency files, e.g. ```requirements.txt```,
+/// and the version of the python interpreter, that creates the virtual environment.
+/// Missing dependency files are skipped, e.g. a poetry project without a ```poetry.lock```.
+/// Correctness: The files are hashed as they are, reordering the requirements or changing a comment changes the hash.
+pub(super) async fn compute(
+    dependency_file_paths: &[PathBuf],
+    interpreter_path: &Path,
+    current_dir: &Path,
+    cancellation_token: &CancellationToken,
+) -> Result<String, RequirementsHashError> {
+    let mut hasher = Sha256::new();
+
+    for dependency_file_path in dependency_file_paths {
+        let dependency_file = match fs::read(dependency_file_path).await {
+            Ok(dependency_file) => dependency_file,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(error) => return Err(RequirementsHashError::CouldNotReadRequirementsTxt(error)),
+        };
+
+        hasher.update(
+            dependency_file_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .as_bytes(),
+        );
+        hasher.update(b"\n");
+        hasher.update(&dependency_file);
+        hasher.update(b"\n");
+    }
+
+    let python_version = python::probe_version(interpreter_path, current_dir, cancellation_token)
+        .await
+        .map_err(RequirementsHashError::Python)?;
+    hasher.update(python_version.to_string().as_bytes());
+
+    let hash = hasher
+        .finalize()
+        .iter()
+        .fold(String::with_capacity(64), |mut hash, byte| {
+            // Writing to a String never fails.
+            let _ = write!(hash, "{byte:02x}");
+            hash
+        });
+
+    Ok(hash)
+}
+
+const FILE_NAME_SUFFIX: &str = ".requirements.sha256";
+
+/// Next to the environment dir, e.g. ```environments/<id>.requirements.sha256```.
+pub(super) fn file_path(project_env_dir: &Path) -> PathBuf {
+    let mut file_name = project_env_dir
+        .file_name()
+        .unwrap_or_default()
+        .to_os_string();
+    file_name.push(FILE_NAME_SUFFIX);
+
+    project_env_dir.with_file_name(file_name)
+}
+
+/// The name of the environment dir the hash file belongs to, e.g. ```<id>``` for ```<id>.requirements.sha256```.
+pub(super) fn env_dir_name_of_file(file_name: &str) -> Option<&str> {
+    file_name.strip_suffix(FILE_NAME_SUFFIX)
+}
+
+/// ```None``` if no hash was stored, e.g. before the first installation.
+pub(super) async fn read(hash_file_path: &Path) -> Result<Option<String>, IoError> {
+    match fs::read_to_string(hash_file_path).await {
+        Ok(hash) => Ok(Some(hash)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+pub(super) async fn write(hash_file_path: &Path, hash: &str) -> Result<(), IoError> {
+    fs::write(hash_file_path, hash).await
+}
+
+/// A missing hash file is not an error.
+pub(super) async fn remove(hash_file_path: &Path) -> Result<(), IoError> {
+    match fs::remove_file(hash_file_path).await {
+        Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
+
+#[derive(ThisError, Debug)]
+pub enum RequirementsHashError {
+    #[error("Could not read a dependency file: {0}")]
+    CouldNotReadRequirementsTxt(#[source] IoError),
+    #[error("Could not get the python version: {0}")]
+    Python(#[source] PythonProbeError),
+    #[error("Could not read or write the requirements hash file {1}: {0}")]
+    CouldNotAccessHashFile(#[source] IoError, PathBuf),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_dir;
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn change_requirements_and_expect_different_hash() {
+        let temp_dir = test_dir("requirements_hash");
+        let dir = temp_dir.path();
+        let dependency_file_paths = vec![dir.join("requirements.txt")];
+        let requirements_file_path = &dependency_file_paths[0];
+
+        fs::write(requirements_file_path, "locust==2.15.1")
+            .await
+            .expect("Error writing requirements.");
+        let first_hash = compute(
+            &dependency_file_paths,
+            Path::new("python3"),
+            dir,
+            &CancellationToken::new(),
+        )
+        .await;
+        let second_hash = compute(
+            &dependency_file_paths,
+            Path::new("python3"),
+            dir,
+            &CancellationToken::new(),
+        )
+        .await;
+
+        fs::write(requirements_file_path, "locust==2.16.0")
+            .await
+            .expect("Error writing requirements.");
+        let changed_hash = compute(
+            &dependency_file_paths,
+            Path::new("python3"),
+            dir,
+            &CancellationToken::new(),
+        )
+        .await;
+
+        let first_hash = first_hash.expect("Error computing hash.");
+        assert_eq!(first_hash.len(), 64);
+        assert_eq!(first_hash, second_hash.expect("Error computing hash."));
+        assert_ne!(first_hash, changed_hash.expect("Error computing hash."));
+    }
+}
@@ -0,0 +1,226 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{Error as IoError, ErrorKind, SeekFrom},
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncSeekExt},
+    sync::broadcast,
+    task::JoinHandle,
+};
+use tokio_util::sync::CancellationToken;
+
+/// A chart of a long run is fed from the stored report, not from the channel, a few minutes of samples are enough.
+pub(super) const METRICS_CHANNEL_CAPACITY: usize = 256;
+
+/// Locust appends to its stats history every second, see ```--csv```.
+const STATS_HISTORY_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The aggregated stats of all requests of a run at a point in time, written by locust to ```<prefix>_stats_history.csv```.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunMetricsSample {
+    /// Requests per second.
+    pub rps: f64,
+    /// The failed requests since the run started.
+    pub failures: u64,
+    /// Median response time in milliseconds, ```None``` before the first response.
+    pub p50: Option<f64>,
+    /// In milliseconds, ```None``` before the first response.
+    pub p95: Option<f64>,
+    pub users: u32,
+    pub timestamp: SystemTime,
+}
+
+/// The positions of the used columns, the header of locust changed between versions.
+#[derive(Debug, Clone, Copy)]
+struct StatsHistoryColumns {
+    timestamp: usize,
+    users: usize,
+    name: usize,
+    rps: usize,
+    p50: usize,
+    p95: usize,
+    failures: usize,
+}
+
+impl StatsHistoryColumns {
+    fn from_header(header: &str) -> Option<Self> {
+        let names: Vec<&str> = header.split(',').map(str::trim).collect();
+        let position = |name: &str| names.iter().position(|column| *column == name);
+
+        Some(Self {
+            timestamp: position("Timestamp")?,
+            users: position("User Count")?,
+            name: position("Name")?,
+            rps: position("Requests/s")?,
+            p50: position("50%")?,
+            p95: position("95%")?,
+            failures: position("Total Failure Count")?,
+        })
+    }
+}
+
+/// Parses the lines of a stats history, the first line is the header.
+#[derive(Debug, Default)]
+pub(super) struct StatsHistoryParser {
+    columns: Option<StatsHistoryColumns>,
+}
+
+impl StatsHistoryParser {
+    /// ```None``` for the header, the rows of single requests and malformed rows.
+    /// Correctness: Locust quotes names with commas. Only the ```Aggregated``` row is parsed, it never has quotes.
+    pub(super) fn parse_line(&mut self, line: &str) -> Option<RunMetricsSample> {
+        let Some(columns) = self.columns else {
+            self.columns = StatsHistoryColumns::from_header(line);
+            if self.columns.is_none() {
+                tracing::warn!(line, "Unknown stats history header");
+            }
+            return None;
+        };
+
+        let values: Vec<&str> = line.split(',').map(str::trim).collect();
+        if values.get(columns.name) != Some(&"Aggregated") {
+            return None;
+        }
+
+        let value = |index: usize| values.get(index).copied();
+        // ```N/A``` before the first response.
+        let response_time = |index: usize| value(index).and_then(|value| value.parse().ok());
+
+        Some(RunMetricsSample {
+            rps: value(columns.rps)?.parse().ok()?,
+            failures: value(columns.failures)?.parse().ok()?,
+            p50: response_time(columns.p50),
+            p95: response_time(columns.p95),
+            users: value(columns.users)?.parse().ok()?,
+            timestamp: UNIX_EPOCH + Duration::from_secs(value(columns.timestamp)?.parse().ok()?),
+        })
+    }
+}
+
+/// Follows the stats history of a run, like ```tail -f```, and sends its samples to ```metrics_sender```.
+/// The file does not have to exist yet. Reads once more and finishes, after ```run_finished``` is cancelled.
+pub(super) fn follow_stats_history(
+    path: PathBuf,
+    metrics_sender: broadcast::Sender<RunMetricsSample>,
+    run_finished: CancellationToken,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut parser = StatsHistoryParser::default();
+        let mut offset = 0;
+        // The end of a line, that locust is still writing.
+        let mut partial_line = String::new();
+        let mut interval = tokio::time::interval(STATS_HISTORY_POLL_INTERVAL);
+
+        loop {
+            let finished = tokio::select! {
+                _ = interval.tick() => false,
+                _ = run_finished.cancelled() => true,
+            };
+
+            match read_from(&path, offset).await {
+                Ok(content) => {
+                    offset += content.len() as u64;
+                    partial_line.push_str(&String::from_utf8_lossy(&content));
+
+                    while let Some(line_end) = partial_line.find('\n') {
+                        let line: String = partial_line.drain(..=line_end).collect();
+                        if let Some(sample) = parser.parse_line(line.trim_end()) {
+                            // No subscriber is not an error.
+                            let _ = metrics_sender.send(sample);
+                        }
+                    }
+                }
+                Err(error) if error.kind() == ErrorKind::NotFound => {}
+                Err(error) => {
+                    tracing::warn!(%error, path = %path.display(), "Could not read stats history");
+                }
+            }
+
+            if finished {
+                break;
+            }
+        }
+    })
+}
+
+async fn read_from(path: &PathBuf, offset: u64) -> Result<Vec<u8>, IoError> {
+    let mut file = File::open(path).await?;
+    file.seek(SeekFrom::Start(offset)).await?;
+
+    let mut content = Vec::new();
+    file.read_to_end(&mut content).await?;
+
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_dir;
+    use tokio::fs;
+
+    const HEADER: &str = "Timestamp,User Count,Type,Name,Requests/s,Failures/s,50%,66%,75%,80%,90%,95%,98%,99%,99.9%,99.99%,100%,Total Request Count,Total Failure Count,Total Median Response Time,Total Average Response Time,Total Min Response Time,Total Max Response Time,Total Average Content Size";
+    const FIRST_ROW: &str = "1700000000,0,,Aggregated,0.000000,0.000000,N/A,N/A,N/A,N/A,N/A,N/A,N/A,N/A,N/A,N/A,N/A,0,0,0,0.0,0,0,0";
+    const SECOND_ROW: &str = "1700000001,10,,Aggregated,12.500000,0.500000,45,52,58,61,75,90,110,130,200,200,200,25,2,44,48.2,12,200,1234.0";
+
+    #[tokio::test]
+    async fn follow_stats_history_and_expect_samples() {
+        let temp_dir = test_dir("run_metrics");
+        let test_dir = temp_dir.path();
+        let path = test_dir.join("locust_stats_history.csv");
+
+        let (metrics_sender, mut metrics_receiver) = broadcast::channel(METRICS_CHANNEL_CAPACITY);
+        let run_finished = CancellationToken::new();
+        let task = follow_stats_history(path.clone(), metrics_sender, run_finished.clone());
+
+        fs::write(&path, format!("{HEADER}\n{FIRST_ROW}\n"))
+            .await
+            .expect("Error writing stats history.");
+        let first_sample = metrics_receiver.recv().await;
+        // The second row is written in two parts, like a row that locust is still writing.
+        fs::write(
+            &path,
+            format!("{HEADER}\n{FIRST_ROW}\n{}", &SECOND_ROW[..20]),
+        )
+        .await
+        .expect("Error writing stats history.");
+        tokio::time::sleep(STATS_HISTORY_POLL_INTERVAL * 2).await;
+        fs::write(&path, format!("{HEADER}\n{FIRST_ROW}\n{SECOND_ROW}\n"))
+            .await
+            .expect("Error writing stats history.");
+        run_finished.cancel();
+        task.await.expect("Following task panicked.");
+        let second_sample = metrics_receiver.recv().await;
+
+        assert_eq!(
+            first_sample.expect("Error receiving sample."),
+            RunMetricsSample {
+                rps: 0.0,
+                failures: 0,
+                p50: None,
+                p95: None,
+                users: 0,
+                timestamp: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            }
+        );
+        assert_eq!(
+            second_sample.expect("Error receiving sample."),
+            RunMetricsSample {
+                rps: 12.5,
+                failures: 2,
+                p50: Some(45.0),
+                p95: Some(90.0),
+                users: 10,
+                timestamp: UNIX_EPOCH + Duration::from_secs(1_700_000_001),
+            }
+        );
+        assert!(metrics_receiver.try_recv().is_err());
+        assert!(StatsHistoryParser::default()
+            .parse_line("Unknown,Header")
+            .is_none());
+    }
+}
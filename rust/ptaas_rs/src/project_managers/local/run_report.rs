@@ -0,0 +1,408 @@
+use super::{
+    local_project_runner::RunOutcome,
+    project_store::{ProjectStoreError, StoredRun},
+};
+use models::run_config::RunConfig;
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{Error as IoError, ErrorKind},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::SystemTime,
+};
+use thiserror::Error as ThisError;
+use tokio::fs;
+
+const RUN_REPORT_FILE_NAME: &str = "run_report.json";
+
+/// The name of the row of all requests in the stats of locust.
+const AGGREGATED_NAME: &str = "Aggregated";
+
+/// A finished run of a project, successful or not.
+/// Persisted as ```run_report.json``` in the run dir, see ```LocalProjectManager::get_run_report```.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunReport {
+    pub id: String,
+    pub project_id: String,
+    /// Correctness: The values of ```RunConfig::env_overrides``` are not persisted, they may be credentials.
+    pub run_config: RunConfig,
+    pub started_at: SystemTime,
+    pub finished_at: SystemTime,
+    pub status: RunReportStatus,
+    /// A row per request name, and the ```Aggregated``` row of all requests.
+    /// Empty if locust failed before it wrote its stats.
+    pub stats: Vec<RequestStats>,
+    pub failures: Vec<FailureStats>,
+    pub exceptions: Vec<ExceptionStats>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RunReportStatus {
+    Finished {
+        outcome: RunOutcome,
+    },
+    /// The message of the ```RunError```.
+    Failed {
+        error: String,
+    },
+}
+
+/// A row of ```locust_stats.csv```. Response times are in milliseconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestStats {
+    /// The method of the requests, e.g. ```GET```. Empty for the ```Aggregated``` row.
+    pub request_type: String,
+    pub name: String,
+    pub request_count: u64,
+    pub failure_count: u64,
+    pub median_response_time: f64,
+    pub average_response_time: f64,
+    pub min_response_time: f64,
+    pub max_response_time: f64,
+    /// Requests per second.
+    pub rps: f64,
+    /// ```None``` without responses.
+    pub p95: Option<f64>,
+    pub p99: Option<f64>,
+}
+
+/// A row of ```locust_failures.csv```, the failures with the same request and error.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailureStats {
+    pub method: String,
+    pub name: String,
+    pub error: String,
+    pub occurrences: u64,
+}
+
+/// A row of ```locust_exceptions.csv```, the exceptions raised by the locustfile with the same traceback.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExceptionStats {
+    pub count: u64,
+    pub message: String,
+    pub traceback: String,
+}
+
+impl RunReport {
+    /// The stats of all requests, ```None``` if locust failed before it wrote its stats.
+    pub fn aggregated(&self) -> Option<&RequestStats> {
+        self.stats
+            .iter()
+            .find(|request_stats| request_stats.name == AGGREGATED_NAME)
+    }
+
+    pub(super) fn to_stored_run(&self) -> StoredRun {
+        let aggregated = self.aggregated();
+
+        StoredRun {
+            id: self.id.clone(),
+            project_id: self.project_id.clone(),
+            script_id: self.run_config.script_id.clone(),
+            started_at: self.started_at,
+            finished_at: self.finished_at,
+            outcome: match self.status {
+                RunReportStatus::Finished { outcome } => Some(outcome),
+                RunReportStatus::Failed { .. } => None,
+            },
+            request_count: aggregated.map_or(0, |stats| stats.request_count),
+            failure_count: aggregated.map_or(0, |stats| stats.failure_count),
+        }
+    }
+}
+
+/// The parsed ```--csv``` files of locust, see ```read_locust_stats```.
+#[derive(Debug, Default)]
+pub(super) struct LocustStats {
+    pub(super) stats: Vec<RequestStats>,
+    pub(super) failures: Vec<FailureStats>,
+    pub(super) exceptions: Vec<ExceptionStats>,
+}
+
+/// Reads the final stats of a run, e.g. ```<csv_prefix>_stats.csv```.
+/// A missing file has no rows, e.g. if locust failed to start. Malformed rows are skipped.
+pub(super) async fn read_locust_stats(csv_prefix: &Path) -> Result<LocustStats, IoError> {
+    let stats = read_csv_table(&csv_file_path(csv_prefix, "stats")).await?;
+    let failures = read_csv_table(&csv_file_path(csv_prefix, "failures")).await?;
+    let exceptions = read_csv_table(&csv_file_path(csv_prefix, "exceptions")).await?;
+
+    Ok(LocustStats {
+        stats: stats
+            .records()
+            .filter_map(|record| {
+                Some(RequestStats {
+                    request_type: record.get("Type")?.to_owned(),
+                    name: record.get("Name")?.to_owned(),
+                    request_count: record.parse("Request Count")?,
+                    failure_count: record.parse("Failure Count")?,
+                    median_response_time: record.parse("Median Response Time")?,
+                    average_response_time: record.parse("Average Response Time")?,
+                    min_response_time: record.parse("Min Response Time")?,
+                    max_response_time: record.parse("Max Response Time")?,
+                    rps: record.parse("Requests/s")?,
+                    // ```N/A``` without responses.
+                    p95: record.parse("95%"),
+                    p99: record.parse("99%"),
+                })
+            })
+            .collect(),
+        failures: failures
+            .records()
+            .filter_map(|record| {
+                Some(FailureStats {
+                    method: record.get("Method")?.to_owned(),
+                    name: record.get("Name")?.to_owned(),
+                    error: record.get("Error")?.to_owned(),
+                    occurrences: record.parse("Occurrences")?,
+                })
+            })
+            .collect(),
+        exceptions: exceptions
+            .records()
+            .filter_map(|record| {
+                Some(ExceptionStats {
+                    count: record.parse("Count")?,
+                    message: record.get("Message")?.to_owned(),
+                    traceback: record.get("Traceback")?.to_owned(),
+                })
+            })
+            .collect(),
+    })
+}
+
+fn csv_file_path(csv_prefix: &Path, name: &str) -> PathBuf {
+    let mut file_name = csv_prefix.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!("_{name}.csv"));
+
+    csv_prefix.with_file_name(file_name)
+}
+
+async fn read_csv_table(path: &Path) -> Result<CsvTable, IoError> {
+    match fs::read_to_string(path).await {
+        Ok(content) => Ok(CsvTable::parse(&content)),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(CsvTable::default()),
+        Err(error) => Err(error),
+    }
+}
+
+/// A CSV file with a header, as written by locust.
+#[derive(Debug, Default)]
+struct CsvTable {
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl CsvTable {
+    /// Quoted fields may hold commas, line breaks and doubled quotes, e.g. the tracebacks of the exceptions.
+    fn parse(content: &str) -> Self {
+        let mut rows = Vec::new();
+        let mut row = Vec::new();
+        let mut field = String::new();
+        let mut is_quoted = false;
+        let mut chars = content.chars().peekable();
+
+        while let Some(char) = chars.next() {
+            match char {
+                '"' if is_quoted && chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => is_quoted = !is_quoted,
+                ',' if !is_quoted => row.push(std::mem::take(&mut field)),
+                '\r' if !is_quoted => {}
+                '\n' if !is_quoted => {
+                    row.push(std::mem::take(&mut field));
+                    rows.push(std::mem::take(&mut row));
+                }
+                char => field.push(char),
+            }
+        }
+
+        // The last line does not have to end with a line break.
+        if !field.is_empty() || !row.is_empty() {
+            row.push(field);
+            rows.push(row);
+        }
+
+        let mut rows = rows.into_iter();
+
+        Self {
+            columns: rows.next().unwrap_or_default(),
+            rows: rows.collect(),
+        }
+    }
+
+    fn records(&self) -> impl Iterator<Item = CsvRecord<'_>> {
+        self.rows.iter().map(|values| CsvRecord {
+            columns: &self.columns,
+            values,
+        })
+    }
+}
+
+struct CsvRecord<'a> {
+    columns: &'a [String],
+    values: &'a [String],
+}
+
+impl<'a> CsvRecord<'a> {
+    fn get(&self, column: &str) -> Option<&'a str> {
+        let index = self.columns.iter().position(|name| name == column)?;
+
+        self.values.get(index).map(String::as_str)
+    }
+
+    fn parse<T: FromStr>(&self, column: &str) -> Option<T> {
+        self.get(column)?.parse().ok()
+    }
+}
+
+pub(super) fn report_file_path(run_dir: &Path) -> PathBuf {
+    run_dir.join(RUN_REPORT_FILE_NAME)
+}
+
+pub(super) async fn write(run_dir: &Path, run_report: &RunReport) -> Result<(), IoError> {
+    let report = serde_json::to_vec_pretty(run_report)?;
+
+    fs::create_dir_all(run_dir).await?;
+    fs::write(report_file_path(run_dir), report).await
+}
+
+pub(super) async fn read(run_dir: &Path) -> Result<RunReport, LoadRunReportError> {
+    let report = fs::read(report_file_path(run_dir))
+        .await
+        .map_err(LoadRunReportError::CouldNotReadReport)?;
+
+    serde_json::from_slice(&report).map_err(LoadRunReportError::CouldNotParseReport)
+}
+
+#[derive(ThisError, Debug)]
+pub enum LoadRunReportError {
+    #[error("Run does not exist: {0}")]
+    RunDoesNotExist(String),
+    #[error("Could not load the run: {0}")]
+    CouldNotLoadRun(#[source] ProjectStoreError),
+    #[error("Could not read the run report: {0}")]
+    CouldNotReadReport(#[source] IoError),
+    #[error("Could not parse the run report: {0}")]
+    CouldNotParseReport(#[source] serde_json::Error),
+    #[error("Could not write the rendered run report: {0}")]
+    CouldNotWriteRenderedReport(#[source] IoError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_dir;
+    use std::collections::HashMap;
+
+    const STATS_CSV: &str = "Type,Name,Request Count,Failure Count,Median Response Time,Average Response Time,Min Response Time,Max Response Time,Average Content Size,Requests/s,Failures/s,50%,66%,75%,80%,90%,95%,98%,99%,99.9%,99.99%,100%
+GET,/,100,5,44,48.25,12,200,1234.0,10.5,0.5,44,52,58,61,75,90,110,130,200,200,200
+POST,/login,0,0,0,0.0,0,0,0.0,0.0,0.0,N/A,N/A,N/A,N/A,N/A,N/A,N/A,N/A,N/A,N/A,N/A
+,Aggregated,100,5,44,48.25,12,200,1234.0,10.5,0.5,44,52,58,61,75,90,110,130,200,200,200
+";
+    const FAILURES_CSV: &str = "Method,Name,Error,Occurrences
+GET,/,\"HTTPError('500 Server Error: Internal Server Error, for url: /')\",5
+";
+    const EXCEPTIONS_CSV: &str = "Count,Message,Traceback,Nodes
+2,division by zero,\"  File \"\"main.py\"\", line 9, in index
+    1 / 0
+\",local
+";
+
+    #[tokio::test]
+    async fn read_locust_stats_and_write_report_and_expect_same_report() {
+        let temp_dir = test_dir("run_report");
+        let run_dir = temp_dir.path();
+        let csv_prefix = run_dir.join("locust");
+        for (name, content) in [
+            ("stats", STATS_CSV),
+            ("failures", FAILURES_CSV),
+            ("exceptions", EXCEPTIONS_CSV),
+        ] {
+            fs::write(csv_file_path(&csv_prefix, name), content)
+                .await
+                .expect("Error writing csv file.");
+        }
+
+        let locust_stats = read_locust_stats(&csv_prefix)
+            .await
+            .expect("Error reading locust stats.");
+        let missing_locust_stats = read_locust_stats(&run_dir.join("missing")).await;
+        let now = SystemTime::now();
+        let run_report = RunReport {
+            id: String::from("run"),
+            project_id: String::from("project"),
+            run_config: RunConfig {
+                script_id: String::from("main.py"),
+                users: 10,
+                spawn_rate: 1.0,
+                duration: 10,
+                workers: 0,
+                host: None,
+                tags: Vec::new(),
+                env_overrides: HashMap::new(),
+            },
+            started_at: now,
+            finished_at: now,
+            status: RunReportStatus::Finished {
+                outcome: RunOutcome::CompletedWithFailures,
+            },
+            stats: locust_stats.stats,
+            failures: locust_stats.failures,
+            exceptions: locust_stats.exceptions,
+        };
+        write(run_dir, &run_report)
+            .await
+            .expect("Error writing report.");
+        let read_run_report = read(run_dir).await;
+
+        assert_eq!(run_report.stats.len(), 3);
+        assert_eq!(run_report.stats[1].p95, None);
+        assert_eq!(
+            run_report.aggregated(),
+            Some(&RequestStats {
+                request_type: String::new(),
+                name: String::from("Aggregated"),
+                request_count: 100,
+                failure_count: 5,
+                median_response_time: 44.0,
+                average_response_time: 48.25,
+                min_response_time: 12.0,
+                max_response_time: 200.0,
+                rps: 10.5,
+                p95: Some(90.0),
+                p99: Some(130.0),
+            })
+        );
+        assert_eq!(
+            run_report.failures,
+            vec![FailureStats {
+                method: String::from("GET"),
+                name: String::from("/"),
+                error: String::from(
+                    "HTTPError('500 Server Error: Internal Server Error, for url: /')"
+                ),
+                occurrences: 5,
+            }]
+        );
+        assert_eq!(
+            run_report.exceptions,
+            vec![ExceptionStats {
+                count: 2,
+                message: String::from("division by zero"),
+                traceback: String::from("  File \"main.py\", line 9, in index\n    1 / 0\n"),
+            }]
+        );
+        assert_eq!(run_report.to_stored_run().request_count, 100);
+        assert!(missing_locust_stats
+            .expect("Error reading missing locust stats.")
+            .stats
+            .is_empty());
+        assert_eq!(read_run_report.expect("Error reading report."), run_report);
+    }
+}
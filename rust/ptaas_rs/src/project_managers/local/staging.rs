@@ -0,0 +1,419 @@
+use super::local_project_installer::InstallPhase;
+use std::{
+    ffi::OsStr,
+    io::Error as IoError,
+    path::{Path, PathBuf},
+};
+use tokio::fs;
+use uuid::Uuid;
+
+/// Marks a dir next to an environment dir as staging, e.g. ```environments/<id>.staging-<uuid>```.
+const STAGING_DIR_MARKER: &str = ".staging-";
+
+/// Inside a staging dir, holds a file per completed phase and the resumable marker.
+const MARKERS_DIR_NAME: &str = ".ptaas_markers";
+
+const RESUMABLE_MARKER_FILE_NAME: &str = "resumable";
+
+/// A new, unique staging dir next to ```env_dir```.
+pub(super) fn staging_dir_path(env_dir: &Path) -> PathBuf {
+    let mut file_name = env_dir.file_name().unwrap_or_default().to_os_string();
+    file_name.push(STAGING_DIR_MARKER);
+    file_name.push(Uuid::new_v4().to_string());
+
+    env_dir.with_file_name(file_name)
+}
+
+/// The name of the environment dir the staging dir belongs to, e.g. ```<id>``` for ```<id>.staging-<uuid>```.
+pub(super) fn env_dir_name_of_staging_dir(file_name: &str) -> Option<&str> {
+    file_name
+        .split_once(STAGING_DIR_MARKER)
+        .map(|(env_dir_name, _)| env_dir_name)
+}
+
+fn is_staging_dir_name(file_name: &OsStr) -> bool {
+    file_name.to_string_lossy().contains(STAGING_DIR_MARKER)
+}
+
+fn phase_marker_path(staging_dir: &Path, phase: InstallPhase) -> PathBuf {
+    staging_dir
+        .join(MARKERS_DIR_NAME)
+        .join(format!("{phase:?}.completed"))
+}
+
+fn resumable_marker_path(staging_dir: &Path) -> PathBuf {
+    staging_dir
+        .join(MARKERS_DIR_NAME)
+        .join(RESUMABLE_MARKER_FILE_NAME)
+}
+
+async fn write_marker(marker_path: &Path) -> Result<(), IoError> {
+    if let Some(markers_dir) = marker_path.parent() {
+        fs::create_dir_all(markers_dir).await?;
+    }
+
+    fs::write(marker_path, "").await
+}
+
+pub(super) async fn mark_phase_completed(
+    staging_dir: &Path,
+    phase: InstallPhase,
+) -> Result<(), IoError> {
+    write_marker(&phase_marker_path(staging_dir, phase)).await
+}
+
+pub(super) async fn is_phase_completed(staging_dir: &Path, phase: InstallPhase) -> bool {
+    fs::try_exists(phase_marker_path(staging_dir, phase))
+        .await
+        .unwrap_or(false)
+}
+
+/// A resumable staging dir is kept by ```remove_stale_staging_dirs```.
+pub(super) async fn mark_resumable(staging_dir: &Path) -> Result<(), IoError> {
+    write_marker(&resumable_marker_path(staging_dir)).await
+}
+
+pub(super) async fn unmark_resumable(staging_dir: &Path) -> Result<(), IoError> {
+    fs::remove_file(resumable_marker_path(staging_dir)).await
+}
+
+async fn is_resumable(staging_dir: &Path) -> bool {
+    fs::try_exists(resumable_marker_path(staging_dir))
+        .await
+        .unwrap_or(false)
+}
+
+/// The resumable staging dirs of ```env_dir```. Unreadable dirs are skipped.
+async fn resumable_staging_dirs(env_dir: &Path) -> Vec<PathBuf> {
+    let (Some(environments_dir), Some(env_dir_name)) = (env_dir.parent(), env_dir.file_name())
+    else {
+        return Vec::new();
+    };
+
+    let mut staging_dir_name_prefix = env_dir_name.to_os_string();
+    staging_dir_name_prefix.push(STAGING_DIR_MARKER);
+    let staging_dir_name_prefix = staging_dir_name_prefix.to_string_lossy().into_owned();
+
+    let Ok(mut entries) = fs::read_dir(environments_dir).await else {
+        return Vec::new();
+    };
+
+    let mut resumable_staging_dirs = Vec::new();
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with(&staging_dir_name_prefix)
+            && is_resumable(&entry.path()).await
+        {
+            resumable_staging_dirs.push(entry.path());
+        }
+    }
+
+    resumable_staging_dirs
+}
+
+/// Correctness: There is at most one, a new installation removes the previous one, see ```remove_resumable_staging_dirs```.
+pub(super) async fn find_resumable_staging_dir(env_dir: &Path) -> Option<PathBuf> {
+    resumable_staging_dirs(env_dir).await.into_iter().next()
+}
+
+/// A new installation of ```env_dir``` discards what could have been resumed.
+pub(super) async fn remove_resumable_staging_dirs(env_dir: &Path) {
+    for resumable_staging_dir in resumable_staging_dirs(env_dir).await {
+        if let Err(error) = fs::remove_dir_all(&resumable_staging_dir).await {
+            tracing::warn!(?resumable_staging_dir, %error, "Could not remove resumable staging dir");
+        }
+    }
+}
+
+/// Moves the environment built in ```staging_dir``` to ```env_dir```.
+/// A previous environment is replaced, see ```replace```, and deleted afterwards.
+/// A previous environment that could not be deleted is left to ```remove_stale_staging_dirs```.
+pub(super) async fn promote(staging_dir: &Path, env_dir: &Path) -> Result<(), IoError> {
+    // A promoted environment is complete, there is nothing to resume.
+    match fs::remove_dir_all(staging_dir.join(MARKERS_DIR_NAME)).await {
+        Err(error) if error.kind() != std::io::ErrorKind::NotFound => return Err(error),
+        _ => {}
+    }
+
+    relocate(staging_dir, env_dir).await?;
+
+    let previous_env_dir = if fs::try_exists(env_dir).await? {
+        Some(replace(staging_dir, env_dir).await?)
+    } else {
+        fs::rename(staging_dir, env_dir).await?;
+        None
+    };
+
+    if let Some(previous_env_dir) = previous_env_dir {
+        if let Err(error) = fs::remove_dir_all(&previous_env_dir).await {
+            tracing::warn!(?previous_env_dir, %error, "Could not delete the previous environment");
+        }
+    }
+
+    Ok(())
+}
+
+/// Replaces the existing ```env_dir``` with ```staging_dir```, returns the staging dir the previous environment is in.
+/// On linux both dirs are exchanged in one ```renameat2``` with ```RENAME_EXCHANGE```, the previous environment ends up in ```staging_dir```.
+/// Elsewhere, or if the file system can not exchange dirs, the previous environment is moved aside to a new staging dir first.
+/// Correctness: ```env_dir``` is always either the previous or the new environment on linux,
+/// otherwise it is missing for the moment between the two renames.
+async fn replace(staging_dir: &Path, env_dir: &Path) -> Result<PathBuf, IoError> {
+    #[cfg(target_os = "linux")]
+    match linux::exchange(staging_dir, env_dir).await {
+        Ok(()) => return Ok(staging_dir.to_path_buf()),
+        Err(error) if matches!(error.raw_os_error(), Some(libc::EINVAL | libc::ENOSYS)) => {
+            tracing::debug!(%error, "Could not exchange the environments, renaming them one after the other");
+        }
+        Err(error) => return Err(error),
+    }
+
+    let previous_env_dir = staging_dir_path(env_dir);
+    fs::rename(env_dir, &previous_env_dir).await?;
+    fs::rename(staging_dir, env_dir).await?;
+
+    Ok(previous_env_dir)
+}
+
+/// Virtual environments are not relocatable, the scripts in ```bin``` contain the absolute path of the environment,
+/// e.g. the shebang of ```pip``` and ```activate```. Replaces ```staging_dir``` with ```env_dir``` in them.
+/// Correctness: Only text files are rewritten. Binary launchers, e.g. the ```.exe``` files on windows, keep pointing to ```staging_dir```
+/// and fail once it is gone. ```LocalProjectRunner``` runs ```python -m locust``` instead of the ```locust``` launcher.
+async fn relocate(staging_dir: &Path, env_dir: &Path) -> Result<(), IoError> {
+    let (Some(staging_dir_str), Some(env_dir_str)) = (staging_dir.to_str(), env_dir.to_str())
+    else {
+        tracing::warn!(
+            ?staging_dir,
+            "Environment path is not valid unicode, skipping relocation"
+        );
+        return Ok(());
+    };
+
+    let scripts_dir = if cfg!(target_os = "windows") {
+        staging_dir.join("Scripts")
+    } else {
+        staging_dir.join("bin")
+    };
+
+    let mut scripts = fs::read_dir(&scripts_dir).await?;
+
+    while let Some(entry) = scripts.next_entry().await? {
+        // The python executables are symlinks to the base interpreter.
+        if !entry.file_type().await?.is_file() {
+            continue;
+        }
+
+        let Ok(script) = String::from_utf8(fs::read(entry.path()).await?) else {
+            continue;
+        };
+
+        if script.contains(staging_dir_str) {
+            fs::write(entry.path(), script.replace(staging_dir_str, env_dir_str)).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Deletes the staging dirs in ```environments_dir```, that were left behind by installations that did not finish,
+/// e.g. because the process died. Returns the deleted dirs.
+/// Resumable staging dirs are kept, see ```mark_resumable```.
+/// Correctness: Must run before any installation starts, the staging dirs of running installations are deleted too.
+pub(super) async fn remove_stale_staging_dirs(
+    environments_dir: &Path,
+) -> Result<Vec<PathBuf>, IoError> {
+    if !fs::try_exists(environments_dir).await? {
+        return Ok(Vec::new());
+    }
+
+    let mut removed_dirs = Vec::new();
+    let mut entries = fs::read_dir(environments_dir).await?;
+
+    while let Some(entry) = entries.next_entry().await? {
+        if !entry.file_type().await?.is_dir()
+            || !is_staging_dir_name(&entry.file_name())
+            || is_resumable(&entry.path()).await
+        {
+            continue;
+        }
+
+        fs::remove_dir_all(entry.path()).await?;
+        removed_dirs.push(entry.path());
+    }
+
+    Ok(removed_dirs)
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::{ffi::CString, io::Error as IoError, os::unix::ffi::OsStrExt, path::Path};
+
+    /// Atomically exchanges two existing paths.
+    /// ```EINVAL``` if the file system does not support it, ```ENOSYS``` if the kernel is older than 3.15.
+    pub(super) async fn exchange(first: &Path, second: &Path) -> Result<(), IoError> {
+        let first = CString::new(first.as_os_str().as_bytes())?;
+        let second = CString::new(second.as_os_str().as_bytes())?;
+
+        tokio::task::spawn_blocking(move || {
+            // Safety: Both paths are nul terminated and outlive the call.
+            // The libc wrapper of renameat2 needs glibc 2.28.
+            let result = unsafe {
+                libc::syscall(
+                    libc::SYS_renameat2,
+                    libc::AT_FDCWD,
+                    first.as_ptr(),
+                    libc::AT_FDCWD,
+                    second.as_ptr(),
+                    libc::RENAME_EXCHANGE,
+                )
+            };
+            if result != 0 {
+                return Err(IoError::last_os_error());
+            }
+
+            Ok(())
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_dir;
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn remove_stale_staging_dirs_and_expect_environments_kept() {
+        let temp_dir = test_dir("staging_gc");
+        let environments_dir = temp_dir.path();
+        let env_dir = environments_dir.join("project");
+        let staging_dir = staging_dir_path(&env_dir);
+        fs::create_dir_all(&env_dir)
+            .await
+            .expect("Error creating dir.");
+        fs::create_dir_all(staging_dir.join("bin"))
+            .await
+            .expect("Error creating dir.");
+
+        let removed_dirs = remove_stale_staging_dirs(environments_dir)
+            .await
+            .expect("Error removing staging dirs.");
+
+        let env_dir_exists = fs::try_exists(&env_dir).await.unwrap_or(false);
+
+        assert_eq!(removed_dirs, vec![staging_dir]);
+        assert!(env_dir_exists);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn mark_staging_dir_resumable_and_expect_found_and_kept() {
+        let temp_dir = test_dir("staging_resumable");
+        let environments_dir = temp_dir.path();
+        let env_dir = environments_dir.join("project");
+        let staging_dir = staging_dir_path(&env_dir);
+        fs::create_dir_all(&staging_dir)
+            .await
+            .expect("Error creating dir.");
+        mark_phase_completed(&staging_dir, InstallPhase::Venv)
+            .await
+            .expect("Error marking phase.");
+        mark_resumable(&staging_dir)
+            .await
+            .expect("Error marking staging dir.");
+
+        let removed_dirs = remove_stale_staging_dirs(environments_dir).await;
+        let resumable_staging_dir = find_resumable_staging_dir(&env_dir).await;
+        let venv_completed = is_phase_completed(&staging_dir, InstallPhase::Venv).await;
+        let requirements_completed =
+            is_phase_completed(&staging_dir, InstallPhase::Requirements).await;
+
+        assert!(removed_dirs
+            .expect("Error removing staging dirs.")
+            .is_empty());
+        assert_eq!(resumable_staging_dir, Some(staging_dir));
+        assert!(venv_completed);
+        assert!(!requirements_completed);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn promote_staging_dir_and_expect_replaced_and_relocated_environment() {
+        let temp_dir = test_dir("staging_promote");
+        let environments_dir = temp_dir.path();
+        let env_dir = environments_dir.join("project");
+        let staging_dir = staging_dir_path(&env_dir);
+        fs::create_dir_all(&env_dir)
+            .await
+            .expect("Error creating dir.");
+        fs::write(env_dir.join("previous"), "")
+            .await
+            .expect("Error writing file.");
+        let scripts_dir = if cfg!(target_os = "windows") {
+            "Scripts"
+        } else {
+            "bin"
+        };
+        fs::create_dir_all(staging_dir.join(scripts_dir))
+            .await
+            .expect("Error creating dir.");
+        fs::write(
+            staging_dir.join(scripts_dir).join("locust"),
+            format!("#!{}/bin/python\n", staging_dir.display()),
+        )
+        .await
+        .expect("Error writing file.");
+
+        promote(&staging_dir, &env_dir)
+            .await
+            .expect("Error promoting staging dir.");
+
+        let script = fs::read_to_string(env_dir.join(scripts_dir).join("locust")).await;
+        let previous_exists = fs::try_exists(env_dir.join("previous")).await;
+        let removed_dirs = remove_stale_staging_dirs(environments_dir).await;
+
+        assert_eq!(
+            script.expect("Error reading script."),
+            format!("#!{}/bin/python\n", env_dir.display())
+        );
+        assert!(!previous_exists.expect("Error checking previous environment."));
+        assert!(removed_dirs
+            .expect("Error removing staging dirs.")
+            .is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn exchange_dirs_and_expect_swapped_contents() {
+        let temp_dir = test_dir("staging_exchange");
+        let first_dir = temp_dir.path().join("first");
+        let second_dir = temp_dir.path().join("second");
+        for dir in [&first_dir, &second_dir] {
+            fs::create_dir_all(dir).await.expect("Error creating dir.");
+            fs::write(dir.join("origin"), dir.display().to_string())
+                .await
+                .expect("Error writing file.");
+        }
+
+        linux::exchange(&first_dir, &second_dir)
+            .await
+            .expect("Error exchanging dirs.");
+
+        let first_origin = fs::read_to_string(first_dir.join("origin")).await;
+        let second_origin = fs::read_to_string(second_dir.join("origin")).await;
+
+        assert_eq!(
+            first_origin.expect("Error reading file."),
+            second_dir.display().to_string()
+        );
+        assert_eq!(
+            second_origin.expect("Error reading file."),
+            first_dir.display().to_string()
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use super::tenant::{self, TenantId};
+use std::{
+    io::Error as IoError,
+    path::{Path, PathBuf},
+};
+use tokio::fs;
+
+/// Where the environments were created before the dir was renamed, see ```migrate_legacy_environments_dir```.
+const LEGACY_ENVIRONMENTS_DIR_NAME: &str = "enviroments";
+
+/// The dirs of a ```LocalProjectManager```, all of them are created when the manager is created.
+/// Relative dirs are relative to the root dir of the manager, see ```StorageLayout::resolve```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageLayout {
+    /// Where the caller puts the uploaded projects, e.g. with ```extract_uploaded_archive```.
+    /// The manager only deletes an uploaded project, see ```LocalProjectManager::delete_project```.
+    pub uploaded_projects_dir: PathBuf,
+    /// Holds a dir per installed project, e.g. its install record.
+    pub installed_projects_dir: PathBuf,
+    /// Holds the virtual environment of every project, e.g. ```environments/<id>```.
+    pub environments_dir: PathBuf,
+    /// Where the caller writes its logs.
+    pub logs_dir: PathBuf,
+    /// Holds a dir per run, with the output, the stats and the report of the run, e.g. ```runs/<run_id>```.
+    pub runs_dir: PathBuf,
+}
+
+impl Default for StorageLayout {
+    fn default() -> Self {
+        Self {
+            uploaded_projects_dir: PathBuf::from("uploaded_projects"),
+            installed_projects_dir: PathBuf::from("installed_projects"),
+            environments_dir: PathBuf::from("environments"),
+            logs_dir: PathBuf::from("logs"),
+            runs_dir: PathBuf::from("runs"),
+        }
+    }
+}
+
+impl StorageLayout {
+    /// Joins the relative dirs to ```root_dir```, absolute dirs are kept.
+    pub fn resolve(&self, root_dir: &Path) -> Self {
+        Self {
+            uploaded_projects_dir: root_dir.join(&self.uploaded_projects_dir),
+            installed_projects_dir: root_dir.join(&self.installed_projects_dir),
+            environments_dir: root_dir.join(&self.environments_dir),
+            logs_dir: root_dir.join(&self.logs_dir),
+            runs_dir: root_dir.join(&self.runs_dir),
+        }
+    }
+
+    /// The dirs of the tenant, e.g. ```<root_dir>/tenants/<tenant_id>/runs```, for a resolved layout of ```root_dir```.
+    /// The default tenant uses the dirs of the layout, e.g. with the projects added before there were tenants.
+    /// Correctness: The dirs outside of ```root_dir``` are shared by the tenants, the ids of the projects and runs are unique.
+    pub fn for_tenant(&self, root_dir: &Path, tenant_id: &TenantId) -> Self {
+        if tenant_id.is_default() {
+            return self.clone();
+        }
+
+        let tenant_root_dir = tenant::tenant_root_dir(root_dir, tenant_id);
+        let for_tenant = |dir: &Path| match dir.strip_prefix(root_dir) {
+            Ok(relative_dir) => tenant_root_dir.join(relative_dir),
+            Err(_) => dir.to_path_buf(),
+        };
+
+        Self {
+            uploaded_projects_dir: for_tenant(&self.uploaded_projects_dir),
+            installed_projects_dir: for_tenant(&self.installed_projects_dir),
+            environments_dir: for_tenant(&self.environments_dir),
+            logs_dir: for_tenant(&self.logs_dir),
+            runs_dir: for_tenant(&self.runs_dir),
+        }
+    }
+
+    pub(super) fn dirs(&self) -> [&Path; 5] {
+        [
+            &self.uploaded_projects_dir,
+            &self.installed_projects_dir,
+            &self.environments_dir,
+            &self.logs_dir,
+            &self.runs_dir,
+        ]
+    }
+}
+
+/// Moves ```root_dir/enviroments``` to ```environments_dir```, e.g. after an update of ptaas_rs.
+/// Returns whether the dir was moved.
+/// Correctness: Nothing is moved if ```environments_dir``` exists, the legacy dir is left as it is.
+/// The requirements hashes and the staging dirs are moved with the environments, they are in the same dir.
+/// The dir is renamed, ```environments_dir``` must be on the same filesystem.
+pub(super) async fn migrate_legacy_environments_dir(
+    root_dir: &Path,
+    environments_dir: &Path,
+) -> Result<bool, IoError> {
+    let legacy_environments_dir = root_dir.join(LEGACY_ENVIRONMENTS_DIR_NAME);
+
+    if legacy_environments_dir == environments_dir
+        || !fs::try_exists(&legacy_environments_dir).await?
+    {
+        return Ok(false);
+    }
+
+    if fs::try_exists(environments_dir).await? {
+        tracing::warn!(
+            ?legacy_environments_dir,
+            ?environments_dir,
+            "Environments dir exists, not migrating the legacy environments dir"
+        );
+
+        return Ok(false);
+    }
+
+    if let Some(parent_dir) = environments_dir.parent() {
+        fs::create_dir_all(parent_dir).await?;
+    }
+
+    fs::rename(&legacy_environments_dir, environments_dir).await?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_dir;
+
+    #[tokio::test]
+    async fn migrate_legacy_environments_dir_and_expect_environments_moved_once() {
+        let temp_dir = test_dir("storage_layout");
+        let root_dir = temp_dir.path();
+        let storage_layout = StorageLayout::default().resolve(root_dir);
+        let legacy_env_dir = root_dir.join(LEGACY_ENVIRONMENTS_DIR_NAME).join("project");
+        fs::create_dir_all(&legacy_env_dir)
+            .await
+            .expect("Error creating dir.");
+
+        let migrated =
+            migrate_legacy_environments_dir(root_dir, &storage_layout.environments_dir).await;
+        let migrated_env_dir_exists =
+            fs::try_exists(storage_layout.environments_dir.join("project")).await;
+        let legacy_env_dir_exists = fs::try_exists(&legacy_env_dir).await;
+        let migrated_again =
+            migrate_legacy_environments_dir(root_dir, &storage_layout.environments_dir).await;
+
+        assert!(migrated.expect("Error migrating environments dir."));
+        assert!(migrated_env_dir_exists.expect("Error checking dir."));
+        assert!(!legacy_env_dir_exists.expect("Error checking dir."));
+        assert!(!migrated_again.expect("Error migrating environments dir."));
+        assert_eq!(
+            StorageLayout {
+                logs_dir: PathBuf::from("/var/log/ptaas"),
+                ..StorageLayout::default()
+            }
+            .resolve(root_dir)
+            .logs_dir,
+            PathBuf::from("/var/log/ptaas")
+        );
+    }
+}
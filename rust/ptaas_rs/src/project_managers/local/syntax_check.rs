@@ -0,0 +1,92 @@
+use crate::project_managers::process::{
+    self, ProcessRunError, Status, TerminationStatus, TerminationWithErrorStatus,
+};
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use thiserror::Error as ThisError;
+use tokio_util::sync::CancellationToken;
+
+/// Compiles every file like ```python -m py_compile```, without writing bytecode into the project.
+/// Stops at the first broken file and prints its path and the error.
+const BOOTSTRAP_SCRIPT: &str = "\
+import sys
+for path in sys.argv[1:]:
+    try:
+        with open(path, 'rb') as file:
+            compile(file.read(), path, 'exec')
+    except (SyntaxError, ValueError) as error:
+        line = getattr(error, 'lineno', None)
+        message = getattr(error, 'msg', None) or str(error)
+        print(path)
+        print(f'line {line}: {message}' if line else message)
+        sys.exit(1)
+";
+
+/// The error message of a broken file is short.
+const MAX_SYNTAX_CHECK_OUTPUT_SIZE: usize = 64 * 1024;
+
+/// Compiling takes seconds even for big projects, a hanging interpreter is killed.
+const SYNTAX_CHECK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The first python file that does not compile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidPythonFile {
+    pub file: PathBuf,
+    /// e.g. ```line 3: invalid syntax```.
+    pub message: String,
+}
+
+/// Compiles ```files``` with the interpreter at ```interpreter_path```.
+/// Returns ```None``` if all files compile.
+/// Correctness: The python version of the interpreter decides what is valid syntax.
+pub(super) async fn check(
+    interpreter_path: &Path,
+    current_dir: &Path,
+    files: &[PathBuf],
+    cancellation_token: &CancellationToken,
+) -> Result<Option<InvalidPythonFile>, SyntaxCheckError> {
+    let mut args = vec![OsStr::new("-c"), OsStr::new(BOOTSTRAP_SCRIPT)];
+    args.extend(files.iter().map(|file| file.as_os_str()));
+
+    let output = process::capture(
+        interpreter_path.as_os_str(),
+        args,
+        current_dir,
+        Vec::new(),
+        MAX_SYNTAX_CHECK_OUTPUT_SIZE,
+        SYNTAX_CHECK_TIMEOUT,
+        Some(cancellation_token),
+    )
+    .await
+    .map_err(SyntaxCheckError::CouldNotRunPython)?;
+
+    match output.status {
+        Status::Terminated(TerminationStatus::TerminatedSuccessfully) => Ok(None),
+        Status::Terminated(TerminationStatus::TerminatedWithError(
+            TerminationWithErrorStatus::TerminatedWithErrorCode(1),
+        )) => {
+            let (file, message) = output
+                .stdout
+                .trim_end()
+                .split_once('\n')
+                .ok_or(SyntaxCheckError::PythonFailed(output.status, output.stderr))?;
+
+            Ok(Some(InvalidPythonFile {
+                file: PathBuf::from(file),
+                message: message.to_owned(),
+            }))
+        }
+        status => Err(SyntaxCheckError::PythonFailed(status, output.stderr)),
+    }
+}
+
+#[derive(ThisError, Debug)]
+pub enum SyntaxCheckError {
+    #[error("Could not run python to check the syntax: {0}")]
+    CouldNotRunPython(#[source] ProcessRunError),
+    #[error("Python failed to check the syntax: {1}")]
+    PythonFailed(Status, String),
+}
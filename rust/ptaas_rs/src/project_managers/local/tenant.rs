@@ -0,0 +1,287 @@
+use super::project_store::ProjectStoreError;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{Error as IoError, ErrorKind},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+};
+use thiserror::Error as ThisError;
+use tokio::fs;
+
+const DEFAULT_TENANT_ID: &str = "default";
+/// Holds a dir per tenant, e.g. ```<root_dir>/tenants/<tenant_id>/uploaded_projects```, see ```StorageLayout::for_tenant```.
+const TENANTS_DIR_NAME: &str = "tenants";
+
+/// Namespaces the projects of a ```LocalProjectManager```, e.g. per customer.
+/// The projects added without a tenant belong to the default tenant, see ```LocalProjectManager::add_new_project_to_database```.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TenantId(String);
+
+#[derive(ThisError, Debug)]
+#[error("Tenant id must be ascii letters, digits, '-' and '_': {0:?}")]
+pub struct InvalidTenantId(pub String);
+
+impl TenantId {
+    /// The id is a dir name, see ```StorageLayout::for_tenant```.
+    pub fn new(id: impl Into<String>) -> Result<Self, InvalidTenantId> {
+        let id = id.into();
+        let is_valid = !id.is_empty()
+            && id
+                .chars()
+                .all(|char| char.is_ascii_alphanumeric() || char == '-' || char == '_');
+
+        if !is_valid {
+            return Err(InvalidTenantId(id));
+        }
+
+        Ok(Self(id))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.0 == DEFAULT_TENANT_ID
+    }
+}
+
+impl Default for TenantId {
+    fn default() -> Self {
+        Self(String::from(DEFAULT_TENANT_ID))
+    }
+}
+
+impl fmt::Display for TenantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TryFrom<String> for TenantId {
+    type Error = InvalidTenantId;
+
+    fn try_from(id: String) -> Result<Self, Self::Error> {
+        Self::new(id)
+    }
+}
+
+impl From<TenantId> for String {
+    fn from(tenant_id: TenantId) -> Self {
+        tenant_id.0
+    }
+}
+
+/// The limits of a tenant, enforced by ```LocalProjectManager```. Nothing is limited by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TenantQuota {
+    /// Checked when a project is added.
+    pub max_projects: Option<usize>,
+    /// The uploaded dirs, the installations, the environments and the runs of the projects of the tenant.
+    /// Checked when a project is added or installed, a run is not interrupted if it exceeds the quota.
+    pub max_disk_usage_bytes: Option<u64>,
+    /// The queued installations count too.
+    pub max_concurrent_installations: Option<usize>,
+    pub max_concurrent_runs: Option<usize>,
+}
+
+#[derive(ThisError, Debug)]
+pub enum TenantQuotaError {
+    #[error("Tenant {tenant_id} has the maximum of {max} projects")]
+    TooManyProjects { tenant_id: TenantId, max: usize },
+    #[error("Tenant {tenant_id} would use {used_bytes} bytes, its quota is {max_bytes} bytes")]
+    DiskUsageExceeded {
+        tenant_id: TenantId,
+        used_bytes: u64,
+        max_bytes: u64,
+    },
+    #[error("Tenant {tenant_id} has the maximum of {max} installations in progress")]
+    TooManyInstallations { tenant_id: TenantId, max: usize },
+    #[error("Tenant {tenant_id} has the maximum of {max} runs in progress")]
+    TooManyRuns { tenant_id: TenantId, max: usize },
+    #[error("Could not load the projects of the tenant: {0}")]
+    CouldNotLoadProjects(#[source] ProjectStoreError),
+}
+
+impl TenantQuota {
+    pub(super) fn check_project_count(
+        &self,
+        tenant_id: &TenantId,
+        project_count: usize,
+    ) -> Result<(), TenantQuotaError> {
+        match self.max_projects {
+            Some(max) if project_count >= max => Err(TenantQuotaError::TooManyProjects {
+                tenant_id: tenant_id.clone(),
+                max,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    pub(super) fn check_disk_usage(
+        &self,
+        tenant_id: &TenantId,
+        used_bytes: u64,
+    ) -> Result<(), TenantQuotaError> {
+        match self.max_disk_usage_bytes {
+            Some(max_bytes) if used_bytes > max_bytes => Err(TenantQuotaError::DiskUsageExceeded {
+                tenant_id: tenant_id.clone(),
+                used_bytes,
+                max_bytes,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// What a ```TenantSlotGuard``` is held for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) enum TenantSlot {
+    Installation,
+    Run,
+}
+
+/// Counts the installations and runs in progress per tenant, see ```TenantQuota```.
+/// Correctness: A slot is released when its ```TenantSlotGuard``` is dropped, no entry is kept for unused slots.
+#[derive(Debug, Clone, Default)]
+pub(super) struct TenantSlots {
+    in_use: Arc<Mutex<HashMap<(TenantId, TenantSlot), usize>>>,
+}
+
+impl TenantSlots {
+    /// Fails if the tenant uses the maximum of ```quota``` for the slot already. Does not wait.
+    pub(super) fn try_acquire(
+        &self,
+        tenant_id: &TenantId,
+        slot: TenantSlot,
+        quota: &TenantQuota,
+    ) -> Result<TenantSlotGuard, TenantQuotaError> {
+        let max = match slot {
+            TenantSlot::Installation => quota.max_concurrent_installations,
+            TenantSlot::Run => quota.max_concurrent_runs,
+        };
+
+        let key = (tenant_id.clone(), slot);
+        let mut in_use = self.in_use.lock().unwrap_or_else(PoisonError::into_inner);
+        let used = in_use.get(&key).copied().unwrap_or(0);
+        if let Some(max) = max.filter(|max| used >= *max) {
+            let tenant_id = tenant_id.clone();
+            return Err(match slot {
+                TenantSlot::Installation => {
+                    TenantQuotaError::TooManyInstallations { tenant_id, max }
+                }
+                TenantSlot::Run => TenantQuotaError::TooManyRuns { tenant_id, max },
+            });
+        }
+        in_use.insert(key, used + 1);
+
+        Ok(TenantSlotGuard {
+            tenant_slots: self.clone(),
+            tenant_id: tenant_id.clone(),
+            slot,
+        })
+    }
+}
+
+/// Held for the whole operation, e.g. moved into the task of an installation.
+#[derive(Debug)]
+pub(super) struct TenantSlotGuard {
+    tenant_slots: TenantSlots,
+    tenant_id: TenantId,
+    slot: TenantSlot,
+}
+
+impl Drop for TenantSlotGuard {
+    fn drop(&mut self) {
+        let mut in_use = self
+            .tenant_slots
+            .in_use
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let key = (self.tenant_id.clone(), self.slot);
+
+        if let Some(used) = in_use.get_mut(&key) {
+            *used = used.saturating_sub(1);
+            if *used == 0 {
+                in_use.remove(&key);
+            }
+        }
+    }
+}
+
+/// The root dir of a tenant, that is not the default tenant, see ```StorageLayout::for_tenant```.
+pub(super) fn tenant_root_dir(root_dir: &Path, tenant_id: &TenantId) -> PathBuf {
+    root_dir.join(TENANTS_DIR_NAME).join(tenant_id.as_str())
+}
+
+/// The default tenant and the tenants with a dir in ```<root_dir>/tenants```, sorted.
+/// Dirs, whose names are not tenant ids, are skipped.
+pub(super) async fn tenant_ids(root_dir: &Path) -> Result<Vec<TenantId>, IoError> {
+    let mut tenant_ids = vec![TenantId::default()];
+
+    let mut dir_content = match fs::read_dir(root_dir.join(TENANTS_DIR_NAME)).await {
+        Ok(dir_content) => dir_content,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(tenant_ids),
+        Err(error) => return Err(error),
+    };
+
+    while let Some(entry) = dir_content.next_entry().await? {
+        if !entry.file_type().await?.is_dir() {
+            continue;
+        }
+
+        if let Some(tenant_id) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| TenantId::new(name).ok())
+        {
+            if !tenant_id.is_default() {
+                tenant_ids.push(tenant_id);
+            }
+        }
+    }
+
+    tenant_ids[1..].sort();
+
+    Ok(tenant_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn acquire_slots_of_two_tenants_and_expect_max_per_tenant() {
+        let tenant_slots = TenantSlots::default();
+        let tenant = TenantId::new("tenant").expect("Error creating tenant id.");
+        let quota = TenantQuota {
+            max_concurrent_installations: Some(1),
+            max_concurrent_runs: Some(0),
+            ..TenantQuota::default()
+        };
+
+        let guard = tenant_slots.try_acquire(&tenant, TenantSlot::Installation, &quota);
+        let second_guard = tenant_slots.try_acquire(&tenant, TenantSlot::Installation, &quota);
+        let run_guard = tenant_slots.try_acquire(&tenant, TenantSlot::Run, &quota);
+        let other_tenant_guard =
+            tenant_slots.try_acquire(&TenantId::default(), TenantSlot::Installation, &quota);
+        drop(guard);
+        let guard_after_drop = tenant_slots.try_acquire(&tenant, TenantSlot::Installation, &quota);
+
+        assert!(matches!(
+            second_guard,
+            Err(TenantQuotaError::TooManyInstallations { max: 1, .. })
+        ));
+        assert!(matches!(
+            run_guard,
+            Err(TenantQuotaError::TooManyRuns { max: 0, .. })
+        ));
+        assert!(other_tenant_guard.is_ok());
+        assert!(guard_after_drop.is_ok());
+        assert!(TenantId::new("../tenant").is_err());
+        assert!(serde_json::from_str::<TenantId>("\"a/b\"").is_err());
+    }
+}
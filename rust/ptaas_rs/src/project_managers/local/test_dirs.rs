@@ -0,0 +1,56 @@
+use crate::util::{copy_dir_all, test_dir};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use tokio::fs;
+
+/// The dirs of an installer test, in a temp dir that is deleted on drop.
+/// The installer writes its io files into the uploaded project dir, so the uploaded projects of ```tests_dir``` are copied first.
+/// The installed projects dir and the environments dir are created empty.
+/// Correctness: The checked-in fixtures are never written to.
+pub(super) struct TestDirs {
+    temp_dir: TempDir,
+}
+
+impl TestDirs {
+    pub(super) async fn new(name: &str) -> Self {
+        let temp_dir = test_dir(name);
+
+        copy_dir_all(
+            &Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests_dir")
+                .join("uploaded_projects"),
+            &temp_dir.path().join("uploaded_projects"),
+            |_| false,
+        )
+        .await
+        .expect("Could not copy uploaded projects");
+
+        let test_dirs = Self { temp_dir };
+        for dir in [
+            test_dirs.installed_projects_dir(),
+            test_dirs.environments_dir(),
+        ] {
+            fs::create_dir_all(dir)
+                .await
+                .expect("Could not create test dir");
+        }
+
+        test_dirs
+    }
+
+    fn path(&self) -> &Path {
+        self.temp_dir.path()
+    }
+
+    pub(super) fn uploaded_projects_dir(&self) -> PathBuf {
+        self.path().join("uploaded_projects")
+    }
+
+    pub(super) fn installed_projects_dir(&self) -> PathBuf {
+        self.path().join("installed_projects")
+    }
+
+    pub(super) fn environments_dir(&self) -> PathBuf {
+        self.path().join("environments")
+    }
+}
@@ -1,3 +1,7 @@
+pub mod k8s;
+pub use k8s::K8sProjectManager;
 pub mod local;
 pub use local::LocalProjectManager;
 pub mod process;
+mod project_manager;
+pub use project_manager::ProjectManager;
@@ -0,0 +1,315 @@
+use std::{
+    io::{Error as IoError, ErrorKind},
+    sync::atomic::Ordering,
+    time::Duration,
+};
+
+use sysinfo::{Pid, PidExt, ProcessExt, ProcessStatus, System, SystemExt};
+use thiserror::Error as ThisError;
+use tracing::debug_span;
+
+use super::{
+    signals, KillSignal, KilledTerminationStatus, OsExitStatus, Process, ProcessController,
+    ProcessKillAndWaitError, ProcessRunError, Status, TerminationStatus,
+};
+
+/// An attached os process is polled this often to check if it is still running.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// An os process that was not spawned by this program, see ```Process::attach```.
+/// Correctness: The start time is compared on every poll, so a reused pid is not mistaken for the attached os process.
+pub(super) struct AttachedOsProcess {
+    pid: Pid,
+    start_time: u64,
+    system: System,
+}
+
+impl AttachedOsProcess {
+    fn find(pid: u32) -> Option<Self> {
+        let pid = Pid::from_u32(pid);
+        let mut system = System::new();
+
+        if !system.refresh_process(pid) {
+            return None;
+        }
+
+        let process = system.process(pid)?;
+        if process.status() == ProcessStatus::Zombie {
+            return None;
+        }
+
+        let start_time = process.start_time();
+
+        Some(Self {
+            pid,
+            start_time,
+            system,
+        })
+    }
+
+    fn is_running(&mut self) -> bool {
+        if !self.system.refresh_process(self.pid) {
+            return false;
+        }
+
+        self.system
+            .process(self.pid)
+            .map(|process| {
+                process.start_time() == self.start_time && process.status() != ProcessStatus::Zombie
+            })
+            .unwrap_or(false)
+    }
+
+    /// Resolves once the os process is gone.
+    async fn wait(&mut self) {
+        while self.is_running() {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    fn kill(&mut self) -> Result<(), IoError> {
+        match self.system.process(self.pid) {
+            Some(process) if process.kill() => Ok(()),
+            _ => Err(IoError::new(
+                ErrorKind::Other,
+                "Could not kill attached os process",
+            )),
+        }
+    }
+}
+
+impl Process {
+    /// Reconstructs a process for an os process that is already running, e.g. a detached one spawned before a restart.
+    /// Run it with ```Process::run_attached```, the returned controller can then poll its status and cancel it.
+    /// Correctness: An attached os process is not a child of this program. Its output can not be forwarded
+    /// and its exit status can not be observed. It is treated as detached and never killed on drop.
+    pub async fn attach(
+        given_id: String,
+        given_name: String,
+        pid: u32,
+        kill_signal: KillSignal,
+    ) -> Result<(Self, ProcessController), ProcessAttachError> {
+        let attached =
+            AttachedOsProcess::find(pid).ok_or(ProcessAttachError::ProcessNotFound(pid))?;
+
+        let (mut process, process_controller) = Self::new(given_id, given_name);
+        process.pid.store(pid, Ordering::SeqCst);
+        process.detached.store(true, Ordering::SeqCst);
+        process.kill_signal = kill_signal;
+        process.attached = Some(attached);
+        process.status_holder.overwrite(Status::Running).await;
+
+        Ok((process, process_controller))
+    }
+
+    /// Waits for the attached os process to terminate or to be cancelled.
+    pub async fn run_attached(&mut self) -> Result<Status, ProcessRunError> {
+        let debug_span = debug_span!(
+            "Process::run_attached",
+            given_id = self.given_id,
+            given_name = self.given_name
+        );
+        let _span_guard = debug_span.enter();
+
+        let (mut cancel_channel_receiver, mut cancel_channel_sender) = self.take_channels()?;
+        let mut attached = self.attached.take().ok_or(ProcessRunError::NotAttached)?;
+
+        let cancellation_token_cancelled = self.cancellation_token_cancelled();
+        tokio::pin!(cancellation_token_cancelled);
+
+        tracing::debug!("Waiting for termination or cancellation signal");
+        loop {
+            tokio::select! {
+                result = &mut cancel_channel_receiver, if !self.controller_dropped => {
+                    if result.is_err() {
+                        self.controller_dropped = true;
+                        tracing::debug!("Controller was dropped, attached os process keeps running");
+                        continue;
+                    }
+
+                    tracing::debug!("Attached os process was cancelled by the controller");
+
+                    let cancel_channel_sender = cancel_channel_sender
+                        .take()
+                        .ok_or(ProcessRunError::ControllerDropped)?;
+
+                    let kill_and_wait_error = self.kill_and_wait_attached(&mut attached).await.err();
+                    cancel_channel_sender
+                        .send(kill_and_wait_error)
+                        .map_err(|_| ProcessRunError::ControllerDropped)?;
+                }
+
+                _ = &mut cancellation_token_cancelled => {
+                    tracing::debug!("Attached os process was cancelled by the cancellation token");
+
+                    self.kill_and_wait_attached(&mut attached).await?;
+                }
+
+                _ = attached.wait() => {
+                    tracing::debug!("Attached os process terminated");
+
+                    self.status_holder
+                        .overwrite(Status::Terminated(
+                            TerminationStatus::TerminatedWithUnknownExitStatus,
+                        ))
+                        .await;
+                }
+            }
+
+            break;
+        }
+
+        Ok(self.status_holder.status().await)
+    }
+
+    async fn kill_and_wait_attached(
+        &mut self,
+        attached: &mut AttachedOsProcess,
+    ) -> Result<(), ProcessKillAndWaitError> {
+        if !attached.is_running() {
+            self.status_holder
+                .overwrite(Status::Terminated(
+                    TerminationStatus::TerminatedWithUnknownExitStatus,
+                ))
+                .await;
+
+            return Ok(());
+        }
+
+        let grace_period = self
+            .kill_signal
+            .send_graceful(attached.pid.as_u32())
+            .map_err(ProcessKillAndWaitError::CouldNotKillProcess)?;
+
+        let exited_within_grace_period = match grace_period {
+            Some(grace_period) => tokio::time::timeout(grace_period, attached.wait())
+                .await
+                .is_ok(),
+            None => false,
+        };
+
+        if !exited_within_grace_period {
+            if !signals::kill_tree(attached.pid.as_u32()).await {
+                attached
+                    .kill()
+                    .map_err(ProcessKillAndWaitError::CouldNotKillProcess)?;
+            }
+            attached.wait().await;
+        }
+
+        self.status_holder
+            .overwrite(Status::Terminated(TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByCancellationSignal,
+                OsExitStatus::default(),
+            )))
+            .await;
+
+        Ok(())
+    }
+}
+
+#[derive(ThisError, Debug)]
+pub enum ProcessAttachError {
+    #[error("Os process {0} is not running")]
+    ProcessNotFound(u32),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_test::traced_test;
+
+    #[cfg(unix)]
+    fn spawn_sleep(seconds: &str) -> std::process::Child {
+        std::process::Command::new("sleep")
+            .arg(seconds)
+            .spawn()
+            .expect("Error spawning sleep.")
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn attach_and_cancel_and_expect_killed() {
+        let mut child = spawn_sleep("30");
+
+        let (mut process, mut controller) = Process::attach(
+            "some_id".into(),
+            "attached_process".into(),
+            child.id(),
+            KillSignal::default(),
+        )
+        .await
+        .expect("Error attaching to process.");
+
+        assert!(matches!(controller.status().await, Status::Running));
+        assert_eq!(controller.pid(), Some(child.id()));
+
+        let task_handle = tokio::spawn(async move { process.run_attached().await });
+
+        let kill_and_wait_error = controller
+            .cancel()
+            .await
+            .expect("Error cancelling process.");
+        assert!(kill_and_wait_error.is_none());
+
+        match task_handle.await.expect("Error waiting for task.") {
+            Ok(Status::Terminated(TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByCancellationSignal,
+                _,
+            ))) => {}
+            result => panic!("Unexpected result: {:?}", result),
+        }
+
+        child.wait().expect("Error reaping child.");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn attach_and_let_it_exit_and_expect_unknown_exit_status() {
+        let mut child = spawn_sleep("1");
+
+        let (mut process, _controller) = Process::attach(
+            "some_id".into(),
+            "attached_process".into(),
+            child.id(),
+            KillSignal::default(),
+        )
+        .await
+        .expect("Error attaching to process.");
+
+        // Reaped in the background, like a process spawned by a previous run of this program.
+        let reaper = std::thread::spawn(move || child.wait());
+
+        match process.run_attached().await {
+            Ok(Status::Terminated(TerminationStatus::TerminatedWithUnknownExitStatus)) => {}
+            result => panic!("Unexpected result: {:?}", result),
+        }
+
+        reaper
+            .join()
+            .expect("Error joining reaper.")
+            .expect("Error reaping child.");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn attach_to_a_non_existing_process_and_expect_process_not_found() {
+        let pid = i32::MAX as u32;
+
+        match Process::attach(
+            "some_id".into(),
+            "attached_process".into(),
+            pid,
+            KillSignal::default(),
+        )
+        .await
+        {
+            Err(ProcessAttachError::ProcessNotFound(not_found_pid)) => {
+                assert_eq!(not_found_pid, pid)
+            }
+            Ok(_) => panic!("Process should not be attached."),
+        }
+    }
+}
@@ -0,0 +1,416 @@
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    fmt::Debug,
+    io::{Error as IoError, ErrorKind},
+    process::ExitStatus,
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use tokio::{
+    io::{AsyncRead, AsyncWriteExt, DuplexStream},
+    process::{Child, Command},
+    sync::oneshot,
+    task::JoinHandle,
+};
+
+use super::spawn_in_current_span;
+
+/// Buffer size of the in-memory pipes of a fake os process. Writing more than this waits for the reader.
+const FAKE_PIPE_CAPACITY: usize = 64 * 1024;
+
+pub(super) type BackendStdio = Box<dyn AsyncRead + Send + Unpin>;
+
+/// Spawns the os processes of a ```Process```. ```OsBackend``` spawns real os processes, ```FakeBackend``` scripted ones.
+pub trait ProcessBackend: Debug + Send + Sync {
+    /// ```command``` is fully configured, including the limits, priority and user of the os process.
+    fn spawn(&self, command: &mut Command) -> Result<Box<dyn BackendChild>, IoError>;
+}
+
+/// A spawned os process, real or fake.
+#[async_trait]
+pub trait BackendChild: Send + Sync {
+    /// ```None``` if the os process has no pid, e.g. a fake one. Signals, pausing and metrics need a pid.
+    fn id(&self) -> Option<u32>;
+
+    fn take_stdout(&mut self) -> Option<BackendStdio>;
+
+    fn take_stderr(&mut self) -> Option<BackendStdio>;
+
+    fn try_wait(&mut self) -> Result<Option<ExitStatus>, IoError>;
+
+    async fn wait(&mut self) -> Result<ExitStatus, IoError>;
+
+    /// Kills the os process right away, without a grace period.
+    async fn kill(&mut self) -> Result<(), IoError>;
+
+    /// The underlying ```tokio::process::Child```, used for os specific settings and ```ProcessPipeline```.
+    fn as_os_child_mut(&mut self) -> Option<&mut Child> {
+        None
+    }
+}
+
+/// Spawns real os processes. The default backend.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsBackend;
+
+impl ProcessBackend for OsBackend {
+    fn spawn(&self, command: &mut Command) -> Result<Box<dyn BackendChild>, IoError> {
+        Ok(Box::new(command.spawn()?))
+    }
+}
+
+#[async_trait]
+impl BackendChild for Child {
+    fn id(&self) -> Option<u32> {
+        Child::id(self)
+    }
+
+    fn take_stdout(&mut self) -> Option<BackendStdio> {
+        self.stdout
+            .take()
+            .map(|stdout| Box::new(stdout) as BackendStdio)
+    }
+
+    fn take_stderr(&mut self) -> Option<BackendStdio> {
+        self.stderr
+            .take()
+            .map(|stderr| Box::new(stderr) as BackendStdio)
+    }
+
+    fn try_wait(&mut self) -> Result<Option<ExitStatus>, IoError> {
+        Child::try_wait(self)
+    }
+
+    async fn wait(&mut self) -> Result<ExitStatus, IoError> {
+        Child::wait(self).await
+    }
+
+    async fn kill(&mut self) -> Result<(), IoError> {
+        Child::kill(self).await
+    }
+
+    fn as_os_child_mut(&mut self) -> Option<&mut Child> {
+        Some(self)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum FakeStep {
+    Stdout(String),
+    Stderr(String),
+    Delay(Duration),
+}
+
+/// What a fake os process does, step by step, before it exits with ```exit_code```.
+#[derive(Debug, Clone, Default)]
+pub struct FakeScript {
+    steps: Vec<FakeStep>,
+    exit_code: i32,
+}
+
+impl FakeScript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn stdout(mut self, line: impl Into<String>) -> Self {
+        self.steps.push(FakeStep::Stdout(line.into()));
+        self
+    }
+
+    #[must_use]
+    pub fn stderr(mut self, line: impl Into<String>) -> Self {
+        self.steps.push(FakeStep::Stderr(line.into()));
+        self
+    }
+
+    #[must_use]
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.steps.push(FakeStep::Delay(delay));
+        self
+    }
+
+    #[must_use]
+    pub fn exit_code(mut self, exit_code: i32) -> Self {
+        self.exit_code = exit_code;
+        self
+    }
+}
+
+/// Spawns in-memory os processes that follow a ```FakeScript```, for tests that should not depend on real scripts.
+/// Correctness: Scripts are looked up by program, the arguments are ignored. An unknown program fails to spawn
+/// with ```ErrorKind::NotFound```, like a missing executable. Delays use ```tokio::time```, so they can be skipped
+/// with a paused clock. The fake os processes have no pid.
+#[derive(Debug, Clone, Default)]
+pub struct FakeBackend {
+    scripts: HashMap<OsString, FakeScript>,
+}
+
+impl FakeBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn script(mut self, program: impl Into<OsString>, script: FakeScript) -> Self {
+        self.scripts.insert(program.into(), script);
+        self
+    }
+
+    pub fn into_shared(self) -> Arc<dyn ProcessBackend> {
+        Arc::new(self)
+    }
+}
+
+impl ProcessBackend for FakeBackend {
+    fn spawn(&self, command: &mut Command) -> Result<Box<dyn BackendChild>, IoError> {
+        let program = command.as_std().get_program();
+        let script = self.scripts.get(program).cloned().ok_or_else(|| {
+            IoError::new(
+                ErrorKind::NotFound,
+                format!("No fake script for program {program:?}"),
+            )
+        })?;
+
+        Ok(Box::new(FakeChild::spawn(script)))
+    }
+}
+
+struct FakeChild {
+    stdout: Option<DuplexStream>,
+    stderr: Option<DuplexStream>,
+    exit_status_receiver: oneshot::Receiver<ExitStatus>,
+    exit_status: Option<ExitStatus>,
+    task: JoinHandle<()>,
+}
+
+impl FakeChild {
+    fn spawn(script: FakeScript) -> Self {
+        let (mut stdout_writer, stdout) = tokio::io::duplex(FAKE_PIPE_CAPACITY);
+        let (mut stderr_writer, stderr) = tokio::io::duplex(FAKE_PIPE_CAPACITY);
+        let (exit_status_sender, exit_status_receiver) = oneshot::channel();
+
+        let task = spawn_in_current_span(async move {
+            for step in script.steps {
+                // A closed pipe is ignored, like a real os process that does not check its writes.
+                match step {
+                    FakeStep::Stdout(line) => {
+                        let _ = stdout_writer
+                            .write_all(format!("{line}\n").as_bytes())
+                            .await;
+                    }
+                    FakeStep::Stderr(line) => {
+                        let _ = stderr_writer
+                            .write_all(format!("{line}\n").as_bytes())
+                            .await;
+                    }
+                    FakeStep::Delay(delay) => tokio::time::sleep(delay).await,
+                }
+            }
+
+            drop(stdout_writer);
+            drop(stderr_writer);
+
+            let _ = exit_status_sender.send(exit_status_from_code(script.exit_code));
+        });
+
+        Self {
+            stdout: Some(stdout),
+            stderr: Some(stderr),
+            exit_status_receiver,
+            exit_status: None,
+            task,
+        }
+    }
+}
+
+#[async_trait]
+impl BackendChild for FakeChild {
+    fn id(&self) -> Option<u32> {
+        None
+    }
+
+    fn take_stdout(&mut self) -> Option<BackendStdio> {
+        self.stdout
+            .take()
+            .map(|stdout| Box::new(stdout) as BackendStdio)
+    }
+
+    fn take_stderr(&mut self) -> Option<BackendStdio> {
+        self.stderr
+            .take()
+            .map(|stderr| Box::new(stderr) as BackendStdio)
+    }
+
+    fn try_wait(&mut self) -> Result<Option<ExitStatus>, IoError> {
+        if self.exit_status.is_none() {
+            self.exit_status = self.exit_status_receiver.try_recv().ok();
+        }
+
+        Ok(self.exit_status)
+    }
+
+    async fn wait(&mut self) -> Result<ExitStatus, IoError> {
+        if let Some(exit_status) = self.exit_status {
+            return Ok(exit_status);
+        }
+
+        let exit_status = (&mut self.exit_status_receiver)
+            .await
+            .map_err(|_| IoError::new(ErrorKind::Other, "Fake os process vanished"))?;
+        self.exit_status = Some(exit_status);
+
+        Ok(exit_status)
+    }
+
+    async fn kill(&mut self) -> Result<(), IoError> {
+        if self.try_wait()?.is_none() {
+            // Aborting the task drops the writers, which closes the pipes.
+            self.task.abort();
+            self.exit_status = Some(killed_exit_status());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn exit_status_from_code(code: i32) -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+
+    ExitStatus::from_raw((code & 0xff) << 8)
+}
+
+#[cfg(windows)]
+fn exit_status_from_code(code: i32) -> ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+
+    ExitStatus::from_raw(code as u32)
+}
+
+/// The exit status of an os process killed by this library, see ```TerminationWithErrorStatus```.
+#[cfg(unix)]
+fn killed_exit_status() -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+
+    ExitStatus::from_raw(libc::SIGKILL)
+}
+
+#[cfg(windows)]
+fn killed_exit_status() -> ExitStatus {
+    exit_status_from_code(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_managers::process::{
+        KilledTerminationStatus, ProcessBuilder, ProcessRunError, Status, TerminationStatus,
+        TerminationWithErrorStatus,
+    };
+    use tokio::sync::mpsc;
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn run_fake_script_and_expect_its_lines_and_exit_code() {
+        let backend = FakeBackend::new().script(
+            "pip",
+            FakeScript::new()
+                .stdout("Collecting locust")
+                .stderr("WARNING: old pip")
+                .delay(Duration::from_millis(10))
+                .stdout("Successfully installed locust")
+                .exit_code(2),
+        );
+
+        let (stdout_sender, mut stdout_receiver) = mpsc::channel(10);
+        let (stderr_sender, mut stderr_receiver) = mpsc::channel(10);
+
+        let (mut process, _controller) = ProcessBuilder::new("some_id", "fake_process")
+            .program("pip")
+            .args(["install", "locust"])
+            .stdout_lines(stdout_sender)
+            .stderr_lines(stderr_sender)
+            .backend(backend.into_shared())
+            .build()
+            .expect("Error building process.");
+
+        let status = process.run_built().await.expect("Error running process.");
+
+        assert!(matches!(
+            status,
+            Status::Terminated(TerminationStatus::TerminatedWithError(
+                TerminationWithErrorStatus::TerminatedWithErrorCode(2)
+            ))
+        ));
+
+        let mut stdout_lines = Vec::new();
+        while let Some(line) = stdout_receiver.recv().await {
+            stdout_lines.push(line);
+        }
+        assert_eq!(
+            stdout_lines,
+            vec!["Collecting locust", "Successfully installed locust"]
+        );
+        assert_eq!(
+            stderr_receiver.recv().await.as_deref(),
+            Some("WARNING: old pip")
+        );
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn cancel_a_running_fake_process_and_expect_killed() {
+        let backend =
+            FakeBackend::new().script("locust", FakeScript::new().delay(Duration::from_secs(3600)));
+
+        let (mut process, mut controller) = ProcessBuilder::new("some_id", "fake_process")
+            .program("locust")
+            .backend(backend.into_shared())
+            .build()
+            .expect("Error building process.");
+
+        let handle = tokio::spawn(async move { process.run_built().await });
+
+        controller
+            .wait_until_running()
+            .await
+            .expect("Process should be running.");
+        let kill_and_wait_error = controller
+            .cancel()
+            .await
+            .expect("Error cancelling process.");
+        assert!(kill_and_wait_error.is_none());
+
+        let result = handle.await.expect("Error joining process.");
+        assert!(matches!(
+            result,
+            Ok(Status::Terminated(TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByCancellationSignal,
+                _
+            )))
+        ));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn run_unknown_fake_program_and_expect_not_found() {
+        let (mut process, _controller) = ProcessBuilder::new("some_id", "fake_process")
+            .program("python")
+            .backend(FakeBackend::new().into_shared())
+            .build()
+            .expect("Error building process.");
+
+        let result = process.run_built().await;
+
+        assert!(matches!(
+            result,
+            Err(ProcessRunError::CouldNotSpawnOsProcess(err)) if err.kind() == ErrorKind::NotFound
+        ));
+    }
+}
@@ -0,0 +1,360 @@
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use thiserror::Error as ThisError;
+use tokio::sync::mpsc;
+
+use super::{
+    EnvMode, IdleTimeout, KillSignal, OutputRateLimit, OutputSink, Process, ProcessBackend,
+    ProcessConfig, ProcessController, ProcessHooks, ProcessIoConfig, ProcessPriority,
+    ResourceLimits, RunAs, SandboxOptions, StripAnsi,
+};
+
+/// Fluent alternative to ```Process::new``` + ```OsProcessArgs```.
+/// The built process is run with ```Process::run_built```.
+#[derive(Debug)]
+pub struct ProcessBuilder {
+    given_id: String,
+    given_name: String,
+    program: Option<OsString>,
+    args: Vec<OsString>,
+    envs: Vec<(OsString, OsString)>,
+    current_dir: Option<PathBuf>,
+    stdout_sender: Option<mpsc::Sender<String>>,
+    stderr_sender: Option<mpsc::Sender<String>>,
+    stdout_sinks: Vec<OutputSink>,
+    stderr_sinks: Vec<OutputSink>,
+    stdout_file: Option<PathBuf>,
+    stderr_file: Option<PathBuf>,
+    limits: ResourceLimits,
+    kill_signal: KillSignal,
+    timeout: Option<Duration>,
+    idle_timeout: Option<IdleTimeout>,
+    strip_ansi: StripAnsi,
+    detached: bool,
+    run_as: Option<RunAs>,
+    sandbox: Option<SandboxOptions>,
+    priority: ProcessPriority,
+    env_mode: EnvMode,
+    hooks: ProcessHooks,
+    backend: Option<Arc<dyn ProcessBackend>>,
+    rate_limit: OutputRateLimit,
+    io_config: ProcessIoConfig,
+}
+
+impl ProcessBuilder {
+    #[must_use]
+    pub fn new(given_id: impl Into<String>, given_name: impl Into<String>) -> Self {
+        Self {
+            given_id: given_id.into(),
+            given_name: given_name.into(),
+            program: None,
+            args: Vec::new(),
+            envs: Vec::new(),
+            current_dir: None,
+            stdout_sender: None,
+            stderr_sender: None,
+            stdout_sinks: Vec::new(),
+            stderr_sinks: Vec::new(),
+            stdout_file: None,
+            stderr_file: None,
+            limits: ResourceLimits::default(),
+            kill_signal: KillSignal::default(),
+            timeout: None,
+            idle_timeout: None,
+            strip_ansi: StripAnsi::default(),
+            detached: false,
+            run_as: None,
+            sandbox: None,
+            priority: ProcessPriority::default(),
+            env_mode: EnvMode::default(),
+            hooks: ProcessHooks::default(),
+            backend: None,
+            rate_limit: OutputRateLimit::default(),
+            io_config: ProcessIoConfig::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn program(mut self, program: impl AsRef<OsStr>) -> Self {
+        self.program = Some(program.as_ref().to_owned());
+        self
+    }
+
+    #[must_use]
+    pub fn arg(mut self, arg: impl AsRef<OsStr>) -> Self {
+        self.args.push(arg.as_ref().to_owned());
+        self
+    }
+
+    #[must_use]
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.args
+            .extend(args.into_iter().map(|arg| arg.as_ref().to_owned()));
+        self
+    }
+
+    /// Adds an environment variable on top of the inherited environment.
+    #[must_use]
+    pub fn env(mut self, key: impl AsRef<OsStr>, value: impl AsRef<OsStr>) -> Self {
+        self.envs
+            .push((key.as_ref().to_owned(), value.as_ref().to_owned()));
+        self
+    }
+
+    /// Defaults to ```EnvMode::Inherit```.
+    #[must_use]
+    pub fn env_mode(mut self, env_mode: EnvMode) -> Self {
+        self.env_mode = env_mode;
+        self
+    }
+
+    /// Defaults to the current working directory.
+    #[must_use]
+    pub fn current_dir(mut self, current_dir: impl AsRef<Path>) -> Self {
+        self.current_dir = Some(current_dir.as_ref().to_owned());
+        self
+    }
+
+    /// Forwards stdout line by line to ```sender```. Stdout is discarded otherwise.
+    #[must_use]
+    pub fn stdout_lines(mut self, sender: mpsc::Sender<String>) -> Self {
+        self.stdout_sender = Some(sender);
+        self
+    }
+
+    /// Forwards stderr line by line to ```sender```. Stderr is discarded otherwise.
+    #[must_use]
+    pub fn stderr_lines(mut self, sender: mpsc::Sender<String>) -> Self {
+        self.stderr_sender = Some(sender);
+        self
+    }
+
+    /// Adds a destination for stdout lines. Can be called multiple times.
+    #[must_use]
+    pub fn stdout_sink(mut self, sink: OutputSink) -> Self {
+        self.stdout_sinks.push(sink);
+        self
+    }
+
+    /// Adds a destination for stderr lines. Can be called multiple times.
+    #[must_use]
+    pub fn stderr_sink(mut self, sink: OutputSink) -> Self {
+        self.stderr_sinks.push(sink);
+        self
+    }
+
+    /// Appends stdout to a file through a buffered writer, that survives log rotation.
+    #[must_use]
+    pub fn stdout_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.stdout_file = Some(path.into());
+        self
+    }
+
+    /// Appends stderr to a file through a buffered writer, that survives log rotation.
+    #[must_use]
+    pub fn stderr_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.stderr_file = Some(path.into());
+        self
+    }
+
+    #[must_use]
+    pub fn limits(mut self, limits: ResourceLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    #[must_use]
+    pub fn kill_signal(mut self, kill_signal: KillSignal) -> Self {
+        self.kill_signal = kill_signal;
+        self
+    }
+
+    /// The process is killed if it is still running after ```timeout```.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    #[must_use]
+    pub fn idle_timeout(mut self, idle_timeout: IdleTimeout) -> Self {
+        self.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    #[must_use]
+    pub fn strip_ansi(mut self, strip_ansi: StripAnsi) -> Self {
+        self.strip_ansi = strip_ansi;
+        self
+    }
+
+    /// Applied after ```strip_ansi```, see ```OutputRateLimit```.
+    #[must_use]
+    pub fn rate_limit(mut self, rate_limit: OutputRateLimit) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    /// Buffer sizes and the flush strategy of the output, see ```ProcessIoConfig```.
+    #[must_use]
+    pub fn io_config(mut self, io_config: ProcessIoConfig) -> Self {
+        self.io_config = io_config;
+        self
+    }
+
+    /// Leaves the os process running if the process or its controller is dropped, see ```OsProcessArgs::detached```.
+    #[must_use]
+    pub fn detached(mut self) -> Self {
+        self.detached = true;
+        self
+    }
+
+    /// Unix only. Runs the os process as another user, see ```RunAs```.
+    #[must_use]
+    pub fn run_as(mut self, run_as: RunAs) -> Self {
+        self.run_as = Some(run_as);
+        self
+    }
+
+    /// Linux only. Runs the os process in a bubblewrap sandbox, see ```SandboxOptions```.
+    #[must_use]
+    pub fn sandbox(mut self, sandbox: SandboxOptions) -> Self {
+        self.sandbox = Some(sandbox);
+        self
+    }
+
+    /// Os scheduling priority of the os process, see ```ProcessPriority```.
+    #[must_use]
+    pub fn priority(mut self, priority: ProcessPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Called right before the os process is spawned and right after it terminated, see ```ProcessHooks```.
+    #[must_use]
+    pub fn hooks(mut self, hooks: ProcessHooks) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    /// Spawns the os process with the given backend instead of ```OsBackend```, e.g. a ```FakeBackend``` in tests.
+    #[must_use]
+    pub fn backend(mut self, backend: Arc<dyn ProcessBackend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    pub fn build(self) -> Result<(Process, ProcessController), ProcessBuilderError> {
+        let program = self.program.ok_or(ProcessBuilderError::ProgramNotSet)?;
+
+        let config = ProcessConfig {
+            program,
+            args: self.args,
+            envs: self.envs,
+            current_dir: self.current_dir.unwrap_or_else(|| PathBuf::from(".")),
+            stdout_sender: self.stdout_sender,
+            stderr_sender: self.stderr_sender,
+            stdout_sinks: self.stdout_sinks,
+            stderr_sinks: self.stderr_sinks,
+            stdout_file: self.stdout_file,
+            stderr_file: self.stderr_file,
+            limits: self.limits,
+            kill_signal: self.kill_signal,
+            timeout: self.timeout,
+            idle_timeout: self.idle_timeout,
+            strip_ansi: self.strip_ansi,
+            detached: self.detached,
+            run_as: self.run_as,
+            sandbox: self.sandbox,
+            priority: self.priority,
+            env_mode: self.env_mode,
+            hooks: self.hooks,
+            backend: self.backend,
+            rate_limit: self.rate_limit,
+            io_config: self.io_config,
+            stdin_pipe: None,
+            stdout_pipe: None,
+        };
+
+        let (mut process, controller) = Process::new(self.given_id, self.given_name);
+        process.config = Some(config);
+
+        Ok((process, controller))
+    }
+}
+
+#[derive(ThisError, Debug)]
+pub enum ProcessBuilderError {
+    #[error("Program was not set")]
+    ProgramNotSet,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_managers::process::{KilledTerminationStatus, Status, TerminationStatus};
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn build_with_env_and_expect_env_in_stdout() {
+        let (stdout_sender, mut stdout_receiver) = mpsc::channel(10);
+        let (mut process, _controller) = ProcessBuilder::new("some_id", "env_process")
+            .program("bash")
+            .arg("-c")
+            .arg("echo $PTAAS_TEST_VAR")
+            .env("PTAAS_TEST_VAR", "some_value")
+            .stdout_lines(stdout_sender)
+            .build()
+            .expect("Error building process.");
+
+        let result = process.run_built().await;
+        match result {
+            Ok(Status::Terminated(TerminationStatus::TerminatedSuccessfully)) => {}
+            _ => panic!("Unexpected result: {:?}", result),
+        }
+
+        let line = stdout_receiver.recv().await.expect("Error receiving line.");
+        assert_eq!(line, "some_value");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn exceed_timeout_and_expect_killed_by_timeout() {
+        let (mut process, _controller) = ProcessBuilder::new("some_id", "sleep_process")
+            .program("sleep")
+            .arg("10")
+            .timeout(Duration::from_secs(1))
+            .build()
+            .expect("Error building process.");
+
+        let result = process.run_built().await;
+        match result {
+            Ok(Status::Terminated(TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByTimeout,
+                _,
+            ))) => {}
+            _ => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn build_without_program_and_expect_program_not_set_error() {
+        let result = ProcessBuilder::new("some_id", "no_program_process").build();
+        match result {
+            Err(ProcessBuilderError::ProgramNotSet) => {}
+            Ok(_) => panic!("Process should not be built."),
+        }
+    }
+}
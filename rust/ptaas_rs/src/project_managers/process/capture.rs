@@ -0,0 +1,188 @@
+use std::{
+    io::Error as IoError,
+    path::{Path, PathBuf},
+};
+
+use tokio::{
+    fs::{File, OpenOptions},
+    io::{AsyncWriteExt, BufWriter},
+};
+
+use super::{FlushPolicy, ProcessIoConfig};
+
+/// Writes the lines of a stream to ```OsProcessArgs::stdout_file``` or ```OsProcessArgs::stderr_file```.
+/// Correctness: Lines are buffered and flushed when the buffer is full, according to the ```FlushPolicy```
+/// and when the stream ends. On every flush the file is reopened if it was moved or removed, e.g. by logrotate.
+/// The file is opened in append mode, so a file that was truncated in place is written at its new end.
+pub(super) struct CaptureFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    file_buffer_capacity: usize,
+    flush_policy: FlushPolicy,
+    /// Bytes written since the last flush.
+    buffered: usize,
+}
+
+impl CaptureFile {
+    pub(super) async fn open(path: PathBuf, io_config: &ProcessIoConfig) -> Result<Self, IoError> {
+        let writer = BufWriter::with_capacity(
+            io_config.file_buffer_capacity,
+            Self::open_file(&path).await?,
+        );
+
+        Ok(Self {
+            path,
+            writer,
+            file_buffer_capacity: io_config.file_buffer_capacity,
+            flush_policy: io_config.flush_policy,
+            buffered: 0,
+        })
+    }
+
+    async fn open_file(path: &Path) -> Result<File, IoError> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+    }
+
+    pub(super) async fn write_line(&mut self, line: &[u8]) -> Result<(), IoError> {
+        self.writer.write_all(line).await?;
+        self.writer.write_all(b"\n").await?;
+        self.buffered += line.len() + 1;
+
+        if self.flush_policy.flush_after_line(self.buffered) {
+            self.flush().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Buffered lines go to the file that was open when they were written, the next lines go to the reopened file.
+    pub(super) async fn flush(&mut self) -> Result<(), IoError> {
+        self.writer.flush().await?;
+        self.buffered = 0;
+
+        if self.is_rotated().await {
+            tracing::debug!(path = ?self.path, "Capture file was rotated, reopening it");
+            self.writer = BufWriter::with_capacity(
+                self.file_buffer_capacity,
+                Self::open_file(&self.path).await?,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Correctness: On windows an open file can not be moved, so only a removed file is detected.
+    async fn is_rotated(&self) -> bool {
+        let path_metadata = match tokio::fs::metadata(&self.path).await {
+            Ok(path_metadata) => path_metadata,
+            Err(_) => return true,
+        };
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+
+            if let Ok(file_metadata) = self.writer.get_ref().metadata().await {
+                return (path_metadata.dev(), path_metadata.ino())
+                    != (file_metadata.dev(), file_metadata.ino());
+            }
+        }
+
+        #[cfg(not(unix))]
+        let _ = path_metadata;
+
+        false
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::util::test_dir;
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn rotate_capture_file_and_expect_next_lines_in_new_file() {
+        let temp_dir = test_dir("capture");
+        let dir = temp_dir.path();
+
+        let path = dir.join("out.log");
+        let rotated_path = dir.join("out.log.1");
+
+        let mut capture_file = CaptureFile::open(path.clone(), &ProcessIoConfig::default())
+            .await
+            .expect("Error opening capture file.");
+
+        capture_file
+            .write_line(b"before")
+            .await
+            .expect("Error writing line.");
+        capture_file.flush().await.expect("Error flushing.");
+
+        tokio::fs::rename(&path, &rotated_path)
+            .await
+            .expect("Error rotating file.");
+
+        capture_file
+            .write_line(b"still before")
+            .await
+            .expect("Error writing line.");
+        capture_file.flush().await.expect("Error flushing.");
+
+        capture_file
+            .write_line(b"after")
+            .await
+            .expect("Error writing line.");
+        capture_file.flush().await.expect("Error flushing.");
+
+        let rotated_content = tokio::fs::read_to_string(&rotated_path)
+            .await
+            .expect("Error reading rotated file.");
+        assert_eq!(rotated_content, "before\nstill before\n");
+
+        let content = tokio::fs::read_to_string(&path)
+            .await
+            .expect("Error reading file.");
+        assert_eq!(content, "after\n");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn flush_every_bytes_and_expect_lines_in_file_once_enough_are_buffered() {
+        let temp_dir = test_dir("flush");
+        let dir = temp_dir.path();
+
+        let path = dir.join("out.log");
+        let io_config = ProcessIoConfig {
+            flush_policy: FlushPolicy::EveryBytes(10),
+            ..ProcessIoConfig::default()
+        };
+
+        let mut capture_file = CaptureFile::open(path.clone(), &io_config)
+            .await
+            .expect("Error opening capture file.");
+
+        capture_file
+            .write_line(b"first")
+            .await
+            .expect("Error writing line.");
+        let content = tokio::fs::read_to_string(&path)
+            .await
+            .expect("Error reading file.");
+        assert_eq!(content, "");
+
+        capture_file
+            .write_line(b"second")
+            .await
+            .expect("Error writing line.");
+        let content = tokio::fs::read_to_string(&path)
+            .await
+            .expect("Error reading file.");
+        assert_eq!(content, "first\nsecond\n");
+    }
+}
@@ -0,0 +1,342 @@
+use std::{future::Future, pin::Pin};
+
+use thiserror::Error as ThisError;
+use tokio::sync::oneshot;
+
+use super::{
+    builder::{ProcessBuilder, ProcessBuilderError},
+    Process, ProcessController, ProcessKillAndWaitError, ProcessRunError,
+    SendingCancellationSignalToProcessError, Status, TerminationStatus,
+};
+
+type Rollback = Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>;
+
+/// A step of a ```ProcessChain```, with an optional rollback.
+pub struct ProcessChainStep {
+    builder: ProcessBuilder,
+    rollback: Option<Rollback>,
+}
+
+impl ProcessChainStep {
+    #[must_use]
+    pub fn new(builder: ProcessBuilder) -> Self {
+        Self {
+            builder,
+            rollback: None,
+        }
+    }
+
+    /// Called if this step or any following step fails.
+    #[must_use]
+    pub fn rollback<F, Fut>(mut self, rollback: F) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.rollback = Some(Box::new(move || Box::pin(rollback())));
+        self
+    }
+}
+
+struct BuiltStep {
+    process: Process,
+    controller: ProcessController,
+    rollback: Option<Rollback>,
+}
+
+/// Runs processes one after another.
+/// Correctness: A step is only started if the previous step terminated successfully.
+/// If a step fails, the rollbacks of this step and all previous steps are called in reverse order.
+/// Cancelling the chain cancels the active step as soon as it is running, and no further steps are started.
+/// Dropping the ```ProcessChainController``` cancels the chain.
+pub struct ProcessChain {
+    steps: Vec<BuiltStep>,
+    /// Option so we can take it. ```None``` once the cancellation signal was received.
+    cancel_channel_receiver: Option<oneshot::Receiver<()>>,
+    /// Option so we can take it. Sends the cancellation result of the active step to the controller.
+    cancel_status_channel_sender: Option<oneshot::Sender<Option<ProcessKillAndWaitError>>>,
+}
+
+pub struct ProcessChainController {
+    /// Option so we can take it. Sends a cancellation signal to the chain.
+    cancel_channel_sender: Option<oneshot::Sender<()>>,
+    /// Option so we can take it. Receives the cancellation result of the active step.
+    cancel_status_channel_receiver: Option<oneshot::Receiver<Option<ProcessKillAndWaitError>>>,
+}
+
+impl ProcessChainController {
+    pub async fn cancel(
+        &mut self,
+    ) -> Result<Option<ProcessKillAndWaitError>, SendingCancellationSignalToProcessError> {
+        let cancel_channel_sender = self
+            .cancel_channel_sender
+            .take()
+            .ok_or(SendingCancellationSignalToProcessError::AlreayTriedToCancel)?;
+
+        let cancel_status_channel_receiver = self
+            .cancel_status_channel_receiver
+            .take()
+            .ok_or(SendingCancellationSignalToProcessError::AlreayTriedToCancel)?;
+
+        cancel_channel_sender
+            .send(())
+            .map_err(|_| SendingCancellationSignalToProcessError::ProcessTerminated)?;
+
+        cancel_status_channel_receiver
+            .await
+            .map_err(|_| SendingCancellationSignalToProcessError::ProcessTerminated)
+    }
+}
+
+impl ProcessChain {
+    pub fn new(
+        steps: Vec<ProcessChainStep>,
+    ) -> Result<(Self, ProcessChainController), ProcessBuilderError> {
+        let steps = steps
+            .into_iter()
+            .map(|step| {
+                let (process, controller) = step.builder.build()?;
+                Ok(BuiltStep {
+                    process,
+                    controller,
+                    rollback: step.rollback,
+                })
+            })
+            .collect::<Result<Vec<_>, ProcessBuilderError>>()?;
+
+        let (cancel_channel_sender, cancel_channel_receiver) = oneshot::channel();
+        let (cancel_status_channel_sender, cancel_status_channel_receiver) = oneshot::channel();
+
+        Ok((
+            Self {
+                steps,
+                cancel_channel_receiver: Some(cancel_channel_receiver),
+                cancel_status_channel_sender: Some(cancel_status_channel_sender),
+            },
+            ProcessChainController {
+                cancel_channel_sender: Some(cancel_channel_sender),
+                cancel_status_channel_receiver: Some(cancel_status_channel_receiver),
+            },
+        ))
+    }
+
+    pub async fn run(mut self) -> Result<(), ProcessChainError> {
+        let steps = std::mem::take(&mut self.steps);
+        let mut rollbacks = Vec::with_capacity(steps.len());
+
+        for (index, step) in steps.into_iter().enumerate() {
+            if self.cancel_channel_receiver.is_none() {
+                tracing::debug!(index, "Chain was cancelled, not starting step");
+                Self::roll_back(rollbacks).await;
+                return Err(ProcessChainError::CancelledBeforeStep(index));
+            }
+
+            let BuiltStep {
+                mut process,
+                controller,
+                rollback,
+            } = step;
+
+            rollbacks.push(rollback);
+
+            tracing::debug!(index, given_name = process.given_name, "Running step");
+            let result = self.run_step(&mut process, controller).await;
+
+            let error = match result {
+                Ok(Status::Terminated(TerminationStatus::TerminatedSuccessfully)) => continue,
+                Ok(status) => ProcessChainError::StepUnsuccessful(index, status),
+                Err(error) => ProcessChainError::StepRunError(index, error),
+            };
+
+            tracing::debug!(index, %error, "Step failed, rolling back");
+            Self::roll_back(rollbacks).await;
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    async fn run_step(
+        &mut self,
+        process: &mut Process,
+        mut controller: ProcessController,
+    ) -> Result<Status, ProcessRunError> {
+        let run = process.run_built();
+        tokio::pin!(run);
+
+        if let Some(cancel_channel_receiver) = self.cancel_channel_receiver.as_mut() {
+            tokio::select! {
+                result = &mut run => return result,
+                _ = cancel_channel_receiver => {
+                    tracing::debug!("Chain was cancelled");
+                    self.cancel_channel_receiver = None;
+                }
+            }
+        }
+
+        // The step may still be spawning, wait for it to run before cancelling it.
+        tokio::select! {
+            result = &mut run => {
+                self.send_cancel_result(None);
+                return result;
+            }
+            _ = controller.wait_until(|status| !matches!(status, Status::Created)) => {}
+        }
+
+        let (result, cancel_result) = tokio::join!(run, controller.cancel());
+
+        self.send_cancel_result(cancel_result.ok().flatten());
+
+        result
+    }
+
+    fn send_cancel_result(&mut self, cancel_result: Option<ProcessKillAndWaitError>) {
+        if let Some(sender) = self.cancel_status_channel_sender.take() {
+            let _ = sender.send(cancel_result);
+        }
+    }
+
+    async fn roll_back(rollbacks: Vec<Option<Rollback>>) {
+        for rollback in rollbacks.into_iter().rev().flatten() {
+            rollback().await;
+        }
+    }
+}
+
+#[derive(ThisError, Debug)]
+pub enum ProcessChainError {
+    #[error("Step {0} failed to run: {1}")]
+    StepRunError(usize, #[source] ProcessRunError),
+    #[error("Step {0} did not terminate successfully: {1:?}")]
+    StepUnsuccessful(usize, Status),
+    #[error("Chain was cancelled before step {0}")]
+    CancelledBeforeStep(usize),
+}
+
+#[cfg(test)]
+#[cfg(unix)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::project_managers::process::{KilledTerminationStatus, TerminationWithErrorStatus};
+    use tokio::sync::mpsc;
+    use tracing_test::traced_test;
+
+    fn recording_step(
+        name: &str,
+        script: &str,
+        rolled_back: &Arc<Mutex<Vec<String>>>,
+    ) -> ProcessChainStep {
+        let rolled_back = rolled_back.clone();
+        let step_name = name.to_owned();
+        ProcessChainStep::new(
+            ProcessBuilder::new(format!("{name}_id"), name)
+                .program("bash")
+                .args(["-c", script]),
+        )
+        .rollback(move || async move {
+            rolled_back
+                .lock()
+                .expect("Error locking rolled back steps.")
+                .push(step_name);
+        })
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn run_successful_steps_and_expect_no_rollback() {
+        let rolled_back = Arc::new(Mutex::new(Vec::new()));
+        let (chain, _controller) = ProcessChain::new(vec![
+            recording_step("first", "exit 0", &rolled_back),
+            recording_step("second", "exit 0", &rolled_back),
+        ])
+        .expect("Error creating chain.");
+
+        let result = chain.run().await;
+        assert!(result.is_ok(), "Unexpected result: {:?}", result);
+        assert!(rolled_back
+            .lock()
+            .expect("Error locking rolled back steps.")
+            .is_empty());
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn fail_second_step_and_expect_rollback_in_reverse_order() {
+        let rolled_back = Arc::new(Mutex::new(Vec::new()));
+        let (chain, _controller) = ProcessChain::new(vec![
+            recording_step("first", "exit 0", &rolled_back),
+            recording_step("second", "exit 3", &rolled_back),
+            recording_step("third", "exit 0", &rolled_back),
+        ])
+        .expect("Error creating chain.");
+
+        let result = chain.run().await;
+        match result {
+            Err(ProcessChainError::StepUnsuccessful(
+                1,
+                Status::Terminated(TerminationStatus::TerminatedWithError(
+                    TerminationWithErrorStatus::TerminatedWithErrorCode(3),
+                )),
+            )) => {}
+            _ => panic!("Unexpected result: {:?}", result),
+        }
+
+        assert_eq!(
+            *rolled_back
+                .lock()
+                .expect("Error locking rolled back steps."),
+            vec!["second", "first"]
+        );
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn cancel_active_step_and_expect_following_steps_not_to_run() {
+        let rolled_back = Arc::new(Mutex::new(Vec::new()));
+        let (stdout_sender, mut stdout_receiver) = mpsc::channel(10);
+        let second_step = recording_step("second", "echo started; sleep 10", &rolled_back);
+        let (chain, mut controller) = ProcessChain::new(vec![
+            recording_step("first", "exit 0", &rolled_back),
+            ProcessChainStep {
+                builder: second_step.builder.stdout_lines(stdout_sender),
+                ..second_step
+            },
+            recording_step("third", "exit 0", &rolled_back),
+        ])
+        .expect("Error creating chain.");
+
+        let handle = tokio::spawn(chain.run());
+
+        stdout_receiver
+            .recv()
+            .await
+            .expect("Error receiving line of the second step.");
+        let cancel_result = controller.cancel().await;
+        assert!(
+            matches!(cancel_result, Ok(None)),
+            "Unexpected cancel result: {:?}",
+            cancel_result
+        );
+
+        let result = handle.await.expect("Error joining chain.");
+        match result {
+            Err(ProcessChainError::StepUnsuccessful(
+                1,
+                Status::Terminated(TerminationStatus::Killed(
+                    KilledTerminationStatus::KilledByCancellationSignal,
+                    _,
+                )),
+            )) => {}
+            _ => panic!("Unexpected result: {:?}", result),
+        }
+
+        assert_eq!(
+            *rolled_back
+                .lock()
+                .expect("Error locking rolled back steps."),
+            vec!["second", "first"]
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use std::ffi::OsStr;
+
+use tokio::process::Command;
+
+/// Which variables of this program's environment the os process inherits.
+/// Variables added with ```ProcessBuilder::env``` or ```OsProcessArgs::envs``` are always set, regardless of the mode.
+/// Correctness: Without an inherited ```PATH```, the program is looked up in the os default path only, prefer an absolute program path.
+/// Some windows programs fail to start without ```SystemRoot```.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum EnvMode {
+    /// Inherits the whole environment, e.g. ```BASIC_AUTH_PASSWORD``` is visible to the os process.
+    #[default]
+    Inherit,
+    /// Inherits nothing.
+    Clear,
+    /// Inherits only the given variables, e.g. ```PATH``` and ```HOME```.
+    AllowList(Vec<String>),
+}
+
+impl EnvMode {
+    /// Must be called before the additional variables are set, clearing the environment removes them otherwise.
+    pub(super) fn apply_to_command(&self, command: &mut Command) {
+        match self {
+            EnvMode::Inherit => {}
+            EnvMode::Clear => {
+                command.env_clear();
+            }
+            EnvMode::AllowList(allowed_keys) => {
+                command.env_clear();
+                command.envs(
+                    std::env::vars_os().filter(|(key, _)| Self::is_allowed(allowed_keys, key)),
+                );
+            }
+        }
+    }
+
+    /// Environment variable names are case insensitive on windows.
+    fn is_allowed(allowed_keys: &[String], key: &OsStr) -> bool {
+        let Some(key) = key.to_str() else {
+            return false;
+        };
+
+        allowed_keys.iter().any(|allowed_key| {
+            if cfg!(windows) {
+                allowed_key.eq_ignore_ascii_case(key)
+            } else {
+                allowed_key == key
+            }
+        })
+    }
+}
@@ -0,0 +1,232 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, PoisonError},
+    time::SystemTime,
+};
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+
+use super::{KilledTerminationStatus, OsExitStatus, Status, TerminationStatus};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProcessEventKind {
+    Spawned {
+        pid: u32,
+    },
+    /// A line forwarded to the stdout sinks.
+    Stdout(String),
+    /// A line forwarded to the stderr sinks.
+    Stderr(String),
+    StatusChanged(Status),
+    /// Recorded right after the ```StatusChanged``` event of a killed process.
+    Killed(KilledTerminationStatus, OsExitStatus),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessEvent {
+    /// Starts at 1 and increases by 1 with every event.
+    pub sequence: u64,
+    pub recorded_at: SystemTime,
+    pub kind: ProcessEventKind,
+}
+
+/// Output lines are recorded as the bytes that were read, and converted to ```String``` only when the events are read.
+#[derive(Debug, Clone)]
+pub(super) enum RecordedEventKind {
+    Event(ProcessEventKind),
+    Stdout(Bytes),
+    Stderr(Bytes),
+}
+
+impl RecordedEventKind {
+    fn to_event_kind(&self) -> ProcessEventKind {
+        match self {
+            Self::Event(kind) => kind.clone(),
+            Self::Stdout(line) => {
+                ProcessEventKind::Stdout(String::from_utf8_lossy(line).into_owned())
+            }
+            Self::Stderr(line) => {
+                ProcessEventKind::Stderr(String::from_utf8_lossy(line).into_owned())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RecordedEvent {
+    recorded_at: SystemTime,
+    kind: RecordedEventKind,
+}
+
+/// Events held by the ```EventLog``` of a process, the oldest are dropped first.
+pub(super) const EVENT_LOG_CAPACITY: usize = 1024;
+
+/// Returned by ```ProcessController::events_since```.
+#[derive(Debug, Clone)]
+pub struct ProcessEvents {
+    /// The held events after the requested one, oldest first.
+    pub events: Vec<ProcessEvent>,
+    /// Events after the requested one that were dropped from the log before they were read.
+    /// Correctness: ```events``` continues at the requested sequence number + ```missed``` + 1,
+    /// a reader that needs every event must treat a non-zero count as lost events.
+    pub missed: u64,
+}
+
+#[derive(Debug)]
+struct HeldEvents {
+    events: VecDeque<RecordedEvent>,
+    /// The sequence number of the oldest held event, or of the next event if there is none.
+    first_sequence: u64,
+}
+
+/// Bounded log of everything that happened to a process, see ```EVENT_LOG_CAPACITY```.
+/// Correctness: The log lives as long as the process or its controller. Once it is full, every event drops the oldest one.
+/// Output lines are recorded only with ```ProcessIoConfig::record_output```, and only if they are forwarded to a sink.
+/// The sequence number of an event is its position in the log, starting at 1, dropped events keep their numbers.
+#[derive(Clone)]
+pub(super) struct EventLog {
+    held_events: Arc<Mutex<HeldEvents>>,
+    capacity: usize,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::with_capacity(EVENT_LOG_CAPACITY)
+    }
+}
+
+impl EventLog {
+    pub(super) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            held_events: Arc::new(Mutex::new(HeldEvents {
+                events: VecDeque::new(),
+                first_sequence: 1,
+            })),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub(super) fn push(&self, kind: ProcessEventKind) {
+        self.record(RecordedEventKind::Event(kind));
+    }
+
+    pub(super) fn record(&self, kind: RecordedEventKind) {
+        let recorded_event = RecordedEvent {
+            recorded_at: SystemTime::now(),
+            kind,
+        };
+        let mut held_events = self
+            .held_events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        if held_events.events.len() == self.capacity {
+            held_events.events.pop_front();
+            held_events.first_sequence += 1;
+        }
+        held_events.events.push_back(recorded_event);
+    }
+
+    pub(super) fn push_status(&self, status: &Status) {
+        self.push(ProcessEventKind::StatusChanged(status.clone()));
+
+        if let Status::Terminated(TerminationStatus::Killed(
+            killed_termination_status,
+            os_exit_status,
+        )) = status
+        {
+            self.push(ProcessEventKind::Killed(
+                killed_termination_status.clone(),
+                *os_exit_status,
+            ));
+        }
+    }
+
+    /// The held events with a sequence number greater than ```sequence```, and how many of them were dropped.
+    pub(super) fn since(&self, sequence: u64) -> ProcessEvents {
+        let next_sequence = sequence.saturating_add(1);
+        // Copied out first, the lines are converted without holding the lock.
+        let (first_sequence, recorded_events) = {
+            let held_events = self
+                .held_events
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let skipped = next_sequence.saturating_sub(held_events.first_sequence);
+            let recorded_events: Vec<RecordedEvent> = held_events
+                .events
+                .iter()
+                .skip(usize::try_from(skipped).unwrap_or(usize::MAX))
+                .cloned()
+                .collect();
+
+            (
+                held_events.first_sequence.max(next_sequence),
+                recorded_events,
+            )
+        };
+
+        ProcessEvents {
+            events: recorded_events
+                .into_iter()
+                .zip(first_sequence..)
+                .map(|(recorded_event, sequence)| ProcessEvent {
+                    sequence,
+                    recorded_at: recorded_event.recorded_at,
+                    kind: recorded_event.kind.to_event_kind(),
+                })
+                .collect(),
+            missed: first_sequence - next_sequence,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stdout_lines(process_events: &ProcessEvents) -> Vec<(u64, String)> {
+        process_events
+            .events
+            .iter()
+            .filter_map(|event| match &event.kind {
+                ProcessEventKind::Stdout(line) => Some((event.sequence, line.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn exceed_capacity_and_expect_oldest_events_missed() {
+        let event_log = EventLog::with_capacity(3);
+        for line in ["1", "2", "3", "4", "5"] {
+            event_log.record(RecordedEventKind::Stdout(Bytes::from(line)));
+        }
+
+        let all_events = event_log.since(0);
+        let held_events = event_log.since(3);
+        let newer_events = event_log.since(4);
+        let no_events = event_log.since(5);
+
+        assert_eq!(all_events.missed, 2);
+        assert_eq!(
+            stdout_lines(&all_events),
+            vec![
+                (3, String::from("3")),
+                (4, String::from("4")),
+                (5, String::from("5"))
+            ]
+        );
+        assert_eq!(held_events.missed, 0);
+        assert_eq!(
+            stdout_lines(&held_events),
+            vec![(4, String::from("4")), (5, String::from("5"))]
+        );
+        assert_eq!(newer_events.missed, 0);
+        assert_eq!(stdout_lines(&newer_events), vec![(5, String::from("5"))]);
+        assert_eq!(no_events.missed, 0);
+        assert!(no_events.events.is_empty());
+    }
+}
@@ -0,0 +1,137 @@
+use super::{
+    spawn_in_current_span, ProcessController, ProcessKillAndWaitError,
+    SendingCancellationSignalToProcessError,
+};
+
+/// Returned by ```ControllerGroup::cancel_all``` for every controller in the group.
+#[derive(Debug)]
+pub struct ControllerGroupCancelResult {
+    pub given_id: String,
+    pub result: Result<Option<ProcessKillAndWaitError>, SendingCancellationSignalToProcessError>,
+}
+
+/// Owns the controllers of all processes that belong together, e.g. the venv, pip and locust processes of a project.
+/// Correctness: ```cancel_all``` cancels every controller at the same time, so the grace periods of the processes overlap.
+/// Processes that already terminated or never started are reported with their error, the others are not affected by it.
+#[derive(Default)]
+pub struct ControllerGroup {
+    controllers: Vec<ProcessController>,
+}
+
+impl ControllerGroup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, controller: ProcessController) {
+        self.controllers.push(controller);
+    }
+
+    pub fn len(&self) -> usize {
+        self.controllers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.controllers.is_empty()
+    }
+
+    /// Cancels all processes and waits for them to terminate.
+    /// The results are in the order the controllers were pushed. The controllers stay in the group.
+    pub async fn cancel_all(&mut self) -> Vec<ControllerGroupCancelResult> {
+        let handles: Vec<_> = self
+            .controllers
+            .drain(..)
+            .map(|mut controller| {
+                spawn_in_current_span(async move {
+                    let result = controller.cancel().await;
+                    (controller, result)
+                })
+            })
+            .collect();
+
+        let mut results = Vec::with_capacity(handles.len());
+        for handle in handles {
+            match handle.await {
+                Ok((controller, result)) => {
+                    results.push(ControllerGroupCancelResult {
+                        given_id: controller.given_id.clone(),
+                        result,
+                    });
+                    self.controllers.push(controller);
+                }
+                Err(join_error) => {
+                    tracing::error!(%join_error, "Cancelling a process of the group panicked");
+                }
+            }
+        }
+
+        results
+    }
+}
+
+#[cfg(test)]
+#[cfg(unix)]
+mod tests {
+    use super::*;
+    use crate::project_managers::process::{
+        KilledTerminationStatus, ProcessBuilder, Status, TerminationStatus,
+    };
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn cancel_all_and_expect_running_processes_killed_and_terminated_reported() {
+        let mut group = ControllerGroup::new();
+        let mut handles = Vec::new();
+
+        for (name, script) in [
+            ("first", "sleep 10"),
+            ("second", "sleep 10"),
+            ("third", "exit 0"),
+        ] {
+            let (mut process, controller) = ProcessBuilder::new(name, name)
+                .program("bash")
+                .args(["-c", script])
+                .build()
+                .expect("Error building process.");
+            handles.push(tokio::spawn(async move { process.run_built().await }));
+
+            if script == "exit 0" {
+                controller.wait_until_terminated().await;
+            } else {
+                controller
+                    .wait_until_running()
+                    .await
+                    .expect("Process should be running.");
+            }
+            group.push(controller);
+        }
+
+        let results = group.cancel_all().await;
+
+        let given_ids: Vec<&str> = results
+            .iter()
+            .map(|result| result.given_id.as_str())
+            .collect();
+        assert_eq!(given_ids, vec!["first", "second", "third"]);
+
+        assert!(matches!(results[0].result, Ok(None)));
+        assert!(matches!(results[1].result, Ok(None)));
+        assert!(matches!(
+            results[2].result,
+            Err(SendingCancellationSignalToProcessError::ProcessTerminated)
+        ));
+        assert_eq!(group.len(), 3);
+
+        for handle in handles.into_iter().take(2) {
+            let result = handle.await.expect("Error joining process.");
+            assert!(matches!(
+                result,
+                Ok(Status::Terminated(TerminationStatus::Killed(
+                    KilledTerminationStatus::KilledByCancellationSignal,
+                    _
+                )))
+            ));
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use super::Status;
+
+type HookFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+type PreSpawnHook = Arc<dyn Fn(HookContext) -> HookFuture + Send + Sync>;
+type PostExitHook = Arc<dyn Fn(HookContext, Status) -> HookFuture + Send + Sync>;
+
+/// Identifies the process a hook is called for.
+#[derive(Debug, Clone)]
+pub struct HookContext {
+    pub given_id: String,
+    pub given_name: String,
+}
+
+/// Async callbacks around every spawned os process, e.g. for audit logging or cleaning up temp dirs.
+/// Correctness: The hooks are awaited by the process, a slow hook delays spawning or returning the status.
+/// ```post_exit``` is only called for os processes that were spawned, with the status they terminated with.
+/// With ```Process::run_with_retry``` both hooks are called for every attempt.
+#[derive(Clone, Default)]
+pub struct ProcessHooks {
+    pre_spawn: Option<PreSpawnHook>,
+    post_exit: Option<PostExitHook>,
+}
+
+impl ProcessHooks {
+    #[must_use]
+    pub fn pre_spawn<F, Fut>(mut self, pre_spawn: F) -> Self
+    where
+        F: Fn(HookContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.pre_spawn = Some(Arc::new(move |context| Box::pin(pre_spawn(context))));
+        self
+    }
+
+    #[must_use]
+    pub fn post_exit<F, Fut>(mut self, post_exit: F) -> Self
+    where
+        F: Fn(HookContext, Status) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.post_exit = Some(Arc::new(move |context, status| {
+            Box::pin(post_exit(context, status))
+        }));
+        self
+    }
+
+    pub(super) async fn call_pre_spawn(&self, context: HookContext) {
+        if let Some(pre_spawn) = &self.pre_spawn {
+            pre_spawn(context).await;
+        }
+    }
+
+    pub(super) async fn call_post_exit(&self, context: HookContext, status: Status) {
+        if let Some(post_exit) = &self.post_exit {
+            post_exit(context, status).await;
+        }
+    }
+}
+
+impl std::fmt::Debug for ProcessHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProcessHooks")
+            .field("pre_spawn", &self.pre_spawn.is_some())
+            .field("post_exit", &self.post_exit.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,54 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::sync::Notify;
+
+/// Watches the output of a process.
+/// Correctness: Every line on stdout or stderr counts as output.
+/// Both streams are piped while the watchdog is active, even if there is no sender for them.
+#[derive(Clone)]
+pub struct IdleTimeout {
+    /// Maximum time without output.
+    pub duration: Duration,
+    pub action: IdleTimeoutAction,
+}
+
+#[derive(Clone)]
+pub enum IdleTimeoutAction {
+    /// Kills the process. Reported as ```KilledByIdleTimeout```.
+    Kill,
+    /// Calls the callback every time the process has been silent for ```IdleTimeout::duration```.
+    Notify(Arc<dyn Fn() + Send + Sync>),
+}
+
+impl std::fmt::Debug for IdleTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let action = match self.action {
+            IdleTimeoutAction::Kill => "Kill",
+            IdleTimeoutAction::Notify(_) => "Notify",
+        };
+
+        f.debug_struct("IdleTimeout")
+            .field("duration", &self.duration)
+            .field("action", &action)
+            .finish()
+    }
+}
+
+/// Resolves once the process should be killed for being silent.
+/// Never resolves for ```IdleTimeoutAction::Notify```.
+pub(super) async fn wait_for_idle_timeout(idle_timeout: IdleTimeout, output_activity: Arc<Notify>) {
+    loop {
+        tokio::select! {
+            _ = output_activity.notified() => {}
+            _ = tokio::time::sleep(idle_timeout.duration) => {
+                match &idle_timeout.action {
+                    IdleTimeoutAction::Kill => return,
+                    IdleTimeoutAction::Notify(callback) => {
+                        tracing::warn!(duration = ?idle_timeout.duration, "Os process is silent");
+                        callback();
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use super::lines::READ_BUFFER_CAPACITY;
+
+/// Buffer sizes, the flush strategy and the recording of the output of a process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessIoConfig {
+    /// Initial size of the buffer a stream is read into. Lines longer than this grow the buffer.
+    pub read_buffer_capacity: usize,
+    /// Size of the buffer in front of ```OsProcessArgs::stdout_file``` and ```OsProcessArgs::stderr_file```.
+    /// A full buffer is always flushed, regardless of the flush policy.
+    pub file_buffer_capacity: usize,
+    /// Capacity of the channels created by the process itself, e.g. by ```Process::run_with_captured_output```.
+    pub channel_capacity: usize,
+    /// When the capture files are flushed.
+    pub flush_policy: FlushPolicy,
+    /// Records the lines forwarded to the sinks as events, see ```ProcessController::events_since```.
+    /// Off by default, the lines of a chatty process would push its other events out of the bounded log.
+    pub record_output: bool,
+}
+
+impl Default for ProcessIoConfig {
+    fn default() -> Self {
+        Self {
+            read_buffer_capacity: READ_BUFFER_CAPACITY,
+            file_buffer_capacity: 8 * 1024,
+            channel_capacity: 100,
+            flush_policy: FlushPolicy::default(),
+            record_output: false,
+        }
+    }
+}
+
+/// When a capture file hands its buffered lines to the os. A capture file is always flushed once its stream ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlushPolicy {
+    /// After every line. The most up to date, but every line costs a write.
+    EveryLine,
+    /// Once at least the given number of bytes is buffered.
+    /// Correctness: Lines of a stream that stays silent are not flushed until the stream ends.
+    EveryBytes(usize),
+    /// Once the stream was silent for the given duration.
+    Interval(Duration),
+}
+
+impl Default for FlushPolicy {
+    fn default() -> Self {
+        Self::Interval(Duration::from_secs(1))
+    }
+}
+
+impl FlushPolicy {
+    /// Whether a capture file with ```buffered``` unflushed bytes is flushed right after a line.
+    pub(super) fn flush_after_line(&self, buffered: usize) -> bool {
+        match self {
+            Self::EveryLine => true,
+            Self::EveryBytes(bytes) => buffered >= *bytes,
+            Self::Interval(_) => false,
+        }
+    }
+
+    /// How long a stream may stay silent before the capture files are flushed.
+    pub(super) fn silence_interval(&self) -> Option<Duration> {
+        match self {
+            Self::Interval(interval) => Some(*interval),
+            Self::EveryLine | Self::EveryBytes(_) => None,
+        }
+    }
+}
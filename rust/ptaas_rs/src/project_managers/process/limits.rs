@@ -0,0 +1,362 @@
+use std::{io::Error as IoError, process::ExitStatus, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, PidExt, ProcessExt, System, SystemExt};
+use tokio::{
+    process::{Child, Command},
+    sync::oneshot,
+};
+
+use super::spawn_in_current_span;
+
+/// How often the memory usage of a process tree is checked against ```ResourceLimits::max_memory```.
+const MEMORY_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+/// The measured cpu time is sampled per scheduler tick, it may be a few ticks below the cpu time the os compared to the limit.
+const CPU_TIME_MEASUREMENT_TOLERANCE: Duration = Duration::from_millis(100);
+
+/// Limits applied to a spawned os process.
+/// Correctness: ```max_memory``` is enforced by the os for every single process on unix: ```RLIMIT_DATA```,
+/// and for every single process and the whole process tree on windows: a Job Object.
+/// An allocation past the limit fails, so the process usually exits with an error instead of being killed.
+/// On top of that, this library polls the whole process tree as a fallback,
+/// the process is killed once the sum of the resident memory of the process and its descendants exceeds the limit.
+/// ```max_cpu_time``` is enforced by the os: ```RLIMIT_CPU``` on unix and a Job Object on windows.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceLimits {
+    /// Maximum memory in bytes, of every single process and of the whole process tree.
+    pub max_memory: Option<u64>,
+    /// Maximum cpu time the process may consume.
+    pub max_cpu_time: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ResourceLimitKind {
+    Memory,
+    CpuTime,
+}
+
+impl ResourceLimits {
+    /// Applies the os enforced limits to the command before it is spawned.
+    pub(super) fn apply_to_command(&self, command: &mut Command) {
+        #[cfg(unix)]
+        {
+            let cpu_time_limit = self.max_cpu_time.map(|max_cpu_time| {
+                let (soft_limit, hard_limit) = cpu_time_rlimits(max_cpu_time);
+                libc::rlimit {
+                    rlim_cur: soft_limit as libc::rlim_t,
+                    rlim_max: hard_limit as libc::rlim_t,
+                }
+            });
+            // Heap and private anonymous mappings, not the shared libraries and mapped files,
+            // which is closer to the resident memory watched by the fallback than ```RLIMIT_AS```.
+            let memory_limit = self.max_memory.map(|max_memory| {
+                let max_memory = libc::rlim_t::try_from(max_memory).unwrap_or(libc::RLIM_INFINITY);
+                libc::rlimit {
+                    rlim_cur: max_memory,
+                    rlim_max: max_memory,
+                }
+            });
+
+            if cpu_time_limit.is_none() && memory_limit.is_none() {
+                return;
+            }
+
+            // Safety: setrlimit is async-signal-safe.
+            unsafe {
+                command.pre_exec(move || {
+                    let limits = [
+                        (libc::RLIMIT_CPU, cpu_time_limit),
+                        (libc::RLIMIT_DATA, memory_limit),
+                    ];
+                    for (resource, limit) in limits {
+                        if let Some(limit) = limit {
+                            if libc::setrlimit(resource, &limit) != 0 {
+                                return Err(IoError::last_os_error());
+                            }
+                        }
+                    }
+                    Ok(())
+                });
+            }
+        }
+
+        #[cfg(not(unix))]
+        let _ = command;
+    }
+
+    /// Applies the os enforced limits to the spawned child.
+    pub(super) fn apply_to_child(&self, child: &Child) -> Result<(), IoError> {
+        #[cfg(windows)]
+        if self.max_cpu_time.is_some() || self.max_memory.is_some() {
+            if let Some(handle) = child.raw_handle() {
+                windows::assign_to_job_with_limits(handle, self)?;
+            }
+        }
+
+        #[cfg(not(windows))]
+        let _ = child;
+
+        Ok(())
+    }
+
+    /// Starts a watchdog for ```max_memory``` if set.
+    /// The returned receiver resolves once the limit is exceeded.
+    pub(super) fn watch(&self, pid: u32) -> Option<oneshot::Receiver<ResourceLimitKind>> {
+        let max_memory = self.max_memory?;
+        let (sender, receiver) = oneshot::channel();
+
+        watch_memory_limit(pid, max_memory, sender);
+
+        Some(receiver)
+    }
+
+    /// Checks if the os terminated the process because of a limit.
+    /// ```exit_cpu_time``` is the cpu time the process consumed, see ```exit_cpu_time```.
+    /// Correctness: SIGKILL is sent by the os on the hard cpu time limit, but also by the oom killer, ```kill -9``` etc.
+    /// It only counts if the process consumed the hard limit, an unknown cpu time does not count.
+    pub(super) fn exceeded_limit_on_exit_status(
+        &self,
+        exit_status: &ExitStatus,
+        exit_cpu_time: Option<Duration>,
+    ) -> Option<ResourceLimitKind> {
+        let max_cpu_time = self.max_cpu_time?;
+
+        #[cfg(unix)]
+        match super::signals::terminating_signal(exit_status) {
+            Some(libc::SIGXCPU) => return Some(ResourceLimitKind::CpuTime),
+            Some(libc::SIGKILL) => {
+                let (_, hard_limit) = cpu_time_rlimits(max_cpu_time);
+                if exit_cpu_time.is_some_and(|exit_cpu_time| {
+                    exit_cpu_time + CPU_TIME_MEASUREMENT_TOLERANCE
+                        >= Duration::from_secs(hard_limit)
+                }) {
+                    return Some(ResourceLimitKind::CpuTime);
+                }
+            }
+            _ => {}
+        }
+
+        #[cfg(not(unix))]
+        let _ = (max_cpu_time, exit_cpu_time);
+
+        #[cfg(windows)]
+        if let Some(windows::ERROR_NOT_ENOUGH_QUOTA) = exit_status.code() {
+            return Some(ResourceLimitKind::CpuTime);
+        }
+
+        None
+    }
+}
+
+/// The soft and the hard ```RLIMIT_CPU``` in seconds for ```max_cpu_time```.
+/// SIGXCPU is sent on the soft limit, SIGKILL on the hard limit.
+fn cpu_time_rlimits(max_cpu_time: Duration) -> (u64, u64) {
+    let soft_limit = max_cpu_time.as_secs().max(1);
+
+    (soft_limit, soft_limit + 1)
+}
+
+/// Waits for the os process to terminate, without reaping it, and returns the cpu time it consumed.
+/// ```None``` if it is unknown, e.g. the process was reaped already, the kernel has no pidfds or not on linux.
+/// Correctness: Must complete before the process is waited for, ```Child::wait``` reaps it.
+/// The process is awaited through a pidfd on the runtime, no thread is blocked while it runs.
+pub(super) async fn exit_cpu_time(pid: u32) -> Option<Duration> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::wait_for_exit_cpu_time(pid).await
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+fn watch_memory_limit(pid: u32, max_memory: u64, sender: oneshot::Sender<ResourceLimitKind>) {
+    spawn_in_current_span(async move {
+        let pid = Pid::from_u32(pid);
+        let mut system = System::new();
+        let mut interval = tokio::time::interval(MEMORY_CHECK_INTERVAL);
+        let mut sender = sender;
+
+        tracing::debug!(%pid, max_memory, "Starting to watch memory limit");
+        loop {
+            tokio::select! {
+                _ = sender.closed() => break,
+                _ = interval.tick() => {}
+            }
+
+            let Some(memory) = process_tree_memory(&mut system, pid) else {
+                break;
+            };
+
+            if memory > max_memory {
+                tracing::warn!(%pid, memory, max_memory, "Os process exceeded memory limit");
+                let _ = sender.send(ResourceLimitKind::Memory);
+                break;
+            }
+        }
+        tracing::debug!(%pid, "Finished watching memory limit");
+    });
+}
+
+/// Sum of the resident memory of the process and all its descendants.
+/// ```None``` if the process does not exist anymore.
+fn process_tree_memory(system: &mut System, pid: Pid) -> Option<u64> {
+    system.refresh_processes();
+
+    let root_memory = system.process(pid)?.memory();
+
+    let descendants_memory: u64 = system
+        .processes()
+        .values()
+        .filter(|process| is_descendant_of(system, process, pid))
+        .map(|process| process.memory())
+        .sum();
+
+    Some(root_memory + descendants_memory)
+}
+
+fn is_descendant_of(system: &System, process: &sysinfo::Process, ancestor: Pid) -> bool {
+    let mut parent = process.parent();
+
+    while let Some(parent_pid) = parent {
+        if parent_pid == ancestor {
+            return true;
+        }
+        parent = system
+            .process(parent_pid)
+            .and_then(|process| process.parent());
+    }
+
+    false
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::{
+        os::fd::{FromRawFd, OwnedFd},
+        time::Duration,
+    };
+
+    use tokio::io::{unix::AsyncFd, Interest};
+
+    /// The pidfd of the process becomes readable once it terminated, its rusage is then read without reaping it.
+    /// The rusage of ```waitid``` includes the reaped children of the process, ```RLIMIT_CPU``` does not,
+    /// so the cpu time may be greater than the one the os compared to the limit.
+    pub(super) async fn wait_for_exit_cpu_time(pid: u32) -> Option<Duration> {
+        let pidfd = AsyncFd::with_interest(pidfd_open(pid)?, Interest::READABLE).ok()?;
+        let _guard = pidfd.readable().await.ok()?;
+
+        // Safety: waitid only writes to info and rusage. WNOWAIT leaves the process waitable,
+        // the pid is not reused until the process is reaped.
+        // The libc wrapper of waitid has no rusage argument.
+        unsafe {
+            let mut info: libc::siginfo_t = std::mem::zeroed();
+            let mut rusage: libc::rusage = std::mem::zeroed();
+            let result = libc::syscall(
+                libc::SYS_waitid,
+                libc::P_PID,
+                pid as libc::id_t,
+                &mut info as *mut libc::siginfo_t,
+                libc::WEXITED | libc::WNOWAIT | libc::WNOHANG,
+                &mut rusage as *mut libc::rusage,
+            );
+            if result != 0 || info.si_pid() != pid as libc::pid_t {
+                return None;
+            }
+
+            Some(timeval_to_duration(rusage.ru_utime) + timeval_to_duration(rusage.ru_stime))
+        }
+    }
+
+    /// ```None``` if the process does not exist or the kernel is older than 5.3.
+    fn pidfd_open(pid: u32) -> Option<OwnedFd> {
+        // Safety: pidfd_open has no pointer arguments, the returned fd is owned by the caller.
+        unsafe {
+            let fd = libc::syscall(libc::SYS_pidfd_open, pid as libc::pid_t, 0);
+            if fd < 0 {
+                return None;
+            }
+
+            Some(OwnedFd::from_raw_fd(fd as libc::c_int))
+        }
+    }
+
+    fn timeval_to_duration(timeval: libc::timeval) -> Duration {
+        Duration::from_secs(u64::try_from(timeval.tv_sec).unwrap_or(0))
+            + Duration::from_micros(u64::try_from(timeval.tv_usec).unwrap_or(0))
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::{io::Error as IoError, os::windows::io::RawHandle};
+
+    use windows_sys::Win32::{
+        Foundation::CloseHandle,
+        System::JobObjects::{
+            AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+            SetInformationJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+            JOB_OBJECT_LIMIT_JOB_MEMORY, JOB_OBJECT_LIMIT_PROCESS_MEMORY,
+            JOB_OBJECT_LIMIT_PROCESS_TIME,
+        },
+    };
+
+    use super::ResourceLimits;
+
+    /// Exit code of processes terminated by a Job Object for exceeding their cpu time.
+    pub(super) const ERROR_NOT_ENOUGH_QUOTA: i32 = 1816;
+
+    /// All the limits are set on one job object, a process can only be assigned to a job object once
+    /// on windows versions without nested jobs. The processes spawned by the process are in the job object as well,
+    /// so ```max_memory``` limits every single process and the committed memory of the whole job.
+    /// The job object lives as long as the assigned processes, so the handle is closed right away.
+    pub(super) fn assign_to_job_with_limits(
+        process_handle: RawHandle,
+        limits: &ResourceLimits,
+    ) -> Result<(), IoError> {
+        // Safety: the process handle is valid as long as the child is not dropped.
+        unsafe {
+            let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+            if job == 0 {
+                return Err(IoError::last_os_error());
+            }
+
+            let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+            if let Some(max_cpu_time) = limits.max_cpu_time {
+                info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PROCESS_TIME;
+                // In 100-nanosecond ticks.
+                info.BasicLimitInformation.PerProcessUserTimeLimit =
+                    (max_cpu_time.as_nanos() / 100) as i64;
+            }
+            if let Some(max_memory) = limits.max_memory {
+                let max_memory = usize::try_from(max_memory).unwrap_or(usize::MAX);
+                info.BasicLimitInformation.LimitFlags |=
+                    JOB_OBJECT_LIMIT_PROCESS_MEMORY | JOB_OBJECT_LIMIT_JOB_MEMORY;
+                info.ProcessMemoryLimit = max_memory;
+                info.JobMemoryLimit = max_memory;
+            }
+
+            let result = if SetInformationJobObject(
+                job,
+                JobObjectExtendedLimitInformation,
+                &info as *const _ as *const _,
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            ) == 0
+                || AssignProcessToJobObject(job, process_handle as isize) == 0
+            {
+                Err(IoError::last_os_error())
+            } else {
+                Ok(())
+            };
+
+            CloseHandle(job);
+
+            result
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::io::Error as IoError;
+
+use bytes::{Bytes, BytesMut};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Default of ```ProcessIoConfig::read_buffer_capacity```.
+pub(super) const READ_BUFFER_CAPACITY: usize = 8 * 1024;
+
+const MIN_READ_CAPACITY: usize = 1024;
+
+/// Splits a stream into lines without allocating a new buffer per line.
+/// Correctness: Every line is a view into the shared read buffer. Its memory is reused once all views into it are dropped.
+/// A line ends with ```\n``` or ```\r\n```, which is not part of the line. The last line does not need a line ending.
+/// Invalid UTF-8 is kept as is, unlike ```AsyncBufReadExt::lines```, which fails on it.
+pub(super) struct LineReader<T> {
+    stdio: T,
+    buffer: BytesMut,
+    /// Reserved whenever the buffer runs out of space.
+    capacity: usize,
+    /// Where to continue searching for ```\n```, so a long line is not searched again after every read.
+    searched: usize,
+    eof: bool,
+}
+
+impl<T: AsyncRead + Unpin> LineReader<T> {
+    pub(super) fn new(stdio: T, capacity: usize) -> Self {
+        Self {
+            stdio,
+            buffer: BytesMut::with_capacity(capacity),
+            capacity,
+            searched: 0,
+            eof: false,
+        }
+    }
+
+    /// ```None``` once the stream ended.
+    /// Correctness: Cancel safe, read bytes stay in the buffer until their line is complete.
+    pub(super) async fn next_line(&mut self) -> Result<Option<Bytes>, IoError> {
+        loop {
+            if let Some(position) = self.buffer[self.searched..]
+                .iter()
+                .position(|byte| *byte == b'\n')
+            {
+                let line_end = self.searched + position;
+                self.searched = 0;
+
+                let mut line = self.buffer.split_to(line_end + 1);
+                line.truncate(line_end);
+                if line.last() == Some(&b'\r') {
+                    line.truncate(line_end - 1);
+                }
+
+                return Ok(Some(line.freeze()));
+            }
+
+            self.searched = self.buffer.len();
+
+            if self.eof {
+                self.searched = 0;
+
+                if self.buffer.is_empty() {
+                    return Ok(None);
+                }
+
+                return Ok(Some(self.buffer.split().freeze()));
+            }
+
+            // Reading into a nearly full buffer would read only a few bytes at a time.
+            if self.buffer.capacity() - self.buffer.len() < MIN_READ_CAPACITY {
+                self.buffer.reserve(self.capacity.max(MIN_READ_CAPACITY));
+            }
+
+            if self.stdio.read_buf(&mut self.buffer).await? == 0 {
+                self.eof = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_test::traced_test;
+
+    async fn read_all_lines(input: &'static [u8]) -> Vec<Bytes> {
+        let mut reader = LineReader::new(input, READ_BUFFER_CAPACITY);
+        let mut lines = Vec::new();
+
+        while let Some(line) = reader.next_line().await.expect("Error reading line.") {
+            lines.push(line);
+        }
+
+        lines
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn split_lines_and_strip_line_endings() {
+        let lines = read_all_lines(b"first\nsecond\r\n\nlast").await;
+
+        assert_eq!(lines, vec!["first", "second", "", "last"]);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn keep_invalid_utf8_and_lines_longer_than_the_buffer() {
+        let long_line: &'static [u8] = Box::leak(
+            [
+                vec![b'a'; READ_BUFFER_CAPACITY * 3],
+                b"\n\xff\xfe\n".to_vec(),
+            ]
+            .concat()
+            .into_boxed_slice(),
+        );
+
+        let lines = read_all_lines(long_line).await;
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), READ_BUFFER_CAPACITY * 3);
+        assert_eq!(lines[1], &b"\xff\xfe"[..]);
+    }
+}
@@ -0,0 +1,71 @@
+use std::time::{Duration, SystemTime};
+
+use sysinfo::{Pid, PidExt, ProcessExt, System, SystemExt};
+use tokio::sync::mpsc;
+
+use super::{spawn_in_current_span, Status, StatusHolder};
+
+/// A single resource usage sample of a running os process.
+#[derive(Debug, Clone)]
+pub struct ProcessMetrics {
+    /// CPU usage in percent since the previous sample. Can exceed 100 on multi-core machines.
+    /// The first sample is always 0, since there is no previous sample to compare to.
+    pub cpu_usage: f32,
+    /// Resident set size in bytes.
+    pub memory: u64,
+    /// Number of direct child processes.
+    pub child_process_count: usize,
+    pub sampled_at: SystemTime,
+}
+
+/// Samples the os process with the given ```pid``` every ```interval``` and sends the samples to ```sender```.
+/// Stops if the process terminates, disappears or the receiver is dropped.
+pub(super) fn sample_metrics_periodically(
+    pid: u32,
+    interval: Duration,
+    status_holder: StatusHolder,
+    sender: mpsc::Sender<ProcessMetrics>,
+) {
+    spawn_in_current_span(async move {
+        let pid = Pid::from_u32(pid);
+        let mut system = System::new();
+        let mut interval = tokio::time::interval(interval);
+
+        tracing::debug!(%pid, "Starting to sample metrics");
+        loop {
+            interval.tick().await;
+
+            if let Status::Terminated(_) = status_holder.status().await {
+                break;
+            }
+
+            let Some(metrics) = sample_metrics(&mut system, pid) else {
+                break;
+            };
+
+            if sender.send(metrics).await.is_err() {
+                break;
+            }
+        }
+        tracing::debug!(%pid, "Finished sampling metrics");
+    });
+}
+
+fn sample_metrics(system: &mut System, pid: Pid) -> Option<ProcessMetrics> {
+    system.refresh_processes();
+
+    let process = system.process(pid)?;
+
+    let child_process_count = system
+        .processes()
+        .values()
+        .filter(|process| process.parent() == Some(pid))
+        .count();
+
+    Some(ProcessMetrics {
+        cpu_usage: process.cpu_usage(),
+        memory: process.memory(),
+        child_process_count,
+        sampled_at: SystemTime::now(),
+    })
+}
@@ -0,0 +1,3032 @@
+use std::{
+    ffi::{OsStr, OsString},
+    future::Future,
+    io::Error as IoError,
+    path::{Path, PathBuf},
+    process::{ExitStatus, Stdio},
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use thiserror::Error as ThisError;
+use tokio::{
+    io::AsyncRead,
+    process::Command,
+    sync::{mpsc, oneshot, watch, Mutex, Notify},
+    task::JoinHandle,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug_span, warn_span, Instrument};
+
+mod attach;
+mod backend;
+mod builder;
+mod capture;
+mod chain;
+mod env;
+mod events;
+mod group;
+mod hooks;
+mod idle;
+mod io_config;
+mod limits;
+mod lines;
+mod metrics;
+mod output;
+mod pipeline;
+mod pool;
+mod priority;
+mod privileges;
+mod retry;
+mod sandbox;
+mod sanitize;
+mod signals;
+mod sinks;
+mod spec;
+mod supervisor;
+mod throttle;
+
+pub use attach::ProcessAttachError;
+pub use backend::{BackendChild, FakeBackend, FakeScript, OsBackend, ProcessBackend};
+pub use builder::{ProcessBuilder, ProcessBuilderError};
+pub use chain::{ProcessChain, ProcessChainController, ProcessChainError, ProcessChainStep};
+pub use env::EnvMode;
+pub use events::{ProcessEvent, ProcessEventKind, ProcessEvents};
+pub use group::{ControllerGroup, ControllerGroupCancelResult};
+pub use hooks::{HookContext, ProcessHooks};
+pub use idle::{IdleTimeout, IdleTimeoutAction};
+pub use io_config::{FlushPolicy, ProcessIoConfig};
+pub use limits::{ResourceLimitKind, ResourceLimits};
+pub use metrics::ProcessMetrics;
+pub use output::{capture, Output};
+pub use pipeline::{
+    PipelineResult, PipelineStageResult, ProcessPipeline, ProcessPipelineController,
+};
+pub use pool::{ProcessPool, ProcessPoolJob, ProcessPoolResult};
+pub use priority::ProcessPriority;
+pub use privileges::RunAs;
+pub use retry::{RetriedStatus, RetryBackoff, RetryPolicy};
+pub use sandbox::SandboxOptions;
+pub use sanitize::StripAnsi;
+pub use signals::KillSignal;
+pub use sinks::OutputSink;
+pub use spec::ProcessSpec;
+pub use supervisor::{ProcessRestart, ProcessSupervisor, RestartPolicy, SupervisedProcess};
+pub use throttle::OutputRateLimit;
+
+use attach::AttachedOsProcess;
+use backend::BackendStdio;
+use events::{EventLog, RecordedEventKind};
+use lines::LineReader;
+use sinks::OpenedOutputSink;
+use throttle::Throttle;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Status {
+    Created,
+    Running,
+    /// Suspended by ```ProcessController::pause```.
+    Paused,
+    Terminated(TerminationStatus),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TerminationStatus {
+    /// Carries how the os reported the termination, e.g. the signal that killed the os process.
+    Killed(KilledTerminationStatus, OsExitStatus),
+    TerminatedSuccessfully,
+    TerminatedWithError(TerminationWithErrorStatus),
+    /// The attached os process is gone. It is not a child of this program, so its exit status is unknown.
+    TerminatedWithUnknownExitStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KilledTerminationStatus {
+    /// Explicitly killed by this library.
+    KilledByCancellationSignal,
+    KilledByDroppingController,
+    /// Killed by this library or the os for exceeding a ```ResourceLimits``` limit.
+    KilledByResourceLimit(ResourceLimitKind),
+    /// Killed by this library for exceeding the timeout set with ```ProcessBuilder::timeout```.
+    KilledByTimeout,
+    /// Killed by this library for being silent longer than ```IdleTimeout::duration```.
+    KilledByIdleTimeout,
+    /// Killed by ```Process::shutdown``` after the ```run``` future was dropped.
+    KilledByShutdown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TerminationWithErrorStatus {
+    /// On SIGTERM, the process will exit with UnknownErrorCode.
+    /// On windows, the process will exit with 1. This will be translated to ```Killed``` if ```child_killed_successfuly``` is true.
+    /// On linux and macOS, the process will exit with UnknownErrorCode. This will be translated to ```Killed``` if ```child_killed_successfuly``` is true.
+    /// Otherwise, it will not be translated.
+    /// On unix, a terminating signal is reported as ```TerminatedBySignal``` instead.
+    TerminatedWithUnknownErrorCode,
+    TerminatedWithErrorCode(i32),
+    /// Unix only. The process was terminated by the given signal, e.g. SIGKILL by the OOM killer or SIGSEGV on a crash.
+    TerminatedBySignal(i32),
+}
+
+/// The raw exit status of an os process, as reported by the os.
+/// Both are ```None``` if the os process was never spawned or is not a child of this program, e.g. an attached one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OsExitStatus {
+    /// Set if the os process exited with a code, e.g. 1 for a killed os process on windows.
+    pub code: Option<i32>,
+    /// Unix only. Set if the os process was terminated by a signal, e.g. 9 for SIGKILL.
+    pub signal: Option<i32>,
+}
+
+impl From<&ExitStatus> for OsExitStatus {
+    fn from(exit_status: &ExitStatus) -> Self {
+        Self {
+            code: exit_status.code(),
+            signal: signals::terminating_signal(exit_status),
+        }
+    }
+}
+
+/// Used in ```Process::run``` to pass arguments, to improve readability.
+/// Everything but ```program```, ```args``` and ```current_dir``` is usually left to ```OsProcessArgs::default```.
+#[derive(Debug, Default)]
+pub struct OsProcessArgs<I, S, P> {
+    pub program: S,
+    pub args: I,
+    pub current_dir: P,
+    pub stdout_sender: Option<mpsc::Sender<String>>,
+    pub stderr_sender: Option<mpsc::Sender<String>>,
+    /// Additional destinations for stdout lines, next to ```stdout_sender```.
+    pub stdout_sinks: Vec<OutputSink>,
+    /// Additional destinations for stderr lines, next to ```stderr_sender```.
+    pub stderr_sinks: Vec<OutputSink>,
+    /// Stdout lines are appended to this file through a buffered writer, that survives log rotation.
+    pub stdout_file: Option<PathBuf>,
+    /// Stderr lines are appended to this file through a buffered writer, that survives log rotation.
+    pub stderr_file: Option<PathBuf>,
+    pub limits: ResourceLimits,
+    pub kill_signal: KillSignal,
+    /// Kills the process or notifies if it stays silent for too long.
+    pub idle_timeout: Option<IdleTimeout>,
+    /// Applied before lines are sent to the senders.
+    pub strip_ansi: StripAnsi,
+    /// Leaves the os process running if the ```Process``` or its controller is dropped.
+    /// Correctness: Output is forwarded only as long as this program runs. A detached process that should outlive it
+    /// must not rely on the senders, sinks or capture files, writing to a closed pipe fails.
+    pub detached: bool,
+    /// Unix only. Runs the os process as another user, see ```RunAs```.
+    pub run_as: Option<RunAs>,
+    /// Linux only. Runs the os process in a bubblewrap sandbox, see ```SandboxOptions```.
+    pub sandbox: Option<SandboxOptions>,
+    /// Os scheduling priority of the os process, see ```ProcessPriority```.
+    pub priority: ProcessPriority,
+    /// Which variables of this program's environment the os process inherits, see ```EnvMode```.
+    pub env_mode: EnvMode,
+    /// Called right before the os process is spawned and right after it terminated, see ```ProcessHooks```.
+    pub hooks: ProcessHooks,
+    /// Spawns the os process, ```None``` spawns a real one with ```OsBackend```.
+    pub backend: Option<Arc<dyn ProcessBackend>>,
+    /// Applied after ```strip_ansi```, see ```OutputRateLimit```.
+    pub rate_limit: OutputRateLimit,
+    /// Buffer sizes and the flush strategy of the output, see ```ProcessIoConfig```.
+    pub io_config: ProcessIoConfig,
+    /// Set in addition to the inherited variables, see ```EnvMode```.
+    pub envs: Vec<(OsString, OsString)>,
+}
+
+/// Owned version of ```OsProcessArgs```, with everything ```ProcessBuilder``` can configure.
+#[derive(Debug)]
+struct ProcessConfig {
+    program: OsString,
+    args: Vec<OsString>,
+    envs: Vec<(OsString, OsString)>,
+    current_dir: PathBuf,
+    stdout_sender: Option<mpsc::Sender<String>>,
+    stderr_sender: Option<mpsc::Sender<String>>,
+    stdout_sinks: Vec<OutputSink>,
+    stderr_sinks: Vec<OutputSink>,
+    stdout_file: Option<PathBuf>,
+    stderr_file: Option<PathBuf>,
+    limits: ResourceLimits,
+    kill_signal: KillSignal,
+    timeout: Option<Duration>,
+    idle_timeout: Option<IdleTimeout>,
+    strip_ansi: StripAnsi,
+    detached: bool,
+    run_as: Option<RunAs>,
+    sandbox: Option<SandboxOptions>,
+    priority: ProcessPriority,
+    env_mode: EnvMode,
+    hooks: ProcessHooks,
+    backend: Option<Arc<dyn ProcessBackend>>,
+    rate_limit: OutputRateLimit,
+    io_config: ProcessIoConfig,
+    /// Set by ```ProcessPipeline```. Receives the stdout of the previous stage.
+    stdin_pipe: Option<oneshot::Receiver<Stdio>>,
+    /// Set by ```ProcessPipeline```. Sends the stdout to the next stage, instead of forwarding it to ```stdout_sender```.
+    stdout_pipe: Option<oneshot::Sender<Stdio>>,
+}
+
+impl<I, S, P> From<OsProcessArgs<I, S, P>> for ProcessConfig
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+    P: AsRef<Path>,
+{
+    fn from(os_process_args: OsProcessArgs<I, S, P>) -> Self {
+        Self {
+            program: os_process_args.program.as_ref().to_owned(),
+            args: os_process_args
+                .args
+                .into_iter()
+                .map(|arg| arg.as_ref().to_owned())
+                .collect(),
+            envs: os_process_args.envs,
+            current_dir: os_process_args.current_dir.as_ref().to_owned(),
+            stdout_sender: os_process_args.stdout_sender,
+            stderr_sender: os_process_args.stderr_sender,
+            stdout_sinks: os_process_args.stdout_sinks,
+            stderr_sinks: os_process_args.stderr_sinks,
+            stdout_file: os_process_args.stdout_file,
+            stderr_file: os_process_args.stderr_file,
+            limits: os_process_args.limits,
+            kill_signal: os_process_args.kill_signal,
+            timeout: None,
+            idle_timeout: os_process_args.idle_timeout,
+            strip_ansi: os_process_args.strip_ansi,
+            detached: os_process_args.detached,
+            run_as: os_process_args.run_as,
+            sandbox: os_process_args.sandbox,
+            priority: os_process_args.priority,
+            env_mode: os_process_args.env_mode,
+            hooks: os_process_args.hooks,
+            backend: os_process_args.backend,
+            rate_limit: os_process_args.rate_limit,
+            io_config: os_process_args.io_config,
+            stdin_pipe: None,
+            stdout_pipe: None,
+        }
+    }
+}
+
+/// Conveniently holding a ```watch::Sender<Status>``` to hide **ugly** operations.
+/// Correctness: Reading the status never waits for a writer. Changes are serialized by ```changing```,
+/// so the events are recorded in the order of the changes.
+#[derive(Clone)]
+struct StatusHolder {
+    /// The sender lives as long as any holder, so receivers never see it closed.
+    status: Arc<watch::Sender<Status>>,
+    changing: Arc<Mutex<()>>,
+    /// Every status change is recorded here.
+    events: EventLog,
+}
+
+impl StatusHolder {
+    fn new() -> Self {
+        let (status, _) = watch::channel(Status::Created);
+
+        Self {
+            status: Arc::new(status),
+            changing: Arc::new(Mutex::new(())),
+            events: EventLog::default(),
+        }
+    }
+
+    async fn overwrite(&self, status: Status) {
+        let _changing_guard = self.changing.lock().await;
+        self.events.push_status(&status);
+        self.status.send_replace(status);
+    }
+
+    async fn status(&self) -> Status {
+        self.status.borrow().clone()
+    }
+
+    /// Resolves with the first status, including the current one, that matches ```predicate```.
+    /// Intermediate statuses that change faster than they are observed may be skipped.
+    async fn wait_for(&self, mut predicate: impl FnMut(&Status) -> bool) -> Status {
+        let mut receiver = self.status.subscribe();
+
+        let status = match receiver.wait_for(|status| predicate(status)).await {
+            Ok(status) => status.clone(),
+            // The sender is owned by this holder and can not be dropped.
+            Err(_) => self.status.borrow().clone(),
+        };
+
+        status
+    }
+}
+
+pub struct ProcessController {
+    status_holder: StatusHolder,
+    /// Set once the os process is spawned, 0 otherwise.
+    pid: Arc<AtomicU32>,
+    /// Set once a detached os process is spawned.
+    detached: Arc<AtomicBool>,
+    given_id: String,
+    /// Option so we can take it. Sends a cancellation signal to the process.
+    cancel_channel_sender: Option<oneshot::Sender<()>>,
+    /// Option so we can take it. Receives the cancellation result from the process.
+    cancel_status_channel_receiver: Option<oneshot::Receiver<Option<ProcessKillAndWaitError>>>,
+}
+
+impl ProcessController {
+    pub async fn cancel(
+        &mut self,
+    ) -> Result<Option<ProcessKillAndWaitError>, SendingCancellationSignalToProcessError> {
+        let debug_span = debug_span!("ProcessController::cancel", given_id = self.given_id);
+        let warn_span = warn_span!("ProcessController::cancel", given_id = self.given_id);
+
+        let _debug_span_guard = debug_span.enter();
+        let _warn_span_guard = warn_span.enter();
+
+        match self.status_holder.status().await {
+            Status::Created => {
+                tracing::debug!("Process has not started yet");
+                return Err(SendingCancellationSignalToProcessError::ProcessNotRunning);
+            }
+            Status::Terminated(_) => {
+                tracing::debug!("Process is already terminated");
+                return Err(SendingCancellationSignalToProcessError::ProcessTerminated);
+            }
+            Status::Running | Status::Paused => {}
+        }
+
+        let cancel_channel_sender = self
+            .cancel_channel_sender
+            .take()
+            .ok_or(SendingCancellationSignalToProcessError::AlreayTriedToCancel)?;
+
+        let cancel_channel_receiver = self
+            .cancel_status_channel_receiver
+            .take()
+            .ok_or(SendingCancellationSignalToProcessError::AlreayTriedToCancel)?;
+
+        tracing::debug!("Sending cancellation signal to process");
+        cancel_channel_sender.send(()).map_err(|_| {
+            tracing::warn!("Failed to send cancellation signal to process");
+            SendingCancellationSignalToProcessError::ProcessTerminated
+        })?;
+
+        tracing::debug!("Waiting for process to terminate");
+        let cencel_result = cancel_channel_receiver.await.map_err(|_| {
+            tracing::warn!("Failed to wait for process to terminate");
+            SendingCancellationSignalToProcessError::ProcessTerminated
+        })?;
+
+        tracing::debug!("Process terminated");
+
+        Ok(cencel_result)
+    }
+
+    /// Kills the process, if it is running, and waits for it to terminate.
+    /// Correctness: Unlike ```cancel```, a process that is not running is not an error.
+    /// A process that has not been started yet is not affected and can still be run.
+    /// A detached process is left running, use ```cancel``` to kill it.
+    pub async fn shutdown(&mut self) -> Result<(), ProcessKillAndWaitError> {
+        if self.is_detached() {
+            tracing::debug!(given_id = self.given_id, "Detached process is left running");
+            return Ok(());
+        }
+
+        match self.cancel().await {
+            Ok(None) => Ok(()),
+            Ok(Some(kill_and_wait_error)) => Err(kill_and_wait_error),
+            Err(
+                SendingCancellationSignalToProcessError::ProcessNotRunning
+                | SendingCancellationSignalToProcessError::AlreayTriedToCancel
+                | SendingCancellationSignalToProcessError::ProcessTerminated,
+            ) => Ok(()),
+        }
+    }
+
+    pub async fn status(&self) -> Status {
+        self.status_holder.status().await
+    }
+
+    /// Resolves once the status matches ```predicate```, right away if it already does.
+    /// Correctness: Only the latest status is observed, a status that is replaced before it is observed is skipped.
+    pub async fn wait_until(&self, predicate: impl FnMut(&Status) -> bool) -> Status {
+        self.status_holder.wait_for(predicate).await
+    }
+
+    /// Resolves once the process is running, e.g. instead of sleeping before ```cancel``` or ```pid```.
+    /// Returns the termination status instead, if the process terminated before it was observed running.
+    /// Correctness: During the backoff of ```Process::run_with_retry``` the status is running, but there is no pid.
+    pub async fn wait_until_running(&self) -> Result<(), TerminationStatus> {
+        let status = self
+            .wait_until(|status| matches!(status, Status::Running | Status::Terminated(_)))
+            .await;
+
+        match status {
+            Status::Terminated(termination_status) => Err(termination_status),
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn wait_until_terminated(&self) -> TerminationStatus {
+        let status = self
+            .wait_until(|status| matches!(status, Status::Terminated(_)))
+            .await;
+
+        match status {
+            Status::Terminated(termination_status) => termination_status,
+            _ => unreachable!("Only a terminated status matches"),
+        }
+    }
+
+    /// Suspends the running os process. Its children are not suspended.
+    pub async fn pause(&self) -> Result<(), PauseOrResumeProcessError> {
+        let debug_span = debug_span!("ProcessController::pause", given_id = self.given_id);
+        let _debug_span_guard = debug_span.enter();
+
+        // Holding the lock, so the process can not terminate in between.
+        let _changing_guard = self.status_holder.changing.lock().await;
+        match self.status_holder.status().await {
+            Status::Running => {}
+            Status::Paused => return Err(PauseOrResumeProcessError::ProcessAlreadyPaused),
+            Status::Created => return Err(PauseOrResumeProcessError::ProcessNotRunning),
+            Status::Terminated(_) => return Err(PauseOrResumeProcessError::ProcessTerminated),
+        }
+
+        let pid = self
+            .pid()
+            .ok_or(PauseOrResumeProcessError::ProcessNotRunning)?;
+
+        tracing::debug!("Pausing process");
+        signals::suspend(pid).map_err(PauseOrResumeProcessError::CouldNotPauseProcess)?;
+
+        self.status_holder.events.push_status(&Status::Paused);
+        self.status_holder.status.send_replace(Status::Paused);
+
+        Ok(())
+    }
+
+    /// Resumes an os process paused by ```pause```.
+    pub async fn resume(&self) -> Result<(), PauseOrResumeProcessError> {
+        let debug_span = debug_span!("ProcessController::resume", given_id = self.given_id);
+        let _debug_span_guard = debug_span.enter();
+
+        let _changing_guard = self.status_holder.changing.lock().await;
+        match self.status_holder.status().await {
+            Status::Paused => {}
+            Status::Running => return Err(PauseOrResumeProcessError::ProcessNotPaused),
+            Status::Created => return Err(PauseOrResumeProcessError::ProcessNotRunning),
+            Status::Terminated(_) => return Err(PauseOrResumeProcessError::ProcessTerminated),
+        }
+
+        let pid = self
+            .pid()
+            .ok_or(PauseOrResumeProcessError::ProcessNotRunning)?;
+
+        tracing::debug!("Resuming process");
+        signals::resume(pid).map_err(PauseOrResumeProcessError::CouldNotResumeProcess)?;
+
+        self.status_holder.events.push_status(&Status::Running);
+        self.status_holder.status.send_replace(Status::Running);
+
+        Ok(())
+    }
+
+    /// The events recorded after the event with the sequence number ```sequence```.
+    /// Pass 0 to get all events, or the last received sequence number to resume.
+    /// Correctness: The log is bounded, events that were dropped before they were read are counted in ```ProcessEvents::missed```.
+    pub fn events_since(&self, sequence: u64) -> ProcessEvents {
+        self.status_holder.events.since(sequence)
+    }
+
+    /// Whether the os process was spawned with ```OsProcessArgs::detached```.
+    pub fn is_detached(&self) -> bool {
+        self.detached.load(Ordering::SeqCst)
+    }
+
+    /// The id of the os process. ```None``` if the process has not started yet.
+    pub fn pid(&self) -> Option<u32> {
+        match self.pid.load(Ordering::SeqCst) {
+            0 => None,
+            pid => Some(pid),
+        }
+    }
+
+    /// Samples the resource usage of the running os process every ```interval```.
+    /// The returned receiver is closed once the process terminates.
+    pub async fn metrics(
+        &self,
+        interval: Duration,
+    ) -> Result<mpsc::Receiver<ProcessMetrics>, ProcessMetricsError> {
+        let pid = match self.status_holder.status().await {
+            Status::Created => return Err(ProcessMetricsError::ProcessNotRunning),
+            Status::Terminated(_) => return Err(ProcessMetricsError::ProcessTerminated),
+            Status::Running | Status::Paused => {
+                self.pid().ok_or(ProcessMetricsError::ProcessNotRunning)?
+            }
+        };
+
+        let (sender, receiver) = mpsc::channel(10);
+
+        metrics::sample_metrics_periodically(pid, interval, self.status_holder.clone(), sender);
+
+        Ok(receiver)
+    }
+}
+
+/// Wrapper around ```tokio::process::Child```, or another ```BackendChild```, abstracting away the **ugly** details.
+pub struct Process {
+    status_holder: StatusHolder,
+    /// Set once the os process is spawned, 0 otherwise.
+    pid: Arc<AtomicU32>,
+    /// Set once a detached os process is spawned.
+    detached: Arc<AtomicBool>,
+    given_id: String,
+    given_name: String,
+    child_killed_successfuly: bool,
+    controller_dropped: bool,
+    limits: ResourceLimits,
+    kill_signal: KillSignal,
+    /// Set if the process was killed for exceeding a resource limit.
+    exceeded_resource_limit: Option<ResourceLimitKind>,
+    /// Set if the process terminated by itself and its cpu time was measured, see ```limits::exit_cpu_time```.
+    exit_cpu_time: Option<Duration>,
+    timed_out: bool,
+    idle_timed_out: bool,
+    shut_down: bool,
+    /// Option so we can take it. Set by ```ProcessBuilder``` or ```Process::run```.
+    config: Option<ProcessConfig>,
+    /// Option so we can take it. ```None``` if the process has not started yet.
+    child: Option<Box<dyn BackendChild>>,
+    /// Option so we can take it. Receives a notification if the process exceeds a watched resource limit.
+    resource_limit_receiver: Option<oneshot::Receiver<ResourceLimitKind>>,
+    /// Option so we can take it. Set if an ```IdleTimeout``` is configured. Notified on every line of output.
+    idle_timeout_watch: Option<(IdleTimeout, Arc<Notify>)>,
+    /// Option so we can take it. ```None``` if the process has started. Receives the cancellation signal from the controller.
+    cancel_status_channel_sender: Option<oneshot::Sender<Option<ProcessKillAndWaitError>>>,
+    /// Option so we can take it. ```None``` if the process has started. Sends the cancellation result to the controller.
+    cancel_channel_receiver: Option<oneshot::Receiver<()>>,
+    /// Set by ```Process::with_cancellation_token```. Cancels the process like ```ProcessController::cancel```.
+    cancellation_token: Option<CancellationToken>,
+    /// Option so we can take it. Set by ```Process::attach```.
+    attached: Option<AttachedOsProcess>,
+}
+
+impl Drop for Process {
+    fn drop(&mut self) {
+        let child = self.child.take();
+
+        let debug_span = debug_span!("Process::drop", given_id = self.given_id);
+
+        {
+            let _debug_span_guard = debug_span.enter();
+            tracing::debug!("Dropping process");
+        }
+
+        let warn_span = warn_span!("Process::drop", given_id = self.given_id);
+
+        if let Some(mut child) = child {
+            let exited = matches!(child.try_wait(), Ok(Some(_)));
+
+            if self.is_detached() && !exited {
+                let _debug_span_guard = debug_span.enter();
+                tracing::debug!("Detached os process keeps running");
+                return;
+            }
+
+            if !self.child_killed_successfuly && !exited {
+                let kill_and_wait = async move {
+                    tracing::warn!("Os process is being dropped without being shut down first");
+
+                    match child.kill().await {
+                        Ok(_) => {
+                            tracing::debug!("Killed os process");
+                        }
+                        Err(err) => {
+                            tracing::warn!(%err, "Failed to kill os process");
+                        }
+                    }
+
+                    match child.wait().await {
+                        Ok(_) => {
+                            tracing::debug!("Waited for os process to terminate");
+                        }
+                        Err(err) => {
+                            tracing::warn!(%err, "Failed to wait for os process to terminate");
+                        }
+                    }
+
+                    tracing::debug!("Dropping os process");
+                };
+
+                // Correctness: The spans are entered on every poll of the task, never across an await.
+                tokio::spawn(kill_and_wait.instrument(warn_span).instrument(debug_span));
+            }
+        }
+    }
+}
+
+impl Process {
+    #[must_use]
+    pub fn new(given_id: String, given_name: String) -> (Self, ProcessController) {
+        let status_holder = StatusHolder::new();
+        let pid = Arc::new(AtomicU32::new(0));
+        let detached = Arc::new(AtomicBool::new(false));
+
+        let (cancel_status_channel_sender, cancel_status_channel_receiver) = oneshot::channel();
+        let (cancel_channel_sender, cancel_channel_receiver) = oneshot::channel();
+
+        let process = Self {
+            status_holder: status_holder.clone(),
+            pid: pid.clone(),
+            detached: detached.clone(),
+            given_id: given_id.clone(),
+            given_name,
+            child_killed_successfuly: false,
+            controller_dropped: false,
+            limits: ResourceLimits::default(),
+            kill_signal: KillSignal::default(),
+            exceeded_resource_limit: None,
+            exit_cpu_time: None,
+            timed_out: false,
+            idle_timed_out: false,
+            shut_down: false,
+            config: None,
+            child: None,
+            resource_limit_receiver: None,
+            idle_timeout_watch: None,
+            cancel_status_channel_sender: Some(cancel_status_channel_sender),
+            cancel_channel_receiver: Some(cancel_channel_receiver),
+            cancellation_token: None,
+            attached: None,
+        };
+
+        let process_controller = ProcessController {
+            status_holder,
+            pid,
+            detached,
+            given_id,
+            cancel_channel_sender: Some(cancel_channel_sender),
+            cancel_status_channel_receiver: Some(cancel_status_channel_receiver),
+        };
+
+        (process, process_controller)
+    }
+
+    /// Like ```Process::new```, but the process is also cancelled once ```cancellation_token``` is cancelled.
+    /// Correctness: Use child tokens to cancel a whole tree of processes at once.
+    /// The result of a token cancellation is not reported to the controller, the status is ```KilledByCancellationSignal```.
+    /// If the token is cancelled before the process runs, the os process is never spawned.
+    #[must_use]
+    pub fn with_cancellation_token(
+        given_id: String,
+        given_name: String,
+        cancellation_token: CancellationToken,
+    ) -> (Self, ProcessController) {
+        let (mut process, process_controller) = Self::new(given_id, given_name);
+        process.cancellation_token = Some(cancellation_token);
+
+        (process, process_controller)
+    }
+
+    pub async fn run<I, S, P>(
+        &mut self,
+        os_process_args: OsProcessArgs<I, S, P>,
+    ) -> Result<Status, ProcessRunError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+        P: AsRef<Path>,
+    {
+        self.config = Some(os_process_args.into());
+        self.run_built().await
+    }
+
+    /// Runs the process configured by ```ProcessBuilder```.
+    pub async fn run_built(&mut self) -> Result<Status, ProcessRunError> {
+        let debug_span = debug_span!(
+            "Process::run",
+            given_id = self.given_id,
+            given_name = self.given_name
+        );
+        let _span_guard = debug_span.enter();
+
+        let (mut cancel_channel_receiver, mut cancel_channel_sender) = self.take_channels()?;
+
+        let config = self.config.take().ok_or(ProcessRunError::NotConfigured)?;
+
+        self.run_attempt(
+            config,
+            &mut cancel_channel_receiver,
+            &mut cancel_channel_sender,
+        )
+        .await?;
+
+        let status = self.status_holder.status().await;
+
+        Ok(status)
+    }
+
+    /// The sender is wrapped in an option, so it can be taken once the cancellation signal is received.
+    #[allow(clippy::type_complexity)]
+    fn take_channels(
+        &mut self,
+    ) -> Result<
+        (
+            oneshot::Receiver<()>,
+            Option<oneshot::Sender<Option<ProcessKillAndWaitError>>>,
+        ),
+        ProcessRunError,
+    > {
+        let cancel_channel_sender = self
+            .cancel_status_channel_sender
+            .take()
+            .ok_or(ProcessRunError::AlreayTriedToRun)?;
+
+        let cancel_channel_receiver = self
+            .cancel_channel_receiver
+            .take()
+            .ok_or(ProcessRunError::AlreayTriedToRun)?;
+
+        Ok((cancel_channel_receiver, Some(cancel_channel_sender)))
+    }
+
+    /// Spawns the os process and waits for it to terminate. The status is set on termination.
+    async fn run_attempt(
+        &mut self,
+        config: ProcessConfig,
+        cancel_channel_receiver: &mut oneshot::Receiver<()>,
+        cancel_channel_sender: &mut Option<oneshot::Sender<Option<ProcessKillAndWaitError>>>,
+    ) -> Result<(), ProcessRunError> {
+        if self.is_cancellation_token_cancelled() {
+            tracing::debug!("Cancellation token was cancelled before spawning the os process");
+
+            self.status_holder
+                .overwrite(Status::Terminated(TerminationStatus::Killed(
+                    KilledTerminationStatus::KilledByCancellationSignal,
+                    OsExitStatus::default(),
+                )))
+                .await;
+
+            return Ok(());
+        }
+
+        let timeout = config.timeout;
+        let hooks = config.hooks.clone();
+
+        hooks.call_pre_spawn(self.hook_context()).await;
+
+        self.spawn_os_process_and_forward_ios_to_channels(config)
+            .await
+            .map_err(ProcessRunError::CouldNotSpawnOsProcess)?;
+
+        self.wait_for_signal_or_termination(
+            cancel_channel_receiver,
+            cancel_channel_sender,
+            timeout,
+        )
+        .await?;
+
+        hooks
+            .call_post_exit(self.hook_context(), self.status_holder.status().await)
+            .await;
+
+        Ok(())
+    }
+
+    fn hook_context(&self) -> HookContext {
+        HookContext {
+            given_id: self.given_id.clone(),
+            given_name: self.given_name.clone(),
+        }
+    }
+
+    async fn wait_for_signal_or_termination(
+        &mut self,
+        cancel_channel_receiver: &mut oneshot::Receiver<()>,
+        cancel_channel_sender: &mut Option<oneshot::Sender<Option<ProcessKillAndWaitError>>>,
+        timeout: Option<Duration>,
+    ) -> Result<(), ProcessRunError> {
+        let cancellation_token_cancelled = self.cancellation_token_cancelled();
+
+        let idle_timeout_watch = self.idle_timeout_watch.take();
+        let idle_timeout_elapsed = async move {
+            match idle_timeout_watch {
+                Some((idle_timeout, output_activity)) => {
+                    idle::wait_for_idle_timeout(idle_timeout, output_activity).await
+                }
+                None => std::future::pending().await,
+            }
+        };
+
+        let resource_limit_receiver = self.resource_limit_receiver.take();
+        let resource_limit_exceeded = async move {
+            match resource_limit_receiver {
+                Some(receiver) => match receiver.await {
+                    Ok(resource_limit_kind) => resource_limit_kind,
+                    Err(_) => std::future::pending().await,
+                },
+                None => std::future::pending().await,
+            }
+        };
+
+        let timeout_elapsed = async move {
+            match timeout {
+                Some(timeout) => tokio::time::sleep(timeout).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::pin!(
+            cancellation_token_cancelled,
+            idle_timeout_elapsed,
+            resource_limit_exceeded,
+            timeout_elapsed
+        );
+
+        tracing::debug!("Waiting for termination or cancellation signal");
+        // Correctness: Only a detached process keeps waiting after its controller was dropped.
+        // The other futures have not completed then and can be polled again.
+        loop {
+            let child = self
+                .child
+                .as_mut()
+                .ok_or(ProcessRunError::OOPS(ChildNotSet {}))?;
+            let cpu_time_pid = self.limits.max_cpu_time.and(child.id());
+
+            tokio::select! {
+                result = &mut *cancel_channel_receiver, if !self.controller_dropped => {
+                    if result.is_ok() {
+                        tracing::debug!(
+                            "Os process was cancelled by the controller"
+                        );
+
+                        // The process was explicitly cancelled by the controller
+                        // Cancellation errors are sent to the controller and this function returns
+                        let cancel_channel_sender = cancel_channel_sender
+                            .take()
+                            .ok_or(ProcessRunError::ControllerDropped)?;
+
+                        match self.check_if_still_running_and_kill_and_wait().await {
+                            Ok(exit_status) => {
+                                self.set_status_on_exit_status(exit_status).await;
+
+                                cancel_channel_sender
+                                    .send(None).map_err(|_| ProcessRunError::ControllerDropped)?;
+                            }
+                            Err(e) => cancel_channel_sender.send(Some(e))
+                                .map_err(|_| ProcessRunError::ControllerDropped)?
+                        }
+                    }
+                    else {
+                        self.controller_dropped = true;
+
+                        if self.is_detached() {
+                            tracing::debug!("Controller was dropped, detached os process keeps running");
+                            continue;
+                        }
+
+                        tracing::debug!(
+                            "Os process was cancelled by dropping the controller"
+                        );
+
+                        // The controller was dropped, wich means we can't send the cancelation error, so we return it here
+                        let exit_status = self.check_if_still_running_and_kill_and_wait().await?;
+                        self.set_status_on_exit_status(exit_status).await;
+                    }
+                }
+
+                _ = &mut cancellation_token_cancelled => {
+                    tracing::debug!("Os process was cancelled by the cancellation token");
+
+                    let exit_status = self.check_if_still_running_and_kill_and_wait().await?;
+                    self.set_status_on_exit_status(exit_status).await;
+                }
+
+                resource_limit_kind = &mut resource_limit_exceeded => {
+                    tracing::warn!(
+                        ?resource_limit_kind,
+                        "Os process exceeded a resource limit"
+                    );
+
+                    self.exceeded_resource_limit = Some(resource_limit_kind);
+                    let exit_status = self.check_if_still_running_and_kill_and_wait().await?;
+                    self.set_status_on_exit_status(exit_status).await;
+                }
+
+                _ = &mut idle_timeout_elapsed => {
+                    tracing::warn!("Os process was silent for too long");
+
+                    self.idle_timed_out = true;
+                    let exit_status = self.check_if_still_running_and_kill_and_wait().await?;
+                    self.set_status_on_exit_status(exit_status).await;
+                }
+
+                _ = &mut timeout_elapsed => {
+                    tracing::warn!("Os process timed out");
+
+                    self.timed_out = true;
+                    let exit_status = self.check_if_still_running_and_kill_and_wait().await?;
+                    self.set_status_on_exit_status(exit_status).await;
+                }
+
+                (result_exit_status, exit_cpu_time) = Self::wait_and_measure_cpu_time(child, cpu_time_pid) => {
+                    tracing::debug!(
+                        "Os process terminated by itself"
+                    );
+
+                    self.exit_cpu_time = exit_cpu_time;
+                    let exit_status = result_exit_status.map_err(ProcessRunError::CouldNotWaitForOsProcess)?;
+                    self.set_status_on_exit_status(exit_status).await;
+                }
+            }
+
+            return Ok(());
+        }
+    }
+
+    /// Measures the cpu time of the os process with ```cpu_time_pid``` before it is reaped, see ```limits::exit_cpu_time```.
+    async fn wait_and_measure_cpu_time(
+        child: &mut Box<dyn BackendChild>,
+        cpu_time_pid: Option<u32>,
+    ) -> (Result<ExitStatus, IoError>, Option<Duration>) {
+        let exit_cpu_time = match cpu_time_pid {
+            Some(pid) => limits::exit_cpu_time(pid).await,
+            None => None,
+        };
+
+        (child.wait().await, exit_cpu_time)
+    }
+
+    async fn spawn_os_process_and_forward_ios_to_channels(
+        &mut self,
+        config: ProcessConfig,
+    ) -> Result<(), IoError> {
+        let ProcessConfig {
+            program,
+            args,
+            envs,
+            current_dir,
+            stdout_sender,
+            stderr_sender,
+            stdout_sinks,
+            stderr_sinks,
+            stdout_file,
+            stderr_file,
+            limits,
+            kill_signal,
+            timeout: _,
+            idle_timeout,
+            strip_ansi,
+            detached,
+            run_as,
+            sandbox,
+            priority,
+            env_mode,
+            hooks: _,
+            backend,
+            rate_limit,
+            io_config,
+            stdin_pipe,
+            stdout_pipe,
+        } = config;
+
+        let stdin = match stdin_pipe {
+            Some(receiver) => receiver.await.map_err(|_| {
+                IoError::new(
+                    std::io::ErrorKind::BrokenPipe,
+                    "Previous pipeline stage did not provide its stdout",
+                )
+            })?,
+            None => Stdio::null(),
+        };
+
+        let output_activity = idle_timeout.as_ref().map(|_| Arc::new(Notify::new()));
+        let watch_output = output_activity.is_some();
+
+        let stdout_sinks =
+            OpenedOutputSink::open_all(stdout_sender, stdout_sinks, stdout_file, &io_config)
+                .await?;
+        let stderr_sinks =
+            OpenedOutputSink::open_all(stderr_sender, stderr_sinks, stderr_file, &io_config)
+                .await?;
+
+        let stdout = match stdout_pipe {
+            Some(_) => Stdio::piped(),
+            None => Self::pipe_if(watch_output || !stdout_sinks.is_empty()),
+        };
+        let stderr = Self::pipe_if(watch_output || !stderr_sinks.is_empty());
+
+        let (program, args) = match sandbox {
+            Some(sandbox) => sandbox.wrap(program, args, &current_dir)?,
+            None => (program, args),
+        };
+
+        let mut command = Command::new(program);
+        env_mode.apply_to_command(&mut command);
+        command
+            .args(args)
+            .envs(envs)
+            .current_dir(current_dir)
+            .stdin(stdin)
+            .stdout(stdout)
+            .stderr(stderr)
+            .kill_on_drop(!detached);
+
+        #[cfg(windows)]
+        command.creation_flags(signals::CREATION_FLAGS);
+
+        if detached {
+            Self::detach_command(&mut command);
+        }
+
+        limits.apply_to_command(&mut command);
+        priority.apply_to_command(&mut command);
+
+        if let Some(run_as) = run_as {
+            run_as.apply_to_command(&mut command)?;
+        }
+
+        let mut child = match backend {
+            Some(backend) => backend.spawn(&mut command)?,
+            None => OsBackend.spawn(&mut command)?,
+        };
+
+        if let Some(os_child) = child.as_os_child_mut() {
+            limits.apply_to_child(os_child)?;
+            priority.apply_to_child(os_child)?;
+        }
+
+        if let Some(pid) = child.id() {
+            self.pid.store(pid, Ordering::SeqCst);
+            self.status_holder
+                .events
+                .push(ProcessEventKind::Spawned { pid });
+            self.resource_limit_receiver = limits.watch(pid);
+        }
+
+        self.detached.store(detached, Ordering::SeqCst);
+        self.limits = limits;
+        self.kill_signal = kill_signal;
+        self.idle_timeout_watch = idle_timeout.zip(output_activity.clone());
+
+        if let Some(sender) = stdout_pipe {
+            if let Some(stdout) = child
+                .as_os_child_mut()
+                .and_then(|os_child| os_child.stdout.take())
+            {
+                let _ = sender.send(stdout.try_into()?);
+            }
+        }
+
+        let stdout = child.take_stdout();
+        let stderr = child.take_stderr();
+
+        Self::forward_ios_to_sinks(
+            stdout,
+            stderr,
+            stdout_sinks,
+            stderr_sinks,
+            &self.status_holder.events,
+            output_activity,
+            strip_ansi,
+            rate_limit,
+            io_config,
+        );
+
+        self.status_holder.overwrite(Status::Running).await;
+
+        self.child = Some(child);
+
+        Ok(())
+    }
+
+    fn is_cancellation_token_cancelled(&self) -> bool {
+        self.cancellation_token
+            .as_ref()
+            .map(CancellationToken::is_cancelled)
+            .unwrap_or(false)
+    }
+
+    /// Resolves once the cancellation token is cancelled. Never resolves if there is no token.
+    fn cancellation_token_cancelled(&self) -> impl std::future::Future<Output = ()> {
+        let cancellation_token = self.cancellation_token.clone();
+        async move {
+            match cancellation_token {
+                Some(cancellation_token) => cancellation_token.cancelled().await,
+                None => std::future::pending().await,
+            }
+        }
+    }
+
+    async fn check_if_still_running_and_kill_and_wait(
+        &mut self,
+    ) -> Result<ExitStatus, ProcessKillAndWaitError> {
+        let child = self
+            .child
+            .as_mut()
+            .ok_or(ProcessKillAndWaitError::OOPS(ChildNotSet {}))?;
+
+        let option_exit_status = child
+            .try_wait()
+            .map_err(ProcessKillAndWaitError::CouldNotCheckStatus)?;
+
+        let exit_status = match option_exit_status {
+            Some(exit_status) => exit_status,
+            None => {
+                if let Some(pid) = child.id() {
+                    let grace_period = self
+                        .kill_signal
+                        .send_graceful(pid)
+                        .map_err(ProcessKillAndWaitError::CouldNotKillProcess)?;
+
+                    if let Some(grace_period) = grace_period {
+                        self.child_killed_successfuly = true;
+
+                        match tokio::time::timeout(grace_period, child.wait()).await {
+                            Ok(result_exit_status) => {
+                                return result_exit_status
+                                    .map_err(ProcessKillAndWaitError::CouldNotWaitForProcess);
+                            }
+                            Err(_) => {
+                                tracing::warn!(
+                                    ?grace_period,
+                                    "Os process did not exit within the grace period, killing it"
+                                );
+                            }
+                        }
+                    }
+                }
+
+                let tree_killed = match child.id() {
+                    Some(pid) => signals::kill_tree(pid).await,
+                    None => false,
+                };
+
+                if !tree_killed {
+                    child
+                        .kill()
+                        .await
+                        .map_err(ProcessKillAndWaitError::CouldNotKillProcess)?;
+                }
+
+                self.child_killed_successfuly = true;
+
+                child
+                    .wait()
+                    .await
+                    .map_err(ProcessKillAndWaitError::CouldNotWaitForProcess)?
+            }
+        };
+
+        Ok(exit_status)
+    }
+
+    async fn get_termination_status_on_exit_status(
+        &self,
+        exit_status: ExitStatus,
+    ) -> TerminationStatus {
+        let os_exit_status = OsExitStatus::from(&exit_status);
+
+        if exit_status.success() {
+            return TerminationStatus::TerminatedSuccessfully;
+        };
+
+        if self.timed_out && self.child_killed_successfuly {
+            return TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByTimeout,
+                os_exit_status,
+            );
+        }
+
+        if self.idle_timed_out && self.child_killed_successfuly {
+            return TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByIdleTimeout,
+                os_exit_status,
+            );
+        }
+
+        if self.shut_down && self.child_killed_successfuly {
+            return TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByShutdown,
+                os_exit_status,
+            );
+        }
+
+        if let Some(resource_limit_kind) = &self.exceeded_resource_limit {
+            if self.child_killed_successfuly {
+                return TerminationStatus::Killed(
+                    KilledTerminationStatus::KilledByResourceLimit(resource_limit_kind.clone()),
+                    os_exit_status,
+                );
+            }
+        }
+
+        if !self.child_killed_successfuly {
+            if let Some(resource_limit_kind) = self
+                .limits
+                .exceeded_limit_on_exit_status(&exit_status, self.exit_cpu_time)
+            {
+                return TerminationStatus::Killed(
+                    KilledTerminationStatus::KilledByResourceLimit(resource_limit_kind),
+                    os_exit_status,
+                );
+            }
+        }
+
+        match exit_status.code() {
+            Some(code) => match code {
+                1 if cfg!(target_os = "windows") && self.child_killed_successfuly => {
+                    if self.controller_dropped && !self.is_detached() {
+                        return TerminationStatus::Killed(
+                            KilledTerminationStatus::KilledByDroppingController,
+                            os_exit_status,
+                        );
+                    }
+
+                    TerminationStatus::Killed(
+                        KilledTerminationStatus::KilledByCancellationSignal,
+                        os_exit_status,
+                    )
+                }
+                _ => TerminationStatus::TerminatedWithError(
+                    TerminationWithErrorStatus::TerminatedWithErrorCode(code),
+                ),
+            },
+            None if cfg!(any(target_os = "linux", target_os = "macos"))
+                && self.child_killed_successfuly =>
+            {
+                if self.controller_dropped && !self.is_detached() {
+                    return TerminationStatus::Killed(
+                        KilledTerminationStatus::KilledByDroppingController,
+                        os_exit_status,
+                    );
+                }
+
+                TerminationStatus::Killed(
+                    KilledTerminationStatus::KilledByCancellationSignal,
+                    os_exit_status,
+                )
+            }
+            None => match signals::terminating_signal(&exit_status) {
+                Some(signal) => TerminationStatus::TerminatedWithError(
+                    TerminationWithErrorStatus::TerminatedBySignal(signal),
+                ),
+                None => TerminationStatus::TerminatedWithError(
+                    TerminationWithErrorStatus::TerminatedWithUnknownErrorCode,
+                ),
+            },
+        }
+    }
+
+    async fn set_status_on_exit_status(&self, exit_status: ExitStatus) {
+        let termination_status = self
+            .get_termination_status_on_exit_status(exit_status)
+            .await;
+        let new_status = Status::Terminated(termination_status);
+        self.status_holder.overwrite(new_status).await;
+    }
+
+    /// Signals sent to the process group of this program, e.g. on Ctrl+C, do not reach a detached os process.
+    /// On windows, every os process is spawned in a new process group anyway.
+    fn detach_command(command: &mut Command) {
+        // Safety: setpgid is async-signal-safe.
+        #[cfg(unix)]
+        unsafe {
+            command.pre_exec(|| {
+                if libc::setpgid(0, 0) != 0 {
+                    return Err(IoError::last_os_error());
+                }
+                Ok(())
+            });
+        }
+
+        #[cfg(not(unix))]
+        let _ = command;
+    }
+
+    fn pipe_if(pipe: bool) -> Stdio {
+        match pipe {
+            true => Stdio::piped(),
+            false => Stdio::null(),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn forward_ios_to_sinks(
+        stdout: Option<BackendStdio>,
+        stderr: Option<BackendStdio>,
+        stdout_sinks: Vec<OpenedOutputSink>,
+        stderr_sinks: Vec<OpenedOutputSink>,
+        events: &EventLog,
+        output_activity: Option<Arc<Notify>>,
+        strip_ansi: StripAnsi,
+        rate_limit: OutputRateLimit,
+        io_config: ProcessIoConfig,
+    ) {
+        // Streams are only piped if there is a sink or the output is watched.
+        if let Some(stdout) = stdout {
+            Self::forward_io(
+                stdout,
+                stdout_sinks,
+                "stdout",
+                events.clone(),
+                RecordedEventKind::Stdout,
+                output_activity.clone(),
+                strip_ansi.stdout,
+                rate_limit.stdout,
+                io_config,
+            );
+        }
+
+        if let Some(stderr) = stderr {
+            Self::forward_io(
+                stderr,
+                stderr_sinks,
+                "stderr",
+                events.clone(),
+                RecordedEventKind::Stderr,
+                output_activity,
+                strip_ansi.stderr,
+                rate_limit.stderr,
+                io_config,
+            );
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn forward_io<T: AsyncRead + Unpin + Send + 'static>(
+        stdio: T,
+        mut sinks: Vec<OpenedOutputSink>,
+        io_name: &'static str,
+        events: EventLog,
+        event_kind: fn(Bytes) -> RecordedEventKind,
+        output_activity: Option<Arc<Notify>>,
+        strip_ansi: bool,
+        max_lines_per_second: Option<u32>,
+        io_config: ProcessIoConfig,
+    ) {
+        let mut lines = LineReader::new(stdio, io_config.read_buffer_capacity);
+        let silence_interval = io_config.flush_policy.silence_interval();
+        let mut throttle = max_lines_per_second.map(Throttle::new);
+
+        spawn_in_current_span(async move {
+            tracing::debug!(io_name, "Starting to forward IO");
+            loop {
+                let batch_due = throttle.as_ref().and_then(Throttle::batch_due);
+                let batch_elapsed = async move {
+                    match batch_due {
+                        Some(batch_due) => tokio::time::sleep_until(batch_due).await,
+                        None => std::future::pending().await,
+                    }
+                };
+
+                // Correctness: ```next_line``` is cancel safe, a line is never lost when the silence interval elapses
+                // or a batch is due.
+                let read_line = async {
+                    match silence_interval {
+                        Some(silence_interval) => {
+                            tokio::time::timeout(silence_interval, lines.next_line()).await
+                        }
+                        None => Ok(lines.next_line().await),
+                    }
+                };
+
+                let next_line = tokio::select! {
+                    next_line = read_line => next_line,
+                    _ = batch_elapsed => {
+                        if let Some(batch) = throttle.as_mut().and_then(Throttle::take_batch) {
+                            sinks::write_line_to_sinks(&mut sinks, &batch, io_name).await;
+                            if io_config.record_output {
+                                events.record(event_kind(batch));
+                            }
+                        }
+                        continue;
+                    }
+                };
+
+                let line = match next_line {
+                    Ok(Ok(Some(line))) => line,
+                    Ok(_) => break,
+                    Err(_) => {
+                        sinks::flush_sinks(&mut sinks, io_name).await;
+                        continue;
+                    }
+                };
+
+                if let Some(output_activity) = &output_activity {
+                    output_activity.notify_one();
+                }
+
+                // Only watched, the line is discarded.
+                if sinks.is_empty() {
+                    continue;
+                }
+
+                let line = match strip_ansi {
+                    true => Bytes::from(sanitize::strip_ansi(&String::from_utf8_lossy(&line))),
+                    false => line,
+                };
+
+                let line = match throttle.as_mut() {
+                    Some(throttle) => match throttle.push(line) {
+                        Some(line) => line,
+                        None => continue,
+                    },
+                    None => line,
+                };
+
+                sinks::write_line_to_sinks(&mut sinks, &line, io_name).await;
+                if io_config.record_output {
+                    events.record(event_kind(line));
+                }
+
+                if sinks.is_empty() && output_activity.is_none() {
+                    break;
+                }
+            }
+            // The stream ended, a pending batch is not held back any longer.
+            if let Some(batch) = throttle.as_mut().and_then(Throttle::take_batch) {
+                sinks::write_line_to_sinks(&mut sinks, &batch, io_name).await;
+                if io_config.record_output {
+                    events.record(event_kind(batch));
+                }
+            }
+            sinks::flush_sinks(&mut sinks, io_name).await;
+            tracing::debug!(io_name, "Finished forwarding IO");
+        });
+    }
+
+    pub async fn status(&self) -> Status {
+        self.status_holder.status().await
+    }
+
+    fn is_detached(&self) -> bool {
+        self.detached.load(Ordering::SeqCst)
+    }
+
+    /// Kills the os process, if it is still running, and waits for it to terminate.
+    /// Use it if the ```run``` future was dropped before the os process terminated, e.g. in a ```select!```.
+    /// Correctness: ```Drop``` can only kill the os process in a detached task, which never runs if the runtime is shutting down.
+    /// ```Drop``` does nothing after a successful shutdown. A detached os process is left running.
+    pub async fn shutdown(&mut self) -> Result<(), ProcessKillAndWaitError> {
+        let debug_span = debug_span!("Process::shutdown", given_id = self.given_id);
+        let _span_guard = debug_span.enter();
+
+        if self.child.is_none() {
+            tracing::debug!("Os process was not spawned");
+            return Ok(());
+        }
+
+        if self.is_detached() {
+            tracing::debug!("Detached os process is left running");
+            self.child = None;
+            return Ok(());
+        }
+
+        self.shut_down = true;
+        let exit_status = self.check_if_still_running_and_kill_and_wait().await?;
+
+        if !matches!(self.status_holder.status().await, Status::Terminated(_)) {
+            self.set_status_on_exit_status(exit_status).await;
+        }
+
+        self.child = None;
+        tracing::debug!("Os process was shut down");
+
+        Ok(())
+    }
+}
+
+/// Like ```tokio::spawn```, but the task runs in the span of the caller, e.g. the span of an install,
+/// so the logs of the task can be correlated with the logs of the caller.
+pub(super) fn spawn_in_current_span<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(future.in_current_span())
+}
+
+/// Getting a ```ChildNotSet``` error, which is extremely weird, requires you to drop the process in order to kill and wait for the child.
+/// Long story short: this is a bug in the code. investigate it.
+#[derive(ThisError, Debug)]
+#[error("Some one should have set the child :D")]
+pub struct ChildNotSet {}
+
+#[derive(ThisError, Debug)]
+pub enum ProcessRunError {
+    #[error("Process was already run!")]
+    AlreayTriedToRun,
+    #[error("Process was not configured. Use ProcessBuilder or Process::run")]
+    NotConfigured,
+    #[error("Process was not attached. Use Process::attach")]
+    NotAttached,
+    #[error("Could not spawn os process: {0}")]
+    CouldNotSpawnOsProcess(#[source] IoError),
+    #[error("Could not wait for os process: {0}")]
+    CouldNotWaitForOsProcess(#[source] IoError),
+    #[error("Corresponding ProcessController was dropped after sending cancellation signal!. Should be infallible")]
+    ControllerDropped,
+    #[error("An error occured while killing and waiting for the process: {0}")]
+    ProcessKillAndWaitError(
+        #[source]
+        #[from]
+        ProcessKillAndWaitError,
+    ),
+    #[error("OOPS: {0}")]
+    OOPS(ChildNotSet),
+}
+
+#[derive(ThisError, Debug)]
+pub enum ProcessKillAndWaitError {
+    #[error("Could not check status of process: {0}")]
+    CouldNotCheckStatus(#[source] IoError),
+    #[error("Could not kill process: {0}")]
+    CouldNotKillProcess(#[source] IoError),
+    #[error("Could not wait for process: {0}")]
+    CouldNotWaitForProcess(#[source] IoError),
+    #[error("OOPS: {0}")]
+    OOPS(ChildNotSet),
+}
+
+/// An error that accures when trying to cancel a process
+#[derive(ThisError, Debug)]
+pub enum SendingCancellationSignalToProcessError {
+    #[error("Process is not running")]
+    ProcessNotRunning,
+    #[error("Cancellation signal can only be sent once")]
+    AlreayTriedToCancel,
+    #[error("Corresponding Process terminated already")]
+    ProcessTerminated,
+}
+
+#[derive(ThisError, Debug)]
+pub enum PauseOrResumeProcessError {
+    #[error("Process is not running")]
+    ProcessNotRunning,
+    #[error("Process is already paused")]
+    ProcessAlreadyPaused,
+    #[error("Process is not paused")]
+    ProcessNotPaused,
+    #[error("Corresponding Process terminated already")]
+    ProcessTerminated,
+    #[error("Could not pause process: {0}")]
+    CouldNotPauseProcess(#[source] IoError),
+    #[error("Could not resume process: {0}")]
+    CouldNotResumeProcess(#[source] IoError),
+}
+
+#[derive(ThisError, Debug)]
+pub enum ProcessMetricsError {
+    #[error("Process is not running")]
+    ProcessNotRunning,
+    #[error("Corresponding Process terminated already")]
+    ProcessTerminated,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::util::test_dir;
+    use std::{path::PathBuf, time::Duration};
+
+    use super::*;
+    use tracing_test::traced_test;
+
+    const CRATE_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+    fn get_tests_dir() -> PathBuf {
+        Path::new(CRATE_DIR).join("tests_dir")
+    }
+
+    fn get_numbers_script_path() -> PathBuf {
+        if cfg!(any(target_os = "linux", target_os = "macos")) {
+            return get_tests_dir().join("numbers.sh");
+        } else if cfg!(target_os = "windows") {
+            return get_tests_dir().join("numbers.ps1");
+        }
+        panic!("Uncovered target_os.");
+    }
+
+    fn get_non_stop_numbers_script_path() -> PathBuf {
+        if cfg!(any(target_os = "linux", target_os = "macos")) {
+            return get_tests_dir().join("non_stop_numbers.sh");
+        } else if cfg!(target_os = "windows") {
+            return get_tests_dir().join("non_stop_numbers.ps1");
+        }
+        panic!("Uncovered target_os.");
+    }
+
+    fn get_numbers_script_with_error_code_path() -> PathBuf {
+        if cfg!(any(target_os = "linux", target_os = "macos")) {
+            return get_tests_dir().join("numbers_with_error_code.sh");
+        } else if cfg!(target_os = "windows") {
+            return get_tests_dir().join("numbers_with_error_code.ps1");
+        }
+        panic!("Uncovered target_os.");
+    }
+
+    fn program() -> &'static str {
+        if cfg!(any(target_os = "linux", target_os = "macos")) {
+            return "bash";
+        } else if cfg!(target_os = "windows") {
+            return "powershell.exe";
+        }
+        panic!("Uncovered target_os.");
+    }
+
+    fn create_process_args(
+        program: String,
+        path: PathBuf,
+        stdout_sender: Option<mpsc::Sender<String>>,
+        stderr_sender: Option<mpsc::Sender<String>>,
+    ) -> OsProcessArgs<Vec<String>, String, String> {
+        let path_str = path
+            .to_str()
+            .expect("Error converting path to string.")
+            .to_owned();
+        OsProcessArgs {
+            program,
+            args: vec![path_str],
+            current_dir: ".".to_owned(),
+            stdout_sender,
+            stderr_sender,
+            ..OsProcessArgs::default()
+        }
+    }
+
+    fn create_numbers_process() -> (Process, ProcessController) {
+        Process::new("some_id".into(), "numbers_process".into())
+    }
+
+    fn create_number_process_run_args() -> OsProcessArgs<Vec<String>, String, String> {
+        let path = get_numbers_script_path();
+        create_process_args(program().to_owned(), path, None, None)
+    }
+
+    fn create_number_process_run_args_with_channels(
+        stdout_sender: Option<mpsc::Sender<String>>,
+        stderr_sender: Option<mpsc::Sender<String>>,
+    ) -> OsProcessArgs<Vec<String>, String, String> {
+        let path = get_numbers_script_path();
+        create_process_args(program().to_owned(), path, stdout_sender, stderr_sender)
+    }
+
+    fn create_non_stop_number_process_run_args_with_channels(
+        stdout_sender: Option<mpsc::Sender<String>>,
+        stderr_sender: Option<mpsc::Sender<String>>,
+    ) -> OsProcessArgs<Vec<String>, String, String> {
+        let path = get_non_stop_numbers_script_path();
+        create_process_args(program().to_owned(), path, stdout_sender, stderr_sender)
+    }
+
+    fn create_numbers_process_with_error_code() -> (Process, ProcessController) {
+        Process::new("some_id".into(), "numbers_process_with_error_code".into())
+    }
+
+    fn create_number_process_with_error_code_run_args() -> OsProcessArgs<Vec<String>, String, String>
+    {
+        let path = get_numbers_script_with_error_code_path();
+        create_process_args(program().to_owned(), path, None, None)
+    }
+
+    fn create_number_process_with_error_code_run_args_with_channels(
+        stdout_sender: Option<mpsc::Sender<String>>,
+        stderr_sender: Option<mpsc::Sender<String>>,
+    ) -> OsProcessArgs<Vec<String>, String, String> {
+        let path = get_numbers_script_with_error_code_path();
+        create_process_args(program().to_owned(), path, stdout_sender, stderr_sender)
+    }
+
+    fn create_non_existing_process() -> (Process, ProcessController) {
+        Process::new("some_id".into(), "non_existing_process".into())
+    }
+
+    fn create_non_existing_process_run_args() -> OsProcessArgs<Vec<String>, String, String> {
+        let path = PathBuf::from("non_existing_process");
+        create_process_args("non_existing_process".to_owned(), path, None, None)
+    }
+
+    fn assert_exit_with_error_code_1(result: Result<Status, ProcessRunError>) {
+        match result {
+            Ok(Status::Terminated(TerminationStatus::TerminatedWithError(
+                TerminationWithErrorStatus::TerminatedWithErrorCode(code),
+            ))) => {
+                assert_eq!(code, 1);
+            }
+            Err(e) => panic!("Unexpected error: {:?}", e),
+            _ => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    fn assert_terminated_successfully(result: Result<Status, ProcessRunError>) {
+        match result {
+            Ok(Status::Terminated(TerminationStatus::TerminatedSuccessfully)) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+            _ => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    fn assert_killed(result: Result<Status, ProcessRunError>) {
+        match result {
+            Ok(Status::Terminated(TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByCancellationSignal,
+                _,
+            ))) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+            _ => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn run_non_existing_process_and_expect_not_found() {
+        let (mut process, _) = create_non_existing_process();
+        let args = create_non_existing_process_run_args();
+
+        let result = process.run(args).await;
+
+        match result {
+            Ok(_) => panic!("Process should not be created."),
+            Err(error) => match error {
+                ProcessRunError::CouldNotSpawnOsProcess(io_error) => match io_error.kind() {
+                    std::io::ErrorKind::NotFound => {}
+                    _ => panic!("Unexpected error kind: {:?}", io_error.kind()),
+                },
+                _ => panic!("Unexpected error: {:?}", error),
+            },
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn run_numbers_script_and_kill_before_termination_and_expect_killed_and_no_kill_and_wait_error(
+    ) {
+        let (mut process, mut controller) = create_numbers_process();
+        let args = create_number_process_run_args();
+
+        let tast_handler = tokio::spawn(async move {
+            controller
+                .wait_until_running()
+                .await
+                .expect("Process should be running.");
+            let kill_and_wait_error = controller
+                .cancel()
+                .await
+                .expect("Error cancelling process.");
+
+            assert!(kill_and_wait_error.is_none());
+        });
+
+        let result = process.run(args).await;
+        assert_killed(result);
+
+        tast_handler.await.expect("Error waiting for handler.");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn run_numbers_script_and_kill_after_termination_and_expect_terminated_successfully_and_process_terminated(
+    ) {
+        let (mut process, mut controller) = create_numbers_process();
+        let args = create_number_process_run_args();
+
+        let task_handler = tokio::spawn(async move {
+            controller.wait_until_terminated().await;
+            match controller.cancel().await {
+                Err(SendingCancellationSignalToProcessError::ProcessTerminated) => {}
+                result => panic!("Unexpected result: {:?}", result),
+            }
+        });
+
+        let result = process.run(args).await;
+        assert_terminated_successfully(result);
+
+        task_handler.await.expect("Error waiting for handler.");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[ignore = "This test is redundant: run_numbers_script_and_kill_after_termination_and_expect_terminated_successfully_and_process_terminated"]
+    async fn cancel_process_after_termination_and_expect_process_terminated_error() {
+        let (mut process, mut controller) = create_numbers_process();
+        let args = create_number_process_run_args();
+
+        process.run(args).await.expect("Error running process.");
+
+        match controller.cancel().await {
+            Err(SendingCancellationSignalToProcessError::ProcessTerminated) => {}
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn run_numbers_script_with_error_code_and_expect_error_code_1() {
+        let (mut process, _controller) = create_numbers_process_with_error_code();
+        let args = create_number_process_with_error_code_run_args();
+
+        let result = process.run(args).await;
+        assert_exit_with_error_code_1(result);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn drop_controller_and_expect_killed_by_dropping_controller() {
+        let (mut process, _) = create_numbers_process_with_error_code();
+        let args = create_number_process_with_error_code_run_args();
+
+        let result = process.run(args).await;
+
+        match result {
+            Ok(Status::Terminated(TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByDroppingController,
+                _,
+            ))) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+            _ => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn cancel_parent_token_while_running_and_expect_killed() {
+        let parent_token = CancellationToken::new();
+        let (mut process, controller) = Process::with_cancellation_token(
+            "some_id".into(),
+            "numbers_process".into(),
+            parent_token.child_token(),
+        );
+        let args = create_number_process_run_args();
+
+        // The controller is returned, dropping it would kill the process before the token is noticed.
+        let task_handler = tokio::spawn(async move {
+            controller
+                .wait_until_running()
+                .await
+                .expect("Process should be running.");
+            parent_token.cancel();
+            controller
+        });
+
+        let result = process.run(args).await;
+        assert_killed(result);
+
+        let _controller = task_handler.await.expect("Error waiting for handler.");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn cancel_token_before_running_and_expect_killed_without_spawning() {
+        let cancellation_token = CancellationToken::new();
+        cancellation_token.cancel();
+
+        let (mut process, controller) = Process::with_cancellation_token(
+            "some_id".into(),
+            "numbers_process".into(),
+            cancellation_token,
+        );
+        let args = create_number_process_run_args();
+
+        let result = process.run(args).await;
+        assert_killed(result);
+        assert!(controller.pid().is_none());
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn cancel_process_before_start_and_expect_process_not_running_error() {
+        let (_process, mut controller) = create_numbers_process();
+
+        match controller.cancel().await {
+            Err(SendingCancellationSignalToProcessError::ProcessNotRunning) => {}
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn cancel_a_dropped_process_before_running_and_expect_process_not_running_error() {
+        let (process, mut controller) = create_numbers_process();
+
+        drop(process);
+
+        match controller.cancel().await {
+            Err(SendingCancellationSignalToProcessError::ProcessNotRunning) => {}
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn cancel_a_dropped_process_after_running_and_expect_process_terminated_error() {
+        let (mut process, mut controller) = create_numbers_process();
+        let args = create_number_process_run_args();
+
+        process.run(args).await.expect("Error running process.");
+
+        drop(process);
+
+        match controller.cancel().await {
+            Err(SendingCancellationSignalToProcessError::ProcessTerminated) => {}
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn cancel_a_process_twice_and_expect_process_terminated_error() {
+        let (mut process, mut controller) = create_numbers_process();
+        let args = create_number_process_run_args();
+
+        let task_handler = tokio::spawn(async move {
+            controller
+                .wait_until_running()
+                .await
+                .expect("Process should be running.");
+            controller
+                .cancel()
+                .await
+                .expect("Error cancelling process.");
+
+            match controller.cancel().await {
+                Err(SendingCancellationSignalToProcessError::ProcessTerminated) => {}
+                result => panic!("Unexpected result {:?}", result),
+            }
+        });
+
+        process.run(args).await.expect("Error running process.");
+
+        task_handler.await.expect("Error awaiting handler.");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn pipe_stdout() {
+        let (mut process, _controller) = create_numbers_process();
+        let (stdout_sender, stdout_receiver) = mpsc::channel(10);
+
+        let args = create_number_process_run_args_with_channels(Some(stdout_sender), None);
+
+        let task_handler = tokio::spawn(async move {
+            let mut lines: Vec<String> = Vec::new();
+            let mut stdout = stdout_receiver;
+
+            while let Some(line) = stdout.recv().await {
+                lines.push(line);
+            }
+
+            let expected_lines: Vec<String> =
+                vec!["1", "2", "3"].iter().map(|s| s.to_string()).collect();
+
+            assert_eq!(lines, expected_lines);
+        });
+
+        let result = process.run(args).await;
+        assert_terminated_successfully(result);
+
+        task_handler.await.expect("Error awaiting handler.");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+
+    async fn pipe_stderr() {
+        let (mut process, _controller) = create_numbers_process_with_error_code();
+        let (stderr_sender, stderr_receiver) = mpsc::channel(10);
+
+        let args =
+            create_number_process_with_error_code_run_args_with_channels(None, Some(stderr_sender));
+
+        let task_handler = tokio::spawn(async move {
+            let mut lines: Vec<String> = Vec::new();
+            let mut stderr = stderr_receiver;
+
+            while let Some(line) = stderr.recv().await {
+                lines.push(line);
+            }
+
+            let expected_first_line = String::from("Error message");
+
+            assert!(lines[0].contains(&expected_first_line));
+        });
+
+        let result = process.run(args).await;
+        assert_exit_with_error_code_1(result);
+
+        task_handler.await.expect("Error awaiting handler.");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn sample_metrics_of_a_running_process() {
+        let (mut process, mut controller) = create_numbers_process();
+        let args = create_non_stop_number_process_run_args_with_channels(None, None);
+
+        let task_handler = tokio::spawn(async move {
+            controller
+                .wait_until_running()
+                .await
+                .expect("Process should be running.");
+            let mut metrics_receiver = controller
+                .metrics(Duration::from_millis(100))
+                .await
+                .expect("Error sampling metrics.");
+
+            let metrics = metrics_receiver
+                .recv()
+                .await
+                .expect("Error receiving metrics.");
+
+            assert!(metrics.memory > 0);
+
+            controller
+                .cancel()
+                .await
+                .expect("Error cancelling process.");
+
+            assert!(metrics_receiver.recv().await.is_none());
+        });
+
+        let result = process.run(args).await;
+        assert_killed(result);
+
+        task_handler.await.expect("Error awaiting handler.");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn sample_metrics_before_start_and_expect_process_not_running_error() {
+        let (_process, controller) = create_numbers_process();
+
+        match controller.metrics(Duration::from_millis(100)).await {
+            Err(ProcessMetricsError::ProcessNotRunning) => {}
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn crash_with_sigsegv_and_expect_terminated_by_signal() {
+        let (mut process, _controller) = create_numbers_process();
+        let args = OsProcessArgs {
+            program: "bash",
+            args: vec!["-c", "kill -SEGV $$"],
+            current_dir: ".",
+            ..OsProcessArgs::default()
+        };
+
+        let result = process.run(args).await;
+
+        match result {
+            Ok(Status::Terminated(TerminationStatus::TerminatedWithError(
+                TerminationWithErrorStatus::TerminatedBySignal(signal),
+            ))) => {
+                assert_eq!(signal, libc::SIGSEGV);
+            }
+            Err(e) => panic!("Unexpected error: {:?}", e),
+            _ => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn cancel_with_interrupt_signal_and_expect_process_to_handle_it() {
+        let (mut process, mut controller) = create_numbers_process();
+        let (stdout_sender, mut stdout_receiver) = mpsc::channel(10);
+        let args = OsProcessArgs {
+            program: "bash",
+            args: vec![
+                "-c",
+                "trap 'echo Flushing; exit 0' INT; echo Started; while true; do sleep 0.1; done",
+            ],
+            current_dir: ".",
+            stdout_sender: Some(stdout_sender),
+            kill_signal: KillSignal::Interrupt {
+                grace_period: Duration::from_secs(5),
+            },
+            ..OsProcessArgs::default()
+        };
+
+        let task_handler = tokio::spawn(async move {
+            let line = stdout_receiver.recv().await.expect("Error receiving line.");
+            assert_eq!(line, "Started");
+
+            controller
+                .cancel()
+                .await
+                .expect("Error cancelling process.");
+
+            let line = stdout_receiver.recv().await.expect("Error receiving line.");
+            assert_eq!(line, "Flushing");
+        });
+
+        let result = process.run(args).await;
+        assert_terminated_successfully(result);
+
+        task_handler.await.expect("Error awaiting handler.");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn cancel_a_process_ignoring_the_kill_signal_and_expect_killed_after_grace_period() {
+        let (mut process, mut controller) = create_numbers_process();
+        let (stdout_sender, mut stdout_receiver) = mpsc::channel(10);
+        let args = OsProcessArgs {
+            program: "bash",
+            args: vec![
+                "-c",
+                "trap '' TERM; echo Started; while true; do sleep 0.1; done",
+            ],
+            current_dir: ".",
+            stdout_sender: Some(stdout_sender),
+            kill_signal: KillSignal::Terminate {
+                grace_period: Duration::from_secs(1),
+            },
+            ..OsProcessArgs::default()
+        };
+
+        let task_handler = tokio::spawn(async move {
+            stdout_receiver.recv().await.expect("Error receiving line.");
+
+            controller
+                .cancel()
+                .await
+                .expect("Error cancelling process.");
+        });
+
+        let result = process.run(args).await;
+        assert_killed(result);
+
+        task_handler.await.expect("Error awaiting handler.");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn pause_and_resume_a_running_process_and_expect_no_output_while_paused() {
+        let (mut process, mut controller) = create_numbers_process();
+        let (stdout_sender, mut stdout_receiver) = mpsc::channel(10);
+        let args = create_non_stop_number_process_run_args_with_channels(Some(stdout_sender), None);
+
+        let task_handler = tokio::spawn(async move {
+            // The script sleeps a second after every line, the process is paused before the next one.
+            let first_line = stdout_receiver.recv().await.expect("Error receiving line.");
+
+            controller.pause().await.expect("Error pausing process.");
+            assert!(matches!(controller.status().await, Status::Paused));
+
+            // The next line is due within a second if the process is not paused.
+            let line_while_paused =
+                tokio::time::timeout(Duration::from_secs(2), stdout_receiver.recv()).await;
+            assert!(
+                line_while_paused.is_err(),
+                "Unexpected line while paused: {:?}",
+                line_while_paused
+            );
+
+            controller.resume().await.expect("Error resuming process.");
+            assert!(matches!(controller.status().await, Status::Running));
+
+            let next_line = stdout_receiver.recv().await.expect("Error receiving line.");
+            assert_eq!(first_line, "1");
+            assert_eq!(next_line, "2");
+
+            controller
+                .cancel()
+                .await
+                .expect("Error cancelling process.");
+        });
+
+        let result = process.run(args).await;
+        assert_killed(result);
+
+        task_handler.await.expect("Error awaiting handler.");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn resume_a_process_before_start_and_expect_process_not_running_error() {
+        let (_process, controller) = create_numbers_process();
+
+        match controller.resume().await {
+            Err(PauseOrResumeProcessError::ProcessNotRunning) => {}
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn allocate_past_memory_limit_and_expect_allocation_to_fail() {
+        let (mut process, _controller) = create_numbers_process();
+        let (stdout_sender, mut stdout_receiver) = mpsc::channel(10);
+        let mut args =
+            create_non_stop_number_process_run_args_with_channels(Some(stdout_sender), None);
+        args.program = String::from("bash");
+        args.args = vec![
+            String::from("-c"),
+            String::from(r#"x=$(head -c 100000000 /dev/zero | tr "\0" a); echo allocated"#),
+        ];
+        args.limits.max_memory = Some(32 * 1024 * 1024);
+
+        let result = process.run(args).await;
+
+        match result {
+            Ok(Status::Terminated(TerminationStatus::TerminatedWithError(_))) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+            _ => panic!("Unexpected result: {:?}", result),
+        }
+        assert_eq!(stdout_receiver.recv().await, None);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn exceed_memory_limit_with_process_tree_and_expect_killed_by_resource_limit() {
+        let (mut process, _controller) = create_numbers_process();
+        let mut args = create_non_stop_number_process_run_args_with_channels(None, None);
+        args.program = String::from("bash");
+        // Every subshell stays below the limit, together they exceed it. The echo keeps bash from replacing the subshell with sleep.
+        args.args = vec![
+            String::from("-c"),
+            String::from(
+                r#"for i in 1 2 3 4 5 6; do (x=$(head -c 10000000 /dev/zero | tr "\0" a); sleep 30; echo ${#x}) & done; wait"#,
+            ),
+        ];
+        args.limits.max_memory = Some(48 * 1024 * 1024);
+
+        let result = process.run(args).await;
+
+        match result {
+            Ok(Status::Terminated(TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByResourceLimit(ResourceLimitKind::Memory),
+                _,
+            ))) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+            _ => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(not(unix))]
+    async fn exceed_memory_limit_and_expect_killed_by_resource_limit() {
+        let (mut process, _controller) = create_numbers_process();
+        let mut args = create_non_stop_number_process_run_args_with_channels(None, None);
+        args.limits.max_memory = Some(1);
+
+        let result = process.run(args).await;
+
+        match result {
+            Ok(Status::Terminated(TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByResourceLimit(ResourceLimitKind::Memory),
+                _,
+            ))) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+            _ => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn exceed_cpu_time_limit_and_expect_killed_by_resource_limit() {
+        let (mut process, _controller) = create_numbers_process();
+        let args = OsProcessArgs {
+            program: "bash",
+            args: vec!["-c", "while true; do :; done"],
+            current_dir: ".",
+            limits: ResourceLimits {
+                max_memory: None,
+                max_cpu_time: Some(Duration::from_secs(1)),
+            },
+            ..OsProcessArgs::default()
+        };
+
+        let result = process.run(args).await;
+
+        match result {
+            Ok(Status::Terminated(TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByResourceLimit(ResourceLimitKind::CpuTime),
+                _,
+            ))) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+            _ => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(target_os = "linux")]
+    async fn kill_with_sigkill_under_and_over_cpu_time_limit_and_expect_only_over_limit_counted() {
+        let run = |script: &'static str| async move {
+            let (mut process, _controller) = create_numbers_process();
+            let mut args = create_non_stop_number_process_run_args_with_channels(None, None);
+            args.program = String::from("bash");
+            args.args = vec![String::from("-c"), String::from(script)];
+            args.limits.max_cpu_time = Some(Duration::from_secs(1));
+
+            process.run(args).await
+        };
+
+        // SIGXCPU is ignored, so the hard limit sends SIGKILL.
+        let over_limit_result = run("trap '' XCPU; while true; do :; done").await;
+        let killed_from_outside_result = run("kill -9 $$").await;
+
+        match over_limit_result {
+            Ok(Status::Terminated(TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByResourceLimit(ResourceLimitKind::CpuTime),
+                _,
+            ))) => {}
+            result => panic!("Unexpected result: {:?}", result),
+        }
+        match killed_from_outside_result {
+            Ok(Status::Terminated(TerminationStatus::TerminatedWithError(
+                TerminationWithErrorStatus::TerminatedBySignal(libc::SIGKILL),
+            ))) => {}
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[ignore = "This is an observation test"]
+    async fn observe_process_drop_for_humans() {
+        let (mut process, controller) = create_numbers_process();
+        let (stdout_sender, stdout_receiver) = mpsc::channel(10);
+
+        let args = create_non_stop_number_process_run_args_with_channels(Some(stdout_sender), None);
+
+        let task_handler = tokio::spawn(async move {
+            let mut stdout = stdout_receiver;
+
+            while let Some(line) = stdout.recv().await {
+                println!("Received line: {}", line);
+            }
+        });
+
+        // ensuring a drop after a select!
+        tokio::spawn(async move {
+            let _move_controller = controller;
+            tokio::select! {
+                _ = process.run(args) => {
+
+                }
+                _ = tokio::time::sleep(Duration::from_secs(2)) => {
+                    println!("Process timed out.");
+                }
+            }
+        });
+
+        task_handler.await.expect("Error awaiting handler.");
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+
+    #[test]
+    fn serialize_status_as_camel_case_and_deserialize_it_back() {
+        let status = Status::Terminated(TerminationStatus::Killed(
+            KilledTerminationStatus::KilledByResourceLimit(ResourceLimitKind::Memory),
+            OsExitStatus {
+                code: None,
+                signal: Some(9),
+            },
+        ));
+
+        let json = serde_json::to_string(&status).expect("Error serializing status.");
+        assert_eq!(
+            json,
+            r#"{"terminated":{"killed":[{"killedByResourceLimit":"memory"},{"code":null,"signal":9}]}}"#
+        );
+
+        match serde_json::from_str(&json).expect("Error deserializing status.") {
+            Status::Terminated(TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByResourceLimit(ResourceLimitKind::Memory),
+                OsExitStatus {
+                    code: None,
+                    signal: Some(9),
+                },
+            )) => {}
+            status => panic!("Unexpected status: {:?}", status),
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn run_process_and_expect_events_in_order_and_resumable() {
+        let (mut process, controller) = create_numbers_process();
+        let (stdout_sender, mut stdout_receiver) = mpsc::channel(10);
+
+        let args = OsProcessArgs {
+            program: "bash",
+            args: vec!["-c", "echo first; echo second"],
+            current_dir: ".",
+            stdout_sender: Some(stdout_sender),
+            io_config: ProcessIoConfig {
+                record_output: true,
+                ..ProcessIoConfig::default()
+            },
+            ..OsProcessArgs::default()
+        };
+
+        let result = process.run(args).await;
+        assert_terminated_successfully(result);
+        while stdout_receiver.recv().await.is_some() {}
+
+        let process_events = controller.events_since(0);
+        assert_eq!(process_events.missed, 0);
+        let events = process_events.events;
+        let sequences: Vec<u64> = events.iter().map(|event| event.sequence).collect();
+        assert_eq!(sequences, (1..=events.len() as u64).collect::<Vec<_>>());
+
+        match events.first().map(|event| &event.kind) {
+            Some(ProcessEventKind::Spawned { .. }) => {}
+            kind => panic!("Unexpected first event: {:?}", kind),
+        }
+
+        let stdout_lines: Vec<&str> = events
+            .iter()
+            .filter_map(|event| match &event.kind {
+                ProcessEventKind::Stdout(line) => Some(line.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(stdout_lines, vec!["first", "second"]);
+
+        assert!(events.iter().any(|event| matches!(
+            event.kind,
+            ProcessEventKind::StatusChanged(Status::Terminated(
+                TerminationStatus::TerminatedSuccessfully
+            ))
+        )));
+
+        let resumed_events = controller.events_since(2).events;
+        assert_eq!(resumed_events.len(), events.len() - 2);
+        assert_eq!(resumed_events.first().map(|event| event.sequence), Some(3));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn stay_silent_and_expect_killed_by_idle_timeout() {
+        let (mut process, _controller) = create_numbers_process();
+        let args = OsProcessArgs {
+            program: "bash",
+            args: vec!["-c", "echo Started; sleep 10"],
+            current_dir: ".",
+            idle_timeout: Some(IdleTimeout {
+                duration: Duration::from_secs(1),
+                action: IdleTimeoutAction::Kill,
+            }),
+            ..OsProcessArgs::default()
+        };
+
+        let result = process.run(args).await;
+
+        match result {
+            Ok(Status::Terminated(TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByIdleTimeout,
+                _,
+            ))) => {}
+            _ => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn stay_silent_and_expect_idle_timeout_callback_without_kill() {
+        let (mut process, _controller) = create_numbers_process();
+        let notifications = Arc::new(AtomicU32::new(0));
+        let callback_notifications = notifications.clone();
+
+        let args = OsProcessArgs {
+            program: "bash",
+            args: vec!["-c", "sleep 2; echo Done"],
+            current_dir: ".",
+            idle_timeout: Some(IdleTimeout {
+                duration: Duration::from_millis(500),
+                action: IdleTimeoutAction::Notify(Arc::new(move || {
+                    callback_notifications.fetch_add(1, Ordering::SeqCst);
+                })),
+            }),
+            ..OsProcessArgs::default()
+        };
+
+        let result = process.run(args).await;
+        assert_terminated_successfully(result);
+        assert!(notifications.load(Ordering::SeqCst) >= 2);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn strip_ansi_on_stdout_only_and_expect_stderr_untouched() {
+        let (mut process, _controller) = create_numbers_process();
+        let (stdout_sender, mut stdout_receiver) = mpsc::channel(10);
+        let (stderr_sender, mut stderr_receiver) = mpsc::channel(10);
+
+        let args = OsProcessArgs {
+            program: "bash",
+            args: vec![
+                "-c",
+                r"printf '\033[32mgreen\033[0m\n'; printf '\033[31mred\033[0m\n' >&2",
+            ],
+            current_dir: ".",
+            stdout_sender: Some(stdout_sender),
+            stderr_sender: Some(stderr_sender),
+            strip_ansi: StripAnsi {
+                stdout: true,
+                stderr: false,
+            },
+            ..OsProcessArgs::default()
+        };
+
+        let result = process.run(args).await;
+        assert_terminated_successfully(result);
+
+        let stdout_line = stdout_receiver.recv().await.expect("Error receiving line.");
+        assert_eq!(stdout_line, "green");
+
+        let stderr_line = stderr_receiver.recv().await.expect("Error receiving line.");
+        assert_eq!(stderr_line, "\u{1b}[31mred\u{1b}[0m");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn fan_out_stdout_to_channel_and_file_and_expect_lines_in_both() {
+        let (mut process, _controller) = create_numbers_process();
+        let (stdout_sender, mut stdout_receiver) = mpsc::channel(10);
+        let temp_dir = test_dir("fan_out");
+        let file_path = temp_dir.path().join("fan_out.log");
+
+        let args = OsProcessArgs {
+            program: "bash",
+            args: vec!["-c", "echo first; echo second"],
+            current_dir: ".",
+            stdout_sender: Some(stdout_sender),
+            stdout_sinks: vec![OutputSink::File(file_path.clone()), OutputSink::Tracing],
+            ..OsProcessArgs::default()
+        };
+
+        let result = process.run(args).await;
+        assert_terminated_successfully(result);
+
+        assert_eq!(stdout_receiver.recv().await.as_deref(), Some("first"));
+        assert_eq!(stdout_receiver.recv().await.as_deref(), Some("second"));
+        // The channel is closed once all sinks are done.
+        assert_eq!(stdout_receiver.recv().await, None);
+
+        let file_content = tokio::fs::read_to_string(&file_path)
+            .await
+            .expect("Error reading sink file.");
+        assert_eq!(file_content, "first\nsecond\n");
+        assert!(logs_contain("second"));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn capture_ios_to_files_and_expect_lines_in_files() {
+        let (mut process, _controller) = create_numbers_process();
+        let (stdout_sender, mut stdout_receiver) = mpsc::channel(10);
+        let (stderr_sender, mut stderr_receiver) = mpsc::channel(10);
+        let temp_dir = test_dir("capture_ios");
+        let dir = temp_dir.path();
+
+        let args = OsProcessArgs {
+            program: "bash",
+            args: vec!["-c", "echo out; echo err >&2; echo more out"],
+            current_dir: ".",
+            stdout_sender: Some(stdout_sender),
+            stderr_sender: Some(stderr_sender),
+            stdout_file: Some(dir.join("out.log")),
+            stderr_file: Some(dir.join("err.log")),
+            ..OsProcessArgs::default()
+        };
+
+        let result = process.run(args).await;
+        assert_terminated_successfully(result);
+
+        // The channels are closed once the files are flushed.
+        while stdout_receiver.recv().await.is_some() {}
+        while stderr_receiver.recv().await.is_some() {}
+
+        let stdout_content = tokio::fs::read_to_string(dir.join("out.log"))
+            .await
+            .expect("Error reading stdout file.");
+        assert_eq!(stdout_content, "out\nmore out\n");
+
+        let stderr_content = tokio::fs::read_to_string(dir.join("err.log"))
+            .await
+            .expect("Error reading stderr file.");
+        assert_eq!(stderr_content, "err\n");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn drop_run_future_and_shutdown_and_expect_killed_by_shutdown() {
+        let (mut process, controller) = create_numbers_process();
+        let args = create_number_process_run_args();
+
+        let result = tokio::time::timeout(Duration::from_secs(2), process.run(args)).await;
+        assert!(result.is_err(), "Process should still be running.");
+
+        process
+            .shutdown()
+            .await
+            .expect("Error shutting down process.");
+
+        match controller.status().await {
+            Status::Terminated(TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByShutdown,
+                _,
+            )) => {}
+            status => panic!("Unexpected status: {:?}", status),
+        }
+
+        // A second shutdown has nothing to do.
+        process
+            .shutdown()
+            .await
+            .expect("Error shutting down process.");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn shutdown_controller_before_start_and_after_termination_and_expect_ok() {
+        let (mut process, mut controller) = create_numbers_process();
+        let args = create_number_process_run_args();
+
+        controller
+            .shutdown()
+            .await
+            .expect("Error shutting down process before start.");
+
+        let result = process.run(args).await;
+        assert_terminated_successfully(result);
+
+        controller
+            .shutdown()
+            .await
+            .expect("Error shutting down terminated process.");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(target_os = "linux")]
+    async fn drop_controller_and_process_of_a_detached_process_and_expect_it_to_keep_running() {
+        let (mut process, controller) = ProcessBuilder::new("some_id", "detached_process")
+            .program("sleep")
+            .arg("30")
+            .detached()
+            .build()
+            .expect("Error building process.");
+
+        let task_handle = tokio::spawn(async move { process.run_built().await });
+
+        controller
+            .wait_until_running()
+            .await
+            .expect("Process should be running.");
+        let pid = controller
+            .pid()
+            .expect("Running process should have a pid.");
+        assert!(controller.is_detached());
+
+        drop(controller);
+        let mut task_handle = task_handle;
+        let run_result = tokio::time::timeout(Duration::from_millis(500), &mut task_handle).await;
+        assert!(run_result.is_err(), "Process should still be waiting.");
+
+        task_handle.abort();
+        let _ = task_handle.await;
+
+        // A killed os process would exit, it is not reaped, since its child was dropped.
+        let exit_result = tokio::time::timeout(Duration::from_millis(500), async {
+            loop {
+                let stat = std::fs::read_to_string(format!("/proc/{pid}/stat"))
+                    .expect("Detached os process should still exist.");
+                let state = stat
+                    .rsplit(')')
+                    .next()
+                    .and_then(|rest| rest.split_whitespace().next());
+                if state == Some("Z") {
+                    break;
+                }
+                tokio::task::yield_now().await;
+            }
+        })
+        .await;
+        assert!(
+            exit_result.is_err(),
+            "Detached os process should not be killed."
+        );
+
+        // Safety: the pid belongs to the detached os process spawned above.
+        unsafe {
+            libc::kill(pid as libc::pid_t, libc::SIGKILL);
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(windows)]
+    async fn cancel_a_shell_with_a_child_and_expect_the_child_to_be_killed() {
+        use sysinfo::{Pid, PidExt, ProcessExt, System, SystemExt};
+
+        let (mut process, mut controller) = create_numbers_process();
+
+        let args = OsProcessArgs {
+            program: "cmd",
+            args: vec!["/C", "ping -n 30 127.0.0.1 > nul"],
+            current_dir: ".",
+            kill_signal: KillSignal::Kill,
+            ..OsProcessArgs::default()
+        };
+
+        let task_handle = tokio::spawn(async move {
+            controller
+                .wait_until_running()
+                .await
+                .expect("Process should be running.");
+
+            let pid = Pid::from_u32(controller.pid().expect("Process should be running."));
+            let mut system = System::new();
+            let child_pids = tokio::time::timeout(Duration::from_secs(30), async {
+                loop {
+                    system.refresh_processes();
+                    let child_pids: Vec<Pid> = system
+                        .processes()
+                        .values()
+                        .filter(|process| process.parent() == Some(pid))
+                        .map(|process| process.pid())
+                        .collect();
+                    if !child_pids.is_empty() {
+                        break child_pids;
+                    }
+                    tokio::task::yield_now().await;
+                }
+            })
+            .await
+            .expect("Shell should have started ping.");
+
+            controller
+                .cancel()
+                .await
+                .expect("Error cancelling process.");
+
+            child_pids
+        });
+
+        let result = process.run(args).await;
+        assert_killed(result);
+
+        let child_pids = task_handle.await.expect("Error waiting for handler.");
+
+        let mut system = System::new();
+        for child_pid in child_pids {
+            assert!(
+                !system.refresh_process(child_pid),
+                "Child of the shell should be killed."
+            );
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn run_as_nobody_and_expect_its_uid_or_a_spawn_error_without_root() {
+        let (stdout_sender, mut stdout_receiver) = mpsc::channel(10);
+        let (mut process, _controller) = ProcessBuilder::new("some_id", "run_as_process")
+            .program("id")
+            .arg("-u")
+            .current_dir("/")
+            .stdout_lines(stdout_sender)
+            .run_as(RunAs {
+                uid: 65534,
+                gid: 65534,
+            })
+            .build()
+            .expect("Error building process.");
+
+        let result = process.run_built().await;
+
+        // Safety: geteuid has no memory safety requirements.
+        if unsafe { libc::geteuid() } == 0 {
+            assert_terminated_successfully(result);
+            assert_eq!(stdout_receiver.recv().await.as_deref(), Some("65534"));
+        } else {
+            match result {
+                Err(ProcessRunError::CouldNotSpawnOsProcess(_)) => {}
+                result => panic!("Unexpected result: {:?}", result),
+            }
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn run_with_low_priority_and_expect_max_niceness() {
+        let (stdout_sender, mut stdout_receiver) = mpsc::channel(10);
+        let (mut process, _controller) = ProcessBuilder::new("some_id", "low_priority_process")
+            .program("nice")
+            .current_dir("/")
+            .stdout_lines(stdout_sender)
+            .priority(ProcessPriority::Low)
+            .build()
+            .expect("Error building process.");
+
+        let result = process.run_built().await;
+
+        assert_terminated_successfully(result);
+        assert_eq!(stdout_receiver.recv().await.as_deref(), Some("19"));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn run_with_allow_list_env_mode_and_expect_only_allowed_and_added_variables() {
+        let (stdout_sender, mut stdout_receiver) = mpsc::channel(100);
+        let (mut process, _controller) = ProcessBuilder::new("some_id", "env_mode_process")
+            .program("/usr/bin/env")
+            .current_dir("/")
+            .env("ADDED_VARIABLE", "added")
+            .env_mode(EnvMode::AllowList(vec![String::from("PATH")]))
+            .stdout_lines(stdout_sender)
+            .build()
+            .expect("Error building process.");
+
+        let result = process.run_built().await;
+        drop(process);
+
+        assert_terminated_successfully(result);
+
+        let mut lines = Vec::new();
+        while let Some(line) = stdout_receiver.recv().await {
+            lines.push(line);
+        }
+
+        assert!(lines.iter().any(|line| line == "ADDED_VARIABLE=added"));
+        assert!(lines
+            .iter()
+            .all(|line| line.starts_with("PATH=") || line.starts_with("ADDED_VARIABLE=")));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn wait_until_running_and_terminated_without_polling() {
+        let (mut process, mut controller) = ProcessBuilder::new("some_id", "waited_process")
+            .program("bash")
+            .args(["-c", "sleep 10"])
+            .kill_signal(KillSignal::Kill)
+            .build()
+            .expect("Error building process.");
+
+        let task_handle = tokio::spawn(async move { process.run_built().await });
+
+        controller
+            .wait_until_running()
+            .await
+            .expect("Process should be running.");
+        assert!(controller.pid().is_some());
+
+        let cancel_result = controller.cancel().await;
+        assert!(matches!(cancel_result, Ok(None)));
+
+        assert!(matches!(
+            controller.wait_until_terminated().await,
+            TerminationStatus::Killed(KilledTerminationStatus::KilledByCancellationSignal, _)
+        ));
+        assert!(matches!(
+            controller.wait_until_running().await,
+            Err(TerminationStatus::Killed(..))
+        ));
+
+        let _ = task_handle.await;
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn run_with_hooks_and_expect_them_around_the_os_process() {
+        let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let pre_spawn_calls = calls.clone();
+        let post_exit_calls = calls.clone();
+
+        let hooks = ProcessHooks::default()
+            .pre_spawn(move |context| {
+                let calls = pre_spawn_calls.clone();
+                async move {
+                    calls
+                        .lock()
+                        .expect("Error locking calls.")
+                        .push(format!("pre_spawn {}", context.given_id));
+                }
+            })
+            .post_exit(move |context, status| {
+                let calls = post_exit_calls.clone();
+                async move {
+                    calls
+                        .lock()
+                        .expect("Error locking calls.")
+                        .push(format!("post_exit {} {:?}", context.given_id, status));
+                }
+            });
+
+        let (mut process, _controller) = ProcessBuilder::new("some_id", "hooked_process")
+            .program("bash")
+            .args(["-c", "exit 0"])
+            .hooks(hooks)
+            .build()
+            .expect("Error building process.");
+
+        let result = process.run_built().await;
+        assert_terminated_successfully(result);
+
+        assert_eq!(
+            *calls.lock().expect("Error locking calls."),
+            vec![
+                String::from("pre_spawn some_id"),
+                String::from("post_exit some_id Terminated(TerminatedSuccessfully)"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn cancel_a_running_process_and_expect_the_terminating_signal_in_the_killed_status() {
+        let (mut process, mut controller) = ProcessBuilder::new("some_id", "sleeping_process")
+            .program("sleep")
+            .arg("10")
+            .build()
+            .expect("Error building process.");
+
+        let handle = tokio::spawn(async move { process.run_built().await });
+
+        controller
+            .wait_until_running()
+            .await
+            .expect("Process should be running.");
+        controller
+            .cancel()
+            .await
+            .expect("Error cancelling process.");
+
+        let result = handle.await.expect("Error joining process.");
+        match result {
+            Ok(Status::Terminated(TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByCancellationSignal,
+                OsExitStatus {
+                    code: None,
+                    signal: Some(libc::SIGTERM),
+                },
+            ))) => {}
+            _ => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn forward_output_and_expect_the_forwarding_task_to_log_in_the_callers_span() {
+        let (stdout_sender, mut stdout_receiver) = mpsc::channel(10);
+        let (mut process, _controller) = ProcessBuilder::new("some_id", "spanned_process")
+            .program("bash")
+            .args(["-c", "echo line"])
+            .stdout_lines(stdout_sender)
+            .build()
+            .expect("Error building process.");
+
+        let caller_span = tracing::info_span!("some_caller_span");
+        let result = process.run_built().instrument(caller_span).await;
+        assert_terminated_successfully(result);
+
+        assert_eq!(stdout_receiver.recv().await.as_deref(), Some("line"));
+        // The receiver is closed once the forwarding task finished.
+        assert!(stdout_receiver.recv().await.is_none());
+
+        logs_assert(|lines: &[&str]| {
+            match lines.iter().any(|line| {
+                line.contains("some_caller_span") && line.contains("Finished forwarding IO")
+            }) {
+                true => Ok(()),
+                false => Err(String::from(
+                    "Forwarding task did not log in the caller span",
+                )),
+            }
+        });
+    }
+}
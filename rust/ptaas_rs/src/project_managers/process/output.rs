@@ -0,0 +1,282 @@
+use std::{
+    collections::VecDeque,
+    ffi::{OsStr, OsString},
+    path::Path,
+    time::Duration,
+};
+
+use tokio::{sync::mpsc, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+
+use super::{
+    spawn_in_current_span, OsProcessArgs, OutputSink, Process, ProcessConfig, ProcessRunError,
+    Status, StripAnsi,
+};
+
+/// Returned by ```Process::run_with_captured_output```.
+#[derive(Debug, Clone)]
+pub struct Output {
+    pub status: Status,
+    /// Lines joined with ```\n```, each line is terminated by it.
+    pub stdout: String,
+    pub stderr: String,
+    /// Whether lines were dropped from the start of stdout to stay within the max capture size.
+    pub stdout_truncated: bool,
+    pub stderr_truncated: bool,
+}
+
+/// The captured lines of a stream.
+#[derive(Default)]
+struct CapturedStream {
+    lines: VecDeque<String>,
+    size: usize,
+    truncated: bool,
+}
+
+impl CapturedStream {
+    /// Keeps the last lines, e.g. the error message at the end of a failed pip install.
+    fn push(&mut self, line: String, max_size: usize) {
+        self.size += line.len() + 1;
+        self.lines.push_back(line);
+
+        while self.size > max_size {
+            let Some(dropped_line) = self.lines.pop_front() else {
+                break;
+            };
+            self.size -= dropped_line.len() + 1;
+            self.truncated = true;
+        }
+    }
+
+    fn into_string(self) -> (String, bool) {
+        let mut output = String::with_capacity(self.size);
+        for line in self.lines {
+            output.push_str(&line);
+            output.push('\n');
+        }
+
+        (output, self.truncated)
+    }
+}
+
+pub(super) fn collect(
+    mut receiver: mpsc::Receiver<String>,
+    max_size: usize,
+) -> JoinHandle<(String, bool)> {
+    spawn_in_current_span(async move {
+        let mut captured_stream = CapturedStream::default();
+        while let Some(line) = receiver.recv().await {
+            captured_stream.push(line, max_size);
+        }
+
+        captured_stream.into_string()
+    })
+}
+
+impl Process {
+    /// Runs the process and returns its output next to its status, instead of forwarding it through channels.
+    /// ```max_capture_size``` is the maximum size in bytes per stream, including line endings.
+    /// Correctness: Lines are captured after ANSI stripping. Once a stream exceeds the max size,
+    /// whole lines are dropped from its start. The output is returned once both streams are closed,
+    /// an os process that leaves a child holding its streams open delays it.
+    pub async fn run_with_captured_output<I, S, P>(
+        &mut self,
+        os_process_args: OsProcessArgs<I, S, P>,
+        max_capture_size: usize,
+    ) -> Result<Output, ProcessRunError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+        P: AsRef<Path>,
+    {
+        self.config = Some(os_process_args.into());
+        self.run_built_with_captured_output(max_capture_size).await
+    }
+
+    /// Like ```run_with_captured_output```, for the process configured by ```capture```.
+    async fn run_built_with_captured_output(
+        &mut self,
+        max_capture_size: usize,
+    ) -> Result<Output, ProcessRunError> {
+        let config = self.config.as_mut().ok_or(ProcessRunError::NotConfigured)?;
+
+        // Lines are buffered in the channels while the collectors append them.
+        let channel_capacity = config.io_config.channel_capacity;
+        let (stdout_sender, stdout_receiver) = mpsc::channel(channel_capacity);
+        let (stderr_sender, stderr_receiver) = mpsc::channel(channel_capacity);
+
+        config.stdout_sinks.push(OutputSink::Channel(stdout_sender));
+        config.stderr_sinks.push(OutputSink::Channel(stderr_sender));
+
+        let stdout_handle = collect(stdout_receiver, max_capture_size);
+        let stderr_handle = collect(stderr_receiver, max_capture_size);
+
+        let status = self.run_built().await?;
+
+        // The collectors only panic if pushing a line panics.
+        let (stdout, stdout_truncated) = stdout_handle.await.unwrap_or_default();
+        let (stderr, stderr_truncated) = stderr_handle.await.unwrap_or_default();
+
+        Ok(Output {
+            status,
+            stdout,
+            stderr,
+            stdout_truncated,
+            stderr_truncated,
+        })
+    }
+}
+
+/// Runs a short command to completion and returns its output, e.g. ```git clone``` or ```pip freeze```.
+/// Both streams are stripped of ANSI codes and captured up to ```max_size``` bytes each, see ```Process::run_with_captured_output```.
+/// Correctness: The os process is killed with ```KilledByTimeout``` once ```timeout``` elapses,
+/// and with ```KilledByCancellationSignal``` once ```cancellation_token``` is cancelled, e.g. by cancelling an installation.
+pub async fn capture(
+    program: &OsStr,
+    args: Vec<&OsStr>,
+    current_dir: &Path,
+    envs: Vec<(OsString, OsString)>,
+    max_size: usize,
+    timeout: Duration,
+    cancellation_token: Option<&CancellationToken>,
+) -> Result<Output, ProcessRunError> {
+    let program_name = Path::new(program)
+        .file_name()
+        .unwrap_or(program)
+        .to_string_lossy()
+        .into_owned();
+    let given_id = format!("{program_name}_id");
+    let given_name = format!("{program_name}_process");
+
+    // The controller is kept until the process terminates, dropping it kills the process.
+    let (mut process, _controller) = match cancellation_token {
+        Some(cancellation_token) => {
+            Process::with_cancellation_token(given_id, given_name, cancellation_token.clone())
+        }
+        None => Process::new(given_id, given_name),
+    };
+
+    let mut config = ProcessConfig::from(OsProcessArgs {
+        program,
+        args,
+        current_dir: current_dir.to_path_buf(),
+        strip_ansi: StripAnsi::both(),
+        envs,
+        ..OsProcessArgs::default()
+    });
+    config.timeout = Some(timeout);
+    process.config = Some(config);
+
+    process.run_built_with_captured_output(max_size).await
+}
+
+#[cfg(test)]
+#[cfg(unix)]
+mod tests {
+    use super::*;
+    use crate::project_managers::process::{KilledTerminationStatus, TerminationStatus};
+    use tracing_test::traced_test;
+
+    fn bash_args(script: &str) -> OsProcessArgs<Vec<String>, String, &'static str> {
+        OsProcessArgs {
+            program: String::from("bash"),
+            args: vec![String::from("-c"), String::from(script)],
+            current_dir: ".",
+            ..OsProcessArgs::default()
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn capture_stdout_and_stderr() {
+        let (mut process, _controller) =
+            Process::new(String::from("some_id"), String::from("output_process"));
+
+        let output = process
+            .run_with_captured_output(bash_args("echo out; echo err >&2; echo more out"), 1024)
+            .await
+            .expect("Error running process.");
+
+        assert!(matches!(
+            output.status,
+            Status::Terminated(TerminationStatus::TerminatedSuccessfully)
+        ));
+        assert_eq!(output.stdout, "out\nmore out\n");
+        assert_eq!(output.stderr, "err\n");
+        assert!(!output.stdout_truncated);
+        assert!(!output.stderr_truncated);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn exceed_max_capture_size_and_expect_last_lines() {
+        let (mut process, _controller) =
+            Process::new(String::from("some_id"), String::from("output_process"));
+
+        let output = process
+            .run_with_captured_output(bash_args("for i in 1 2 3 4 5; do echo line$i; done"), 12)
+            .await
+            .expect("Error running process.");
+
+        assert_eq!(output.stdout, "line4\nline5\n");
+        assert!(output.stdout_truncated);
+        assert_eq!(output.stderr, "");
+        assert!(!output.stderr_truncated);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn capture_with_envs_and_exceed_timeout_and_expect_killed_by_timeout() {
+        let output = capture(
+            OsStr::new("bash"),
+            vec![
+                OsStr::new("-c"),
+                OsStr::new("echo $SOME_VAR; exec sleep 30"),
+            ],
+            Path::new("."),
+            vec![(OsString::from("SOME_VAR"), OsString::from("some_value"))],
+            1024,
+            Duration::from_millis(500),
+            None,
+        )
+        .await
+        .expect("Error running process.");
+
+        assert!(matches!(
+            output.status,
+            Status::Terminated(TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByTimeout,
+                _
+            ))
+        ));
+        assert_eq!(output.stdout, "some_value\n");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn capture_with_cancelled_token_and_expect_killed_by_cancellation_signal() {
+        let cancellation_token = CancellationToken::new();
+        cancellation_token.cancel();
+
+        let output = capture(
+            OsStr::new("bash"),
+            vec![OsStr::new("-c"), OsStr::new("echo out")],
+            Path::new("."),
+            Vec::new(),
+            1024,
+            Duration::from_secs(30),
+            Some(&cancellation_token),
+        )
+        .await
+        .expect("Error running process.");
+
+        assert!(matches!(
+            output.status,
+            Status::Terminated(TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByCancellationSignal,
+                _
+            ))
+        ));
+        assert_eq!(output.stdout, "");
+    }
+}
@@ -0,0 +1,187 @@
+use tokio::sync::oneshot;
+
+use super::{
+    builder::{ProcessBuilder, ProcessBuilderError},
+    spawn_in_current_span, Process, ProcessController, ProcessKillAndWaitError, ProcessRunError,
+    SendingCancellationSignalToProcessError, Status, TerminationStatus,
+};
+
+/// Runs processes like a shell pipe: the stdout of every stage is the stdin of the next stage.
+/// Correctness: All stages are spawned concurrently, every stage waits for the stdout of its predecessor before spawning.
+/// If a stage fails to spawn, all following stages fail to spawn as well.
+/// ```stdout_lines``` and stdout sinks are only respected on the last stage, the stdout of the other stages is piped.
+pub struct ProcessPipeline {
+    stages: Vec<Process>,
+}
+
+/// Cancels all stages of a ```ProcessPipeline```.
+pub struct ProcessPipelineController {
+    controllers: Vec<ProcessController>,
+}
+
+#[derive(Debug)]
+pub struct PipelineStageResult {
+    pub given_name: String,
+    pub result: Result<Status, ProcessRunError>,
+}
+
+#[derive(Debug)]
+pub struct PipelineResult {
+    pub stages: Vec<PipelineStageResult>,
+}
+
+impl PipelineResult {
+    /// The index of the first stage that did not terminate successfully, like bash's ```pipefail```.
+    pub fn first_failed_stage(&self) -> Option<usize> {
+        self.stages.iter().position(|stage| {
+            !matches!(
+                stage.result,
+                Ok(Status::Terminated(
+                    TerminationStatus::TerminatedSuccessfully
+                ))
+            )
+        })
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.first_failed_stage().is_none()
+    }
+}
+
+impl ProcessPipeline {
+    pub fn new(
+        stages: Vec<ProcessBuilder>,
+    ) -> Result<(Self, ProcessPipelineController), ProcessBuilderError> {
+        let mut processes = Vec::with_capacity(stages.len());
+        let mut controllers = Vec::with_capacity(stages.len());
+
+        for stage in stages {
+            let (process, controller) = stage.build()?;
+            processes.push(process);
+            controllers.push(controller);
+        }
+
+        for index in 1..processes.len() {
+            let (stdout_pipe, stdin_pipe) = oneshot::channel();
+
+            if let Some(config) = processes[index - 1].config.as_mut() {
+                config.stdout_pipe = Some(stdout_pipe);
+            }
+
+            if let Some(config) = processes[index].config.as_mut() {
+                config.stdin_pipe = Some(stdin_pipe);
+            }
+        }
+
+        Ok((
+            Self { stages: processes },
+            ProcessPipelineController { controllers },
+        ))
+    }
+
+    pub async fn run(self) -> PipelineResult {
+        let handles: Vec<_> = self
+            .stages
+            .into_iter()
+            .map(|mut process| {
+                spawn_in_current_span(async move {
+                    let result = process.run_built().await;
+                    PipelineStageResult {
+                        given_name: process.given_name.clone(),
+                        result,
+                    }
+                })
+            })
+            .collect();
+
+        let mut stages = Vec::with_capacity(handles.len());
+        for handle in handles {
+            match handle.await {
+                Ok(stage_result) => stages.push(stage_result),
+                Err(err) => {
+                    tracing::error!(%err, "Pipeline stage task failed");
+                }
+            }
+        }
+
+        PipelineResult { stages }
+    }
+}
+
+impl ProcessPipelineController {
+    /// Cancels every stage. Returns the cancellation result of every stage in order.
+    pub async fn cancel(
+        &mut self,
+    ) -> Vec<Result<Option<ProcessKillAndWaitError>, SendingCancellationSignalToProcessError>> {
+        let mut results = Vec::with_capacity(self.controllers.len());
+        for controller in self.controllers.iter_mut() {
+            results.push(controller.cancel().await);
+        }
+        results
+    }
+
+    pub async fn statuses(&self) -> Vec<Status> {
+        let mut statuses = Vec::with_capacity(self.controllers.len());
+        for controller in self.controllers.iter() {
+            statuses.push(controller.status().await);
+        }
+        statuses
+    }
+}
+
+#[cfg(test)]
+#[cfg(unix)]
+mod tests {
+    use super::*;
+    use crate::project_managers::process::TerminationWithErrorStatus;
+    use tokio::sync::mpsc;
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn pipe_stdout_of_first_stage_into_second_stage() {
+        let (stdout_sender, mut stdout_receiver) = mpsc::channel(10);
+        let (pipeline, _controller) = ProcessPipeline::new(vec![
+            ProcessBuilder::new("echo_id", "echo_stage")
+                .program("bash")
+                .args(["-c", "echo hello; echo pipeline"]),
+            ProcessBuilder::new("tr_id", "tr_stage")
+                .program("tr")
+                .args(["a-z", "A-Z"])
+                .stdout_lines(stdout_sender),
+        ])
+        .expect("Error creating pipeline.");
+
+        let result = pipeline.run().await;
+        assert!(result.succeeded(), "Unexpected result: {:?}", result);
+
+        let mut lines = Vec::new();
+        while let Some(line) = stdout_receiver.recv().await {
+            lines.push(line);
+        }
+
+        assert_eq!(lines, vec!["HELLO", "PIPELINE"]);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn fail_first_stage_and_expect_first_failed_stage() {
+        let (pipeline, _controller) = ProcessPipeline::new(vec![
+            ProcessBuilder::new("fail_id", "fail_stage")
+                .program("bash")
+                .args(["-c", "exit 3"]),
+            ProcessBuilder::new("cat_id", "cat_stage").program("cat"),
+        ])
+        .expect("Error creating pipeline.");
+
+        let result = pipeline.run().await;
+        assert_eq!(result.first_failed_stage(), Some(0));
+
+        match &result.stages[0].result {
+            Ok(Status::Terminated(TerminationStatus::TerminatedWithError(
+                TerminationWithErrorStatus::TerminatedWithErrorCode(3),
+            ))) => {}
+            stage_result => panic!("Unexpected result: {:?}", stage_result),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::{ffi::OsStr, path::Path, sync::Arc};
+
+use tokio::sync::{mpsc, Semaphore};
+
+use super::{
+    spawn_in_current_span, OsProcessArgs, Process, ProcessConfig, ProcessController,
+    ProcessRunError, Status,
+};
+
+/// A process waiting in a ```ProcessPool```.
+#[derive(Debug)]
+pub struct ProcessPoolJob<I, S, P> {
+    pub given_id: String,
+    pub given_name: String,
+    pub os_process_args: OsProcessArgs<I, S, P>,
+}
+
+#[derive(Debug)]
+pub struct ProcessPoolResult {
+    pub given_id: String,
+    pub given_name: String,
+    pub result: Result<Status, ProcessRunError>,
+}
+
+/// Runs processes concurrently, with at most ```max_parallelism``` running at the same time.
+/// Correctness: The limit is shared by all clones of the pool and all calls to ```ProcessPool::run```.
+/// A queued process stays in ```Status::Created``` until a slot is free,
+/// so cancelling its controller fails with ```SendingCancellationSignalToProcessError::ProcessNotRunning```.
+/// Dropping the controller of a queued process kills it as soon as it is spawned.
+#[derive(Clone)]
+pub struct ProcessPool {
+    semaphore: Arc<Semaphore>,
+}
+
+impl ProcessPool {
+    /// ```max_parallelism``` is at least 1.
+    pub fn new(max_parallelism: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_parallelism.max(1))),
+        }
+    }
+
+    /// Number of processes that could be started right now.
+    pub fn available_slots(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    /// Queues all jobs and returns their controllers in order.
+    /// Results are sent in the order the processes terminate, the receiver yields ```None``` once all jobs are done.
+    pub fn run<I, S, P>(
+        &self,
+        jobs: Vec<ProcessPoolJob<I, S, P>>,
+    ) -> (mpsc::Receiver<ProcessPoolResult>, Vec<ProcessController>)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+        P: AsRef<Path>,
+    {
+        let (result_sender, result_receiver) = mpsc::channel(jobs.len().max(1));
+        let mut controllers = Vec::with_capacity(jobs.len());
+
+        for job in jobs {
+            let (mut process, controller) = Process::new(job.given_id, job.given_name);
+            process.config = Some(ProcessConfig::from(job.os_process_args));
+            controllers.push(controller);
+
+            let semaphore = self.semaphore.clone();
+            let result_sender = result_sender.clone();
+            spawn_in_current_span(async move {
+                // Only fails if the semaphore is closed, which never happens.
+                let _permit = semaphore.acquire_owned().await;
+                let result = process.run_built().await;
+
+                let _ = result_sender
+                    .send(ProcessPoolResult {
+                        given_id: process.given_id.clone(),
+                        given_name: process.given_name.clone(),
+                        result,
+                    })
+                    .await;
+            });
+        }
+
+        (result_receiver, controllers)
+    }
+}
+
+#[cfg(test)]
+#[cfg(unix)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::project_managers::process::TerminationStatus;
+    use tracing_test::traced_test;
+
+    fn sleep_job(given_id: &str) -> ProcessPoolJob<Vec<&'static str>, &'static str, &'static str> {
+        ProcessPoolJob {
+            given_id: given_id.to_owned(),
+            given_name: "sleep_process".to_owned(),
+            os_process_args: OsProcessArgs {
+                program: "sleep",
+                args: vec!["1"],
+                current_dir: ".",
+                ..OsProcessArgs::default()
+            },
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn run_jobs_with_max_parallelism_and_expect_them_to_be_queued() {
+        let pool = ProcessPool::new(2);
+        let start = Instant::now();
+
+        let (mut result_receiver, _controllers) =
+            pool.run(vec![sleep_job("a"), sleep_job("b"), sleep_job("c")]);
+
+        let mut given_ids = Vec::new();
+        while let Some(pool_result) = result_receiver.recv().await {
+            match pool_result.result {
+                Ok(Status::Terminated(TerminationStatus::TerminatedSuccessfully)) => {}
+                _ => panic!("Unexpected result: {:?}", pool_result),
+            }
+            given_ids.push(pool_result.given_id);
+        }
+
+        given_ids.sort();
+        assert_eq!(given_ids, vec!["a", "b", "c"]);
+        assert!(start.elapsed() >= Duration::from_secs(2));
+        assert_eq!(pool.available_slots(), 2);
+    }
+}
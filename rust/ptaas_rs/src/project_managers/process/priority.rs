@@ -0,0 +1,98 @@
+use std::io::Error as IoError;
+
+use tokio::process::{Child, Command};
+
+/// Os scheduling priority of a spawned os process, e.g. pip installs run with ```Low``` while locust masters stay responsive.
+/// Correctness: Mapped to a nice value on unix and to a priority class on windows.
+/// Raising the priority above ```Normal``` usually requires root on unix or admin rights on windows, spawning fails otherwise.
+/// Children of the os process inherit its priority.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProcessPriority {
+    Low,
+    BelowNormal,
+    #[default]
+    Normal,
+    AboveNormal,
+    High,
+}
+
+impl ProcessPriority {
+    #[cfg(unix)]
+    fn niceness(&self) -> libc::c_int {
+        match self {
+            ProcessPriority::Low => 19,
+            ProcessPriority::BelowNormal => 10,
+            ProcessPriority::Normal => 0,
+            ProcessPriority::AboveNormal => -5,
+            ProcessPriority::High => -10,
+        }
+    }
+
+    /// Sets the nice value of the os process right before exec.
+    pub(super) fn apply_to_command(&self, command: &mut Command) {
+        #[cfg(unix)]
+        if *self != ProcessPriority::Normal {
+            let niceness = self.niceness();
+
+            // Safety: setpriority is async-signal-safe.
+            unsafe {
+                command.pre_exec(move || {
+                    if libc::setpriority(libc::PRIO_PROCESS, 0, niceness) != 0 {
+                        return Err(IoError::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+        }
+
+        #[cfg(not(unix))]
+        let _ = command;
+    }
+
+    /// Sets the priority class of the spawned child.
+    pub(super) fn apply_to_child(&self, child: &Child) -> Result<(), IoError> {
+        #[cfg(windows)]
+        if *self != ProcessPriority::Normal {
+            if let Some(handle) = child.raw_handle() {
+                windows::set_priority_class(handle, *self)?;
+            }
+        }
+
+        #[cfg(not(windows))]
+        let _ = child;
+
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::{io::Error as IoError, os::windows::io::RawHandle};
+
+    use windows_sys::Win32::System::Threading::{
+        SetPriorityClass, ABOVE_NORMAL_PRIORITY_CLASS, BELOW_NORMAL_PRIORITY_CLASS,
+        HIGH_PRIORITY_CLASS, IDLE_PRIORITY_CLASS, NORMAL_PRIORITY_CLASS,
+    };
+
+    use super::ProcessPriority;
+
+    pub(super) fn set_priority_class(
+        process_handle: RawHandle,
+        priority: ProcessPriority,
+    ) -> Result<(), IoError> {
+        let priority_class = match priority {
+            ProcessPriority::Low => IDLE_PRIORITY_CLASS,
+            ProcessPriority::BelowNormal => BELOW_NORMAL_PRIORITY_CLASS,
+            ProcessPriority::Normal => NORMAL_PRIORITY_CLASS,
+            ProcessPriority::AboveNormal => ABOVE_NORMAL_PRIORITY_CLASS,
+            ProcessPriority::High => HIGH_PRIORITY_CLASS,
+        };
+
+        // Safety: the process handle is valid as long as the child is not dropped.
+        if unsafe { SetPriorityClass(process_handle as isize, priority_class) } == 0 {
+            return Err(IoError::last_os_error());
+        }
+
+        Ok(())
+    }
+}
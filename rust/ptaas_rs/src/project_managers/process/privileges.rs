@@ -0,0 +1,32 @@
+use std::io::Error as IoError;
+
+use tokio::process::Command;
+
+/// The user and group the os process runs as, so uploaded scripts never run with the privileges of this program.
+/// Correctness: The ids are set right before exec. Supplementary groups are dropped if this program runs as root.
+/// Spawning fails if this program is not allowed to switch to the user, or on non unix systems.
+/// The user needs access to ```current_dir``` and the program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunAs {
+    pub uid: u32,
+    pub gid: u32,
+}
+
+impl RunAs {
+    pub(super) fn apply_to_command(&self, command: &mut Command) -> Result<(), IoError> {
+        #[cfg(unix)]
+        {
+            command.uid(self.uid).gid(self.gid);
+            Ok(())
+        }
+
+        #[cfg(not(unix))]
+        {
+            let _ = command;
+            Err(IoError::new(
+                std::io::ErrorKind::Unsupported,
+                "Running as a different user is only supported on unix",
+            ))
+        }
+    }
+}
@@ -0,0 +1,482 @@
+use std::{
+    ffi::OsStr,
+    path::Path,
+    sync::{atomic::Ordering, Arc},
+    time::Duration,
+};
+
+use tokio::sync::{mpsc, oneshot};
+use tracing::debug_span;
+
+use super::{
+    output, KilledTerminationStatus, OsExitStatus, OsProcessArgs, OutputSink, Process,
+    ProcessConfig, ProcessKillAndWaitError, ProcessRestart, ProcessRunError, Status,
+    TerminationStatus,
+};
+
+/// Only the end of stderr is passed to ```RetryPolicy::retry_on_stderr```, e.g. the error message of a failed pip install.
+const MAX_RETRY_STDERR_SIZE: usize = 64 * 1024;
+
+type RetryOnStderr = Arc<dyn Fn(&TerminationStatus, &str) -> bool + Send + Sync>;
+
+/// Delay between two attempts.
+#[derive(Debug, Clone)]
+pub enum RetryBackoff {
+    Fixed(Duration),
+    /// Doubles after every attempt, up to ```max```.
+    Exponential {
+        initial: Duration,
+        max: Duration,
+    },
+}
+
+impl RetryBackoff {
+    /// ```attempt``` starts at 1.
+    fn delay_after_attempt(&self, attempt: usize) -> Duration {
+        match self {
+            Self::Fixed(delay) => *delay,
+            Self::Exponential { initial, max } => {
+                let factor = 2u32.saturating_pow(attempt.saturating_sub(1) as u32);
+                initial.saturating_mul(factor).min(*max)
+            }
+        }
+    }
+}
+
+/// Used in ```Process::run_with_retry```.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    /// Including the first attempt. At least 1.
+    pub max_attempts: usize,
+    pub backoff: RetryBackoff,
+    /// Decides if a terminated attempt should be retried.
+    pub retry_on: Arc<dyn Fn(&TerminationStatus) -> bool + Send + Sync>,
+    /// Replaces ```retry_on``` if set. Decides with the captured stderr of the attempt,
+    /// e.g. to only retry network errors.
+    pub retry_on_stderr: Option<RetryOnStderr>,
+}
+
+impl RetryPolicy {
+    /// Retries every attempt that terminated with an error. Killed attempts are not retried.
+    pub fn new(max_attempts: usize, backoff: RetryBackoff) -> Self {
+        Self {
+            max_attempts,
+            backoff,
+            retry_on: Arc::new(|termination_status| {
+                matches!(
+                    termination_status,
+                    TerminationStatus::TerminatedWithError(_)
+                )
+            }),
+            retry_on_stderr: None,
+        }
+    }
+
+    #[must_use]
+    pub fn retry_on(
+        mut self,
+        retry_on: impl Fn(&TerminationStatus) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.retry_on = Arc::new(retry_on);
+        self
+    }
+
+    /// Correctness: The stderr of every attempt is captured after ANSI stripping, up to the last 64 KiB.
+    /// The decision waits until stderr is closed, an os process that leaves a child holding it open delays it.
+    #[must_use]
+    pub fn retry_on_stderr(
+        mut self,
+        retry_on_stderr: impl Fn(&TerminationStatus, &str) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.retry_on_stderr = Some(Arc::new(retry_on_stderr));
+        self
+    }
+}
+
+impl std::fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("backoff", &self.backoff)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returned by ```Process::run_with_retry```.
+#[derive(Debug, Clone)]
+pub struct RetriedStatus {
+    /// The status of the process after the last attempt.
+    pub status: Status,
+    /// The termination status of every spawned attempt in order.
+    pub attempts: Vec<TerminationStatus>,
+}
+
+impl Process {
+    /// Runs the process and re-spawns it as long as ```RetryPolicy::retry_on``` matches, up to ```RetryPolicy::max_attempts``` times.
+    /// Correctness: The controller controls the whole run. The status stays ```Status::Running``` between attempts,
+    /// cancelling the process during the backoff stops retrying and sets the status to ```KilledByCancellationSignal```.
+    /// Pausing the process during the backoff fails with ```PauseOrResumeProcessError::ProcessNotRunning```.
+    /// A process that fails to spawn is not retried.
+    pub async fn run_with_retry<I, S, P>(
+        &mut self,
+        os_process_args: OsProcessArgs<I, S, P>,
+        retry_policy: RetryPolicy,
+    ) -> Result<RetriedStatus, ProcessRunError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+        P: AsRef<Path>,
+    {
+        self.run_config_with_retry(ProcessConfig::from(os_process_args), retry_policy, None)
+            .await
+    }
+
+    /// Sends a ```ProcessRestart``` to ```restart_sender``` before every backoff.
+    pub(super) async fn run_config_with_retry(
+        &mut self,
+        config: ProcessConfig,
+        retry_policy: RetryPolicy,
+        restart_sender: Option<mpsc::UnboundedSender<ProcessRestart>>,
+    ) -> Result<RetriedStatus, ProcessRunError> {
+        let debug_span = debug_span!(
+            "Process::run_with_retry",
+            given_id = self.given_id,
+            given_name = self.given_name
+        );
+        let _span_guard = debug_span.enter();
+
+        let (mut cancel_channel_receiver, mut cancel_channel_sender) = self.take_channels()?;
+
+        let max_attempts = retry_policy.max_attempts.max(1);
+        let mut attempts = Vec::new();
+
+        for attempt in 1..=max_attempts {
+            tracing::debug!(attempt, max_attempts, "Running attempt");
+
+            let mut attempt_config = config.for_attempt();
+            let stderr_handle = retry_policy.retry_on_stderr.as_ref().map(|_| {
+                let (stderr_sender, stderr_receiver) =
+                    mpsc::channel(config.io_config.channel_capacity);
+                attempt_config
+                    .stderr_sinks
+                    .push(OutputSink::Channel(stderr_sender));
+                output::collect(stderr_receiver, MAX_RETRY_STDERR_SIZE)
+            });
+
+            self.run_attempt(
+                attempt_config,
+                &mut cancel_channel_receiver,
+                &mut cancel_channel_sender,
+            )
+            .await?;
+
+            let Status::Terminated(termination_status) = self.status_holder.status().await else {
+                break;
+            };
+
+            attempts.push(termination_status.clone());
+
+            let cancelled = cancel_channel_sender.is_none()
+                || self.controller_dropped
+                || self.is_cancellation_token_cancelled();
+            if cancelled || attempt == max_attempts {
+                break;
+            }
+
+            let retry = match (&retry_policy.retry_on_stderr, stderr_handle) {
+                (Some(retry_on_stderr), Some(stderr_handle)) => {
+                    // The collector only panics if pushing a line panics.
+                    let (stderr, _) = stderr_handle.await.unwrap_or_default();
+                    retry_on_stderr(&termination_status, &stderr)
+                }
+                _ => (retry_policy.retry_on)(&termination_status),
+            };
+
+            if !retry {
+                break;
+            }
+
+            let delay = retry_policy.backoff.delay_after_attempt(attempt);
+            tracing::debug!(attempt, ?delay, ?termination_status, "Retrying attempt");
+
+            if let Some(restart_sender) = &restart_sender {
+                let _ = restart_sender.send(ProcessRestart {
+                    restart: attempt,
+                    previous_termination_status: termination_status,
+                    delay,
+                });
+            }
+
+            self.reset_for_next_attempt().await;
+
+            let cancellation_token_cancelled = self.cancellation_token_cancelled();
+            let cancelled_during_backoff = tokio::select! {
+                _ = tokio::time::sleep(delay) => false,
+                _ = cancellation_token_cancelled => {
+                    tracing::debug!("Process was cancelled by the cancellation token during backoff");
+                    self.status_holder
+                        .overwrite(Status::Terminated(TerminationStatus::Killed(
+                            KilledTerminationStatus::KilledByCancellationSignal, OsExitStatus::default()
+                        )))
+                        .await;
+                    true
+                }
+                result = &mut cancel_channel_receiver => {
+                    self.cancel_during_backoff(result.is_ok(), &mut cancel_channel_sender).await;
+                    true
+                }
+            };
+
+            if cancelled_during_backoff {
+                break;
+            }
+        }
+
+        let status = self.status_holder.status().await;
+
+        Ok(RetriedStatus { status, attempts })
+    }
+
+    async fn reset_for_next_attempt(&mut self) {
+        // The child was already waited for.
+        self.child = None;
+        self.pid.store(0, Ordering::SeqCst);
+        self.child_killed_successfuly = false;
+        self.timed_out = false;
+        self.idle_timed_out = false;
+        self.idle_timeout_watch = None;
+        self.exceeded_resource_limit = None;
+        self.resource_limit_receiver = None;
+
+        self.status_holder.overwrite(Status::Running).await;
+    }
+
+    async fn cancel_during_backoff(
+        &mut self,
+        cancelled_by_controller: bool,
+        cancel_channel_sender: &mut Option<oneshot::Sender<Option<ProcessKillAndWaitError>>>,
+    ) {
+        let killed_termination_status = if cancelled_by_controller {
+            tracing::debug!("Process was cancelled by the controller during backoff");
+            KilledTerminationStatus::KilledByCancellationSignal
+        } else {
+            tracing::debug!("Process was cancelled by dropping the controller during backoff");
+            self.controller_dropped = true;
+            KilledTerminationStatus::KilledByDroppingController
+        };
+
+        self.status_holder
+            .overwrite(Status::Terminated(TerminationStatus::Killed(
+                killed_termination_status,
+                OsExitStatus::default(),
+            )))
+            .await;
+
+        if let Some(sender) = cancel_channel_sender.take() {
+            let _ = sender.send(None);
+        }
+    }
+}
+
+impl ProcessConfig {
+    /// Pipes are only set by ```ProcessPipeline```, which does not retry.
+    fn for_attempt(&self) -> Self {
+        Self {
+            program: self.program.clone(),
+            args: self.args.clone(),
+            envs: self.envs.clone(),
+            current_dir: self.current_dir.clone(),
+            stdout_sender: self.stdout_sender.clone(),
+            stderr_sender: self.stderr_sender.clone(),
+            stdout_sinks: self.stdout_sinks.clone(),
+            stderr_sinks: self.stderr_sinks.clone(),
+            stdout_file: self.stdout_file.clone(),
+            stderr_file: self.stderr_file.clone(),
+            limits: self.limits.clone(),
+            kill_signal: self.kill_signal.clone(),
+            timeout: self.timeout,
+            idle_timeout: self.idle_timeout.clone(),
+            strip_ansi: self.strip_ansi,
+            detached: self.detached,
+            run_as: self.run_as,
+            sandbox: self.sandbox.clone(),
+            priority: self.priority,
+            env_mode: self.env_mode.clone(),
+            hooks: self.hooks.clone(),
+            backend: self.backend.clone(),
+            rate_limit: self.rate_limit,
+            io_config: self.io_config,
+            stdin_pipe: None,
+            stdout_pipe: None,
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(unix)]
+mod tests {
+    use super::*;
+    use crate::project_managers::process::TerminationWithErrorStatus;
+    use crate::util::test_dir;
+    use tracing_test::traced_test;
+
+    /// Fails until the marker file exists. The first attempt creates it.
+    fn fail_once_args(marker: &Path) -> OsProcessArgs<Vec<String>, String, &'static str> {
+        let script = format!(
+            "if [ -f {0} ]; then exit 0; else touch {0}; exit 7; fi",
+            marker.display()
+        );
+
+        OsProcessArgs {
+            program: String::from("bash"),
+            args: vec![String::from("-c"), script],
+            current_dir: ".",
+            ..OsProcessArgs::default()
+        }
+    }
+
+    fn exit_args(code: i32) -> OsProcessArgs<Vec<String>, String, &'static str> {
+        OsProcessArgs {
+            program: String::from("bash"),
+            args: vec![String::from("-c"), format!("exit {code}")],
+            current_dir: ".",
+            ..OsProcessArgs::default()
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn fail_first_attempt_and_expect_second_attempt_to_succeed() {
+        let temp_dir = test_dir("retry_marker");
+        let marker = temp_dir.path().join("marker");
+
+        let (mut process, _controller) =
+            Process::new(String::from("some_id"), String::from("retry_process"));
+
+        let result = process
+            .run_with_retry(
+                fail_once_args(&marker),
+                RetryPolicy::new(3, RetryBackoff::Fixed(Duration::from_millis(10))),
+            )
+            .await;
+
+        let retried_status = result.expect("Error running process.");
+        match retried_status.status {
+            Status::Terminated(TerminationStatus::TerminatedSuccessfully) => {}
+            _ => panic!("Unexpected result: {:?}", retried_status),
+        }
+
+        match retried_status.attempts.as_slice() {
+            [TerminationStatus::TerminatedWithError(
+                TerminationWithErrorStatus::TerminatedWithErrorCode(7),
+            ), TerminationStatus::TerminatedSuccessfully] => {}
+            _ => panic!("Unexpected attempts: {:?}", retried_status.attempts),
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn fail_with_unmatched_exit_code_and_expect_no_retry() {
+        let (mut process, _controller) =
+            Process::new(String::from("some_id"), String::from("retry_process"));
+
+        let retry_policy = RetryPolicy::new(3, RetryBackoff::Fixed(Duration::from_millis(10)))
+            .retry_on(|termination_status| {
+                matches!(
+                    termination_status,
+                    TerminationStatus::TerminatedWithError(
+                        TerminationWithErrorStatus::TerminatedWithErrorCode(2)
+                    )
+                )
+            });
+
+        let retried_status = process
+            .run_with_retry(exit_args(3), retry_policy)
+            .await
+            .expect("Error running process.");
+
+        assert_eq!(retried_status.attempts.len(), 1);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn fail_with_transient_stderr_and_expect_retry_until_other_stderr() {
+        let temp_dir = test_dir("retry_stderr_marker");
+        let marker = temp_dir.path().join("marker");
+
+        let script = format!(
+            "if [ -f {0} ]; then echo 'No matching distribution' >&2; else touch {0}; echo 'Connection reset' >&2; fi; exit 1",
+            marker.display()
+        );
+        let mut os_process_args = exit_args(1);
+        os_process_args.args = vec![String::from("-c"), script];
+
+        let retry_policy = RetryPolicy::new(3, RetryBackoff::Fixed(Duration::from_millis(10)))
+            .retry_on_stderr(|_, stderr| stderr.contains("Connection reset"));
+
+        let (mut process, _controller) =
+            Process::new(String::from("some_id"), String::from("retry_process"));
+
+        let result = process.run_with_retry(os_process_args, retry_policy).await;
+
+        let retried_status = result.expect("Error running process.");
+        assert_eq!(retried_status.attempts.len(), 2);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn cancel_during_backoff_and_expect_killed_by_cancellation_signal() {
+        let (mut process, mut controller) =
+            Process::new(String::from("some_id"), String::from("retry_process"));
+
+        let (restart_sender, mut restart_receiver) = mpsc::unbounded_channel();
+
+        let handle = tokio::spawn(async move {
+            process
+                .run_config_with_retry(
+                    ProcessConfig::from(exit_args(1)),
+                    RetryPolicy::new(3, RetryBackoff::Fixed(Duration::from_secs(10))),
+                    Some(restart_sender),
+                )
+                .await
+        });
+
+        restart_receiver
+            .recv()
+            .await
+            .expect("Error receiving restart before the backoff.");
+        let cancel_result = controller.cancel().await;
+        assert!(
+            matches!(cancel_result, Ok(None)),
+            "Unexpected cancel result: {:?}",
+            cancel_result
+        );
+
+        let retried_status = handle
+            .await
+            .expect("Error joining process.")
+            .expect("Error running process.");
+
+        match retried_status.status {
+            Status::Terminated(TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByCancellationSignal,
+                _,
+            )) => {}
+            _ => panic!("Unexpected result: {:?}", retried_status),
+        }
+        assert_eq!(retried_status.attempts.len(), 1);
+    }
+
+    #[test]
+    fn exponential_backoff_is_capped() {
+        let backoff = RetryBackoff::Exponential {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(5),
+        };
+
+        assert_eq!(backoff.delay_after_attempt(1), Duration::from_secs(1));
+        assert_eq!(backoff.delay_after_attempt(2), Duration::from_secs(2));
+        assert_eq!(backoff.delay_after_attempt(3), Duration::from_secs(4));
+        assert_eq!(backoff.delay_after_attempt(4), Duration::from_secs(5));
+    }
+}
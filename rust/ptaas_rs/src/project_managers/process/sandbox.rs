@@ -0,0 +1,152 @@
+use std::{
+    ffi::OsString,
+    io::Error as IoError,
+    path::{Path, PathBuf},
+};
+
+/// The sandboxed program is spawned through bubblewrap.
+const BWRAP: &str = "bwrap";
+
+/// Mounted read-only in every sandbox, so interpreters and their shared libraries are found.
+const SYSTEM_DIRS: &[&str] = &["/usr", "/bin", "/sbin", "/lib", "/lib64", "/etc"];
+
+/// Linux only. Runs the os process inside a bubblewrap sandbox, since project code is uploaded by users.
+/// The os process gets its own namespaces, read-only system directories, an empty ```/tmp``` and only the given paths of the host.
+/// Correctness: ```bwrap``` must be installed, spawning fails otherwise and on non linux systems.
+/// ```current_dir``` and the program must be inside the mounted paths.
+/// No seccomp filter is installed, the os process is confined by the namespaces and mounts only.
+/// The pid of the process is the one of ```bwrap```. Graceful kill signals are not forwarded,
+/// the sandboxed os process is killed together with ```bwrap```.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SandboxOptions {
+    /// Mounted read-only at the same path, e.g. the project dir.
+    pub read_only_paths: Vec<PathBuf>,
+    /// Mounted writable at the same path, e.g. the virtual environment while installing.
+    pub writable_paths: Vec<PathBuf>,
+    /// Keeps the network of this program. Pip needs it while installing, the run phase should not.
+    pub network: bool,
+}
+
+impl SandboxOptions {
+    /// Replaces the program and its args with a ```bwrap``` invocation that runs them.
+    pub(super) fn wrap(
+        &self,
+        program: OsString,
+        args: Vec<OsString>,
+        current_dir: &Path,
+    ) -> Result<(OsString, Vec<OsString>), IoError> {
+        if !cfg!(target_os = "linux") {
+            return Err(IoError::new(
+                std::io::ErrorKind::Unsupported,
+                "Sandboxing is only supported on linux",
+            ));
+        }
+
+        let mut bwrap_args = self.bwrap_args(current_dir);
+        bwrap_args.push(program);
+        bwrap_args.extend(args);
+
+        Ok((BWRAP.into(), bwrap_args))
+    }
+
+    fn bwrap_args(&self, current_dir: &Path) -> Vec<OsString> {
+        let mut bwrap_args: Vec<OsString> = vec![
+            "--unshare-all".into(),
+            "--die-with-parent".into(),
+            "--new-session".into(),
+        ];
+
+        if self.network {
+            bwrap_args.push("--share-net".into());
+        }
+
+        for system_dir in SYSTEM_DIRS {
+            bwrap_args.extend(["--ro-bind-try".into(), system_dir.into(), system_dir.into()]);
+        }
+
+        bwrap_args.extend([
+            "--proc".into(),
+            "/proc".into(),
+            "--dev".into(),
+            "/dev".into(),
+            "--tmpfs".into(),
+            "/tmp".into(),
+        ]);
+
+        for path in &self.read_only_paths {
+            bwrap_args.extend([
+                "--ro-bind".into(),
+                path.clone().into_os_string(),
+                path.clone().into_os_string(),
+            ]);
+        }
+
+        // Mounted after the read-only paths, so a writable path inside a read-only one stays writable.
+        for path in &self.writable_paths {
+            bwrap_args.extend([
+                "--bind".into(),
+                path.clone().into_os_string(),
+                path.clone().into_os_string(),
+            ]);
+        }
+
+        bwrap_args.extend([
+            "--chdir".into(),
+            current_dir.as_os_str().to_owned(),
+            "--".into(),
+        ]);
+
+        bwrap_args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_test::traced_test;
+
+    #[test]
+    #[traced_test]
+    fn bwrap_args_mount_paths_and_drop_network() {
+        let sandbox_options = SandboxOptions {
+            read_only_paths: vec![PathBuf::from("/project")],
+            writable_paths: vec![PathBuf::from("/project/venv")],
+            network: false,
+        };
+
+        let bwrap_args = sandbox_options.bwrap_args(Path::new("/project"));
+
+        assert!(!bwrap_args.contains(&"--share-net".into()));
+        assert!(bwrap_args
+            .windows(3)
+            .any(|window| window == ["--ro-bind", "/project", "/project"]));
+
+        let read_only_position = bwrap_args
+            .iter()
+            .position(|arg| arg == "/project")
+            .expect("Project dir should be mounted.");
+        let writable_position = bwrap_args
+            .iter()
+            .position(|arg| arg == "/project/venv")
+            .expect("Venv dir should be mounted.");
+        assert!(read_only_position < writable_position);
+
+        assert_eq!(
+            bwrap_args[bwrap_args.len() - 3..],
+            ["--chdir", "/project", "--"]
+        );
+    }
+
+    #[test]
+    #[traced_test]
+    fn bwrap_args_share_network_if_requested() {
+        let sandbox_options = SandboxOptions {
+            network: true,
+            ..Default::default()
+        };
+
+        let bwrap_args = sandbox_options.bwrap_args(Path::new("/"));
+
+        assert!(bwrap_args.contains(&"--share-net".into()));
+    }
+}
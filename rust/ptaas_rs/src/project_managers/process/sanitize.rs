@@ -0,0 +1,94 @@
+const ESC: char = '\u{1b}';
+const BEL: char = '\u{7}';
+
+/// Strips ANSI escape sequences from forwarded lines, per stream.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StripAnsi {
+    pub stdout: bool,
+    pub stderr: bool,
+}
+
+impl StripAnsi {
+    pub fn both() -> Self {
+        Self {
+            stdout: true,
+            stderr: true,
+        }
+    }
+}
+
+/// Removes ANSI escape sequences and control characters from a line.
+/// Correctness: Spinners redraw the line with ```\r```, only the text after the last ```\r``` is kept,
+/// which is what a terminal would show. Tabs are kept.
+pub(super) fn strip_ansi(line: &str) -> String {
+    let line = line
+        .rsplit('\r')
+        .find(|part| !part.is_empty())
+        .unwrap_or("");
+
+    let mut stripped = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != ESC {
+            if c == '\t' || !c.is_control() {
+                stripped.push(c);
+            }
+            continue;
+        }
+
+        match chars.next() {
+            // CSI: parameters and intermediates until a final byte in @..~
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC: until BEL or ESC \
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == BEL {
+                        break;
+                    }
+                    if c == ESC && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // Two character sequences like ESC ( B, or a lone ESC at the end
+            Some('(' | ')') => {
+                chars.next();
+            }
+            Some(_) | None => {}
+        }
+    }
+
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_colors_and_expect_plain_text() {
+        assert_eq!(
+            strip_ansi("\u{1b}[1;31mERROR\u{1b}[0m: could not install"),
+            "ERROR: could not install"
+        );
+    }
+
+    #[test]
+    fn strip_spinner_redraws_and_expect_last_frame() {
+        assert_eq!(strip_ansi("| 10%\r/ 50%\r- 100%"), "- 100%");
+        assert_eq!(strip_ansi("Downloading\r"), "Downloading");
+    }
+
+    #[test]
+    fn strip_osc_and_control_characters_and_keep_tabs() {
+        assert_eq!(strip_ansi("\u{1b}]0;title\u{7}a\tb\u{8}\u{1b}(Bc"), "a\tbc");
+    }
+}
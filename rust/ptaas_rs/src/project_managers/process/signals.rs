@@ -0,0 +1,194 @@
+use std::{io::Error as IoError, process::ExitStatus, time::Duration};
+
+const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+#[cfg(unix)]
+pub(super) use unix::{resume, suspend};
+#[cfg(not(any(unix, windows)))]
+pub(super) use unsupported::{resume, suspend};
+#[cfg(windows)]
+pub(super) use windows::{resume, suspend};
+
+/// The signal sent to the os process by ```ProcessController::cancel```.
+/// If the process does not exit within the grace period, it is killed with SIGKILL.
+/// A process handling the signal may exit with its own exit code, which is then reported as is.
+/// On windows, there are no signals and the process tree is always killed right away.
+#[derive(Debug, Clone)]
+pub enum KillSignal {
+    /// SIGINT. Locust needs it to write its CSV summary before exiting.
+    Interrupt { grace_period: Duration },
+    /// SIGTERM.
+    Terminate { grace_period: Duration },
+    /// SIGKILL.
+    Kill,
+}
+
+impl Default for KillSignal {
+    fn default() -> Self {
+        Self::Terminate {
+            grace_period: DEFAULT_GRACE_PERIOD,
+        }
+    }
+}
+
+impl KillSignal {
+    /// Sends the signal to the os process if it is a graceful one.
+    /// Returns the grace period to wait for the process to exit, ```None``` if it should be killed right away.
+    pub(super) fn send_graceful(&self, pid: u32) -> Result<Option<Duration>, IoError> {
+        #[cfg(unix)]
+        match self {
+            Self::Interrupt { grace_period } => {
+                unix::send_signal(pid, libc::SIGINT)?;
+                return Ok(Some(*grace_period));
+            }
+            Self::Terminate { grace_period } => {
+                unix::send_signal(pid, libc::SIGTERM)?;
+                return Ok(Some(*grace_period));
+            }
+            Self::Kill => {}
+        }
+
+        #[cfg(not(unix))]
+        let _ = pid;
+
+        Ok(None)
+    }
+}
+
+/// Every os process is spawned in a new process group on windows, so ```taskkill``` can kill its tree
+/// and a Ctrl+C in the console of this program does not reach it.
+#[cfg(windows)]
+pub(super) const CREATION_FLAGS: u32 =
+    windows_sys::Win32::System::Threading::CREATE_NEW_PROCESS_GROUP;
+
+/// Kills the os process and all its descendants.
+/// Returns ```false``` if the tree could not be killed, the os process should then be killed alone.
+/// Correctness: On windows, killing a shell does not kill python or pip started by it, ```taskkill /T /F``` does.
+/// Always returns ```false``` on other systems.
+pub(super) async fn kill_tree(pid: u32) -> bool {
+    #[cfg(windows)]
+    match windows::taskkill_tree(pid).await {
+        Ok(()) => return true,
+        Err(err) => {
+            tracing::warn!(%err, pid, "Failed to kill os process tree, killing the os process alone");
+        }
+    }
+
+    #[cfg(not(windows))]
+    let _ = pid;
+
+    false
+}
+
+/// The signal that terminated the os process. Always ```None``` on non unix systems.
+#[cfg(unix)]
+pub(super) fn terminating_signal(exit_status: &ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+
+    exit_status.signal()
+}
+
+#[cfg(not(unix))]
+pub(super) fn terminating_signal(_exit_status: &ExitStatus) -> Option<i32> {
+    None
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::io::Error as IoError;
+
+    pub(in super::super) fn suspend(pid: u32) -> Result<(), IoError> {
+        send_signal(pid, libc::SIGSTOP)
+    }
+
+    pub(in super::super) fn resume(pid: u32) -> Result<(), IoError> {
+        send_signal(pid, libc::SIGCONT)
+    }
+
+    pub(super) fn send_signal(pid: u32, signal: libc::c_int) -> Result<(), IoError> {
+        // Safety: kill has no memory safety requirements.
+        if unsafe { libc::kill(pid as libc::pid_t, signal) } != 0 {
+            return Err(IoError::last_os_error());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::io::Error as IoError;
+
+    use windows_sys::Win32::{
+        Foundation::{CloseHandle, HANDLE},
+        System::Threading::{OpenProcess, PROCESS_SUSPEND_RESUME},
+    };
+
+    #[link(name = "ntdll")]
+    extern "system" {
+        fn NtSuspendProcess(process_handle: HANDLE) -> i32;
+        fn NtResumeProcess(process_handle: HANDLE) -> i32;
+    }
+
+    pub(in super::super) fn suspend(pid: u32) -> Result<(), IoError> {
+        // Safety: the handle is valid for the duration of the call.
+        with_process_handle(pid, |handle| unsafe { NtSuspendProcess(handle) })
+    }
+
+    pub(in super::super) fn resume(pid: u32) -> Result<(), IoError> {
+        // Safety: the handle is valid for the duration of the call.
+        with_process_handle(pid, |handle| unsafe { NtResumeProcess(handle) })
+    }
+
+    pub(in super::super) async fn taskkill_tree(pid: u32) -> Result<(), IoError> {
+        let output = tokio::process::Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/T", "/F"])
+            .output()
+            .await?;
+
+        if output.status.success() {
+            return Ok(());
+        }
+
+        Err(IoError::new(
+            std::io::ErrorKind::Other,
+            String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        ))
+    }
+
+    fn with_process_handle(pid: u32, f: impl FnOnce(HANDLE) -> i32) -> Result<(), IoError> {
+        // Safety: the handle is checked and closed before returning.
+        let nt_status = unsafe {
+            let handle = OpenProcess(PROCESS_SUSPEND_RESUME, 0, pid);
+            if handle == 0 {
+                return Err(IoError::last_os_error());
+            }
+
+            let nt_status = f(handle);
+            CloseHandle(handle);
+            nt_status
+        };
+
+        if nt_status < 0 {
+            return Err(IoError::new(
+                std::io::ErrorKind::Other,
+                format!("NTSTATUS {nt_status:#x}"),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod unsupported {
+    use std::io::{Error as IoError, ErrorKind};
+
+    pub(in super::super) fn suspend(_pid: u32) -> Result<(), IoError> {
+        Err(IoError::new(ErrorKind::Unsupported, "Uncovered target_os"))
+    }
+
+    pub(in super::super) fn resume(_pid: u32) -> Result<(), IoError> {
+        Err(IoError::new(ErrorKind::Unsupported, "Uncovered target_os"))
+    }
+}
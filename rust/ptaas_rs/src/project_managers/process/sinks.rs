@@ -0,0 +1,139 @@
+use std::{
+    io::{Error as IoError, ErrorKind},
+    path::PathBuf,
+};
+
+use bytes::Bytes;
+use tokio::{
+    fs::{File, OpenOptions},
+    io::AsyncWriteExt,
+    sync::mpsc,
+};
+
+use super::{capture::CaptureFile, ProcessIoConfig};
+
+/// A destination for the lines of a stream. A stream may have many sinks.
+#[derive(Debug, Clone)]
+pub enum OutputSink {
+    Channel(mpsc::Sender<String>),
+    /// Lines are appended to the file, which is created if it does not exist.
+    File(PathBuf),
+    /// Lines are logged with ```tracing::info!```.
+    Tracing,
+}
+
+/// Correctness: A sink that fails is removed, the other sinks keep receiving lines.
+pub(super) enum OpenedOutputSink {
+    Channel(mpsc::Sender<String>),
+    File(File),
+    Tracing,
+    Capture(CaptureFile),
+}
+
+impl OpenedOutputSink {
+    /// The sender of ```OsProcessArgs``` is the first sink, the capture file is the last one.
+    /// Files are opened before the os process is spawned, so a spawned process never misses a sink.
+    pub(super) async fn open_all(
+        sender: Option<mpsc::Sender<String>>,
+        sinks: Vec<OutputSink>,
+        capture_file: Option<PathBuf>,
+        io_config: &ProcessIoConfig,
+    ) -> Result<Vec<Self>, IoError> {
+        let mut opened_sinks = Vec::with_capacity(sinks.len() + 2);
+
+        if let Some(sender) = sender {
+            opened_sinks.push(Self::Channel(sender));
+        }
+
+        for sink in sinks {
+            let opened_sink = match sink {
+                OutputSink::Channel(sender) => Self::Channel(sender),
+                OutputSink::File(path) => Self::File(
+                    OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(path)
+                        .await?,
+                ),
+                OutputSink::Tracing => Self::Tracing,
+            };
+
+            opened_sinks.push(opened_sink);
+        }
+
+        if let Some(path) = capture_file {
+            opened_sinks.push(Self::Capture(CaptureFile::open(path, io_config).await?));
+        }
+
+        Ok(opened_sinks)
+    }
+
+    /// Invalid UTF-8 is replaced for the channel and tracing sinks, files get the line as it was read.
+    pub(super) async fn write_line(
+        &mut self,
+        line: &Bytes,
+        io_name: &'static str,
+    ) -> Result<(), IoError> {
+        match self {
+            Self::Channel(sender) => sender
+                .send(String::from_utf8_lossy(line).into_owned())
+                .await
+                .map_err(|_| IoError::new(ErrorKind::BrokenPipe, "Receiver was dropped")),
+            // Correctness: tokio buffers file writes, flushing hands every line to the os right away.
+            Self::File(file) => {
+                file.write_all(line).await?;
+                file.write_all(b"\n").await?;
+                file.flush().await
+            }
+            Self::Tracing => {
+                let line = String::from_utf8_lossy(line);
+                tracing::info!(io_name, line = &*line);
+                Ok(())
+            }
+            Self::Capture(capture_file) => capture_file.write_line(line).await,
+        }
+    }
+
+    async fn flush(&mut self) -> Result<(), IoError> {
+        match self {
+            Self::Capture(capture_file) => capture_file.flush().await,
+            Self::Channel(_) | Self::File(_) | Self::Tracing => Ok(()),
+        }
+    }
+}
+
+/// Writes the line to all sinks and removes the failed ones.
+pub(super) async fn write_line_to_sinks(
+    sinks: &mut Vec<OpenedOutputSink>,
+    line: &Bytes,
+    io_name: &'static str,
+) {
+    let mut failed_sinks = Vec::new();
+
+    for (index, sink) in sinks.iter_mut().enumerate() {
+        if let Err(err) = sink.write_line(line, io_name).await {
+            tracing::warn!(%err, io_name, "Failed to write line to sink, removing it");
+            failed_sinks.push(index);
+        }
+    }
+
+    for index in failed_sinks.into_iter().rev() {
+        sinks.remove(index);
+    }
+}
+
+/// Flushes buffered sinks and removes the failed ones.
+pub(super) async fn flush_sinks(sinks: &mut Vec<OpenedOutputSink>, io_name: &'static str) {
+    let mut failed_sinks = Vec::new();
+
+    for (index, sink) in sinks.iter_mut().enumerate() {
+        if let Err(err) = sink.flush().await {
+            tracing::warn!(%err, io_name, "Failed to flush sink, removing it");
+            failed_sinks.push(index);
+        }
+    }
+
+    for index in failed_sinks.into_iter().rev() {
+        sinks.remove(index);
+    }
+}
@@ -0,0 +1,126 @@
+use std::{collections::BTreeMap, path::PathBuf, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use super::{ProcessBuilder, ResourceLimits};
+
+/// Serializable definition of an os process, so install and run steps can be stored as data, e.g. in the project database.
+/// Converted into a ```ProcessBuilder```, since ```OsProcessArgs``` has no environment and no timeout.
+/// Everything that can not be serialized, like senders and sinks, is added to the builder afterwards.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessSpec {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Added on top of the inherited environment.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    pub cwd: PathBuf,
+    #[serde(default)]
+    pub limits: ResourceLimits,
+    #[serde(default)]
+    pub timeout: Option<Duration>,
+}
+
+impl ProcessSpec {
+    #[must_use]
+    pub fn into_builder(
+        self,
+        given_id: impl Into<String>,
+        given_name: impl Into<String>,
+    ) -> ProcessBuilder {
+        let mut builder = ProcessBuilder::new(given_id, given_name)
+            .program(self.program)
+            .args(self.args)
+            .current_dir(self.cwd)
+            .limits(self.limits);
+
+        for (key, value) in self.env {
+            builder = builder.env(key, value);
+        }
+
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_managers::process::{Status, TerminationStatus};
+    use tokio::sync::mpsc;
+    use tracing_test::traced_test;
+
+    #[test]
+    #[traced_test]
+    fn deserialize_from_json_with_defaults() {
+        let json = r#"{"program": "python", "cwd": "/project"}"#;
+
+        let spec: ProcessSpec = serde_json::from_str(json).expect("Error deserializing spec.");
+
+        assert_eq!(
+            spec,
+            ProcessSpec {
+                program: String::from("python"),
+                cwd: PathBuf::from("/project"),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    #[traced_test]
+    fn serialize_to_json_and_back() {
+        let spec = ProcessSpec {
+            program: String::from("pip"),
+            args: vec![String::from("install"), String::from("locust")],
+            env: BTreeMap::from([(String::from("PIP_NO_CACHE_DIR"), String::from("1"))]),
+            cwd: PathBuf::from("/project"),
+            limits: ResourceLimits {
+                max_memory: Some(1024),
+                max_cpu_time: Some(Duration::from_secs(60)),
+            },
+            timeout: Some(Duration::from_secs(300)),
+        };
+
+        let json = serde_json::to_string(&spec).expect("Error serializing spec.");
+        let deserialized: ProcessSpec =
+            serde_json::from_str(&json).expect("Error deserializing spec.");
+
+        assert_eq!(spec, deserialized);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn run_from_spec_and_expect_env_to_be_set() {
+        let spec = ProcessSpec {
+            program: String::from("sh"),
+            args: vec![String::from("-c"), String::from("echo $GREETING")],
+            env: BTreeMap::from([(String::from("GREETING"), String::from("hello"))]),
+            cwd: PathBuf::from("/"),
+            ..Default::default()
+        };
+
+        let (stdout_sender, mut stdout_receiver) = mpsc::channel(10);
+        let (mut process, _controller) = spec
+            .into_builder("some_id", "spec_process")
+            .stdout_lines(stdout_sender)
+            .build()
+            .expect("Error building process.");
+
+        let result = process.run_built().await;
+
+        assert!(matches!(
+            result,
+            Ok(Status::Terminated(
+                TerminationStatus::TerminatedSuccessfully
+            ))
+        ));
+        assert_eq!(stdout_receiver.recv().await.as_deref(), Some("hello"));
+    }
+}
@@ -0,0 +1,228 @@
+use std::{ffi::OsStr, path::Path, sync::Arc, time::Duration};
+
+use tokio::{sync::mpsc, task::JoinHandle};
+
+use super::{
+    spawn_in_current_span, OsProcessArgs, Process, ProcessConfig, ProcessController,
+    ProcessRunError, RetriedStatus, RetryBackoff, RetryPolicy, TerminationStatus,
+};
+
+/// Decides if a ```ProcessSupervisor``` restarts a terminated os process.
+/// A process that was cancelled through its controller or cancellation token is never restarted.
+#[derive(Debug, Clone)]
+pub enum RestartPolicy {
+    Never,
+    /// Restarts the process if it terminated with an error, at most ```max_restarts``` times.
+    OnFailure {
+        max_restarts: usize,
+        backoff: RetryBackoff,
+    },
+    /// Restarts the process every time it terminates, including successful terminations and timeouts.
+    Always {
+        backoff: RetryBackoff,
+    },
+}
+
+impl RestartPolicy {
+    fn into_retry_policy(self) -> RetryPolicy {
+        match self {
+            Self::Never => RetryPolicy::new(1, RetryBackoff::Fixed(Duration::ZERO)),
+            Self::OnFailure {
+                max_restarts,
+                backoff,
+            } => RetryPolicy::new(max_restarts.saturating_add(1), backoff),
+            Self::Always { backoff } => RetryPolicy {
+                max_attempts: usize::MAX,
+                backoff,
+                retry_on: Arc::new(|_| true),
+                retry_on_stderr: None,
+            },
+        }
+    }
+}
+
+/// Sent by a ```ProcessSupervisor``` right before the backoff of a restart.
+#[derive(Debug, Clone)]
+pub struct ProcessRestart {
+    /// Starts at 1.
+    pub restart: usize,
+    pub previous_termination_status: TerminationStatus,
+    /// The time until the process is spawned again.
+    pub delay: Duration,
+}
+
+/// A process running under a ```ProcessSupervisor```.
+pub struct SupervisedProcess {
+    /// Controls the whole supervised run, cancelling it stops restarting.
+    pub controller: ProcessController,
+    /// Yields ```None``` once the process is not restarted anymore.
+    pub restarts: mpsc::UnboundedReceiver<ProcessRestart>,
+    /// Resolves once the process is not restarted anymore.
+    /// Correctness: ```RetriedStatus::attempts``` grows with every restart, which is unbounded for ```RestartPolicy::Always```.
+    pub handle: JoinHandle<Result<RetriedStatus, ProcessRunError>>,
+}
+
+/// Keeps processes running in the background, e.g. locust workers, by restarting them according to a ```RestartPolicy```.
+/// Built on top of ```Process::run_with_retry```, the status stays ```Status::Running``` between restarts.
+#[derive(Debug, Clone)]
+pub struct ProcessSupervisor {
+    restart_policy: RestartPolicy,
+}
+
+impl ProcessSupervisor {
+    pub fn new(restart_policy: RestartPolicy) -> Self {
+        Self { restart_policy }
+    }
+
+    pub fn supervise<I, S, P>(
+        &self,
+        given_id: String,
+        given_name: String,
+        os_process_args: OsProcessArgs<I, S, P>,
+    ) -> SupervisedProcess
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+        P: AsRef<Path>,
+    {
+        let (mut process, controller) = Process::new(given_id, given_name);
+        let config = ProcessConfig::from(os_process_args);
+        let retry_policy = self.restart_policy.clone().into_retry_policy();
+        let (restart_sender, restart_receiver) = mpsc::unbounded_channel();
+
+        let handle = spawn_in_current_span(async move {
+            process
+                .run_config_with_retry(config, retry_policy, Some(restart_sender))
+                .await
+        });
+
+        SupervisedProcess {
+            controller,
+            restarts: restart_receiver,
+            handle,
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(unix)]
+mod tests {
+    use super::*;
+    use crate::project_managers::process::{
+        KillSignal, KilledTerminationStatus, Status, TerminationWithErrorStatus,
+    };
+    use tracing_test::traced_test;
+
+    fn bash_args(script: &str) -> OsProcessArgs<Vec<String>, String, &'static str> {
+        OsProcessArgs {
+            program: String::from("bash"),
+            args: vec![String::from("-c"), String::from(script)],
+            current_dir: ".",
+            kill_signal: KillSignal::Kill,
+            ..OsProcessArgs::default()
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn crash_on_failure_and_expect_max_restarts_events() {
+        let supervisor = ProcessSupervisor::new(RestartPolicy::OnFailure {
+            max_restarts: 2,
+            backoff: RetryBackoff::Fixed(Duration::from_millis(10)),
+        });
+
+        let mut supervised_process = supervisor.supervise(
+            String::from("some_id"),
+            String::from("crashing_process"),
+            bash_args("exit 3"),
+        );
+
+        let retried_status = supervised_process
+            .handle
+            .await
+            .expect("Supervisor task panicked.")
+            .expect("Error running process.");
+
+        assert_eq!(retried_status.attempts.len(), 3);
+        assert!(matches!(
+            retried_status.status,
+            Status::Terminated(TerminationStatus::TerminatedWithError(
+                TerminationWithErrorStatus::TerminatedWithErrorCode(3)
+            ))
+        ));
+
+        let mut restarts = Vec::new();
+        while let Some(restart) = supervised_process.restarts.recv().await {
+            restarts.push(restart.restart);
+        }
+        assert_eq!(restarts, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn never_restart_and_expect_single_attempt() {
+        let supervisor = ProcessSupervisor::new(RestartPolicy::Never);
+
+        let mut supervised_process = supervisor.supervise(
+            String::from("some_id"),
+            String::from("crashing_process"),
+            bash_args("exit 3"),
+        );
+
+        let retried_status = supervised_process
+            .handle
+            .await
+            .expect("Supervisor task panicked.")
+            .expect("Error running process.");
+
+        assert_eq!(retried_status.attempts.len(), 1);
+        assert!(supervised_process.restarts.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn always_restart_successful_process_until_cancelled() {
+        let supervisor = ProcessSupervisor::new(RestartPolicy::Always {
+            backoff: RetryBackoff::Fixed(Duration::from_millis(10)),
+        });
+
+        let mut supervised_process = supervisor.supervise(
+            String::from("some_id"),
+            String::from("worker_process"),
+            bash_args("exit 0"),
+        );
+
+        for expected_restart in 1..=3 {
+            let restart = supervised_process
+                .restarts
+                .recv()
+                .await
+                .expect("Process should be restarted.");
+            assert_eq!(restart.restart, expected_restart);
+            assert!(matches!(
+                restart.previous_termination_status,
+                TerminationStatus::TerminatedSuccessfully
+            ));
+        }
+
+        supervised_process
+            .controller
+            .cancel()
+            .await
+            .expect("Error cancelling process.");
+
+        let retried_status = supervised_process
+            .handle
+            .await
+            .expect("Supervisor task panicked.")
+            .expect("Error running process.");
+
+        assert!(matches!(
+            retried_status.status,
+            Status::Terminated(TerminationStatus::Killed(
+                KilledTerminationStatus::KilledByCancellationSignal,
+                _
+            ))
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+use std::time::Duration;
+
+use bytes::{Bytes, BytesMut};
+use tokio::time::Instant;
+
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Limits how many lines per second are forwarded, per stream. ```None``` forwards every line right away.
+/// Correctness: Lines above the limit are not dropped. They are coalesced into one message, joined with ```\n```,
+/// which is forwarded once the current second is over and counts as one line of the next second.
+/// The coalesced lines are recorded as one event and reach a channel as one ```String```.
+/// A pending batch is forwarded right away once the stream ends.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OutputRateLimit {
+    /// Maximum lines per second on stdout. 0 is treated as 1.
+    pub stdout: Option<u32>,
+    /// Maximum lines per second on stderr. 0 is treated as 1.
+    pub stderr: Option<u32>,
+}
+
+impl OutputRateLimit {
+    pub fn both(max_lines_per_second: u32) -> Self {
+        Self {
+            stdout: Some(max_lines_per_second),
+            stderr: Some(max_lines_per_second),
+        }
+    }
+}
+
+/// Counts the lines of a stream in windows of one second.
+pub(super) struct Throttle {
+    max_lines: u32,
+    window_start: Instant,
+    lines_in_window: u32,
+    batch: BytesMut,
+}
+
+impl Throttle {
+    pub(super) fn new(max_lines_per_second: u32) -> Self {
+        Self {
+            max_lines: max_lines_per_second.max(1),
+            window_start: Instant::now(),
+            lines_in_window: 0,
+            batch: BytesMut::new(),
+        }
+    }
+
+    /// Returns the line if it may be forwarded right away, otherwise it is added to the batch.
+    pub(super) fn push(&mut self, line: Bytes) -> Option<Bytes> {
+        // Correctness: A pending batch keeps the window open, so lines are never forwarded before it.
+        if self.batch.is_empty() && self.window_start.elapsed() >= WINDOW {
+            self.window_start = Instant::now();
+            self.lines_in_window = 0;
+        }
+
+        if self.batch.is_empty() && self.lines_in_window < self.max_lines {
+            self.lines_in_window += 1;
+            return Some(line);
+        }
+
+        if !self.batch.is_empty() {
+            self.batch.extend_from_slice(b"\n");
+        }
+        self.batch.extend_from_slice(&line);
+
+        None
+    }
+
+    /// When the batch is due. ```None``` if there is no batch.
+    pub(super) fn batch_due(&self) -> Option<Instant> {
+        match self.batch.is_empty() {
+            true => None,
+            false => Some(self.window_start + WINDOW),
+        }
+    }
+
+    /// Starts a new window with the batch as its first line.
+    pub(super) fn take_batch(&mut self) -> Option<Bytes> {
+        if self.batch.is_empty() {
+            return None;
+        }
+
+        self.window_start = Instant::now();
+        self.lines_in_window = 1;
+
+        Some(self.batch.split().freeze())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn exceed_max_lines_and_expect_rest_coalesced_until_next_second() {
+        let mut throttle = Throttle::new(2);
+
+        assert_eq!(throttle.push(Bytes::from("a")), Some(Bytes::from("a")));
+        assert_eq!(throttle.push(Bytes::from("b")), Some(Bytes::from("b")));
+        assert_eq!(throttle.push(Bytes::from("c")), None);
+        assert_eq!(throttle.push(Bytes::from("d")), None);
+
+        let batch_due = throttle.batch_due().expect("Batch should be pending.");
+        tokio::time::sleep_until(batch_due).await;
+
+        // The batch is still pending, so new lines are appended to it.
+        assert_eq!(throttle.push(Bytes::from("e")), None);
+        assert_eq!(throttle.take_batch(), Some(Bytes::from("c\nd\ne")));
+        assert_eq!(throttle.batch_due(), None);
+
+        assert_eq!(throttle.push(Bytes::from("f")), Some(Bytes::from("f")));
+        assert_eq!(throttle.push(Bytes::from("g")), None);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    #[cfg(unix)]
+    async fn flood_stdout_and_expect_all_lines_in_fewer_messages() {
+        use crate::project_managers::process::{ProcessBuilder, Status, TerminationStatus};
+        use tokio::sync::mpsc;
+
+        let (stdout_sender, mut stdout_receiver) = mpsc::channel(100);
+        let (mut process, _controller) = ProcessBuilder::new("some_id", "chatty_process")
+            .program("bash")
+            .args(["-c", "for i in $(seq 1 20); do echo line$i; done"])
+            .stdout_lines(stdout_sender)
+            .rate_limit(OutputRateLimit {
+                stdout: Some(5),
+                stderr: None,
+            })
+            .build()
+            .expect("Error building process.");
+
+        let status = process.run_built().await.expect("Error running process.");
+        assert!(matches!(
+            status,
+            Status::Terminated(TerminationStatus::TerminatedSuccessfully)
+        ));
+
+        let mut messages = Vec::new();
+        while let Some(message) = stdout_receiver.recv().await {
+            messages.push(message);
+        }
+
+        assert_eq!(messages.len(), 6);
+        let expected_lines: Vec<String> = (1..=20).map(|i| format!("line{i}")).collect();
+        assert_eq!(messages.join("\n"), expected_lines.join("\n"));
+    }
+}
@@ -0,0 +1,221 @@
+use super::local::{
+    AddProjectError, DeleteProjectError, InstallProjectError, InstallTicket, InstallerEvent,
+    LocalProjectManager, Page, Pagination, ProjectEvent, ProjectFilter, ProjectState,
+    ProjectStateChange, ProjectStoreError, ProjectSummary, UninstallProjectError,
+};
+use async_trait::async_trait;
+use std::{error::Error as StdError, path::PathBuf};
+use tokio::sync::{broadcast, mpsc};
+
+/// Manages the lifecycle of the projects, see ```ProjectState```.
+/// The HTTP layer is written against this trait, so a manager of another backend, e.g. Docker or Kubernetes,
+/// can replace the ```LocalProjectManager```.
+#[async_trait]
+pub trait ProjectManager: Send + Sync {
+    type AddError: StdError + Send + Sync + 'static;
+    type InstallError: StdError + Send + Sync + 'static;
+    type UninstallError: StdError + Send + Sync + 'static;
+    type DeleteError: StdError + Send + Sync + 'static;
+    /// Of ```list_projects``` and ```project_state```.
+    type QueryError: StdError + Send + Sync + 'static;
+
+    /// Checks the project in ```project_dir``` and saves it as ```ProjectState::Uploaded```.
+    async fn add_project(
+        &self,
+        project_id: String,
+        project_name: String,
+        project_dir: PathBuf,
+    ) -> Result<(), Self::AddError>;
+
+    /// Starts the installation, the ticket finishes with it. The events of the installation are forwarded to ```event_sender```.
+    async fn install_project(
+        &self,
+        project_id: String,
+        event_sender: Option<mpsc::Sender<InstallerEvent>>,
+    ) -> Result<InstallTicket, Self::InstallError>;
+
+    /// Returns the project to ```ProjectState::Uploaded```.
+    async fn uninstall_project(&self, project_id: String) -> Result<(), Self::UninstallError>;
+
+    /// Uninstalls the project, if it is installed, and deletes it with its uploaded dir.
+    async fn delete_project(&self, project_id: String) -> Result<(), Self::DeleteError>;
+
+    async fn list_projects(
+        &self,
+        filter: ProjectFilter,
+        page: Pagination,
+    ) -> Result<Page<ProjectSummary>, Self::QueryError>;
+
+    /// ```None``` if the project does not exist.
+    async fn project_state(
+        &self,
+        project_id: &str,
+    ) -> Result<Option<ProjectState>, Self::QueryError>;
+
+    /// Receives the state changes of all projects from now on, e.g. to push them to the clients of the HTTP layer.
+    fn subscribe(&self) -> broadcast::Receiver<ProjectStateChange>;
+
+    /// Receives the events of all projects from now on, e.g. for the websocket layer, the audit log and the webhooks.
+    fn subscribe_events(&self) -> broadcast::Receiver<ProjectEvent>;
+}
+
+#[async_trait]
+impl ProjectManager for LocalProjectManager {
+    type AddError = AddProjectError;
+    type InstallError = InstallProjectError;
+    type UninstallError = UninstallProjectError;
+    type DeleteError = DeleteProjectError;
+    type QueryError = ProjectStoreError;
+
+    async fn add_project(
+        &self,
+        project_id: String,
+        project_name: String,
+        project_dir: PathBuf,
+    ) -> Result<(), Self::AddError> {
+        self.add_new_project_to_database(project_id, project_name, project_dir)
+            .await
+    }
+
+    async fn install_project(
+        &self,
+        project_id: String,
+        event_sender: Option<mpsc::Sender<InstallerEvent>>,
+    ) -> Result<InstallTicket, Self::InstallError> {
+        self.do_install_project(project_id, event_sender).await
+    }
+
+    async fn uninstall_project(&self, project_id: String) -> Result<(), Self::UninstallError> {
+        LocalProjectManager::uninstall_project(self, project_id).await
+    }
+
+    async fn delete_project(&self, project_id: String) -> Result<(), Self::DeleteError> {
+        LocalProjectManager::delete_project(self, project_id).await
+    }
+
+    async fn list_projects(
+        &self,
+        filter: ProjectFilter,
+        page: Pagination,
+    ) -> Result<Page<ProjectSummary>, Self::QueryError> {
+        LocalProjectManager::list_projects(self, filter, page).await
+    }
+
+    async fn project_state(
+        &self,
+        project_id: &str,
+    ) -> Result<Option<ProjectState>, Self::QueryError> {
+        LocalProjectManager::project_state(self, project_id).await
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<ProjectStateChange> {
+        self.subscribe_project_states()
+    }
+
+    fn subscribe_events(&self) -> broadcast::Receiver<ProjectEvent> {
+        self.subscribe_project_events()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        project_managers::local::{PipCacheConfig, ProjectEventKind, ProjectStoreBackend},
+        util::{copy_dir_all, test_dir},
+    };
+    use std::path::Path;
+    use tracing_test::traced_test;
+
+    const CRATE_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+    /// Only uses the trait, as the HTTP layer does.
+    async fn add_and_delete_project<M: ProjectManager>(
+        project_manager: &M,
+        project_dir: PathBuf,
+    ) -> (Option<ProjectState>, usize) {
+        project_manager
+            .add_project(
+                String::from("valid_offline"),
+                String::from("Valid offline"),
+                project_dir,
+            )
+            .await
+            .expect("Error adding project.");
+        let state = project_manager
+            .project_state("valid_offline")
+            .await
+            .expect("Error getting project state.");
+        project_manager
+            .delete_project(String::from("valid_offline"))
+            .await
+            .expect("Error deleting project.");
+        let total_items = project_manager
+            .list_projects(ProjectFilter::default(), Pagination::default())
+            .await
+            .expect("Error listing projects.")
+            .total_items;
+
+        (state, total_items)
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn add_and_delete_project_through_trait_and_expect_state_changes() {
+        let temp_dir = test_dir("project_manager_trait");
+        let root_dir = temp_dir.path();
+        let project_dir = root_dir.join("uploaded_projects").join("valid_offline");
+        copy_dir_all(
+            &Path::new(CRATE_DIR)
+                .join("tests_dir")
+                .join("uploaded_projects")
+                .join("valid_offline"),
+            &project_dir,
+            |_| false,
+        )
+        .await
+        .expect("Could not copy uploaded project");
+        let local_project_manager = LocalProjectManager::with_project_store_backend(
+            root_dir.to_path_buf(),
+            PipCacheConfig::default(),
+            ProjectStoreBackend::InMemory,
+        )
+        .await
+        .expect("Error creating project manager.");
+        let mut state_changes = local_project_manager.subscribe();
+        let mut events = local_project_manager.subscribe_events();
+
+        let (state, total_items) =
+            add_and_delete_project(&local_project_manager, project_dir).await;
+        let mut received_state_changes = Vec::new();
+        while let Ok(state_change) = state_changes.try_recv() {
+            received_state_changes.push(state_change);
+        }
+        let received_event_kinds: Vec<ProjectEventKind> =
+            std::iter::from_fn(|| events.try_recv().ok())
+                .map(|event| event.kind)
+                .collect();
+
+        assert_eq!(state, Some(ProjectState::Uploaded));
+        assert_eq!(total_items, 0);
+        assert_eq!(
+            received_state_changes,
+            vec![
+                ProjectStateChange {
+                    project_id: String::from("valid_offline"),
+                    previous: None,
+                    state: ProjectState::Uploaded,
+                },
+                ProjectStateChange {
+                    project_id: String::from("valid_offline"),
+                    previous: Some(ProjectState::Uploaded),
+                    state: ProjectState::Deleted,
+                },
+            ]
+        );
+        assert_eq!(
+            received_event_kinds,
+            vec![ProjectEventKind::Created, ProjectEventKind::Deleted]
+        );
+    }
+}
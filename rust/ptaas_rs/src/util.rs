@@ -1,29 +1,224 @@
-use std::{io::Error as IoError, path::Path, time::Duration};
+use std::{
+    io::Error as IoError,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 use thiserror::Error as ThisError;
 use tokio::fs;
+use tokio_util::sync::CancellationToken;
 
 #[derive(ThisError, Debug)]
-#[error("Max attempts exceeded")]
+#[error("Max attempts exceeded after {} failed attempts", .0.len())]
 pub struct MaxAttemptsExceeded(Vec<IoError>);
 
+impl MaxAttemptsExceeded {
+    /// The error of every failed attempt, in order.
+    pub fn errors(&self) -> &[IoError] {
+        &self.0
+    }
+}
+
+#[derive(ThisError, Debug)]
+pub enum RemoveDirAllError {
+    #[error("{0}")]
+    MaxAttemptsExceeded(
+        #[source]
+        #[from]
+        MaxAttemptsExceeded,
+    ),
+    /// The errors of the attempts before the cancellation.
+    #[error("Cancelled after {} failed attempts", .0.len())]
+    Cancelled(Vec<IoError>),
+}
+
+impl RemoveDirAllError {
+    /// The error of every failed attempt, in order.
+    pub fn errors(&self) -> &[IoError] {
+        match self {
+            Self::MaxAttemptsExceeded(max_attempts_exceeded) => max_attempts_exceeded.errors(),
+            Self::Cancelled(errors) => errors,
+        }
+    }
+}
+
+/// Stops once ```cancellation_token``` is cancelled, the delay between two attempts is cut short.
+/// Correctness: A running attempt is not interrupted, the dir may be partially deleted.
 pub async fn remove_dir_all_with_max_attempts_and_delay(
     max_attempts: u16,
     delay: Duration,
     path: &Path,
-) -> Result<Vec<IoError>, MaxAttemptsExceeded> {
+    cancellation_token: &CancellationToken,
+) -> Result<Vec<IoError>, RemoveDirAllError> {
     let mut errors = Vec::new();
 
     for _ in 0..max_attempts {
+        if cancellation_token.is_cancelled() {
+            return Err(RemoveDirAllError::Cancelled(errors));
+        }
+
         tracing::debug!(?path, "Attempting to delete dir");
         match fs::remove_dir_all(path).await {
             Ok(_) => return Ok(errors),
             Err(err) => {
                 tracing::error!(%err, ?path, "Failed to delete dir");
                 errors.push(err);
-                tokio::time::sleep(delay).await;
+
+                tokio::select! {
+                    _ = cancellation_token.cancelled() => {
+                        return Err(RemoveDirAllError::Cancelled(errors));
+                    }
+                    _ = tokio::time::sleep(delay) => {}
+                }
+            }
+        }
+    }
+
+    Err(MaxAttemptsExceeded(errors).into())
+}
+
+/// A ```SystemTime``` in UTC, e.g. to format it without a date crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UtcDateTime {
+    pub year: i64,
+    pub month: u64,
+    pub day: u64,
+    pub hour: u64,
+    pub minute: u64,
+    pub second: u64,
+}
+
+impl UtcDateTime {
+    /// A time before the epoch is the epoch.
+    pub fn new(time: SystemTime) -> Self {
+        let seconds = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO)
+            .as_secs();
+        let (days, seconds_of_day) = (seconds / 86_400, seconds % 86_400);
+
+        // The civil date of the days since the epoch, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let days = days as i64 + 719_468;
+        let era = days / 146_097;
+        let day_of_era = days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 {
+            month_index + 3
+        } else {
+            month_index - 9
+        };
+
+        Self {
+            year: year_of_era + era * 400 + i64::from(month <= 2),
+            month: month as u64,
+            day: day as u64,
+            hour: seconds_of_day / 3_600,
+            minute: seconds_of_day % 3_600 / 60,
+            second: seconds_of_day % 60,
+        }
+    }
+}
+
+/// Copies the content of ```src``` into ```dst``` recursively, existing files are overwritten.
+/// ```skip``` is called with the path of every entry relative to ```src```, skipped dirs are not entered.
+/// Correctness: Symlinks are followed, their targets are copied.
+pub async fn copy_dir_all<F>(src: &Path, dst: &Path, skip: F) -> Result<(), IoError>
+where
+    F: Fn(&Path) -> bool,
+{
+    let mut dirs = vec![PathBuf::new()];
+
+    while let Some(relative_dir) = dirs.pop() {
+        fs::create_dir_all(dst.join(&relative_dir)).await?;
+
+        let mut dir_content = fs::read_dir(src.join(&relative_dir)).await?;
+
+        while let Some(entry) = dir_content.next_entry().await? {
+            let relative_path = relative_dir.join(entry.file_name());
+            if skip(&relative_path) {
+                continue;
+            }
+
+            if fs::metadata(entry.path()).await?.is_dir() {
+                dirs.push(relative_path);
+                continue;
             }
+
+            fs::copy(entry.path(), dst.join(&relative_path)).await?;
         }
     }
 
-    Err(MaxAttemptsExceeded(errors))
+    Ok(())
+}
+
+/// Creates a unique dir under the temp dir, named ```ptaas_<name>_<random>```.
+/// The dir and its content are deleted when the returned ```TempDir``` is dropped, even if the test panics.
+#[cfg(test)]
+pub(crate) fn test_dir(name: &str) -> tempfile::TempDir {
+    tempfile::Builder::new()
+        .prefix(&format!("ptaas_{name}_"))
+        .tempdir()
+        .expect("Error creating test dir.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn copy_dir_and_expect_nested_files_without_skipped_ones() {
+        let test_dir = test_dir("copy_dir_all");
+        let src = test_dir.path().join("src");
+        let dst = test_dir.path().join("dst");
+        fs::create_dir_all(src.join("locust"))
+            .await
+            .expect("Error creating dir.");
+        fs::write(src.join("locust").join("locustfile.py"), "")
+            .await
+            .expect("Error writing file.");
+        fs::write(src.join("req_out.txt"), "")
+            .await
+            .expect("Error writing file.");
+
+        let copy_result = copy_dir_all(&src, &dst, |relative_path| {
+            relative_path == Path::new("req_out.txt")
+        })
+        .await;
+        let locustfile_exists = fs::try_exists(dst.join("locust").join("locustfile.py")).await;
+        let skipped_file_exists = fs::try_exists(dst.join("req_out.txt")).await;
+
+        copy_result.expect("Error copying dir.");
+        assert!(locustfile_exists.expect("Error checking file."));
+        assert!(!skipped_file_exists.expect("Error checking file."));
+    }
+
+    #[tokio::test]
+    async fn cancel_remove_dir_during_delay_and_expect_cancelled_with_attempt_errors() {
+        let test_dir = test_dir("remove_dir_missing");
+        let missing_dir = test_dir.path().join("missing");
+        let cancellation_token = CancellationToken::new();
+        let canceller_token = cancellation_token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            canceller_token.cancel();
+        });
+
+        let started_at = std::time::Instant::now();
+        let remove_result = remove_dir_all_with_max_attempts_and_delay(
+            5,
+            Duration::from_secs(60),
+            &missing_dir,
+            &cancellation_token,
+        )
+        .await;
+
+        assert!(started_at.elapsed() < Duration::from_secs(10));
+        match remove_result {
+            Err(RemoveDirAllError::Cancelled(errors)) => assert_eq!(errors.len(), 1),
+            _ => panic!("Unexpected result: {:?}", remove_result),
+        }
+    }
 }
@@ -0,0 +1,6 @@
+#!/bin/bash
+
+# Prints a report like pip-audit --format json.
+echo '{"dependencies": [{"name": "locust", "version": "2.15.1", "vulns": []}, {"name": "requests", "version": "2.30.0", "vulns": [{"id": "GHSA-j8r2-6x86-q33q", "fix_versions": ["2.31.0"], "aliases": ["CVE-2023-32681"], "description": "Leaked Proxy-Authorization header"}]}], "fixes": []}'
+
+exit 1